@@ -0,0 +1,417 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use toml_edit::Table;
+use yang_codegen::config_file::{ConfigFile, ConfigFileError};
+
+/// An error finding, reading or writing the bindings of a package.
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("failed to read {0}")]
+    Read(PathBuf, #[source] std::io::Error),
+
+    #[error("failed to write {0}")]
+    Write(PathBuf, #[source] std::io::Error),
+
+    #[error("could not find Cargo.toml in {0} or any parent directory")]
+    NoManifest(PathBuf),
+
+    #[error("invalid TOML in {0}")]
+    Toml(PathBuf, #[source] toml_edit::TomlError),
+
+    #[error("{0} has no [package.metadata.yang] table")]
+    NoMetadata(PathBuf),
+
+    #[error("invalid [package.metadata.yang] table in {0}")]
+    Metadata(PathBuf, #[source] Box<ConfigFileError>),
+
+    #[error("failed to generate the bindings")]
+    Codegen(#[source] Box<ConfigFileError>),
+}
+
+fn main() -> ExitCode {
+    let matches = cli().get_matches();
+    let (_, matches) = matches.subcommand().expect("the yang subcommand to be required");
+    match run(matches) {
+        Ok(code) => code,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            let mut source = std::error::Error::source(&error);
+            while let Some(error) = source {
+                eprintln!("  caused by: {}", error);
+                source = error.source();
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// Cargo runs `cargo yang <args>` as `cargo-yang yang <args>`.
+fn cli() -> Command {
+    Command::new("cargo").bin_name("cargo").subcommand_required(true).subcommand(
+        Command::new("yang")
+            .about("Regenerate the Rust bindings of the YANG modules in the [package.metadata.yang] table of the manifest when they're stale")
+            .version(env!("CARGO_PKG_VERSION"))
+            .arg(
+                Arg::new("manifest-path")
+                    .long("manifest-path")
+                    .value_name("PATH")
+                    .value_parser(value_parser!(PathBuf))
+                    .help("The Cargo.toml of the package, by default the nearest one in the working directory or its parents"),
+            )
+            .arg(
+                Arg::new("check")
+                    .long("check")
+                    .action(ArgAction::SetTrue)
+                    .help("Fail if the bindings are stale rather than regenerating them, like in CI"),
+            ),
+    )
+}
+
+fn run(matches: &ArgMatches) -> Result<ExitCode, Error> {
+    let manifest = match matches.get_one::<PathBuf>("manifest-path") {
+        Some(path) => path.clone(),
+        None => find_manifest()?,
+    };
+    let bindings = load(&manifest)?;
+    let stale = stale_files(&bindings)?;
+
+    if stale.is_empty() {
+        eprintln!("Bindings in {} are up to date", bindings.output.display());
+        return Ok(ExitCode::SUCCESS);
+    }
+    if matches.get_flag("check") {
+        for (path, _) in &stale {
+            eprintln!("stale: {}", path.display());
+        }
+        eprintln!(
+            "Bindings in {} are stale, run `cargo yang` to regenerate them",
+            bindings.output.display()
+        );
+        return Ok(ExitCode::FAILURE);
+    }
+
+    let mut removed = 0;
+    for (path, code) in &stale {
+        match code {
+            Some(code) => {
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent).map_err(|error| Error::Write(parent.to_path_buf(), error))?;
+                }
+                std::fs::write(path, code).map_err(|error| Error::Write(path.clone(), error))?;
+            }
+            None => {
+                std::fs::remove_file(path).map_err(|error| Error::Write(path.clone(), error))?;
+                remove_empty_directories(path, &bindings.output);
+                removed += 1;
+            }
+        }
+    }
+    let written = stale.len() - removed;
+    let files = |count: usize| if count == 1 { "file" } else { "files" };
+    let mut message = format!("Regenerated {} {}", written, files(written));
+    if removed > 0 {
+        message += &format!(" and removed {} {}", removed, files(removed));
+    }
+    eprintln!("{} in {}", message, bindings.output.display());
+    Ok(ExitCode::SUCCESS)
+}
+
+// Remove the directories of the removed file which are left empty, up to the output directory, like the directory of a
+// module generated with the tree layout.
+fn remove_empty_directories(path: &Path, output: &Path) {
+    for directory in path.ancestors().skip(1) {
+        if !directory.starts_with(output) || directory == output || std::fs::remove_dir(directory).is_err() {
+            break;
+        }
+    }
+}
+
+// The Cargo.toml in the working directory or the nearest of its parents, like cargo finds it.
+fn find_manifest() -> Result<PathBuf, Error> {
+    let working_directory =
+        std::env::current_dir().map_err(|error| Error::Read(PathBuf::from("the working directory"), error))?;
+    working_directory
+        .ancestors()
+        .map(|directory| directory.join("Cargo.toml"))
+        .find(|path| path.is_file())
+        .ok_or(Error::NoManifest(working_directory))
+}
+
+/// The bindings described by the `[package.metadata.yang]` table of the manifest, which has the keys of a
+/// `yang-codegen.toml` file, with paths relative to the directory of the manifest:
+///
+/// ```toml
+/// [package.metadata.yang]
+/// modules = ["yang/example-system.yang"]
+/// search-paths = ["yang/ietf"]
+/// features = ["ietf-interfaces:arbitrary-names"]
+/// output = "src/yang"
+///
+/// [package.metadata.yang.options]
+/// xml = true
+/// ```
+fn load(manifest: &Path) -> Result<ConfigFile, Error> {
+    let content = std::fs::read_to_string(manifest).map_err(|error| Error::Read(manifest.to_path_buf(), error))?;
+    let document: toml_edit::Document<String> = content
+        .parse()
+        .map_err(|error| Error::Toml(manifest.to_path_buf(), error))?;
+    let metadata = ["package", "metadata", "yang"]
+        .iter()
+        .try_fold(document.as_table(), |table: &Table, key| table.get(key)?.as_table())
+        .ok_or_else(|| Error::NoMetadata(manifest.to_path_buf()))?;
+
+    let mut bindings = ConfigFile::from_table(metadata, manifest.parent().unwrap_or(Path::new("")))
+        .map_err(|error| Error::Metadata(manifest.to_path_buf(), Box::new(error)))?;
+    bindings.config.warnings = Some(Arc::new(|warning| eprintln!("warning: {}", warning)));
+    Ok(bindings)
+}
+
+// The start of every file yang-codegen generates, which tells the files it generated from the files written by hand.
+const GENERATED_HEADER: &str = "// Generated by yang-codegen";
+
+// The files of the bindings whose code differs from what's generated now, with the code they should have, or None for
+// files which aren't generated anymore, like the files of a module removed from the manifest. The code is generated in
+// a temporary directory and compared with the output, so the bindings are stale whenever a module, an option or
+// yang-codegen itself changes, or a file was edited by hand. Files which are up to date aren't written, so cargo
+// doesn't rebuild the crate for them.
+fn stale_files(bindings: &ConfigFile) -> Result<Vec<(PathBuf, Option<String>)>, Error> {
+    // Each run has a directory of its own, as the tests run in the same process.
+    static RUNS: AtomicUsize = AtomicUsize::new(0);
+    let directory = std::env::temp_dir().join(format!(
+        "cargo-yang-{}-{}",
+        std::process::id(),
+        RUNS.fetch_add(1, Ordering::Relaxed)
+    ));
+    let generated = ConfigFile {
+        output: directory.clone(),
+        ..bindings.clone()
+    };
+    let result = generated
+        .generate()
+        .map_err(|error| Error::Codegen(Box::new(error)))
+        .and_then(|()| generated_files(&directory, Path::new("")));
+    // The temporary directory is removed even if generating fails.
+    let _ = std::fs::remove_dir_all(&directory);
+
+    let generated = result?;
+    let mut stale = Vec::new();
+    for (path, code) in &generated {
+        let path = bindings.output.join(path);
+        if std::fs::read_to_string(&path).ok().as_ref() != Some(code) {
+            stale.push((path, Some(code.clone())));
+        }
+    }
+
+    // Files in the output which were generated before but aren't now, leaving the files written by hand alone.
+    if bindings.output.is_dir() {
+        let generated: HashSet<&Path> = generated.iter().map(|(path, _)| path.as_path()).collect();
+        for path in files(&bindings.output, Path::new(""))? {
+            if generated.contains(path.as_path()) || path.extension().is_none_or(|extension| extension != "rs") {
+                continue;
+            }
+            let path = bindings.output.join(path);
+            let code = std::fs::read_to_string(&path).unwrap_or_default();
+            if code.starts_with(GENERATED_HEADER) {
+                stale.push((path, None));
+            }
+        }
+    }
+    Ok(stale)
+}
+
+// The files generated in the directory, with their paths relative to it, in the order of their paths.
+fn generated_files(root: &Path, relative: &Path) -> Result<Vec<(PathBuf, String)>, Error> {
+    files(root, relative)?
+        .into_iter()
+        .map(|path| {
+            let code =
+                std::fs::read_to_string(root.join(&path)).map_err(|error| Error::Read(root.join(&path), error))?;
+            Ok((path, code))
+        })
+        .collect()
+}
+
+// The paths of the files in the directory and its subdirectories, relative to the root, in the order of their paths.
+fn files(root: &Path, relative: &Path) -> Result<Vec<PathBuf>, Error> {
+    let directory = root.join(relative);
+    let read_error = |error| Error::Read(directory.clone(), error);
+    let mut entries = std::fs::read_dir(&directory)
+        .map_err(read_error)?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(read_error)?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    let mut paths = Vec::new();
+    for entry in entries {
+        let path = relative.join(entry.file_name());
+        if entry.file_type().map_err(read_error)?.is_dir() {
+            paths.extend(files(root, &path)?);
+        } else {
+            paths.push(path);
+        }
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODULE: &str = r#"module example-system {
+  namespace "urn:example:system";
+  prefix sys;
+
+  container system {
+    leaf hostname {
+      type string;
+    }
+  }
+}
+"#;
+
+    // A package of its own for the test, with the modules in its yang directory and its bindings in src/yang, removed
+    // when it's dropped.
+    struct Package(PathBuf);
+
+    impl Package {
+        fn new(test: &str, modules: &[&str]) -> Self {
+            let path = std::env::temp_dir().join(format!("cargo-yang-{}-{}", test, std::process::id()));
+            let _ = std::fs::remove_dir_all(&path);
+            std::fs::create_dir_all(path.join("yang")).expect("the package to be created");
+            for module in ["example-system", "example-other"] {
+                let source = MODULE.replace("example-system", module);
+                std::fs::write(path.join("yang").join(format!("{}.yang", module)), source)
+                    .expect("the module to be written");
+            }
+            let package = Self(path);
+            package.set_modules(modules);
+            package
+        }
+
+        fn set_modules(&self, modules: &[&str]) {
+            let modules: Vec<String> = modules
+                .iter()
+                .map(|module| format!("\"yang/{}.yang\"", module))
+                .collect();
+            let manifest = format!(
+                "[package]\nname = \"example\"\n\n[package.metadata.yang]\nmodules = [{}]\noutput = \"src/yang\"\n",
+                modules.join(", ")
+            );
+            std::fs::write(self.0.join("Cargo.toml"), manifest).expect("the manifest to be written");
+        }
+
+        fn output(&self, file: &str) -> PathBuf {
+            self.0.join("src/yang").join(file)
+        }
+
+        // Run `cargo yang`, with --check if it's checked.
+        fn run(&self, check: bool) -> ExitCode {
+            let manifest = self.0.join("Cargo.toml");
+            let mut args = vec![
+                "cargo",
+                "yang",
+                "--manifest-path",
+                manifest.to_str().expect("a UTF-8 path"),
+            ];
+            if check {
+                args.push("--check");
+            }
+            let matches = cli().get_matches_from(args);
+            let (_, matches) = matches.subcommand().expect("the yang subcommand");
+            run(matches).expect("the bindings to be checked")
+        }
+
+        fn stale(&self) -> Vec<(PathBuf, bool)> {
+            let bindings = load(&self.0.join("Cargo.toml")).expect("the manifest to be loaded");
+            let stale = stale_files(&bindings).expect("the bindings to be generated");
+            stale.into_iter().map(|(path, code)| (path, code.is_some())).collect()
+        }
+    }
+
+    impl Drop for Package {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn generated_bindings_are_up_to_date() {
+        let package = Package::new("up-to-date", &["example-system"]);
+        assert_eq!(package.run(false), ExitCode::SUCCESS);
+        assert!(package.output("example_system.rs").is_file());
+        assert!(package.output("mod.rs").is_file());
+
+        let modified = std::fs::metadata(package.output("mod.rs")).and_then(|metadata| metadata.modified());
+        assert_eq!(package.stale(), []);
+        assert_eq!(package.run(true), ExitCode::SUCCESS);
+        assert_eq!(package.run(false), ExitCode::SUCCESS);
+        assert_eq!(
+            std::fs::metadata(package.output("mod.rs"))
+                .and_then(|metadata| metadata.modified())
+                .ok(),
+            modified.ok()
+        );
+    }
+
+    #[test]
+    fn edited_bindings_are_stale_and_checking_leaves_them() {
+        let package = Package::new("stale", &["example-system"]);
+        assert_eq!(
+            package.stale(),
+            [
+                (package.output("example_system.rs"), true),
+                (package.output("mod.rs"), true)
+            ]
+        );
+        assert_eq!(package.run(true), ExitCode::FAILURE);
+        assert!(!package.output("mod.rs").exists());
+
+        package.run(false);
+        std::fs::write(package.output("example_system.rs"), "// Edited by hand.\n").expect("the file to be written");
+        assert_eq!(package.stale(), [(package.output("example_system.rs"), true)]);
+        assert_eq!(package.run(true), ExitCode::FAILURE);
+        assert_eq!(
+            std::fs::read_to_string(package.output("example_system.rs"))
+                .ok()
+                .as_deref(),
+            Some("// Edited by hand.\n")
+        );
+
+        assert_eq!(package.run(false), ExitCode::SUCCESS);
+        assert_eq!(package.stale(), []);
+    }
+
+    #[test]
+    fn bindings_of_removed_modules_are_stale_and_removed() {
+        let package = Package::new("removed", &["example-system", "example-other"]);
+        assert_eq!(package.run(false), ExitCode::SUCCESS);
+        std::fs::write(package.output("hand_written.rs"), "pub fn hand_written() {}\n")
+            .expect("the file to be written");
+
+        package.set_modules(&["example-system"]);
+        assert_eq!(
+            package.stale(),
+            [
+                (package.output("mod.rs"), true),
+                (package.output("example_other.rs"), false)
+            ]
+        );
+        assert_eq!(package.run(true), ExitCode::FAILURE);
+        assert!(package.output("example_other.rs").is_file());
+
+        assert_eq!(package.run(false), ExitCode::SUCCESS);
+        assert!(!package.output("example_other.rs").exists());
+        assert!(package.output("example_system.rs").is_file());
+        assert!(package.output("hand_written.rs").is_file());
+        assert_eq!(package.stale(), []);
+    }
+}
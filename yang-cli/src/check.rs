@@ -0,0 +1,161 @@
+use std::sync::Arc;
+
+use yang_parser::{
+    model::{Module, TypeBody, TypeInfo},
+    report::Diagnostic,
+    schema_tree::{NodeKind, SchemaTree},
+    types::{self, TypeResolver},
+    value::{self, Value},
+    ErrorCode, Severity, SeverityPolicy,
+};
+
+/// The diagnostics of the types of the leaves and leaf-lists of a parsed module, which parsing leaves unchecked: types
+/// which aren't defined or whose prefix isn't imported, ranges and lengths which aren't intervals of values of their
+/// type in ascending order, like "9..5", and defaults which aren't values of their type. They're errors unless the
+/// severity policy says otherwise.
+pub fn types(module: &Module, policy: &SeverityPolicy) -> Vec<Diagnostic> {
+    let tree = SchemaTree::new(module);
+    let resolver = TypeResolver::new(module);
+    let mut diagnostics = Vec::new();
+    for id in tree.iter() {
+        let kind = tree.kind(id);
+        let defaults = match kind {
+            NodeKind::Leaf(leaf) => leaf.default.as_slice(),
+            NodeKind::LeafList(leaf_list) => leaf_list.default.as_slice(),
+            _ => continue,
+        };
+        let path = tree.path(id);
+        let Some(type_info) = resolver.leaf_type(&path) else {
+            continue;
+        };
+
+        let mut add = |code: ErrorCode, message: String| {
+            diagnostics.push(Diagnostic {
+                severity: policy.severity(code, Severity::Error),
+                code,
+                message,
+                module: Some(module.name.to_string()),
+                statement: Some(format!("{} {}", kind.keyword(), kind.name())),
+                path: Some(path.clone()),
+                file: None,
+                location: None,
+            })
+        };
+
+        let mut resolved = true;
+        check_type(module, &type_info, &mut |code, message| {
+            resolved &= code != ErrorCode::UnresolvedType;
+            add(code, message)
+        });
+        if !resolved {
+            continue;
+        }
+        for default in defaults {
+            let checked = value::parse_default(&type_info, default)
+                .and_then(|value| value::validate_value(&type_info, &value).map(|_| value));
+            if let Err(error) = checked {
+                add(
+                    ErrorCode::InvalidDefault,
+                    format!("default {} is not a value of the type: {}", default, error),
+                );
+            }
+        }
+    }
+
+    diagnostics
+}
+
+// Check the effective type, and the members of a union.
+fn check_type(module: &Module, type_info: &TypeInfo, add: &mut impl FnMut(ErrorCode, String)) {
+    if !types::is_built_in(&type_info.name) {
+        let message = match type_info.name.split_once(':') {
+            Some((prefix, _)) if prefix != module.prefix && !module.prefix_to_module.contains_key(prefix) => {
+                format!("prefix {} of type {} is not imported", prefix, type_info.name)
+            }
+            _ => format!("type {} is not defined", type_info.name),
+        };
+        return add(ErrorCode::UnresolvedType, message);
+    }
+
+    let (restriction, intervals, base) = match type_info.type_body.as_deref() {
+        Some(TypeBody::Numerical { range }) => ("range", &range.value, base_type(&type_info.name, None)),
+        Some(TypeBody::Decimal64 {
+            fraction_digits,
+            range: Some(range),
+        }) => {
+            let body = TypeBody::Decimal64 {
+                fraction_digits: fraction_digits.clone(),
+                range: None,
+            };
+            ("range", &range.value, base_type("decimal64", Some(body)))
+        }
+        Some(
+            TypeBody::String {
+                length: Some(length), ..
+            }
+            | TypeBody::Binary { length: Some(length) },
+        ) => ("length", &length.value, base_type("uint64", None)),
+        Some(TypeBody::Union { types }) => {
+            for member in types {
+                check_type(module, member, add);
+            }
+            return;
+        }
+        _ => return,
+    };
+    if let Err(message) = check_intervals(intervals, &base) {
+        add(
+            ErrorCode::InvalidRestriction,
+            format!("{} \"{}\" is invalid: {}", restriction, intervals, message),
+        );
+    }
+}
+
+fn base_type(name: &str, type_body: Option<TypeBody>) -> TypeInfo {
+    TypeInfo {
+        name: name.into(),
+        type_body: type_body.map(Arc::new),
+    }
+}
+
+// The intervals of a range or length, like "1..10 | 20..max", have to be values of the base type in ascending order.
+fn check_intervals(intervals: &str, base: &TypeInfo) -> Result<(), String> {
+    let mut previous = None;
+    for interval in intervals.split('|') {
+        let (lower, upper) = interval.split_once("..").unwrap_or((interval, interval));
+        let (lower, upper) = (bound(lower.trim(), base)?, bound(upper.trim(), base)?);
+        if lower > upper {
+            return Err(format!("the interval {} is empty", interval.trim()));
+        }
+        if previous.is_some_and(|previous| lower <= previous) {
+            return Err(format!(
+                "the interval {} is not after the interval before it",
+                interval.trim()
+            ));
+        }
+        previous = Some(upper);
+    }
+
+    Ok(())
+}
+
+// The bound as a number which orders like the values of the type, where min and max are the lowest and highest.
+fn bound(bound: &str, base: &TypeInfo) -> Result<i128, String> {
+    let value = match bound {
+        "min" => return Ok(i128::MIN),
+        "max" => return Ok(i128::MAX),
+        bound => value::parse_default(base, bound).map_err(|error| error.to_string())?,
+    };
+    match value {
+        Value::Int8(value) => Ok(value.into()),
+        Value::Int16(value) => Ok(value.into()),
+        Value::Int32(value) => Ok(value.into()),
+        Value::Int64(value) => Ok(value.into()),
+        Value::Uint8(value) => Ok(value.into()),
+        Value::Uint16(value) => Ok(value.into()),
+        Value::Uint32(value) => Ok(value.into()),
+        Value::Uint64(value) => Ok(value.into()),
+        Value::Decimal64(value) => Ok(value.value.into()),
+        value => Err(format!("{} is not a number", value)),
+    }
+}
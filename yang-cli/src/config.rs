@@ -0,0 +1,142 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use toml_edit::Item;
+use yang_parser::{catalog::YangCatalog, ErrorCode, ParseOptions, Severity, SeverityPolicy};
+
+use crate::{features::Features, Error};
+
+/// The file the settings are read from when no `--config` is given, if it's in the working directory.
+pub const DEFAULT_FILE: &str = "yang-rs.toml";
+
+/// The settings shared by the subcommands, from a `yang-rs.toml` file and the flags, where the flags add to the file:
+///
+/// ```toml
+/// search-paths = ["models/ietf", "models/vendor"]
+/// deviations = ["models/vendor/vendor-deviations.yang"]
+/// features = ["ietf-interfaces:arbitrary-names", "vendor-system"]
+/// style = "libyang"
+/// yangcatalog = true
+///
+/// [severity]
+/// "yang::unused_import" = "error"
+/// "yang::io" = "warning"
+/// ```
+///
+/// Paths are relative to the directory of the file.
+#[derive(Debug, Clone, Default)]
+pub struct Settings {
+    pub search_paths: Vec<PathBuf>,
+    /// Other modules whose augments and deviations are applied to the modules.
+    pub deviations: Vec<PathBuf>,
+    pub features: Features,
+    pub severity_policy: SeverityPolicy,
+    pub style: Style,
+    /// Fetch the modules which aren't in the search paths from yangcatalog.org.
+    pub yangcatalog: bool,
+}
+
+/// The conventions of the output of the subcommands, for output compared against other tools'.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Style {
+    /// Trees like pyang's, and errors with their yang-rs codes.
+    #[default]
+    YangRs,
+    /// Trees and errors like yanglint's, with libyang's error codes.
+    Libyang,
+}
+
+impl Style {
+    pub const NAMES: [&'static str; 2] = ["yang-rs", "libyang"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "yang-rs" => Some(Style::YangRs),
+            "libyang" => Some(Style::Libyang),
+            _ => None,
+        }
+    }
+}
+
+impl Settings {
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let content = std::fs::read_to_string(path).map_err(|error| Error::Read(path.to_path_buf(), error))?;
+        Self::parse(&content, path.parent().unwrap_or(Path::new("")))
+    }
+
+    /// Parse the settings from the content of a file, with paths relative to the directory. Unknown keys are errors, so
+    /// misspelled settings aren't silently ignored.
+    pub fn parse(content: &str, directory: &Path) -> Result<Self, Error> {
+        let document: toml_edit::Document<String> = content.parse()?;
+        let mut settings = Settings::default();
+
+        for (key, item) in document.as_table() {
+            match key {
+                "search-paths" => settings.search_paths = paths(key, item, directory)?,
+                "deviations" => settings.deviations = paths(key, item, directory)?,
+                "features" => {
+                    for spec in strings(key, item)? {
+                        settings.features.add(&spec)?;
+                    }
+                }
+                "style" => {
+                    settings.style = item
+                        .as_str()
+                        .and_then(Style::from_name)
+                        .ok_or_else(|| invalid(key, "\"yang-rs\" or \"libyang\""))?
+                }
+                "yangcatalog" => settings.yangcatalog = item.as_bool().ok_or_else(|| invalid(key, "true or false"))?,
+                "severity" => {
+                    let table = item.as_table_like().ok_or_else(|| invalid(key, "a table of strings"))?;
+                    for (name, severity) in table.iter() {
+                        let key_path = format!("{}.{}", key, name);
+                        let code = ErrorCode::from_name(name).ok_or(Error::UnknownCode(name.to_string()))?;
+                        let severity = severity
+                            .as_str()
+                            .and_then(Severity::from_name)
+                            .ok_or_else(|| invalid(&key_path, "\"error\" or \"warning\""))?;
+                        settings.severity_policy.set(code, severity);
+                    }
+                }
+                _ => return Err(Error::UnknownKey(key.to_string())),
+            }
+        }
+
+        Ok(settings)
+    }
+
+    /// The options modules are parsed with.
+    pub fn parse_options(&self) -> ParseOptions {
+        ParseOptions {
+            search_paths: self.search_paths.clone(),
+            modules: self.deviations.clone(),
+            severity_policy: self.severity_policy.clone(),
+            source: self
+                .yangcatalog
+                .then(|| Arc::new(YangCatalog::new()) as Arc<dyn yang_parser::ModuleSource>),
+            ..Default::default()
+        }
+    }
+}
+
+fn invalid(key: &str, expected: &'static str) -> Error {
+    Error::InvalidValue {
+        key: key.to_string(),
+        expected,
+    }
+}
+
+fn strings(key: &str, item: &Item) -> Result<Vec<String>, Error> {
+    let array = item.as_array().ok_or_else(|| invalid(key, "an array of strings"))?;
+    array
+        .iter()
+        .map(|value| value.as_str().map(String::from))
+        .collect::<Option<_>>()
+        .ok_or_else(|| invalid(key, "an array of strings"))
+}
+
+fn paths(key: &str, item: &Item, directory: &Path) -> Result<Vec<PathBuf>, Error> {
+    Ok(strings(key, item)?.iter().map(|path| directory.join(path)).collect())
+}
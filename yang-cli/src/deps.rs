@@ -0,0 +1,99 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use yang_parser::{model::YangModule, Document};
+
+use crate::Error;
+
+/// The import and include graph of the module at the path as a tree, with the file each module is found in, like:
+///
+/// ```text
+/// example-system (models/example-system.yang)
+///   import ietf-inet-types (models/ietf/ietf-inet-types@2013-07-15.yang)
+///   include example-system-types (models/example-system-types.yang)
+///     import ietf-yang-types (models/ietf/ietf-yang-types.yang)
+/// ```
+///
+/// Files are looked up like parsing looks them up, in the directory of the module importing them and then in the search
+/// paths. The dependencies of a module are only listed the first time it's in the tree, and imports leading back to a
+/// module which is importing them are marked as cycles.
+pub fn format(path: &Path, search_paths: &[PathBuf]) -> Result<String, Error> {
+    let name = match read(path)?.module() {
+        YangModule::Module(module) => module.name.to_string(),
+        YangModule::Submodule(submodule) => submodule.name.to_string(),
+    };
+    let mut graph = Graph {
+        search_paths,
+        listed: HashSet::new(),
+        ancestors: Vec::new(),
+        out: String::new(),
+    };
+    graph.add(&name, path, "", 0)?;
+
+    Ok(graph.out)
+}
+
+struct Graph<'a> {
+    search_paths: &'a [PathBuf],
+    // The modules whose dependencies are already in the tree, and the modules from the root down to the one being added.
+    listed: HashSet<String>,
+    ancestors: Vec<String>,
+    out: String,
+}
+
+impl Graph<'_> {
+    fn add(&mut self, name: &str, path: &Path, statement: &str, depth: usize) -> Result<(), Error> {
+        let indent = "  ".repeat(depth);
+        let note = if self.ancestors.iter().any(|ancestor| ancestor == name) {
+            Some("cycle")
+        } else if !path.is_file() {
+            Some("not found")
+        } else if self.listed.contains(name) {
+            Some("listed above")
+        } else {
+            None
+        };
+        self.out
+            .push_str(&format!("{}{}{} ({})", indent, statement, name, path.display()));
+        if let Some(note) = note {
+            self.out.push_str(&format!(" [{}]\n", note));
+            return Ok(());
+        }
+        self.out.push('\n');
+        self.listed.insert(name.to_string());
+
+        let document = read(path)?;
+        let dependencies: Vec<(&str, String)> = document
+            .imports()
+            .map(|import| ("import ", import.module.to_string()))
+            .chain(
+                document
+                    .includes()
+                    .map(|include| ("include ", include.module.to_string())),
+            )
+            .collect();
+
+        self.ancestors.push(name.to_string());
+        for (statement, dependency) in dependencies {
+            // A file which isn't found is listed where it would be in the directory of the module.
+            let dependency_path =
+                yang_parser::find_module_file(path, self.search_paths, &dependency).unwrap_or_else(|| {
+                    path.parent()
+                        .unwrap_or(Path::new("."))
+                        .join(format!("{}.yang", dependency))
+                });
+            self.add(&dependency, &dependency_path, statement, depth + 1)?;
+        }
+        self.ancestors.pop();
+
+        Ok(())
+    }
+}
+
+// The statements of the file, without loading its imports and includes.
+fn read(path: &Path) -> Result<Document, Error> {
+    let source = std::fs::read_to_string(path).map_err(|error| Error::Read(path.to_path_buf(), error))?;
+    Document::parse(source).map_err(|error| Error::Parse(path.to_path_buf(), Box::new(error)))
+}
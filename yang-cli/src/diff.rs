@@ -0,0 +1,205 @@
+use std::collections::BTreeMap;
+
+use yang_parser::{
+    model::{Module, Status},
+    schema_tree::{NodeId, NodeKind, SchemaTree},
+};
+
+use crate::tree::{config, if_features, status};
+
+/// A difference between the schema trees of two revisions of a module, at the schema path of a node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    Added {
+        path: String,
+        keyword: &'static str,
+    },
+    Removed {
+        path: String,
+        keyword: &'static str,
+    },
+    /// A property of the node changed, like its type or whether it's mandatory, from the old value to the new one.
+    Changed {
+        path: String,
+        keyword: &'static str,
+        property: &'static str,
+        old: String,
+        new: String,
+    },
+}
+
+impl std::fmt::Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Change::Added { path, keyword } => write!(f, "+ {} {}", keyword, path),
+            Change::Removed { path, keyword } => write!(f, "- {} {}", keyword, path),
+            Change::Changed {
+                path,
+                keyword,
+                property,
+                old,
+                new,
+            } => write!(f, "~ {} {}: {} {} -> {}", keyword, path, property, old, new),
+        }
+    }
+}
+
+/// The nodes added, removed and changed from the old module to the new one, by schema path. Choices and cases aren't
+/// compared themselves, as they aren't part of paths, but the nodes in them are.
+pub fn diff(old: &Module, new: &Module) -> Vec<Change> {
+    let old_nodes = nodes(old);
+    let new_nodes = nodes(new);
+
+    let mut changes = Vec::new();
+    for (path, old_node) in &old_nodes {
+        let Some(new_node) = new_nodes.get(path) else {
+            changes.push(Change::Removed {
+                path: path.clone(),
+                keyword: old_node.keyword,
+            });
+            continue;
+        };
+        if old_node.keyword != new_node.keyword {
+            changes.push(Change::Changed {
+                path: path.clone(),
+                keyword: new_node.keyword,
+                property: "keyword",
+                old: old_node.keyword.to_string(),
+                new: new_node.keyword.to_string(),
+            });
+            continue;
+        }
+        for ((property, old), (_, new)) in old_node.properties.iter().zip(&new_node.properties) {
+            if old != new {
+                changes.push(Change::Changed {
+                    path: path.clone(),
+                    keyword: new_node.keyword,
+                    property,
+                    old: old.clone(),
+                    new: new.clone(),
+                });
+            }
+        }
+    }
+    for (path, new_node) in &new_nodes {
+        if !old_nodes.contains_key(path) {
+            changes.push(Change::Added {
+                path: path.clone(),
+                keyword: new_node.keyword,
+            });
+        }
+    }
+
+    changes.sort_by(|a, b| path(a).cmp(path(b)));
+    // The nodes below an added or removed node are added or removed with it.
+    let mut subtree: Option<String> = None;
+    changes.retain(|change| {
+        let path = path(change);
+        match change {
+            _ if subtree
+                .as_ref()
+                .is_some_and(|subtree| path.starts_with(subtree.as_str())) =>
+            {
+                false
+            }
+            Change::Added { .. } | Change::Removed { .. } => {
+                subtree = Some(format!("{}/", path));
+                true
+            }
+            Change::Changed { .. } => {
+                subtree = None;
+                true
+            }
+        }
+    });
+    changes
+}
+
+fn path(change: &Change) -> &str {
+    match change {
+        Change::Added { path, .. } | Change::Removed { path, .. } | Change::Changed { path, .. } => path,
+    }
+}
+
+// The properties of a node which are compared, in the same order for every node, where "-" is a property the node
+// doesn't have. Mandatory and status are compared with their defaults, so setting them to the default isn't a change.
+struct Summary {
+    keyword: &'static str,
+    properties: [(&'static str, String); 9],
+}
+
+fn nodes(module: &Module) -> BTreeMap<String, Summary> {
+    let tree = SchemaTree::new(module);
+    let mut nodes = BTreeMap::new();
+    let roots: Vec<NodeId> = tree.roots().collect();
+    add_nodes(&tree, &roots, Some(true), &mut nodes);
+    nodes
+}
+
+// Config is inherited from the parent, and doesn't apply in operations and notifications.
+fn add_nodes(tree: &SchemaTree, ids: &[NodeId], parent_config: Option<bool>, nodes: &mut BTreeMap<String, Summary>) {
+    for &id in ids {
+        let kind = tree.kind(id);
+        let config = match kind {
+            NodeKind::Rpc(_) | NodeKind::Action(_) | NodeKind::Notification(_) => None,
+            _ => parent_config.map(|parent| config(kind).unwrap_or(parent)),
+        };
+        if kind.is_in_path() {
+            nodes.insert(tree.path(id), summary(kind, config));
+        }
+        let children: Vec<NodeId> = tree.children(id).collect();
+        add_nodes(tree, &children, config, nodes);
+    }
+}
+
+fn summary(kind: NodeKind, config: Option<bool>) -> Summary {
+    let mut type_name = None;
+    let mut default = None;
+    let mut units = None;
+    let mut mandatory = None;
+    let mut key = None;
+    let mut presence = None;
+    match kind {
+        NodeKind::Leaf(leaf) => {
+            type_name = Some(leaf.type_info.name.to_string());
+            default = leaf.default.clone();
+            units = leaf.meta.units.clone();
+            mandatory = Some(leaf.mandatory.unwrap_or(false));
+        }
+        NodeKind::LeafList(leaf_list) => {
+            type_name = Some(leaf_list.type_info.name.to_string());
+            default = (!leaf_list.default.is_empty()).then(|| leaf_list.default.join(", "));
+            units = leaf_list.meta.units.clone();
+        }
+        NodeKind::List(list) => key = list.key.clone(),
+        NodeKind::Container(container) => presence = container.meta.presence.clone(),
+        NodeKind::Anydata(anydata) => mandatory = Some(anydata.mandatory.unwrap_or(false)),
+        NodeKind::Anyxml(anyxml) => mandatory = Some(anyxml.mandatory.unwrap_or(false)),
+        _ => {}
+    }
+    let status = match status(kind) {
+        Some(Status::Deprecated) => "deprecated",
+        Some(Status::Obsolete) => "obsolete",
+        _ => "current",
+    };
+    let if_features = if_features(kind);
+
+    let value = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+    Summary {
+        keyword: kind.keyword(),
+        properties: [
+            ("type", value(type_name)),
+            ("default", value(default)),
+            ("units", value(units)),
+            ("config", value(config.map(|config| config.to_string()))),
+            ("mandatory", value(mandatory.map(|mandatory| mandatory.to_string()))),
+            ("key", value(key)),
+            ("presence", value(presence)),
+            ("status", status.to_string()),
+            (
+                "if-feature",
+                value((!if_features.is_empty()).then(|| if_features.join(", "))),
+            ),
+        ],
+    }
+}
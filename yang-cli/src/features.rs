@@ -0,0 +1,98 @@
+use std::collections::{HashMap, HashSet};
+
+use yang_parser::model::Module;
+
+use crate::Error;
+
+/// The features supported by the server whose schema is looked at, which decide the nodes guarded by if-feature
+/// statements. Features are given per module like pyang's `--features`, as `module:feature,feature`, where a module
+/// given without features supports none of them. Every feature of the modules which aren't given is supported.
+#[derive(Debug, Clone, Default)]
+pub struct Features {
+    modules: HashMap<String, HashSet<String>>,
+}
+
+impl Features {
+    /// Add the features of a module, like `ietf-interfaces:arbitrary-names,pre-provisioning`.
+    pub fn add(&mut self, spec: &str) -> Result<(), Error> {
+        let (module, features) = spec.split_once(':').unwrap_or((spec, ""));
+        if module.is_empty() {
+            return Err(Error::InvalidFeatures(spec.to_string()));
+        }
+        self.modules.entry(module.to_string()).or_default().extend(
+            features
+                .split(',')
+                .map(str::trim)
+                .filter(|feature| !feature.is_empty())
+                .map(str::to_string),
+        );
+        Ok(())
+    }
+
+    pub fn is_supported(&self, module: &str, feature: &str) -> bool {
+        self.modules
+            .get(module)
+            .is_none_or(|features| features.contains(feature))
+    }
+
+    /// Whether every if-feature expression of a node of the module holds, where prefixed features are looked up in the
+    /// module they're imported from. Expressions which can't be parsed hold, so the node isn't hidden.
+    pub fn allows(&self, module: &Module, if_features: &[String]) -> bool {
+        if self.modules.is_empty() {
+            return true;
+        }
+        if_features
+            .iter()
+            .all(|expression| self.evaluate(module, expression).unwrap_or(true))
+    }
+
+    // The value of an if-feature expression, like "a and (b or not c)", whose operators bind like they do in YANG 1.1:
+    // "not" before "and" before "or".
+    fn evaluate(&self, module: &Module, expression: &str) -> Option<bool> {
+        let spaced = expression
+            .trim_matches(['"', '\''])
+            .replace('(', " ( ")
+            .replace(')', " ) ");
+        let mut tokens = spaced.split_whitespace().peekable();
+        let value = self.evaluate_or(module, &mut tokens)?;
+        tokens.next().is_none().then_some(value)
+    }
+
+    fn evaluate_or(&self, module: &Module, tokens: &mut Tokens) -> Option<bool> {
+        let mut value = self.evaluate_and(module, tokens)?;
+        while tokens.next_if_eq(&"or").is_some() {
+            value |= self.evaluate_and(module, tokens)?;
+        }
+        Some(value)
+    }
+
+    fn evaluate_and(&self, module: &Module, tokens: &mut Tokens) -> Option<bool> {
+        let mut value = self.evaluate_factor(module, tokens)?;
+        while tokens.next_if_eq(&"and").is_some() {
+            value &= self.evaluate_factor(module, tokens)?;
+        }
+        Some(value)
+    }
+
+    fn evaluate_factor(&self, module: &Module, tokens: &mut Tokens) -> Option<bool> {
+        match tokens.next()? {
+            "not" => self.evaluate_factor(module, tokens).map(|value| !value),
+            "(" => {
+                let value = self.evaluate_or(module, tokens)?;
+                tokens.next_if_eq(&")").map(|_| value)
+            }
+            ")" | "and" | "or" => None,
+            name => {
+                let (prefix, feature) = name.split_once(':').unwrap_or((&module.prefix, name));
+                let feature_module = match module.prefix_to_module.get(prefix) {
+                    Some(feature_module) => feature_module.as_ref(),
+                    None if prefix == &*module.prefix => &*module.name,
+                    None => prefix,
+                };
+                Some(self.is_supported(feature_module, feature))
+            }
+        }
+    }
+}
+
+type Tokens<'a> = std::iter::Peekable<std::str::SplitWhitespace<'a>>;
@@ -0,0 +1,517 @@
+mod check;
+mod config;
+mod deps;
+mod diff;
+mod features;
+mod tree;
+
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    sync::{Arc, Mutex},
+};
+
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use yang_codegen::config_file::{ConfigFile, ConfigFileError};
+use yang_parser::{
+    model::{Module, YangModule},
+    report::{Diagnostic, Report},
+    ErrorCode, ErrorPolicy, ParserError, Severity, Warning,
+};
+
+use config::{Settings, Style};
+
+/// The file `codegen` generates from when it's given no module.
+const CODEGEN_FILE: &str = "yang-codegen.toml";
+
+/// An error of a subcommand, other than the errors and warnings it reports about the modules it checks.
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("failed to read {0}")]
+    Read(PathBuf, #[source] std::io::Error),
+
+    #[error("failed to write {0}")]
+    Write(PathBuf, #[source] std::io::Error),
+
+    #[error("failed to parse {0}")]
+    Parse(PathBuf, #[source] Box<ParserError>),
+
+    #[error("{0} is a submodule, parse the module it belongs to instead")]
+    Submodule(PathBuf),
+
+    #[error("invalid TOML")]
+    Toml(#[from] toml_edit::TomlError),
+
+    #[error("unknown key: {0}")]
+    UnknownKey(String),
+
+    #[error("invalid value of {key}, expected {expected}")]
+    InvalidValue { key: String, expected: &'static str },
+
+    #[error("unknown error code: {0}")]
+    UnknownCode(String),
+
+    #[error("invalid features {0}, expected a module and its features, like module:feature,feature")]
+    InvalidFeatures(String),
+
+    #[error("failed to generate code")]
+    Codegen(#[from] ConfigFileError),
+}
+
+fn main() -> ExitCode {
+    let matches = cli().get_matches();
+    match run(&matches) {
+        Ok(code) => code,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            let mut source = std::error::Error::source(&error);
+            while let Some(error) = source {
+                eprintln!("  caused by: {}", error);
+                source = error.source();
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn cli() -> Command {
+    let file = || {
+        Arg::new("file")
+            .value_name("FILE")
+            .required(true)
+            .value_parser(value_parser!(PathBuf))
+            .help("The YANG module")
+    };
+    let files = || {
+        Arg::new("files")
+            .value_name("FILE")
+            .required(true)
+            .num_args(1..)
+            .value_parser(value_parser!(PathBuf))
+            .help("The YANG modules")
+    };
+
+    Command::new("yang-rs")
+        .about("Validate, lint and explore YANG modules, and generate code from them")
+        .version(env!("CARGO_PKG_VERSION"))
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .short('c')
+                .value_name("FILE")
+                .global(true)
+                .value_parser(value_parser!(PathBuf))
+                .help(format!(
+                    "The settings file, by default {} in the working directory if there is one",
+                    config::DEFAULT_FILE
+                )),
+        )
+        .arg(
+            Arg::new("path")
+                .long("path")
+                .short('p')
+                .value_name("DIR")
+                .global(true)
+                .action(ArgAction::Append)
+                .value_parser(value_parser!(PathBuf))
+                .help("A directory searched for imported modules and included submodules"),
+        )
+        .arg(
+            Arg::new("features")
+                .long("features")
+                .short('F')
+                .value_name("MODULE:FEATURES")
+                .global(true)
+                .action(ArgAction::Append)
+                .help("The supported features of a module, like ietf-interfaces:arbitrary-names, where every feature of modules not given is supported"),
+        )
+        .arg(
+            Arg::new("deviation-module")
+                .long("deviation-module")
+                .value_name("FILE")
+                .global(true)
+                .action(ArgAction::Append)
+                .value_parser(value_parser!(PathBuf))
+                .help("A module whose augments and deviations are applied to the modules"),
+        )
+        .arg(
+            Arg::new("style")
+                .long("style")
+                .value_name("STYLE")
+                .global(true)
+                .value_parser(Style::NAMES)
+                .help("The conventions trees and errors are printed with, yang-rs's own or libyang's, for comparing them against yanglint's"),
+        )
+        .arg(
+            Arg::new("yangcatalog")
+                .long("yangcatalog")
+                .global(true)
+                .action(ArgAction::SetTrue)
+                .help("Fetch the modules which aren't in the search paths from yangcatalog.org, caching them in ~/.cache/yang-rs/yangcatalog"),
+        )
+        .subcommand(
+            Command::new("parse")
+                .about("Print the resolved module")
+                .arg(file())
+                .arg(
+                    Arg::new("json")
+                        .long("json")
+                        .action(ArgAction::SetTrue)
+                        .help("Print the module as JSON rather than as Rust debug output"),
+                ),
+        )
+        .subcommand(
+            Command::new("validate")
+                .about("Check that the modules parse and resolve, and print their errors")
+                .arg(files()),
+        )
+        .subcommand(
+            Command::new("lint")
+                .about("Print the errors and warnings of the modules")
+                .arg(files())
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .short('f')
+                        .value_parser(["text", "json", "sarif"])
+                        .default_value("text")
+                        .help("The format the errors and warnings are printed in"),
+                )
+                .arg(
+                    Arg::new("error")
+                        .long("error")
+                        .value_name("CODE")
+                        .action(ArgAction::Append)
+                        .help("Report the warnings with the code as errors, like yang::unused_import"),
+                )
+                .arg(
+                    Arg::new("warning")
+                        .long("warning")
+                        .value_name("CODE")
+                        .action(ArgAction::Append)
+                        .help("Report the errors with the code as warnings, like yang::io"),
+                ),
+        )
+        .subcommand(
+            Command::new("tree")
+                .about("Print the schema tree of the module like pyang's tree output, or yanglint's in the libyang style")
+                .arg(file()),
+        )
+        .subcommand(
+            Command::new("codegen")
+                .about(format!(
+                    "Generate code for the module, or from a {} file when no module is given",
+                    CODEGEN_FILE
+                ))
+                .arg(file().required(false))
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .short('f')
+                        .value_parser(["rust", "proto", "json-schema"])
+                        .default_value("rust")
+                        .help("What is generated for the module"),
+                )
+                .arg(
+                    Arg::new("output")
+                        .long("output")
+                        .short('o')
+                        .value_name("FILE")
+                        .value_parser(value_parser!(PathBuf))
+                        .help("The file the code is written to, rather than printing it"),
+                )
+                .arg(
+                    Arg::new("from")
+                        .long("from")
+                        .value_name("FILE")
+                        .value_parser(value_parser!(PathBuf))
+                        .conflicts_with("file")
+                        .help(format!("The generation setup, by default {}", CODEGEN_FILE)),
+                ),
+        )
+        .subcommand(
+            Command::new("diff")
+                .about("Print the nodes added, removed and changed between two revisions of a module")
+                .arg(
+                    Arg::new("old")
+                        .value_name("OLD")
+                        .required(true)
+                        .value_parser(value_parser!(PathBuf)),
+                )
+                .arg(
+                    Arg::new("new")
+                        .value_name("NEW")
+                        .required(true)
+                        .value_parser(value_parser!(PathBuf)),
+                ),
+        )
+        .subcommand(
+            Command::new("deps")
+                .about("Print the modules the module imports and includes, and the files they're in")
+                .arg(file()),
+        )
+}
+
+fn run(matches: &ArgMatches) -> Result<ExitCode, Error> {
+    let (name, matches) = matches.subcommand().expect("a subcommand to be required");
+    let settings = settings(matches)?;
+    match name {
+        "parse" => parse(matches, &settings),
+        "validate" => lint(matches, &settings, None),
+        "lint" => {
+            let format = matches.get_one::<String>("format").expect("a default format");
+            lint(matches, &settings, Some(format))
+        }
+        "tree" => {
+            let module = parse_module(file(matches), &settings)?;
+            print(&tree::format(&module, &settings.features, settings.style))?;
+            Ok(ExitCode::SUCCESS)
+        }
+        "codegen" => codegen(matches, &settings),
+        "diff" => {
+            let old = parse_module(matches.get_one::<PathBuf>("old").expect("a required path"), &settings)?;
+            let new = parse_module(matches.get_one::<PathBuf>("new").expect("a required path"), &settings)?;
+            let changes = diff::diff(&old, &new);
+            print(&changes.iter().map(|change| format!("{}\n", change)).collect::<String>())?;
+            // Like diff, the exit code tells whether the modules differ.
+            Ok(if changes.is_empty() {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            })
+        }
+        "deps" => {
+            print(&deps::format(file(matches), &settings.search_paths)?)?;
+            Ok(ExitCode::SUCCESS)
+        }
+        _ => unreachable!("every subcommand to be handled"),
+    }
+}
+
+// The settings of the config file, with the flags added to them.
+fn settings(matches: &ArgMatches) -> Result<Settings, Error> {
+    let mut settings = match matches.get_one::<PathBuf>("config") {
+        Some(path) => Settings::load(path)?,
+        None if Path::new(config::DEFAULT_FILE).is_file() => Settings::load(Path::new(config::DEFAULT_FILE))?,
+        None => Settings::default(),
+    };
+    settings
+        .search_paths
+        .extend(matches.get_many::<PathBuf>("path").into_iter().flatten().cloned());
+    settings.deviations.extend(
+        matches
+            .get_many::<PathBuf>("deviation-module")
+            .into_iter()
+            .flatten()
+            .cloned(),
+    );
+    for spec in matches.get_many::<String>("features").into_iter().flatten() {
+        settings.features.add(spec)?;
+    }
+    if let Some(style) = matches.get_one::<String>("style") {
+        settings.style = Style::from_name(style).expect("a style of the possible values");
+    }
+    settings.yangcatalog |= matches.get_flag("yangcatalog");
+
+    Ok(settings)
+}
+
+// Output piped into a command which stops reading early, like head, isn't an error.
+fn print(output: &str) -> Result<(), Error> {
+    match std::io::stdout().lock().write_all(output.as_bytes()) {
+        Err(error) if error.kind() != std::io::ErrorKind::BrokenPipe => {
+            Err(Error::Write(PathBuf::from("stdout"), error))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn file(matches: &ArgMatches) -> &Path {
+    matches.get_one::<PathBuf>("file").expect("a required path")
+}
+
+fn parse_module(path: &Path, settings: &Settings) -> Result<Module, Error> {
+    match yang_parser::parse_with_options(path, &settings.parse_options()) {
+        Ok(YangModule::Module(module)) => Ok(module),
+        Ok(YangModule::Submodule(_)) => Err(Error::Submodule(path.to_path_buf())),
+        Err(error) => Err(Error::Parse(path.to_path_buf(), Box::new(error))),
+    }
+}
+
+fn parse(matches: &ArgMatches, settings: &Settings) -> Result<ExitCode, Error> {
+    let path = file(matches);
+    let module = yang_parser::parse_with_options(path, &settings.parse_options())
+        .map_err(|error| Error::Parse(path.to_path_buf(), Box::new(error)))?;
+    if matches.get_flag("json") {
+        print(&format!(
+            "{}\n",
+            serde_json::to_string_pretty(&module).expect("a module to serialize")
+        ))?;
+    } else {
+        print(&format!("{:#?}\n", module))?;
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+// Validate the modules, printing their errors, or lint them, printing their warnings as well in the format. The exit
+// code tells whether there were errors, after the severity policy.
+fn lint(matches: &ArgMatches, settings: &Settings, format: Option<&str>) -> Result<ExitCode, Error> {
+    let mut options = settings.parse_options();
+    options.error_policy = ErrorPolicy::CollectAll;
+    let warnings = Arc::new(Mutex::new(Vec::new()));
+    if format.is_some() {
+        options.warnings = Some(warnings.clone());
+        for (flag, severity) in [("error", Severity::Error), ("warning", Severity::Warning)] {
+            for name in matches.get_many::<String>(flag).into_iter().flatten() {
+                let code = ErrorCode::from_name(name).ok_or_else(|| Error::UnknownCode(name.clone()))?;
+                options.severity_policy.set(code, severity);
+            }
+        }
+    }
+
+    // Errors which aren't in a file, like the errors of most references, are in the module being checked.
+    let mut report = Report::new();
+    let mut add = |path: Option<&Path>, result: Result<(), ParserError>, warnings: Vec<Warning>, checked| {
+        let start = report.diagnostics.len();
+        if let Err(error) = result {
+            report.add_error(&error);
+        }
+        for warning in &warnings {
+            report.add_warning(warning);
+        }
+        report.diagnostics.extend(checked);
+        for diagnostic in &mut report.diagnostics[start..] {
+            if diagnostic.file.is_none() {
+                diagnostic.file = path.map(Path::to_path_buf);
+            }
+        }
+    };
+    let take_warnings = || std::mem::take(&mut *warnings.lock().expect("the warnings to not be poisoned"));
+
+    let paths: Vec<&PathBuf> = matches.get_many::<PathBuf>("files").expect("required paths").collect();
+    let result = yang_parser::scan(&paths, &options, |path, result| {
+        // The types of the modules which parsed are checked as well, as parsing doesn't check them.
+        let checked = match &result {
+            Ok(YangModule::Module(module)) => check::types(module, &options.severity_policy),
+            _ => Vec::new(),
+        };
+        add(Some(path), result.map(|_| ()), take_warnings(), checked);
+    });
+    // The errors of the deviation modules come after the scan.
+    add(None, result, take_warnings(), Vec::new());
+
+    match format {
+        Some("json") => print(&format!("{}\n", report.to_json()))?,
+        Some("sarif") => print(&format!("{}\n", report.to_sarif()))?,
+        _ => {
+            for diagnostic in &report.diagnostics {
+                let line = match settings.style {
+                    Style::YangRs => diagnostic_line(diagnostic),
+                    Style::Libyang => libyang_line(diagnostic),
+                };
+                eprintln!("{}", line);
+            }
+        }
+    }
+
+    Ok(if report.has_errors() {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+// The diagnostic like "a.yang:3:5: error: message, in uses b, at /c [yang::unresolved_grouping]".
+fn diagnostic_line(diagnostic: &Diagnostic) -> String {
+    let mut line = String::new();
+    if let Some(file) = &diagnostic.file {
+        line.push_str(&format!("{}:", file.display()));
+        if let Some(location) = diagnostic.location {
+            line.push_str(&format!("{}:{}:", location.line, location.column));
+        }
+        line.push(' ');
+    }
+    line.push_str(&format!("{}: {}", diagnostic.severity.as_str(), diagnostic.message));
+    if let Some(statement) = &diagnostic.statement {
+        line.push_str(&format!(", in {}", statement));
+    }
+    if let Some(path) = &diagnostic.path {
+        line.push_str(&format!(", at {}", path));
+    }
+    line.push_str(&format!(" [{}]", diagnostic.code));
+    line
+}
+
+// The diagnostic the way yanglint prints libyang's, like
+// "libyang err : message (Schema location "/a:c", line number 3.) [LYVE_REFERENCE]", where the schema path is qualified
+// with the module like libyang's are. Like libyang's, warnings have no code.
+fn libyang_line(diagnostic: &Diagnostic) -> String {
+    let level = match diagnostic.severity {
+        Severity::Error => "err ",
+        Severity::Warning => "warn",
+    };
+    let schema_location = diagnostic
+        .path
+        .as_ref()
+        .map(|path| match (&diagnostic.module, path.strip_prefix('/')) {
+            (Some(module), Some(rest)) if !rest.split('/').next().unwrap_or_default().contains(':') => {
+                format!("Schema location \"/{}:{}\"", module, rest)
+            }
+            _ => format!("Schema location \"{}\"", path),
+        });
+
+    let mut line = format!("libyang {}: {}", level, diagnostic.message);
+    match (schema_location, diagnostic.location) {
+        (Some(schema_location), Some(location)) => {
+            line.push_str(&format!(" ({}, line number {}.)", schema_location, location.line))
+        }
+        (Some(schema_location), None) => line.push_str(&format!(" ({}.)", schema_location)),
+        (None, Some(location)) => line.push_str(&format!(" (Line number {}.)", location.line)),
+        (None, None) => {}
+    }
+    if diagnostic.severity == Severity::Error {
+        line.push_str(&format!(" [{}]", diagnostic.code.libyang_code()));
+    }
+    line
+}
+
+// The warnings of generating code are printed to stderr, so they don't end up in the code printed to stdout.
+fn codegen_warnings() -> Arc<dyn yang_codegen::WarningSink> {
+    Arc::new(|warning| eprintln!("warning: {}", warning))
+}
+
+fn codegen(matches: &ArgMatches, settings: &Settings) -> Result<ExitCode, Error> {
+    let Some(path) = matches.get_one::<PathBuf>("file") else {
+        let setup = matches
+            .get_one::<PathBuf>("from")
+            .cloned()
+            .unwrap_or_else(|| PathBuf::from(CODEGEN_FILE));
+        let mut file = ConfigFile::load(&setup)?;
+        file.config.warnings = Some(codegen_warnings());
+        file.generate()?;
+        return Ok(ExitCode::SUCCESS);
+    };
+
+    let module = parse_module(path, settings)?;
+    let code = match matches.get_one::<String>("format").map(String::as_str) {
+        Some("proto") => yang_codegen::proto::generate(&module),
+        Some("json-schema") => yang_codegen::json_schema::generate(&module),
+        _ => {
+            let config = yang_codegen::Config {
+                warnings: Some(codegen_warnings()),
+                ..Default::default()
+            };
+            yang_codegen::generate_with_config(YangModule::Module(module), &config)
+        }
+    };
+    match matches.get_one::<PathBuf>("output") {
+        Some(output) => std::fs::write(output, code).map_err(|error| Error::Write(output.clone(), error))?,
+        None => print(&code)?,
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
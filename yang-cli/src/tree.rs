@@ -0,0 +1,273 @@
+use yang_parser::{
+    model::{Module, Status, TypeBody, TypeInfo},
+    schema_tree::{NodeId, NodeKind, SchemaTree},
+};
+
+use crate::{config::Style, features::Features};
+
+/// The schema tree of the module in the format of pyang's tree output, like:
+///
+/// ```text
+/// module: example-system
+///   +--rw system
+///      +--rw hostname?      string
+///      +--rw interface* [name]
+///         +--rw name    string
+/// ```
+///
+/// Nodes are read-write (`rw`) or read-only (`ro`), inputs are `-w`, rpcs and actions `-x` and notifications `-n`.
+/// Optional nodes end with `?`, lists and leaf-lists with `*` and presence containers with `!`. Choices are in
+/// parentheses, and cases start with a colon. Deprecated nodes start with `x` and obsolete nodes with `o`. Nodes whose
+/// if-features aren't supported are left out.
+///
+/// In the libyang style, anydata and anyxml nodes have `<anydata>` and `<anyxml>` in the type column like in yanglint's
+/// tree output.
+pub fn format(module: &Module, features: &Features, style: Style) -> String {
+    let tree = SchemaTree::new(module);
+    let printer = Printer {
+        tree: &tree,
+        module,
+        features,
+        style,
+    };
+
+    let mut data = Vec::new();
+    let mut rpcs = Vec::new();
+    let mut notifications = Vec::new();
+    for id in tree.roots().filter(|id| printer.is_supported(*id)) {
+        match tree.kind(id) {
+            NodeKind::Rpc(_) => rpcs.push(id),
+            NodeKind::Notification(_) => notifications.push(id),
+            _ => data.push(id),
+        }
+    }
+
+    let mut out = format!("module: {}\n", module.name);
+    printer.write_siblings(&mut out, &data, "  ", Access::ReadWrite);
+    if !rpcs.is_empty() {
+        out.push_str("\n  rpcs:\n");
+        printer.write_siblings(&mut out, &rpcs, "    ", Access::ReadWrite);
+    }
+    if !notifications.is_empty() {
+        out.push_str("\n  notifications:\n");
+        printer.write_siblings(&mut out, &notifications, "    ", Access::ReadOnly);
+    }
+
+    out
+}
+
+// What the data nodes under a node can be used for, which they inherit unless they change it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Access {
+    ReadWrite,
+    ReadOnly,
+    Input,
+}
+
+struct Printer<'a, 'm> {
+    tree: &'a SchemaTree<'m>,
+    module: &'m Module,
+    features: &'a Features,
+    style: Style,
+}
+
+impl Printer<'_, '_> {
+    fn write_siblings(&self, out: &mut String, ids: &[NodeId], prefix: &str, access: Access) {
+        let heads: Vec<(String, Access)> = ids.iter().map(|id| self.head(*id, access)).collect();
+        // The types of the leaves and leaf-lists are aligned in a column, where the node of a short case is on its own
+        // below the case.
+        let width = ids
+            .iter()
+            .zip(&heads)
+            .filter(|(id, _)| self.type_label(**id).is_some() && !self.is_short_case(**id))
+            .map(|(_, (head, _))| head.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        for (index, (id, (head, child_access))) in ids.iter().zip(heads).enumerate() {
+            let last = index + 1 == ids.len();
+            let child_prefix = format!("{}{}", prefix, if last { "   " } else { "|  " });
+            if self.is_short_case(*id) {
+                out.push_str(&format!("{}+--:({})\n", prefix, self.tree.kind(*id).name()));
+                self.write_node(out, *id, &head, &child_prefix, child_access, true);
+            } else {
+                let head = match self.type_label(*id) {
+                    Some(_) => format!("{:width$}", head, width = width),
+                    None => head,
+                };
+                self.write_node(out, *id, &head, prefix, child_access, last);
+            }
+        }
+    }
+
+    // The line of the node, with its head padded to the type column of its siblings, and the lines of its children.
+    fn write_node(&self, out: &mut String, id: NodeId, head: &str, prefix: &str, access: Access, last: bool) {
+        let kind = self.tree.kind(id);
+        out.push_str(prefix);
+        out.push_str(head);
+        if let Some(type_label) = self.type_label(id) {
+            out.push_str("   ");
+            out.push_str(&type_label);
+        }
+        if let NodeKind::List(list) = kind {
+            if let Some(key) = &list.key {
+                out.push_str(&format!(" [{}]", key.split_whitespace().collect::<Vec<_>>().join(" ")));
+            }
+        }
+        let if_features = if_features(kind);
+        if !if_features.is_empty() {
+            out.push_str(&format!(" {{{}}}?", if_features.join(",")));
+        }
+        out.push('\n');
+
+        let children: Vec<NodeId> = self
+            .tree
+            .children(id)
+            .filter(|child| self.is_supported(*child))
+            .collect();
+        let child_prefix = format!("{}{}", prefix, if last { "   " } else { "|  " });
+        self.write_siblings(out, &children, &child_prefix, access);
+    }
+
+    // The line of the node before its type, like "+--rw name?", and what its children are used for.
+    fn head(&self, id: NodeId, access: Access) -> (String, Access) {
+        let kind = self.tree.kind(id);
+        let status = match status(kind) {
+            Some(Status::Deprecated) => 'x',
+            Some(Status::Obsolete) => 'o',
+            _ => '+',
+        };
+        let name = kind.name();
+
+        let access = match (config(kind), access) {
+            (Some(false), Access::ReadWrite) => Access::ReadOnly,
+            _ => access,
+        };
+        let (flags, child_access) = match kind {
+            NodeKind::Case(_) => return (format!("{}--:({})", status, name), access),
+            NodeKind::Rpc(_) | NodeKind::Action(_) => ("-x", access),
+            NodeKind::Input(_) => ("-w", Access::Input),
+            NodeKind::Output(_) => ("ro", Access::ReadOnly),
+            NodeKind::Notification(_) => ("-n", Access::ReadOnly),
+            _ => match access {
+                Access::ReadWrite => ("rw", access),
+                Access::ReadOnly => ("ro", access),
+                Access::Input => ("-w", access),
+            },
+        };
+
+        let (name, marker) = match kind {
+            NodeKind::Choice(choice) => (format!("({})", name), optional(choice.mandatory)),
+            NodeKind::Container(container) if container.meta.presence.is_some() => (name.to_string(), "!"),
+            NodeKind::List(_) | NodeKind::LeafList(_) => (name.to_string(), "*"),
+            NodeKind::Leaf(leaf) if !self.is_key(id) => (name.to_string(), optional(leaf.mandatory)),
+            NodeKind::Anydata(anydata) => (name.to_string(), optional(anydata.mandatory)),
+            NodeKind::Anyxml(anyxml) => (name.to_string(), optional(anyxml.mandatory)),
+            _ => (name.to_string(), ""),
+        };
+
+        (format!("{}--{} {}{}", status, flags, name, marker), child_access)
+    }
+
+    // The type column of the node, which leaves and leaf-lists have, and in the libyang style anydata and anyxml.
+    fn type_label(&self, id: NodeId) -> Option<String> {
+        match (self.tree.kind(id), self.style) {
+            (NodeKind::Anydata(_), Style::Libyang) => Some("<anydata>".to_string()),
+            (NodeKind::Anyxml(_), Style::Libyang) => Some("<anyxml>".to_string()),
+            (kind, _) => type_info(kind).map(type_label),
+        }
+    }
+
+    fn is_supported(&self, id: NodeId) -> bool {
+        self.features.allows(self.module, if_features(self.tree.kind(id)))
+    }
+
+    // Whether the node is a case of a choice without a case statement, which is shown with a case like in pyang.
+    fn is_short_case(&self, id: NodeId) -> bool {
+        let parent = self.tree.parent(id).map(|parent| self.tree.kind(parent));
+        matches!(parent, Some(NodeKind::Choice(_))) && !matches!(self.tree.kind(id), NodeKind::Case(_))
+    }
+
+    // Whether the node is a key of the list it's in.
+    fn is_key(&self, id: NodeId) -> bool {
+        let Some(NodeKind::List(list)) = self.tree.parent(id).map(|parent| self.tree.kind(parent)) else {
+            return false;
+        };
+        let name = self.tree.kind(id).name();
+        list.key
+            .as_deref()
+            .unwrap_or_default()
+            .split_whitespace()
+            .any(|key| key.rsplit(':').next() == Some(name))
+    }
+}
+
+fn optional(mandatory: Option<bool>) -> &'static str {
+    match mandatory {
+        Some(true) => "",
+        _ => "?",
+    }
+}
+
+// The type of a leafref is the path it refers to, like "-> /interfaces/interface/name".
+fn type_label(type_info: &TypeInfo) -> String {
+    match type_info.type_body.as_deref() {
+        Some(TypeBody::Leafref { path, .. }) => format!("-> {}", path),
+        _ => type_info.name.to_string(),
+    }
+}
+
+fn type_info<'m>(kind: NodeKind<'m>) -> Option<&'m TypeInfo> {
+    match kind {
+        NodeKind::Leaf(leaf) => Some(&leaf.type_info),
+        NodeKind::LeafList(leaf_list) => Some(&leaf_list.type_info),
+        _ => None,
+    }
+}
+
+pub(crate) fn config(kind: NodeKind) -> Option<bool> {
+    match kind {
+        NodeKind::Container(container) => container.config,
+        NodeKind::Leaf(leaf) => leaf.config,
+        NodeKind::LeafList(leaf_list) => leaf_list.config,
+        NodeKind::List(list) => list.config,
+        NodeKind::Choice(choice) => choice.config,
+        NodeKind::Anydata(anydata) => anydata.config,
+        NodeKind::Anyxml(anyxml) => anyxml.config,
+        _ => None,
+    }
+}
+
+pub(crate) fn status<'m>(kind: NodeKind<'m>) -> Option<&'m Status> {
+    match kind {
+        NodeKind::Container(container) => container.meta.status.as_ref(),
+        NodeKind::Leaf(leaf) => leaf.meta.status.as_ref(),
+        NodeKind::LeafList(leaf_list) => leaf_list.meta.status.as_ref(),
+        NodeKind::List(list) => list.meta.status.as_ref(),
+        NodeKind::Choice(choice) => choice.meta.status.as_ref(),
+        NodeKind::Case(case) => case.meta.status.as_ref(),
+        NodeKind::Anydata(anydata) => anydata.meta.status.as_ref(),
+        NodeKind::Anyxml(anyxml) => anyxml.meta.status.as_ref(),
+        NodeKind::Rpc(rpc) => rpc.status.as_ref(),
+        NodeKind::Action(action) => action.status.as_ref(),
+        NodeKind::Notification(notification) => notification.status.as_ref(),
+        NodeKind::Input(_) | NodeKind::Output(_) => None,
+    }
+}
+
+pub(crate) fn if_features<'m>(kind: NodeKind<'m>) -> &'m [String] {
+    match kind {
+        NodeKind::Container(container) => &container.if_features,
+        NodeKind::Leaf(leaf) => &leaf.if_features,
+        NodeKind::LeafList(leaf_list) => &leaf_list.if_features,
+        NodeKind::List(list) => &list.if_features,
+        NodeKind::Choice(choice) => &choice.if_features,
+        NodeKind::Case(case) => &case.if_features,
+        NodeKind::Anydata(anydata) => &anydata.if_features,
+        NodeKind::Anyxml(anyxml) => &anyxml.if_features,
+        NodeKind::Rpc(rpc) => &rpc.if_features,
+        NodeKind::Action(action) => &action.if_features,
+        NodeKind::Notification(notification) => &notification.if_features,
+        NodeKind::Input(_) | NodeKind::Output(_) => &[],
+    }
+}
@@ -0,0 +1,150 @@
+use std::process::{Command, Output};
+
+use serde_json::Value;
+
+// Run yang-rs in tests/yang, searching it for imports, so the settings file of the working directory isn't read.
+fn yang_rs(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_yang-rs"))
+        .current_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/yang"))
+        .args(["--path", "."])
+        .args(args)
+        .output()
+        .expect("yang-rs to run")
+}
+
+fn json(output: &Output) -> Value {
+    serde_json::from_slice(&output.stdout).expect("the output to be JSON")
+}
+
+#[test]
+fn lint_prints_a_sarif_log() {
+    let output = yang_rs(&[
+        "lint",
+        "--format",
+        "sarif",
+        "example-unused.yang",
+        "example-system.yang",
+    ]);
+    assert!(output.status.success());
+
+    let sarif = json(&output);
+    let results = sarif["runs"][0]["results"]
+        .as_array()
+        .expect("the results to be an array");
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["ruleId"], "yang::unused_import");
+    assert_eq!(results[0]["level"], "warning");
+    assert_eq!(
+        results[0]["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+        "example-unused.yang"
+    );
+}
+
+#[test]
+fn lint_fails_on_warnings_promoted_to_errors() {
+    let output = yang_rs(&[
+        "lint",
+        "--format",
+        "json",
+        "--error",
+        "yang::unused_import",
+        "example-unused.yang",
+    ]);
+    assert!(!output.status.success());
+
+    let report = json(&output);
+    assert_eq!(report["diagnostics"][0]["severity"], "error");
+    assert_eq!(report["diagnostics"][0]["code"], "yang::unused_import");
+    assert_eq!(report["diagnostics"][0]["module"], "example-unused");
+}
+
+#[test]
+fn lint_rejects_unknown_codes() {
+    let output = yang_rs(&["lint", "--error", "yang::no_such_code", "example-unused.yang"]);
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn codegen_prints_only_the_generated_code() {
+    let output = yang_rs(&["codegen", "--format", "json-schema", "example-system.yang"]);
+    assert!(output.status.success());
+
+    let schema = json(&output);
+    let server = &schema["properties"]["example-system:system"]["properties"]["server"];
+    let mut properties: Vec<&String> = server["items"]["properties"]
+        .as_object()
+        .expect("the entries of the list to have properties")
+        .keys()
+        .collect();
+    properties.sort();
+    assert_eq!(properties, ["ip", "name", "port"]);
+}
+
+#[test]
+fn lint_checks_types_ranges_defaults_and_groupings() {
+    let output = yang_rs(&["lint", "--format", "json", "example-invalid.yang"]);
+    assert!(!output.status.success());
+
+    let report = json(&output);
+    let diagnostics: Vec<(&str, &str, &str)> = report["diagnostics"]
+        .as_array()
+        .expect("the diagnostics to be an array")
+        .iter()
+        .map(|diagnostic| {
+            (
+                diagnostic["severity"].as_str().expect("a severity"),
+                diagnostic["code"].as_str().expect("a code"),
+                diagnostic["path"].as_str().expect("a path"),
+            )
+        })
+        .collect();
+    assert_eq!(
+        diagnostics,
+        [
+            ("warning", "yang::unresolved_grouping", "/"),
+            ("error", "yang::unresolved_type", "/system/undefined"),
+            ("error", "yang::unresolved_type", "/system/unknown-prefix"),
+            ("error", "yang::invalid_restriction", "/system/empty-range"),
+            ("error", "yang::invalid_default", "/system/level"),
+        ]
+    );
+    assert_eq!(
+        report["diagnostics"][2]["message"],
+        "prefix x of type x:missing is not imported"
+    );
+}
+
+#[test]
+fn validate_fails_on_invalid_types() {
+    let output = yang_rs(&["validate", "example-invalid.yang"]);
+    assert!(!output.status.success());
+
+    let stderr = String::from_utf8(output.stderr).expect("the errors to be UTF-8");
+    for message in [
+        "type missing is not defined",
+        "range \"9..5\" is invalid: the interval 9..5 is empty",
+        "default 300 is not a value of the type: int8 value out of range: 300",
+    ] {
+        assert!(stderr.contains(message), "{}", stderr);
+    }
+
+    assert!(yang_rs(&["validate", "example-system.yang"]).status.success());
+}
+
+#[test]
+fn lint_demotes_type_errors_to_warnings() {
+    let output = yang_rs(&[
+        "lint",
+        "--format",
+        "json",
+        "--warning",
+        "yang::unresolved_type",
+        "--warning",
+        "yang::invalid_restriction",
+        "--warning",
+        "yang::invalid_default",
+        "example-invalid.yang",
+    ]);
+    assert!(output.status.success());
+}
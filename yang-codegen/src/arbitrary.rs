@@ -0,0 +1,244 @@
+//! The proptest `Arbitrary` implementations and strategies of the generated types.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use yang_parser::model::*;
+
+use crate::{structs::allow_deprecated, utils, CodeGenerator, Field};
+
+impl<'a> CodeGenerator<'a> {
+    // The Arbitrary implementation of a generated type, with the strategy for its values.
+    pub(crate) fn arbitrary_impl(&self, type_name: &syn::Ident, strategy: TokenStream) -> TokenStream {
+        if !self.proptest() {
+            return quote! {};
+        }
+
+        quote! {
+            impl proptest::arbitrary::Arbitrary for #type_name {
+                type Parameters = ();
+                type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+                fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+                    use proptest::strategy::Strategy;
+                    #strategy.boxed()
+                }
+            }
+        }
+    }
+
+    // Whether the type has values to generate, which identityrefs without identities don't.
+    pub(crate) fn has_values(&self, type_info: &TypeInfo, module_name: &str) -> bool {
+        match type_info.type_body.as_deref() {
+            Some(TypeBody::Identityref { bases }) => !self.derived_identities(bases, module_name).is_empty(),
+            _ => match self.find_type_def(&type_info.name, module_name) {
+                Some((type_module, _, type_def)) => self.has_values(&type_def.type_info, &type_module),
+                None => true,
+            },
+        }
+    }
+
+    // A strategy for values of a leaf or leaf-list type with the value type as its Rust type. Values of built-in types
+    // are generated with their restrictions, while generated types, newtypes of typedefs and mapped types generate
+    // their own.
+    pub(crate) fn arbitrary_value(
+        &mut self,
+        value_type: &TokenStream,
+        type_info: &TypeInfo,
+        module_name: &str,
+    ) -> TokenStream {
+        let built_in = self.base_is_built_in(type_info, module_name)
+            && self.find_type_def(&type_info.name, module_name).is_none()
+            && self.mapped_type(&type_info.name, module_name).is_none();
+        match built_in {
+            true => self.built_in_strategy(type_info, module_name),
+            false => quote! { proptest::arbitrary::any::<#value_type>() },
+        }
+    }
+
+    // A strategy for values of a built-in type, restricted by the type and the typedefs it's derived from. Values are
+    // generated within the range or length, and matching the first pattern, closest to the type, as restrictions of
+    // derived types are at least as restrictive as those of their base types. The values are then filtered by the
+    // checks of all the restrictions, like inverted patterns and the patterns of base types.
+    pub(crate) fn built_in_strategy(&mut self, type_info: &TypeInfo, module_name: &str) -> TokenStream {
+        let mut type_bodies = Vec::new();
+        let mut current = (type_info.clone(), module_name.to_string());
+        loop {
+            type_bodies.extend(current.0.type_body.as_deref().cloned());
+            match self.find_type_def(&current.0.name, &current.1) {
+                Some((type_module, _, type_def)) => current = (type_def.type_info.clone(), type_module),
+                None => break,
+            }
+        }
+        let built_in = current.0.name;
+        let rust_type = self.rust_type(&built_in);
+        let arbitrary = self.arbitrary_path();
+
+        let range = type_bodies.iter().find_map(|type_body| match type_body {
+            TypeBody::Numerical { range } | TypeBody::Decimal64 { range: Some(range), .. } => Some(&range.value),
+            _ => None,
+        });
+        // A string restricted only by its length is parsed as a binary type body, as they look the same.
+        let lengths = type_bodies
+            .iter()
+            .find_map(|type_body| match type_body {
+                TypeBody::String {
+                    length: Some(length), ..
+                }
+                | TypeBody::Binary { length: Some(length) } => {
+                    utils::parse_intervals(&length.value, |bound| match bound {
+                        "min" => Some(0),
+                        "max" => Some(u64::MAX),
+                        _ => bound.parse::<u64>().ok(),
+                    })
+                }
+                _ => None,
+            })
+            .unwrap_or(vec![(0, 16)]);
+        // Unbounded lengths are kept short.
+        let lengths = lengths.iter().map(|(lower, upper)| {
+            let upper = proc_macro2::Literal::u64_unsuffixed((*upper).min(lower.saturating_add(32)));
+            let lower = proc_macro2::Literal::u64_unsuffixed(*lower);
+            quote! { (#lower, #upper) }
+        });
+
+        let strategy = match built_in.as_str() {
+            "int8" | "int16" | "int32" | "int64" | "uint8" | "uint16" | "uint32" | "uint64" => {
+                let (min, max) = utils::integer_bounds(&built_in);
+                let intervals = range
+                    .and_then(|range| {
+                        utils::parse_intervals(range, |bound| match bound {
+                            "min" => Some(min),
+                            "max" => Some(max),
+                            _ => utils::parse_integer(&built_in, bound),
+                        })
+                    })
+                    .unwrap_or(vec![(min, max)]);
+                let intervals = intervals.iter().map(|(lower, upper)| {
+                    let lower = proc_macro2::Literal::i128_suffixed(*lower);
+                    let upper = proc_macro2::Literal::i128_suffixed(*upper);
+                    quote! { (#lower as #rust_type)..=(#upper as #rust_type) }
+                });
+                quote! { proptest::strategy::Union::new([#(#intervals),*]) }
+            }
+            // Decimal values are generated as integers scaled by their fraction digits, so they have no more fraction
+            // digits than the type allows.
+            "decimal64" => {
+                let fraction_digits = type_bodies
+                    .iter()
+                    .find_map(|type_body| match type_body {
+                        TypeBody::Decimal64 { fraction_digits, .. } => fraction_digits.parse::<u32>().ok(),
+                        _ => None,
+                    })
+                    .unwrap_or(0);
+                let scaled = |bound: &str| {
+                    let (mantissa, scale) = utils::decimal_parts(bound)?;
+                    let factor = 10i128.checked_pow(fraction_digits.checked_sub(scale)?)?;
+                    Some(i128::from(mantissa) * factor)
+                };
+                let (min, max) = (i128::from(i64::MIN), i128::from(i64::MAX));
+                let intervals = range
+                    .and_then(|range| {
+                        utils::parse_intervals(range, |bound| match bound {
+                            "min" => Some(min),
+                            "max" => Some(max),
+                            _ => scaled(bound),
+                        })
+                    })
+                    .unwrap_or(vec![(min, max)]);
+                let intervals = intervals.iter().map(|(lower, upper)| {
+                    let lower = proc_macro2::Literal::i128_suffixed(*lower);
+                    let upper = proc_macro2::Literal::i128_suffixed(*upper);
+                    quote! { (#lower as i64)..=(#upper as i64) }
+                });
+                let value = match self.config.rust_decimal {
+                    true => quote! { rust_decimal::Decimal::new(value, #fraction_digits) },
+                    false => {
+                        let factor = proc_macro2::Literal::f64_unsuffixed(10f64.powi(fraction_digits as i32));
+                        quote! { value as f64 / #factor }
+                    }
+                };
+                quote! { proptest::strategy::Union::new([#(#intervals),*]).prop_map(|value| #value) }
+            }
+            "string" | "leafref" => {
+                let pattern = type_bodies.iter().find_map(|type_body| match type_body {
+                    TypeBody::String { patterns, .. } => patterns
+                        .iter()
+                        .find(|pattern| pattern.modifier.as_deref() != Some("invert-match")),
+                    _ => None,
+                });
+                let pattern = pattern.and_then(|pattern| yang_parser::value::pattern_regex(&pattern.value).ok());
+                let pattern = match pattern {
+                    Some(pattern) => {
+                        let pattern = format!("(?:{})", pattern);
+                        quote! { Some(#pattern) }
+                    }
+                    None => quote! { None },
+                };
+                quote! { #arbitrary::string(&[#(#lengths),*], #pattern) }
+            }
+            "binary" => quote! { #arbitrary::binary(&[#(#lengths),*]) },
+            "empty" => quote! { proptest::strategy::Just(#rust_type) },
+            _ => quote! { proptest::arbitrary::any::<#rust_type>() },
+        };
+
+        let checks = self.value_checks(
+            type_info,
+            module_name,
+            [quote! { *value }, quote! { value }, quote! { value }],
+            |_| quote! { return false; },
+        );
+        match checks.is_empty() {
+            true => strategy,
+            false => quote! {
+                #strategy.prop_filter("values satisfying the restrictions of the type", |value: &#rust_type| {
+                    #checks
+                    true
+                })
+            },
+        }
+    }
+
+    // Random structs start out with their defaults, and get a random value for each field.
+    pub(crate) fn generate_arbitrary_impl(&self, struct_name: &syn::Ident, fields: &[Field]) -> TokenStream {
+        if !self.proptest() {
+            return quote! {};
+        }
+
+        let values = fields.iter().map(
+            |Field {
+                 name, cfg, arbitrary, ..
+             }| {
+                quote! {
+                    #cfg
+                    let strategy = (strategy, #arbitrary)
+                        .prop_map(|(mut node, value)| {
+                            node.#name = value;
+                            node
+                        })
+                        .boxed();
+                }
+            },
+        );
+        let allow_deprecated = allow_deprecated(fields);
+
+        quote! {
+            #allow_deprecated
+            impl proptest::arbitrary::Arbitrary for #struct_name {
+                type Parameters = ();
+                type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+                fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+                    use proptest::strategy::Strategy;
+                    let strategy = proptest::strategy::Just(Self::default()).boxed();
+                    #(#values)*
+                    strategy
+                }
+            }
+        }
+    }
+
+    pub(crate) fn arbitrary_path(&self) -> TokenStream {
+        let supers = std::iter::repeat_n(quote! { super:: }, self.depth);
+        quote! { #(#supers)* arbitrary }
+    }
+}
@@ -0,0 +1,281 @@
+//! The checks of the generated `validate` methods, for the restrictions of types and the number of entries of lists and
+//! leaf-lists.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use yang_parser::model::*;
+
+use crate::{utils, CodeGenerator};
+
+impl<'a> CodeGenerator<'a> {
+    // Check the number of entries of a list or leaf-list against its min-elements and max-elements.
+    pub(crate) fn elements_check(
+        &self,
+        name: &str,
+        field_name: &syn::Ident,
+        min_elements: Option<i64>,
+        max_elements: &Option<MaxElements>,
+    ) -> TokenStream {
+        let validation = self.validation_path();
+        let min_check = min_elements.filter(|min| *min > 0).map(|min| {
+            let message = format!("expected at least {} entries of {}, found {{}}", min, name);
+            let min = proc_macro2::Literal::i64_unsuffixed(min);
+            quote! {
+                if self.#field_name.len() < #min {
+                    errors.push(#validation::ValidationError {
+                        path: path.to_string(),
+                        message: format!(#message, self.#field_name.len()),
+                    });
+                }
+            }
+        });
+        let max_check = match max_elements {
+            Some(MaxElements::Value(max)) => {
+                let message = format!("expected at most {} entries of {}, found {{}}", max, name);
+                let max = proc_macro2::Literal::i64_unsuffixed(*max);
+                Some(quote! {
+                    if self.#field_name.len() > #max {
+                        errors.push(#validation::ValidationError {
+                            path: path.to_string(),
+                            message: format!(#message, self.#field_name.len()),
+                        });
+                    }
+                })
+            }
+            _ => None,
+        };
+
+        quote! {
+            #min_check
+            #max_check
+        }
+    }
+
+    // Checks of the range, length and pattern restrictions of a leaf or leaf-list value, including the restrictions of
+    // the typedefs its type is derived from. The checks use the value, a reference to the field's value, and report
+    // errors at the path. Generated types, like enumerations and unions, have no restrictions to check, checked
+    // newtypes check their own, and restrictions which can't be converted, like patterns the regex crate doesn't support, are skipped.
+    pub(crate) fn restriction_checks(&mut self, type_info: &TypeInfo) -> TokenStream {
+        let module_name = self.module.name.clone();
+        if !self.base_is_built_in(type_info, &module_name) {
+            return quote! {};
+        }
+
+        // Newtypes of typedefs hold the value of the built-in type. The receiver is used to call methods on the value.
+        let (value, value_ref, receiver) = match self.find_type_def(&type_info.name, &module_name) {
+            Some(_) => (quote! { value.0 }, quote! { &value.0 }, quote! { value.0 }),
+            None => (quote! { *value }, quote! { value }, quote! { value }),
+        };
+        let validation = self.validation_path();
+        let error = |message: TokenStream| {
+            quote! {
+                errors.push(#validation::ValidationError {
+                    path: path.clone(),
+                    message: #message,
+                });
+            }
+        };
+        self.value_checks(type_info, &module_name, [value, value_ref, receiver], error)
+    }
+
+    // Checks of the restrictions of the type and the typedefs it's derived from on a value of the built-in type, given
+    // as expressions of the value, a reference to it and a receiver to call methods on. The error builds the code
+    // handling a violated restriction from the expression of its message.
+    pub(crate) fn value_checks(
+        &mut self,
+        type_info: &TypeInfo,
+        module_name: &str,
+        [value, value_ref, receiver]: [TokenStream; 3],
+        error: impl Fn(TokenStream) -> TokenStream,
+    ) -> TokenStream {
+        let no_std = self.config.no_std;
+        let mut type_bodies = Vec::new();
+        let mut current = (type_info.clone(), module_name.to_string());
+        loop {
+            type_bodies.extend(current.0.type_body.as_deref().cloned());
+            match self.find_type_def(&current.0.name, &current.1) {
+                Some((type_module, _, type_def)) => current = (type_def.type_info.clone(), type_module),
+                None => break,
+            }
+        }
+        let built_in = current.0.name;
+
+        // A string restricted only by its length is parsed as a binary type body, as they look the same.
+        let type_bodies: Vec<TypeBody> = type_bodies
+            .into_iter()
+            .map(|type_body| match type_body {
+                TypeBody::Binary { length } if built_in == "string" => TypeBody::String {
+                    length,
+                    patterns: Vec::new(),
+                },
+                type_body => type_body,
+            })
+            .collect();
+        let message = |error_message: &Option<String>, default: String, argument: TokenStream| match error_message {
+            Some(error_message) => quote! { #error_message.to_string() },
+            None => quote! { format!(#default, #argument) },
+        };
+
+        let checks = type_bodies.iter().flat_map(|type_body| match type_body {
+            TypeBody::Numerical { range } => {
+                let (min, max) = utils::integer_bounds(&built_in);
+                let intervals = utils::parse_intervals(&range.value, |bound| match bound {
+                    "min" => Some(min),
+                    "max" => Some(max),
+                    _ => utils::parse_integer(&built_in, bound),
+                });
+                intervals
+                    .map(|intervals| {
+                        let conditions = intervals.iter().map(|(lower, upper)| {
+                            let lower = proc_macro2::Literal::i128_unsuffixed(*lower);
+                            let upper = proc_macro2::Literal::i128_unsuffixed(*upper);
+                            quote! { (#lower..=#upper).contains(&number) }
+                        });
+                        let message = message(
+                            &range.error_message,
+                            format!("value {{}} is outside of the allowed range {}", range.value),
+                            quote! { number },
+                        );
+                        let error = error(message);
+                        quote! {
+                            let number = i128::from(#value);
+                            if !(#(#conditions)||*) {
+                                #error
+                            }
+                        }
+                    })
+                    .into_iter()
+                    .collect::<Vec<_>>()
+            }
+            TypeBody::Decimal64 { range: Some(range), .. } => {
+                let number_type = self.rust_type("decimal64");
+                let intervals = utils::parse_intervals(&range.value, |bound| match bound {
+                    "min" => Some(quote! { #number_type::MIN }),
+                    "max" => Some(quote! { #number_type::MAX }),
+                    _ => Some(self.default_literal("decimal64", bound)),
+                });
+                intervals
+                    .map(|intervals| {
+                        let conditions = intervals
+                            .iter()
+                            .map(|(lower, upper)| quote! { (#lower..=#upper).contains(&number) });
+                        let message = message(
+                            &range.error_message,
+                            format!("value {{}} is outside of the allowed range {}", range.value),
+                            quote! { number },
+                        );
+                        let error = error(message);
+                        quote! {
+                            let number: #number_type = #value;
+                            if !(#(#conditions)||*) {
+                                #error
+                            }
+                        }
+                    })
+                    .into_iter()
+                    .collect()
+            }
+            TypeBody::String { length, patterns } => {
+                let length_check = length.as_ref().and_then(|length| {
+                    let intervals = utils::parse_intervals(&length.value, |bound| match bound {
+                        "min" => Some(0),
+                        "max" => Some(u64::MAX),
+                        _ => bound.parse::<u64>().ok(),
+                    })?;
+                    let conditions = intervals.iter().map(|(lower, upper)| {
+                        let lower = proc_macro2::Literal::u64_unsuffixed(*lower);
+                        let upper = proc_macro2::Literal::u64_unsuffixed(*upper);
+                        quote! { (#lower..=#upper).contains(&length) }
+                    });
+                    let message = message(
+                        &length.error_message,
+                        format!("value {{}} does not satisfy the length restriction {}", length.value),
+                        quote! { #value_ref },
+                    );
+                    let error = error(message);
+                    Some(quote! {
+                        let length = #receiver.chars().count() as u64;
+                        if !(#(#conditions)||*) {
+                            #error
+                        }
+                    })
+                });
+
+                // YANG patterns are XML Schema regular expressions, which are implicitly anchored at both ends.
+                let pattern_checks = patterns.iter().filter_map(|pattern| {
+                    let regex = format!("^(?:{})$", yang_parser::value::pattern_regex(&pattern.value).ok()?);
+                    regex::Regex::new(&regex).ok()?;
+                    let inverted = pattern.modifier.as_deref() == Some("invert-match");
+                    let default_message = match inverted {
+                        true => format!("value {{}} matches the inverted pattern {}", pattern.value),
+                        false => format!("value {{}} does not match the pattern {}", pattern.value),
+                    };
+                    let message = message(&pattern.error_message, default_message, quote! { #value_ref });
+                    let error = error(message);
+                    // Patterns are compiled once, except in no_std crates, which don't have LazyLock.
+                    let pattern = match no_std {
+                        true => quote! {
+                            let pattern = regex::Regex::new(#regex).expect("pattern to be valid");
+                        },
+                        false => quote! {
+                            static PATTERN: std::sync::LazyLock<regex::Regex> =
+                                std::sync::LazyLock::new(|| regex::Regex::new(#regex).expect("pattern to be valid"));
+                            let pattern = &*PATTERN;
+                        },
+                    };
+                    Some(quote! {
+                        {
+                            #pattern
+                            if pattern.is_match(#value_ref) == #inverted {
+                                #error
+                            }
+                        }
+                    })
+                });
+
+                length_check.into_iter().chain(pattern_checks).collect()
+            }
+            // The length of a binary value is its number of octets.
+            TypeBody::Binary { length: Some(length) } => {
+                let intervals = utils::parse_intervals(&length.value, |bound| match bound {
+                    "min" => Some(0),
+                    "max" => Some(u64::MAX),
+                    _ => bound.parse::<u64>().ok(),
+                });
+                intervals
+                    .map(|intervals| {
+                        let conditions = intervals.iter().map(|(lower, upper)| {
+                            let lower = proc_macro2::Literal::u64_unsuffixed(*lower);
+                            let upper = proc_macro2::Literal::u64_unsuffixed(*upper);
+                            quote! { (#lower..=#upper).contains(&length) }
+                        });
+                        let message = message(
+                            &length.error_message,
+                            format!(
+                                "value of {{}} octets does not satisfy the length restriction {}",
+                                length.value
+                            ),
+                            quote! { length },
+                        );
+                        let error = error(message);
+                        quote! {
+                            let length = #receiver.len() as u64;
+                            if !(#(#conditions)||*) {
+                                #error
+                            }
+                        }
+                    })
+                    .into_iter()
+                    .collect()
+            }
+            _ => Vec::new(),
+        });
+
+        quote! { #(#checks)* }
+    }
+
+    pub(crate) fn validation_path(&self) -> TokenStream {
+        let supers = std::iter::repeat_n(quote! { super:: }, self.depth);
+        quote! { #(#supers)* validation }
+    }
+}
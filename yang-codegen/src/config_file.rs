@@ -0,0 +1,301 @@
+//! Generation setups described by a `yang-codegen.toml` file, so the modules, search paths, deviations, options and
+//! output layout can be versioned with the code rather than encoded in build scripts. Paths in the file are relative
+//! to the directory of the file. For example:
+//!
+//! ```toml
+//! # The modules to generate code for.
+//! modules = ["yang/example-system.yang"]
+//! # Directories searched for imported modules, after the directory of the module importing them.
+//! search-paths = ["yang/ietf"]
+//! # Modules whose augments and deviations of the generated modules are applied.
+//! deviations = ["yang/example-deviations.yang"]
+//! # The supported features of modules, where every feature of the modules not given is supported.
+//! features = ["ietf-interfaces:arbitrary-names"]
+//! # The directory the code is written to, with a mod.rs declaring a Rust module per YANG module.
+//! output = "src/yang"
+//! # "files" writes a file per module, like example_system.rs, and "tree" writes a directory per module, with a
+//! # file per top-level Rust module.
+//! layout = "files"
+//!
+//! # The options of [Config], named in kebab-case.
+//! [options]
+//! xml = true
+//! if-features = true
+//! derives = ["PartialEq"]
+//! exclude-paths = ["/system/ntp"]
+//!
+//! [options.naming]
+//! strip-prefixes = ["openconfig-"]
+//! abbreviations = ["ip", "bgp"]
+//!
+//! [options.type-attributes]
+//! Interface = ["#[derive(Hash)]"]
+//!
+//! [options.external-modules]
+//! ietf-inet-types = "crate::inet"
+//!
+//! # Typedefs mapped to Rust types, keyed by the module and typedef name.
+//! [type-mappings]
+//! "ietf-inet-types:ipv4-address" = "std::net::Ipv4Addr"
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use syn::ext::IdentExt;
+use toml_edit::{Item, Table};
+use yang_parser::{model::YangModule, ParseOptions};
+
+use crate::{CaseConvention, Config, Naming};
+
+/// A generation setup read from a `yang-codegen.toml` file.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigFile {
+    /// The modules to generate code for.
+    pub modules: Vec<PathBuf>,
+    /// Directories searched for imported modules and included submodules.
+    pub search_paths: Vec<PathBuf>,
+    /// Modules whose augments and deviations of the generated modules are applied.
+    pub deviations: Vec<PathBuf>,
+    /// The directory the code is written to.
+    pub output: PathBuf,
+    pub layout: Layout,
+    /// The options of the generated code, with the type mappings of the file as its type mapper.
+    pub config: Config,
+}
+
+/// How the code of the modules is laid out in the output directory.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Layout {
+    /// A file per module, like `example_system.rs`.
+    #[default]
+    Files,
+    /// A directory per module, like `example_system/mod.rs`, with a file per top-level Rust module.
+    Tree,
+}
+
+/// An error reading a config file or generating the code it describes.
+#[derive(thiserror::Error, Debug)]
+pub enum ConfigFileError {
+    #[error("failed to read or write a file")]
+    Io(#[from] std::io::Error),
+
+    #[error("invalid TOML")]
+    Toml(#[from] toml_edit::TomlError),
+
+    #[error("unknown key: {0}")]
+    UnknownKey(String),
+
+    #[error("invalid value of {key}, expected {expected}")]
+    InvalidValue { key: String, expected: &'static str },
+
+    #[error("missing key: {0}")]
+    MissingKey(&'static str),
+
+    #[error("failed to parse module")]
+    Parser(#[from] yang_parser::ParserError),
+}
+
+impl ConfigFile {
+    /// Read the setup from the file at the path, like `yang-codegen.toml`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigFileError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)?;
+        Self::parse(&content, path.parent().unwrap_or(Path::new("")))
+    }
+
+    /// Parse the setup from the content of a file, with paths relative to the directory. Unknown keys are errors, so
+    /// misspelled options aren't silently ignored.
+    pub fn parse(content: &str, directory: &Path) -> Result<Self, ConfigFileError> {
+        let document: toml_edit::Document<String> = content.parse()?;
+        Self::from_table(document.as_table(), directory)
+    }
+
+    /// Read the setup from a table with the keys of a file, like the `[package.metadata.yang]` table of a Cargo
+    /// manifest, with paths relative to the directory.
+    pub fn from_table(keys: &Table, directory: &Path) -> Result<Self, ConfigFileError> {
+        let mut file = ConfigFile::default();
+        let mut output = None;
+
+        for (key, item) in keys {
+            match key {
+                "modules" => file.modules = paths(key, item, directory)?,
+                "search-paths" => file.search_paths = paths(key, item, directory)?,
+                "deviations" => file.deviations = paths(key, item, directory)?,
+                "features" => file.config.features = strings(key, item)?,
+                "output" => output = Some(directory.join(string(key, item)?)),
+                "layout" => {
+                    file.layout = match string(key, item)?.as_str() {
+                        "files" => Layout::Files,
+                        "tree" => Layout::Tree,
+                        _ => return Err(invalid(key, "\"files\" or \"tree\"")),
+                    }
+                }
+                "options" => parse_options(table(key, item)?, &mut file.config)?,
+                "type-mappings" => {
+                    let type_mappings = string_table(key, item)?;
+                    if !type_mappings.is_empty() {
+                        file.config.type_mapper = Some(Arc::new(type_mappings.into_iter().collect::<BTreeMap<_, _>>()));
+                    }
+                }
+                _ => return Err(ConfigFileError::UnknownKey(key.to_string())),
+            }
+        }
+        file.output = output.ok_or(ConfigFileError::MissingKey("output"))?;
+
+        Ok(file)
+    }
+
+    /// Generate the code of the modules, and write it to the output directory along with a `mod.rs` declaring the Rust
+    /// module of each YANG module.
+    pub fn generate(&self) -> Result<(), ConfigFileError> {
+        std::fs::create_dir_all(&self.output)?;
+        let options = ParseOptions {
+            search_paths: self.search_paths.clone(),
+            modules: self.deviations.clone(),
+            ..Default::default()
+        };
+
+        let mut module_names = Vec::new();
+        for path in &self.modules {
+            let YangModule::Module(module) = yang_parser::parse_with_options(path, &options)? else {
+                return Err(yang_parser::ParserError::from(yang_parser::ErrorKind::InvalidParserEntrypoint).into());
+            };
+            match self.layout {
+                Layout::Files => {
+                    let file_name = format!("{}.rs", crate::utils::sanitize_identifier(&module.name).unraw());
+                    let code = crate::generate_with_config(YangModule::Module(module.clone()), &self.config);
+                    std::fs::write(self.output.join(file_name), code)?;
+                }
+                Layout::Tree => {
+                    let tokens = crate::generate_tokens(YangModule::Module(module.clone()), &self.config);
+                    crate::write_module_tree(&self.output, &module, tokens)?;
+                }
+            }
+            module_names.push(module.name.to_string());
+        }
+        crate::write_mod_file(&self.output, &module_names)?;
+
+        Ok(())
+    }
+}
+
+fn parse_options(options: &Table, config: &mut Config) -> Result<(), ConfigFileError> {
+    for (key, item) in options {
+        let key_path = format!("options.{}", key);
+        match key {
+            "xml" => config.xml = boolean(&key_path, item)?,
+            "config-state" => config.config_state = boolean(&key_path, item)?,
+            "if-features" => config.if_features = boolean(&key_path, item)?,
+            "skip-obsolete" => config.skip_obsolete = boolean(&key_path, item)?,
+            "derives" => config.derives = strings(&key_path, item)?,
+            "rust-decimal" => config.rust_decimal = boolean(&key_path, item)?,
+            "list-maps" => config.list_maps = boolean(&key_path, item)?,
+            "share-groupings" => config.share_groupings = boolean(&key_path, item)?,
+            "no-std" => config.no_std = boolean(&key_path, item)?,
+            "non-exhaustive" => config.non_exhaustive = boolean(&key_path, item)?,
+            "checked-types" => config.checked_types = boolean(&key_path, item)?,
+            "proptest" => config.proptest = boolean(&key_path, item)?,
+            "include-paths" => config.include_paths = strings(&key_path, item)?,
+            "exclude-paths" => config.exclude_paths = strings(&key_path, item)?,
+            "external-modules" => config.external_modules = string_table(&key_path, item)?,
+            // Each type gets an attribute or a list of attributes.
+            "type-attributes" => {
+                let types = item.as_table_like().ok_or_else(|| invalid(&key_path, "a table"))?;
+                for (type_name, attributes) in types.iter() {
+                    let key_path = format!("{}.{}", key_path, type_name);
+                    let attributes = match attributes.as_str() {
+                        Some(attribute) => vec![attribute.to_string()],
+                        None => strings(&key_path, attributes)?,
+                    };
+                    config.type_attributes.extend(
+                        attributes
+                            .into_iter()
+                            .map(|attribute| (type_name.to_string(), attribute)),
+                    );
+                }
+            }
+            "naming" => parse_naming(&key_path, table(&key_path, item)?, &mut config.naming)?,
+            _ => return Err(ConfigFileError::UnknownKey(key_path)),
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_naming(path: &str, naming: &Table, config: &mut Naming) -> Result<(), ConfigFileError> {
+    for (key, item) in naming {
+        let key_path = format!("{}.{}", path, key);
+        match key {
+            "type-case" => {
+                config.type_case = match case_convention(&key_path, item)? {
+                    case @ (CaseConvention::Pascal | CaseConvention::ScreamingSnake) => case,
+                    _ => return Err(invalid(&key_path, "\"PascalCase\" or \"SCREAMING_SNAKE_CASE\"")),
+                }
+            }
+            "field-case" => config.field_case = case_convention(&key_path, item)?,
+            "strip-prefixes" => config.strip_prefixes = strings(&key_path, item)?,
+            "abbreviations" => config.abbreviations = strings(&key_path, item)?,
+            _ => return Err(ConfigFileError::UnknownKey(key_path)),
+        }
+    }
+
+    Ok(())
+}
+
+fn case_convention(key: &str, item: &Item) -> Result<CaseConvention, ConfigFileError> {
+    match string(key, item)?.as_str() {
+        "PascalCase" => Ok(CaseConvention::Pascal),
+        "camelCase" => Ok(CaseConvention::Camel),
+        "snake_case" => Ok(CaseConvention::Snake),
+        "SCREAMING_SNAKE_CASE" => Ok(CaseConvention::ScreamingSnake),
+        _ => Err(invalid(
+            key,
+            "\"PascalCase\", \"camelCase\", \"snake_case\" or \"SCREAMING_SNAKE_CASE\"",
+        )),
+    }
+}
+
+fn invalid(key: &str, expected: &'static str) -> ConfigFileError {
+    ConfigFileError::InvalidValue {
+        key: key.to_string(),
+        expected,
+    }
+}
+
+fn boolean(key: &str, item: &Item) -> Result<bool, ConfigFileError> {
+    item.as_bool().ok_or_else(|| invalid(key, "a boolean"))
+}
+
+fn string(key: &str, item: &Item) -> Result<String, ConfigFileError> {
+    item.as_str().map(String::from).ok_or_else(|| invalid(key, "a string"))
+}
+
+fn strings(key: &str, item: &Item) -> Result<Vec<String>, ConfigFileError> {
+    let array = item.as_array().ok_or_else(|| invalid(key, "an array of strings"))?;
+    array
+        .iter()
+        .map(|value| value.as_str().map(String::from))
+        .collect::<Option<_>>()
+        .ok_or_else(|| invalid(key, "an array of strings"))
+}
+
+fn paths(key: &str, item: &Item, directory: &Path) -> Result<Vec<PathBuf>, ConfigFileError> {
+    Ok(strings(key, item)?.iter().map(|path| directory.join(path)).collect())
+}
+
+fn table<'a>(key: &str, item: &'a Item) -> Result<&'a Table, ConfigFileError> {
+    item.as_table().ok_or_else(|| invalid(key, "a table"))
+}
+
+fn string_table(key: &str, item: &Item) -> Result<Vec<(String, String)>, ConfigFileError> {
+    let table = item.as_table_like().ok_or_else(|| invalid(key, "a table of strings"))?;
+    table
+        .iter()
+        .map(|(name, value)| Some((name.to_string(), value.as_str()?.to_string())))
+        .collect::<Option<_>>()
+        .ok_or_else(|| invalid(key, "a table of strings"))
+}
@@ -0,0 +1,842 @@
+//! The types generated for restricted types, enumerations, unions, bits and identityrefs.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use yang_parser::model::*;
+
+use crate::{runtime::generate_string_serde_impl, structs::generate_default_impl, utils, CodeGenerator};
+
+impl<'a> CodeGenerator<'a> {
+    // A checked newtype holds a value of the built-in type, which is checked against the restrictions of the type and
+    // the typedefs it's derived from whenever a value is made, including by parsing and deserialization.
+    pub(crate) fn generate_checked_type(
+        &mut self,
+        name: &str,
+        description: &Option<String>,
+        type_info: &TypeInfo,
+        default: Option<TokenStream>,
+        module_name: &str,
+    ) -> TokenStream {
+        let type_name = self.config.naming.type_identifier(name);
+        let doc = utils::format_doc(description, &utils::type_details(type_info));
+        let built_in = self.built_in_type(type_info, module_name);
+        let base_type = self.rust_type(&built_in);
+        let string_encoding = self.string_encoding(
+            &TypeInfo {
+                name: built_in.as_str().into(),
+                type_body: None,
+            },
+            "string",
+        );
+        let default_impl =
+            generate_default_impl(&type_name, Some(default.unwrap_or(quote! { Self(Default::default()) })));
+
+        let checks = self.value_checks(
+            type_info,
+            module_name,
+            [quote! { *value }, quote! { value }, quote! { value }],
+            |message| quote! { return Err(#message); },
+        );
+        let checks = match checks.is_empty() {
+            true => quote! {},
+            false => quote! {
+                {
+                    let value = &value;
+                    #checks
+                }
+            },
+        };
+
+        // Binary values are formatted as base64.
+        let rfc7951 = self.rfc7951_path();
+        let (display, from_str) = match built_in.as_str() {
+            "binary" => (
+                quote! { f.write_str(&#rfc7951::encode(&self.0)) },
+                quote! { #rfc7951::decode(s).and_then(Self::new) },
+            ),
+            _ => (
+                quote! { std::fmt::Display::fmt(&self.0, f) },
+                quote! { s.parse::<#base_type>().map_err(|error| error.to_string()).and_then(Self::new) },
+            ),
+        };
+
+        // Random values satisfy the restrictions, so they are valid values of the type.
+        let arbitrary_impl = match self.proptest() {
+            true => {
+                let value = self.built_in_strategy(type_info, module_name);
+                self.arbitrary_impl(&type_name, quote! { #value.prop_map(Self) })
+            }
+            false => quote! {},
+        };
+
+        let (ordering, derived): (TokenStream, &[&str]) = match self.orderable(type_info, module_name) {
+            true => (
+                quote! { PartialEq, Eq, PartialOrd, Ord, Hash, },
+                &["PartialEq", "Eq", "PartialOrd", "Ord", "Hash"],
+            ),
+            false => (quote! {}, &[]),
+        };
+        let attributes = self.type_attributes(&type_name, derived);
+        quote! {
+            #[doc = #doc]
+            #[derive(Debug, Clone, #ordering serde::Serialize)]
+            #attributes
+            #[serde(transparent)]
+            pub struct #type_name(#string_encoding #base_type);
+
+            impl #type_name {
+                /// Make a value of the type, or fail with the message of the first restriction of the type the value
+                /// violates.
+                pub fn new(value: #base_type) -> Result<Self, String> {
+                    #checks
+                    Ok(Self(value))
+                }
+
+                /// The value of the type.
+                pub fn get(&self) -> &#base_type {
+                    &self.0
+                }
+
+                /// Take the value of the type.
+                pub fn into_inner(self) -> #base_type {
+                    self.0
+                }
+            }
+
+            impl TryFrom<#base_type> for #type_name {
+                type Error = String;
+
+                fn try_from(value: #base_type) -> Result<Self, Self::Error> {
+                    Self::new(value)
+                }
+            }
+
+            impl std::fmt::Display for #type_name {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    #display
+                }
+            }
+
+            impl std::str::FromStr for #type_name {
+                type Err = String;
+
+                fn from_str(s: &str) -> Result<Self, Self::Err> {
+                    #from_str
+                }
+            }
+
+            impl<'de> serde::Deserialize<'de> for #type_name {
+                fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    #[derive(serde::Deserialize)]
+                    #[serde(transparent)]
+                    struct Value(#string_encoding #base_type);
+
+                    let Value(value) = serde::Deserialize::deserialize(deserializer)?;
+                    Self::new(value).map_err(serde::de::Error::custom)
+                }
+            }
+
+            #default_impl
+
+            #arbitrary_impl
+        }
+    }
+
+    // Enum values without an explicit value are assigned one greater than the current highest value, starting at zero.
+    // Enums taking unknown values hold them as strings, so they can't be represented by their values.
+    pub(crate) fn generate_enumeration(
+        &self,
+        name: &str,
+        description: &Option<String>,
+        enums: &[EnumValue],
+        default: Option<TokenStream>,
+    ) -> TokenStream {
+        let enum_name = self.config.naming.type_identifier(name);
+        let doc = utils::format_docstring(description);
+        let variant_names = utils::enum_variants(enums, self.config);
+        let default = default.or_else(|| {
+            let first_variant = variant_names.first()?;
+            Some(quote! { Self::#first_variant })
+        });
+        let default_impl = generate_default_impl(&enum_name, default);
+
+        // Enums without a value statement get the value after the highest value so far.
+        let mut next_value = 0;
+        let values: Vec<proc_macro2::Literal> = enums
+            .iter()
+            .map(|enum_value| {
+                let value = enum_value.value.unwrap_or(next_value);
+                next_value = next_value.max(value + 1);
+                proc_macro2::Literal::i64_unsuffixed(value)
+            })
+            .collect();
+        let unknown = self.config.non_exhaustive && !self.union_member;
+        let variants = enums
+            .iter()
+            .zip(&variant_names)
+            .zip(&values)
+            .map(|((enum_value, variant_name), value)| {
+                let doc = utils::format_doc(&enum_value.description, &[("Reference", enum_value.reference.clone())]);
+                let yang_name = &enum_value.name;
+                match unknown {
+                    true => quote! {
+                        #[doc = #doc]
+                        #variant_name,
+                    },
+                    false => quote! {
+                        #[doc = #doc]
+                        #[serde(rename = #yang_name)]
+                        #variant_name = #value,
+                    },
+                }
+            });
+        let from_str_arms = enums.iter().zip(&variant_names).map(|(enum_value, variant_name)| {
+            let yang_name = &enum_value.name;
+            quote! { #yang_name => Ok(Self::#variant_name), }
+        });
+        let to_string_arms = enums.iter().zip(&variant_names).map(|(enum_value, variant_name)| {
+            let yang_name = &enum_value.name;
+            quote! { Self::#variant_name => f.write_str(#yang_name), }
+        });
+        let names = enums.iter().zip(&variant_names).map(|(enum_value, variant_name)| {
+            let yang_name = &enum_value.name;
+            quote! { (#yang_name, Self::#variant_name) }
+        });
+        let from_value_arms = values.iter().zip(&variant_names).map(|(value, variant_name)| {
+            quote! { #value => Some(Self::#variant_name), }
+        });
+        let arbitrary_impl = self.arbitrary_impl(
+            &enum_name,
+            quote! { proptest::sample::select(Self::NAMES).prop_map(|(_, value)| value) },
+        );
+
+        if unknown {
+            let unknown_name = self.config.naming.type_identifier("unknown");
+            let value_arms = values.iter().zip(&variant_names).map(|(value, variant_name)| {
+                quote! { Self::#variant_name => Some(#value), }
+            });
+            let derived = ["PartialEq", "Eq", "PartialOrd", "Ord", "Hash"];
+            let attributes = self.type_attributes(&enum_name, &derived);
+            let serde_impl = generate_string_serde_impl(&enum_name);
+            return quote! {
+                #[doc = #doc]
+                #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+                #attributes
+                #[non_exhaustive]
+                pub enum #enum_name {
+                    #(#variants)*
+                    /// A value unknown to the revision of the module the code was generated from.
+                    #unknown_name(String),
+                }
+
+                impl #enum_name {
+                    /// YANG names of the enums, in the order they are defined.
+                    pub const NAMES: &'static [(&'static str, Self)] = &[#(#names),*];
+
+                    /// The YANG value of the enum, if it's known.
+                    pub fn value(&self) -> Option<i32> {
+                        match self {
+                            #(#value_arms)*
+                            Self::#unknown_name(_) => None,
+                        }
+                    }
+
+                    /// The enum with the YANG value, if there is one.
+                    pub fn from_value(value: i32) -> Option<Self> {
+                        match value {
+                            #(#from_value_arms)*
+                            _ => None,
+                        }
+                    }
+                }
+
+                impl std::fmt::Display for #enum_name {
+                    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                        match self {
+                            #(#to_string_arms)*
+                            Self::#unknown_name(value) => f.write_str(value),
+                        }
+                    }
+                }
+
+                impl std::str::FromStr for #enum_name {
+                    type Err = String;
+
+                    fn from_str(s: &str) -> Result<Self, Self::Err> {
+                        match s {
+                            #(#from_str_arms)*
+                            _ => Ok(Self::#unknown_name(s.to_string())),
+                        }
+                    }
+                }
+
+                #default_impl
+
+                #serde_impl
+
+                #arbitrary_impl
+            };
+        }
+
+        let derived = ["Copy", "PartialEq", "Eq", "PartialOrd", "Ord", "Hash"];
+        let attributes = self.type_attributes(&enum_name, &derived);
+        quote! {
+            #[doc = #doc]
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize)]
+            #attributes
+            #[repr(i32)]
+            pub enum #enum_name {
+                #(#variants)*
+            }
+
+            impl #enum_name {
+                /// YANG names of the enums, in the order they are defined.
+                pub const NAMES: &'static [(&'static str, Self)] = &[#(#names),*];
+
+                /// The YANG value of the enum.
+                pub const fn value(self) -> i32 {
+                    self as i32
+                }
+
+                /// The enum with the YANG value, if there is one.
+                pub fn from_value(value: i32) -> Option<Self> {
+                    match value {
+                        #(#from_value_arms)*
+                        _ => None,
+                    }
+                }
+            }
+
+            impl std::fmt::Display for #enum_name {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    match *self {
+                        #(#to_string_arms)*
+                    }
+                }
+            }
+
+            impl std::str::FromStr for #enum_name {
+                type Err = String;
+
+                fn from_str(s: &str) -> Result<Self, Self::Err> {
+                    match s {
+                        #(#from_str_arms)*
+                        _ => Err(format!("unknown enum: {}", s)),
+                    }
+                }
+            }
+
+            #default_impl
+
+            #arbitrary_impl
+        }
+    }
+
+    // A union becomes an enum with one variant per member type, named after the member type. Member types which need
+    // a generated type of their own, like enumerations, are generated next to the union and named after both.
+    // Parsing a value tries the member types in order and picks the first one the value can be parsed as, like
+    // RFC 7950 section 9.12 describes. Restrictions of the member types, like ranges and patterns, are not checked.
+    pub(crate) fn generate_union(
+        &mut self,
+        name: &str,
+        description: &Option<String>,
+        types: &[TypeInfo],
+        default: Option<TokenStream>,
+        module_name: &str,
+    ) -> TokenStream {
+        let enum_name = self.config.naming.type_identifier(name);
+        let doc = utils::format_docstring(description);
+        let types: Vec<&TypeInfo> = types.iter().map(utils::resolved_type).collect();
+
+        // Member types used more than once get their position in the union as part of their name.
+        let member_names: Vec<String> = types
+            .iter()
+            .enumerate()
+            .map(|(idx, type_info)| {
+                let member_name = type_info.name.rsplit(':').next().unwrap_or(&type_info.name);
+                match types.iter().filter(|other| other.name == type_info.name).count() {
+                    1 => member_name.to_string(),
+                    _ => format!("{}-{}", member_name, idx + 1),
+                }
+            })
+            .collect();
+        let member_names = self.config.naming.unique_names(member_names.iter().map(String::as_str));
+
+        let mut member_types = Vec::new();
+        let mut variants = Vec::new();
+        let mut to_string_arms = Vec::new();
+        let mut from_str_checks = Vec::new();
+        let mut arbitrary_members = Vec::new();
+        let mut matches_any_value = false;
+        for (type_info, member_name) in types.iter().zip(&member_names) {
+            let variant_name = self.config.naming.type_identifier(member_name);
+            let (variant_type, type_def_path) = match self.has_generated_type(type_info, module_name) {
+                true => {
+                    let member_type_name = format!("{}-{}", name, member_name);
+                    let union_member = std::mem::replace(&mut self.union_member, true);
+                    member_types.extend(self.generate_leaf_type(
+                        &member_type_name,
+                        type_info,
+                        &None,
+                        None,
+                        module_name,
+                    ));
+                    self.union_member = union_member;
+                    let member_type = self.config.naming.type_identifier(&member_type_name);
+                    (quote! { #member_type }, None)
+                }
+                false => match self.find_type_def(&type_info.name, module_name) {
+                    Some((type_module, path, type_def)) => {
+                        let type_def_path = self.type_def_path(type_module, path, type_def);
+                        (type_def_path.clone(), Some(type_def_path))
+                    }
+                    None => (self.named_type(&type_info.name, module_name), None),
+                },
+            };
+            let string_encoding = self.string_encoding(type_info, "string");
+            variants.push(quote! { #string_encoding #variant_name(#variant_type), });
+            if self.proptest() && self.has_values(type_info, module_name) {
+                let value = self.arbitrary_value(&variant_type, type_info, module_name);
+                arbitrary_members.push(quote! { #value.prop_map(Self::#variant_name).boxed() });
+            }
+            let rfc7951 = self.rfc7951_path();
+            // Values of checked newtypes, and newtypes of them, are parsed and formatted by the newtypes.
+            let base_is_built_in = self.base_is_built_in(type_info, module_name);
+            to_string_arms.push(match self.built_in_type(type_info, module_name).as_str() {
+                "empty" => quote! { Self::#variant_name(_) => Ok(()), },
+                "binary" if type_def_path.is_none() && base_is_built_in => {
+                    quote! { Self::#variant_name(value) => f.write_str(&#rfc7951::encode(value)), }
+                }
+                _ => quote! { Self::#variant_name(value) => std::fmt::Display::fmt(value, f), },
+            });
+
+            // Empty values and strings can't be parsed like the other types, and any value is a valid string, so
+            // member types after a string are never tried. The same goes for typedefs of enumerations and identityrefs
+            // taking unknown values.
+            if matches_any_value {
+                continue;
+            }
+            let wrap = |value: TokenStream| match &type_def_path {
+                Some(type_def_path) => quote! { #type_def_path(#value) },
+                None => value,
+            };
+            match self.built_in_type(type_info, module_name).as_str() {
+                "empty" => {
+                    let value = wrap(quote! { #rfc7951::Empty });
+                    from_str_checks.push(quote! {
+                        if s.is_empty() {
+                            return Ok(Self::#variant_name(#value));
+                        }
+                    });
+                }
+                "binary" if type_def_path.is_none() && base_is_built_in => from_str_checks.push(quote! {
+                    if let Ok(value) = #rfc7951::decode(s) {
+                        return Ok(Self::#variant_name(value));
+                    }
+                }),
+                "string" if base_is_built_in => {
+                    let value = wrap(quote! { s.to_string() });
+                    from_str_checks.push(quote! { Ok(Self::#variant_name(#value)) });
+                    matches_any_value = true;
+                }
+                built_in_type
+                    if self.config.non_exhaustive
+                        && type_def_path.is_some()
+                        && matches!(built_in_type, "enumeration" | "identityref") =>
+                {
+                    from_str_checks.push(quote! { s.parse().map(Self::#variant_name) });
+                    matches_any_value = true;
+                }
+                _ => from_str_checks.push(quote! {
+                    if let Ok(value) = s.parse() {
+                        return Ok(Self::#variant_name(value));
+                    }
+                }),
+            }
+        }
+        let default = default.or_else(|| {
+            let first_variant = self.config.naming.type_identifier(member_names.first()?);
+            Some(quote! { Self::#first_variant(Default::default()) })
+        });
+        let default_impl = generate_default_impl(&enum_name, default);
+
+        let no_match = match matches_any_value {
+            true => quote! {},
+            false => quote! { Err(format!("no member type of the union matches: {}", s)) },
+        };
+        let arbitrary_impl = self.arbitrary_impl(
+            &enum_name,
+            quote! { proptest::strategy::Union::new([#(#arbitrary_members),*]) },
+        );
+
+        // Union values are encoded like the value of the member type in RFC 7951, so they are untagged.
+        let attributes = self.type_attributes(&enum_name, &[]);
+        quote! {
+            #[doc = #doc]
+            #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+            #attributes
+            #[serde(untagged)]
+            pub enum #enum_name {
+                #(#variants)*
+            }
+
+            impl std::fmt::Display for #enum_name {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    match self {
+                        #(#to_string_arms)*
+                    }
+                }
+            }
+
+            impl std::str::FromStr for #enum_name {
+                type Err = String;
+
+                fn from_str(s: &str) -> Result<Self, Self::Err> {
+                    #(#from_str_checks)*
+                    #no_match
+                }
+            }
+
+            #default_impl
+
+            #arbitrary_impl
+
+            #(#member_types)*
+        }
+    }
+
+    // Bits are generated as bitflags, with one flag per bit at its declared position. Bits without an explicit position
+    // are assigned one greater than the current highest position. The canonical string form is the names of the set
+    // bits separated by spaces, ordered by position.
+    pub(crate) fn generate_bits(
+        &self,
+        name: &str,
+        description: &Option<String>,
+        bits: &[Bit],
+        default: Option<TokenStream>,
+    ) -> TokenStream {
+        let type_name = self.config.naming.type_identifier(name);
+        let doc = utils::format_docstring(description);
+        let default_impl = generate_default_impl(&type_name, Some(default.unwrap_or(quote! { Self::empty() })));
+        let serde_impl = generate_string_serde_impl(&type_name);
+
+        let mut next_position = 0;
+        let mut positioned_bits: Vec<(i64, &Bit, syn::Ident)> = bits
+            .iter()
+            .zip(utils::bit_flags(bits))
+            .map(|(bit, flag_name)| {
+                let position = bit.position.unwrap_or(next_position);
+                next_position = next_position.max(position + 1);
+                (position, bit, flag_name)
+            })
+            .collect();
+        positioned_bits.sort_by_key(|(position, _, _)| *position);
+
+        let backing_type = match positioned_bits.last().map(|(position, _, _)| *position).unwrap_or(0) {
+            0..=31 => quote! { u32 },
+            32..=63 => quote! { u64 },
+            64..=127 => quote! { u128 },
+            position => panic!(
+                "bit position {} in {} is too large to be generated as bitflags",
+                position, name
+            ),
+        };
+
+        let flags = positioned_bits.iter().map(|(position, bit, flag_name)| {
+            let position = proc_macro2::Literal::i64_unsuffixed(*position);
+            let doc = utils::format_doc(&bit.description, &[("Reference", bit.reference.clone())]);
+            quote! {
+                #[doc = #doc]
+                const #flag_name = 1 << #position;
+            }
+        });
+        let names = positioned_bits.iter().map(|(_, bit, flag_name)| {
+            let yang_name = &bit.name;
+            quote! { (#yang_name, Self::#flag_name) }
+        });
+        let positions = positioned_bits.iter().map(|(position, bit, _)| {
+            let yang_name = &bit.name;
+            let position = proc_macro2::Literal::i64_unsuffixed(*position);
+            quote! { (#yang_name, #position) }
+        });
+
+        let arbitrary_impl = self.arbitrary_impl(
+            &type_name,
+            quote! {
+                proptest::sample::subsequence(Self::NAMES, 0..=Self::NAMES.len())
+                    .prop_map(|bits| bits.into_iter().fold(Self::empty(), |flags, (_, flag)| flags | flag))
+            },
+        );
+
+        let derived = ["Copy", "PartialEq", "Eq", "PartialOrd", "Ord", "Hash"];
+        let attributes = self.type_attributes(&type_name, &derived);
+        quote! {
+            bitflags::bitflags! {
+                #[doc = #doc]
+                #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+                #attributes
+                pub struct #type_name: #backing_type {
+                    #(#flags)*
+                }
+            }
+
+            impl #type_name {
+                /// YANG names of the bits, ordered by position.
+                pub const NAMES: &'static [(&'static str, Self)] = &[#(#names),*];
+
+                /// YANG positions of the bits, by their YANG names, ordered by position.
+                pub const POSITIONS: &'static [(&'static str, u32)] = &[#(#positions),*];
+            }
+
+            impl std::fmt::Display for #type_name {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    let names: Vec<&str> = Self::NAMES
+                        .iter()
+                        .filter(|(_, flag)| self.contains(*flag))
+                        .map(|(name, _)| *name)
+                        .collect();
+                    write!(f, "{}", names.join(" "))
+                }
+            }
+
+            impl std::str::FromStr for #type_name {
+                type Err = String;
+
+                fn from_str(s: &str) -> Result<Self, Self::Err> {
+                    let mut flags = Self::empty();
+                    for name in s.split_whitespace() {
+                        match Self::NAMES.iter().find(|(bit, _)| *bit == name) {
+                            Some((_, flag)) => flags.insert(*flag),
+                            None => return Err(format!("unknown bit: {}", name)),
+                        }
+                    }
+                    Ok(flags)
+                }
+            }
+
+            #default_impl
+
+            #serde_impl
+
+            #arbitrary_impl
+        }
+    }
+
+    // An identityref becomes an enum of every identity derived from all of its bases, in this module and the imported
+    // modules. Other modules may derive new identities from the bases, so the enum is non-exhaustive. The string form
+    // of an identity is its name qualified with the name of the module defining it, like "module:identity".
+    pub(crate) fn generate_identityref(
+        &self,
+        name: &str,
+        description: &Option<String>,
+        bases: &[String],
+        default: Option<TokenStream>,
+        module_name: &str,
+    ) -> TokenStream {
+        let enum_name = self.config.naming.type_identifier(name);
+        let doc = utils::format_docstring(description);
+        let identities = self.derived_identities(bases, module_name);
+
+        // Identities with the same name in different modules get the module name in their variant name.
+        let names: Vec<String> = identities
+            .iter()
+            .map(|(module_name, identity)| {
+                let duplicate = identities
+                    .iter()
+                    .any(|(other_module, other)| other.name == identity.name && other_module != module_name);
+                match duplicate {
+                    true => format!("{}-{}", module_name, identity.name),
+                    false => identity.name.clone(),
+                }
+            })
+            .collect();
+        let variant_names = utils::variants(names.iter().map(String::as_str), self.config);
+
+        let default = default.or_else(|| {
+            let first_variant = variant_names.first()?;
+            Some(quote! { Self::#first_variant })
+        });
+        let default_impl = generate_default_impl(&enum_name, default);
+        let serde_impl = generate_string_serde_impl(&enum_name);
+
+        let variants = identities
+            .iter()
+            .zip(&variant_names)
+            .map(|((_, identity), variant_name)| {
+                let doc = utils::format_doc(&identity.description, &[("Reference", identity.reference.clone())]);
+                quote! {
+                    #[doc = #doc]
+                    #variant_name,
+                }
+            });
+        let to_string_arms = identities
+            .iter()
+            .zip(&variant_names)
+            .map(|((module_name, identity), variant_name)| {
+                let qualified_name = format!("{}:{}", module_name, identity.name);
+                quote! { Self::#variant_name => f.write_str(#qualified_name), }
+            });
+        let from_str_arms = identities
+            .iter()
+            .zip(&variant_names)
+            .map(|((module_name, identity), variant_name)| {
+                let qualified_name = format!("{}:{}", module_name, identity.name);
+                quote! { #qualified_name => Ok(Self::#variant_name), }
+            });
+
+        // Unknown identities are held as their qualified names, which aren't Copy.
+        let unknown_name = self.config.naming.type_identifier("unknown");
+        let (derives, derived, unknown_variant, unknown_to_string, unknown_from_str) =
+            match self.config.non_exhaustive && !self.union_member {
+                true => (
+                    quote! { #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)] },
+                    &["PartialEq", "Eq", "PartialOrd", "Ord", "Hash"][..],
+                    quote! {
+                        /// An identity unknown to the revisions of the modules the code was generated from, with its
+                        /// qualified name.
+                        #unknown_name(String),
+                    },
+                    quote! { Self::#unknown_name(ref name) => f.write_str(name), },
+                    quote! { _ => Ok(Self::#unknown_name(s.to_string())), },
+                ),
+                false => (
+                    quote! { #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)] },
+                    &["Copy", "PartialEq", "Eq", "PartialOrd", "Ord", "Hash"][..],
+                    quote! {},
+                    quote! {},
+                    quote! { _ => Err(format!("unknown identity: {}", s)), },
+                ),
+            };
+        // Identityrefs without identities have no values, so their strategy rejects every value.
+        let arbitrary_impl = match variant_names.is_empty() {
+            true => self.arbitrary_impl(
+                &enum_name,
+                quote! { proptest::strategy::Just(()).prop_filter_map("identities", |_| None) },
+            ),
+            false => self.arbitrary_impl(
+                &enum_name,
+                quote! { proptest::sample::select(vec![#(Self::#variant_names),*]) },
+            ),
+        };
+        // Identities are written in XML with the prefix of their module, which the element declares with the namespace
+        // of the module.
+        let namespace_impl = match self.xml() {
+            true => {
+                let module: &'a Module = self.module;
+                let namespace_arms = identities
+                    .iter()
+                    .zip(&variant_names)
+                    .map(|((module_name, _), variant_name)| {
+                        let namespace = match module_name == module.name.as_str() {
+                            true => Some(&module.namespace),
+                            false => module.imported_namespaces.get(module_name.as_str()),
+                        };
+                        match namespace {
+                            Some(namespace) => quote! { Self::#variant_name => Some(#namespace), },
+                            None => quote! { Self::#variant_name => None, },
+                        }
+                    });
+                let unknown_namespace = match self.config.non_exhaustive && !self.union_member {
+                    true => quote! { Self::#unknown_name(_) => None, },
+                    false => quote! {},
+                };
+                quote! {
+                    impl #enum_name {
+                        /// The XML namespace of the module defining the identity, if it's known.
+                        pub fn namespace(&self) -> Option<&'static str> {
+                            match *self {
+                                #(#namespace_arms)*
+                                #unknown_namespace
+                            }
+                        }
+                    }
+                }
+            }
+            false => quote! {},
+        };
+        let attributes = self.type_attributes(&enum_name, derived);
+        quote! {
+            #[doc = #doc]
+            #derives
+            #attributes
+            #[non_exhaustive]
+            pub enum #enum_name {
+                #(#variants)*
+                #unknown_variant
+            }
+
+            #namespace_impl
+
+            impl std::fmt::Display for #enum_name {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    match *self {
+                        #(#to_string_arms)*
+                        #unknown_to_string
+                    }
+                }
+            }
+
+            impl std::str::FromStr for #enum_name {
+                type Err = String;
+
+                fn from_str(s: &str) -> Result<Self, Self::Err> {
+                    match s {
+                        #(#from_str_arms)*
+                        #unknown_from_str
+                    }
+                }
+            }
+
+            #default_impl
+
+            #serde_impl
+
+            #arbitrary_impl
+        }
+    }
+
+    // The identities derived from all the bases, in this module and the imported modules, with the names of the modules
+    // defining them, ordered by module and name.
+    pub(crate) fn derived_identities(&self, bases: &[String], module_name: &str) -> Vec<(String, &'a Identity)> {
+        let bases: Vec<(String, String)> = bases
+            .iter()
+            .map(|base| {
+                let (module_name, base) = self.resolve_prefix(base, module_name);
+                (module_name, base.to_string())
+            })
+            .collect();
+
+        let module: &'a Module = self.module;
+        let mut identities: Vec<(String, &'a Identity)> = std::iter::once(module.name.as_str())
+            .chain(module.imported_modules.keys().map(Symbol::as_str))
+            .flat_map(|module_name| {
+                self.reference_nodes(module_name)
+                    .into_iter()
+                    .flat_map(|reference_nodes| reference_nodes.identities.values())
+                    .map(move |identity| (module_name.to_string(), identity))
+            })
+            .filter(|(module_name, identity)| {
+                bases
+                    .iter()
+                    .all(|base| self.is_derived_from(module_name, identity, base))
+            })
+            .collect();
+        identities.sort_by(|(a_module, a), (b_module, b)| (a_module, &a.name).cmp(&(b_module, &b.name)));
+        identities
+    }
+
+    // Check if the identity, defined in the given module, is derived from the base identity, directly or through
+    // other identities. The base identity is given as its module name and identity name.
+    pub(crate) fn is_derived_from(&self, module_name: &str, identity: &Identity, base: &(String, String)) -> bool {
+        identity.bases.iter().any(|identity_base| {
+            let (base_module, base_name) = self.resolve_prefix(identity_base, module_name);
+            if (&base_module, base_name) == (&base.0, base.1.as_str()) {
+                return true;
+            }
+
+            self.reference_nodes(&base_module)
+                .and_then(|reference_nodes| reference_nodes.identities.get(&format!("/{}", base_name)))
+                .is_some_and(|base_identity| self.is_derived_from(&base_module, base_identity, base))
+        })
+    }
+}
@@ -0,0 +1,712 @@
+//! The fields of the structs of data nodes, generated from leaves, leaf-lists, anydata and the children of nodes.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use yang_parser::model::*;
+
+use crate::{
+    structs::{deprecated, entry_counts, in_namespace},
+    utils, CodeGenerator, Field, Warning,
+};
+
+impl<'a> CodeGenerator<'a> {
+    // Fields referring to generated types use paths relative to the parent's child module, named after the parent.
+    // The parent is None for the top-level nodes of the module, whose types are not in a child module and whose
+    // names are qualified with the module name, as described in RFC 7951. Nodes added by augments of other modules
+    // are qualified with the name of the other module, and their XML elements declare its namespace.
+    // Keys are the names of the key leaves when the parent is a list, as key leaves are always present.
+    pub(crate) fn generate_fields(
+        &mut self,
+        parent_name: Option<&str>,
+        data_defs: &[DataDef],
+        keys: &[String],
+    ) -> Vec<Field> {
+        let module_path = match parent_name {
+            Some(parent_name) => {
+                let module_name = utils::sanitize_identifier(&self.node_name(parent_name));
+                quote! { #module_name:: }
+            }
+            None => quote! {},
+        };
+        let module: &'a Module = self.module;
+        let node_module = self.node_module.clone();
+        let serde_name = |name: &str, child: &DataDef| match parent_name {
+            Some(_) => utils::qualified_name(name, utils::node_module(child), &node_module),
+            None => format!("{}:{}", module.name, name),
+        };
+        let xml = self.xml_path();
+        let validation = self.validation_path();
+        let data_defs = self.generated_data_defs(data_defs);
+        let names = self.level_names(&data_defs);
+        self.push_names(names.iter().map(String::as_str));
+
+        let fields: Vec<Option<Field>> = data_defs
+            .iter()
+            .map(|child| match child {
+                DataDef::Leaf(leaf) => {
+                    let doc = self.node_doc(child);
+                    Some(self.generate_leaf(leaf, &doc, &module_path, &serde_name(&leaf.name, child), keys.contains(&leaf.name)))
+                }
+                DataDef::LeafList(leaf_list) => {
+                    let doc = self.node_doc(child);
+                    Some(self.generate_leaf_list(leaf_list, &doc, &module_path, &serde_name(&leaf_list.name, child)))
+                }
+                DataDef::Container(container) => {
+                    let field_name = self.config.naming.field_identifier(&self.node_name(&container.name));
+                    let field_type = self.config.naming.type_identifier(&self.node_name(&container.name));
+                    let doc = self.node_doc(child);
+                    let serde_name = serde_name(&container.name, child);
+                    let element_name = &container.name;
+
+                    // Presence containers mean something by existing, so they are optional, while other containers
+                    // are always there.
+                    let presence = container.meta.presence.is_some();
+                    let (node_type, serde_attributes, default, xml_write, read_node, validation) = match presence {
+                        true => (
+                            quote! { Option<#module_path #field_type> },
+                            quote! { #[serde(rename = #serde_name, skip_serializing_if = "Option::is_none")] },
+                            quote! { None },
+                            quote! {
+                                if let Some(node) = &self.#field_name {
+                                    #xml::write_node(writer, #element_name, namespace, node)?;
+                                }
+                            },
+                            quote! { Some(#xml::read_node(reader, empty)?) },
+                            quote! {
+                                if let Some(node) = &self.#field_name {
+                                    #validation::Validate::validate_at(node, &format!("{}/{}", path, #element_name), errors);
+                                }
+                            },
+                        ),
+                        false => (
+                            quote! { #module_path #field_type },
+                            quote! { #[serde(rename = #serde_name)] },
+                            quote! { Default::default() },
+                            quote! { #xml::write_node(writer, #element_name, namespace, &self.#field_name)?; },
+                            quote! { #xml::read_node(reader, empty)? },
+                            quote! {
+                                #validation::Validate::validate_at(
+                                    &self.#field_name,
+                                    &format!("{}/{}", path, #element_name),
+                                    errors,
+                                );
+                            },
+                        ),
+                    };
+
+                    let arbitrary = match presence {
+                        true => quote! { proptest::option::of(proptest::arbitrary::any::<#module_path #field_type>()) },
+                        false => quote! { proptest::arbitrary::any::<#module_path #field_type>() },
+                    };
+
+                    Some(Field {
+                        name: field_name.clone(),
+                        cfg: quote! {},
+                        deprecated: quote! {},
+                        value_type: quote! { #module_path #field_type },
+                        optional: presence,
+                        mandatory: None,
+                        definition: quote! {
+                            #[doc = #doc]
+                            #serde_attributes
+                            pub #field_name: #node_type,
+                        },
+                        default: quote! { #field_name: #default, },
+                        xml_write,
+                        xml_read: quote! {
+                            if #xml::is_element(start, #element_name) {
+                                self.#field_name = #read_node;
+                                return Ok(true);
+                            }
+                        },
+                        xml_clear_defaults: quote! {},
+                        xml_restore_defaults: quote! {},
+                        validation,
+                        arbitrary,
+                    })
+                }
+                DataDef::List(list) => {
+                    let field_name = self.config.naming.field_identifier(&self.node_name(&list.name));
+                    let entry_type = self.config.naming.type_identifier(&self.node_name(&list.name));
+                    let doc = self.node_doc(child);
+                    let serde_name = serde_name(&list.name, child);
+                    let element_name = &list.name;
+                    let elements_check =
+                        self.elements_check(&list.name, &field_name, list.min_elements, &list.max_elements);
+
+                    // Entries of lists with keys are generated without duplicate keys, and put in the map by their key for
+                    // lists generated as maps.
+                    let arbitrary = self.arbitrary_path();
+                    let rfc7951 = self.rfc7951_path();
+                    let entry = quote! { proptest::arbitrary::any::<#module_path #entry_type>() };
+                    let (min, max) = entry_counts(list.min_elements, &list.max_elements);
+                    let arbitrary = match (utils::list_keys(list).is_empty(), self.map_kind(list)) {
+                        (true, _) => quote! { proptest::collection::vec(#entry, #min..=#max) },
+                        (false, None) => quote! {
+                            #arbitrary::unique(#entry, #min, #max, <#module_path #entry_type as #arbitrary::ListKey>::key_values)
+                        },
+                        (false, Some(_)) => quote! {
+                            #arbitrary::unique(#entry, #min, #max, <#module_path #entry_type as #arbitrary::ListKey>::key_values)
+                                .prop_map(|entries| {
+                                    entries
+                                        .into_iter()
+                                        .map(|entry| (#rfc7951::ListEntry::key(&entry), entry))
+                                        .collect()
+                                })
+                        },
+                    };
+
+                    // Lists generated as maps are encoded as arrays of their entries, like other lists.
+                    let (field_type, serde_attributes, entries, add_entry) = match self.map_kind(list) {
+                        Some(map_type) => {
+                            let key_type = self.config.naming.type_identifier(&self.node_name(&utils::key_name(&list.name)));
+                            let rfc7951 = self.rfc7951_path();
+                            let helper = match list.ordered_by {
+                                Some(OrderedBy::User) => "index_map",
+                                _ => "btree_map",
+                            };
+                            self.uses_string_encoding = true;
+                            self.uses_index_map |= helper == "index_map";
+                            let helper_path = format!("{}rfc7951::{}", "super::".repeat(self.depth), helper);
+                            let is_empty = format!("{}::is_empty", map_type);
+                            let map_type: TokenStream = map_type.parse().expect("map type to be a valid path");
+                            (
+                                quote! { #map_type<#module_path #key_type, #module_path #entry_type> },
+                                quote! {
+                                    #[serde(rename = #serde_name, skip_serializing_if = #is_empty)]
+                                    #[serde(with = #helper_path)]
+                                },
+                                quote! { self.#field_name.values() },
+                                quote! {
+                                    let entry: #module_path #entry_type = #xml::read_node(reader, empty)?;
+                                    self.#field_name.insert(#rfc7951::ListEntry::key(&entry), entry);
+                                },
+                            )
+                        }
+                        None => (
+                            quote! { Vec<#module_path #entry_type> },
+                            quote! { #[serde(rename = #serde_name, skip_serializing_if = "Vec::is_empty")] },
+                            quote! { self.#field_name.iter() },
+                            quote! { self.#field_name.push(#xml::read_node(reader, empty)?); },
+                        ),
+                    };
+
+                    Some(Field {
+                        name: field_name.clone(),
+                        cfg: quote! {},
+                        deprecated: quote! {},
+                        value_type: field_type.clone(),
+                        optional: false,
+                        mandatory: None,
+                        definition: quote! {
+                            #[doc = #doc]
+                            #serde_attributes
+                            pub #field_name: #field_type,
+                        },
+                        default: quote! { #field_name: Default::default(), },
+                        xml_write: quote! {
+                            for entry in #entries {
+                                #xml::write_node(writer, #element_name, namespace, entry)?;
+                            }
+                        },
+                        xml_read: quote! {
+                            if #xml::is_element(start, #element_name) {
+                                #add_entry
+                                return Ok(true);
+                            }
+                        },
+                        xml_clear_defaults: quote! {},
+                        xml_restore_defaults: quote! {},
+                        validation: quote! {
+                            for (idx, entry) in #entries.enumerate() {
+                                #validation::Validate::validate_at(
+                                    entry,
+                                    &format!("{}/{}[{}]", path, #element_name, idx + 1),
+                                    errors,
+                                );
+                            }
+                            #elements_check
+                        },
+                        arbitrary,
+                    })
+                }
+                // Choices and cases don't exist in the data tree, so the nodes of the selected case are flattened
+                // into the parent. When deserializing, the case with members in the data is selected.
+                DataDef::Choice(choice) => {
+                    let field_name = self.config.naming.field_identifier(&self.node_name(&choice.name));
+                    let field_type = self.config.naming.type_identifier(&self.node_name(&choice.name));
+                    let choice_module = utils::sanitize_identifier(&self.node_name(&choice.name));
+                    let case_names = utils::case_variants(choice, &self.config.naming);
+                    let doc = self.node_doc(child);
+
+                    // A choice with a default case defaults to that case, with the defaults of its nodes.
+                    let default_case = choice.default.as_ref().and_then(|default_case| {
+                        let position = choice
+                            .cases
+                            .iter()
+                            .position(|case| utils::case_parts(case).0 == *default_case)?;
+                        Some(&case_names[position])
+                    });
+                    let default = match default_case {
+                        Some(case_name) => {
+                            quote! {
+                                Some(#module_path #field_type::#case_name(
+                                    #module_path #choice_module::#case_name::default()
+                                ))
+                            }
+                        }
+                        None => quote! { None },
+                    };
+
+                    // An element of the selected case is read into it, while an element of another case selects that
+                    // case, starting from its defaults.
+                    let missing_case = match choice.mandatory == Some(true) {
+                        true => {
+                            let message = format!("missing mandatory choice {}", choice.name);
+                            quote! {
+                                errors.push(#validation::ValidationError {
+                                    path: path.to_string(),
+                                    message: #message.to_string(),
+                                });
+                            }
+                        }
+                        false => quote! {},
+                    };
+                    // A choice without cases has no values.
+                    let case = quote! { proptest::arbitrary::any::<#module_path #field_type>() };
+                    let arbitrary = match (case_names.is_empty(), choice.mandatory == Some(true)) {
+                        (true, _) => quote! { proptest::strategy::Just(None) },
+                        (false, true) => quote! { #case.prop_map(Some) },
+                        (false, false) => quote! { proptest::option::of(#case) },
+                    };
+                    Some(Field {
+                        name: field_name.clone(),
+                        cfg: quote! {},
+                        deprecated: quote! {},
+                        value_type: quote! { #module_path #field_type },
+                        optional: true,
+                        mandatory: (choice.mandatory == Some(true)).then(|| choice.name.clone()),
+                        definition: quote! {
+                            #[doc = #doc]
+                            #[serde(flatten)]
+                            pub #field_name: Option<#module_path #field_type>,
+                        },
+                        default: quote! { #field_name: #default, },
+                        xml_write: quote! {
+                            if let Some(value) = &self.#field_name {
+                                #xml::XmlNode::write_children(value, writer, namespace)?;
+                            }
+                        },
+                        xml_read: quote! {
+                            if let Some(value) = &mut self.#field_name {
+                                if #xml::XmlNode::read_child(value, reader, start, empty)? {
+                                    return Ok(true);
+                                }
+                            }
+                            #(
+                                let mut case = #module_path #choice_module::#case_names::default();
+                                #xml::XmlNode::clear_defaults(&mut case);
+                                if #xml::XmlNode::read_child(&mut case, reader, start, empty)? {
+                                    self.#field_name = Some(#module_path #field_type::#case_names(case));
+                                    return Ok(true);
+                                }
+                            )*
+                        },
+                        xml_clear_defaults: quote! {
+                            if let Some(value) = &mut self.#field_name {
+                                #xml::XmlNode::clear_defaults(value);
+                            }
+                        },
+                        xml_restore_defaults: quote! {
+                            if let Some(value) = &mut self.#field_name {
+                                #xml::XmlNode::restore_defaults(value);
+                            }
+                        },
+                        validation: quote! {
+                            match &self.#field_name {
+                                Some(value) => #validation::Validate::validate_at(value, path, errors),
+                                None => { #missing_case }
+                            }
+                        },
+                        arbitrary,
+                    })
+                }
+                DataDef::AnyData(anydata) => {
+                    let doc = self.node_doc(child);
+                    let serde_name = serde_name(&anydata.name, child);
+                    Some(self.generate_anydata(&anydata.name, &doc, anydata.mandatory, &serde_name))
+                }
+                DataDef::Anyxml(anyxml) => {
+                    let doc = self.node_doc(child);
+                    let serde_name = serde_name(&anyxml.name, child);
+                    Some(self.generate_anydata(&anyxml.name, &doc, anyxml.mandatory, &serde_name))
+                }
+                _ => None,
+            })
+            .collect();
+
+        self.names.pop();
+
+        fields
+            .into_iter()
+            .zip(&data_defs)
+            .filter_map(|(field, child)| {
+                let namespace = utils::node_module(child)
+                    .filter(|child_module| **child_module != node_module)
+                    .and_then(|child_module| module.augmenting_modules.get(child_module));
+                let field = match namespace {
+                    Some(namespace) => in_namespace(field?, namespace),
+                    None => field?,
+                };
+                let field = self.feature_gated(field, utils::if_features(child));
+                Some(deprecated(field, utils::status(child)))
+            })
+            .collect()
+    }
+
+    // The data nodes which are generated, leaving out obsolete nodes if they are skipped, and nodes guarded by features
+    // which aren't supported.
+    pub(crate) fn generated_data_defs(&self, data_defs: &[DataDef]) -> Vec<DataDef> {
+        data_defs
+            .iter()
+            .filter(|data_def| !(self.config.skip_obsolete && matches!(utils::status(data_def), Status::Obsolete)))
+            .filter(|data_def| self.is_supported(data_def))
+            .cloned()
+            .collect()
+    }
+
+    // Whether every if-feature expression of the data node holds with the supported features of the config. Expressions
+    // which can't be parsed hold, so the node isn't left out.
+    pub(crate) fn is_supported(&self, data_def: &DataDef) -> bool {
+        if self.config.features.is_empty() {
+            return true;
+        }
+
+        let module: &'a Module = self.module;
+        let is_supported = |name: &str| {
+            let (module_name, feature) = self.resolve_prefix(name, &module.name);
+            utils::is_feature_supported(&self.config.features, &module_name, feature)
+        };
+        utils::if_features(data_def)
+            .iter()
+            .all(|expression| utils::if_feature_holds(expression, &is_supported).unwrap_or(true))
+    }
+
+    // The parts of a field guarded by if-features are only compiled with the features enabled. The statements of the
+    // field are wrapped in blocks, as the attribute applies to a single statement.
+    pub(crate) fn feature_gated(&mut self, field: Field, if_features: &[String]) -> Field {
+        if !self.config.if_features || if_features.is_empty() {
+            return field;
+        }
+
+        let module: &'a Module = self.module;
+        let feature_name = |name: &str| match self.resolve_prefix(name, &module.name) {
+            (module_name, name) if module_name == module.name => name.to_string(),
+            (module_name, name) => format!("{}-{}", module_name, name),
+        };
+        let conditions: Vec<Option<TokenStream>> = if_features
+            .iter()
+            .map(|expression| utils::if_feature_cfg(expression, &feature_name))
+            .collect();
+        let Some(conditions) = conditions.iter().cloned().collect::<Option<Vec<TokenStream>>>() else {
+            for (expression, _) in if_features
+                .iter()
+                .zip(&conditions)
+                .filter(|(_, condition)| condition.is_none())
+            {
+                self.warn(Warning::UnsupportedIfFeature(expression.clone()));
+            }
+            return field;
+        };
+        let cfg = match conditions.as_slice() {
+            [condition] => quote! { #[cfg(#condition)] },
+            _ => quote! { #[cfg(all(#(#conditions),*))] },
+        };
+        let gate = |statements: TokenStream| match statements.is_empty() {
+            true => statements,
+            false => quote! { #cfg { #statements } },
+        };
+
+        Field {
+            definition: {
+                let definition = field.definition;
+                quote! { #cfg #definition }
+            },
+            default: {
+                let default = field.default;
+                quote! { #cfg #default }
+            },
+            xml_write: gate(field.xml_write),
+            xml_read: gate(field.xml_read),
+            xml_clear_defaults: gate(field.xml_clear_defaults),
+            xml_restore_defaults: gate(field.xml_restore_defaults),
+            validation: gate(field.validation),
+            cfg,
+            ..field
+        }
+    }
+
+    // Leaves which always have a value, because they are mandatory, keys or have a default, are plain fields. Other
+    // leaves are optional, so an absent leaf can be told apart from a leaf set to the default of its type.
+    pub(crate) fn generate_leaf(
+        &mut self,
+        leaf: &Leaf,
+        doc: &str,
+        module_path: &TokenStream,
+        serde_name: &str,
+        is_key: bool,
+    ) -> Field {
+        let field_name = self.config.naming.field_identifier(&self.node_name(&leaf.name));
+        // Leafrefs have the type of the node they refer to.
+        let type_info = utils::resolved_type(&leaf.type_info);
+        let leaf_type = self.leaf_type(&leaf.name, type_info, module_path);
+
+        let always_present = is_key
+            || leaf.mandatory == Some(true)
+            || leaf.default.is_some()
+            || self.type_default(type_info, &self.module.name).is_some();
+        let (field_type, serde_attributes) = match always_present {
+            true => (leaf_type.clone(), self.string_encoding(type_info, "string")),
+            false => (
+                quote! { Option<#leaf_type> },
+                self.string_encoding(type_info, "option_string"),
+            ),
+        };
+        let serde_attributes = match always_present {
+            true => quote! { #[serde(rename = #serde_name)] #serde_attributes },
+            false => {
+                quote! { #[serde(rename = #serde_name, skip_serializing_if = "Option::is_none")] #serde_attributes }
+            }
+        };
+
+        // Defaults from typedefs are the defaults of the generated types, while leaves without a default start out
+        // with the default of their type.
+        let default = match (&leaf.default, always_present) {
+            (Some(default), _) => self.default_value(&leaf_type, type_info, default, &self.module.name),
+            (None, true) => quote! { Default::default() },
+            (None, false) => quote! { None },
+        };
+
+        let (write_value, read_value) = self.xml_leaf_value(&leaf.name, type_info);
+        let (xml_write, xml_read) = match always_present {
+            true => (
+                write_value(quote! { &self.#field_name }),
+                quote! { self.#field_name = #read_value; },
+            ),
+            false => {
+                let write_value = write_value(quote! { value });
+                (
+                    quote! {
+                        if let Some(value) = &self.#field_name {
+                            #write_value
+                        }
+                    },
+                    quote! { self.#field_name = Some(#read_value); },
+                )
+            }
+        };
+        let element_name = &leaf.name;
+        let xml = self.xml_path();
+
+        let module_name = self.module.name.clone();
+        let arbitrary = match (self.proptest(), always_present) {
+            (false, _) => quote! {},
+            (true, true) => self.arbitrary_value(&leaf_type, type_info, &module_name),
+            (true, false) if !self.has_values(type_info, &module_name) => quote! { proptest::strategy::Just(None) },
+            (true, false) => {
+                let value = self.arbitrary_value(&leaf_type, type_info, &module_name);
+                quote! { proptest::option::of(#value) }
+            }
+        };
+
+        let checks = self.restriction_checks(type_info);
+        let validation = match (checks.is_empty(), always_present) {
+            (true, _) => quote! {},
+            (false, true) => quote! {
+                {
+                    let path = format!("{}/{}", path, #element_name);
+                    let value = &self.#field_name;
+                    #checks
+                }
+            },
+            (false, false) => quote! {
+                if let Some(value) = &self.#field_name {
+                    let path = format!("{}/{}", path, #element_name);
+                    #checks
+                }
+            },
+        };
+
+        Field {
+            name: field_name.clone(),
+            cfg: quote! {},
+            deprecated: quote! {},
+            value_type: leaf_type,
+            optional: !always_present,
+            mandatory: (is_key || leaf.mandatory == Some(true)).then(|| leaf.name.clone()),
+            definition: quote! {
+                #[doc = #doc]
+                #serde_attributes
+                pub #field_name: #field_type,
+            },
+            default: quote! { #field_name: #default, },
+            xml_write,
+            xml_read: quote! {
+                if #xml::is_element(start, #element_name) {
+                    #xml_read
+                    return Ok(true);
+                }
+            },
+            xml_clear_defaults: quote! {},
+            xml_restore_defaults: quote! {},
+            validation,
+            arbitrary,
+        }
+    }
+
+    // Anydata and anyxml nodes hold data which the schema doesn't describe. It's kept as a JSON value, or when XML
+    // encoding is enabled, as the JSON value or the raw content of the element it was decoded from, as neither
+    // encoding can be converted to the other without a schema.
+    pub(crate) fn generate_anydata(
+        &mut self,
+        name: &str,
+        doc: &str,
+        mandatory: Option<bool>,
+        serde_name: &str,
+    ) -> Field {
+        let field_name = self.config.naming.field_identifier(&self.node_name(name));
+        let xml = self.xml_path();
+        let value_type = match self.xml() {
+            true => quote! { #xml::AnyData },
+            false => quote! { serde_json::Value },
+        };
+
+        Field {
+            name: field_name.clone(),
+            cfg: quote! {},
+            deprecated: quote! {},
+            value_type: value_type.clone(),
+            optional: true,
+            mandatory: (mandatory == Some(true)).then(|| name.to_string()),
+            definition: quote! {
+                #[doc = #doc]
+                #[serde(rename = #serde_name, skip_serializing_if = "Option::is_none")]
+                pub #field_name: Option<#value_type>,
+            },
+            default: quote! { #field_name: None, },
+            xml_write: quote! {
+                if let Some(value) = &self.#field_name {
+                    #xml::write_anydata(writer, #name, namespace, value)?;
+                }
+            },
+            xml_read: quote! {
+                if #xml::is_element(start, #name) {
+                    self.#field_name = Some(#xml::AnyData::Xml(#xml::read_raw(reader, start, empty)?));
+                    return Ok(true);
+                }
+            },
+            xml_clear_defaults: quote! {},
+            xml_restore_defaults: quote! {},
+            validation: quote! {},
+            // The content of anydata and anyxml nodes isn't described by the schema.
+            arbitrary: quote! { proptest::strategy::Just(None) },
+        }
+    }
+
+    pub(crate) fn generate_leaf_list(
+        &mut self,
+        leaf_list: &LeafList,
+        doc: &str,
+        module_path: &TokenStream,
+        serde_name: &str,
+    ) -> Field {
+        let field_name = self.config.naming.field_identifier(&self.node_name(&leaf_list.name));
+        let type_info = utils::resolved_type(&leaf_list.type_info);
+        let field_type = self.leaf_type(&leaf_list.name, type_info, module_path);
+        let string_encoding = self.string_encoding(type_info, "vec_string");
+        let (write_value, read_value) = self.xml_leaf_value(&leaf_list.name, type_info);
+        let write_value = write_value(quote! { value });
+        let element_name = &leaf_list.name;
+        let xml = self.xml_path();
+
+        let checks = self.restriction_checks(type_info);
+        let checks = match checks.is_empty() {
+            true => quote! {},
+            false => quote! {
+                for (idx, value) in self.#field_name.iter().enumerate() {
+                    let path = format!("{}/{}[{}]", path, #element_name, idx + 1);
+                    #checks
+                }
+            },
+        };
+        let elements_check = self.elements_check(
+            &leaf_list.name,
+            &field_name,
+            leaf_list.min_elements,
+            &leaf_list.max_elements,
+        );
+        let validation = quote! {
+            #checks
+            #elements_check
+        };
+
+        let module_name = self.module.name.clone();
+        let arbitrary = match self.proptest() {
+            true if !self.has_values(type_info, &module_name) => quote! { proptest::strategy::Just(Vec::new()) },
+            true => {
+                let value = self.arbitrary_value(&field_type, type_info, &module_name);
+                let arbitrary = self.arbitrary_path();
+                let (min, max) = entry_counts(leaf_list.min_elements, &leaf_list.max_elements);
+                quote! { #arbitrary::unique(#value, #min, #max, |value| format!("{:?}", value)) }
+            }
+            false => quote! {},
+        };
+
+        let defaults: Vec<TokenStream> = leaf_list
+            .default
+            .iter()
+            .map(|default| self.default_value(&field_type, type_info, default, &self.module.name))
+            .collect();
+
+        Field {
+            name: field_name.clone(),
+            cfg: quote! {},
+            deprecated: quote! {},
+            value_type: quote! { Vec<#field_type> },
+            optional: false,
+            mandatory: None,
+            definition: quote! {
+                #[doc = #doc]
+                #[serde(rename = #serde_name, skip_serializing_if = "Vec::is_empty")]
+                #string_encoding
+                pub #field_name: Vec<#field_type>,
+            },
+            default: quote! { #field_name: vec![#(#defaults),*], },
+            xml_write: quote! {
+                for value in &self.#field_name {
+                    #write_value
+                }
+            },
+            xml_read: quote! {
+                if #xml::is_element(start, #element_name) {
+                    self.#field_name.push(#read_value);
+                    return Ok(true);
+                }
+            },
+            xml_clear_defaults: match defaults.is_empty() {
+                true => quote! {},
+                false => quote! { self.#field_name.clear(); },
+            },
+            xml_restore_defaults: match defaults.is_empty() {
+                true => quote! {},
+                false => quote! {
+                    if self.#field_name.is_empty() {
+                        self.#field_name = vec![#(#defaults),*];
+                    }
+                },
+            },
+            validation,
+            arbitrary,
+        }
+    }
+}
@@ -0,0 +1,465 @@
+//! JSON Schema (draft 2020-12) generated from the schema, describing the RFC 7951 JSON encoding of the data tree of a
+//! module, so web frontends and generic validators can check YANG modeled data without YANG tooling. The schema holds
+//! the types, ranges, lengths, patterns and enums of the leaves, the mandatory nodes and list keys as required
+//! members, and the number of list entries. Typedefs are defined once in `$defs` and referenced by the leaves using
+//! them.
+//!
+//! The members of the cases of a choice are members of the object containing the choice, constrained so only the
+//! members of one case are present. Constraints which JSON Schema can't express, like must and when expressions,
+//! leafref instances and the ranges of 64-bit integers and decimals, which are strings in RFC 7951, aren't checked.
+
+use serde_json::{json, Map, Value as JsonValue};
+use yang_parser::model::*;
+
+use crate::utils;
+
+/// Generate the JSON Schema for the data tree of the module, describing a JSON document with the module-qualified
+/// top-level nodes as members, like `{"module:interfaces": {...}}`.
+pub fn generate(module: &Module) -> String {
+    let data_defs: Vec<DataDef> = module
+        .body
+        .iter()
+        .filter_map(|node| match node {
+            SchemaNode::DataDef(data_def) => Some(data_def.clone()),
+            _ => None,
+        })
+        .collect();
+
+    let mut generator = SchemaGenerator {
+        module,
+        defs: Map::new(),
+    };
+    let mut schema = Map::new();
+    schema.insert("$schema".into(), "https://json-schema.org/draft/2020-12/schema".into());
+    schema.insert("title".into(), module.name.as_str().into());
+    if let Some(description) = &module.meta.description {
+        schema.insert("description".into(), description.trim().into());
+    }
+    schema.extend(generator.object(&data_defs, &module.name, true, true, &[]));
+    if !generator.defs.is_empty() {
+        schema.insert("$defs".into(), JsonValue::Object(generator.defs));
+    }
+
+    serde_json::to_string_pretty(&JsonValue::Object(schema)).expect("JSON values to always serialize")
+}
+
+// The required members and other constraints of an object, or of a case of a choice within it.
+#[derive(Default)]
+struct Constraints {
+    required: Vec<String>,
+    all_of: Vec<JsonValue>,
+}
+
+struct SchemaGenerator<'a> {
+    module: &'a Module,
+    // The schemas of the typedefs, keyed by the module name and path of the typedef.
+    defs: Map<String, JsonValue>,
+}
+
+impl SchemaGenerator<'_> {
+    // Objects don't allow members which aren't part of the schema, so misspelled members are caught.
+    fn object(
+        &mut self,
+        data_defs: &[DataDef],
+        module_name: &str,
+        top_level: bool,
+        config: bool,
+        keys: &[String],
+    ) -> Map<String, JsonValue> {
+        let mut properties = Map::new();
+        let mut constraints = Constraints::default();
+        self.members(
+            data_defs,
+            module_name,
+            top_level,
+            config,
+            &mut properties,
+            &mut constraints,
+        );
+        for key in keys {
+            if !constraints.required.contains(key) {
+                constraints.required.push(key.clone());
+            }
+        }
+
+        let mut schema = Map::new();
+        schema.insert("type".into(), "object".into());
+        schema.insert("properties".into(), JsonValue::Object(properties));
+        if !constraints.required.is_empty() {
+            schema.insert("required".into(), constraints.required.into());
+        }
+        if !constraints.all_of.is_empty() {
+            schema.insert("allOf".into(), constraints.all_of.into());
+        }
+        schema.insert("additionalProperties".into(), false.into());
+        schema
+    }
+
+    // Add the members of the data nodes to the properties, and return their names. Top-level members are qualified
+    // with the module name, like the members added by augments of other modules, which are qualified with the name of
+    // the other module. Mandatory nodes are required unless they have a when condition, which may make them absent.
+    fn members(
+        &mut self,
+        data_defs: &[DataDef],
+        module_name: &str,
+        top_level: bool,
+        parent_config: bool,
+        properties: &mut Map<String, JsonValue>,
+        constraints: &mut Constraints,
+    ) -> Vec<String> {
+        let mut names = Vec::new();
+        for data_def in data_defs {
+            let node_module = utils::node_module(data_def).map_or(module_name, Symbol::as_str);
+            let name = utils::data_def_name(data_def);
+            let member_name = match top_level || node_module != module_name {
+                true => format!("{}:{}", node_module, name),
+                false => name.to_string(),
+            };
+
+            let (mut schema, config, required) = match data_def {
+                DataDef::Container(container) => {
+                    let config = container.config.unwrap_or(parent_config);
+                    let mut schema = self.object(&container.data_defs, node_module, false, config, &[]);
+                    annotate(&mut schema, &container.meta.description, None);
+                    (schema, config, false)
+                }
+                DataDef::List(list) => {
+                    let config = list.config.unwrap_or(parent_config);
+                    let keys = utils::list_keys(list);
+                    let items = self.object(&list.data_defs, node_module, false, config, &keys);
+                    let mut schema = array(items, list.min_elements, &list.max_elements);
+                    annotate(&mut schema, &list.meta.description, None);
+                    let required = list.min_elements.unwrap_or(0) > 0 && list.when.is_none();
+                    (schema, config, required)
+                }
+                DataDef::Leaf(leaf) => {
+                    let mut schema = self.type_schema(&leaf.type_info, node_module);
+                    let default = leaf
+                        .default
+                        .as_ref()
+                        .map(|default| self.json_value(&leaf.type_info, node_module, default));
+                    annotate(&mut schema, &leaf.meta.description, default);
+                    let required = leaf.mandatory == Some(true) && leaf.when.is_none();
+                    (schema, leaf.config.unwrap_or(parent_config), required)
+                }
+                DataDef::LeafList(leaf_list) => {
+                    let items = self.type_schema(&leaf_list.type_info, node_module);
+                    let mut schema = array(items, leaf_list.min_elements, &leaf_list.max_elements);
+                    let default = (!leaf_list.default.is_empty()).then(|| {
+                        leaf_list
+                            .default
+                            .iter()
+                            .map(|default| self.json_value(&leaf_list.type_info, node_module, default))
+                            .collect()
+                    });
+                    annotate(&mut schema, &leaf_list.meta.description, default);
+                    let required = leaf_list.min_elements.unwrap_or(0) > 0 && leaf_list.when.is_none();
+                    (schema, leaf_list.config.unwrap_or(parent_config), required)
+                }
+                DataDef::Choice(choice) => {
+                    let config = choice.config.unwrap_or(parent_config);
+                    names.extend(self.choice(choice, module_name, top_level, config, properties, constraints));
+                    continue;
+                }
+                DataDef::AnyData(anydata) => {
+                    let mut schema = Map::new();
+                    schema.insert("type".into(), "object".into());
+                    annotate(&mut schema, &anydata.meta.description, None);
+                    let required = anydata.mandatory == Some(true) && anydata.when.is_none();
+                    (schema, anydata.config.unwrap_or(parent_config), required)
+                }
+                DataDef::Anyxml(anyxml) => {
+                    let mut schema = Map::new();
+                    annotate(&mut schema, &anyxml.meta.description, None);
+                    let required = anyxml.mandatory == Some(true) && anyxml.when.is_none();
+                    (schema, anyxml.config.unwrap_or(parent_config), required)
+                }
+                DataDef::Uses(_) => continue,
+            };
+
+            if matches!(utils::status(data_def), Status::Deprecated | Status::Obsolete) {
+                schema.insert("deprecated".into(), true.into());
+            }
+            // State data is read-only, which is inherited by the nodes within the state data.
+            if parent_config && !config {
+                schema.insert("readOnly".into(), true.into());
+            }
+            properties.insert(member_name.clone(), JsonValue::Object(schema));
+            if required {
+                constraints.required.push(member_name.clone());
+            }
+            names.push(member_name);
+        }
+
+        names
+    }
+
+    // The members of every case are added to the properties, and the choice adds a constraint allowing the members of
+    // one case only. Each case is an alternative with the required members and other constraints of the case, which
+    // holds when no members of other cases are present. Unless the choice is mandatory, having no members of any case
+    // is an alternative too.
+    fn choice(
+        &mut self,
+        choice: &Choice,
+        module_name: &str,
+        top_level: bool,
+        config: bool,
+        properties: &mut Map<String, JsonValue>,
+        constraints: &mut Constraints,
+    ) -> Vec<String> {
+        let cases: Vec<(Vec<String>, Constraints)> = choice
+            .cases
+            .iter()
+            .map(|case| {
+                let (_, _, data_defs) = utils::case_parts(case);
+                let mut case_constraints = Constraints::default();
+                let names = self.members(
+                    &data_defs,
+                    module_name,
+                    top_level,
+                    config,
+                    properties,
+                    &mut case_constraints,
+                );
+                (names, case_constraints)
+            })
+            .collect();
+        let names: Vec<String> = cases.iter().flat_map(|(names, _)| names.clone()).collect();
+        let mandatory = choice.mandatory == Some(true) && choice.when.is_none();
+
+        let mut alternatives: Vec<JsonValue> = cases
+            .into_iter()
+            .map(|(case_names, case_constraints)| {
+                let mut alternative = Map::new();
+                if mandatory && !case_names.is_empty() {
+                    alternative.insert("anyOf".into(), any_present(&case_names));
+                }
+                if !case_constraints.required.is_empty() {
+                    alternative.insert("required".into(), case_constraints.required.into());
+                }
+                if !case_constraints.all_of.is_empty() {
+                    alternative.insert("allOf".into(), case_constraints.all_of.into());
+                }
+                let others: Vec<String> = names
+                    .iter()
+                    .filter(|name| !case_names.contains(name))
+                    .cloned()
+                    .collect();
+                if !others.is_empty() {
+                    alternative.insert("not".into(), json!({ "anyOf": any_present(&others) }));
+                }
+                JsonValue::Object(alternative)
+            })
+            .collect();
+        if !mandatory {
+            alternatives.push(json!({ "not": { "anyOf": any_present(&names) } }));
+        }
+        if !names.is_empty() {
+            constraints.all_of.push(json!({ "anyOf": alternatives }));
+        }
+
+        names
+    }
+
+    // Typedefs are referenced from `$defs`, with the restrictions added where the typedef is used, and unions allow
+    // any of their member types. Leafrefs have the type of the leaf they refer to, and allow any value if their path
+    // can't be resolved.
+    fn type_schema(&mut self, type_info: &TypeInfo, module_name: &str) -> Map<String, JsonValue> {
+        let type_info = utils::resolved_type(type_info);
+        let Some((type_module, path, type_def)) = utils::find_type_def(self.module, &type_info.name, module_name)
+        else {
+            let mut schema = match (type_info.name.as_str(), type_info.type_body.as_deref()) {
+                ("union", Some(TypeBody::Union { types })) => {
+                    let types: Vec<JsonValue> = types
+                        .iter()
+                        .map(|type_info| JsonValue::Object(self.type_schema(type_info, module_name)))
+                        .collect();
+                    let mut schema = Map::new();
+                    schema.insert("anyOf".into(), types.into());
+                    schema
+                }
+                (name, _) => built_in_schema(name),
+            };
+            if let Some(type_body) = type_info.type_body.as_deref() {
+                schema.extend(restrictions(&type_info.name, type_body));
+            }
+            return schema;
+        };
+
+        let key = format!("{}:{}", type_module, path.trim_start_matches('/').replace('/', "."));
+        if !self.defs.contains_key(&key) {
+            // The key is taken first, so typedefs referring to themselves don't recurse forever.
+            self.defs.insert(key.clone(), JsonValue::Null);
+            let mut schema = self.type_schema(&type_def.type_info, &type_module);
+            let default = type_def
+                .default
+                .as_ref()
+                .map(|default| self.json_value(&type_def.type_info, &type_module, default));
+            annotate(&mut schema, &type_def.description, default);
+            self.defs.insert(key.clone(), JsonValue::Object(schema));
+        }
+
+        let mut schema = Map::new();
+        schema.insert("$ref".into(), format!("#/$defs/{}", key).into());
+        if let Some(type_body) = type_info.type_body.as_deref() {
+            let built_in = self.built_in_type(type_info, module_name);
+            schema.extend(restrictions(&built_in.name, type_body));
+        }
+        schema
+    }
+
+    // The built-in type a type is derived from, through typedefs and leafrefs.
+    fn built_in_type(&self, type_info: &TypeInfo, module_name: &str) -> TypeInfo {
+        let mut type_info = utils::resolved_type(type_info).clone();
+        let mut module_name = module_name.to_string();
+        while let Some((type_module, _, type_def)) = utils::find_type_def(self.module, &type_info.name, &module_name) {
+            type_info = utils::resolved_type(&type_def.type_info).clone();
+            module_name = type_module;
+        }
+        type_info
+    }
+
+    fn json_value(&self, type_info: &TypeInfo, module_name: &str, value: &str) -> JsonValue {
+        utils::json_value(&self.built_in_type(type_info, module_name), value)
+    }
+}
+
+// RFC 7951 encodes 64-bit integers and decimals as strings, the empty type as [null], and binary values as base64.
+fn built_in_schema(name: &str) -> Map<String, JsonValue> {
+    let schema = match name {
+        "int8" | "int16" | "int32" | "uint8" | "uint16" | "uint32" => {
+            let (minimum, maximum) = utils::integer_bounds(name);
+            json!({ "type": "integer", "minimum": minimum as i64, "maximum": maximum as i64 })
+        }
+        "int64" => json!({ "type": "string", "pattern": "^[+-]?[0-9]+$" }),
+        "uint64" => json!({ "type": "string", "pattern": "^[+]?[0-9]+$" }),
+        "decimal64" => json!({ "type": "string", "pattern": "^[+-]?[0-9]+(\\.[0-9]+)?$" }),
+        "boolean" => json!({ "type": "boolean" }),
+        "empty" => json!({ "const": [null] }),
+        "binary" => json!({ "type": "string", "contentEncoding": "base64" }),
+        "string" | "enumeration" | "bits" | "identityref" | "instance-identifier" => json!({ "type": "string" }),
+        _ => json!({}),
+    };
+
+    match schema {
+        JsonValue::Object(schema) => schema,
+        _ => Map::new(),
+    }
+}
+
+// The restrictions of a type, for the built-in type it's derived from. YANG patterns are XML Schema regular
+// expressions, which are implicitly anchored at both ends, while JSON Schema patterns are not.
+fn restrictions(built_in: &str, type_body: &TypeBody) -> Map<String, JsonValue> {
+    let mut schema = Map::new();
+    match type_body {
+        TypeBody::Numerical { range } if !matches!(built_in, "int64" | "uint64") => {
+            let (min, max) = utils::integer_bounds(built_in);
+            let intervals = utils::parse_intervals(&range.value, |bound| match bound {
+                "min" => Some(min),
+                "max" => Some(max),
+                _ => utils::parse_integer(built_in, bound),
+            });
+            let intervals: Vec<JsonValue> = intervals
+                .into_iter()
+                .flatten()
+                .map(|(lower, upper)| json!({ "minimum": lower as i64, "maximum": upper as i64 }))
+                .collect();
+            match intervals.as_slice() {
+                [JsonValue::Object(interval)] => schema.extend(interval.clone()),
+                [] => {}
+                _ => {
+                    schema.insert("anyOf".into(), intervals.into());
+                }
+            }
+        }
+        TypeBody::String { length, patterns } => {
+            let intervals = length.as_ref().and_then(|length| {
+                utils::parse_intervals(&length.value, |bound| match bound {
+                    "min" => Some(Some(0)),
+                    "max" => Some(None),
+                    _ => bound.parse::<u64>().ok().map(Some),
+                })
+            });
+            let intervals: Vec<Map<String, JsonValue>> = intervals
+                .into_iter()
+                .flatten()
+                .map(|(lower, upper)| {
+                    let mut interval = Map::new();
+                    interval.insert("minLength".into(), lower.unwrap_or(0).into());
+                    if let Some(upper) = upper {
+                        interval.insert("maxLength".into(), upper.into());
+                    }
+                    interval
+                })
+                .collect();
+            match intervals.as_slice() {
+                [interval] => schema.extend(interval.clone()),
+                [] => {}
+                _ => {
+                    schema.insert("anyOf".into(), intervals.into_iter().map(JsonValue::Object).collect());
+                }
+            }
+
+            let patterns: Vec<JsonValue> = patterns
+                .iter()
+                .map(|pattern| {
+                    let regex = format!("^(?:{})$", pattern.value);
+                    match pattern.modifier.as_deref() == Some("invert-match") {
+                        true => json!({ "not": { "pattern": regex } }),
+                        false => json!({ "pattern": regex }),
+                    }
+                })
+                .collect();
+            match patterns.as_slice() {
+                [JsonValue::Object(pattern)] => schema.extend(pattern.clone()),
+                [] => {}
+                _ => {
+                    schema.insert("allOf".into(), patterns.into());
+                }
+            }
+        }
+        TypeBody::Enum { enums } => {
+            let names: Vec<JsonValue> = enums.iter().map(|enum_value| enum_value.name.clone().into()).collect();
+            schema.insert("enum".into(), names.into());
+        }
+        // Bits are the names of the set bits, separated by spaces.
+        TypeBody::Bits { bits } if !bits.is_empty() => {
+            let names: Vec<String> = bits.iter().map(|bit| bit.name.replace('.', "\\.")).collect();
+            let regex = format!("^ *(?:(?:{})(?: +|$))*$", names.join("|"));
+            schema.insert("pattern".into(), regex.into());
+        }
+        _ => {}
+    }
+
+    schema
+}
+
+fn array(
+    items: Map<String, JsonValue>,
+    min_elements: Option<i64>,
+    max_elements: &Option<MaxElements>,
+) -> Map<String, JsonValue> {
+    let mut schema = Map::new();
+    schema.insert("type".into(), "array".into());
+    schema.insert("items".into(), JsonValue::Object(items));
+    if let Some(min_elements) = min_elements.filter(|min_elements| *min_elements > 0) {
+        schema.insert("minItems".into(), min_elements.into());
+    }
+    if let Some(MaxElements::Value(max_elements)) = max_elements {
+        schema.insert("maxItems".into(), (*max_elements).into());
+    }
+    schema
+}
+
+fn annotate(schema: &mut Map<String, JsonValue>, description: &Option<String>, default: Option<JsonValue>) {
+    if let Some(description) = description {
+        schema.insert("description".into(), description.trim().into());
+    }
+    if let Some(default) = default {
+        schema.insert("default".into(), default);
+    }
+}
+
+fn any_present(names: &[String]) -> JsonValue {
+    names.iter().map(|name| json!({ "required": [name] })).collect()
+}
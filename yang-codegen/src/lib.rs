@@ -1,55 +1,921 @@
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet, HashMap},
+    path::Path,
+    sync::Arc,
+};
+
 use proc_macro2::TokenStream;
 use quote::quote;
+use syn::ext::IdentExt;
 use yang_parser::model::*;
 
+mod arbitrary;
+mod checks;
+pub mod config_file;
+mod derived_types;
+mod fields;
+pub mod json_schema;
+mod nodes;
+mod operations;
+pub mod proto;
+mod runtime;
+pub mod skeleton;
+mod structs;
+mod types;
 mod utils;
+mod xml;
 
-pub fn generate(module: YangModule) {
-    if let YangModule::Module(module) = module {
-        for node in module.body {
-            match node {
-                SchemaNode::DataDef(data_def) => match data_def {
-                    DataDef::Container(container) => generate_container(container),
-                    _ => (),
-                },
-                _ => (),
-            }
-        }
+use runtime::{
+    generate_arbitrary_helpers, generate_rpc_error, generate_string_encoding, generate_validation,
+    generate_xml_helpers, no_std,
+};
+
+/// Generate Rust code for the data nodes of the module, and the typedefs they reference, and return it formatted.
+/// The generated types can be (de)serialized as RFC 7951 JSON, so the generated code depends on the `serde` crate
+/// with the `derive` feature, on the `serde_json` crate if the module has choices, whose cases are selected by the
+/// members of the data, on the `bitflags` crate if the module uses bits types, and on the `regex` crate if the
+/// module has pattern restrictions, which are checked by the generated `validate` methods.
+/// Submodules are generated as part of the module including them, so nothing is generated for them.
+///
+/// The code only depends on the module and the config, so it's the same on every run and platform, which makes it
+/// suitable for checking in. It starts with a comment naming the module and its latest revision.
+pub fn generate(module: YangModule) -> String {
+    generate_with_config(module, &Config::default())
+}
+
+/// Generate Rust code for the module like [generate], with the options in the config.
+pub fn generate_with_config(module: YangModule, config: &Config) -> String {
+    let header = match &module {
+        YangModule::Module(module) => header(module),
+        YangModule::Submodule(_) => String::new(),
+    };
+    header + &format_code(generate_tokens(module, config))
+}
+
+/// Generate Rust code for the module like [generate_with_config], and write it to the file at the path.
+pub fn generate_to_file(module: YangModule, config: &Config, path: impl AsRef<Path>) -> std::io::Result<()> {
+    std::fs::write(path, generate_with_config(module, config))
+}
+
+/// Generate Rust code for the module like [generate_with_config], as unformatted tokens. This is useful for
+/// procedural macros, which return tokens rather than source code.
+pub fn generate_tokens(module: YangModule, config: &Config) -> TokenStream {
+    match module {
+        YangModule::Module(module) => CodeGenerator::new(&select_paths(&module, config), config).generate(),
+        YangModule::Submodule(_) => TokenStream::new(),
+    }
+}
+
+/// Generate Rust code for the module at the path and every module it imports, directly or through other imported
+/// modules, which are loaded from the module's directory like `yang_parser::parse` does. The formatted code of each
+/// module is returned by the module name, and is meant to be included as sibling Rust modules named after the YANG
+/// modules, like `ietf_inet_types`. Modules use the typedefs of the modules they import from their code rather than
+/// generating them again, so imported modules get every top-level typedef, referenced or not.
+pub fn generate_modules(
+    path: impl AsRef<Path>,
+    config: &Config,
+) -> Result<BTreeMap<String, String>, yang_parser::ParserError> {
+    Ok(generate_module_tokens(path.as_ref(), config)?
+        .into_iter()
+        .map(|(module, tokens)| (module.name.to_string(), header(&module) + &format_code(tokens)))
+        .collect())
+}
+
+/// Generate Rust code for the module at the path and every module it imports like [generate_modules], and write it to
+/// the directory as a tree of files, which keeps the code of very large models navigable. Each YANG module gets a
+/// directory named after it with a `mod.rs`, like `ietf_interfaces/mod.rs`, and each Rust module at the top level of
+/// its code gets a file in that directory, like `ietf_interfaces/interfaces.rs` for the `interfaces` container. The
+/// `mod.rs` of the directory declares the module directories, so writing the code to `src/yang` and declaring
+/// `mod yang;` includes every module.
+pub fn generate_modules_to_dir(
+    path: impl AsRef<Path>,
+    config: &Config,
+    directory: impl AsRef<Path>,
+) -> Result<(), yang_parser::ParserError> {
+    let directory = directory.as_ref();
+    let mut module_names = Vec::new();
+    for (module, tokens) in generate_module_tokens(path.as_ref(), config)? {
+        write_module_tree(directory, &module, tokens)?;
+        module_names.push(module.name.to_string());
+    }
+    write_mod_file(directory, &module_names)?;
+    Ok(())
+}
+
+// Write the code of the module to a directory named after it in the directory, with the Rust modules at the top level
+// of the code in files of their own.
+fn write_module_tree(directory: &Path, module: &Module, tokens: TokenStream) -> std::io::Result<()> {
+    let header = header(module);
+    let module_directory = directory.join(utils::sanitize_identifier(&module.name).unraw().to_string());
+    std::fs::create_dir_all(&module_directory)?;
+
+    let (code, module_files) = split_modules(parse_code(tokens));
+    for (file_name, module_code) in module_files {
+        let code = header.clone() + &prettyplease::unparse(&module_code);
+        std::fs::write(module_directory.join(file_name), code)?;
+    }
+    std::fs::write(module_directory.join("mod.rs"), header + &prettyplease::unparse(&code))
+}
+
+// Write the mod.rs of the directory, declaring the Rust modules of the YANG modules generated in it.
+fn write_mod_file(directory: &Path, module_names: &[String]) -> std::io::Result<()> {
+    let module_idents = module_names.iter().map(|name| utils::sanitize_identifier(name));
+    let code = format_code(quote! { #(pub mod #module_idents;)* });
+    std::fs::write(
+        directory.join("mod.rs"),
+        format!("// Generated by yang-codegen. Do not edit.\n\n{}", code),
+    )
+}
+
+// Generate the tokens of the module at the path and every module it imports, with the module first. Imported modules
+// are generated with all their top-level typedefs, as the other modules use them from their code.
+fn generate_module_tokens(
+    path: &Path,
+    config: &Config,
+) -> Result<Vec<(Module, TokenStream)>, yang_parser::ParserError> {
+    let mut modules = vec![(parse_module(path)?, false)];
+    let directory = path.parent().unwrap_or(Path::new(""));
+    for module_name in modules[0].0.imported_modules.keys().cloned().collect::<BTreeSet<_>>() {
+        let module = parse_module(directory.join(format!("{}.yang", module_name)))?;
+        modules.push((module, true));
     }
+
+    Ok(modules
+        .into_iter()
+        .map(|(module, imported)| {
+            let mut config = config.clone();
+            config
+                .external_modules
+                .extend(module.imported_modules.keys().map(|module_name| {
+                    let path = format!("super::{}", utils::sanitize_identifier(module_name));
+                    (module_name.to_string(), path)
+                }));
+            let selected = select_paths(&module, &config);
+            let mut generator = CodeGenerator::new(&selected, &config);
+            generator.all_type_defs = imported;
+            let tokens = generator.generate();
+            (module, tokens)
+        })
+        .collect())
 }
 
-fn generate_container(container: Container) {
-    let struct_name = utils::sanitize_identifier(container.name.as_str());
-    let struct_fields: Vec<TokenStream> = container
-        .data_defs
+// Move the inline Rust modules at the top level of the code to files of their own, leaving their declarations in the
+// code. Returns the code and the code of each module by its file name. Inner attributes of a module are attributes of
+// its file.
+fn split_modules(code: syn::File) -> (syn::File, Vec<(String, syn::File)>) {
+    let mut module_files = Vec::new();
+    let items = code
+        .items
+        .into_iter()
+        .map(|item| match item {
+            syn::Item::Mod(mut item_mod) if item_mod.content.is_some() => {
+                let (_, items) = item_mod.content.take().expect("the module to have content");
+                let (attrs, outer_attrs) = std::mem::take(&mut item_mod.attrs)
+                    .into_iter()
+                    .partition(|attr| matches!(attr.style, syn::AttrStyle::Inner(_)));
+                item_mod.attrs = outer_attrs;
+                item_mod.semi = Some(Default::default());
+                let file_name = format!("{}.rs", item_mod.ident.unraw());
+                module_files.push((
+                    file_name,
+                    syn::File {
+                        shebang: None,
+                        attrs,
+                        items,
+                    },
+                ));
+                syn::Item::Mod(item_mod)
+            }
+            item => item,
+        })
+        .collect();
+
+    (syn::File { items, ..code }, module_files)
+}
+
+// The module with the data nodes, RPCs and notifications at the paths selected by the config. RPCs and notifications
+// are selected by their name at the top level, like "/reboot".
+fn select_paths<'m>(module: &'m Module, config: &Config) -> Cow<'m, Module> {
+    if config.include_paths.is_empty() && config.exclude_paths.is_empty() {
+        return Cow::Borrowed(module);
+    }
+
+    let filter = utils::PathFilter::new(&config.include_paths, &config.exclude_paths);
+    let body = module
+        .body
         .iter()
-        .filter_map(|child| match child {
-            DataDef::Leaf(leaf) => Some(generate_leaf(leaf)),
-            _ => None,
+        .filter_map(|node| match node {
+            SchemaNode::DataDef(data_def) => filter
+                .filter(std::slice::from_ref(data_def), "")
+                .pop()
+                .map(SchemaNode::DataDef),
+            SchemaNode::Rpc(rpc) => filter.selects(&format!("/{}", rpc.name)).then(|| node.clone()),
+            SchemaNode::Notification(notification) => {
+                filter.selects(&format!("/{}", notification.name)).then(|| node.clone())
+            }
         })
         .collect();
-    let doc = utils::format_docstring(&container.description);
 
-    let struct_def = quote! {
-        #[doc = #doc]
-        #[derive(Debug, Clone)]
-        pub struct #struct_name {
-            #(#struct_fields)*
+    Cow::Owned(Module { body, ..module.clone() })
+}
+
+fn parse_module(path: impl AsRef<Path>) -> Result<Module, yang_parser::ParserError> {
+    match yang_parser::parse(path)? {
+        YangModule::Module(module) => Ok(module),
+        YangModule::Submodule(_) => Err(yang_parser::ErrorKind::InvalidParserEntrypoint.into()),
+    }
+}
+
+/// Options for the generated code.
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    /// Generate XML encoding and decoding of the data nodes, with the element names and namespaces used by NETCONF.
+    /// The root struct gets `to_xml` and `from_xml` methods, and the generated code depends on the `quick-xml` crate,
+    /// version 0.42 or later.
+    pub xml: bool,
+
+    /// Generate the config and state data of the module as separate trees, in addition to the complete tree. The
+    /// config tree, with the `ModuleConfig` root struct, holds the nodes with config true. The state tree, with the
+    /// `ModuleState` root struct, holds the nodes with config false, and the containers and list keys leading to them.
+    pub config_state: bool,
+
+    /// Generate the data nodes guarded by `if-feature` statements behind `#[cfg(feature = "...")]` attributes, so the
+    /// generated code mirrors the features supported by a device. Features of the module are named like the YANG
+    /// feature, and features of imported modules are prefixed with the module name, like
+    /// "ietf-interfaces-arbitrary-names". The features must be declared in the manifest of the crate including the
+    /// generated code.
+    pub if_features: bool,
+
+    /// The features supported by the device the code is for, as a module and its features, like
+    /// "ietf-interfaces:arbitrary-names,pre-provisioning", where a module given without features supports none of them.
+    /// Data nodes guarded by if-features which aren't supported are left out. Every feature of the modules which aren't
+    /// given is supported, so nothing is left out when there are none.
+    pub features: Vec<String>,
+
+    /// Omit the data nodes with status obsolete. Otherwise obsolete nodes are generated like deprecated nodes, whose
+    /// fields are marked with `#[deprecated]`.
+    pub skip_obsolete: bool,
+
+    /// Extra derives for every generated struct and enum, like "PartialEq", "Hash" or "arbitrary::Arbitrary". Derives
+    /// a type already has are left out for it. The derives must not conflict with the implementations of `Debug`,
+    /// `Clone`, `Default` and serde's traits, which are always generated.
+    pub derives: Vec<String>,
+
+    /// Extra attributes for generated types, as pairs of the name of the type, like "Interface", and the attribute,
+    /// like `#[serde(deny_unknown_fields)]`. The attribute is added to every generated type with the name.
+    pub type_attributes: Vec<(String, String)>,
+
+    /// Generate decimal64 values as `rust_decimal::Decimal` rather than `f64`, so they keep their exact value. The
+    /// generated code then depends on the `rust_decimal` crate.
+    pub rust_decimal: bool,
+
+    /// Generate keyed lists as maps from the keys of their entries to the entries, rather than vectors. Lists ordered
+    /// by the user become `indexmap::IndexMap`s, which keep the order of the entries, and other lists become
+    /// `BTreeMap`s. Lists with several key leaves get a key struct, like `InterfaceKey`. Lists with a key leaf which
+    /// can't be ordered, like a decimal64 or a union, stay vectors.
+    pub list_maps: bool,
+
+    /// Imported modules whose code is generated separately, as pairs of the module name and the Rust path to its
+    /// generated code, like ("ietf-inet-types", "crate::ietf_inet_types"). Typedefs of these modules are used from
+    /// that code, which is re-exported in a module named after the imported module, rather than generated again.
+    /// [generate_modules] sets them for every module it imports.
+    pub external_modules: Vec<(String, String)>,
+
+    /// Generate one set of types for the containers and lists of a grouping, shared by every node using the grouping
+    /// without refining or augmenting it, rather than types per node. The shared types are generated in the
+    /// `groupings` module, in a module named after the grouping, and re-exported where the nodes' types would be.
+    /// Shared types don't have the `SCHEMA_PATH` and `instance_path` of a single node, and nodes with actions or
+    /// notifications, which are called on a single node, aren't shared. The config and state trees aren't shared either.
+    pub share_groupings: bool,
+
+    /// Generate only the data nodes at or below these schema paths, like "/interfaces" or "/system/dns", along with the
+    /// containers and lists leading to them. Everything is generated when there are none. Paths are made of node names,
+    /// leaving out choices and cases, and prefixes are ignored. RPCs and notifications are selected by their name, like
+    /// "/reboot". The paths apply to every module generated by [generate_modules].
+    pub include_paths: Vec<String>,
+
+    /// Leave out the data nodes at or below these schema paths, like "/bgp", even when they are below included paths.
+    /// List keys are always generated with their list.
+    pub exclude_paths: Vec<String>,
+
+    /// Generate code for `#![no_std]` crates using the `alloc` crate, like agents on embedded devices. The generated
+    /// code uses `core` and `alloc` rather than `std`, so the crate including it must declare `extern crate alloc;`,
+    /// and depend on serde, and the other crates the code uses, without their `std` features. Patterns are compiled
+    /// for every check rather than once. XML encoding isn't generated, as `quick-xml` needs `std`, and lists ordered
+    /// by the user stay vectors with `list_maps`, as `indexmap::IndexMap` needs `std` for its default hasher.
+    pub no_std: bool,
+
+    /// Mark the generated structs, enumerations and identityrefs with `#[non_exhaustive]`, and give the enums an
+    /// `Unknown(String)` variant holding values they don't know, so code using them keeps compiling, and data keeps
+    /// decoding, when a later revision of the model adds nodes, enums or identities. Enumerations with unknown values
+    /// aren't `Copy`, and their `value` is `None` for unknown values. Enumerations and identityrefs which are members of
+    /// a union don't get an unknown variant, so the union tries its other members for values they don't know.
+    pub non_exhaustive: bool,
+
+    /// Generate types with range, length or pattern restrictions of their own, like a typedef of uint16 with the range
+    /// 1..4094 or a leaf of type string with a pattern, as newtypes of their built-in type which check the restrictions
+    /// whenever a value is made, so values violating them can't be constructed. The newtypes are made with `new`,
+    /// `TryFrom`, `FromStr` and deserialization, which fail for values violating the restrictions of the type and the
+    /// typedefs it's derived from, and the value is read with `get` and `into_inner`. Their default, when the type and
+    /// leaf have none, is the default of the built-in type, which may violate the restrictions.
+    pub checked_types: bool,
+
+    /// Generate implementations of proptest's `Arbitrary` for the generated types, for property tests with random data
+    /// valid for the schema. Values satisfy the range, length and pattern restrictions of their types, enums and bits
+    /// take their defined values, and lists and leaf-lists have between min-elements and max-elements entries, up to
+    /// a few more than min-elements, without duplicate keys or values. Must and when conditions aren't evaluated, and
+    /// leafrefs don't refer to existing instances. Types mapped to Rust types need to implement `Arbitrary`, and
+    /// patterns which proptest can't generate strings for are satisfied by filtering random strings. It's not
+    /// generated for `no_std` crates, as proptest's regex strategies need std.
+    pub proptest: bool,
+
+    /// Rust types chosen for typedefs, like `std::net::Ipv4Addr` for ietf-inet-types' ipv4-address, which are used
+    /// rather than generating the typedefs. See [TypeMapper].
+    pub type_mapper: Option<Arc<dyn TypeMapper>>,
+
+    /// How the identifiers of the generated code are made from YANG names. See [Naming].
+    pub naming: Naming,
+
+    /// Receives the warnings found while generating, like if-feature expressions which can't be generated as `cfg`
+    /// attributes and types which aren't known, so applications can log, collect or escalate them. Each warning is
+    /// reported once per module.
+    pub warnings: Option<Arc<dyn WarningSink>>,
+}
+
+/// Parts of a module which the generated code doesn't represent as the module describes them.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Warning {
+    #[error("if-feature expression {0} is not supported, so the node is generated unconditionally")]
+    UnsupportedIfFeature(String),
+
+    #[error("type {0} is not a built-in type or a known typedef, so it's generated as a String")]
+    UnknownType(String),
+}
+
+/// Receives the warnings found while generating code, set with [Config::warnings]. Closures taking a [Warning] are
+/// sinks, and so is a `Mutex<Vec<Warning>>`, which collects them.
+pub trait WarningSink: Send + Sync {
+    fn warning(&self, warning: Warning);
+}
+
+impl<F: Fn(Warning) + Send + Sync> WarningSink for F {
+    fn warning(&self, warning: Warning) {
+        self(warning)
+    }
+}
+
+impl WarningSink for std::sync::Mutex<Vec<Warning>> {
+    fn warning(&self, warning: Warning) {
+        self.lock().expect("the warnings to not be poisoned").push(warning)
+    }
+}
+
+impl std::fmt::Debug for dyn WarningSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("WarningSink")
+    }
+}
+
+/// Conventions for making identifiers from YANG names, which apply to every identifier made from the name of a node,
+/// type, enum value or case. Names which would make the same identifier at a level, like "ip-address" and "IPAddress"
+/// with the abbreviation "ip", are made unique by numbering them. Rust modules are always in snake_case, as they name
+/// the files of the generated code, and constants are always in SCREAMING_SNAKE_CASE.
+///
+/// The default follows the Rust conventions, with PascalCase types and snake_case fields.
+#[derive(Debug, Clone)]
+pub struct Naming {
+    /// The case of type and variant names, like structs for containers and variants for enum values. It must be
+    /// PascalCase or SCREAMING_SNAKE_CASE, as types share their namespace with the snake_case Rust modules of nodes.
+    pub type_case: CaseConvention,
+
+    /// The case of field names.
+    pub field_case: CaseConvention,
+
+    /// Prefixes removed from the start of names when making type and variant names, like "openconfig-", so the root
+    /// struct of openconfig-interfaces is `Interfaces`. Names made of only a prefix are kept.
+    pub strip_prefixes: Vec<String>,
+
+    /// Words written in uppercase in PascalCase and camelCase names, like "ip" and "bgp", so ip-address becomes
+    /// `IPAddress` rather than `IpAddress`. They are matched ignoring case.
+    pub abbreviations: Vec<String>,
+}
+
+impl Default for Naming {
+    fn default() -> Self {
+        Self {
+            type_case: CaseConvention::Pascal,
+            field_case: CaseConvention::Snake,
+            strip_prefixes: Vec::new(),
+            abbreviations: Vec::new(),
         }
-    };
+    }
+}
+
+/// The case of identifiers made from YANG names, whose words are split at characters like "-" and where a lowercase
+/// letter is followed by an uppercase one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaseConvention {
+    /// Like `InterfaceState`.
+    Pascal,
+    /// Like `interfaceState`.
+    Camel,
+    /// Like `interface_state`.
+    Snake,
+    /// Like `INTERFACE_STATE`.
+    ScreamingSnake,
+}
+
+/// Maps typedefs to Rust types, which are used for the leaves of the typedefs, and of typedefs derived from them,
+/// instead of generated newtypes. The generated code uses the Rust type like a newtype of a typedef, so it must
+/// implement `Debug`, `Clone`, `Default`, serde's traits for its RFC 7951 encoding, and `Display` and `FromStr` for its
+/// string form, which is used by XML encoding, unions and defaults. It must implement `Ord` as well if it's the type of
+/// the key of a list generated as a map. Restrictions of the typedef aren't checked by the generated validation.
+///
+/// Maps from the qualified names of typedefs, like "ietf-inet-types:ipv4-address", to paths of Rust types implement
+/// it, so a map can be used as the type mapper.
+pub trait TypeMapper: std::fmt::Debug {
+    /// The path of the Rust type for the typedef, if it's mapped. The module is the name of the module defining the
+    /// typedef, like "ietf-yang-types", and the name is the typedef's name, like "date-and-time".
+    fn map_type(&self, module: &str, name: &str) -> Option<String>;
+}
+
+impl TypeMapper for HashMap<String, String> {
+    fn map_type(&self, module: &str, name: &str) -> Option<String> {
+        self.get(&format!("{}:{}", module, name)).cloned()
+    }
+}
+
+impl TypeMapper for BTreeMap<String, String> {
+    fn map_type(&self, module: &str, name: &str) -> Option<String> {
+        self.get(&format!("{}:{}", module, name)).cloned()
+    }
+}
+
+/// A field of a generated struct, with its definition and the initialization of the field in the Default impl.
+/// The XML code writes the field's elements, and reads a child element into the field if it belongs to the field.
+/// Leaf-lists only use their defaults when no values are given, so they are cleared before reading elements, and
+/// restored to their defaults afterwards if no values were read.
+/// The builder of the struct has a setter per field taking the value type, which is the field type without the Option
+/// of optional fields. Mandatory fields, named by their YANG name, have to be set before building.
+/// The validation checks the restrictions of the field's nodes, reporting errors at the path of the nodes.
+/// The cfg attribute is set when the field's node is guarded by if-features, and is applied to every part of the field.
+/// Fields of deprecated and obsolete nodes, and their setters, are marked as deprecated.
+/// The arbitrary strategy generates values of the field, when Arbitrary implementations are generated.
+struct Field {
+    name: syn::Ident,
+    cfg: TokenStream,
+    deprecated: TokenStream,
+    value_type: TokenStream,
+    optional: bool,
+    mandatory: Option<String>,
+    definition: TokenStream,
+    default: TokenStream,
+    xml_write: TokenStream,
+    xml_read: TokenStream,
+    xml_clear_defaults: TokenStream,
+    xml_restore_defaults: TokenStream,
+    validation: TokenStream,
+    arbitrary: TokenStream,
+}
+
+/// The generated types of an RPC or action, and the paths to them used by the method calling the operation.
+struct Operation {
+    module: TokenStream,
+    doc: String,
+    method_name: syn::Ident,
+    input: Option<TokenStream>,
+    output: TokenStream,
+}
+
+// The node is the parameter for the node an action is called on, which RPCs don't have.
+impl Operation {
+    fn method(&self, node: Option<&TokenStream>) -> TokenStream {
+        let Operation {
+            doc,
+            method_name,
+            output,
+            ..
+        } = self;
+        let input = self.input.iter();
+        let node = node.iter();
+        quote! {
+            #[doc = #doc]
+            fn #method_name(&self #(, #node)* #(, input: #input)*) -> Result<#output, Self::Error>;
+        }
+    }
+
+    fn async_method(&self, node: Option<&TokenStream>) -> TokenStream {
+        let Operation {
+            doc,
+            method_name,
+            output,
+            ..
+        } = self;
+        let input = self.input.iter();
+        let node = node.iter();
+        quote! {
+            #[doc = #doc]
+            fn #method_name(
+                &self
+                #(, #node)*
+                #(, input: #input)*
+            ) -> impl std::future::Future<Output = Result<#output, Self::Error>> + Send;
+        }
+    }
+
+    // Servers handle operations asynchronously, failing with an error carrying a NETCONF error-tag, at the path of
+    // the rpc_error module.
+    fn server_method(&self, node: Option<&TokenStream>, rpc_error: &TokenStream) -> TokenStream {
+        let Operation {
+            doc,
+            method_name,
+            output,
+            ..
+        } = self;
+        let input = self.input.iter();
+        let node = node.iter();
+        quote! {
+            #[doc = #doc]
+            fn #method_name(
+                &self
+                #(, #node)*
+                #(, input: #input)*
+            ) -> impl std::future::Future<Output = Result<#output, #rpc_error::RpcError>> + Send;
+        }
+    }
+}
 
-    let syntax_tree = syn::parse_file(&struct_def.to_string()).expect("Failed to parse generated code");
-    let formatted_code = prettyplease::unparse(&syntax_tree);
-    println!("{}", formatted_code);
+/// The types of the containers and lists of a grouping shared by the nodes using it, generated in a module within the
+/// groupings module. The names of the struct of each node, and of its key type if it's a list generated as a map, are
+/// stored by the node's name.
+struct SharedGrouping {
+    module_name: syn::Ident,
+    types: HashMap<String, (syn::Ident, Option<syn::Ident>)>,
+    items: Vec<TokenStream>,
 }
 
-fn generate_leaf(leaf: &Leaf) -> TokenStream {
-    let field_name = utils::sanitize_identifier(leaf.name.as_str());
-    let field_type = utils::yang_to_rust_type(leaf.type_info.name.as_str());
-    let doc = utils::format_docstring(&leaf.description);
+/// A grouping whose types are shared, identified by its path qualified with its module name, whether its nodes are
+/// state data, and the module whose namespace its nodes are in.
+type GroupingKey = (String, bool, Symbol);
+
+/// A typedef referenced by the generated code, identified by the name of the module it is defined in and its path
+/// within that module.
+type TypeDefKey = (String, String);
+
+struct CodeGenerator<'a> {
+    module: &'a Module,
+    config: &'a Config,
+
+    // Properties used during generation.
+    // depth is the number of Rust modules enclosing the items currently being generated. It is used to build paths
+    // to the types generated at the top level, like typedefs.
+    depth: usize,
+
+    // type_defs stores the typedefs referenced so far. Only referenced typedefs are generated, after the data nodes,
+    // unless all_type_defs is set for a module imported by other generated modules, which generates every top-level
+    // typedef of the module.
+    type_defs: BTreeSet<TypeDefKey>,
+    all_type_defs: bool,
+
+    // uses_string_encoding is set when a field needs the serde helpers for values encoded as JSON strings, and
+    // uses_index_map when a list is generated as an IndexMap, which has its own serde helpers.
+    uses_string_encoding: bool,
+    uses_index_map: bool,
+
+    // uses_operations is set when the module has RPCs or actions, whose server traits use the rpc_error module.
+    uses_operations: bool,
 
-    quote! {
-        #[doc = #doc]
-        pub #field_name: #field_type,
+    // module_path is the path from the root to the Rust module currently being generated, and schema_path the names of
+    // the nodes enclosing it, with their keys for lists. They are used to refer to nested notifications from the root,
+    // and for the paths of the generated types.
+    module_path: Vec<syn::Ident>,
+    schema_path: Vec<(String, Vec<String>)>,
+
+    // notifications stores the schema path and the Rust path of every notification generated so far.
+    notifications: Vec<(String, TokenStream)>,
+
+    // names stores the unique names of the sibling nodes being generated, by their YANG name, for each level of the
+    // tree. Identifiers of nodes are made from their unique names, so siblings never get the same identifier.
+    names: Vec<HashMap<String, String>>,
+
+    // state is set while generating the descendants of a node with config false, which are state data.
+    state: bool,
+
+    // node_module is the module defining the data node whose descendants are being generated. It's the generated
+    // module, except within nodes added by augments of other modules, whose descendants are in the namespace of the
+    // other module.
+    node_module: Symbol,
+
+    // groupings stores the shared types of groupings when share_groupings is set. sharing is cleared while generating
+    // the config and state trees, whose nodes are filtered, and shared is set while generating shared types, which
+    // don't get the paths of a single node.
+    groupings: BTreeMap<GroupingKey, SharedGrouping>,
+    sharing: bool,
+    shared: bool,
+
+    // union_member is set while generating the types of union members, which don't take unknown values.
+    union_member: bool,
+
+    // warnings stores the warnings reported to the sink of the config, so each is reported once.
+    warnings: BTreeSet<Warning>,
+}
+
+impl<'a> CodeGenerator<'a> {
+    fn new(module: &'a Module, config: &'a Config) -> Self {
+        Self {
+            module,
+            config,
+            depth: 0,
+            type_defs: BTreeSet::new(),
+            all_type_defs: false,
+            uses_string_encoding: false,
+            uses_index_map: false,
+            uses_operations: false,
+            module_path: Vec::new(),
+            schema_path: Vec::new(),
+            notifications: Vec::new(),
+            names: Vec::new(),
+            state: false,
+            node_module: module.name.clone(),
+            groupings: BTreeMap::new(),
+            sharing: config.share_groupings,
+            shared: false,
+            union_member: false,
+            warnings: BTreeSet::new(),
+        }
     }
+
+    fn warn(&mut self, warning: Warning) {
+        if let Some(sink) = &self.config.warnings {
+            if self.warnings.insert(warning.clone()) {
+                sink.warning(warning);
+            }
+        }
+    }
+
+    // Enter a level of the tree with the sibling nodes of the names. The generated helper modules are at the top level,
+    // so top-level nodes don't get their names.
+    fn push_names<'n>(&mut self, names: impl IntoIterator<Item = &'n str> + Clone) {
+        let reserved: &[&str] = match (self.names.is_empty(), self.config.share_groupings) {
+            (true, true) => &["groupings", "notifications", "rfc7951", "rpcs", "validation", "xml"],
+            (true, false) => &["notifications", "rfc7951", "rpcs", "validation", "xml"],
+            (false, _) => &[],
+        };
+        let unique_names = self
+            .config
+            .naming
+            .unique_names(reserved.iter().copied().chain(names.clone()));
+        let unique_names = unique_names.into_iter().skip(reserved.len());
+        self.names
+            .push(names.into_iter().map(String::from).zip(unique_names).collect());
+    }
+
+    // The names of the nodes at a level of the tree. The key types of lists generated as maps are named after the list,
+    // and come after the nodes, so they don't change the names of nodes.
+    fn level_names(&self, data_defs: &[DataDef]) -> Vec<String> {
+        let key_names = data_defs.iter().filter_map(|data_def| match data_def {
+            DataDef::List(list) if self.map_kind(list).is_some() => Some(utils::key_name(&list.name)),
+            _ => None,
+        });
+        data_defs
+            .iter()
+            .map(|data_def| utils::data_def_name(data_def).to_string())
+            .chain(key_names)
+            .collect()
+    }
+
+    // The unique name of a node at the current level of the tree, from which its identifiers are made.
+    fn node_name(&self, name: &str) -> String {
+        self.names
+            .last()
+            .and_then(|names| names.get(name))
+            .cloned()
+            .unwrap_or_else(|| name.to_string())
+    }
+
+    // The doc comment of a data node, with its description followed by the details documenting the node, like its
+    // reference, units, default and constraints. Nodes of state data are marked as such.
+    fn node_doc(&self, data_def: &DataDef) -> String {
+        let config =
+            |config: &Option<bool>| (self.state || *config == Some(false)).then(|| "false (state data)".into());
+        let flag = |value: &Option<bool>| (*value == Some(true)).then(|| "true".to_string());
+        let elements = |min_elements: &Option<i64>, max_elements: &Option<MaxElements>| {
+            [
+                (
+                    "Min elements",
+                    min_elements.filter(|min| *min > 0).map(|min| min.to_string()),
+                ),
+                (
+                    "Max elements",
+                    match max_elements {
+                        Some(MaxElements::Value(max)) => Some(max.to_string()),
+                        _ => None,
+                    },
+                ),
+            ]
+        };
+        let ordered_by = |ordered_by: &Option<OrderedBy>| match ordered_by {
+            Some(OrderedBy::User) => Some("user".to_string()),
+            _ => None,
+        };
+
+        let (description, details) = match data_def {
+            DataDef::Container(container) => {
+                // The presence statement describes what the existence of a presence container means.
+                let mut details = vec![
+                    ("Presence", container.meta.presence.clone()),
+                    ("Config", config(&container.config)),
+                ];
+                details.extend(utils::condition_details(container.when.as_deref(), &container.must));
+                details.push(("Reference", container.meta.reference.clone()));
+                (&container.meta.description, details)
+            }
+            DataDef::Leaf(leaf) => {
+                let mut details = utils::type_details(&leaf.type_info);
+                details.extend([
+                    ("Units", leaf.meta.units.clone()),
+                    ("Default", leaf.default.as_deref().map(utils::doc_code)),
+                    ("Mandatory", flag(&leaf.mandatory)),
+                    ("Config", config(&leaf.config)),
+                ]);
+                details.extend(utils::condition_details(leaf.when.as_deref(), &leaf.must));
+                details.push(("Reference", leaf.meta.reference.clone()));
+                (&leaf.meta.description, details)
+            }
+            DataDef::LeafList(leaf_list) => {
+                let mut details = utils::type_details(&leaf_list.type_info);
+                let defaults: Vec<String> = leaf_list
+                    .default
+                    .iter()
+                    .map(|default| utils::doc_code(default))
+                    .collect();
+                details.extend([
+                    ("Units", leaf_list.meta.units.clone()),
+                    ("Defaults", (!defaults.is_empty()).then(|| defaults.join(", "))),
+                ]);
+                details.extend(elements(&leaf_list.min_elements, &leaf_list.max_elements));
+                details.extend([
+                    ("Ordered by", ordered_by(&leaf_list.ordered_by)),
+                    ("Config", config(&leaf_list.config)),
+                ]);
+                details.extend(utils::condition_details(leaf_list.when.as_deref(), &leaf_list.must));
+                details.push(("Reference", leaf_list.meta.reference.clone()));
+                (&leaf_list.meta.description, details)
+            }
+            DataDef::List(list) => {
+                let mut details = vec![("Key", list.key.clone())];
+                details.extend(list.unique.iter().map(|unique| ("Unique", Some(unique.clone()))));
+                details.extend(elements(&list.min_elements, &list.max_elements));
+                details.extend([
+                    ("Ordered by", ordered_by(&list.ordered_by)),
+                    ("Config", config(&list.config)),
+                ]);
+                details.extend(utils::condition_details(list.when.as_deref(), &list.must));
+                details.push(("Reference", list.meta.reference.clone()));
+                (&list.meta.description, details)
+            }
+            DataDef::Choice(choice) => {
+                let details = vec![
+                    ("Default case", choice.default.clone()),
+                    ("Mandatory", flag(&choice.mandatory)),
+                    ("Config", config(&choice.config)),
+                    (
+                        "When",
+                        choice.when.as_ref().map(|when| utils::doc_code(&when.condition)),
+                    ),
+                    ("Reference", choice.meta.reference.clone()),
+                ];
+                (&choice.meta.description, details)
+            }
+            DataDef::AnyData(anydata) => {
+                let mut details = vec![
+                    ("Mandatory", flag(&anydata.mandatory)),
+                    ("Config", config(&anydata.config)),
+                ];
+                details.extend(utils::condition_details(anydata.when.as_deref(), &anydata.must));
+                details.push(("Reference", anydata.meta.reference.clone()));
+                (&anydata.meta.description, details)
+            }
+            DataDef::Anyxml(anyxml) => {
+                let mut details = vec![
+                    ("Mandatory", flag(&anyxml.mandatory)),
+                    ("Config", config(&anyxml.config)),
+                ];
+                details.extend(utils::condition_details(anyxml.when.as_deref(), &anyxml.must));
+                details.push(("Reference", anyxml.meta.reference.clone()));
+                (&anyxml.meta.description, details)
+            }
+            DataDef::Uses(_) => return String::new(),
+        };
+
+        utils::format_doc(description, &details)
+    }
+
+    fn generate(&mut self) -> TokenStream {
+        let data_defs: Vec<DataDef> = self
+            .module
+            .body
+            .iter()
+            .filter_map(|node| match node {
+                SchemaNode::DataDef(data_def) => Some(data_def.clone()),
+                _ => None,
+            })
+            .collect();
+        let meta = &self.module.meta;
+        let doc = utils::format_doc(
+            &meta.description,
+            &[
+                ("Organization", meta.organization.clone()),
+                ("Reference", meta.reference.clone()),
+            ],
+        );
+        let root = self.generate_root(&self.module.name, &doc, &data_defs);
+        let items = self.generate_child_types(&data_defs);
+        let notifications = self.generate_notifications();
+        let config_state = match self.config.config_state {
+            true => {
+                let config = self.generate_config_state(&data_defs, true);
+                let state = self.generate_config_state(&data_defs, false);
+                quote! { #config #state }
+            }
+            false => quote! {},
+        };
+        let rpcs = self.generate_rpcs();
+        let groupings = self.generate_groupings();
+        let type_defs = self.generate_type_defs();
+        let string_encoding = match self.uses_string_encoding {
+            true => generate_string_encoding(self.uses_index_map),
+            false => quote! {},
+        };
+        let xml = match self.xml() {
+            true => generate_xml_helpers(),
+            false => quote! {},
+        };
+
+        let arbitrary = match self.proptest() {
+            true => generate_arbitrary_helpers(),
+            false => quote! {},
+        };
+        let rpc_error = match self.uses_operations {
+            true => generate_rpc_error(),
+            false => quote! {},
+        };
+        let validation = generate_validation();
+
+        let tokens = quote! {
+            #root
+            #(#items)*
+            #notifications
+            #config_state
+            #rpcs
+            #groupings
+            #type_defs
+            #string_encoding
+            #xml
+            #arbitrary
+            #rpc_error
+            #validation
+        };
+
+        match self.config.no_std {
+            true => no_std(tokens),
+            false => tokens,
+        }
+    }
+
+    // XML encoding is generated if it's enabled, except for no_std crates, as quick-xml needs std.
+    fn xml(&self) -> bool {
+        self.config.xml && !self.config.no_std
+    }
+
+    // Arbitrary implementations are generated if they are enabled, except for no_std crates, as proptest's regex
+    // strategies need std.
+    fn proptest(&self) -> bool {
+        self.config.proptest && !self.config.no_std
+    }
+}
+
+// The header of the generated code, naming the module it's generated from. Revisions are listed newest first, but the
+// latest date is used in case they aren't.
+fn header(module: &Module) -> String {
+    let revision = module.revisions.iter().map(|revision| &revision.date).max();
+    match revision {
+        Some(revision) => format!(
+            "// Generated by yang-codegen from YANG module {}, revision {}. Do not edit.\n\n",
+            module.name, revision
+        ),
+        None => format!(
+            "// Generated by yang-codegen from YANG module {}. Do not edit.\n\n",
+            module.name
+        ),
+    }
+}
+
+fn format_code(tokens: TokenStream) -> String {
+    prettyplease::unparse(&parse_code(tokens))
+}
+
+fn parse_code(tokens: TokenStream) -> syn::File {
+    syn::parse_file(&tokens.to_string()).expect("Failed to parse generated code")
 }
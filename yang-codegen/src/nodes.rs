@@ -0,0 +1,751 @@
+//! The structs of containers, lists and choices, with the modules of their child types, the shared types of groupings,
+//! and the config and state trees.
+
+use std::collections::HashMap;
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use yang_parser::model::*;
+
+use crate::{
+    structs::{allow_deprecated, generate_struct},
+    utils, CodeGenerator, Field, Operation, SharedGrouping,
+};
+
+impl<'a> CodeGenerator<'a> {
+    // The root struct holds the top-level data nodes of the module, and is named after the module. If a top-level node
+    // already has that name, "Module" is added to the name of the root struct.
+    pub(crate) fn generate_root(&mut self, name: &str, doc: &str, data_defs: &[DataDef]) -> TokenStream {
+        let mut struct_name = self.config.naming.type_identifier(name);
+        let name_taken = data_defs.iter().any(|data_def| match data_def {
+            DataDef::AnyData(_) | DataDef::Anyxml(_) | DataDef::Uses(_) => false,
+            _ => self.config.naming.type_identifier(utils::data_def_name(data_def)) == struct_name,
+        });
+        if name_taken {
+            struct_name = self.config.naming.type_identifier(&format!("{}-module", name));
+        }
+
+        let struct_fields = self.generate_fields(None, data_defs, &[]);
+        let struct_type = generate_struct(&struct_name, doc, self.struct_attributes(&struct_name), &struct_fields);
+        let xml_impl = self.generate_struct_impls(&struct_name, &struct_fields);
+
+        // The top-level elements carry the namespace of the module, like the contents of a NETCONF <config> element.
+        let xml_methods = match self.xml() {
+            true => {
+                let xml = self.xml_path();
+                let namespace = &self.module.namespace;
+                quote! {
+                    impl #struct_name {
+                        /// XML namespace of the module.
+                        pub const NAMESPACE: &'static str = #namespace;
+
+                        /// Encode the data nodes as XML elements, qualified with the namespace of the module.
+                        pub fn to_xml(&self) -> Result<String, String> {
+                            let mut writer = quick_xml::Writer::new(Vec::new());
+                            #xml::XmlNode::write_children(self, &mut writer, Some(Self::NAMESPACE))?;
+                            String::from_utf8(writer.into_inner()).map_err(|e| e.to_string())
+                        }
+
+                        /// Decode data nodes from XML elements. Elements which aren't data nodes of the module are
+                        /// skipped.
+                        pub fn from_xml(xml: &str) -> Result<Self, String> {
+                            let mut reader = quick_xml::Reader::from_str(xml);
+                            let mut value = Self::default();
+                            #xml::read_children(&mut reader, &mut value)?;
+                            Ok(value)
+                        }
+                    }
+                }
+            }
+            false => quote! {},
+        };
+
+        quote! {
+            #struct_type
+            #xml_impl
+            #xml_methods
+        }
+    }
+
+    // The config or state part of the tree is generated in its own Rust module, named after its root struct, like
+    // "module_config" with the "ModuleConfig" root struct. Typedefs are shared with the complete tree.
+    pub(crate) fn generate_config_state(&mut self, data_defs: &[DataDef], config: bool) -> TokenStream {
+        let (name, doc) = match config {
+            true => (format!("{}-config", self.module.name), "Config data of the module."),
+            false => (format!("{}-state", self.module.name), "State data of the module."),
+        };
+        let module_name = utils::sanitize_identifier(&name);
+        let data_defs = utils::filter_config(data_defs, true, config);
+
+        self.depth += 1;
+        let sharing = std::mem::replace(&mut self.sharing, false);
+        let root = self.generate_root(&name, doc, &data_defs);
+        let items = self.generate_child_types(&data_defs);
+        self.sharing = sharing;
+        self.depth -= 1;
+
+        quote! {
+            #[doc = #doc]
+            pub mod #module_name {
+                #root
+                #(#items)*
+            }
+        }
+    }
+
+    // Every node which becomes a struct or an enum also gets a Rust module with the same name, containing the types of
+    // its child nodes. This mirrors the schema hierarchy and avoids name clashes between nodes with the same name in
+    // different parts of the tree.
+    pub(crate) fn generate_child_types(&mut self, data_defs: &[DataDef]) -> Vec<TokenStream> {
+        let module: &'a Module = self.module;
+        let data_defs = self.generated_data_defs(data_defs);
+        let names = self.level_names(&data_defs);
+        self.push_names(names.iter().map(String::as_str));
+        let child_types = data_defs
+            .iter()
+            .filter_map(|data_def| match data_def {
+                _ if self.sharing && utils::shared_grouping(data_def).is_some() => {
+                    let grouping = utils::shared_grouping(data_def).expect("the node to be shared");
+                    Some(self.generate_shared(data_def, grouping))
+                }
+                DataDef::Container(container) => Some(self.generate_container(container, &self.node_doc(data_def))),
+                DataDef::List(list) => Some(self.generate_list(list, &self.node_doc(data_def))),
+                DataDef::Choice(choice) => Some(self.generate_choice(choice, &self.node_doc(data_def))),
+                DataDef::Leaf(leaf) => {
+                    let name = self.node_name(&leaf.name);
+                    let type_info = utils::resolved_type(&leaf.type_info);
+                    self.generate_leaf_type(&name, type_info, &leaf.meta.description, None, &module.name)
+                }
+                DataDef::LeafList(leaf_list) => {
+                    let name = self.node_name(&leaf_list.name);
+                    let type_info = utils::resolved_type(&leaf_list.type_info);
+                    self.generate_leaf_type(&name, type_info, &leaf_list.meta.description, None, &module.name)
+                }
+                _ => None,
+            })
+            .collect();
+        self.names.pop();
+
+        child_types
+    }
+
+    // The types of a container or list used from a grouping are generated once for the grouping, as if the node was a
+    // top-level node of the grouping's module within the groupings module, and re-exported with the node's names.
+    pub(crate) fn generate_shared(&mut self, data_def: &DataDef, grouping: &str) -> TokenStream {
+        let name = utils::data_def_name(data_def);
+        let key = (grouping.to_string(), self.state, self.node_module.clone());
+        let generated = self
+            .groupings
+            .get(&key)
+            .is_some_and(|shared| shared.types.contains_key(name));
+        if !generated {
+            if !self.groupings.contains_key(&key) {
+                let grouping_name = grouping.rsplit('/').next().unwrap_or(grouping);
+                let base_name = match self.state {
+                    true => format!("{}-state", grouping_name),
+                    false => grouping_name.to_string(),
+                };
+                let mut module_name = utils::sanitize_identifier(&base_name);
+                for index in 2.. {
+                    if self.groupings.values().all(|shared| shared.module_name != module_name) {
+                        break;
+                    }
+                    module_name = utils::sanitize_identifier(&format!("{}-{}", base_name, index));
+                }
+                let shared = SharedGrouping {
+                    module_name,
+                    types: HashMap::new(),
+                    items: Vec::new(),
+                };
+                self.groupings.insert(key.clone(), shared);
+            }
+
+            let module_name = self.groupings[&key].module_name.clone();
+            let depth = std::mem::replace(&mut self.depth, 2);
+            let schema_path = std::mem::take(&mut self.schema_path);
+            let module_path = std::mem::replace(
+                &mut self.module_path,
+                vec![utils::sanitize_identifier("groupings"), module_name],
+            );
+            let shared = std::mem::replace(&mut self.shared, true);
+            let names = self.level_names(std::slice::from_ref(data_def));
+            self.push_names(names.iter().map(String::as_str));
+            let doc = self.node_doc(data_def);
+            let (item, key_type) = match data_def {
+                DataDef::Container(container) => (self.generate_container(container, &doc), None),
+                DataDef::List(list) => {
+                    let key_type = self.map_kind(list).map(|_| {
+                        self.config
+                            .naming
+                            .type_identifier(&self.node_name(&utils::key_name(name)))
+                    });
+                    (self.generate_list(list, &doc), key_type)
+                }
+                _ => unreachable!("only containers and lists are shared"),
+            };
+            let struct_name = self.config.naming.type_identifier(&self.node_name(name));
+            self.names.pop();
+            self.shared = shared;
+            self.module_path = module_path;
+            self.schema_path = schema_path;
+            self.depth = depth;
+
+            let shared = self.groupings.get_mut(&key).expect("the grouping was added above");
+            shared.types.insert(name.to_string(), (struct_name, key_type));
+            shared.items.push(item);
+        }
+
+        let shared = &self.groupings[&key];
+        let supers = std::iter::repeat_n(quote! { super:: }, self.depth);
+        let module_name = &shared.module_name;
+        let module_path = quote! { #(#supers)* groupings::#module_name };
+        let (struct_name, key_type) = &shared.types[name];
+        let local_names = [
+            Some(self.config.naming.type_identifier(&self.node_name(name))),
+            key_type.as_ref().map(|_| {
+                self.config
+                    .naming
+                    .type_identifier(&self.node_name(&utils::key_name(name)))
+            }),
+        ];
+        let uses = [Some(struct_name), key_type.as_ref()]
+            .into_iter()
+            .zip(local_names)
+            .filter_map(|(shared_name, local_name)| match (shared_name?, local_name?) {
+                (shared_name, local_name) if *shared_name == local_name => Some(quote! { #shared_name }),
+                (shared_name, local_name) => Some(quote! { #shared_name as #local_name }),
+            });
+
+        quote! {
+            #(pub use #module_path::#uses;)*
+        }
+    }
+
+    // The modules of the shared types of groupings, within the groupings module.
+    pub(crate) fn generate_groupings(&self) -> TokenStream {
+        if self.groupings.is_empty() {
+            return quote! {};
+        }
+
+        let modules = self.groupings.values().map(|shared| {
+            let SharedGrouping { module_name, items, .. } = shared;
+            quote! {
+                pub mod #module_name {
+                    #(#items)*
+                }
+            }
+        });
+
+        quote! {
+            /// Types of the containers and lists of groupings, shared by the nodes using the groupings.
+            pub mod groupings {
+                #(#modules)*
+            }
+        }
+    }
+
+    // Actions of the node are generated in its child module as well, and returned to be generated as methods of the node.
+    // So are the node's notifications.
+    pub(crate) fn generate_child_module(
+        &mut self,
+        name: &str,
+        data_defs: &[DataDef],
+        actions: &[Action],
+        notifications: &[Notification],
+    ) -> (TokenStream, Vec<Operation>) {
+        let module_name = utils::sanitize_identifier(&self.node_name(name));
+        self.depth += 1;
+        self.module_path.push(module_name.clone());
+        let child_types = self.generate_child_types(data_defs);
+        // Notifications and the input and output of actions aren't state data, even within state data.
+        let state = std::mem::replace(&mut self.state, false);
+        let notification_structs: Vec<TokenStream> = notifications
+            .iter()
+            .map(|notification| self.generate_notification(notification))
+            .collect();
+        let operations: Vec<Operation> = actions
+            .iter()
+            .map(|action| {
+                let doc = utils::format_doc(&action.description, &[("Reference", action.reference.clone())]);
+                let path_prefix = quote! { #module_name:: };
+                self.generate_operation(&action.name, "action", doc, &action.input, &action.output, path_prefix)
+            })
+            .collect();
+        self.state = state;
+        self.module_path.pop();
+        self.depth -= 1;
+
+        if child_types.is_empty() && operations.is_empty() && notification_structs.is_empty() {
+            return (quote! {}, operations);
+        }
+
+        let action_modules = operations.iter().map(|operation| &operation.module);
+        let child_module = quote! {
+            pub mod #module_name {
+                #(#child_types)*
+                #(#action_modules)*
+                #(#notification_structs)*
+            }
+        };
+
+        (child_module, operations)
+    }
+
+    pub(crate) fn generate_container(&mut self, container: &Container, doc: &str) -> TokenStream {
+        let struct_name = self.config.naming.type_identifier(&self.node_name(&container.name));
+        let path_name = utils::qualified_name(&container.name, container.meta.module.as_ref(), &self.node_module);
+        let state = self.state;
+        self.state |= container.config == Some(false);
+        let node_module = self.node_module.clone();
+        self.node_module
+            .clone_from(container.meta.module.as_ref().unwrap_or(&node_module));
+        let struct_fields = self.generate_fields(Some(&container.name), &container.data_defs, &[]);
+        self.schema_path.push((path_name, Vec::new()));
+        let paths = self.generate_paths();
+        let (child_module, operations) = self.generate_child_module(
+            &container.name,
+            &container.data_defs,
+            &container.actions,
+            &container.notifications,
+        );
+        self.schema_path.pop();
+        self.state = state;
+        self.node_module = node_module;
+        let struct_type = generate_struct(&struct_name, doc, self.struct_attributes(&struct_name), &struct_fields);
+        let xml_impl = self.generate_struct_impls(&struct_name, &struct_fields);
+        let actions = self.generate_actions(&container.name, &struct_name, &operations);
+
+        quote! {
+            #struct_type
+            #xml_impl
+            #actions
+
+            impl #struct_name {
+                #paths
+            }
+
+            #child_module
+        }
+    }
+
+    pub(crate) fn generate_list(&mut self, list: &List, doc: &str) -> TokenStream {
+        let struct_name = self.config.naming.type_identifier(&self.node_name(&list.name));
+        let keys = utils::list_keys(list);
+
+        // Key leaves are placed first, in the order they appear in the key statement.
+        let mut data_defs = list.data_defs.clone();
+        data_defs.sort_by_key(|data_def| match data_def {
+            DataDef::Leaf(leaf) => keys.iter().position(|key| *key == leaf.name).unwrap_or(keys.len()),
+            _ => keys.len(),
+        });
+        let path_name = utils::qualified_name(&list.name, list.meta.module.as_ref(), &self.node_module);
+        let state = self.state;
+        self.state |= list.config == Some(false);
+        let node_module = self.node_module.clone();
+        self.node_module
+            .clone_from(list.meta.module.as_ref().unwrap_or(&node_module));
+        let struct_fields = self.generate_fields(Some(&list.name), &data_defs, &keys);
+        self.schema_path.push((path_name, keys.clone()));
+        let paths = self.generate_paths();
+        let (child_module, operations) =
+            self.generate_child_module(&list.name, &data_defs, &list.actions, &list.notifications);
+        self.schema_path.pop();
+        self.state = state;
+        self.node_module = node_module;
+        let struct_type = generate_struct(&struct_name, doc, self.struct_attributes(&struct_name), &struct_fields);
+        let xml_impl = self.generate_struct_impls(&struct_name, &struct_fields);
+        let actions = self.generate_actions(&list.name, &struct_name, &operations);
+        let key_type = match self.map_kind(list) {
+            Some(_) => self.generate_key_type(&list.name, &struct_name, &struct_fields[..keys.len()]),
+            None => quote! {},
+        };
+        let list_key = match self.proptest() && !keys.is_empty() {
+            true => {
+                let arbitrary = self.arbitrary_path();
+                let key_fields = &struct_fields[..keys.len()];
+                let names = key_fields.iter().map(|field| &field.name);
+                let allow_deprecated = allow_deprecated(key_fields);
+                quote! {
+                    #allow_deprecated
+                    impl #arbitrary::ListKey for #struct_name {
+                        fn key_values(&self) -> Vec<String> {
+                            vec![#(format!("{:?}", self.#names)),*]
+                        }
+                    }
+                }
+            }
+            false => quote! {},
+        };
+
+        quote! {
+            #struct_type
+            #xml_impl
+            #actions
+            #key_type
+            #list_key
+
+            impl #struct_name {
+                /// Names of the key leaves, in the order of the list's key statement.
+                pub const KEYS: &'static [&'static str] = &[#(#keys),*];
+
+                #paths
+            }
+
+            #child_module
+        }
+    }
+
+    // The key of a list generated as a map is the value of its key leaf, or a struct with the values of its key leaves.
+    pub(crate) fn generate_key_type(
+        &mut self,
+        list_name: &str,
+        entry_type: &syn::Ident,
+        key_fields: &[Field],
+    ) -> TokenStream {
+        let key_type = self
+            .config
+            .naming
+            .type_identifier(&self.node_name(&utils::key_name(list_name)));
+        let rfc7951 = self.rfc7951_path();
+        let names: Vec<&syn::Ident> = key_fields.iter().map(|field| &field.name).collect();
+        let (definition, key) = match key_fields {
+            [field] => {
+                let doc = format!(" The key of a [{}].", entry_type);
+                let Field { name, value_type, .. } = field;
+                (
+                    quote! {
+                        #[doc = #doc]
+                        pub type #key_type = #value_type;
+                    },
+                    quote! { self.#name.clone() },
+                )
+            }
+            _ => {
+                let doc = format!(" The key of a [{}], with the values of its key leaves.", entry_type);
+                let value_types = key_fields.iter().map(|field| &field.value_type);
+                let derived = ["Debug", "Clone", "PartialEq", "Eq", "PartialOrd", "Ord", "Hash"];
+                let attributes = self.type_attributes(&key_type, &derived);
+                (
+                    quote! {
+                        #[doc = #doc]
+                        #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+                        #attributes
+                        pub struct #key_type {
+                            #(pub #names: #value_types,)*
+                        }
+                    },
+                    quote! { #key_type { #(#names: self.#names.clone(),)* } },
+                )
+            }
+        };
+
+        quote! {
+            #definition
+
+            impl #rfc7951::ListEntry for #entry_type {
+                type Key = #key_type;
+
+                fn key(&self) -> Self::Key {
+                    #key
+                }
+            }
+        }
+    }
+
+    // The map type of a list generated as a map, if it is one. Only lists whose key leaves all have ordered types are
+    // generated as maps.
+    pub(crate) fn map_kind(&self, list: &List) -> Option<&'static str> {
+        let keys = utils::list_keys(list);
+        let orderable_keys = keys.iter().all(|key| {
+            list.data_defs.iter().any(|data_def| match data_def {
+                DataDef::Leaf(leaf) => leaf.name == *key && self.orderable(&leaf.type_info, &self.module.name),
+                _ => false,
+            })
+        });
+        if !self.config.list_maps || keys.is_empty() || !orderable_keys {
+            return None;
+        }
+
+        match list.ordered_by {
+            Some(OrderedBy::User) if self.config.no_std => None,
+            Some(OrderedBy::User) => Some("indexmap::IndexMap"),
+            // The map type is also used in serde attributes, whose paths aren't changed for no_std crates.
+            _ if self.config.no_std => Some("alloc::collections::BTreeMap"),
+            _ => Some("std::collections::BTreeMap"),
+        }
+    }
+
+    // Whether the Rust type of the type is ordered and hashable, so it can be a key of a map. Floats aren't, and
+    // neither are unions.
+    pub(crate) fn orderable(&self, type_info: &TypeInfo, module_name: &str) -> bool {
+        let type_info = utils::resolved_type(type_info);
+        match self.find_type_def(&type_info.name, module_name) {
+            Some((type_module, _, type_def)) => self.orderable(&type_def.type_info, &type_module),
+            None => match type_info.name.as_str() {
+                "decimal64" => self.config.rust_decimal,
+                "union" => false,
+                _ => true,
+            },
+        }
+    }
+
+    // The schema path of the current node, with the module name as the prefix of the top-level node, and the format
+    // string of the paths of its instances, with a placeholder for the value of each key of the node and the lists
+    // leading to it, like "/module:interfaces/interface[name={}]".
+    pub(crate) fn path_strings(&self) -> (String, String) {
+        let names: Vec<&str> = self.schema_path.iter().map(|(name, _)| name.as_str()).collect();
+        let segments: Vec<String> = self
+            .schema_path
+            .iter()
+            .map(|(name, keys)| {
+                let predicates: String = keys.iter().map(|key| format!("[{}={{}}]", key)).collect();
+                format!("{}{}", name, predicates)
+            })
+            .collect();
+
+        (
+            format!("/{}:{}", self.module.name, names.join("/")),
+            format!("/{}:{}", self.module.name, segments.join("/")),
+        )
+    }
+
+    // The schema path of a container or list, and a function building the path of an instance of it from the key
+    // values of the lists leading to it, if there are any. The parameters are named after the lists, without the
+    // module of lists added by augments, and their keys.
+    pub(crate) fn generate_paths(&self) -> TokenStream {
+        if self.shared {
+            return quote! {};
+        }
+
+        let (schema_path, instance_format) = self.path_strings();
+        let key_names: Vec<String> = self
+            .schema_path
+            .iter()
+            .flat_map(|(name, keys)| {
+                let name = name.split_once(':').map_or(name.as_str(), |(_, name)| name);
+                keys.iter().map(move |key| format!("{}-{}", name, key))
+            })
+            .collect();
+        let params: Vec<syn::Ident> = utils::unique_names(key_names.iter().map(String::as_str))
+            .iter()
+            .map(|name| utils::sanitize_identifier(name))
+            .collect();
+        let instance_path = match params.is_empty() {
+            true => quote! {},
+            false => quote! {
+                /// Path of an instance of the node, with the key values of the lists leading to it, and its own if
+                /// it's a list. The values are formatted with their Display implementations.
+                pub fn instance_path(#(#params: impl std::fmt::Display),*) -> String {
+                    format!(#instance_format, #(#params),*)
+                }
+            },
+        };
+
+        quote! {
+            /// Schema path of the node, with the module name as the prefix of the top-level node, as in gNMI and
+            /// RESTCONF paths.
+            pub const SCHEMA_PATH: &'static str = #schema_path;
+
+            #instance_path
+        }
+    }
+
+    // A choice becomes an enum with one variant per case, where each case is a struct holding the case's nodes.
+    // The case structs are placed in the choice's child module.
+    pub(crate) fn generate_choice(&mut self, choice: &Choice, doc: &str) -> TokenStream {
+        let enum_name = self.config.naming.type_identifier(&self.node_name(&choice.name));
+        let module_name = utils::sanitize_identifier(&self.node_name(&choice.name));
+
+        let cases: Vec<(String, Option<String>, Vec<DataDef>)> = choice.cases.iter().map(utils::case_parts).collect();
+        let case_variants = utils::case_variants(choice, &self.config.naming);
+        let case_members: Vec<Vec<String>> = cases
+            .iter()
+            .map(|(_, _, data_defs)| self.case_members(data_defs))
+            .collect();
+        let variants = cases
+            .iter()
+            .zip(&case_variants)
+            .map(|((_, description, _), variant_name)| {
+                let doc = utils::format_docstring(description);
+                quote! {
+                    #[doc = #doc]
+                    #variant_name(#module_name::#variant_name),
+                }
+            });
+        self.depth += 1;
+        self.module_path.push(module_name.clone());
+        self.push_names(cases.iter().map(|(name, _, _)| name.as_str()));
+        let state = self.state;
+        self.state |= choice.config == Some(false);
+        let case_structs: Vec<TokenStream> = cases
+            .iter()
+            .map(|(name, description, data_defs)| self.generate_case(name, description, data_defs))
+            .collect();
+        self.state = state;
+        self.names.pop();
+        self.module_path.pop();
+        self.depth -= 1;
+
+        // The elements of a choice are the elements of the selected case.
+        let xml_impl = match self.xml() {
+            true => {
+                let xml = self.xml_path();
+                let variant_names = &case_variants;
+                quote! {
+                    impl #xml::XmlNode for #enum_name {
+                        fn write_children<W: std::io::Write>(
+                            &self,
+                            writer: &mut quick_xml::Writer<W>,
+                            namespace: Option<&str>,
+                        ) -> Result<(), String> {
+                            match self {
+                                #(Self::#variant_names(case) => #xml::XmlNode::write_children(case, writer, namespace),)*
+                            }
+                        }
+
+                        fn read_child(
+                            &mut self,
+                            reader: &mut quick_xml::Reader<&[u8]>,
+                            start: &quick_xml::events::BytesStart,
+                            empty: bool,
+                        ) -> Result<bool, String> {
+                            match self {
+                                #(Self::#variant_names(case) => #xml::XmlNode::read_child(case, reader, start, empty),)*
+                            }
+                        }
+
+                        fn clear_defaults(&mut self) {
+                            match self {
+                                #(Self::#variant_names(case) => #xml::XmlNode::clear_defaults(case),)*
+                            }
+                        }
+
+                        fn restore_defaults(&mut self) {
+                            match self {
+                                #(Self::#variant_names(case) => #xml::XmlNode::restore_defaults(case),)*
+                            }
+                        }
+                    }
+                }
+            }
+            false => quote! {},
+        };
+
+        // Choices without cases have no values to generate.
+        let arbitrary_impl = match case_variants.is_empty() {
+            true => quote! {},
+            false => self.arbitrary_impl(
+                &enum_name,
+                quote! {
+                    proptest::strategy::Union::new([
+                        #(proptest::arbitrary::any::<#module_name::#case_variants>().prop_map(Self::#case_variants).boxed()),*
+                    ])
+                },
+            ),
+        };
+        // The case is selected by the members of the data, as every member of a case is optional. Without members of
+        // any case, the choice is its default case, or missing.
+        let default_case = choice
+            .default
+            .as_ref()
+            .and_then(|default| cases.iter().position(|(name, _, _)| name == default));
+        let no_case = match default_case {
+            Some(position) => {
+                let variant_name = &case_variants[position];
+                quote! { Ok(Self::#variant_name(Default::default())) }
+            }
+            None => {
+                let message = format!("no member of a case of the choice {}", choice.name);
+                quote! { Err(serde::de::Error::custom(#message)) }
+            }
+        };
+        let deserialize_impl = quote! {
+            impl<'de> serde::Deserialize<'de> for #enum_name {
+                fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    let members = <serde_json::Map<String, serde_json::Value> as serde::Deserialize>::deserialize(deserializer)?;
+                    let has_member = |names: &[&str]| members.keys().any(|name| names.contains(&name.as_str()));
+                    #(
+                        if has_member(&[#(#case_members),*]) {
+                            return serde_json::from_value(serde_json::Value::Object(members))
+                                .map(Self::#case_variants)
+                                .map_err(serde::de::Error::custom);
+                        }
+                    )*
+                    #no_case
+                }
+            }
+        };
+        let validation = self.validation_path();
+        let attributes = self.type_attributes(&enum_name, &[]);
+
+        quote! {
+            #[doc = #doc]
+            #[derive(Debug, Clone, serde::Serialize)]
+            #attributes
+            #[serde(untagged)]
+            pub enum #enum_name {
+                #(#variants)*
+            }
+
+            #deserialize_impl
+
+            #arbitrary_impl
+
+            impl #validation::Validate for #enum_name {
+                fn validate_at(&self, path: &str, errors: &mut Vec<#validation::ValidationError>) {
+                    match self {
+                        #(Self::#case_variants(case) => #validation::Validate::validate_at(case, path, errors),)*
+                    }
+                }
+            }
+
+            #xml_impl
+
+            pub mod #module_name {
+                #(#case_structs)*
+            }
+        }
+    }
+
+    // The names of the members of a case in instance data, which are the names of its fields, and the members of the
+    // cases of the choices within it.
+    pub(crate) fn case_members(&self, data_defs: &[DataDef]) -> Vec<String> {
+        self.generated_data_defs(data_defs)
+            .iter()
+            .flat_map(|child| match child {
+                DataDef::Choice(choice) => choice
+                    .cases
+                    .iter()
+                    .flat_map(|case| self.case_members(&utils::case_parts(case).2))
+                    .collect(),
+                DataDef::Uses(_) => Vec::new(),
+                child => vec![utils::qualified_name(
+                    utils::data_def_name(child),
+                    utils::node_module(child),
+                    &self.node_module,
+                )],
+            })
+            .collect()
+    }
+
+    pub(crate) fn generate_case(
+        &mut self,
+        name: &str,
+        description: &Option<String>,
+        data_defs: &[DataDef],
+    ) -> TokenStream {
+        let struct_name = self.config.naming.type_identifier(&self.node_name(name));
+        let struct_fields = self.generate_fields(Some(name), data_defs, &[]);
+        let (child_module, _) = self.generate_child_module(name, data_defs, &[], &[]);
+        let doc = utils::format_docstring(description);
+        let struct_type = generate_struct(&struct_name, &doc, self.struct_attributes(&struct_name), &struct_fields);
+        let xml_impl = self.generate_struct_impls(&struct_name, &struct_fields);
+
+        quote! {
+            #struct_type
+            #xml_impl
+
+            #child_module
+        }
+    }
+}
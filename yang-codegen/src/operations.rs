@@ -0,0 +1,341 @@
+//! The code of RPCs, actions and notifications: the modules with the input and output types of each operation, the
+//! client and server traits calling them, and the enum of the notifications of the module.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use yang_parser::model::*;
+
+use crate::{utils, CodeGenerator, Operation};
+
+impl<'a> CodeGenerator<'a> {
+    // Each RPC gets a Rust module within the rpcs module, holding the Input and Output structs of the RPC. The RPCs are
+    // the methods of the ModuleRpcs trait and its async variant, which are implemented by clients for a transport,
+    // like NETCONF, so the RPCs can be called with typed inputs and outputs. Servers implement the ModuleRpcServer
+    // trait to handle them.
+    pub(crate) fn generate_rpcs(&mut self) -> TokenStream {
+        let module: &'a Module = self.module;
+        let rpcs: Vec<&Rpc> = module
+            .body
+            .iter()
+            .filter_map(|node| match node {
+                SchemaNode::Rpc(rpc) => Some(&**rpc),
+                _ => None,
+            })
+            .collect();
+        if rpcs.is_empty() {
+            return quote! {};
+        }
+
+        self.depth += 1;
+        let operations: Vec<Operation> = rpcs
+            .iter()
+            .map(|rpc| {
+                let doc = utils::format_doc(&rpc.description, &[("Reference", rpc.reference.clone())]);
+                self.generate_operation(&rpc.name, "RPC", doc, &rpc.input, &rpc.output, quote! { rpcs:: })
+            })
+            .collect();
+        self.depth -= 1;
+
+        let modules = operations.iter().map(|operation| &operation.module);
+        let methods = operations.iter().map(|operation| operation.method(None));
+        let async_methods = operations.iter().map(|operation| operation.async_method(None));
+        let trait_name = self.config.naming.type_identifier(&format!("{}-rpcs", module.name));
+        let trait_doc = format!(
+            "RPCs of the {} module, implemented by clients for a transport.",
+            module.name
+        );
+        let async_trait_name = self
+            .config
+            .naming
+            .type_identifier(&format!("{}-async-rpcs", module.name));
+        let async_trait_doc = format!("Async variant of [{}].", trait_name);
+        let rpc_error = self.rpc_error_path();
+        let server_methods = operations
+            .iter()
+            .map(|operation| operation.server_method(None, &rpc_error));
+        let server_trait_name = self
+            .config
+            .naming
+            .type_identifier(&format!("{}-rpc-server", module.name));
+        let server_trait_doc = format!(
+            "RPCs of the {} module, implemented by servers handling them with typed inputs and outputs.",
+            module.name
+        );
+        self.uses_operations = true;
+
+        quote! {
+            /// Inputs and outputs of the RPCs of the module.
+            pub mod rpcs {
+                #(#modules)*
+            }
+
+            #[doc = #trait_doc]
+            pub trait #trait_name {
+                type Error;
+
+                #(#methods)*
+            }
+
+            #[doc = #async_trait_doc]
+            pub trait #async_trait_name {
+                type Error;
+
+                #(#async_methods)*
+            }
+
+            #[doc = #server_trait_doc]
+            pub trait #server_trait_name {
+                #(#server_methods)*
+            }
+        }
+    }
+
+    // Top-level notifications get a struct each within the notifications module, while nested notifications are
+    // generated in the child module of the node they are defined in. All of them are wrapped by the ModuleNotifications
+    // enum, which must be generated after the data nodes so the nested notifications are known.
+    pub(crate) fn generate_notifications(&mut self) -> TokenStream {
+        let module: &'a Module = self.module;
+        let notifications: Vec<&Notification> = module
+            .body
+            .iter()
+            .filter_map(|node| match node {
+                SchemaNode::Notification(notification) => Some(&**notification),
+                _ => None,
+            })
+            .collect();
+
+        self.depth += 1;
+        self.module_path.push(quote::format_ident!("notifications"));
+        let structs: Vec<TokenStream> = notifications
+            .iter()
+            .map(|notification| self.generate_notification(notification))
+            .collect();
+        self.module_path.pop();
+        self.depth -= 1;
+
+        if self.notifications.is_empty() {
+            return quote! {};
+        }
+
+        let notifications_module = match structs.is_empty() {
+            true => quote! {},
+            false => quote! {
+                /// Top-level notifications of the module.
+                pub mod notifications {
+                    #(#structs)*
+                }
+            },
+        };
+
+        // Top-level notifications are encoded as an object with the module-qualified name of the notification as its
+        // only member, like within the notification element of RFC 8040. Nested notifications are encoded within the
+        // data tree of their parent nodes instead, which the enum can't represent, so they are skipped by serde.
+        let top_level = format!("/{}:", module.name);
+        let variants = self.notifications.iter().map(|(schema_path, type_path)| {
+            let variant_name = self
+                .config
+                .naming
+                .type_identifier(&schema_path.split_once(':').unwrap().1.replace('/', "-"));
+            let serde_attribute = match schema_path.strip_prefix(&top_level) {
+                Some(name) if !name.contains('/') => {
+                    let serde_name = format!("{}:{}", module.name, name);
+                    quote! { #[serde(rename = #serde_name)] }
+                }
+                _ => quote! { #[serde(skip)] },
+            };
+            let doc = format!(" The {} notification.", schema_path);
+            quote! {
+                #[doc = #doc]
+                #serde_attribute
+                #variant_name(#type_path),
+            }
+        });
+        let path_arms = self.notifications.iter().map(|(schema_path, _)| {
+            let variant_name = self
+                .config
+                .naming
+                .type_identifier(&schema_path.split_once(':').unwrap().1.replace('/', "-"));
+            quote! { Self::#variant_name(_) => #schema_path, }
+        });
+        let enum_name = self
+            .config
+            .naming
+            .type_identifier(&format!("{}-notifications", module.name));
+        let enum_doc = format!("Notifications of the {} module.", module.name);
+        let attributes = self.type_attributes(&enum_name, &[]);
+
+        quote! {
+            #notifications_module
+
+            #[doc = #enum_doc]
+            #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+            #attributes
+            pub enum #enum_name {
+                #(#variants)*
+            }
+
+            impl #enum_name {
+                /// Schema node identifier of the notification, like "/module:container/notification".
+                pub fn schema_path(&self) -> &'static str {
+                    match self {
+                        #(#path_arms)*
+                    }
+                }
+            }
+        }
+    }
+
+    // A notification is a struct holding the notification's nodes, like a case, and is recorded to be wrapped by the
+    // ModuleNotifications enum.
+    pub(crate) fn generate_notification(&mut self, notification: &Notification) -> TokenStream {
+        let struct_name = self.config.naming.type_identifier(&notification.name);
+        let module_path = &self.module_path;
+        let type_path = quote! { #(#module_path::)* #struct_name };
+        self.schema_path.push((notification.name.clone(), Vec::new()));
+        self.notifications.push((self.path_strings().0, type_path));
+        let notification_struct =
+            self.generate_case(&notification.name, &notification.description, &notification.data_defs);
+        self.schema_path.pop();
+
+        notification_struct
+    }
+
+    // An RPC or action gets a Rust module named after it, with the Input and Output structs of its input and output
+    // nodes. The path prefix is the path to that module from where the method calling the operation is generated.
+    pub(crate) fn generate_operation(
+        &mut self,
+        name: &str,
+        kind: &str,
+        doc: String,
+        input: &Option<Input>,
+        output: &Option<Output>,
+        path_prefix: TokenStream,
+    ) -> Operation {
+        let module_name = utils::sanitize_identifier(name);
+        let input_doc = Some(format!("Input of the {} {}.", name, kind));
+        let output_doc = Some(format!("Output of the {} {}.", name, kind));
+
+        self.depth += 1;
+        self.schema_path.push((name.to_string(), Vec::new()));
+        let mut generate_part = |part: &str, doc: &Option<String>, data_defs: &[DataDef]| {
+            self.schema_path.push((part.to_string(), Vec::new()));
+            let part_struct = self.generate_case(part, doc, data_defs);
+            self.schema_path.pop();
+            part_struct
+        };
+        let input_struct = input
+            .as_ref()
+            .map(|input| generate_part("input", &input_doc, &input.data_defs));
+        let output_struct = output
+            .as_ref()
+            .map(|output| generate_part("output", &output_doc, &output.data_defs));
+        self.schema_path.pop();
+        self.depth -= 1;
+
+        let input_name = self.config.naming.type_identifier("input");
+        let output_name = self.config.naming.type_identifier("output");
+        Operation {
+            module: quote! {
+                #[doc = #doc]
+                pub mod #module_name {
+                    #input_struct
+                    #output_struct
+                }
+            },
+            doc,
+            method_name: module_name.clone(),
+            input: input
+                .as_ref()
+                .map(|_| quote! { #path_prefix #module_name::#input_name }),
+            output: match output {
+                Some(_) => quote! { #path_prefix #module_name::#output_name },
+                None => quote! { () },
+            },
+        }
+    }
+
+    // Actions are called on an instance of the node they are defined in, so the node is passed to the methods of the
+    // node's actions trait, for the client to identify the instance, and of the node's action server trait, for the
+    // server to find it. The node's struct gets a method per action as well, calling the action on the node with a
+    // client.
+    pub(crate) fn generate_actions(
+        &mut self,
+        name: &str,
+        struct_name: &syn::Ident,
+        operations: &[Operation],
+    ) -> TokenStream {
+        if operations.is_empty() {
+            return quote! {};
+        }
+
+        let node_name = self.node_name(name);
+        let trait_name = self.config.naming.type_identifier(&format!("{}-actions", node_name));
+        let trait_doc = format!("Actions of the {} node, implemented by clients for a transport.", name);
+        let async_trait_name = self
+            .config
+            .naming
+            .type_identifier(&format!("{}-async-actions", node_name));
+        let async_trait_doc = format!("Async variant of [{}].", trait_name);
+        let node = quote! { node: &#struct_name };
+        let methods = operations.iter().map(|operation| operation.method(Some(&node)));
+        let async_methods = operations.iter().map(|operation| operation.async_method(Some(&node)));
+        let server_trait_name = self
+            .config
+            .naming
+            .type_identifier(&format!("{}-action-server", node_name));
+        let server_trait_doc = format!(
+            "Actions of the {} node, implemented by servers handling them with typed inputs and outputs.",
+            name
+        );
+        let rpc_error = self.rpc_error_path();
+        let server_methods = operations
+            .iter()
+            .map(|operation| operation.server_method(Some(&node), &rpc_error));
+        self.uses_operations = true;
+        let node_methods = operations.iter().map(|operation| {
+            let Operation {
+                doc,
+                method_name,
+                output,
+                ..
+            } = operation;
+            let input_parameter = operation.input.iter().map(|input| quote! { input: #input });
+            let input_argument = operation.input.iter().map(|_| quote! { input });
+            quote! {
+                #[doc = #doc]
+                pub fn #method_name<C: #trait_name>(
+                    &self,
+                    client: &C
+                    #(, #input_parameter)*
+                ) -> Result<#output, C::Error> {
+                    client.#method_name(self #(, #input_argument)*)
+                }
+            }
+        });
+
+        quote! {
+            #[doc = #trait_doc]
+            pub trait #trait_name {
+                type Error;
+
+                #(#methods)*
+            }
+
+            #[doc = #async_trait_doc]
+            pub trait #async_trait_name {
+                type Error;
+
+                #(#async_methods)*
+            }
+
+            #[doc = #server_trait_doc]
+            pub trait #server_trait_name {
+                #(#server_methods)*
+            }
+
+            impl #struct_name {
+                #(#node_methods)*
+            }
+        }
+    }
+}
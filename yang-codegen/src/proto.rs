@@ -0,0 +1,370 @@
+//! Protocol Buffers (proto3) definitions generated from the schema, for gRPC and gNMI pipelines which use proto
+//! equivalents of YANG models. The root message is named after the module and holds the top-level data nodes.
+//! Containers and lists become messages nested in the message of their parent, enumerations become enums, and choices
+//! become oneofs. Notifications become messages, and RPCs become the methods of a service with messages for their input
+//! and output. Actions and notifications within data nodes have no proto equivalent, so they are left out.
+//!
+//! Leaves which aren't mandatory or list keys are optional fields. Types without a proto equivalent, like unions, bits
+//! and identityrefs, are strings with their RFC 7951 string form, and anydata and anyxml nodes are strings with their
+//! JSON encoding. Enum values are numbered in the order of the YANG enums, after the zero value proto3 requires.
+
+use std::collections::HashSet;
+
+use yang_parser::model::*;
+
+use crate::utils;
+
+/// Generate the proto3 definitions for the module, in a package named after the module, like `ietf_interfaces`.
+pub fn generate(module: &Module) -> String {
+    let data_defs: Vec<DataDef> = module
+        .body
+        .iter()
+        .filter_map(|node| match node {
+            SchemaNode::DataDef(data_def) => Some(data_def.clone()),
+            _ => None,
+        })
+        .collect();
+    let notifications: Vec<&Notification> = module
+        .body
+        .iter()
+        .filter_map(|node| match node {
+            SchemaNode::Notification(notification) => Some(&**notification),
+            _ => None,
+        })
+        .collect();
+    let rpcs: Vec<&Rpc> = module
+        .body
+        .iter()
+        .filter_map(|node| match node {
+            SchemaNode::Rpc(rpc) => Some(&**rpc),
+            _ => None,
+        })
+        .collect();
+
+    // Top-level messages and the service share the namespace of the package.
+    let mut names = vec![module.name.to_string(), format!("{}-rpcs", module.name)];
+    names.extend(notifications.iter().map(|notification| notification.name.clone()));
+    for rpc in &rpcs {
+        names.extend([format!("{}-input", rpc.name), format!("{}-output", rpc.name)]);
+    }
+    let mut names = utils::unique_names(names.iter().map(String::as_str))
+        .into_iter()
+        .map(|name| utils::pascal_case(&name));
+    let root_name = names.next().unwrap_or_default();
+    let service_name = names.next().unwrap_or_default();
+
+    let mut output = format!(
+        "syntax = \"proto3\";\n\npackage {};\n\n",
+        utils::snake_case(&module.name)
+    );
+    message(
+        module,
+        &root_name,
+        &module.meta.description,
+        &data_defs,
+        &[],
+        0,
+        &mut output,
+    );
+    for (notification, name) in notifications.iter().zip(names.by_ref()) {
+        output.push('\n');
+        message(
+            module,
+            &name,
+            &notification.description,
+            &notification.data_defs,
+            &[],
+            0,
+            &mut output,
+        );
+    }
+
+    let mut methods = String::new();
+    for rpc in &rpcs {
+        let input_name = names.next().unwrap_or_default();
+        let output_name = names.next().unwrap_or_default();
+        let input = rpc.input.as_ref().map_or(&[][..], |input| &input.data_defs);
+        let output_data_defs = rpc.output.as_ref().map_or(&[][..], |output| &output.data_defs);
+        output.push('\n');
+        message(module, &input_name, &None, input, &[], 0, &mut output);
+        output.push('\n');
+        message(module, &output_name, &None, output_data_defs, &[], 0, &mut output);
+
+        comment(&rpc.description, "  ", &mut methods);
+        methods.push_str(&format!(
+            "  rpc {}({}) returns ({});\n",
+            utils::pascal_case(&rpc.name),
+            input_name,
+            output_name
+        ));
+    }
+    if !rpcs.is_empty() {
+        output.push_str(&format!("\nservice {} {{\n{}}}\n", service_name, methods));
+    }
+
+    output
+}
+
+// The fields of the message, followed by the messages and enums of the fields' types, which are nested in the message.
+fn message(
+    module: &Module,
+    name: &str,
+    description: &Option<String>,
+    data_defs: &[DataDef],
+    keys: &[String],
+    depth: usize,
+    output: &mut String,
+) {
+    let indent = "  ".repeat(depth);
+    let mut names = Vec::new();
+    level_names(data_defs, &mut names);
+    let mut seen = HashSet::new();
+    names.retain(|name| seen.insert(name.clone()));
+    let unique_names = utils::unique_names(names.iter().map(String::as_str));
+    let level = Level {
+        module,
+        names: names.into_iter().zip(unique_names).collect(),
+        depth: depth + 1,
+    };
+
+    let mut number = 0;
+    let mut fields = String::new();
+    let mut types = String::new();
+    level.fields(data_defs, keys, false, &mut number, &mut fields, &mut types);
+
+    if depth > 0 && !output.is_empty() {
+        output.push('\n');
+    }
+    comment(description, &indent, output);
+    output.push_str(&format!("{}message {} {{\n{}", indent, name, fields));
+    if !types.is_empty() {
+        output.push_str(&format!("\n{}", types));
+    }
+    output.push_str(&format!("{}}}\n", indent));
+}
+
+// The names of the fields and nested types of a message, which are the names of its data nodes, and of the choices
+// and cases within it, as oneofs and the nodes of cases are part of the message too.
+fn level_names(data_defs: &[DataDef], names: &mut Vec<String>) {
+    for data_def in data_defs {
+        names.push(utils::data_def_name(data_def).to_string());
+        if let DataDef::Choice(choice) = data_def {
+            for case in &choice.cases {
+                let (case_name, _, case_data_defs) = utils::case_parts(case);
+                names.push(case_name);
+                level_names(&case_data_defs, names);
+            }
+        }
+    }
+}
+
+// A message being generated, with the unique names of the nodes at its level.
+struct Level<'a> {
+    module: &'a Module,
+    names: Vec<(String, String)>,
+    depth: usize,
+}
+
+impl Level<'_> {
+    fn field_name(&self, name: &str) -> String {
+        utils::snake_case(self.unique_name(name))
+    }
+
+    fn type_name(&self, name: &str) -> String {
+        utils::pascal_case(self.unique_name(name))
+    }
+
+    fn unique_name<'n>(&'n self, name: &'n str) -> &'n str {
+        self.names
+            .iter()
+            .find(|(node_name, _)| node_name == name)
+            .map_or(name, |(_, unique_name)| unique_name)
+    }
+
+    // Fields in a oneof can't be optional or repeated, so cases with several nodes, or with a node which would be
+    // repeated, get a message of their own.
+    fn fields(
+        &self,
+        data_defs: &[DataDef],
+        keys: &[String],
+        in_oneof: bool,
+        number: &mut u32,
+        fields: &mut String,
+        types: &mut String,
+    ) {
+        // Fields of oneofs are indented in the oneof, while the types of the fields are nested in the message.
+        let indent = "  ".repeat(self.depth + in_oneof as usize);
+        let label = |optional: bool| match optional && !in_oneof {
+            true => "optional ",
+            false => "",
+        };
+
+        for data_def in data_defs {
+            let mut field = |description: &Option<String>, field_type: &str, label: &str, name: &str| {
+                *number += 1;
+                comment(description, &indent, fields);
+                fields.push_str(&format!(
+                    "{}{}{} {} = {};\n",
+                    indent,
+                    label,
+                    field_type,
+                    self.field_name(name),
+                    number
+                ));
+            };
+
+            match data_def {
+                DataDef::Container(container) => {
+                    let type_name = self.type_name(&container.name);
+                    message(
+                        self.module,
+                        &type_name,
+                        &container.meta.description,
+                        &container.data_defs,
+                        &[],
+                        self.depth,
+                        types,
+                    );
+                    field(&None, &type_name, "", &container.name);
+                }
+                DataDef::List(list) => {
+                    let type_name = self.type_name(&list.name);
+                    let keys = utils::list_keys(list);
+                    message(
+                        self.module,
+                        &type_name,
+                        &list.meta.description,
+                        &list.data_defs,
+                        &keys,
+                        self.depth,
+                        types,
+                    );
+                    field(&None, &type_name, "repeated ", &list.name);
+                }
+                DataDef::Leaf(leaf) => {
+                    let leaf_type = self.leaf_type(&leaf.type_info, &self.module.name, &leaf.name, types);
+                    let optional = leaf.mandatory != Some(true) && !keys.contains(&leaf.name);
+                    field(&leaf.meta.description, &leaf_type, label(optional), &leaf.name);
+                }
+                DataDef::LeafList(leaf_list) => {
+                    let leaf_type = self.leaf_type(&leaf_list.type_info, &self.module.name, &leaf_list.name, types);
+                    field(&leaf_list.meta.description, &leaf_type, "repeated ", &leaf_list.name);
+                }
+                DataDef::Choice(choice) if !choice.cases.is_empty() => {
+                    let mut oneof_fields = String::new();
+                    for case in &choice.cases {
+                        let (case_name, description, case_data_defs) = utils::case_parts(case);
+                        match case_data_defs.as_slice() {
+                            [DataDef::Leaf(_) | DataDef::Container(_) | DataDef::AnyData(_) | DataDef::Anyxml(_)] => {
+                                self.fields(&case_data_defs, keys, true, number, &mut oneof_fields, types)
+                            }
+                            _ => {
+                                let type_name = self.type_name(&case_name);
+                                message(
+                                    self.module,
+                                    &type_name,
+                                    &description,
+                                    &case_data_defs,
+                                    &[],
+                                    self.depth,
+                                    types,
+                                );
+                                *number += 1;
+                                oneof_fields.push_str(&format!(
+                                    "{}  {} {} = {};\n",
+                                    indent,
+                                    type_name,
+                                    self.field_name(&case_name),
+                                    number
+                                ));
+                            }
+                        }
+                    }
+                    comment(&choice.meta.description, &indent, fields);
+                    fields.push_str(&format!(
+                        "{}oneof {} {{\n{}{}}}\n",
+                        indent,
+                        self.field_name(&choice.name),
+                        oneof_fields,
+                        indent
+                    ));
+                }
+                DataDef::AnyData(anydata) => field(
+                    &anydata.meta.description,
+                    "string",
+                    label(anydata.mandatory != Some(true)),
+                    &anydata.name,
+                ),
+                DataDef::Anyxml(anyxml) => field(
+                    &anyxml.meta.description,
+                    "string",
+                    label(anyxml.mandatory != Some(true)),
+                    &anyxml.name,
+                ),
+                DataDef::Choice(_) | DataDef::Uses(_) => {}
+            }
+        }
+    }
+
+    // The proto type of a leaf, following typedefs to their built-in type. Enumerations get an enum named after the
+    // leaf, nested in the message.
+    fn leaf_type(&self, type_info: &TypeInfo, module_name: &str, leaf_name: &str, types: &mut String) -> String {
+        let type_info = utils::resolved_type(type_info);
+        if let Some(TypeBody::Enum { enums }) = type_info.type_body.as_deref() {
+            let type_name = self.type_name(leaf_name);
+            enumeration(&type_name, &self.field_name(leaf_name), enums, self.depth, types);
+            return type_name;
+        }
+
+        match utils::find_type_def(self.module, &type_info.name, module_name) {
+            Some((type_module, _, type_def)) => self.leaf_type(&type_def.type_info, &type_module, leaf_name, types),
+            None => scalar_type(&type_info.name).to_string(),
+        }
+    }
+}
+
+// proto3 enums start with a zero value, which is the default of enum fields. Enum values are scoped to the message
+// containing the enum, so they are prefixed with the name of the leaf.
+fn enumeration(name: &str, leaf_name: &str, enums: &[EnumValue], depth: usize, output: &mut String) {
+    let indent = "  ".repeat(depth);
+    let prefix = leaf_name.to_uppercase();
+    let names = std::iter::once("unspecified").chain(enums.iter().map(|enum_value| enum_value.name.as_str()));
+    let values = utils::unique_names(names);
+
+    if !output.is_empty() {
+        output.push('\n');
+    }
+    output.push_str(&format!("{}enum {} {{\n", indent, name));
+    for (number, value) in values.iter().enumerate() {
+        if let Some(enum_value) = number.checked_sub(1).and_then(|idx| enums.get(idx)) {
+            comment(&enum_value.description, &format!("{}  ", indent), output);
+        }
+        let value_name = utils::snake_case(value).to_uppercase();
+        output.push_str(&format!("{}  {}_{} = {};\n", indent, prefix, value_name, number));
+    }
+    output.push_str(&format!("{}}}\n", indent));
+}
+
+fn scalar_type(yang_type: &str) -> &'static str {
+    match yang_type {
+        "int8" | "int16" | "int32" => "int32",
+        "int64" => "int64",
+        "uint8" | "uint16" | "uint32" => "uint32",
+        "uint64" => "uint64",
+        "decimal64" => "double",
+        "boolean" | "empty" => "bool",
+        "binary" => "bytes",
+        _ => "string",
+    }
+}
+
+fn comment(description: &Option<String>, indent: &str, output: &mut String) {
+    if let Some(description) = description {
+        for line in description.trim().lines() {
+            match line.trim() {
+                "" => output.push_str(&format!("{}//\n", indent)),
+                line => output.push_str(&format!("{}// {}\n", indent, line)),
+            }
+        }
+    }
+}
@@ -0,0 +1,742 @@
+//! The helper modules generated next to the types, which the generated code uses for string encodings, validation,
+//! XML, proptest and RPC errors, and the rewriting of the code for no_std crates.
+
+use proc_macro2::{Delimiter, Group, TokenStream, TokenTree};
+use quote::quote;
+
+use crate::{utils, CaseConvention, CodeGenerator};
+
+impl<'a> CodeGenerator<'a> {
+    pub(crate) fn rpc_error_path(&self) -> TokenStream {
+        let supers = std::iter::repeat_n(quote! { super:: }, self.depth);
+        quote! { #(#supers)* rpc_error }
+    }
+
+    pub(crate) fn rfc7951_path(&self) -> TokenStream {
+        let supers = std::iter::repeat_n(quote! { super:: }, self.depth);
+        quote! { #(#supers)* rfc7951 }
+    }
+}
+
+// Types like bits and identityrefs are encoded with their string form in RFC 7951, using their Display and FromStr
+// implementations.
+pub(crate) fn generate_string_serde_impl(type_name: &syn::Ident) -> TokenStream {
+    quote! {
+        impl serde::Serialize for #type_name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for #type_name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let value = <String as serde::Deserialize>::deserialize(deserializer)?;
+                value.parse().map_err(serde::de::Error::custom)
+            }
+        }
+    }
+}
+
+// Serde helpers for values RFC 7951 encodes as JSON strings, like 64-bit integers, for use with #[serde(with)]. The
+// helpers for lists generated as IndexMaps are only generated if they are used, as they depend on the indexmap crate.
+pub(crate) fn generate_string_encoding(index_map: bool) -> TokenStream {
+    let index_map = match index_map {
+        true => quote! {
+            pub mod index_map {
+                use indexmap::IndexMap;
+
+                pub fn serialize<K, V, S>(map: &IndexMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+                where
+                    V: serde::Serialize,
+                    S: serde::Serializer,
+                {
+                    serializer.collect_seq(map.values())
+                }
+
+                pub fn deserialize<'de, K, V, D>(deserializer: D) -> Result<IndexMap<K, V>, D::Error>
+                where
+                    K: std::hash::Hash + Eq,
+                    V: super::ListEntry<Key = K> + serde::Deserialize<'de>,
+                    D: serde::Deserializer<'de>,
+                {
+                    let entries = <Vec<V> as serde::Deserialize>::deserialize(deserializer)?;
+                    let mut map = IndexMap::new();
+                    for entry in entries {
+                        if map.insert(entry.key(), entry).is_some() {
+                            return Err(serde::de::Error::custom("duplicate key of a list entry"));
+                        }
+                    }
+                    Ok(map)
+                }
+            }
+        },
+        false => quote! {},
+    };
+
+    quote! {
+        /// Serde helpers for values encoded as JSON strings in RFC 7951, like 64-bit integers and decimal64 values, and
+        /// binary values encoded as base64. Also has the type of empty leaves.
+        pub mod rfc7951 {
+            /// The entries of lists generated as maps, which are keyed by the values of their key leaves.
+            pub trait ListEntry {
+                type Key;
+
+                fn key(&self) -> Self::Key;
+            }
+
+            pub mod btree_map {
+                use std::collections::BTreeMap;
+
+                pub fn serialize<K, V, S>(map: &BTreeMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+                where
+                    V: serde::Serialize,
+                    S: serde::Serializer,
+                {
+                    serializer.collect_seq(map.values())
+                }
+
+                pub fn deserialize<'de, K, V, D>(deserializer: D) -> Result<BTreeMap<K, V>, D::Error>
+                where
+                    K: Ord,
+                    V: super::ListEntry<Key = K> + serde::Deserialize<'de>,
+                    D: serde::Deserializer<'de>,
+                {
+                    let entries = <Vec<V> as serde::Deserialize>::deserialize(deserializer)?;
+                    let mut map = BTreeMap::new();
+                    for entry in entries {
+                        if map.insert(entry.key(), entry).is_some() {
+                            return Err(serde::de::Error::custom("duplicate key of a list entry"));
+                        }
+                    }
+                    Ok(map)
+                }
+            }
+
+            #index_map
+
+            /// The value of a leaf of the empty type, which is set by being present. It's encoded as `[null]`.
+            #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+            pub struct Empty;
+
+            impl serde::Serialize for Empty {
+                fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serde::Serialize::serialize(&[()], serializer)
+                }
+            }
+
+            impl<'de> serde::Deserialize<'de> for Empty {
+                fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    <[(); 1] as serde::Deserialize>::deserialize(deserializer).map(|_| Empty)
+                }
+            }
+
+            const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+            /// Encode a binary value as base64, with padding.
+            pub fn encode(bytes: &[u8]) -> String {
+                let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+                for chunk in bytes.chunks(3) {
+                    let group = chunk
+                        .iter()
+                        .enumerate()
+                        .fold(0u32, |group, (idx, byte)| group | (u32::from(*byte) << (16 - 8 * idx)));
+                    for idx in 0..4 {
+                        match idx <= chunk.len() {
+                            true => encoded.push(BASE64_ALPHABET[((group >> (18 - 6 * idx)) & 0x3f) as usize] as char),
+                            false => encoded.push('='),
+                        }
+                    }
+                }
+                encoded
+            }
+
+            /// Decode a base64 encoded binary value. Whitespace is ignored, as in the XML encoding of binary values.
+            pub fn decode(s: &str) -> Result<Vec<u8>, String> {
+                let mut bytes = Vec::with_capacity(s.len() / 4 * 3);
+                let mut buffer = 0u32;
+                let mut bits = 0;
+                for c in s.bytes().filter(|c| !c.is_ascii_whitespace()).take_while(|c| *c != b'=') {
+                    let value = BASE64_ALPHABET
+                        .iter()
+                        .position(|a| *a == c)
+                        .ok_or_else(|| format!("invalid base64 value: {}", s))?;
+                    buffer = (buffer << 6) | value as u32;
+                    bits += 6;
+                    if bits >= 8 {
+                        bits -= 8;
+                        bytes.push((buffer >> bits) as u8);
+                        buffer &= (1 << bits) - 1;
+                    }
+                }
+                Ok(bytes)
+            }
+
+            pub mod base64 {
+                pub fn serialize<S: serde::Serializer>(value: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+                    serializer.serialize_str(&super::encode(value))
+                }
+
+                pub fn deserialize<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+                    let value = <String as serde::Deserialize>::deserialize(deserializer)?;
+                    super::decode(&value).map_err(serde::de::Error::custom)
+                }
+            }
+
+            pub mod option_base64 {
+                pub fn serialize<S: serde::Serializer>(value: &Option<Vec<u8>>, serializer: S) -> Result<S::Ok, S::Error> {
+                    match value {
+                        Some(value) => serializer.serialize_str(&super::encode(value)),
+                        None => serializer.serialize_none(),
+                    }
+                }
+
+                pub fn deserialize<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<Vec<u8>>, D::Error> {
+                    let value = <Option<String> as serde::Deserialize>::deserialize(deserializer)?;
+                    value.map(|value| super::decode(&value).map_err(serde::de::Error::custom)).transpose()
+                }
+            }
+
+            pub mod vec_base64 {
+                pub fn serialize<S: serde::Serializer>(values: &[Vec<u8>], serializer: S) -> Result<S::Ok, S::Error> {
+                    serializer.collect_seq(values.iter().map(|value| super::encode(value)))
+                }
+
+                pub fn deserialize<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<Vec<u8>>, D::Error> {
+                    let values = <Vec<String> as serde::Deserialize>::deserialize(deserializer)?;
+                    values
+                        .iter()
+                        .map(|value| super::decode(value).map_err(serde::de::Error::custom))
+                        .collect()
+                }
+            }
+
+            pub mod string {
+                pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+                where
+                    T: std::fmt::Display,
+                    S: serde::Serializer,
+                {
+                    serializer.collect_str(value)
+                }
+
+                pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+                where
+                    T: std::str::FromStr,
+                    T::Err: std::fmt::Display,
+                    D: serde::Deserializer<'de>,
+                {
+                    let value = <String as serde::Deserialize>::deserialize(deserializer)?;
+                    value.parse().map_err(serde::de::Error::custom)
+                }
+            }
+
+            pub mod option_string {
+                pub fn serialize<T, S>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+                where
+                    T: std::fmt::Display,
+                    S: serde::Serializer,
+                {
+                    match value {
+                        Some(value) => serializer.collect_str(value),
+                        None => serializer.serialize_none(),
+                    }
+                }
+
+                pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+                where
+                    T: std::str::FromStr,
+                    T::Err: std::fmt::Display,
+                    D: serde::Deserializer<'de>,
+                {
+                    let value = <Option<String> as serde::Deserialize>::deserialize(deserializer)?;
+                    value.map(|value| value.parse().map_err(serde::de::Error::custom)).transpose()
+                }
+            }
+
+            pub mod vec_string {
+                pub fn serialize<T, S>(values: &[T], serializer: S) -> Result<S::Ok, S::Error>
+                where
+                    T: std::fmt::Display,
+                    S: serde::Serializer,
+                {
+                    serializer.collect_seq(values.iter().map(|value| value.to_string()))
+                }
+
+                pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Vec<T>, D::Error>
+                where
+                    T: std::str::FromStr,
+                    T::Err: std::fmt::Display,
+                    D: serde::Deserializer<'de>,
+                {
+                    let values = <Vec<String> as serde::Deserialize>::deserialize(deserializer)?;
+                    values
+                        .iter()
+                        .map(|value| value.parse().map_err(serde::de::Error::custom))
+                        .collect()
+                }
+            }
+        }
+    }
+}
+
+// The Validate trait is implemented by all generated structs and choices, checking the restrictions of their nodes.
+// Paths of nodes are schema paths with list entries numbered from 1, like "/interfaces/interface[1]/mtu".
+pub(crate) fn generate_validation() -> TokenStream {
+    quote! {
+        /// Validation of the restrictions of the data nodes.
+        pub mod validation {
+            /// A violated restriction of the data node at the path.
+            #[derive(Debug, Clone, PartialEq, Eq)]
+            pub struct ValidationError {
+                pub path: String,
+                pub message: String,
+            }
+
+            impl std::fmt::Display for ValidationError {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    write!(f, "{}: {}", self.path, self.message)
+                }
+            }
+
+            impl std::error::Error for ValidationError {}
+
+            pub trait Validate {
+                /// Check the restrictions of the node and all its descendants, adding an error for each violated
+                /// restriction. The path is the path of the node.
+                fn validate_at(&self, path: &str, errors: &mut Vec<ValidationError>);
+            }
+        }
+    }
+}
+
+// Strategies used by the Arbitrary implementations of the generated types, for values of built-in types and the
+// entries of lists and leaf-lists.
+pub(crate) fn generate_arbitrary_helpers() -> TokenStream {
+    quote! {
+        /// Strategies for random values of the data nodes, used by the `Arbitrary` implementations.
+        pub mod arbitrary {
+            use proptest::arbitrary::any;
+            use proptest::strategy::{BoxedStrategy, Strategy, Union};
+
+            /// A list entry, identified by the values of its key leaves.
+            pub trait ListKey {
+                /// The values of the key leaves, formatted to tell entries with different keys apart.
+                fn key_values(&self) -> Vec<String>;
+            }
+
+            /// Strings matching the pattern, or with a length within one of the ranges if there is no pattern, or
+            /// proptest can't generate strings for it.
+            pub fn string(lengths: &[(usize, usize)], pattern: Option<&str>) -> BoxedStrategy<String> {
+                if let Some(Ok(strategy)) = pattern.map(proptest::string::string_regex) {
+                    return strategy.boxed();
+                }
+                let characters = any::<char>().prop_filter("printable characters", |c| !c.is_control());
+                Union::new(lengths.iter().map(|&(min, max)| {
+                    proptest::collection::vec(characters.clone(), min..=max).prop_map(String::from_iter)
+                }))
+                .boxed()
+            }
+
+            /// Binary values with a number of octets within one of the ranges.
+            pub fn binary(lengths: &[(usize, usize)]) -> BoxedStrategy<Vec<u8>> {
+                Union::new(
+                    lengths
+                        .iter()
+                        .map(|&(min, max)| proptest::collection::vec(any::<u8>(), min..=max)),
+                )
+                .boxed()
+            }
+
+            /// Between min and max values, without values with the same key, like the entries of a list with the same
+            /// key values or equal values of a leaf-list.
+            pub fn unique<T, K>(
+                value: impl Strategy<Value = T> + 'static,
+                min: usize,
+                max: usize,
+                key: fn(&T) -> K,
+            ) -> BoxedStrategy<Vec<T>>
+            where
+                T: std::fmt::Debug + 'static,
+                K: Ord + 'static,
+            {
+                proptest::collection::vec(value, min..=max)
+                    .prop_map(move |values| {
+                        let mut keys = std::collections::BTreeSet::new();
+                        values.into_iter().filter(|value| keys.insert(key(value))).collect::<Vec<T>>()
+                    })
+                    .prop_filter("enough unique values", move |values| values.len() >= min)
+                    .boxed()
+            }
+        }
+    }
+}
+
+// The RpcError of the server traits of RPCs and actions carries the error-tag and the other fields of the rpc-error
+// element of NETCONF, which RESTCONF uses as well, so servers can report the errors of their handlers.
+pub(crate) fn generate_rpc_error() -> TokenStream {
+    let tags = [
+        "in-use",
+        "invalid-value",
+        "too-big",
+        "missing-attribute",
+        "bad-attribute",
+        "unknown-attribute",
+        "missing-element",
+        "bad-element",
+        "unknown-element",
+        "unknown-namespace",
+        "access-denied",
+        "lock-denied",
+        "resource-denied",
+        "rollback-failed",
+        "data-exists",
+        "data-missing",
+        "operation-not-supported",
+        "operation-failed",
+        "malformed-message",
+    ];
+    let variants: Vec<syn::Ident> = tags
+        .iter()
+        .map(|tag| quote::format_ident!("{}", utils::convert_case(tag, CaseConvention::Pascal, &[])))
+        .collect();
+
+    quote! {
+        /// Errors of the servers of RPCs and actions.
+        pub mod rpc_error {
+            /// The error-tag of an rpc-error, as defined in RFC 6241.
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+            pub enum ErrorTag {
+                #(#variants,)*
+            }
+
+            impl std::fmt::Display for ErrorTag {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    f.write_str(match self {
+                        #(Self::#variants => #tags,)*
+                    })
+                }
+            }
+
+            /// The error of an RPC or action, reported by the server in an rpc-error.
+            #[derive(Debug, Clone, PartialEq, Eq)]
+            pub struct RpcError {
+                pub tag: ErrorTag,
+                pub app_tag: Option<String>,
+                pub path: Option<String>,
+                pub message: Option<String>,
+            }
+
+            impl RpcError {
+                /// Make an error with the error-tag and message.
+                pub fn new(tag: ErrorTag, message: impl Into<String>) -> Self {
+                    Self {
+                        tag,
+                        app_tag: None,
+                        path: None,
+                        message: Some(message.into()),
+                    }
+                }
+            }
+
+            impl std::fmt::Display for RpcError {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    write!(f, "{}", self.tag)?;
+                    if let Some(path) = &self.path {
+                        write!(f, " at {}", path)?;
+                    }
+                    if let Some(message) = &self.message {
+                        write!(f, ": {}", message)?;
+                    }
+                    Ok(())
+                }
+            }
+
+            impl std::error::Error for RpcError {}
+        }
+    }
+}
+
+// Helpers for the XML encoding of the data nodes, used by the XmlNode implementations of the generated structs.
+// Elements are matched by their local name, as all data nodes of the module are in the module's namespace.
+pub(crate) fn generate_xml_helpers() -> TokenStream {
+    quote! {
+        /// XML encoding of the data nodes, as used by NETCONF.
+        pub mod xml {
+            use quick_xml::events::{BytesEnd, BytesStart, BytesText, Event};
+            use quick_xml::{Reader, Writer};
+
+            /// A node encoded as an XML element, with the elements of its child nodes as content.
+            pub trait XmlNode {
+                /// Write the elements of the child nodes. The namespace is declared on each element if set.
+                fn write_children<W: std::io::Write>(
+                    &self,
+                    writer: &mut Writer<W>,
+                    namespace: Option<&str>,
+                ) -> Result<(), String>;
+
+                /// Read a child element into the node, returning false if the element isn't a child node. Empty is
+                /// set for elements without an end tag, like `<name/>`.
+                fn read_child(
+                    &mut self,
+                    reader: &mut Reader<&[u8]>,
+                    start: &BytesStart,
+                    empty: bool,
+                ) -> Result<bool, String>;
+
+                /// Clear the leaf-lists with defaults, before reading child elements.
+                fn clear_defaults(&mut self);
+
+                /// Restore the defaults of leaf-lists without values, after reading child elements.
+                fn restore_defaults(&mut self);
+            }
+
+            /// Check if the element is the element of the node with the given name.
+            pub fn is_element(start: &BytesStart, name: &str) -> bool {
+                start.local_name().into_inner() == name
+            }
+
+            fn start_element<'a>(name: &'a str, namespace: Option<&'a str>) -> BytesStart<'a> {
+                let start = BytesStart::new(name);
+                match namespace {
+                    Some(namespace) => start.with_attributes([("xmlns", namespace)]),
+                    None => start,
+                }
+            }
+
+            pub fn write_node<W: std::io::Write, T: XmlNode>(
+                writer: &mut Writer<W>,
+                name: &str,
+                namespace: Option<&str>,
+                node: &T,
+            ) -> Result<(), String> {
+                writer
+                    .write_event(Event::Start(start_element(name, namespace)))
+                    .map_err(|e| e.to_string())?;
+                node.write_children(writer, None)?;
+                writer
+                    .write_event(Event::End(BytesEnd::new(name)))
+                    .map_err(|e| e.to_string())
+            }
+
+            pub fn write_leaf<W: std::io::Write, T: std::fmt::Display>(
+                writer: &mut Writer<W>,
+                name: &str,
+                namespace: Option<&str>,
+                value: &T,
+            ) -> Result<(), String> {
+                writer
+                    .write_event(Event::Start(start_element(name, namespace)))
+                    .map_err(|e| e.to_string())?;
+                writer
+                    .write_event(Event::Text(BytesText::new(&value.to_string())))
+                    .map_err(|e| e.to_string())?;
+                writer
+                    .write_event(Event::End(BytesEnd::new(name)))
+                    .map_err(|e| e.to_string())
+            }
+
+            /// Write an identity, qualified with the name of its module like "module:identity", declaring the name as the
+            /// prefix of the namespace of the module.
+            pub fn write_identity<W: std::io::Write, T: std::fmt::Display>(
+                writer: &mut Writer<W>,
+                name: &str,
+                namespace: Option<&str>,
+                value: &T,
+                identity_namespace: Option<&str>,
+            ) -> Result<(), String> {
+                let value = value.to_string();
+                let mut start = start_element(name, namespace);
+                if let (Some((prefix, _)), Some(identity_namespace)) = (value.split_once(':'), identity_namespace) {
+                    start.push_attribute((format!("xmlns:{}", prefix).as_str(), identity_namespace));
+                }
+                writer.write_event(Event::Start(start)).map_err(|e| e.to_string())?;
+                writer
+                    .write_event(Event::Text(BytesText::new(&value)))
+                    .map_err(|e| e.to_string())?;
+                writer
+                    .write_event(Event::End(BytesEnd::new(name)))
+                    .map_err(|e| e.to_string())
+            }
+
+            /// The content of an anydata or anyxml element, as raw XML. It's encoded as a string in JSON.
+            #[derive(Debug, Clone, Default, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+            #[serde(transparent)]
+            pub struct RawXml(pub String);
+
+            /// The content of an anydata or anyxml node in the encoding it was decoded from: a JSON value from JSON,
+            /// or the raw content of the element from XML, as neither can be converted to the other without a schema.
+            /// Raw XML is encoded as a string in JSON, while JSON values can't be written as XML.
+            #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+            #[serde(untagged)]
+            pub enum AnyData {
+                Json(serde_json::Value),
+                Xml(RawXml),
+            }
+
+            impl From<serde_json::Value> for AnyData {
+                fn from(value: serde_json::Value) -> Self {
+                    Self::Json(value)
+                }
+            }
+
+            impl From<RawXml> for AnyData {
+                fn from(value: RawXml) -> Self {
+                    Self::Xml(value)
+                }
+            }
+
+            pub fn write_anydata<W: std::io::Write>(
+                writer: &mut Writer<W>,
+                name: &str,
+                namespace: Option<&str>,
+                value: &AnyData,
+            ) -> Result<(), String> {
+                match value {
+                    AnyData::Xml(value) => write_raw(writer, name, namespace, value),
+                    AnyData::Json(_) => Err(format!("{} holds a JSON value, which can't be written as XML", name)),
+                }
+            }
+
+            pub fn write_raw<W: std::io::Write>(
+                writer: &mut Writer<W>,
+                name: &str,
+                namespace: Option<&str>,
+                value: &RawXml,
+            ) -> Result<(), String> {
+                writer
+                    .write_event(Event::Start(start_element(name, namespace)))
+                    .map_err(|e| e.to_string())?;
+                writer
+                    .write_event(Event::Text(BytesText::from_escaped(value.0.as_str())))
+                    .map_err(|e| e.to_string())?;
+                writer
+                    .write_event(Event::End(BytesEnd::new(name)))
+                    .map_err(|e| e.to_string())
+            }
+
+            pub fn write_empty<W: std::io::Write>(
+                writer: &mut Writer<W>,
+                name: &str,
+                namespace: Option<&str>,
+            ) -> Result<(), String> {
+                writer
+                    .write_event(Event::Empty(start_element(name, namespace)))
+                    .map_err(|e| e.to_string())
+            }
+
+            /// Read child elements into the node until the end of the enclosing element, or the end of the input.
+            /// Elements which aren't child nodes are skipped.
+            pub fn read_children<T: XmlNode>(reader: &mut Reader<&[u8]>, node: &mut T) -> Result<(), String> {
+                node.clear_defaults();
+                loop {
+                    match reader.read_event().map_err(|e| e.to_string())? {
+                        Event::Start(start) => {
+                            if !node.read_child(reader, &start, false)? {
+                                reader.read_to_end(start.name()).map_err(|e| e.to_string())?;
+                            }
+                        }
+                        Event::Empty(start) => {
+                            node.read_child(reader, &start, true)?;
+                        }
+                        Event::End(_) | Event::Eof => {
+                            node.restore_defaults();
+                            return Ok(());
+                        }
+                        _ => {}
+                    }
+                }
+            }
+
+            /// Read a node from the content of its element, starting from the node's defaults.
+            pub fn read_node<T: XmlNode + Default>(reader: &mut Reader<&[u8]>, empty: bool) -> Result<T, String> {
+                let mut node = T::default();
+                if !empty {
+                    read_children(reader, &mut node)?;
+                }
+                Ok(node)
+            }
+
+            pub fn read_leaf<T>(reader: &mut Reader<&[u8]>, start: &BytesStart, empty: bool) -> Result<T, String>
+            where
+                T: std::str::FromStr,
+                T::Err: std::fmt::Display,
+            {
+                let text = match empty {
+                    true => String::new(),
+                    false => {
+                        let text = reader.read_text(start.name()).map_err(|e| e.to_string())?;
+                        quick_xml::escape::unescape(&text.into_inner())
+                            .map_err(|e| e.to_string())?
+                            .into_owned()
+                    }
+                };
+                text.parse().map_err(|e: T::Err| e.to_string())
+            }
+
+            /// Read the content of an element as raw XML, including the elements it contains.
+            pub fn read_raw(reader: &mut Reader<&[u8]>, start: &BytesStart, empty: bool) -> Result<RawXml, String> {
+                match empty {
+                    true => Ok(RawXml::default()),
+                    false => {
+                        let text = reader.read_text(start.name()).map_err(|e| e.to_string())?;
+                        Ok(RawXml(text.into_inner().into_owned()))
+                    }
+                }
+            }
+
+            pub fn read_empty(reader: &mut Reader<&[u8]>, start: &BytesStart, empty: bool) -> Result<(), String> {
+                if !empty {
+                    reader.read_to_end(start.name()).map_err(|e| e.to_string())?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+// Code for no_std crates uses the modules of core and alloc rather than std, and every Rust module imports the items
+// of the std prelude which the generated code uses from alloc, as no_std crates only have the core prelude.
+pub(crate) fn no_std(tokens: TokenStream) -> TokenStream {
+    let prelude = quote! {
+        #[allow(unused_imports)]
+        use alloc::{borrow::ToOwned, boxed::Box, format, string::{String, ToString}, vec, vec::Vec};
+    };
+    let tokens = without_std(tokens, &prelude);
+
+    quote! {
+        #prelude
+        #tokens
+    }
+}
+
+pub(crate) fn without_std(tokens: TokenStream, prelude: &TokenStream) -> TokenStream {
+    const ALLOC_MODULES: &[&str] = &["borrow", "boxed", "collections", "string", "vec"];
+    let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+    tokens
+        .iter()
+        .enumerate()
+        .map(|(idx, token)| match token {
+            // A std path, like std::fmt::Display, starts with std followed by the two colons of the path separator.
+            TokenTree::Ident(ident) if ident == "std" => match tokens.get(idx + 3) {
+                Some(TokenTree::Ident(module)) if ALLOC_MODULES.contains(&module.to_string().as_str()) => {
+                    TokenTree::Ident(proc_macro2::Ident::new("alloc", ident.span()))
+                }
+                Some(TokenTree::Ident(_)) => TokenTree::Ident(proc_macro2::Ident::new("core", ident.span())),
+                _ => token.clone(),
+            },
+            TokenTree::Group(group) => {
+                let mut stream = without_std(group.stream(), prelude);
+                let module_body = group.delimiter() == Delimiter::Brace
+                    && idx >= 2
+                    && matches!(&tokens[idx - 2], TokenTree::Ident(keyword) if keyword == "mod");
+                if module_body {
+                    stream = prelude.clone().into_iter().chain(stream).collect();
+                }
+                let mut without_std = Group::new(group.delimiter(), stream);
+                without_std.set_span(group.span());
+                TokenTree::Group(without_std)
+            }
+            _ => token.clone(),
+        })
+        .collect()
+}
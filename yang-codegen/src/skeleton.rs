@@ -0,0 +1,316 @@
+//! Skeleton instance documents generated from the schema, similar to pyang's sample-xml-skeleton output.
+//! Leaves are filled with their default value when they have one, otherwise with a placeholder that is valid for
+//! the leaf's type where possible.
+
+use serde_json::{Map, Value as JsonValue};
+use yang_parser::{
+    model::*,
+    value::{parse_default, parse_value, validate_value},
+};
+
+use crate::utils;
+
+/// Generate a JSON (RFC 7951) skeleton for the subtree at the given schema path, like "/system/cpu".
+/// Use "/" for the whole module. Returns None if the path doesn't exist.
+pub fn json(module: &Module, path: &str) -> Option<String> {
+    let data_defs = select(module, path)?;
+    let mut object = Map::new();
+    json_members(&data_defs, &module.name, true, &mut object);
+
+    Some(serde_json::to_string_pretty(&JsonValue::Object(object)).expect("JSON values to always serialize"))
+}
+
+/// Generate an XML skeleton for the subtree at the given schema path, like "/system/cpu".
+/// Use "/" for the whole module. Returns None if the path doesn't exist.
+pub fn xml(module: &Module, path: &str) -> Option<String> {
+    let data_defs = select(module, path)?;
+    let mut output = String::new();
+    xml_nodes(module, &data_defs, None, 0, &mut output);
+
+    Some(output)
+}
+
+// Find the nodes to generate the skeleton for. Choices and cases are not part of data paths, so they are skipped
+// transparently when following the path.
+fn select(module: &Module, path: &str) -> Option<Vec<DataDef>> {
+    let mut data_defs: Vec<DataDef> = module
+        .body
+        .iter()
+        .filter_map(|node| match node {
+            SchemaNode::DataDef(data_def) => Some(data_def.clone()),
+            _ => None,
+        })
+        .collect();
+
+    let segments: Vec<&str> = path.split('/').filter(|segment| !segment.is_empty()).collect();
+    for (idx, segment) in segments.iter().enumerate() {
+        // Prefixes are allowed in the path, but all nodes belong to the same module anyway.
+        let name = segment.rsplit(':').next().unwrap_or(segment);
+        let node = flatten_choices(&data_defs)
+            .into_iter()
+            .find(|data_def| data_def_name(data_def) == Some(name))?;
+
+        data_defs = match idx + 1 == segments.len() {
+            true => vec![node],
+            false => children(&node),
+        };
+    }
+
+    Some(data_defs)
+}
+
+fn children(data_def: &DataDef) -> Vec<DataDef> {
+    match data_def {
+        DataDef::Container(container) => container.data_defs.clone(),
+        DataDef::List(list) => list.data_defs.clone(),
+        _ => Vec::new(),
+    }
+}
+
+// Replace every choice with the data nodes of all its cases.
+fn flatten_choices(data_defs: &[DataDef]) -> Vec<DataDef> {
+    data_defs
+        .iter()
+        .flat_map(|data_def| match data_def {
+            DataDef::Choice(choice) => flatten_choices(
+                &choice
+                    .cases
+                    .iter()
+                    .flat_map(case_data_defs)
+                    .collect::<Vec<_>>(),
+            ),
+            _ => vec![data_def.clone()],
+        })
+        .collect()
+}
+
+// The case used in the skeleton is the default case if there is one, otherwise the first case.
+fn selected_case(choice: &Choice) -> Option<&Case> {
+    choice
+        .default
+        .as_ref()
+        .and_then(|default| choice.cases.iter().find(|case| case_name(case) == default))
+        .or_else(|| choice.cases.first())
+}
+
+fn case_name(case: &Case) -> &str {
+    match case {
+        Case::LongCase(long_case) => &long_case.name,
+        Case::ShortCase(short_case) => match short_case {
+            ShortCase::Choice(choice) => &choice.name,
+            ShortCase::Container(container) => &container.name,
+            ShortCase::Leaf(leaf) => &leaf.name,
+            ShortCase::LeafList(leaf_list) => &leaf_list.name,
+            ShortCase::List(list) => &list.name,
+            ShortCase::Anydata(anydata) => &anydata.name,
+            ShortCase::Anyxml(anyxml) => &anyxml.name,
+        },
+    }
+}
+
+fn case_data_defs(case: &Case) -> Vec<DataDef> {
+    match case {
+        Case::LongCase(long_case) => long_case.data_defs.clone(),
+        Case::ShortCase(short_case) => vec![match short_case.clone() {
+            ShortCase::Choice(choice) => DataDef::Choice(choice),
+            ShortCase::Container(container) => DataDef::Container(container),
+            ShortCase::Leaf(leaf) => DataDef::Leaf(leaf),
+            ShortCase::LeafList(leaf_list) => DataDef::LeafList(leaf_list),
+            ShortCase::List(list) => DataDef::List(list),
+            ShortCase::Anydata(anydata) => DataDef::AnyData(anydata),
+            ShortCase::Anyxml(anyxml) => DataDef::Anyxml(anyxml),
+        }],
+    }
+}
+
+fn data_def_name(data_def: &DataDef) -> Option<&str> {
+    match data_def {
+        DataDef::Container(container) => Some(&container.name),
+        DataDef::Leaf(leaf) => Some(&leaf.name),
+        DataDef::LeafList(leaf_list) => Some(&leaf_list.name),
+        DataDef::List(list) => Some(&list.name),
+        DataDef::Choice(choice) => Some(&choice.name),
+        DataDef::AnyData(anydata) => Some(&anydata.name),
+        DataDef::Anyxml(anyxml) => Some(&anyxml.name),
+        DataDef::Uses(_) => None,
+    }
+}
+
+// Top-level members are qualified with the module name in RFC 7951 JSON, like the members added by augments of other
+// modules, which are qualified with the name of the other module.
+fn json_members(data_defs: &[DataDef], module_name: &str, top_level: bool, object: &mut Map<String, JsonValue>) {
+    for data_def in data_defs {
+        let node_module = utils::node_module(data_def).map_or(module_name, Symbol::as_str);
+        let member_name = |name: &str| match top_level || node_module != module_name {
+            true => format!("{}:{}", node_module, name),
+            false => name.to_string(),
+        };
+
+        match data_def {
+            DataDef::Container(container) => {
+                let mut members = Map::new();
+                json_members(&container.data_defs, node_module, false, &mut members);
+                object.insert(member_name(&container.name), JsonValue::Object(members));
+            }
+            DataDef::List(list) => {
+                let mut members = Map::new();
+                json_members(&list.data_defs, node_module, false, &mut members);
+                object.insert(member_name(&list.name), JsonValue::Array(vec![JsonValue::Object(members)]));
+            }
+            DataDef::Leaf(leaf) => {
+                let value = leaf_value(&leaf.type_info, leaf.default.as_deref(), &leaf.name);
+                object.insert(member_name(&leaf.name), utils::json_value(&leaf.type_info, &value));
+            }
+            DataDef::LeafList(leaf_list) => {
+                let values = match leaf_list.default.is_empty() {
+                    true => vec![leaf_value(&leaf_list.type_info, None, &leaf_list.name)],
+                    false => leaf_list
+                        .default
+                        .iter()
+                        .map(|default| canonical_default(&leaf_list.type_info, default))
+                        .collect(),
+                };
+                let values = values
+                    .iter()
+                    .map(|value| utils::json_value(&leaf_list.type_info, value))
+                    .collect();
+                object.insert(member_name(&leaf_list.name), JsonValue::Array(values));
+            }
+            DataDef::Choice(choice) => {
+                if let Some(case) = selected_case(choice) {
+                    json_members(&case_data_defs(case), module_name, top_level, object);
+                }
+            }
+            DataDef::AnyData(anydata) => {
+                object.insert(member_name(&anydata.name), JsonValue::Object(Map::new()));
+            }
+            DataDef::Anyxml(anyxml) => {
+                object.insert(member_name(&anyxml.name), JsonValue::Object(Map::new()));
+            }
+            DataDef::Uses(_) => {}
+        }
+    }
+}
+
+// XML elements of top-level nodes carry the module namespace, which is inherited by all nested elements. Elements of
+// nodes added by augments of other modules carry the namespace of the other module. The parent module is None for
+// the top-level nodes.
+fn xml_nodes(module: &Module, data_defs: &[DataDef], parent_module: Option<&str>, depth: usize, output: &mut String) {
+    let indent = "  ".repeat(depth);
+
+    for data_def in data_defs {
+        let node_module = utils::node_module(data_def).map_or(parent_module.unwrap_or(&module.name), Symbol::as_str);
+        let namespace = match parent_module {
+            None => Some(&module.namespace),
+            Some(parent_module) if node_module != parent_module => module.augmenting_modules.get(node_module),
+            Some(_) => None,
+        };
+        let open_tag = |name: &str| match namespace {
+            Some(namespace) => format!("{}<{} xmlns=\"{}\">", indent, name, escape_xml(namespace)),
+            None => format!("{}<{}>", indent, name),
+        };
+
+        match data_def {
+            DataDef::Container(container) => {
+                output.push_str(&format!("{}\n", open_tag(&container.name)));
+                xml_nodes(module, &container.data_defs, Some(node_module), depth + 1, output);
+                output.push_str(&format!("{}</{}>\n", indent, container.name));
+            }
+            DataDef::List(list) => {
+                output.push_str(&format!("{}\n", open_tag(&list.name)));
+                xml_nodes(module, &list.data_defs, Some(node_module), depth + 1, output);
+                output.push_str(&format!("{}</{}>\n", indent, list.name));
+            }
+            DataDef::Leaf(leaf) => {
+                let value = leaf_value(&leaf.type_info, leaf.default.as_deref(), &leaf.name);
+                output.push_str(&format!("{}{}</{}>\n", open_tag(&leaf.name), escape_xml(&value), leaf.name));
+            }
+            DataDef::LeafList(leaf_list) => {
+                let values = match leaf_list.default.is_empty() {
+                    true => vec![leaf_value(&leaf_list.type_info, None, &leaf_list.name)],
+                    false => leaf_list
+                        .default
+                        .iter()
+                        .map(|default| canonical_default(&leaf_list.type_info, default))
+                        .collect(),
+                };
+                for value in values {
+                    output.push_str(&format!(
+                        "{}{}</{}>\n",
+                        open_tag(&leaf_list.name),
+                        escape_xml(&value),
+                        leaf_list.name
+                    ));
+                }
+            }
+            DataDef::Choice(choice) => {
+                if let Some(case) = selected_case(choice) {
+                    xml_nodes(module, &case_data_defs(case), parent_module, depth, output);
+                }
+            }
+            DataDef::AnyData(anydata) => output.push_str(&format!("{}</{}>\n", open_tag(&anydata.name), anydata.name)),
+            DataDef::Anyxml(anyxml) => output.push_str(&format!("{}</{}>\n", open_tag(&anyxml.name), anyxml.name)),
+            DataDef::Uses(_) => {}
+        }
+    }
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn leaf_value(type_info: &TypeInfo, default: Option<&str>, name: &str) -> String {
+    match default {
+        Some(default) => canonical_default(type_info, default),
+        None => placeholder(type_info, name),
+    }
+}
+
+// The default in the canonical form of instance data, so integers written in hexadecimal or octal in the module are
+// decimal in the document. Defaults which can't be parsed, like those of typedefs, are kept as they are.
+fn canonical_default(type_info: &TypeInfo, default: &str) -> String {
+    parse_default(utils::resolved_type(type_info), default)
+        .map_or_else(|_| default.to_string(), |value| value.to_string())
+}
+
+// Pick the first candidate that is a valid value for the type, including its restrictions. Resolved leafrefs get a
+// value of the type they refer to, while types which can't be parsed, like typedefs and unresolved leafrefs, get an
+// empty placeholder.
+fn placeholder(type_info: &TypeInfo, name: &str) -> String {
+    let type_info = utils::resolved_type(type_info);
+    let lower_bound = |expression: &str| {
+        let bound = expression.split('|').next()?.split("..").next()?.trim();
+        (bound != "min").then(|| bound.to_string())
+    };
+
+    let candidates: Vec<String> = match (type_info.name.as_str(), type_info.type_body.as_deref()) {
+        (_, Some(TypeBody::Numerical { range })) => [lower_bound(&range.value), Some("0".into())]
+            .into_iter()
+            .flatten()
+            .collect(),
+        (_, Some(TypeBody::Decimal64 { range: Some(range), .. })) => [lower_bound(&range.value), Some("0.0".into())]
+            .into_iter()
+            .flatten()
+            .collect(),
+        ("decimal64", _) => vec!["0.0".into()],
+        ("boolean", _) => vec!["false".into()],
+        ("string", _) => vec![name.into(), "string".into(), "a".into(), "0".into(), String::new()],
+        (_, Some(TypeBody::Enum { enums })) => enums.iter().map(|e| e.name.clone()).take(1).collect(),
+        (_, Some(TypeBody::Bits { bits })) => bits.iter().map(|bit| bit.name.clone()).take(1).collect(),
+        (_, Some(TypeBody::Identityref { bases })) => bases.iter().take(1).cloned().collect(),
+        (_, Some(TypeBody::Union { types })) => types.iter().map(|member| placeholder(member, name)).collect(),
+        ("instance-identifier", _) => vec!["/".into()],
+        _ => vec!["0".into()],
+    };
+
+    candidates
+        .into_iter()
+        .find(|candidate| {
+            parse_value(type_info, candidate).is_ok_and(|value| validate_value(type_info, &value).is_ok())
+        })
+        .unwrap_or_default()
+}
@@ -0,0 +1,279 @@
+//! The definitions of generated structs, with their Default implementations, builders and the attributes of types.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use yang_parser::model::*;
+
+use crate::{CodeGenerator, Field};
+
+impl<'a> CodeGenerator<'a> {
+    // Trait implementations of generated structs which depend on where the struct is generated, as they use the
+    // helper modules generated at the top level.
+    pub(crate) fn generate_struct_impls(&self, struct_name: &syn::Ident, fields: &[Field]) -> TokenStream {
+        let xml_impl = self.generate_xml_impl(struct_name, fields);
+        let arbitrary_impl = self.generate_arbitrary_impl(struct_name, fields);
+        let validation = self.validation_path();
+        let validations = fields.iter().map(|field| &field.validation);
+        let allow_deprecated = allow_deprecated(fields);
+
+        quote! {
+            #xml_impl
+            #arbitrary_impl
+
+            // Structs without fields don't use the arguments.
+            #[allow(unused_variables)]
+            #allow_deprecated
+            impl #validation::Validate for #struct_name {
+                fn validate_at(&self, path: &str, errors: &mut Vec<#validation::ValidationError>) {
+                    #(#validations)*
+                }
+            }
+
+            impl #struct_name {
+                /// Check the restrictions of the node and all its descendants, like ranges, lengths and patterns,
+                /// returning every violated restriction.
+                pub fn validate(&self) -> Result<(), Vec<#validation::ValidationError>> {
+                    let mut errors = Vec::new();
+                    #validation::Validate::validate_at(self, "", &mut errors);
+                    match errors.is_empty() {
+                        true => Ok(()),
+                        false => Err(errors),
+                    }
+                }
+            }
+        }
+    }
+
+    // The extra derives and attributes from the config for the generated type, leaving out the derives the type already
+    // has.
+    pub(crate) fn type_attributes(&self, type_name: &syn::Ident, derived: &[&str]) -> TokenStream {
+        let derives: Vec<TokenStream> = self
+            .config
+            .derives
+            .iter()
+            .filter(|derive| !derived.contains(&derive.as_str()))
+            .map(|derive| derive.parse().expect("derive to be a valid path"))
+            .collect();
+        let derive = match derives.is_empty() {
+            true => quote! {},
+            false => quote! { #[derive(#(#derives),*)] },
+        };
+        let attributes = self
+            .config
+            .type_attributes
+            .iter()
+            .filter(|(name, _)| type_name == name)
+            .map(|(_, attribute)| attribute.parse::<TokenStream>().expect("attribute to be valid"));
+
+        quote! {
+            #derive
+            #(#attributes)*
+        }
+    }
+
+    // The attributes of a struct generated for nodes, which gets new fields when the model gains nodes.
+    pub(crate) fn struct_attributes(&self, struct_name: &syn::Ident) -> TokenStream {
+        let attributes = self.type_attributes(struct_name, &[]);
+        match self.config.non_exhaustive {
+            true => quote! {
+                #attributes
+                #[non_exhaustive]
+            },
+            false => attributes,
+        }
+    }
+}
+
+// Generated structs implement Default with the default of each field, so all their fields have to be known first.
+// Each struct also gets a builder, which starts from the defaults and checks that mandatory fields are set.
+pub(crate) fn generate_struct(
+    struct_name: &syn::Ident,
+    doc: &str,
+    attributes: TokenStream,
+    fields: &[Field],
+) -> TokenStream {
+    let definitions = fields.iter().map(|field| &field.definition);
+    let defaults = fields.iter().map(|field| &field.default);
+
+    let built = match fields.is_empty() {
+        true => quote! { built },
+        false => quote! { mut built },
+    };
+    let builder_name = quote::format_ident!("{}Builder", struct_name);
+    let builder_doc = format!("Builder for [{}].", struct_name);
+    let builder_fields = fields.iter().map(
+        |Field {
+             name, cfg, value_type, ..
+         }| {
+            quote! { #cfg #name: Option<#value_type>, }
+        },
+    );
+    let setters = fields.iter().map(
+        |Field {
+             name,
+             cfg,
+             deprecated,
+             value_type,
+             ..
+         }| {
+            // The setter of a field named build would clash with the build method.
+            let setter = match name == "build" {
+                true => quote::format_ident!("build_"),
+                false => name.clone(),
+            };
+            quote! {
+                #cfg
+                #deprecated
+                pub fn #setter(mut self, value: impl Into<#value_type>) -> Self {
+                    self.#name = Some(value.into());
+                    self
+                }
+            }
+        },
+    );
+    let build_fields = fields.iter().map(|field| {
+        let Field { name, cfg, .. } = field;
+        let value = match field.optional {
+            true => quote! { Some(value) },
+            false => quote! { value },
+        };
+        match &field.mandatory {
+            Some(yang_name) => {
+                let error = format!("missing mandatory node: {}", yang_name);
+                quote! {
+                    #cfg
+                    match self.#name {
+                        Some(value) => built.#name = #value,
+                        None => return Err(#error.to_string()),
+                    }
+                }
+            }
+            None => quote! {
+                #cfg
+                if let Some(value) = self.#name {
+                    built.#name = #value;
+                }
+            },
+        }
+    });
+
+    let allow_deprecated = allow_deprecated(fields);
+
+    quote! {
+        #[doc = #doc]
+        #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+        #attributes
+        #[serde(default)]
+        pub struct #struct_name {
+            #(#definitions)*
+        }
+
+        #allow_deprecated
+        impl Default for #struct_name {
+            fn default() -> Self {
+                Self {
+                    #(#defaults)*
+                }
+            }
+        }
+
+        impl #struct_name {
+            pub fn builder() -> #builder_name {
+                #builder_name::default()
+            }
+        }
+
+        #[doc = #builder_doc]
+        #[derive(Debug, Clone, Default)]
+        pub struct #builder_name {
+            #(#builder_fields)*
+        }
+
+        #allow_deprecated
+        impl #builder_name {
+            #(#setters)*
+
+            /// Build the struct, with the defaults of the fields which aren't set. Fails if a mandatory node, like a
+            /// mandatory leaf or a list key, isn't set.
+            pub fn build(self) -> Result<#struct_name, String> {
+                let #built = #struct_name::default();
+                #(#build_fields)*
+                Ok(built)
+            }
+        }
+    }
+}
+
+// The elements of nodes added by augments of other modules declare the namespace of the other module, which their
+// descendants inherit.
+pub(crate) fn in_namespace(field: Field, namespace: &str) -> Field {
+    if field.xml_write.is_empty() {
+        return field;
+    }
+
+    let xml_write = field.xml_write;
+    Field {
+        xml_write: quote! {
+            {
+                let namespace = Some(#namespace);
+                #xml_write
+            }
+        },
+        ..field
+    }
+}
+
+// Fields of deprecated and obsolete nodes are marked as deprecated, with the definition and the setter of the field
+// carrying the attribute.
+pub(crate) fn deprecated(field: Field, status: &Status) -> Field {
+    let note = match status {
+        Status::Current => return field,
+        Status::Deprecated => "The node is deprecated in the YANG module.",
+        Status::Obsolete => "The node is obsolete in the YANG module.",
+    };
+    let deprecated = quote! { #[deprecated(note = #note)] };
+    let definition = field.definition;
+
+    Field {
+        definition: quote! { #deprecated #definition },
+        deprecated,
+        ..field
+    }
+}
+
+// The generated impls of a struct with deprecated fields use the fields, which is allowed so only users of the fields
+// get warnings.
+pub(crate) fn allow_deprecated(fields: &[Field]) -> Option<TokenStream> {
+    fields
+        .iter()
+        .any(|field| !field.deprecated.is_empty())
+        .then(|| quote! { #[allow(deprecated)] })
+}
+
+// The numbers of entries generated for a list or leaf-list, from its min-elements to its max-elements, but no more than
+// a few more than its min-elements, so random trees stay small.
+pub(crate) fn entry_counts(
+    min_elements: Option<i64>,
+    max_elements: &Option<MaxElements>,
+) -> (proc_macro2::Literal, proc_macro2::Literal) {
+    let min = min_elements.unwrap_or(0).max(0) as usize;
+    let max = match max_elements {
+        Some(MaxElements::Value(max)) => (*max).max(0) as usize,
+        _ => usize::MAX,
+    };
+    (
+        proc_macro2::Literal::usize_unsuffixed(min),
+        proc_macro2::Literal::usize_unsuffixed(max.min(min + 3).max(min)),
+    )
+}
+
+pub(crate) fn generate_default_impl(type_name: &syn::Ident, default: Option<TokenStream>) -> Option<TokenStream> {
+    let default = default?;
+    Some(quote! {
+        impl Default for #type_name {
+            fn default() -> Self {
+                #default
+            }
+        }
+    })
+}
@@ -0,0 +1,503 @@
+//! The Rust types of YANG types: built-in types, typedefs and their defaults, and the types they are mapped to.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use yang_parser::model::*;
+
+use crate::{structs::generate_default_impl, utils, CodeGenerator, Warning};
+
+impl<'a> CodeGenerator<'a> {
+    // RFC 7951 encodes 64-bit integers and decimal64 values as JSON strings, so fields of these types are serialized
+    // with the helpers in the generated rfc7951 module. The helper is the name of the module with helpers for the
+    // kind of field, like "string" for plain fields and "option_string" for optional fields.
+    pub(crate) fn string_encoding(&mut self, type_info: &TypeInfo, helper: &str) -> Option<TokenStream> {
+        // Checked newtypes encode their values themselves.
+        if self.is_checked(type_info, &self.module.name) {
+            return None;
+        }
+        let helper = match type_info.name.as_str() {
+            "int64" | "uint64" | "decimal64" => helper.to_string(),
+            "binary" => helper.replace("string", "base64"),
+            _ => return None,
+        };
+
+        self.uses_string_encoding = true;
+        let path = format!("{}rfc7951::{}", "super::".repeat(self.depth), helper);
+        Some(quote! { #[serde(with = #path)] })
+    }
+
+    // Build an expression for a default value of a type, which is the type of a leaf or a typedef. The type path is
+    // the path to the generated type, for types defined inline in a leaf.
+    pub(crate) fn default_value(
+        &mut self,
+        type_path: &TokenStream,
+        type_info: &TypeInfo,
+        value: &str,
+        module_name: &str,
+    ) -> TokenStream {
+        match type_info.type_body.as_deref() {
+            Some(TypeBody::Enum { enums }) => {
+                let variant_names = utils::enum_variants(enums, self.config);
+                match enums.iter().position(|enum_value| enum_value.name == value) {
+                    Some(position) => {
+                        let variant_name = &variant_names[position];
+                        quote! { #type_path::#variant_name }
+                    }
+                    None => quote! { #value.parse::<#type_path>().expect("default value to be a valid enum") },
+                }
+            }
+            Some(TypeBody::Bits { bits }) => {
+                let bit_flags = utils::bit_flags(bits);
+                let flags: Option<Vec<&syn::Ident>> = value
+                    .split_whitespace()
+                    .map(|name| Some(&bit_flags[bits.iter().position(|bit| bit.name == name)?]))
+                    .collect();
+                match flags {
+                    Some(flags) if flags.is_empty() => quote! { #type_path::empty() },
+                    Some(flags) => quote! { #(#type_path::#flags)|* },
+                    None => quote! { #value.parse::<#type_path>().expect("default value to be valid bits") },
+                }
+            }
+            // The string form of identities is qualified with the module name rather than the prefix.
+            Some(TypeBody::Identityref { .. }) => {
+                let (identity_module, identity) = self.resolve_prefix(value, module_name);
+                let value = format!("{}:{}", identity_module, identity);
+                quote! { #value.parse::<#type_path>().expect("default value to be a valid identity") }
+            }
+            Some(TypeBody::Union { .. }) => {
+                quote! { #value.parse::<#type_path>().expect("default value to be valid for the union") }
+            }
+            _ if self.is_checked(type_info, module_name) => {
+                quote! { #value.parse::<#type_path>().expect("default value to satisfy the restrictions") }
+            }
+            _ => match self.find_type_def(&type_info.name, module_name) {
+                Some((type_module, path, type_def)) => {
+                    let type_def_path = self.type_def_path(type_module.clone(), path, type_def);
+                    match self.has_generated_type(&type_def.type_info, &type_module) {
+                        true => self.default_value(&type_def_path, &type_def.type_info, value, &type_module),
+                        false => {
+                            let base_value = self.base_default_value(&type_def.type_info, value, &type_module);
+                            quote! { #type_def_path(#base_value) }
+                        }
+                    }
+                }
+                None => self.default_literal(&type_info.name, value),
+            },
+        }
+    }
+
+    // Build an expression for a default value of the base type of a newtype, following the chain of typedefs like
+    // base_type does.
+    pub(crate) fn base_default_value(&mut self, type_info: &TypeInfo, value: &str, module_name: &str) -> TokenStream {
+        match self.find_type_def(&type_info.name, module_name) {
+            Some((type_module, path, type_def)) => match self.has_generated_type(&type_def.type_info, &type_module) {
+                true => {
+                    let type_def_path = self.type_def_path(type_module.clone(), path, type_def);
+                    self.default_value(&type_def_path, &type_def.type_info, value, &type_module)
+                }
+                false => self.base_default_value(&type_def.type_info, value, &type_module),
+            },
+            None => self.default_literal(&type_info.name, value),
+        }
+    }
+
+    // The Rust type of a built-in type, which is an exact decimal type for decimal64 if it's enabled. The empty type
+    // is the marker type of the RFC 7951 helpers, which is encoded as `[null]`. Unknown types, like typedefs of
+    // modules which aren't loaded, are strings, with a warning.
+    pub(crate) fn rust_type(&mut self, yang_type: &str) -> TokenStream {
+        match yang_type {
+            "decimal64" if self.config.rust_decimal => quote! { rust_decimal::Decimal },
+            "empty" => {
+                self.uses_string_encoding = true;
+                let rfc7951 = self.rfc7951_path();
+                quote! { #rfc7951::Empty }
+            }
+            _ => utils::yang_to_rust_type(yang_type).unwrap_or_else(|| {
+                self.warn(Warning::UnknownType(yang_type.to_string()));
+                quote! { String }
+            }),
+        }
+    }
+
+    // A Rust literal for the default value of a built-in type. Exact decimals are built from their mantissa and scale,
+    // and binary values are decoded from base64.
+    pub(crate) fn default_literal(&self, yang_type: &str, value: &str) -> TokenStream {
+        match yang_type == "decimal64" && self.config.rust_decimal {
+            true => match utils::decimal_parts(value) {
+                Some((mantissa, scale)) => {
+                    let mantissa = proc_macro2::Literal::i64_unsuffixed(mantissa);
+                    let scale = proc_macro2::Literal::u32_unsuffixed(scale);
+                    quote! { rust_decimal::Decimal::new(#mantissa, #scale) }
+                }
+                None => quote! { #value.parse().expect("default value to be valid") },
+            },
+            false if yang_type == "binary" => {
+                let rfc7951 = self.rfc7951_path();
+                quote! { #rfc7951::decode(#value).expect("default value to be valid base64") }
+            }
+            false => utils::default_literal(yang_type, value),
+        }
+    }
+
+    // Types defined inline in a leaf, like enumerations, are generated as a type named after the leaf in the parent's
+    // child module. Typedefs refer to their generated newtype, and built-in types are mapped directly to Rust types.
+    pub(crate) fn leaf_type(
+        &mut self,
+        leaf_name: &str,
+        type_info: &TypeInfo,
+        module_path: &TokenStream,
+    ) -> TokenStream {
+        match self.has_generated_type(type_info, &self.module.name) {
+            true => {
+                let type_name = self.config.naming.type_identifier(&self.node_name(leaf_name));
+                quote! { #module_path #type_name }
+            }
+            false => match self.find_type_def(&type_info.name, &self.module.name) {
+                Some((type_module, path, type_def)) => self.type_def_path(type_module, path, type_def),
+                None => self.named_type(&type_info.name, &self.module.name),
+            },
+        }
+    }
+
+    // Typedefs of the module itself are generated at the top level, while typedefs of imported modules are generated
+    // in a Rust module named after the imported module. The code of external modules is re-exported under that name
+    // instead.
+    pub(crate) fn generate_type_defs(&mut self) -> TokenStream {
+        let mut generated = BTreeSet::new();
+        let mut local_types = Vec::new();
+        let mut imported_types: BTreeMap<String, Vec<TokenStream>> = BTreeMap::new();
+        let mut external_modules = BTreeMap::new();
+
+        if self.all_type_defs {
+            let module_name = &self.module.name;
+            let top_level_paths = self.module.reference_nodes.type_defs.keys();
+            let top_level_paths = top_level_paths.filter(|path| path.rfind('/') == Some(0));
+            self.type_defs
+                .extend(top_level_paths.map(|path| (module_name.to_string(), path.clone())));
+        }
+
+        // Generating a typedef might reference other typedefs, so continue until every referenced typedef is generated.
+        while let Some((module_name, path)) = self.type_defs.difference(&generated).next().cloned() {
+            generated.insert((module_name.clone(), path.clone()));
+            if let Some((_, external_path)) = self
+                .config
+                .external_modules
+                .iter()
+                .find(|(name, _)| *name == module_name)
+            {
+                external_modules.insert(module_name, external_path);
+                continue;
+            }
+            let type_def = &self
+                .reference_nodes(&module_name)
+                .expect("referenced typedefs to be in a known module")
+                .type_defs[&path];
+
+            if module_name == self.module.name {
+                self.depth = 0;
+                local_types.push(self.generate_type_def(&module_name, type_def));
+            } else {
+                self.depth = 1;
+                let type_def = self.generate_type_def(&module_name, type_def);
+                imported_types.entry(module_name).or_default().push(type_def);
+            }
+        }
+        self.depth = 0;
+
+        let imported_modules = imported_types.iter().map(|(module_name, types)| {
+            let module_name = utils::sanitize_identifier(module_name);
+            quote! {
+                pub mod #module_name {
+                    #(#types)*
+                }
+            }
+        });
+        let external_modules = external_modules.iter().map(|(module_name, path)| {
+            let module_name = utils::sanitize_identifier(module_name);
+            let path: syn::Path = syn::parse_str(path).expect("paths of external modules to be Rust paths");
+            quote! { pub use #path as #module_name; }
+        });
+
+        quote! {
+            #(#local_types)*
+            #(#imported_modules)*
+            #(#external_modules)*
+        }
+    }
+
+    // Typedefs of enumerations, bits, identityrefs and unions are generated like the inline types of leaves, while
+    // other typedefs become a newtype around their base type.
+    pub(crate) fn generate_type_def(&mut self, module_name: &str, type_def: &TypeDef) -> TokenStream {
+        if let Some(type_def) = self.generate_leaf_type(
+            &type_def.name,
+            &type_def.type_info,
+            &type_def.description,
+            type_def.default.as_deref(),
+            module_name,
+        ) {
+            return type_def;
+        }
+
+        let type_name = self.config.naming.type_identifier(&type_def.name);
+        let base_type = self.base_type(&type_def.type_info, module_name);
+        let mut details = utils::type_details(&type_def.type_info);
+        details.extend([
+            ("Units", type_def.units.clone()),
+            ("Default", type_def.default.as_deref().map(utils::doc_code)),
+            ("Reference", type_def.reference.clone()),
+        ]);
+        let doc = utils::format_doc(&type_def.description, &details);
+        let default = match type_def
+            .default
+            .as_deref()
+            .or(self.type_default(&type_def.type_info, module_name))
+        {
+            Some(default) => self.base_default_value(&type_def.type_info, default, module_name),
+            None => quote! { Default::default() },
+        };
+        let default_impl = generate_default_impl(&type_name, Some(quote! { Self(#default) }));
+
+        // The empty type has no string form to format or parse, and binary values are formatted as base64.
+        let rfc7951 = self.rfc7951_path();
+        let base_is_built_in = self.base_is_built_in(&type_def.type_info, module_name);
+        let string_impls = match self.built_in_type(&type_def.type_info, module_name).as_str() {
+            "empty" => quote! {},
+            "binary" if base_is_built_in => quote! {
+                impl std::fmt::Display for #type_name {
+                    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                        f.write_str(&#rfc7951::encode(&self.0))
+                    }
+                }
+
+                impl std::str::FromStr for #type_name {
+                    type Err = String;
+
+                    fn from_str(s: &str) -> Result<Self, Self::Err> {
+                        #rfc7951::decode(s).map(Self)
+                    }
+                }
+            },
+            _ => quote! {
+                impl std::fmt::Display for #type_name {
+                    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                        std::fmt::Display::fmt(&self.0, f)
+                    }
+                }
+
+                impl std::str::FromStr for #type_name {
+                    type Err = <#base_type as std::str::FromStr>::Err;
+
+                    fn from_str(s: &str) -> Result<Self, Self::Err> {
+                        s.parse().map(Self)
+                    }
+                }
+            },
+        };
+
+        let string_encoding = self.base_string_encoding(&type_def.type_info, module_name);
+
+        // Newtypes of built-in types generate values with the restrictions of the typedef, while newtypes of generated
+        // and mapped types use the values of those types.
+        let arbitrary_impl = match self.proptest() {
+            true => {
+                let mut end = (type_def.type_info.clone(), module_name.to_string());
+                while let Some((type_module, _, type_def)) = self.find_type_def(&end.0.name, &end.1) {
+                    end = (type_def.type_info.clone(), type_module);
+                }
+                let value = match self.base_is_built_in(&type_def.type_info, module_name)
+                    && self.mapped_type(&end.0.name, &end.1).is_none()
+                {
+                    true => self.built_in_strategy(&type_def.type_info, module_name),
+                    false => quote! { proptest::arbitrary::any::<#base_type>() },
+                };
+                self.arbitrary_impl(&type_name, quote! { #value.prop_map(Self) })
+            }
+            false => quote! {},
+        };
+
+        // Newtypes of ordered types are ordered as well, so they can be keys of lists generated as maps.
+        let (ordering, derived): (TokenStream, &[&str]) = match self.orderable(&type_def.type_info, module_name) {
+            true => (
+                quote! { PartialEq, Eq, PartialOrd, Ord, Hash, },
+                &["PartialEq", "Eq", "PartialOrd", "Ord", "Hash"],
+            ),
+            false => (quote! {}, &[]),
+        };
+        let attributes = self.type_attributes(&type_name, derived);
+        quote! {
+            #[doc = #doc]
+            #[derive(Debug, Clone, #ordering serde::Serialize, serde::Deserialize)]
+            #attributes
+            #[serde(transparent)]
+            pub struct #type_name(#string_encoding pub #base_type);
+
+            #string_impls
+
+            #default_impl
+
+            #arbitrary_impl
+        }
+    }
+
+    // Find the serde helper needed for the base type of a newtype, following the chain of typedefs like base_type does.
+    pub(crate) fn base_string_encoding(&mut self, type_info: &TypeInfo, module_name: &str) -> Option<TokenStream> {
+        match self.find_type_def(&type_info.name, module_name) {
+            Some((type_module, _, type_def)) => match self.has_generated_type(&type_def.type_info, &type_module) {
+                true => None,
+                false => self.base_string_encoding(&type_def.type_info, &type_module),
+            },
+            None => self.string_encoding(type_info, "string"),
+        }
+    }
+
+    // Find the first default in the chain of typedefs, which is then the default of leaves using the type.
+    pub(crate) fn type_default(&self, type_info: &TypeInfo, module_name: &str) -> Option<&'a str> {
+        let (type_module, _, type_def) = self.find_type_def(&type_info.name, module_name)?;
+        type_def
+            .default
+            .as_deref()
+            .or_else(|| self.type_default(&type_def.type_info, &type_module))
+    }
+
+    // Find the name of the built-in type at the end of the chain of typedefs.
+    pub(crate) fn built_in_type(&self, type_info: &TypeInfo, module_name: &str) -> String {
+        match self.find_type_def(&type_info.name, module_name) {
+            Some((type_module, _, type_def)) => self.built_in_type(&type_def.type_info, &type_module),
+            None => type_info.name.to_string(),
+        }
+    }
+
+    // Follow the chain of typedefs down to the built-in type. Typedefs of enumerations, bits, identityrefs and unions
+    // have their own generated type, so the chain stops there.
+    pub(crate) fn base_type(&mut self, type_info: &TypeInfo, module_name: &str) -> TokenStream {
+        match self.find_type_def(&type_info.name, module_name) {
+            Some((type_module, path, type_def)) => match self.has_generated_type(&type_def.type_info, &type_module) {
+                true => self.type_def_path(type_module, path, type_def),
+                false => self.base_type(&type_def.type_info, &type_module),
+            },
+            None => self.named_type(&type_info.name, module_name),
+        }
+    }
+
+    // Whether the type gets a generated type, like an enum for an enumeration, rather than a built-in or typedef type.
+    pub(crate) fn has_generated_type(&self, type_info: &TypeInfo, module_name: &str) -> bool {
+        matches!(
+            type_info.type_body.as_deref(),
+            Some(TypeBody::Enum { .. })
+                | Some(TypeBody::Bits { .. })
+                | Some(TypeBody::Identityref { .. })
+                | Some(TypeBody::Union { .. })
+        ) || self.is_checked(type_info, module_name)
+    }
+
+    // Whether the type is generated as a checked newtype, which it is with checked_types if it has restrictions of its
+    // own. Types derived from typedefs mapped to Rust types aren't, as their values can't be checked.
+    pub(crate) fn is_checked(&self, type_info: &TypeInfo, module_name: &str) -> bool {
+        let restricted = match type_info.type_body.as_deref() {
+            Some(TypeBody::Numerical { .. }) => true,
+            Some(TypeBody::Decimal64 { range, .. }) => range.is_some(),
+            Some(TypeBody::String { length, patterns }) => length.is_some() || !patterns.is_empty(),
+            Some(TypeBody::Binary { length }) => length.is_some(),
+            _ => false,
+        };
+        if !self.config.checked_types || !restricted {
+            return false;
+        }
+
+        let mut current = (type_info.clone(), module_name.to_string());
+        while let Some((type_module, _, type_def)) = self.find_type_def(&current.0.name, &current.1) {
+            current = (type_def.type_info.clone(), type_module);
+        }
+        self.mapped_type(&current.0.name, &current.1).is_none()
+    }
+
+    // Whether the Rust type of the type is its built-in type, or a newtype of it, rather than a generated type.
+    pub(crate) fn base_is_built_in(&self, type_info: &TypeInfo, module_name: &str) -> bool {
+        if self.has_generated_type(type_info, module_name) {
+            return false;
+        }
+        match self.find_type_def(&type_info.name, module_name) {
+            Some((type_module, _, type_def)) => self.base_is_built_in(&type_def.type_info, &type_module),
+            None => true,
+        }
+    }
+
+    // Typedefs mapped to Rust types aren't found, as they are used like built-in types.
+    pub(crate) fn find_type_def(&self, name: &str, module_name: &str) -> Option<(String, String, &'a TypeDef)> {
+        if self.mapped_type(name, module_name).is_some() {
+            return None;
+        }
+        utils::find_type_def(self.module, name, module_name)
+    }
+
+    // The path of the Rust type the type mapper maps the typedef with the name to, if it's mapped.
+    pub(crate) fn mapped_type(&self, name: &str, module_name: &str) -> Option<String> {
+        let type_mapper = self.config.type_mapper.as_ref()?;
+        let (module_name, name) = self.resolve_prefix(name, module_name);
+        type_mapper.map_type(&module_name, name)
+    }
+
+    // The Rust type of a type which isn't a typedef to generate, which is either a typedef mapped to a Rust type or a
+    // built-in type.
+    pub(crate) fn named_type(&mut self, name: &str, module_name: &str) -> TokenStream {
+        match self.mapped_type(name, module_name) {
+            Some(rust_type) => rust_type.parse().expect("mapped type to be a valid type"),
+            None => self.rust_type(name),
+        }
+    }
+
+    pub(crate) fn resolve_prefix<'n>(&self, name: &'n str, module_name: &str) -> (String, &'n str) {
+        utils::resolve_prefix(self.module, name, module_name)
+    }
+
+    pub(crate) fn reference_nodes(&self, module_name: &str) -> Option<&'a ReferenceNodes> {
+        utils::reference_nodes(self.module, module_name)
+    }
+
+    // Mark the typedef as referenced and build the path to its generated type from the current depth.
+    pub(crate) fn type_def_path(&mut self, module_name: String, path: String, type_def: &TypeDef) -> TokenStream {
+        let supers = std::iter::repeat_n(quote! { super:: }, self.depth);
+        let type_name = self.config.naming.type_identifier(&type_def.name);
+        let module_path = match module_name == self.module.name {
+            true => quote! {},
+            false => {
+                let module_name = utils::sanitize_identifier(&module_name);
+                quote! { #module_name:: }
+            }
+        };
+
+        self.type_defs.insert((module_name, path));
+        quote! { #(#supers)* #module_path #type_name }
+    }
+
+    // The module name is the module the type statement is in, used to resolve the prefixes of identityref bases.
+    // The default is the default of a typedef, which becomes the default of the generated type. Generated types
+    // without a default from a typedef default to their first value, or no bits set for bits.
+    pub(crate) fn generate_leaf_type(
+        &mut self,
+        leaf_name: &str,
+        type_info: &TypeInfo,
+        description: &Option<String>,
+        default: Option<&str>,
+        module_name: &str,
+    ) -> Option<TokenStream> {
+        if !self.has_generated_type(type_info, module_name) {
+            return None;
+        }
+        let default = default.map(|default| self.default_value(&quote! { Self }, type_info, default, module_name));
+
+        match type_info.type_body.as_deref() {
+            Some(TypeBody::Enum { enums }) => Some(self.generate_enumeration(leaf_name, description, enums, default)),
+            Some(TypeBody::Bits { bits }) => Some(self.generate_bits(leaf_name, description, bits, default)),
+            Some(TypeBody::Identityref { bases }) => {
+                Some(self.generate_identityref(leaf_name, description, bases, default, module_name))
+            }
+            Some(TypeBody::Union { types }) => {
+                Some(self.generate_union(leaf_name, description, types, default, module_name))
+            }
+            _ => Some(self.generate_checked_type(leaf_name, description, type_info, default, module_name)),
+        }
+    }
+}
@@ -1,7 +1,21 @@
+use std::{collections::HashSet, sync::Arc};
+
 use proc_macro2::TokenStream;
-use quote::format_ident;
+use quote::{format_ident, quote};
+use yang_parser::{
+    model::{
+        Bit, Case, Choice, Container, DataDef, EnumValue, List, LongCase, Module, Must, NodeMeta, ReferenceNodes,
+        ShortCase, Status, TypeBody, TypeDef, TypeInfo, When,
+    },
+    value::{parse_default, Value},
+    Symbol,
+};
+
+use crate::{CaseConvention, Config, Naming};
 
-pub fn yang_to_rust_type(yang_type: &str) -> proc_macro2::TokenStream {
+/// The Rust type of a built-in YANG type, or None if the type isn't a built-in type, like a typedef which can't be
+/// resolved.
+pub fn yang_to_rust_type(yang_type: &str) -> Option<proc_macro2::TokenStream> {
     let rust_type = match yang_type.trim().to_lowercase().as_str() {
         "int8" => "i8",
         "int16" => "i16",
@@ -13,17 +27,267 @@ pub fn yang_to_rust_type(yang_type: &str) -> proc_macro2::TokenStream {
         "uint64" => "u64",
         "decimal64" => "f64",
         "string" => "String",
+        // Leafrefs whose path isn't resolved can't have the type of the node they refer to.
+        "leafref" => "String",
+        // Instance identifiers are kept as their path.
+        "instance-identifier" => "String",
         "boolean" => "bool",
         "empty" => "()",
-        _ => panic!("Unknown YANG type: {}", yang_type),
+        "binary" => "Vec<u8>",
+        _ => return None,
+    };
+
+    Some(rust_type.parse::<TokenStream>().expect("Failed to parse Rust type"))
+}
+
+/// Convert a default value of a built-in type to a Rust literal of the type from yang_to_rust_type.
+/// Values which can't be converted are parsed when the default is created instead.
+pub fn default_literal(yang_type: &str, value: &str) -> TokenStream {
+    let type_info = TypeInfo {
+        name: yang_type.into(),
+        type_body: None,
+    };
+
+    match parse_default(&type_info, value) {
+        Ok(Value::String(value)) => quote! { #value.to_string() },
+        Ok(Value::Boolean(value)) => quote! { #value },
+        Ok(value @ (Value::Int8(_) | Value::Int16(_) | Value::Int32(_) | Value::Int64(_))) => {
+            value.to_string().parse().expect("integers to be valid literals")
+        }
+        Ok(value @ (Value::Uint8(_) | Value::Uint16(_) | Value::Uint32(_) | Value::Uint64(_))) => {
+            value.to_string().parse().expect("integers to be valid literals")
+        }
+        _ => match value.parse::<f64>() {
+            Ok(value) if yang_type == "decimal64" => {
+                let value = proc_macro2::Literal::f64_unsuffixed(value);
+                quote! { #value }
+            }
+            _ => quote! { #value.parse().expect("default value to be valid") },
+        },
+    }
+}
+
+/// Keywords of Rust, including reserved keywords, which can't be used as identifiers unless they are raw identifiers.
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate", "do", "dyn", "else",
+    "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let", "loop", "macro", "match", "mod",
+    "move", "mut", "override", "priv", "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait",
+    "true", "try", "type", "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Split a name into words, at characters which can't be part of a Rust identifier, like "-" and ".", and where a
+/// lowercase letter or digit is followed by an uppercase letter, like "ifIndex" into "if" and "Index".
+fn words(id: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut start = None;
+    let mut previous: Option<char> = None;
+    for (idx, c) in id.char_indices() {
+        if !c.is_ascii_alphanumeric() {
+            if let Some(start) = start.take() {
+                words.push(&id[start..idx]);
+            }
+        } else if let Some(word_start) = start {
+            if c.is_ascii_uppercase() && previous.is_some_and(|p| p.is_ascii_lowercase() || p.is_ascii_digit()) {
+                words.push(&id[word_start..idx]);
+                start = Some(idx);
+            }
+        } else {
+            start = Some(idx);
+        }
+        previous = Some(c);
+    }
+    if let Some(start) = start {
+        words.push(&id[start..]);
+    }
+    words
+}
+
+/// Convert a name to snake_case, without making it a valid Rust identifier.
+pub fn snake_case(id: &str) -> String {
+    words(id)
+        .iter()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<String>>()
+        .join("_")
+}
+
+/// Convert a name to PascalCase, without making it a valid Rust identifier.
+pub fn pascal_case(id: &str) -> String {
+    words(id)
+        .iter()
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// Convert a name to the case. Words which are abbreviations, like "ip" in "ip-address", are written in uppercase in
+/// PascalCase and camelCase, unless they start a camelCase name.
+pub fn convert_case(id: &str, case: CaseConvention, abbreviations: &[String]) -> String {
+    let capitalize = |word: &str| match abbreviations
+        .iter()
+        .any(|abbreviation| abbreviation.eq_ignore_ascii_case(word))
+    {
+        true => word.to_uppercase(),
+        false => pascal_case(word),
     };
+    let words = words(id);
+    match case {
+        CaseConvention::Pascal => words.iter().map(|word| capitalize(word)).collect(),
+        CaseConvention::Camel => words
+            .iter()
+            .enumerate()
+            .map(|(idx, word)| match idx {
+                0 => word.to_lowercase(),
+                _ => capitalize(word),
+            })
+            .collect(),
+        CaseConvention::Snake => snake_case(id),
+        CaseConvention::ScreamingSnake => snake_case(id).to_uppercase(),
+    }
+}
+
+/// Make an identifier from a converted name. Names without any words get a placeholder, names starting with a digit
+/// get a leading underscore, and keywords become raw identifiers, or get a trailing underscore if they can't be raw.
+fn identifier(name: String) -> syn::Ident {
+    let name = match name.chars().next() {
+        None => "unnamed".to_string(),
+        Some(first) if first.is_ascii_digit() => format!("_{}", name),
+        Some(_) => name,
+    };
+    match name.as_str() {
+        "self" | "Self" | "super" | "crate" => format_ident!("{}_", name),
+        _ if KEYWORDS.contains(&name.as_str()) => syn::Ident::new_raw(&name, proc_macro2::Span::call_site()),
+        _ => format_ident!("{}", name),
+    }
+}
 
-    rust_type.parse::<TokenStream>().expect("Failed to parse Rust type")
+/// Split a decimal number, like "-12.345", into its mantissa and scale, like -12345 and 3.
+pub fn decimal_parts(value: &str) -> Option<(i64, u32)> {
+    let value = value.trim();
+    let (integer, fraction) = value.split_once('.').unwrap_or((value, ""));
+    let mantissa = format!("{}{}", integer, fraction).parse().ok()?;
+    Some((mantissa, fraction.len() as u32))
 }
 
+/// Convert a YANG name to a Rust field or module name in snake_case.
 pub fn sanitize_identifier(id: &str) -> syn::Ident {
-    let sanitized = id.replace("-", "_");
-    format_ident!("{}", sanitized)
+    identifier(snake_case(id))
+}
+
+/// Convert a YANG name to a Rust constant name in SCREAMING_SNAKE_CASE.
+pub fn constant_identifier(id: &str) -> syn::Ident {
+    identifier(snake_case(id).to_uppercase())
+}
+
+/// Make the names unique when converted to Rust identifiers, by appending a number to each name whose identifiers are
+/// taken by an earlier name, like "foo_bar" after "foo-bar" becoming "foo_bar-2". Other names are unchanged, so the
+/// identifiers only depend on the order of the names.
+pub fn unique_names<'a>(names: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    unique_names_by(names, |name| [snake_case(name), pascal_case(name)])
+}
+
+fn unique_names_by<'a>(
+    names: impl IntoIterator<Item = &'a str>,
+    identifiers: impl Fn(&str) -> [String; 2],
+) -> Vec<String> {
+    let mut taken = HashSet::new();
+    names
+        .into_iter()
+        .map(|name| {
+            let unique_name = (1..)
+                .map(|n| match n {
+                    1 => name.to_string(),
+                    n => format!("{}-{}", name, n),
+                })
+                .find(|candidate| {
+                    identifiers(candidate)
+                        .iter()
+                        .all(|identifier| !taken.contains(identifier))
+                })
+                .expect("a unique name to be found");
+            taken.extend(identifiers(&unique_name));
+            unique_name
+        })
+        .collect()
+}
+
+impl Naming {
+    /// Convert a YANG name to a Rust type or variant name.
+    pub fn type_identifier(&self, id: &str) -> syn::Ident {
+        identifier(self.type_name(id))
+    }
+
+    /// Convert a YANG name to a Rust field name.
+    pub fn field_identifier(&self, id: &str) -> syn::Ident {
+        identifier(self.field_name(id))
+    }
+
+    /// Make the names unique when converted to Rust identifiers with the conventions, like [unique_names].
+    pub fn unique_names<'a>(&self, names: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+        unique_names_by(names, |name| [self.field_name(name), self.type_name(name)])
+    }
+
+    fn type_name(&self, id: &str) -> String {
+        let id = self
+            .strip_prefixes
+            .iter()
+            .find_map(|prefix| id.strip_prefix(prefix.as_str()))
+            .filter(|stripped| !stripped.is_empty())
+            .unwrap_or(id);
+        convert_case(id, self.type_case, &self.abbreviations)
+    }
+
+    fn field_name(&self, id: &str) -> String {
+        convert_case(id, self.field_case, &self.abbreviations)
+    }
+}
+
+/// The name the key type of a list is made from, like "interface key" for InterfaceKey. It has a space, so it's never the
+/// name of a node.
+pub fn key_name(list_name: &str) -> String {
+    format!("{} key", list_name)
+}
+
+/// The name of the data node, which is empty for uses statements.
+pub fn data_def_name(data_def: &DataDef) -> &str {
+    match data_def {
+        DataDef::Container(container) => &container.name,
+        DataDef::Leaf(leaf) => &leaf.name,
+        DataDef::LeafList(leaf_list) => &leaf_list.name,
+        DataDef::List(list) => &list.name,
+        DataDef::Choice(choice) => &choice.name,
+        DataDef::AnyData(anydata) => &anydata.name,
+        DataDef::Anyxml(anyxml) => &anyxml.name,
+        DataDef::Uses(_) => "",
+    }
+}
+
+/// The module defining the data node, if it was added by an augment of another module.
+pub fn node_module(data_def: &DataDef) -> Option<&Symbol> {
+    match data_def {
+        DataDef::Container(container) => container.meta.module.as_ref(),
+        DataDef::Leaf(leaf) => leaf.meta.module.as_ref(),
+        DataDef::LeafList(leaf_list) => leaf_list.meta.module.as_ref(),
+        DataDef::List(list) => list.meta.module.as_ref(),
+        DataDef::Choice(choice) => choice.meta.module.as_ref(),
+        DataDef::AnyData(anydata) => anydata.meta.module.as_ref(),
+        DataDef::Anyxml(anyxml) => anyxml.meta.module.as_ref(),
+        DataDef::Uses(_) => None,
+    }
+}
+
+/// The name of a data node in instance data, qualified with the module defining the node when that isn't the module of
+/// its parent, which is the case for nodes added by augments of other modules.
+pub fn qualified_name(name: &str, module: Option<&Symbol>, parent_module: &str) -> String {
+    match module {
+        Some(module) if module != parent_module => format!("{}:{}", module, name),
+        _ => name.to_string(),
+    }
 }
 
 pub fn format_docstring(input: &Option<String>) -> String {
@@ -32,3 +296,653 @@ pub fn format_docstring(input: &Option<String>) -> String {
         None => "".into(),
     }
 }
+
+/// Format the description of a node as a doc comment, followed by a list of details documenting the node, like its
+/// reference or its units. The details are pairs of a label and a value, and details without a value are left out.
+pub fn format_doc(description: &Option<String>, details: &[(&str, Option<String>)]) -> String {
+    let details: Vec<String> = details
+        .iter()
+        .filter_map(|(label, value)| Some(format!(" - {}: {}", label, value.as_ref()?.replace('\n', "\n   "))))
+        .collect();
+    let description = format_docstring(description);
+    match (description.is_empty(), details.is_empty()) {
+        (_, true) => description,
+        (true, false) => details.join("\n"),
+        (false, false) => format!("{}\n\n{}", description, details.join("\n")),
+    }
+}
+
+/// Format a value as code in a doc comment, like an expression or a pattern.
+pub fn doc_code(value: &str) -> String {
+    format!("`{}`", value)
+}
+
+/// Resolve the prefix of a name from a statement in the module with the given name. Prefixed names refer to the module
+/// with that prefix, while names without a prefix, or with a prefix which isn't known, refer to the given module
+/// itself. Returns the module name and the name without the prefix.
+pub fn resolve_prefix<'n>(module: &Module, name: &'n str, module_name: &str) -> (String, &'n str) {
+    match name.split_once(':') {
+        Some((prefix, name)) if prefix == module.prefix => (module.name.to_string(), name),
+        Some((prefix, name)) => match module.prefix_to_module.get(prefix) {
+            Some(module_name) => (module_name.to_string(), name),
+            None => (module_name.to_string(), name),
+        },
+        None => (module_name.to_string(), name),
+    }
+}
+
+/// The reference nodes of the module with the name, which is the module itself or one of its imported modules.
+pub fn reference_nodes<'m>(module: &'m Module, module_name: &str) -> Option<&'m ReferenceNodes> {
+    match module_name == module.name {
+        true => Some(&module.reference_nodes),
+        false => module.imported_modules.get(module_name),
+    }
+}
+
+/// Find the typedef a type name refers to, from a type statement in the module with the given name, returning the name
+/// of the module defining the typedef, its path and the typedef. Typedefs at the top level are preferred, but typedefs
+/// defined within other statements are found by their name as well.
+pub fn find_type_def<'m>(module: &'m Module, name: &str, module_name: &str) -> Option<(String, String, &'m TypeDef)> {
+    let (module_name, name) = resolve_prefix(module, name, module_name);
+    let type_defs = &reference_nodes(module, &module_name)?.type_defs;
+    let top_level_path = format!("/{}", name);
+    let path = match type_defs.contains_key(&top_level_path) {
+        true => top_level_path,
+        false => type_defs
+            .keys()
+            .filter(|path| path.ends_with(&top_level_path))
+            .min()?
+            .clone(),
+    };
+    let type_def = &type_defs[&path];
+
+    Some((module_name, path, type_def))
+}
+
+/// The type of values of the type, which is the type of the node a leafref refers to if the leafref is resolved.
+pub fn resolved_type(type_info: &TypeInfo) -> &TypeInfo {
+    match type_info.type_body.as_deref() {
+        Some(TypeBody::Leafref {
+            target: Some(target), ..
+        }) => target,
+        _ => type_info,
+    }
+}
+
+/// The details documenting a type: its name and its restrictions.
+pub fn type_details(type_info: &TypeInfo) -> Vec<(&'static str, Option<String>)> {
+    let mut details = vec![("Type", Some(doc_code(&type_info.name)))];
+    match type_info.type_body.as_deref() {
+        Some(TypeBody::Numerical { range }) => details.push(("Range", Some(doc_code(&range.value)))),
+        Some(TypeBody::Decimal64 { fraction_digits, range }) => {
+            details.push(("Fraction digits", Some(fraction_digits.clone())));
+            details.push(("Range", range.as_ref().map(|range| doc_code(&range.value))));
+        }
+        Some(TypeBody::String { length, patterns }) => {
+            details.push(("Length", length.as_ref().map(|length| doc_code(&length.value))));
+            details.extend(patterns.iter().map(|pattern| match pattern.modifier.as_deref() {
+                Some("invert-match") => ("Not matching", Some(doc_code(&pattern.value))),
+                _ => ("Pattern", Some(doc_code(&pattern.value))),
+            }));
+        }
+        Some(TypeBody::Binary { length }) => {
+            details.push(("Length", length.as_ref().map(|length| doc_code(&length.value))))
+        }
+        Some(TypeBody::Leafref { path, .. }) => details.push(("Path", Some(doc_code(path)))),
+        Some(TypeBody::Identityref { bases }) => details.push(("Base", Some(bases.join(", ")))),
+        _ => {}
+    }
+    details
+}
+
+/// The details documenting the conditions of a node: its when statement and its must statements.
+pub fn condition_details(when: Option<&When>, must: &[Must]) -> Vec<(&'static str, Option<String>)> {
+    let mut details = vec![("When", when.map(|when| doc_code(&when.condition)))];
+    details.extend(must.iter().map(|must| ("Must", Some(doc_code(&must.condition)))));
+    details
+}
+
+/// Split the list's key statement into the names of the key leaves, without any prefixes.
+pub fn list_keys(list: &List) -> Vec<String> {
+    list.key
+        .as_deref()
+        .unwrap_or_default()
+        .split_whitespace()
+        .map(|key| key.rsplit(':').next().unwrap_or(key).to_string())
+        .collect()
+}
+
+/// Split a case into its name, description and data nodes. A short case is a case containing only the single node.
+pub fn case_parts(case: &Case) -> (String, Option<String>, Vec<DataDef>) {
+    match case {
+        Case::LongCase(long_case) => (
+            long_case.name.clone(),
+            long_case.meta.description.clone(),
+            long_case.data_defs.clone(),
+        ),
+        Case::ShortCase(short_case) => {
+            let (name, description, data_def) = match short_case.clone() {
+                ShortCase::Choice(choice) => (
+                    choice.name.clone(),
+                    choice.meta.description.clone(),
+                    DataDef::Choice(choice),
+                ),
+                ShortCase::Container(container) => (
+                    container.name.clone(),
+                    container.meta.description.clone(),
+                    DataDef::Container(container),
+                ),
+                ShortCase::Leaf(leaf) => (leaf.name.clone(), leaf.meta.description.clone(), DataDef::Leaf(leaf)),
+                ShortCase::LeafList(leaf_list) => (
+                    leaf_list.name.clone(),
+                    leaf_list.meta.description.clone(),
+                    DataDef::LeafList(leaf_list),
+                ),
+                ShortCase::List(list) => (list.name.clone(), list.meta.description.clone(), DataDef::List(list)),
+                ShortCase::Anydata(anydata) => (
+                    anydata.name.clone(),
+                    anydata.meta.description.clone(),
+                    DataDef::AnyData(anydata),
+                ),
+                ShortCase::Anyxml(anyxml) => (
+                    anyxml.name.clone(),
+                    anyxml.meta.description.clone(),
+                    DataDef::Anyxml(anyxml),
+                ),
+            };
+            (name, description, vec![data_def])
+        }
+    }
+}
+
+/// The grouping a container or list was used from, if it's the same wherever the grouping is used and neither it nor
+/// its descendants have actions or notifications, so its types can be shared.
+pub fn shared_grouping(data_def: &DataDef) -> Option<&Symbol> {
+    let grouping = match data_def {
+        DataDef::Container(container) => container.grouping.as_ref(),
+        DataDef::List(list) => list.grouping.as_ref(),
+        _ => None,
+    };
+    grouping.filter(|_| !has_operations(std::slice::from_ref(data_def)))
+}
+
+/// Whether the data nodes or any of their descendants have actions or notifications.
+pub fn has_operations(data_defs: &[DataDef]) -> bool {
+    data_defs.iter().any(|data_def| match data_def {
+        DataDef::Container(container) => {
+            !container.actions.is_empty() || !container.notifications.is_empty() || has_operations(&container.data_defs)
+        }
+        DataDef::List(list) => {
+            !list.actions.is_empty() || !list.notifications.is_empty() || has_operations(&list.data_defs)
+        }
+        DataDef::Choice(choice) => choice.cases.iter().any(|case| has_operations(&case_parts(case).2)),
+        _ => false,
+    })
+}
+
+/// The variant names of the enum values, which are unique among the values, and different from the variant of unknown
+/// values if the config gives enums one.
+pub fn enum_variants(enums: &[EnumValue], config: &Config) -> Vec<syn::Ident> {
+    variants(enums.iter().map(|enum_value| enum_value.name.as_str()), config)
+}
+
+/// The variant names of enums generated for enumerations and identityrefs, made from the names of their values.
+pub fn variants<'a>(names: impl IntoIterator<Item = &'a str>, config: &Config) -> Vec<syn::Ident> {
+    let reserved: &[&str] = match config.non_exhaustive {
+        true => &["unknown"],
+        false => &[],
+    };
+    config
+        .naming
+        .unique_names(reserved.iter().copied().chain(names))
+        .iter()
+        .skip(reserved.len())
+        .map(|name| config.naming.type_identifier(name))
+        .collect()
+}
+
+/// The flag names of the bits, which are unique among the bits.
+pub fn bit_flags(bits: &[Bit]) -> Vec<syn::Ident> {
+    unique_names(bits.iter().map(|bit| bit.name.as_str()))
+        .iter()
+        .map(|name| constant_identifier(name))
+        .collect()
+}
+
+/// The variant names of the cases of the choice, which are unique among the cases.
+pub fn case_variants(choice: &Choice, naming: &Naming) -> Vec<syn::Ident> {
+    let names: Vec<String> = choice.cases.iter().map(|case| case_parts(case).0).collect();
+    naming
+        .unique_names(names.iter().map(String::as_str))
+        .iter()
+        .map(|name| naming.type_identifier(name))
+        .collect()
+}
+
+/// Select the config or state part of a tree of data nodes. Nodes inherit the config property of their parent when
+/// they don't have one. Config data is every node with config true. State data is every node with config false, along
+/// with the config nodes and list keys leading to them, which identify where in the tree the state data is. Actions and
+/// notifications are not part of either.
+pub fn filter_config(data_defs: &[DataDef], parent_config: bool, config: bool) -> Vec<DataDef> {
+    data_defs
+        .iter()
+        .filter_map(|data_def| match data_def {
+            DataDef::Container(container) => {
+                let is_config = container.config.unwrap_or(parent_config);
+                let data_defs = filter_config(&container.data_defs, is_config, config);
+                let keep = match config {
+                    true => is_config,
+                    false => !is_config || !data_defs.is_empty(),
+                };
+                keep.then(|| {
+                    DataDef::Container(Arc::new(Container {
+                        data_defs,
+                        actions: Vec::new(),
+                        notifications: Vec::new(),
+                        ..(**container).clone()
+                    }))
+                })
+            }
+            DataDef::List(list) => {
+                let is_config = list.config.unwrap_or(parent_config);
+                let mut data_defs = filter_config(&list.data_defs, is_config, config);
+                let keep = match config {
+                    true => is_config,
+                    false => !is_config || !data_defs.is_empty(),
+                };
+                if keep && !config && is_config {
+                    let keys = list_keys(list);
+                    data_defs = list
+                        .data_defs
+                        .iter()
+                        .filter(|data_def| matches!(data_def, DataDef::Leaf(leaf) if keys.contains(&leaf.name)))
+                        .cloned()
+                        .chain(data_defs)
+                        .collect();
+                }
+                keep.then(|| {
+                    DataDef::List(Arc::new(List {
+                        data_defs,
+                        actions: Vec::new(),
+                        notifications: Vec::new(),
+                        ..(**list).clone()
+                    }))
+                })
+            }
+            DataDef::Choice(choice) => {
+                let is_config = choice.config.unwrap_or(parent_config);
+                let cases: Vec<Case> = choice
+                    .cases
+                    .iter()
+                    .filter_map(|case| {
+                        let (name, description, data_defs) = case_parts(case);
+                        let data_defs = filter_config(&data_defs, is_config, config);
+                        (!data_defs.is_empty()).then(|| {
+                            Case::LongCase(Arc::new(LongCase {
+                                name,
+                                meta: NodeMeta {
+                                    description,
+                                    ..Default::default()
+                                }
+                                .into(),
+                                data_defs,
+                                ..Default::default()
+                            }))
+                        })
+                    })
+                    .collect();
+                // The default case may not be part of the selected data.
+                let default = choice
+                    .default
+                    .clone()
+                    .filter(|default| cases.iter().any(|case| case_parts(case).0 == *default));
+                (!cases.is_empty()).then(|| {
+                    DataDef::Choice(Arc::new(Choice {
+                        cases,
+                        default,
+                        ..(**choice).clone()
+                    }))
+                })
+            }
+            DataDef::Leaf(leaf) => (leaf.config.unwrap_or(parent_config) == config).then(|| data_def.clone()),
+            DataDef::LeafList(leaf_list) => {
+                (leaf_list.config.unwrap_or(parent_config) == config).then(|| data_def.clone())
+            }
+            DataDef::AnyData(anydata) => (anydata.config.unwrap_or(parent_config) == config).then(|| data_def.clone()),
+            DataDef::Anyxml(anyxml) => (anyxml.config.unwrap_or(parent_config) == config).then(|| data_def.clone()),
+            DataDef::Uses(_) => None,
+        })
+        .collect()
+}
+
+/// Schema paths selecting the part of a tree of data nodes to generate. Paths are made of node names, like
+/// "/interfaces/interface", without choices and cases, and prefixes of the names are ignored.
+pub struct PathFilter {
+    include: Vec<String>,
+    exclude: Vec<String>,
+}
+
+impl PathFilter {
+    pub fn new(include: &[String], exclude: &[String]) -> Self {
+        let normalize = |paths: &[String]| {
+            paths
+                .iter()
+                .map(|path| {
+                    let steps = path.split('/').filter(|step| !step.is_empty());
+                    let steps = steps.map(|step| step.rsplit(':').next().unwrap_or(step));
+                    steps.map(|step| format!("/{}", step)).collect()
+                })
+                .collect()
+        };
+
+        Self {
+            include: normalize(include),
+            exclude: normalize(exclude),
+        }
+    }
+
+    /// Whether the node at the path is selected, which it is when it's at or below an included path, or every path is
+    /// included, and not at or below an excluded path.
+    pub fn selects(&self, path: &str) -> bool {
+        (self.include.is_empty() || within(&self.include, path)) && !within(&self.exclude, path)
+    }
+
+    // Whether the node at the path is above an included path, so it's needed to reach the included nodes.
+    fn leads_to_included(&self, path: &str) -> bool {
+        let prefix = format!("{}/", path);
+        self.include.iter().any(|filter_path| filter_path.starts_with(&prefix))
+    }
+
+    /// Select the data nodes at the selected paths, given the path of their parent, which is empty at the top level.
+    /// Containers and lists above included paths are kept with the nodes leading to the included paths, and lists are
+    /// always kept with their keys.
+    pub fn filter(&self, data_defs: &[DataDef], parent_path: &str) -> Vec<DataDef> {
+        data_defs
+            .iter()
+            .filter_map(|data_def| {
+                let path = format!("{}/{}", parent_path, data_def_name(data_def));
+                let selected = self.selects(&path);
+                let leads_to_included = self.leads_to_included(&path) && !within(&self.exclude, &path);
+                match data_def {
+                    DataDef::Container(container) => (selected || leads_to_included).then(|| {
+                        DataDef::Container(Arc::new(Container {
+                            data_defs: self.filter(&container.data_defs, &path),
+                            actions: self.filter_operations(&container.actions, &path, |action| &action.name),
+                            notifications: self
+                                .filter_operations(&container.notifications, &path, |notification| &notification.name),
+                            ..(**container).clone()
+                        }))
+                    }),
+                    DataDef::List(list) => (selected || leads_to_included).then(|| {
+                        let keys = list_keys(list);
+                        let data_defs = self.filter(&list.data_defs, &path);
+                        let missing_keys: Vec<DataDef> = list
+                            .data_defs
+                            .iter()
+                            .filter(|data_def| {
+                                matches!(data_def, DataDef::Leaf(leaf) if keys.contains(&leaf.name))
+                                    && !data_defs
+                                        .iter()
+                                        .any(|kept| data_def_name(kept) == data_def_name(data_def))
+                            })
+                            .cloned()
+                            .collect();
+                        DataDef::List(Arc::new(List {
+                            data_defs: missing_keys.into_iter().chain(data_defs).collect(),
+                            actions: self.filter_operations(&list.actions, &path, |action| &action.name),
+                            notifications: self
+                                .filter_operations(&list.notifications, &path, |notification| &notification.name),
+                            ..(**list).clone()
+                        }))
+                    }),
+                    DataDef::Choice(choice) => {
+                        let cases: Vec<Case> = choice
+                            .cases
+                            .iter()
+                            .filter_map(|case| {
+                                let (name, description, data_defs) = case_parts(case);
+                                let data_defs = self.filter(&data_defs, parent_path);
+                                (!data_defs.is_empty()).then(|| {
+                                    Case::LongCase(Arc::new(LongCase {
+                                        name,
+                                        meta: NodeMeta {
+                                            description,
+                                            ..Default::default()
+                                        }
+                                        .into(),
+                                        data_defs,
+                                        ..Default::default()
+                                    }))
+                                })
+                            })
+                            .collect();
+                        let default = choice
+                            .default
+                            .clone()
+                            .filter(|default| cases.iter().any(|case| case_parts(case).0 == *default));
+                        (!cases.is_empty()).then(|| {
+                            DataDef::Choice(Arc::new(Choice {
+                                cases,
+                                default,
+                                ..(**choice).clone()
+                            }))
+                        })
+                    }
+                    DataDef::Uses(_) => None,
+                    _ => selected.then(|| data_def.clone()),
+                }
+            })
+            .collect()
+    }
+
+    fn filter_operations<T: Clone>(&self, operations: &[T], parent_path: &str, name: impl Fn(&T) -> &String) -> Vec<T> {
+        operations
+            .iter()
+            .filter(|operation| self.selects(&format!("{}/{}", parent_path, name(operation))))
+            .cloned()
+            .collect()
+    }
+}
+
+// Whether the path is at or below one of the paths.
+fn within(paths: &[String], path: &str) -> bool {
+    paths
+        .iter()
+        .any(|filter_path| path == filter_path || path.starts_with(&format!("{}/", filter_path)))
+}
+
+/// Parse a range or length expression into its intervals. Intervals are separated by "|", and are either a single value
+/// or a lower and upper bound separated by "..". Returns None if a bound can't be parsed.
+pub fn parse_intervals<T: Clone>(expression: &str, parse_bound: impl Fn(&str) -> Option<T>) -> Option<Vec<(T, T)>> {
+    expression
+        .split('|')
+        .map(|interval| {
+            let (lower, upper) = interval.split_once("..").unwrap_or((interval, interval));
+            Some((parse_bound(lower.trim())?, parse_bound(upper.trim())?))
+        })
+        .collect()
+}
+
+/// Parse an integer of the built-in integer type, which may be written in hexadecimal or octal notation.
+pub fn parse_integer(type_name: &str, value: &str) -> Option<i128> {
+    let type_info = TypeInfo {
+        name: type_name.into(),
+        type_body: None,
+    };
+
+    parse_default(&type_info, value).ok()?.to_string().parse().ok()
+}
+
+/// The RFC 7951 JSON encoding of a value of the type written in the module, like a default, which encodes 64-bit
+/// integers and decimals as strings, and the empty type as [null]. Values which can't be parsed, like values of
+/// typedefs, are strings.
+pub fn json_value(type_info: &TypeInfo, value: &str) -> serde_json::Value {
+    let type_info = resolved_type(type_info);
+    match parse_default(type_info, value) {
+        Ok(Value::Int8(v)) => v.into(),
+        Ok(Value::Int16(v)) => v.into(),
+        Ok(Value::Int32(v)) => v.into(),
+        Ok(Value::Uint8(v)) => v.into(),
+        Ok(Value::Uint16(v)) => v.into(),
+        Ok(Value::Uint32(v)) => v.into(),
+        Ok(Value::Boolean(v)) => v.into(),
+        Ok(Value::Empty) => serde_json::Value::Array(vec![serde_json::Value::Null]),
+        Ok(value) => value.to_string().into(),
+        Err(_) => value.into(),
+    }
+}
+
+/// The smallest and largest value of the built-in integer type.
+pub fn integer_bounds(type_name: &str) -> (i128, i128) {
+    match type_name {
+        "int8" => (i8::MIN.into(), i8::MAX.into()),
+        "int16" => (i16::MIN.into(), i16::MAX.into()),
+        "int32" => (i32::MIN.into(), i32::MAX.into()),
+        "int64" => (i64::MIN.into(), i64::MAX.into()),
+        "uint8" => (0, u8::MAX.into()),
+        "uint16" => (0, u16::MAX.into()),
+        "uint32" => (0, u32::MAX.into()),
+        _ => (0, u64::MAX.into()),
+    }
+}
+
+/// The if-feature statements of the data node.
+pub fn if_features(data_def: &DataDef) -> &[String] {
+    match data_def {
+        DataDef::Container(container) => &container.if_features,
+        DataDef::Leaf(leaf) => &leaf.if_features,
+        DataDef::LeafList(leaf_list) => &leaf_list.if_features,
+        DataDef::List(list) => &list.if_features,
+        DataDef::Choice(choice) => &choice.if_features,
+        DataDef::AnyData(anydata) => &anydata.if_features,
+        DataDef::Anyxml(anyxml) => &anyxml.if_features,
+        DataDef::Uses(uses) => &uses.if_features,
+    }
+}
+
+/// The status of the data node, which is current if it has no status statement.
+pub fn status(data_def: &DataDef) -> &Status {
+    let status = match data_def {
+        DataDef::Container(container) => &container.meta.status,
+        DataDef::Leaf(leaf) => &leaf.meta.status,
+        DataDef::LeafList(leaf_list) => &leaf_list.meta.status,
+        DataDef::List(list) => &list.meta.status,
+        DataDef::Choice(choice) => &choice.meta.status,
+        DataDef::AnyData(anydata) => &anydata.meta.status,
+        DataDef::Anyxml(anyxml) => &anyxml.meta.status,
+        DataDef::Uses(uses) => &uses.status,
+    };
+    status.as_ref().unwrap_or(&Status::Current)
+}
+
+/// Convert an if-feature expression, like "a and (b or not c)", to the predicate of a cfg attribute, with the features
+/// named by feature_name. "not" binds tighter than "and", which binds tighter than "or", as described in RFC 7950.
+/// Returns None if the expression can't be parsed.
+pub fn if_feature_cfg(expression: &str, feature_name: &impl Fn(&str) -> String) -> Option<TokenStream> {
+    let spaced = expression
+        .trim_matches(['"', '\''])
+        .replace('(', " ( ")
+        .replace(')', " ) ");
+    let mut tokens = spaced.split_whitespace().peekable();
+    let cfg = parse_if_feature_or(&mut tokens, feature_name)?;
+    tokens.next().is_none().then_some(cfg)
+}
+
+type IfFeatureTokens<'a> = std::iter::Peekable<std::str::SplitWhitespace<'a>>;
+
+/// Whether an if-feature expression, like "a and (b or not c)", holds when the features for which is_supported returns
+/// true are supported. Returns None if the expression can't be parsed.
+pub fn if_feature_holds(expression: &str, is_supported: &impl Fn(&str) -> bool) -> Option<bool> {
+    let spaced = expression
+        .trim_matches(['"', '\''])
+        .replace('(', " ( ")
+        .replace(')', " ) ");
+    let mut tokens = spaced.split_whitespace().peekable();
+    let value = if_feature_holds_or(&mut tokens, is_supported)?;
+    tokens.next().is_none().then_some(value)
+}
+
+fn if_feature_holds_or(tokens: &mut IfFeatureTokens, is_supported: &impl Fn(&str) -> bool) -> Option<bool> {
+    let mut value = if_feature_holds_and(tokens, is_supported)?;
+    while tokens.next_if_eq(&"or").is_some() {
+        value |= if_feature_holds_and(tokens, is_supported)?;
+    }
+    Some(value)
+}
+
+fn if_feature_holds_and(tokens: &mut IfFeatureTokens, is_supported: &impl Fn(&str) -> bool) -> Option<bool> {
+    let mut value = if_feature_holds_factor(tokens, is_supported)?;
+    while tokens.next_if_eq(&"and").is_some() {
+        value &= if_feature_holds_factor(tokens, is_supported)?;
+    }
+    Some(value)
+}
+
+fn if_feature_holds_factor(tokens: &mut IfFeatureTokens, is_supported: &impl Fn(&str) -> bool) -> Option<bool> {
+    match tokens.next()? {
+        "not" => if_feature_holds_factor(tokens, is_supported).map(|value| !value),
+        "(" => {
+            let value = if_feature_holds_or(tokens, is_supported)?;
+            tokens.next_if_eq(&")").map(|_| value)
+        }
+        ")" | "and" | "or" => None,
+        name => Some(is_supported(name)),
+    }
+}
+
+/// Whether the feature of the module is supported by the features, given as a module and its features, like
+/// "ietf-interfaces:arbitrary-names,pre-provisioning". A module given without features supports none of them, and every
+/// feature of the modules which aren't given is supported.
+pub fn is_feature_supported(features: &[String], module: &str, feature: &str) -> bool {
+    let mut given = features
+        .iter()
+        .filter_map(|spec| {
+            let (spec_module, features) = spec.split_once(':').unwrap_or((spec, ""));
+            (spec_module == module).then_some(features)
+        })
+        .peekable();
+    given.peek().is_none() || given.any(|features| features.split(',').any(|name| name.trim() == feature))
+}
+
+fn parse_if_feature_or(tokens: &mut IfFeatureTokens, feature_name: &impl Fn(&str) -> String) -> Option<TokenStream> {
+    let mut terms = vec![parse_if_feature_and(tokens, feature_name)?];
+    while tokens.next_if_eq(&"or").is_some() {
+        terms.push(parse_if_feature_and(tokens, feature_name)?);
+    }
+    Some(match terms.len() {
+        1 => terms.remove(0),
+        _ => quote! { any(#(#terms),*) },
+    })
+}
+
+fn parse_if_feature_and(tokens: &mut IfFeatureTokens, feature_name: &impl Fn(&str) -> String) -> Option<TokenStream> {
+    let mut factors = vec![parse_if_feature_factor(tokens, feature_name)?];
+    while tokens.next_if_eq(&"and").is_some() {
+        factors.push(parse_if_feature_factor(tokens, feature_name)?);
+    }
+    Some(match factors.len() {
+        1 => factors.remove(0),
+        _ => quote! { all(#(#factors),*) },
+    })
+}
+
+fn parse_if_feature_factor(
+    tokens: &mut IfFeatureTokens,
+    feature_name: &impl Fn(&str) -> String,
+) -> Option<TokenStream> {
+    match tokens.next()? {
+        "not" => {
+            let factor = parse_if_feature_factor(tokens, feature_name)?;
+            Some(quote! { not(#factor) })
+        }
+        "(" => {
+            let cfg = parse_if_feature_or(tokens, feature_name)?;
+            tokens.next_if_eq(&")").map(|_| cfg)
+        }
+        ")" | "and" | "or" => None,
+        name => {
+            let name = feature_name(name);
+            Some(quote! { feature = #name })
+        }
+    }
+}
@@ -0,0 +1,104 @@
+//! The XML encoding of the generated structs.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use yang_parser::model::*;
+
+use crate::{structs::allow_deprecated, CodeGenerator, Field};
+
+impl<'a> CodeGenerator<'a> {
+    // Build the code writing a leaf or leaf-list value as an XML element, and the expression reading the value from the
+    // element. Values are written with their Display implementation and read with their FromStr implementation, except
+    // for the empty type, which is an element without content.
+    // Identityref values are written with the module name as the prefix, which the element declares as a namespace prefix.
+    pub(crate) fn xml_leaf_value(
+        &self,
+        name: &str,
+        type_info: &TypeInfo,
+    ) -> (impl Fn(TokenStream) -> TokenStream, TokenStream) {
+        let xml = self.xml_path();
+        let name = name.to_string();
+        let built_in = self.built_in_type(type_info, &self.module.name);
+        let empty = built_in == "empty";
+        let identityref = built_in == "identityref";
+        // Binary values are encoded as base64, while newtypes of binary typedefs have their own string form.
+        let binary = built_in == "binary"
+            && self.find_type_def(&type_info.name, &self.module.name).is_none()
+            && !self.is_checked(type_info, &self.module.name);
+        let rfc7951 = self.rfc7951_path();
+
+        let read_value = match (empty, binary) {
+            (true, _) => quote! {
+                {
+                    #xml::read_empty(reader, start, empty)?;
+                    Default::default()
+                }
+            },
+            (false, true) => quote! { #rfc7951::decode(&#xml::read_leaf::<String>(reader, start, empty)?)? },
+            (false, false) => quote! { #xml::read_leaf(reader, start, empty)? },
+        };
+        let write_value = move |value: TokenStream| match (empty, binary) {
+            (true, _) => quote! { #xml::write_empty(writer, #name, namespace)?; },
+            (false, _) if identityref => quote! {
+                #xml::write_identity(writer, #name, namespace, #value, (#value).namespace())?;
+            },
+            (false, true) => quote! { #xml::write_leaf(writer, #name, namespace, &#rfc7951::encode(#value))?; },
+            (false, false) => quote! { #xml::write_leaf(writer, #name, namespace, #value)?; },
+        };
+
+        (write_value, read_value)
+    }
+
+    pub(crate) fn xml_path(&self) -> TokenStream {
+        let supers = std::iter::repeat_n(quote! { super:: }, self.depth);
+        quote! { #(#supers)* xml }
+    }
+
+    // Structs implement the XmlNode trait from the generated xml module, writing and reading the elements of all fields.
+    pub(crate) fn generate_xml_impl(&self, struct_name: &syn::Ident, fields: &[Field]) -> TokenStream {
+        if !self.xml() {
+            return quote! {};
+        }
+
+        let xml = self.xml_path();
+        let writes = fields.iter().map(|field| &field.xml_write);
+        let reads = fields.iter().map(|field| &field.xml_read);
+        let clear_defaults = fields.iter().map(|field| &field.xml_clear_defaults);
+        let restore_defaults = fields.iter().map(|field| &field.xml_restore_defaults);
+        let allow_deprecated = allow_deprecated(fields);
+
+        // Structs without fields don't use the arguments.
+        quote! {
+            #[allow(unused_variables)]
+            #allow_deprecated
+            impl #xml::XmlNode for #struct_name {
+                fn write_children<W: std::io::Write>(
+                    &self,
+                    writer: &mut quick_xml::Writer<W>,
+                    namespace: Option<&str>,
+                ) -> Result<(), String> {
+                    #(#writes)*
+                    Ok(())
+                }
+
+                fn read_child(
+                    &mut self,
+                    reader: &mut quick_xml::Reader<&[u8]>,
+                    start: &quick_xml::events::BytesStart,
+                    empty: bool,
+                ) -> Result<bool, String> {
+                    #(#reads)*
+                    Ok(false)
+                }
+
+                fn clear_defaults(&mut self) {
+                    #(#clear_defaults)*
+                }
+
+                fn restore_defaults(&mut self) {
+                    #(#restore_defaults)*
+                }
+            }
+        }
+    }
+}
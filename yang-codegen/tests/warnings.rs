@@ -0,0 +1,42 @@
+use std::sync::{Arc, Mutex};
+
+use yang_codegen::{Config, Warning};
+
+// Generate the code of the module in tests/yang, returning the warnings reported to the sink of the config.
+fn warnings(file: &str, config: Config) -> Vec<Warning> {
+    let path = format!("{}/tests/yang/{}", env!("CARGO_MANIFEST_DIR"), file);
+    let module = yang_parser::parse(&path).expect("the module to parse");
+    let warnings = Arc::new(Mutex::new(Vec::new()));
+    let config = Config {
+        warnings: Some(warnings.clone()),
+        ..config
+    };
+    yang_codegen::generate_with_config(module, &config);
+    let warnings = warnings.lock().expect("the warnings to not be poisoned");
+    warnings.clone()
+}
+
+#[test]
+fn unsupported_if_feature_expressions_are_warnings() {
+    let config = Config {
+        if_features: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        warnings("features.yang", config),
+        [Warning::UnsupportedIfFeature("fast and".to_string())]
+    );
+}
+
+#[test]
+fn if_features_are_not_checked_without_cfg_attributes() {
+    assert_eq!(warnings("features.yang", Config::default()), []);
+}
+
+#[test]
+fn unknown_types_are_warnings() {
+    assert_eq!(
+        warnings("unknown-types.yang", Config::default()),
+        [Warning::UnknownType("ip-address".to_string())]
+    );
+}
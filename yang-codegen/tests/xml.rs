@@ -0,0 +1,35 @@
+use yang_codegen::Config;
+
+fn generate(file: &str, config: &Config) -> String {
+    let path = format!("{}/tests/yang/{}", env!("CARGO_MANIFEST_DIR"), file);
+    let module = yang_parser::parse(&path).expect("the module to parse");
+    yang_codegen::generate_with_config(module, config)
+}
+
+#[test]
+fn identities_declare_the_namespace_of_their_module() {
+    let config = Config {
+        xml: true,
+        ..Default::default()
+    };
+    let code = generate("interfaces.yang", &config);
+
+    assert!(code.contains(r#"Self::Ethernet => Some("urn:example:interface-types"),"#));
+    assert!(code.contains(r#"Self::Loopback => Some("urn:example:interfaces"),"#));
+    assert!(code.contains(r#"xml::write_identity(writer, "type", namespace, value, (value).namespace())?;"#));
+}
+
+#[test]
+fn anydata_is_json_unless_xml_is_enabled() {
+    let code = generate("interfaces.yang", &Config::default());
+    assert!(code.contains("pub statistics: Option<serde_json::Value>,"));
+
+    let config = Config {
+        xml: true,
+        ..Default::default()
+    };
+    let code = generate("interfaces.yang", &config);
+    assert!(code.contains("pub statistics: Option<xml::AnyData>,"));
+    assert!(code.contains(r#"xml::write_anydata(writer, "statistics", namespace, value)?;"#));
+    assert!(code.contains("xml::AnyData::Xml(xml::read_raw(reader, start, empty)?)"));
+}
@@ -0,0 +1,618 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{Map, Value as Json};
+use yang_parser::{
+    instance::{self, lexical},
+    model::{Module, TypeInfo},
+    schema_tree::{NodeId, NodeKind, SchemaTree},
+    types::TypeResolver,
+    value::{parse_and_validate as parse, Value},
+    ValueError,
+};
+
+use crate::{
+    proto::{typed_value, Decimal64, Notification, Path, PathElem, ScalarArray, TypedValue, Update},
+    GnmiError,
+};
+
+/// Converts between the data of a module and gNMI notifications, using the schema of the module for the keys of lists
+/// in paths and the types of values, so telemetry collectors can decode updates into typed data. Data is JSON encoded
+/// like RFC 7951, which is how the types generated by yang-codegen are serialized, so [Codec::updates_of] and
+/// [Codec::decode_into] convert generated types directly.
+///
+/// Leaves are updates whose typed values depend on the type of the leaf: integers are `int_val` or `uint_val`,
+/// booleans `bool_val`, binary `bytes_val`, decimal64 `decimal_val` with its digits and precision, so it stays exact,
+/// empty leaves `json_ietf_val` of `[null]`, and the other types `string_val` in their canonical form. Decimals sent as
+/// `double_val` or `float_val` are decoded as well. Leaf-lists are `leaflist_val`s, and anydata and anyxml
+/// `json_ietf_val`s. Names in paths are qualified with their module like the members of the JSON data, where the
+/// top-level nodes and nodes added by augments of other modules are qualified, like
+/// "/example-system:system/interface[name=eth0]/mtu".
+pub struct Codec<'m> {
+    module: &'m Module,
+    tree: SchemaTree<'m>,
+    types: TypeResolver<'m>,
+}
+
+impl<'m> Codec<'m> {
+    pub fn new(module: &'m Module) -> Self {
+        Self {
+            module,
+            tree: SchemaTree::new(module),
+            types: TypeResolver::new(module),
+        }
+    }
+
+    /// The updates of the leaves, leaf-lists, anydata and anyxml nodes of the data, with their full paths. The data is
+    /// checked against the schema first, like [yang_parser::instance::validate], and its first error is returned.
+    pub fn updates(&self, data: &Json) -> Result<Vec<Update>, GnmiError> {
+        if let Some(error) = instance::validate(&self.tree, &self.types, data).into_iter().next() {
+            return Err(error.into());
+        }
+        let mut updates = Vec::new();
+        self.encode_members(data, "", &mut Vec::new(), &mut updates)?;
+        Ok(updates)
+    }
+
+    /// The updates of a value of a generated type, or any other value serializing to JSON like RFC 7951.
+    pub fn updates_of<T: Serialize>(&self, data: &T) -> Result<Vec<Update>, GnmiError> {
+        self.updates(&serde_json::to_value(data)?)
+    }
+
+    /// A notification with the updates of the data, at the time in nanoseconds since the Unix epoch.
+    pub fn notification(&self, data: &Json, timestamp: i64) -> Result<Notification, GnmiError> {
+        Ok(Notification {
+            timestamp,
+            update: self.updates(data)?,
+            ..Default::default()
+        })
+    }
+
+    /// The data of the updates of the notification, merged into one tree of JSON like RFC 7951, with the values checked
+    /// against the types of their leaves and in their canonical form. The paths of the updates are relative to the
+    /// prefix of the notification, and names may be qualified with their module or not. Deletes aren't applied.
+    pub fn decode(&self, notification: &Notification) -> Result<Json, GnmiError> {
+        let mut data = Json::Object(Map::new());
+        for update in &notification.update {
+            let path = update
+                .path
+                .clone()
+                .unwrap_or_default()
+                .prefixed(notification.prefix.as_ref());
+            if let Some(value) = &update.val {
+                self.merge(&mut data, &path, value)?;
+            }
+        }
+        Ok(data)
+    }
+
+    /// The data of the updates of the notification, like [Codec::decode], as a generated type.
+    pub fn decode_into<T: DeserializeOwned>(&self, notification: &Notification) -> Result<T, GnmiError> {
+        Ok(serde_json::from_value(self.decode(notification)?)?)
+    }
+
+    /// The value of the leaf at the path, parsed and checked against the type of the leaf.
+    pub fn decode_value(&self, path: &Path, value: &TypedValue) -> Result<Value, GnmiError> {
+        let type_info = self.leaf_type(path)?;
+        let lexical = lexical_of_typed(&type_info, value).ok_or_else(|| unexpected(path, "a scalar value"))?;
+        parse(&type_info, &lexical).map_err(|source| GnmiError::InvalidValue {
+            path: path.to_string(),
+            source,
+        })
+    }
+
+    fn encode_members(
+        &self,
+        data: &Json,
+        schema_path: &str,
+        elems: &mut Vec<PathElem>,
+        updates: &mut Vec<Update>,
+    ) -> Result<(), GnmiError> {
+        let Json::Object(members) = data else {
+            return Err(unexpected(&Path::from_elems(elems.clone()), "an object"));
+        };
+
+        for (name, value) in members {
+            let node_path = format!("{}/{}", schema_path, local_name(name));
+            let mut path = Path::from_elems(elems.clone());
+            path.elem.push(PathElem::new(name.clone()));
+            let id = self
+                .tree
+                .find(&node_path)
+                .ok_or_else(|| GnmiError::UnknownNode(path.to_string()))?;
+            let name = self.member_name(id);
+
+            match self.tree.kind(id) {
+                NodeKind::Container(_) | NodeKind::Input(_) | NodeKind::Output(_) => {
+                    elems.push(PathElem::new(name));
+                    self.encode_members(value, &node_path, elems, updates)?;
+                    elems.pop();
+                }
+                NodeKind::List(list) => {
+                    let Json::Array(entries) = value else {
+                        return Err(unexpected(&path, "an array of list entries"));
+                    };
+                    for entry in entries {
+                        let mut elem = PathElem::new(name.clone());
+                        for key in list.key.iter().flat_map(|key| key.split_whitespace()) {
+                            let key_type = self.types.leaf_type(&format!("{}/{}", node_path, key));
+                            let key_value = entry
+                                .get(key)
+                                .zip(key_type)
+                                .and_then(|(value, key_type)| lexical(&key_type, value))
+                                .ok_or_else(|| unexpected(&path, "list entries with their keys"))?;
+                            elem.key.insert(key.to_string(), key_value);
+                        }
+                        elems.push(elem);
+                        self.encode_members(entry, &node_path, elems, updates)?;
+                        elems.pop();
+                    }
+                }
+                NodeKind::Leaf(_) => {
+                    let val = self.encode_leaf(&node_path, &path, value)?;
+                    updates.push(update(elems, name, val));
+                }
+                NodeKind::LeafList(_) => {
+                    let Json::Array(values) = value else {
+                        return Err(unexpected(&path, "an array of values"));
+                    };
+                    let element = values
+                        .iter()
+                        .map(|value| self.encode_leaf(&node_path, &path, value))
+                        .collect::<Result<_, _>>()?;
+                    let val = typed(typed_value::Value::LeaflistVal(ScalarArray { element }));
+                    updates.push(update(elems, name, val));
+                }
+                NodeKind::Anydata(_) | NodeKind::Anyxml(_) => {
+                    let val = typed(typed_value::Value::JsonIetfVal(serde_json::to_vec(value)?));
+                    updates.push(update(elems, name, val));
+                }
+                _ => return Err(GnmiError::UnknownNode(path.to_string())),
+            }
+        }
+        Ok(())
+    }
+
+    // The typed value of the JSON value of the leaf or leaf-list at the schema path.
+    fn encode_leaf(&self, schema_path: &str, path: &Path, value: &Json) -> Result<TypedValue, GnmiError> {
+        let Some(type_info) = self.types.leaf_type(schema_path) else {
+            return Err(GnmiError::UnknownNode(path.to_string()));
+        };
+        let lexical = lexical(&type_info, value).ok_or_else(|| unexpected(path, "a value encoded like its type"))?;
+        let value = match parse(&type_info, &lexical) {
+            Ok(value) => value,
+            // Types which can't be resolved, like typedefs of modules which failed to load, are sent as strings.
+            Err(ValueError::UnresolvedType(_)) => Value::String(lexical),
+            Err(source) => {
+                return Err(GnmiError::InvalidValue {
+                    path: path.to_string(),
+                    source,
+                })
+            }
+        };
+
+        Ok(typed(match value {
+            Value::Int8(v) => typed_value::Value::IntVal(v.into()),
+            Value::Int16(v) => typed_value::Value::IntVal(v.into()),
+            Value::Int32(v) => typed_value::Value::IntVal(v.into()),
+            Value::Int64(v) => typed_value::Value::IntVal(v),
+            Value::Uint8(v) => typed_value::Value::UintVal(v.into()),
+            Value::Uint16(v) => typed_value::Value::UintVal(v.into()),
+            Value::Uint32(v) => typed_value::Value::UintVal(v.into()),
+            Value::Uint64(v) => typed_value::Value::UintVal(v),
+            Value::Decimal64(v) => typed_value::Value::DecimalVal(Decimal64 {
+                digits: v.value,
+                precision: v.fraction_digits.into(),
+            }),
+            Value::Boolean(v) => typed_value::Value::BoolVal(v),
+            Value::Binary(v) => typed_value::Value::BytesVal(v),
+            Value::Empty => typed_value::Value::JsonIetfVal(b"[null]".to_vec()),
+            value => typed_value::Value::StringVal(value.to_string()),
+        }))
+    }
+
+    // Merge the value at the path into the data, adding the containers and list entries leading to it.
+    fn merge(&self, data: &mut Json, path: &Path, value: &TypedValue) -> Result<(), GnmiError> {
+        let schema_path = path.schema_path();
+        let id = match path.elem.is_empty() {
+            true => None,
+            false => Some(
+                self.tree
+                    .find(&schema_path)
+                    .ok_or_else(|| GnmiError::UnknownNode(path.to_string()))?,
+            ),
+        };
+
+        // The value of the root, a container or a list entry is its subtree, whose leaves are merged one by one, so
+        // they're checked against their types.
+        if id.is_none_or(|id| {
+            matches!(
+                self.tree.kind(id),
+                NodeKind::Container(_) | NodeKind::List(_) | NodeKind::Input(_) | NodeKind::Output(_)
+            )
+        }) {
+            let subtree = json_of_typed(path, value)?;
+            let mut updates = Vec::new();
+            self.encode_members(&subtree, &schema_path, &mut Vec::new(), &mut updates)
+                .map_err(|error| match error {
+                    GnmiError::UnknownNode(below) => GnmiError::UnknownNode(format!("{}{}", path, below)),
+                    error => error,
+                })?;
+            for update in updates {
+                if let Some(value) = &update.val {
+                    self.merge(data, &update.path.unwrap_or_default().prefixed(Some(path)), value)?;
+                }
+            }
+            return Ok(());
+        }
+
+        let mut node = data;
+        let mut schema_path = String::new();
+        for (index, elem) in path.elem.iter().enumerate() {
+            schema_path.push('/');
+            schema_path.push_str(local_name(&elem.name));
+            let id = self
+                .tree
+                .find(&schema_path)
+                .ok_or_else(|| GnmiError::UnknownNode(path.to_string()))?;
+            let name = self.member_name(id);
+            let last = index + 1 == path.elem.len();
+            let Json::Object(members) = node else {
+                return Err(unexpected(path, "an object"));
+            };
+
+            match self.tree.kind(id) {
+                NodeKind::Leaf(_) | NodeKind::LeafList(_) if last => {
+                    members.insert(name, self.leaf_json(&schema_path, path, value)?);
+                    return Ok(());
+                }
+                NodeKind::Anydata(_) | NodeKind::Anyxml(_) if last => {
+                    members.insert(name, json_of_typed(path, value)?);
+                    return Ok(());
+                }
+                NodeKind::List(list) => {
+                    let mut keys = Map::new();
+                    for key in list.key.iter().flat_map(|key| key.split_whitespace()) {
+                        let key_value = elem
+                            .key
+                            .get(key)
+                            .ok_or_else(|| unexpected(path, "the keys of every list entry"))?;
+                        let key_path = format!("{}/{}", schema_path, key);
+                        let key_value = self.leaf_json(&key_path, path, &string_value(key_value))?;
+                        keys.insert(key.to_string(), key_value);
+                    }
+                    let Json::Array(entries) = members.entry(name).or_insert_with(|| Json::Array(Vec::new())) else {
+                        return Err(unexpected(path, "an array of list entries"));
+                    };
+                    let position = entries
+                        .iter()
+                        .position(|entry| keys.iter().all(|(key, key_value)| entry.get(key) == Some(key_value)));
+                    node = match position {
+                        Some(position) => &mut entries[position],
+                        None => {
+                            entries.push(Json::Object(keys));
+                            entries.last_mut().expect("the entry to be added")
+                        }
+                    };
+                }
+                NodeKind::Container(_) | NodeKind::Input(_) | NodeKind::Output(_) => {
+                    node = members.entry(name).or_insert_with(|| Json::Object(Map::new()));
+                }
+                _ => break,
+            }
+        }
+        Err(unexpected(path, "a path to a data node"))
+    }
+
+    // The JSON value of the leaf or leaf-list at the schema path, checked against its type.
+    fn leaf_json(&self, schema_path: &str, path: &Path, value: &TypedValue) -> Result<Json, GnmiError> {
+        let type_info = self
+            .types
+            .leaf_type(schema_path)
+            .ok_or_else(|| GnmiError::UnknownNode(path.to_string()))?;
+        let scalar = |lexical: String| match parse(&type_info, &lexical) {
+            Ok(value) => Ok(json_of(&value)),
+            Err(ValueError::UnresolvedType(_)) => Ok(Json::String(lexical)),
+            Err(source) => Err(GnmiError::InvalidValue {
+                path: path.to_string(),
+                source,
+            }),
+        };
+
+        match &value.value {
+            Some(typed_value::Value::LeaflistVal(values)) => values
+                .element
+                .iter()
+                .map(|value| {
+                    let lexical =
+                        lexical_of_typed(&type_info, value).ok_or_else(|| unexpected(path, "scalar values"))?;
+                    scalar(lexical)
+                })
+                .collect(),
+            Some(typed_value::Value::JsonVal(_) | typed_value::Value::JsonIetfVal(_)) => {
+                match json_of_typed(path, value)? {
+                    Json::Array(values) if !is_empty_value(&values) => values
+                        .iter()
+                        .map(|value| {
+                            scalar(lexical(&type_info, value).ok_or_else(|| unexpected(path, "scalar values"))?)
+                        })
+                        .collect(),
+                    value => scalar(lexical(&type_info, &value).ok_or_else(|| unexpected(path, "a scalar value"))?),
+                }
+            }
+            _ => scalar(lexical_of_typed(&type_info, value).ok_or_else(|| unexpected(path, "a scalar value"))?),
+        }
+    }
+
+    fn leaf_type(&self, path: &Path) -> Result<TypeInfo, GnmiError> {
+        self.types
+            .leaf_type(&path.schema_path())
+            .ok_or_else(|| GnmiError::UnknownNode(path.to_string()))
+    }
+
+    // The name of the node in JSON data and paths, which is qualified with its module at the top level and where the
+    // module changes, like below the target of an augment of another module.
+    fn member_name(&self, id: NodeId) -> String {
+        let module = self.node_module(id);
+        let parent_module = self
+            .tree
+            .ancestors(id)
+            .find(|id| self.tree.kind(*id).is_in_path())
+            .map(|id| self.node_module(id));
+        let name = self.tree.kind(id).name();
+        match parent_module {
+            Some(parent_module) if parent_module == module => name.to_string(),
+            _ => format!("{}:{}", module, name),
+        }
+    }
+
+    // The module defining the node, which nodes added by augments of other modules record, and other nodes inherit.
+    fn node_module(&self, id: NodeId) -> &'m str {
+        std::iter::once(id)
+            .chain(self.tree.ancestors(id))
+            .find_map(|id| match self.tree.kind(id) {
+                NodeKind::Container(container) => container.meta.module.as_deref(),
+                NodeKind::Leaf(leaf) => leaf.meta.module.as_deref(),
+                NodeKind::LeafList(leaf_list) => leaf_list.meta.module.as_deref(),
+                NodeKind::List(list) => list.meta.module.as_deref(),
+                NodeKind::Choice(choice) => choice.meta.module.as_deref(),
+                NodeKind::Anydata(anydata) => anydata.meta.module.as_deref(),
+                NodeKind::Anyxml(anyxml) => anyxml.meta.module.as_deref(),
+                _ => None,
+            })
+            .unwrap_or(&self.module.name)
+    }
+}
+
+fn update(elems: &[PathElem], name: String, val: TypedValue) -> Update {
+    let mut path = Path::from_elems(elems.to_vec());
+    path.elem.push(PathElem::new(name));
+    Update {
+        path: Some(path),
+        val: Some(val),
+        duplicates: 0,
+    }
+}
+
+fn typed(value: typed_value::Value) -> TypedValue {
+    TypedValue { value: Some(value) }
+}
+
+fn string_value(value: &str) -> TypedValue {
+    typed(typed_value::Value::StringVal(value.to_string()))
+}
+
+fn unexpected(path: &Path, expected: &'static str) -> GnmiError {
+    GnmiError::UnexpectedData {
+        path: path.to_string(),
+        expected,
+    }
+}
+
+fn local_name(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+// The value of an empty leaf in JSON, which is [null].
+fn is_empty_value(values: &[Json]) -> bool {
+    matches!(values, [Json::Null])
+}
+
+// The lexical representation of a scalar typed value of the type, like "1.5" for a decimal with the digits 15 and
+// precision 1. JSON values have to be encoded like the type, see [lexical].
+fn lexical_of_typed(type_info: &TypeInfo, value: &TypedValue) -> Option<String> {
+    match value.value.as_ref()? {
+        typed_value::Value::StringVal(string) | typed_value::Value::AsciiVal(string) => Some(string.clone()),
+        typed_value::Value::IntVal(v) => Some(v.to_string()),
+        typed_value::Value::UintVal(v) => Some(v.to_string()),
+        typed_value::Value::BoolVal(v) => Some(v.to_string()),
+        typed_value::Value::BytesVal(bytes) => Some(STANDARD.encode(bytes)),
+        typed_value::Value::FloatVal(v) => Some(v.to_string()),
+        typed_value::Value::DoubleVal(v) => Some(v.to_string()),
+        typed_value::Value::DecimalVal(decimal) => {
+            let digits = decimal.digits.unsigned_abs().to_string();
+            let precision = decimal.precision as usize;
+            let digits = format!("{:0>width$}", digits, width = precision + 1);
+            let (integer, fraction) = digits.split_at(digits.len() - precision);
+            let sign = if decimal.digits < 0 { "-" } else { "" };
+            match fraction.is_empty() {
+                true => Some(format!("{}{}", sign, integer)),
+                false => Some(format!("{}{}.{}", sign, integer, fraction)),
+            }
+        }
+        typed_value::Value::JsonVal(json) | typed_value::Value::JsonIetfVal(json) => {
+            lexical(type_info, &serde_json::from_slice(json).ok()?)
+        }
+        typed_value::Value::LeaflistVal(_) | typed_value::Value::ProtoBytes(_) => None,
+    }
+}
+
+// The JSON of a typed value holding a subtree or an anydata value.
+fn json_of_typed(path: &Path, value: &TypedValue) -> Result<Json, GnmiError> {
+    match &value.value {
+        Some(typed_value::Value::JsonVal(json) | typed_value::Value::JsonIetfVal(json)) => {
+            Ok(serde_json::from_slice(json)?)
+        }
+        _ => Err(unexpected(path, "a JSON value")),
+    }
+}
+
+// The JSON of a value like RFC 7951, where 64-bit integers and decimals are strings so they keep their precision.
+fn json_of(value: &Value) -> Json {
+    match value {
+        Value::Int8(v) => (*v).into(),
+        Value::Int16(v) => (*v).into(),
+        Value::Int32(v) => (*v).into(),
+        Value::Uint8(v) => (*v).into(),
+        Value::Uint16(v) => (*v).into(),
+        Value::Uint32(v) => (*v).into(),
+        Value::Boolean(v) => (*v).into(),
+        Value::Empty => Json::Array(vec![Json::Null]),
+        value => Json::String(value.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use yang_parser::{model::YangModule, Document};
+
+    use super::*;
+
+    const MODULE: &str = r#"
+        module example-system {
+            namespace "urn:example:system";
+            prefix sys;
+
+            container system {
+                leaf hostname {
+                    type string;
+                }
+                list interface {
+                    key "name";
+                    leaf name {
+                        type string;
+                    }
+                    leaf mtu {
+                        type uint16 {
+                            range "68..9000";
+                        }
+                    }
+                    leaf load {
+                        type decimal64 {
+                            fraction-digits 2;
+                        }
+                    }
+                    leaf-list address {
+                        type string;
+                    }
+                    leaf enabled {
+                        type empty;
+                    }
+                }
+                anydata statistics;
+            }
+        }
+    "#;
+
+    fn document() -> Document {
+        Document::parse(MODULE).expect("the module to parse")
+    }
+
+    fn module(document: &Document) -> &Module {
+        match document.module() {
+            YangModule::Module(module) => module,
+            YangModule::Submodule(_) => panic!("expected a module"),
+        }
+    }
+
+    fn data() -> Json {
+        json!({
+            "example-system:system": {
+                "hostname": "router",
+                "interface": [
+                    {
+                        "name": "eth0",
+                        "mtu": 1500,
+                        "load": "2.55",
+                        "address": ["192.0.2.1", "192.0.2.2"],
+                        "enabled": [null]
+                    },
+                    {"name": "eth1", "mtu": 9000}
+                ],
+                "statistics": {"uptime": 42}
+            }
+        })
+    }
+
+    #[test]
+    fn notifications_decode_into_the_data_they_were_made_of() {
+        let document = document();
+        let codec = Codec::new(module(&document));
+        let notification = codec.notification(&data(), 1).expect("the data to encode");
+        assert_eq!(notification.update.len(), 9);
+        assert_eq!(codec.decode(&notification).expect("the notification to decode"), data());
+    }
+
+    #[test]
+    fn leaves_are_typed_values_of_their_type() {
+        let document = document();
+        let codec = Codec::new(module(&document));
+        let updates = codec.updates(&data()).expect("the data to encode");
+        let value = |path: &str| {
+            updates
+                .iter()
+                .find(|update| update.path.as_ref().map(Path::to_string).as_deref() == Some(path))
+                .and_then(|update| update.val.clone()?.value)
+                .unwrap_or_else(|| panic!("an update of {}", path))
+        };
+
+        let interface = "/example-system:system/interface[name=eth0]";
+        assert_eq!(value(&format!("{}/mtu", interface)), typed_value::Value::UintVal(1500));
+        assert_eq!(
+            value(&format!("{}/load", interface)),
+            typed_value::Value::DecimalVal(Decimal64 {
+                digits: 255,
+                precision: 2
+            })
+        );
+        assert_eq!(
+            value(&format!("{}/enabled", interface)),
+            typed_value::Value::JsonIetfVal(b"[null]".to_vec())
+        );
+        assert_eq!(
+            value(&format!("{}/address", interface)),
+            typed_value::Value::LeaflistVal(ScalarArray {
+                element: vec![string_value("192.0.2.1"), string_value("192.0.2.2")]
+            })
+        );
+        assert_eq!(
+            value("/example-system:system/statistics"),
+            typed_value::Value::JsonIetfVal(br#"{"uptime":42}"#.to_vec())
+        );
+    }
+
+    #[test]
+    fn decimals_sent_as_doubles_are_decoded() {
+        let document = document();
+        let codec = Codec::new(module(&document));
+        let path: Path = "/system/interface[name=eth0]/load".parse().expect("the path to parse");
+        let value = codec
+            .decode_value(&path, &typed(typed_value::Value::DoubleVal(2.5)))
+            .expect("the value to decode");
+        assert_eq!(value.to_string(), "2.5");
+    }
+
+    #[test]
+    fn invalid_data_is_not_encoded() {
+        let document = document();
+        let codec = Codec::new(module(&document));
+        let data = json!({"example-system:system": {"interface": [{"name": "eth0", "mtu": 67}]}});
+        let Err(GnmiError::InvalidData(error)) = codec.updates(&data) else {
+            panic!("expected invalid data");
+        };
+        assert_eq!(
+            error.to_string(),
+            "/example-system:system/interface[1]/mtu: value 67 is outside of the allowed range 68..9000"
+        );
+    }
+}
@@ -0,0 +1,27 @@
+use yang_parser::{instance::DataError, ValueError};
+
+/// An error converting between gNMI messages and the data of a module.
+#[derive(thiserror::Error, Debug)]
+pub enum GnmiError {
+    #[error("invalid path {path}: {reason}")]
+    InvalidPath { path: String, reason: &'static str },
+
+    #[error("{0} is not a data node of the module")]
+    UnknownNode(String),
+
+    #[error("invalid data at {path}, expected {expected}")]
+    UnexpectedData { path: String, expected: &'static str },
+
+    #[error("invalid value at {path}")]
+    InvalidValue {
+        path: String,
+        #[source]
+        source: ValueError,
+    },
+
+    #[error("invalid data")]
+    InvalidData(#[from] DataError),
+
+    #[error("invalid JSON")]
+    Json(#[from] serde_json::Error),
+}
@@ -1,14 +1,17 @@
-pub fn add(left: u64, right: u64) -> u64 {
-    left + right
-}
+//! Conversions between gNMI messages and the data of YANG modules, with the `proto` feature: the [proto] messages of
+//! gNMI which carry data, paths written like "/interfaces/interface[name=eth0]", and a [Codec] converting between
+//! notifications and data checked against a module's schema.
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+#[cfg(feature = "proto")]
+mod codec;
+#[cfg(feature = "proto")]
+mod error;
+#[cfg(feature = "proto")]
+mod path;
+#[cfg(feature = "proto")]
+pub mod proto;
 
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
-    }
-}
+#[cfg(feature = "proto")]
+pub use codec::Codec;
+#[cfg(feature = "proto")]
+pub use error::GnmiError;
@@ -0,0 +1,119 @@
+use std::{fmt, str::FromStr};
+
+use crate::{
+    proto::{Path, PathElem},
+    GnmiError,
+};
+
+impl Path {
+    /// The path of the elements, without an origin or target.
+    pub fn from_elems(elem: Vec<PathElem>) -> Self {
+        Self {
+            elem,
+            ..Default::default()
+        }
+    }
+
+    /// The path of the prefix followed by this path, like the full path of an update in a notification. The origin and
+    /// target are the prefix's.
+    pub fn prefixed(&self, prefix: Option<&Path>) -> Path {
+        match prefix {
+            Some(prefix) => Path {
+                elem: prefix.elem.iter().chain(&self.elem).cloned().collect(),
+                ..prefix.clone()
+            },
+            None => self.clone(),
+        }
+    }
+
+    /// The schema path of the path, like "/interfaces/interface/state", without the keys and with the names as they
+    /// are, for looking nodes up with [yang_parser::schema_tree::SchemaTree::find].
+    pub fn schema_path(&self) -> String {
+        self.elem.iter().map(|elem| format!("/{}", elem.name)).collect()
+    }
+}
+
+impl PathElem {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    /// The element with the value of a key, for selecting list entries in one expression.
+    pub fn with_key(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.key.insert(name.into(), value.into());
+        self
+    }
+}
+
+/// Paths are written like in gNMI's path conventions, like "/interfaces/interface[name=eth0/1]/state", where the
+/// values of keys may contain slashes, and `]` and `\` in them are escaped with a backslash.
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.elem.is_empty() {
+            return f.write_str("/");
+        }
+        for elem in &self.elem {
+            write!(f, "/{}", elem.name)?;
+            for (name, value) in &elem.key {
+                write!(f, "[{}={}]", name, value.replace('\\', "\\\\").replace(']', "\\]"))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Path {
+    type Err = GnmiError;
+
+    fn from_str(path: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &'static str| GnmiError::InvalidPath {
+            path: path.to_string(),
+            reason,
+        };
+
+        let mut elems = Vec::new();
+        let mut chars = path.chars().peekable();
+        if chars.next_if_eq(&'/').is_none() && !path.is_empty() {
+            return Err(invalid("it doesn't start with /"));
+        }
+        while chars.peek().is_some() {
+            let mut elem = PathElem::default();
+            while let Some(c) = chars.next_if(|c| !matches!(c, '/' | '[')) {
+                elem.name.push(c);
+            }
+            if elem.name.is_empty() {
+                return Err(invalid("an element has no name"));
+            }
+
+            while chars.next_if_eq(&'[').is_some() {
+                let mut name = String::new();
+                while let Some(c) = chars.next_if(|c| *c != '=') {
+                    name.push(c);
+                }
+                if name.is_empty() || chars.next().is_none() {
+                    return Err(invalid("a key has no name or value"));
+                }
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some(']') => break,
+                        Some('\\') => value.push(chars.next().ok_or_else(|| invalid("a key ends with \\"))?),
+                        Some(c) => value.push(c),
+                        None => return Err(invalid("a key isn't closed with ]")),
+                    }
+                }
+                elem.key.insert(name, value);
+            }
+
+            if chars.next_if_eq(&'/').is_none() && chars.peek().is_some() {
+                return Err(invalid("an element continues after its keys"));
+            }
+            elems.push(elem);
+        }
+
+        Ok(Path::from_elems(elems))
+    }
+}
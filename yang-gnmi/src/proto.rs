@@ -0,0 +1,117 @@
+//! The messages of gNMI's `gnmi.proto` (version 0.10) which carry data: notifications, their updates, paths and typed
+//! values, with their field numbers, so they're encoded and decoded like the messages of any gNMI client or server.
+//! The deprecated `element` of paths and `value` of updates, and the `any_val` of typed values, aren't included, and
+//! are skipped when decoding.
+
+use std::collections::BTreeMap;
+
+/// Data of a target at a point in time: values at paths which were updated, and paths which were deleted.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Notification {
+    /// Nanoseconds since the Unix epoch.
+    #[prost(int64, tag = "1")]
+    pub timestamp: i64,
+    /// The path which the paths of the updates and deletes are relative to.
+    #[prost(message, optional, tag = "2")]
+    pub prefix: Option<Path>,
+    #[prost(message, repeated, tag = "4")]
+    pub update: Vec<Update>,
+    #[prost(message, repeated, tag = "5")]
+    pub delete: Vec<Path>,
+    /// Whether the updates and deletes have to be applied together.
+    #[prost(bool, tag = "6")]
+    pub atomic: bool,
+}
+
+/// The value of a path.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Update {
+    #[prost(message, optional, tag = "1")]
+    pub path: Option<Path>,
+    #[prost(message, optional, tag = "3")]
+    pub val: Option<TypedValue>,
+    /// How many updates of the path were coalesced into this one.
+    #[prost(uint32, tag = "4")]
+    pub duplicates: u32,
+}
+
+/// A value, in the encoding the client asked for.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct TypedValue {
+    #[prost(oneof = "typed_value::Value", tags = "1, 2, 3, 4, 5, 6, 7, 8, 10, 11, 12, 13, 14")]
+    pub value: Option<typed_value::Value>,
+}
+
+pub mod typed_value {
+    /// The value of a [super::TypedValue].
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    pub enum Value {
+        #[prost(string, tag = "1")]
+        StringVal(String),
+        #[prost(int64, tag = "2")]
+        IntVal(i64),
+        #[prost(uint64, tag = "3")]
+        UintVal(u64),
+        #[prost(bool, tag = "4")]
+        BoolVal(bool),
+        #[prost(bytes, tag = "5")]
+        BytesVal(Vec<u8>),
+        /// Deprecated in favor of `DoubleVal`.
+        #[prost(float, tag = "6")]
+        FloatVal(f32),
+        /// Deprecated in favor of `DoubleVal`.
+        #[prost(message, tag = "7")]
+        DecimalVal(super::Decimal64),
+        #[prost(message, tag = "8")]
+        LeaflistVal(super::ScalarArray),
+        #[prost(bytes, tag = "10")]
+        JsonVal(Vec<u8>),
+        /// JSON encoded like RFC 7951.
+        #[prost(bytes, tag = "11")]
+        JsonIetfVal(Vec<u8>),
+        #[prost(string, tag = "12")]
+        AsciiVal(String),
+        #[prost(bytes, tag = "13")]
+        ProtoBytes(Vec<u8>),
+        #[prost(double, tag = "14")]
+        DoubleVal(f64),
+    }
+}
+
+/// A path of a data node, like "/interfaces/interface[name=eth0]/state", as its elements.
+#[derive(Clone, PartialEq, Eq, prost::Message)]
+pub struct Path {
+    /// The schema the path is in, like "openconfig", or empty for the default.
+    #[prost(string, tag = "2")]
+    pub origin: String,
+    #[prost(message, repeated, tag = "3")]
+    pub elem: Vec<PathElem>,
+    /// The target the path is on, set in the prefix of requests and notifications.
+    #[prost(string, tag = "4")]
+    pub target: String,
+}
+
+/// An element of a path: the name of a node, and the values of the keys of the list entry it selects.
+#[derive(Clone, PartialEq, Eq, prost::Message)]
+pub struct PathElem {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(btree_map = "string, string", tag = "2")]
+    pub key: BTreeMap<String, String>,
+}
+
+/// A decimal number, as its digits and the number of digits after the decimal point.
+#[derive(Clone, Copy, PartialEq, Eq, prost::Message)]
+pub struct Decimal64 {
+    #[prost(int64, tag = "1")]
+    pub digits: i64,
+    #[prost(uint32, tag = "2")]
+    pub precision: u32,
+}
+
+/// The values of a leaf-list.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ScalarArray {
+    #[prost(message, repeated, tag = "1")]
+    pub element: Vec<TypedValue>,
+}
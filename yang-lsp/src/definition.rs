@@ -0,0 +1,193 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use lsp_types::{Range, Uri};
+use yang_parser::{model::YangModule, Document, SourceStatement};
+
+use crate::{
+    position::LineIndex,
+    server::{path_of, uri_of, Server},
+};
+
+/// The definition a statement refers to, like the grouping of a uses statement.
+pub struct Definition {
+    pub path: PathBuf,
+    /// The range of the name of the definition.
+    pub range: Range,
+    pub statement: SourceStatement,
+    /// The module or submodule the definition is in.
+    pub module: String,
+}
+
+// A document to look definitions up in, which is open in the editor, or read from its file otherwise.
+enum Source<'a> {
+    Open(&'a Document),
+    Read(Box<Document>),
+}
+
+impl std::ops::Deref for Source<'_> {
+    type Target = Document;
+
+    fn deref(&self) -> &Document {
+        match self {
+            Source::Open(document) => document,
+            Source::Read(document) => document,
+        }
+    }
+}
+
+impl Server {
+    /// The definition of the grouping, typedef, identity or feature named at the offset of the document, by a uses,
+    /// type, base or if-feature statement. Unprefixed names are looked up in the statements around the reference, then
+    /// at the top level of the module and of its submodules. Prefixed names are looked up in the imported module.
+    pub fn definition(&self, uri: &Uri, offset: usize) -> Option<Definition> {
+        let document = self.files.get(uri)?.document.as_ref()?;
+        let path = path_of(uri)?;
+        let statements = document.statements_at(offset);
+        let (statement, ancestors) = statements.split_last()?;
+        let keyword = defined_by(statement.keyword)?;
+        let name = identifier_at(document.source(), statement.argument_span.clone()?, offset)?;
+
+        let own_prefix = match document.module() {
+            YangModule::Module(module) => &module.prefix,
+            YangModule::Submodule(submodule) => &submodule.belongs_to.prefix,
+        };
+        let mut visited = HashSet::from([path.clone()]);
+        match name.split_once(':') {
+            Some((prefix, name)) if prefix != &**own_prefix => {
+                let import = document.imports().find(|import| &*import.prefix == prefix)?;
+                let file = yang_parser::find_module_file(&path, &self.search_paths, &import.module)?;
+                self.find_in_file(&file, keyword, name, &mut visited)
+            }
+            prefixed => {
+                let name = prefixed.map_or(name, |(_, name)| name);
+                let local = ancestors
+                    .iter()
+                    .rev()
+                    .find_map(|ancestor| find(&ancestor.children, keyword, name))
+                    .or_else(|| find(document.statements(), keyword, name));
+                match local {
+                    Some(statement) => Some(definition(&path, document, statement)),
+                    None => self.find_in_related(&path, document, keyword, name, &mut visited),
+                }
+            }
+        }
+    }
+
+    // Look the definition up at the top level of the module or submodule in the file, and of the files related to it.
+    fn find_in_file(
+        &self,
+        path: &Path,
+        keyword: &str,
+        name: &str,
+        visited: &mut HashSet<PathBuf>,
+    ) -> Option<Definition> {
+        let document = self.source(path)?;
+        match find(document.statements(), keyword, name) {
+            Some(statement) => Some(definition(path, &document, statement)),
+            None => self.find_in_related(path, &document, keyword, name, visited),
+        }
+    }
+
+    // Look the definition up in the submodules included by the document, and the module it belongs to if it's a
+    // submodule, as their top-level definitions are all visible to each other.
+    fn find_in_related(
+        &self,
+        path: &Path,
+        document: &Document,
+        keyword: &str,
+        name: &str,
+        visited: &mut HashSet<PathBuf>,
+    ) -> Option<Definition> {
+        let belongs_to = match document.module() {
+            YangModule::Module(_) => None,
+            YangModule::Submodule(submodule) => Some(&submodule.belongs_to.module),
+        };
+        let files = document
+            .includes()
+            .map(|include| &include.module)
+            .chain(belongs_to)
+            .filter_map(|module| yang_parser::find_module_file(path, &self.search_paths, module));
+        for file in files {
+            if visited.insert(file.clone()) {
+                if let Some(definition) = self.find_in_file(&file, keyword, name, visited) {
+                    return Some(definition);
+                }
+            }
+        }
+        None
+    }
+
+    // The document of the file, which is the one in the editor if it's open there, as it may not be saved yet.
+    fn source(&self, path: &Path) -> Option<Source<'_>> {
+        let open = uri_of(path)
+            .and_then(|uri| self.files.get(&uri))
+            .and_then(|file| file.document.as_ref());
+        match open {
+            Some(document) => Some(Source::Open(document)),
+            None => {
+                let source = std::fs::read_to_string(path).ok()?;
+                Document::parse(source)
+                    .ok()
+                    .map(|document| Source::Read(Box::new(document)))
+            }
+        }
+    }
+}
+
+// The keyword of the statements defining what the argument of a statement with the keyword refers to.
+fn defined_by(keyword: &str) -> Option<&'static str> {
+    match keyword {
+        "uses" => Some("grouping"),
+        "type" => Some("typedef"),
+        "base" => Some("identity"),
+        "if-feature" => Some("feature"),
+        _ => None,
+    }
+}
+
+fn find<'a>(
+    statements: impl IntoIterator<Item = &'a SourceStatement>,
+    keyword: &str,
+    name: &str,
+) -> Option<&'a SourceStatement> {
+    statements
+        .into_iter()
+        .find(|statement| statement.keyword == keyword && statement.argument.as_deref() == Some(name))
+}
+
+fn definition(path: &Path, document: &Document, statement: &SourceStatement) -> Definition {
+    let span = statement.argument_span.clone().unwrap_or(statement.span.clone());
+    let module = match document.module() {
+        YangModule::Module(module) => module.name.to_string(),
+        YangModule::Submodule(submodule) => submodule.name.to_string(),
+    };
+    Definition {
+        path: path.to_path_buf(),
+        range: LineIndex::new(document.source()).range(span),
+        statement: statement.clone(),
+        module,
+    }
+}
+
+// The identifier or prefixed identifier at the offset of an argument, like "inet:ipv4-address" in the argument of a
+// type, or "b" in the if-feature expression "a and b".
+fn identifier_at(source: &str, argument: std::ops::Range<usize>, offset: usize) -> Option<&str> {
+    if !(argument.start..=argument.end).contains(&offset) {
+        return None;
+    }
+    let is_identifier = |c: char| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | ':');
+    let start = source[argument.start..offset]
+        .rfind(|c| !is_identifier(c))
+        .map_or(argument.start, |index| argument.start + index + 1);
+    let end = source[offset..argument.end]
+        .find(|c| !is_identifier(c))
+        .map_or(argument.end, |index| offset + index);
+    let identifier = &source[start..end];
+    match identifier {
+        "" | "and" | "or" | "not" => None,
+        _ => Some(identifier),
+    }
+}
@@ -0,0 +1,128 @@
+use std::sync::{Arc, Mutex};
+
+use lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Range, Uri};
+use yang_parser::{
+    model::YangModule,
+    report::{self, Report},
+    Document, ErrorPolicy, ParseOptions, Severity, SourceStatement,
+};
+
+use crate::{
+    position::LineIndex,
+    server::{path_of, Server},
+};
+
+impl Server {
+    /// The errors and warnings of the file. A file with a syntax error only gets the syntax error. Otherwise the saved
+    /// module is loaded with its imports and includes, and resolved, and gets the errors and warnings of that, where the
+    /// ones in the modules it imports are put on the import statement.
+    pub fn diagnostics(&self, uri: &Uri) -> Vec<Diagnostic> {
+        let Some(file) = self.files.get(uri) else {
+            return Vec::new();
+        };
+        let lines = LineIndex::new(&file.text);
+        if let Some(error) = &file.error {
+            let mut report = Report::new();
+            report.add_error(error);
+            return report
+                .diagnostics
+                .iter()
+                .map(|diagnostic| lsp_diagnostic(diagnostic, located(diagnostic, &lines)))
+                .collect();
+        }
+        // A submodule is only loaded by the module it belongs to.
+        let (Some(document), Some(path)) = (&file.document, path_of(uri)) else {
+            return Vec::new();
+        };
+        let YangModule::Module(module) = document.module() else {
+            return Vec::new();
+        };
+
+        let warnings = Arc::new(Mutex::new(Vec::new()));
+        let options = ParseOptions {
+            search_paths: self.search_paths.clone(),
+            error_policy: ErrorPolicy::CollectAll,
+            warnings: Some(warnings.clone()),
+            ..Default::default()
+        };
+        let mut report = Report::new();
+        if let Err(error) = yang_parser::parse_with_options(&path, &options) {
+            report.add_error(&error);
+        }
+        for warning in warnings.lock().expect("the warnings to not be poisoned").iter() {
+            report.add_warning(warning);
+        }
+
+        report
+            .diagnostics
+            .iter()
+            .map(|diagnostic| {
+                let range = match (&diagnostic.file, &diagnostic.module) {
+                    (Some(file), _) if *file == path => located(diagnostic, &lines),
+                    (None, Some(name)) if *name == *module.name => diagnostic
+                        .statement
+                        .as_deref()
+                        .and_then(|statement| find_statement(document, statement))
+                        .map(|statement| lines.range(statement.argument_span.clone().unwrap_or(statement.span.clone())))
+                        .unwrap_or_default(),
+                    (_, Some(name)) => {
+                        let range = find_reference(document, name)
+                            .map(|statement| lines.range(statement.span.clone()))
+                            .unwrap_or_default();
+                        let mut diagnostic = lsp_diagnostic(diagnostic, range);
+                        diagnostic.message = format!("{}, in {}", diagnostic.message, name);
+                        return diagnostic;
+                    }
+                    _ => Range::default(),
+                };
+                lsp_diagnostic(diagnostic, range)
+            })
+            .collect()
+    }
+}
+
+// The diagnostic of the editor for the one of the report, which is at the range.
+fn lsp_diagnostic(diagnostic: &report::Diagnostic, range: Range) -> Diagnostic {
+    let severity = match diagnostic.severity {
+        Severity::Error => DiagnosticSeverity::ERROR,
+        Severity::Warning => DiagnosticSeverity::WARNING,
+    };
+    Diagnostic {
+        range,
+        severity: Some(severity),
+        code: Some(NumberOrString::String(diagnostic.code.to_string())),
+        source: Some("yang-rs".to_string()),
+        message: diagnostic.message.clone(),
+        ..Default::default()
+    }
+}
+
+// The position of the diagnostic, which is where its location is in the file, or the start of the file without one.
+fn located(diagnostic: &report::Diagnostic, lines: &LineIndex) -> Range {
+    let position = diagnostic
+        .location
+        .map(|location| lines.line_column(location.line, location.column))
+        .unwrap_or_default();
+    Range::new(position, position)
+}
+
+// The statement a warning is about, given as its keyword and argument, like `uses endpoint`.
+fn find_statement<'a>(document: &'a Document, statement: &str) -> Option<&'a SourceStatement> {
+    let (keyword, argument) = statement.split_once(' ').unwrap_or((statement, ""));
+    let argument = argument.trim_matches(['"', '\'']);
+    let mut pending: Vec<&SourceStatement> = document.statements().collect();
+    while let Some(statement) = pending.pop() {
+        if statement.keyword == keyword && statement.argument.as_deref().unwrap_or("") == argument {
+            return Some(statement);
+        }
+        pending.extend(&statement.children);
+    }
+    None
+}
+
+// The import or include statement of another module or submodule.
+fn find_reference<'a>(document: &'a Document, module: &str) -> Option<&'a SourceStatement> {
+    document.statements().find(|statement| {
+        matches!(statement.keyword, "import" | "include") && statement.argument.as_deref() == Some(module)
+    })
+}
@@ -0,0 +1,72 @@
+use lsp_types::{Hover, HoverContents, MarkupContent, MarkupKind, Uri};
+use yang_parser::SourceStatement;
+
+use crate::{position::LineIndex, server::Server, symbols};
+
+// The substatements shown as properties of the statement hovered, in this order.
+const PROPERTIES: &[&str] = &[
+    "type",
+    "base",
+    "units",
+    "default",
+    "key",
+    "config",
+    "mandatory",
+    "presence",
+    "ordered-by",
+    "status",
+];
+
+impl Server {
+    /// The description of what's at the offset of the document: the definition a uses, type, base or if-feature
+    /// statement refers to, or the schema node, definition or operation whose keyword or name it's on.
+    pub fn hover(&self, uri: &Uri, offset: usize) -> Option<Hover> {
+        let document = self.files.get(uri)?.document.as_ref()?;
+        let lines = LineIndex::new(document.source());
+        let statement = *document.statements_at(offset).last()?;
+        let head = statement.span.start
+            ..statement
+                .argument_span
+                .as_ref()
+                .map_or(statement.span.start + statement.keyword.len(), |span| span.end);
+        if !head.contains(&offset) {
+            return None;
+        }
+
+        let value = match self.definition(uri, offset) {
+            Some(definition) => describe(&definition.statement, Some(&definition.module)),
+            None if symbols::symbol_kind(statement.keyword).is_some() => describe(statement, None),
+            None => return None,
+        };
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value,
+            }),
+            range: Some(lines.range(head)),
+        })
+    }
+}
+
+// The statement with its properties and description as markdown, and the module it's in if it's in another one.
+fn describe(statement: &SourceStatement, module: Option<&str>) -> String {
+    let mut text = match &statement.argument {
+        Some(argument) => format!("```yang\n{} {}\n```", statement.keyword, argument),
+        None => format!("```yang\n{}\n```", statement.keyword),
+    };
+    for keyword in PROPERTIES {
+        if let Some(value) = statement.child_argument(keyword) {
+            text.push_str(&format!("\n- {}: `{}`", keyword, value));
+        }
+    }
+    if let Some(module) = module {
+        text.push_str(&format!("\n\nDefined in `{}`.", module));
+    }
+    if let Some(description) = statement.child_argument("description") {
+        // Descriptions are indented like the statement in the source, which would make them code blocks in markdown.
+        let description: Vec<&str> = description.lines().map(str::trim).collect();
+        text.push_str("\n\n");
+        text.push_str(&description.join("\n"));
+    }
+    text
+}
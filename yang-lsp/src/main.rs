@@ -0,0 +1,41 @@
+mod definition;
+mod diagnostics;
+mod hover;
+mod position;
+mod server;
+mod symbols;
+
+use lsp_server::Connection;
+use lsp_types::{
+    HoverProviderCapability, InitializeParams, OneOf, ServerCapabilities, TextDocumentSyncCapability,
+    TextDocumentSyncKind, TextDocumentSyncOptions, TextDocumentSyncSaveOptions,
+};
+
+use server::Server;
+
+// The server talks LSP over stdin and stdout, and is started by the editor.
+fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let (connection, io_threads) = Connection::stdio();
+
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Options(TextDocumentSyncOptions {
+            open_close: Some(true),
+            change: Some(TextDocumentSyncKind::INCREMENTAL),
+            save: Some(TextDocumentSyncSaveOptions::Supported(true)),
+            ..Default::default()
+        })),
+        document_symbol_provider: Some(OneOf::Left(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        ..Default::default()
+    };
+    let params = connection.initialize(serde_json::to_value(capabilities)?)?;
+    let params: InitializeParams = serde_json::from_value(params)?;
+
+    Server::new(&params).run(&connection)?;
+    // The writer thread stops once the connection is dropped.
+    drop(connection);
+    io_threads.join()?;
+
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use lsp_types::{Position, Range};
+
+/// The starts of the lines of a source, for converting between byte offsets and the positions of LSP, which are lines
+/// and UTF-16 code units within them.
+pub struct LineIndex<'a> {
+    source: &'a str,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(source: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+        Self { source, line_starts }
+    }
+
+    /// The byte offset of the position, which is clamped to the end of its line and the end of the source.
+    pub fn offset(&self, position: Position) -> usize {
+        let Some(&start) = self.line_starts.get(position.line as usize) else {
+            return self.source.len();
+        };
+        let line = self.line(position.line as usize);
+        let mut units = 0;
+        for (index, c) in line.char_indices() {
+            if units >= position.character as usize {
+                return start + index;
+            }
+            units += c.len_utf16();
+        }
+        start + line.len()
+    }
+
+    pub fn position(&self, offset: usize) -> Position {
+        let offset = offset.min(self.source.len());
+        let line = self.line_starts.partition_point(|start| *start <= offset) - 1;
+        let start = self.line_starts[line];
+        let character = self.source[start..offset].chars().map(char::len_utf16).sum::<usize>();
+        Position::new(line as u32, character as u32)
+    }
+
+    pub fn range(&self, range: std::ops::Range<usize>) -> Range {
+        Range::new(self.position(range.start), self.position(range.end))
+    }
+
+    /// The position of a 1-based line and character column, like the locations of parser errors.
+    pub fn line_column(&self, line: usize, column: usize) -> Position {
+        let Some(&start) = self.line_starts.get(line.saturating_sub(1)) else {
+            return self.position(self.source.len());
+        };
+        let line = self.line(line.saturating_sub(1));
+        let offset = line
+            .char_indices()
+            .nth(column.saturating_sub(1))
+            .map_or(line.len(), |(index, _)| index);
+        self.position(start + offset)
+    }
+
+    // The line without its line break.
+    fn line(&self, line: usize) -> &'a str {
+        let start = self.line_starts[line];
+        let end = self
+            .line_starts
+            .get(line + 1)
+            .map_or(self.source.len(), |next| next - 1);
+        self.source[start..end].trim_end_matches('\r')
+    }
+}
@@ -0,0 +1,261 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, RequestId, Response};
+use lsp_types::{
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, DidSaveTextDocument,
+        Notification as NotificationTrait, PublishDiagnostics,
+    },
+    request::{DocumentSymbolRequest, GotoDefinition, HoverRequest, Request as RequestTrait},
+    DocumentSymbolResponse, GotoDefinitionResponse, InitializeParams, Location, PublishDiagnosticsParams, Uri,
+};
+use yang_parser::{Document, ParserError};
+
+use crate::{position::LineIndex, symbols};
+
+/// The state of the server: the files open in the editor, and where the modules they import are searched for.
+pub struct Server {
+    pub files: HashMap<Uri, OpenFile>,
+    pub search_paths: Vec<PathBuf>,
+}
+
+/// A file open in the editor, with its text as edited, which may not be saved yet.
+pub struct OpenFile {
+    pub text: String,
+    /// The document of the text, or None if it has never parsed. It keeps the statements of the last text which parsed.
+    pub document: Option<Document>,
+    /// The syntax error of the text, if it doesn't parse.
+    pub error: Option<ParserError>,
+}
+
+impl OpenFile {
+    fn new(text: String) -> Self {
+        let (document, error) = match Document::parse(text.clone()) {
+            Ok(document) => (Some(document), None),
+            Err(error) => (None, Some(error)),
+        };
+        Self { text, document, error }
+    }
+
+    // Replace the byte range of the text, re-parsing the statements of the document which the edit touches.
+    fn edit(&mut self, range: std::ops::Range<usize>, text: &str) {
+        self.text.replace_range(range.clone(), text);
+        let result = match &mut self.document {
+            Some(document) => document.edit(range, text),
+            None => Document::parse(self.text.clone()).map(|document| {
+                self.document = Some(document);
+            }),
+        };
+        self.error = result.err();
+    }
+}
+
+impl Server {
+    /// A server for the workspace of the editor, where the search paths are given in the initialization options, like
+    /// `{"searchPaths": ["models/ietf"]}`, relative to the root of the workspace.
+    pub fn new(params: &InitializeParams) -> Self {
+        #[allow(deprecated)]
+        let root = params
+            .workspace_folders
+            .iter()
+            .flatten()
+            .map(|folder| &folder.uri)
+            .chain(&params.root_uri)
+            .find_map(path_of)
+            .unwrap_or_default();
+        let search_paths = params
+            .initialization_options
+            .as_ref()
+            .and_then(|options| options.get("searchPaths")?.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|path| path.as_str())
+            .map(|path| root.join(path))
+            .collect();
+
+        Self {
+            files: HashMap::new(),
+            search_paths,
+        }
+    }
+
+    /// Handle the messages of the editor until it shuts the server down.
+    pub fn run(&mut self, connection: &Connection) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        for message in &connection.receiver {
+            match message {
+                Message::Request(request) => {
+                    if connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+                    connection.sender.send(Message::Response(self.respond(request)))?;
+                }
+                Message::Notification(notification) => {
+                    if let Some(diagnostics) = self.notify(notification) {
+                        let notification = Notification::new(PublishDiagnostics::METHOD.to_string(), diagnostics);
+                        connection.sender.send(Message::Notification(notification))?;
+                    }
+                }
+                Message::Response(_) => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn respond(&self, request: Request) -> Response {
+        let id = request.id.clone();
+        let result = match request.method.as_str() {
+            DocumentSymbolRequest::METHOD => {
+                params::<DocumentSymbolRequest>(request).map(|params| self.document_symbols(&params.text_document.uri))
+            }
+            GotoDefinition::METHOD => params::<GotoDefinition>(request).map(|params| {
+                let position = params.text_document_position_params;
+                let definition = self
+                    .offset(&position.text_document.uri, position.position)
+                    .and_then(|offset| self.definition(&position.text_document.uri, offset))
+                    .and_then(|definition| uri_of(&definition.path).map(|uri| Location::new(uri, definition.range)));
+                serde_json::to_value(definition.map(GotoDefinitionResponse::Scalar))
+            }),
+            HoverRequest::METHOD => params::<HoverRequest>(request).map(|params| {
+                let position = params.text_document_position_params;
+                let hover = self
+                    .offset(&position.text_document.uri, position.position)
+                    .and_then(|offset| self.hover(&position.text_document.uri, offset));
+                serde_json::to_value(hover)
+            }),
+            _ => {
+                return error(
+                    id,
+                    ErrorCode::MethodNotFound,
+                    format!("unknown method: {}", request.method),
+                )
+            }
+        };
+
+        match result {
+            Ok(Ok(result)) => Response::new_ok(id, result),
+            Ok(Err(json_error)) => error(id, ErrorCode::InternalError, json_error.to_string()),
+            Err(message) => error(id, ErrorCode::InvalidParams, message),
+        }
+    }
+
+    // Keep the open files up to date, and return the diagnostics of a file which was opened or saved.
+    fn notify(&mut self, notification: Notification) -> Option<PublishDiagnosticsParams> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params = notification_params::<DidOpenTextDocument>(notification)?;
+                let uri = params.text_document.uri;
+                self.files.insert(uri.clone(), OpenFile::new(params.text_document.text));
+                Some(self.publish(uri))
+            }
+            DidChangeTextDocument::METHOD => {
+                let params = notification_params::<DidChangeTextDocument>(notification)?;
+                let file = self.files.get_mut(&params.text_document.uri)?;
+                for change in params.content_changes {
+                    let range = match change.range {
+                        Some(range) => {
+                            let lines = LineIndex::new(&file.text);
+                            lines.offset(range.start)..lines.offset(range.end)
+                        }
+                        None => 0..file.text.len(),
+                    };
+                    file.edit(range, &change.text);
+                }
+                None
+            }
+            DidSaveTextDocument::METHOD => {
+                let params = notification_params::<DidSaveTextDocument>(notification)?;
+                Some(self.publish(params.text_document.uri))
+            }
+            DidCloseTextDocument::METHOD => {
+                let params = notification_params::<DidCloseTextDocument>(notification)?;
+                self.files.remove(&params.text_document.uri);
+                // The diagnostics of a closed file are cleared.
+                Some(PublishDiagnosticsParams::new(
+                    params.text_document.uri,
+                    Vec::new(),
+                    None,
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    fn publish(&self, uri: Uri) -> PublishDiagnosticsParams {
+        let diagnostics = self.diagnostics(&uri);
+        PublishDiagnosticsParams::new(uri, diagnostics, None)
+    }
+
+    fn document_symbols(&self, uri: &Uri) -> serde_json::Result<serde_json::Value> {
+        let symbols = self.files.get(uri).and_then(|file| {
+            let document = file.document.as_ref()?;
+            let statements: Vec<_> = document.statements().collect();
+            let lines = LineIndex::new(document.source());
+            Some(DocumentSymbolResponse::Nested(symbols::document_symbols(
+                &statements,
+                &lines,
+            )))
+        });
+        serde_json::to_value(symbols)
+    }
+
+    // The byte offset of the position in the document of the file.
+    fn offset(&self, uri: &Uri, position: lsp_types::Position) -> Option<usize> {
+        let document = self.files.get(uri)?.document.as_ref()?;
+        Some(LineIndex::new(document.source()).offset(position))
+    }
+}
+
+fn params<R: RequestTrait>(request: Request) -> Result<R::Params, String> {
+    serde_json::from_value(request.params).map_err(|error| error.to_string())
+}
+
+fn notification_params<N: NotificationTrait>(notification: Notification) -> Option<N::Params> {
+    serde_json::from_value(notification.params).ok()
+}
+
+fn error(id: RequestId, code: ErrorCode, message: String) -> Response {
+    Response::new_err(id, code as i32, message)
+}
+
+/// The path of a `file:` URI, or None if it's another kind of URI, like an unsaved file.
+pub fn path_of(uri: &Uri) -> Option<PathBuf> {
+    let path = uri.as_str().strip_prefix("file://")?;
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = (bytes[index] == b'%')
+            .then(|| path.get(index + 1..index + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+
+    Some(PathBuf::from(String::from_utf8(decoded).ok()?))
+}
+
+/// The `file:` URI of the path, with the characters which aren't allowed in URIs escaped.
+pub fn uri_of(path: &Path) -> Option<Uri> {
+    let path = std::path::absolute(path).ok()?;
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b'@' => uri.push(byte as char),
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri.parse().ok()
+}
@@ -0,0 +1,62 @@
+use lsp_types::{DocumentSymbol, SymbolKind, SymbolTag};
+use yang_parser::SourceStatement;
+
+use crate::position::LineIndex;
+
+/// The outline of the statements: the schema nodes, operations, definitions, augments and deviations, nested like in
+/// the module. Other statements, like descriptions and types, are details of the symbols rather than symbols.
+pub fn document_symbols(statements: &[&SourceStatement], lines: &LineIndex) -> Vec<DocumentSymbol> {
+    statements
+        .iter()
+        .filter_map(|statement| symbol(statement, lines))
+        .collect()
+}
+
+#[allow(deprecated)]
+fn symbol(statement: &SourceStatement, lines: &LineIndex) -> Option<DocumentSymbol> {
+    let kind = symbol_kind(statement.keyword)?;
+    let name = match (&statement.argument, statement.keyword) {
+        (Some(argument), _) => argument.clone(),
+        (None, keyword) => keyword.to_string(),
+    };
+    let detail = match statement.keyword {
+        "leaf" | "leaf-list" | "typedef" => statement.child_argument("type").map(str::to_string),
+        "list" => statement.child_argument("key").map(|key| format!("[{}]", key)),
+        _ => Some(statement.keyword.to_string()),
+    };
+    let deprecated = matches!(statement.child_argument("status"), Some("deprecated" | "obsolete"));
+    let children: Vec<&SourceStatement> = statement.children.iter().collect();
+
+    Some(DocumentSymbol {
+        name,
+        detail,
+        kind,
+        tags: deprecated.then(|| vec![SymbolTag::DEPRECATED]),
+        deprecated: None,
+        range: lines.range(statement.span.clone()),
+        selection_range: lines.range(statement.argument_span.clone().unwrap_or(statement.span.clone())),
+        children: Some(document_symbols(&children, lines)),
+    })
+}
+
+pub fn symbol_kind(keyword: &str) -> Option<SymbolKind> {
+    let kind = match keyword {
+        "container" => SymbolKind::STRUCT,
+        "list" | "leaf-list" => SymbolKind::ARRAY,
+        "leaf" => SymbolKind::FIELD,
+        "choice" => SymbolKind::ENUM,
+        "case" => SymbolKind::ENUM_MEMBER,
+        "anydata" | "anyxml" | "input" | "output" => SymbolKind::OBJECT,
+        "grouping" => SymbolKind::CLASS,
+        "typedef" => SymbolKind::TYPE_PARAMETER,
+        "identity" => SymbolKind::CONSTANT,
+        "feature" => SymbolKind::BOOLEAN,
+        "extension" => SymbolKind::KEY,
+        "rpc" | "action" => SymbolKind::FUNCTION,
+        "notification" => SymbolKind::EVENT,
+        "augment" | "deviation" => SymbolKind::NAMESPACE,
+        "uses" => SymbolKind::MODULE,
+        _ => return None,
+    };
+    Some(kind)
+}
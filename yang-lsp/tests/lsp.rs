@@ -0,0 +1,119 @@
+use std::{
+    io::{Read, Write},
+    path::Path,
+    process::{Command, Stdio},
+    time::{Duration, Instant},
+};
+
+use serde_json::{json, Value};
+
+const MODULES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/yang");
+
+// Frame the message like LSP does, with a Content-Length header.
+fn frame(message: Value) -> String {
+    let body = message.to_string();
+    format!("Content-Length: {}\r\n\r\n{}", body.len(), body)
+}
+
+// The messages of the output, which must be nothing but framed messages, as editors can't read anything else.
+fn messages(mut output: &str) -> Vec<Value> {
+    let mut messages = Vec::new();
+    while !output.is_empty() {
+        let rest = output
+            .strip_prefix("Content-Length: ")
+            .unwrap_or_else(|| panic!("expected an LSP message, found {:?}", output));
+        let (length, rest) = rest.split_once("\r\n\r\n").expect("the header to end");
+        let length: usize = length.parse().expect("the content length to be a number");
+        messages.push(serde_json::from_str(&rest[..length]).expect("the message to be JSON"));
+        output = &rest[length..];
+    }
+    messages
+}
+
+// Open the module in the server, ask for its symbols and shut the server down, returning the messages it sent. A server
+// which doesn't exit is killed, as anything else writing to stdout blocks on the lock the server's writer holds on it.
+fn session(file: &str) -> Vec<Value> {
+    let root = format!("file://{}", MODULES);
+    let uri = format!("{}/{}", root, file);
+    let text = std::fs::read_to_string(Path::new(MODULES).join(file)).expect("the module to be readable");
+    let input = [
+        json!({
+            "jsonrpc": "2.0", "id": 1, "method": "initialize",
+            "params": {"capabilities": {}, "rootUri": root, "initializationOptions": {"searchPaths": ["."]}},
+        }),
+        json!({"jsonrpc": "2.0", "method": "initialized", "params": {}}),
+        json!({
+            "jsonrpc": "2.0", "method": "textDocument/didOpen",
+            "params": {"textDocument": {"uri": uri, "languageId": "yang", "version": 1, "text": text}},
+        }),
+        json!({
+            "jsonrpc": "2.0", "id": 2, "method": "textDocument/documentSymbol",
+            "params": {"textDocument": {"uri": uri}},
+        }),
+        json!({"jsonrpc": "2.0", "id": 3, "method": "shutdown"}),
+        json!({"jsonrpc": "2.0", "method": "exit"}),
+    ];
+
+    let mut server = Command::new(env!("CARGO_BIN_EXE_yang-lsp"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("the server to start");
+    let mut stdin = server.stdin.take().expect("the server to have stdin");
+    for message in input {
+        stdin.write_all(frame(message).as_bytes()).expect("the server to read");
+    }
+    drop(stdin);
+
+    let mut stdout = server.stdout.take().expect("the server to have stdout");
+    let reader = std::thread::spawn(move || {
+        let mut output = String::new();
+        stdout.read_to_string(&mut output).map(|_| output)
+    });
+    let deadline = Instant::now() + Duration::from_secs(30);
+    let status = loop {
+        if let Some(status) = server.try_wait().expect("the server to be waited for") {
+            break status;
+        }
+        if Instant::now() > deadline {
+            server.kill().expect("the server to be killed");
+            panic!("the server didn't exit");
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    };
+    assert!(status.success());
+
+    let output = reader.join().expect("the output to be read");
+    messages(&output.expect("the output to be UTF-8"))
+}
+
+#[test]
+fn opened_modules_get_their_diagnostics_and_symbols() {
+    let messages = session("example-system.yang");
+    let response = |id: i64| {
+        messages
+            .iter()
+            .find(|message| message["id"] == id)
+            .unwrap_or_else(|| panic!("a response to request {}", id))
+    };
+
+    assert!(response(1)["result"]["capabilities"]["documentSymbolProvider"] == true);
+
+    let published = messages
+        .iter()
+        .find(|message| message["method"] == "textDocument/publishDiagnostics")
+        .expect("the diagnostics of the module to be published");
+    let diagnostics = published["params"]["diagnostics"]
+        .as_array()
+        .expect("the diagnostics to be an array");
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0]["code"], "yang::unresolved_grouping");
+    assert_eq!(diagnostics[0]["severity"], 2);
+
+    let symbols = response(2)["result"].as_array().expect("the symbols to be an array");
+    let names: Vec<&Value> = symbols.iter().map(|symbol| &symbol["name"]).collect();
+    assert_eq!(names, ["name", "system"]);
+
+    assert!(response(3)["error"].is_null());
+}
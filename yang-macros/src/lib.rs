@@ -0,0 +1,85 @@
+use std::{path::PathBuf, sync::Arc};
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input,
+    punctuated::Punctuated,
+    Ident, LitStr, Token,
+};
+
+// The path of the module, followed by the names of the options of the code which are enabled, like
+// `"yang/module.yang", xml, list_maps`.
+struct Input {
+    path: LitStr,
+    options: Punctuated<Ident, Token![,]>,
+}
+
+impl Parse for Input {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let path = input.parse()?;
+        let options = match input.parse::<Option<Token![,]>>()? {
+            Some(_) => Punctuated::parse_terminated(input)?,
+            None => Punctuated::new(),
+        };
+        Ok(Self { path, options })
+    }
+}
+
+/// Generate the types for a YANG module at compile time, expanding to the code `yang_codegen::generate` returns.
+/// The path is relative to the directory of the crate's Cargo.toml, like `yang_include!("yang/module.yang")`.
+/// The generated code has the same dependencies as the code from `yang_codegen`.
+///
+/// The path may be followed by the options of `yang_codegen::Config` to enable, like
+/// `yang_include!("yang/module.yang", xml, list_maps)`. The options are `xml`, `config_state`, `if_features`,
+/// `skip_obsolete`, `rust_decimal`, `list_maps`, `share_groupings`, `no_std`, `non_exhaustive`, `checked_types` and
+/// `proptest`.
+#[proc_macro]
+pub fn yang_include(input: TokenStream) -> TokenStream {
+    let Input { path, options } = parse_macro_input!(input as Input);
+    let mut config = yang_codegen::Config::default();
+    for option in &options {
+        let enabled = match option.to_string().as_str() {
+            "xml" => &mut config.xml,
+            "config_state" => &mut config.config_state,
+            "if_features" => &mut config.if_features,
+            "skip_obsolete" => &mut config.skip_obsolete,
+            "rust_decimal" => &mut config.rust_decimal,
+            "list_maps" => &mut config.list_maps,
+            "share_groupings" => &mut config.share_groupings,
+            "no_std" => &mut config.no_std,
+            "non_exhaustive" => &mut config.non_exhaustive,
+            "checked_types" => &mut config.checked_types,
+            "proptest" => &mut config.proptest,
+            _ => {
+                return syn::Error::new(option.span(), "unknown option")
+                    .to_compile_error()
+                    .into()
+            }
+        };
+        *enabled = true;
+    }
+
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let full_path = PathBuf::from(manifest_dir).join(path.value());
+
+    let module = match yang_parser::parse(&full_path) {
+        Ok(module) => module,
+        Err(e) => {
+            let message = format!("failed to parse {}: {}", full_path.display(), e);
+            return syn::Error::new(path.span(), message).to_compile_error().into();
+        }
+    };
+    // The warnings of generating the code are printed by the compiler like the output of build scripts.
+    config.warnings = Some(Arc::new(|warning| eprintln!("warning: {}", warning)));
+    let generated = yang_codegen::generate_tokens(module, &config);
+
+    // Including the file makes Cargo rebuild the crate when the module changes.
+    let full_path = full_path.to_string_lossy().into_owned();
+    quote! {
+        const _: &[u8] = include_bytes!(#full_path);
+        #generated
+    }
+    .into()
+}
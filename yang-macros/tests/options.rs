@@ -0,0 +1,159 @@
+// The code of every option is compiled and round-tripped on its own, in a module of its own, so an option which makes
+// code that doesn't compile or doesn't read back what it writes fails the tests of the option.
+extern crate alloc;
+
+use serde_json::{json, Value};
+
+fn data() -> Value {
+    json!({
+        "options:system": {
+            "hostname": "router",
+            "mtu": 9000,
+            "load": "0.75",
+            "server": {"endpoint": {"address": "192.0.2.1", "port": 830}},
+            "client": {"endpoint": {"address": "192.0.2.2"}},
+            "interface": [{"name": "eth1", "enabled": true}, {"name": "eth0", "enabled": false}],
+            "route": [{"prefix": "198.51.100.0/24", "next-hop": "192.0.2.254"}, {"prefix": "0.0.0.0/0"}],
+            "statistics": {"uptime": "3600"}
+        }
+    })
+}
+
+fn round_trip<T: serde::Serialize + serde::de::DeserializeOwned>(data: Value) -> (T, Value) {
+    let decoded: T = serde_json::from_value(data).expect("the data to deserialize");
+    let encoded = serde_json::to_value(&decoded).expect("the data to serialize");
+    (decoded, encoded)
+}
+
+mod xml {
+    yang_macros::yang_include!("tests/options.yang", xml);
+
+    #[test]
+    fn data_round_trips_through_json_and_xml() {
+        let (decoded, encoded) = super::round_trip::<Options>(super::data());
+        assert_eq!(encoded, super::data());
+
+        let xml = decoded.to_xml().expect("the data to encode as XML");
+        assert!(xml.starts_with(r#"<system xmlns="urn:example:options"><hostname>router</hostname>"#));
+        let read = Options::from_xml(&xml).expect("the XML to decode");
+        assert_eq!(serde_json::to_value(&read).expect("the data to serialize"), encoded);
+    }
+}
+
+mod list_maps {
+    yang_macros::yang_include!("tests/options.yang", list_maps);
+
+    #[test]
+    fn lists_are_maps_of_their_keys() {
+        let (decoded, encoded) = super::round_trip::<Options>(super::data());
+
+        // Lists ordered by the system are sorted by their keys, lists ordered by the user keep their order.
+        let interfaces: Vec<&String> = decoded.system.interface.keys().collect();
+        assert_eq!(interfaces, ["eth0", "eth1"]);
+        let routes: Vec<&String> = decoded.system.route.keys().collect();
+        assert_eq!(routes, ["198.51.100.0/24", "0.0.0.0/0"]);
+
+        let mut expected = super::data();
+        expected["options:system"]["interface"]
+            .as_array_mut()
+            .expect("the interfaces to be an array")
+            .reverse();
+        assert_eq!(encoded, expected);
+    }
+
+    #[test]
+    fn duplicate_keys_are_rejected() {
+        let data = serde_json::json!({"options:system": {"interface": [{"name": "eth0"}, {"name": "eth0"}]}});
+        assert!(serde_json::from_value::<Options>(data).is_err());
+    }
+}
+
+mod checked_types {
+    yang_macros::yang_include!("tests/options.yang", checked_types);
+
+    #[test]
+    fn values_outside_of_the_restrictions_are_rejected() {
+        let (decoded, encoded) = super::round_trip::<Options>(super::data());
+        assert_eq!(*decoded.system.mtu.get(), 9000);
+        assert_eq!(encoded, super::data());
+
+        assert!(system::Mtu::new(67).is_err());
+        assert!(system::Hostname::new(String::new()).is_err());
+        let data = serde_json::json!({"options:system": {"mtu": 9001}});
+        assert!(serde_json::from_value::<Options>(data).is_err());
+    }
+}
+
+mod proptest {
+    use ::proptest::prelude::*;
+
+    yang_macros::yang_include!("tests/options.yang", proptest);
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn arbitrary_data_round_trips(options in any::<Options>()) {
+            let encoded = serde_json::to_value(&options).expect("the data to serialize");
+            prop_assert!(options.validate().is_ok());
+            let (_, round_tripped) = super::round_trip::<Options>(encoded.clone());
+            prop_assert_eq!(round_tripped, encoded);
+        }
+    }
+}
+
+mod no_std {
+    yang_macros::yang_include!("tests/options.yang", no_std);
+
+    #[test]
+    fn data_round_trips() {
+        let (_, encoded) = super::round_trip::<Options>(super::data());
+        assert_eq!(encoded, super::data());
+    }
+}
+
+mod share_groupings {
+    yang_macros::yang_include!("tests/options.yang", share_groupings);
+
+    #[test]
+    fn the_nodes_of_a_grouping_have_one_type() {
+        let (decoded, encoded) = super::round_trip::<Options>(super::data());
+        let endpoints: [&groupings::endpoint::Endpoint; 2] =
+            [&decoded.system.server.endpoint, &decoded.system.client.endpoint];
+        assert_eq!(endpoints[0].port, Some(830));
+        assert_eq!(endpoints[1].port, None);
+        assert_eq!(encoded, super::data());
+    }
+}
+
+mod config_state {
+    yang_macros::yang_include!("tests/options.yang", config_state);
+
+    #[test]
+    fn config_data_leaves_out_state_data() {
+        let (_, encoded) = super::round_trip::<Options>(super::data());
+        assert_eq!(encoded, super::data());
+
+        let (_, config) = super::round_trip::<options_config::OptionsConfig>(super::data());
+        let mut expected = super::data();
+        expected["options:system"]
+            .as_object_mut()
+            .expect("the system to be an object")
+            .remove("statistics");
+        assert_eq!(config, expected);
+
+        let (_, state) = super::round_trip::<options_state::OptionsState>(super::data());
+        assert_eq!(state["options:system"]["statistics"]["uptime"], "3600");
+    }
+}
+
+mod rust_decimal {
+    yang_macros::yang_include!("tests/options.yang", rust_decimal);
+
+    #[test]
+    fn decimals_are_exact() {
+        let (decoded, encoded) = super::round_trip::<Options>(super::data());
+        assert_eq!(decoded.system.load, Some(::rust_decimal::Decimal::new(75, 2)));
+        assert_eq!(encoded, super::data());
+    }
+}
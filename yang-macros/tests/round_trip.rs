@@ -0,0 +1,68 @@
+use serde_json::json;
+
+yang_macros::yang_include!("tests/round-trip.yang");
+
+fn round_trip(data: serde_json::Value) -> (RoundTrip, serde_json::Value) {
+    let decoded: RoundTrip = serde_json::from_value(data).expect("the data to deserialize");
+    let encoded = serde_json::to_value(&decoded).expect("the data to serialize");
+    (decoded, encoded)
+}
+
+#[test]
+fn choices_select_the_case_with_members_in_the_data() {
+    let data = json!({
+        "round-trip:system": {
+            "hostname": "router",
+            "mtu": 9000,
+            "tls-port": 6514,
+            "certificate": "router.pem",
+            "ipv6-address": "2001:db8::1",
+            "interface": [{"name": "eth0", "enabled": true}]
+        }
+    });
+    let (decoded, encoded) = round_trip(data.clone());
+
+    match &decoded.system.transport {
+        Some(system::Transport::Tls(tls)) => {
+            assert_eq!(tls.tls_port, Some(6514));
+            assert_eq!(tls.certificate.as_deref(), Some("router.pem"));
+        }
+        transport => panic!("expected the tls case, found {:?}", transport),
+    }
+    assert!(matches!(
+        decoded.system.address_family,
+        Some(system::AddressFamily::Ipv6(_))
+    ));
+    assert_eq!(encoded, data);
+}
+
+#[test]
+fn choices_within_cases_select_their_case_as_well() {
+    let data = json!({"round-trip:system": {"mtu": 1500, "public-key": "ssh-ed25519 AAAA", "ipv4-address": "0.0.0.0"}});
+    let (decoded, encoded) = round_trip(data.clone());
+
+    let Some(system::Transport::Ssh(ssh)) = &decoded.system.transport else {
+        panic!("expected the ssh case, found {:?}", decoded.system.transport);
+    };
+    assert!(matches!(
+        ssh.authentication,
+        Some(system::transport::ssh::Authentication::PublicKey(_))
+    ));
+    assert_eq!(encoded, data);
+}
+
+#[test]
+fn choices_without_members_are_their_default_case_or_missing() {
+    let (decoded, encoded) = round_trip(json!({"round-trip:system": {}}));
+
+    assert!(decoded.system.transport.is_none());
+    match &decoded.system.address_family {
+        Some(system::AddressFamily::Ipv4(ipv4)) => assert_eq!(ipv4.ipv4_address, "0.0.0.0"),
+        address_family => panic!("expected the ipv4 case, found {:?}", address_family),
+    }
+    assert_eq!(decoded.system.mtu, 1500);
+    assert_eq!(
+        encoded,
+        json!({"round-trip:system": {"mtu": 1500, "ipv4-address": "0.0.0.0"}})
+    );
+}
@@ -0,0 +1,101 @@
+use std::{path::PathBuf, sync::Arc};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use yang_parser::{parse_with_options, scan, ModuleRegistry, ParseOptions};
+
+// The number of modules the common modules are imported by, and of the common modules.
+const MODULES: usize = 64;
+const COMMON: usize = 4;
+
+// A module with the containers, lists and leaves vendor modules are made of, importing the common modules and using
+// their types and groupings.
+fn module(name: &str, imports: usize) -> String {
+    let mut source =
+        format!("module {name} {{\n  yang-version 1.1;\n  namespace \"urn:example:{name}\";\n  prefix {name};\n");
+    for i in 0..imports {
+        source.push_str(&format!("  import common-{i} {{ prefix c{i}; }}\n"));
+    }
+    for i in 0..100 {
+        source.push_str(&format!(
+            r#"
+  typedef name-{i} {{
+    type string {{
+      length "1..64";
+      pattern '[a-zA-Z_][a-zA-Z0-9_\-.]*';
+    }}
+    description "The name of an entry of section {i}.";
+  }}
+
+  container section-{i} {{
+    description "Configuration and state of the entries of section {i}.";
+    list entry {{
+      key "name";
+      leaf name {{
+        type name-{i};
+      }}
+      leaf mtu {{
+        type uint16 {{
+          range "68..9216";
+        }}
+      }}
+"#
+        ));
+        for c in 0..imports {
+            source.push_str(&format!(
+                "      uses c{c}:counters;\n      leaf kind-{c} {{ type c{c}:kind; }}\n"
+            ));
+        }
+        source.push_str("    }\n  }\n");
+    }
+    source.push_str("}\n");
+    source
+}
+
+// Modules importing the common modules, and the common modules, which are in a directory of their own, like the
+// standard modules of a search path.
+fn registry() -> (ModuleRegistry, Vec<PathBuf>) {
+    let mut registry = ModuleRegistry::new();
+    for i in 0..COMMON {
+        let common = format!(
+            "module common-{i} {{\n  namespace \"urn:example:common-{i}\";\n  prefix c;\n\n  \
+             typedef kind {{ type enumeration {{ enum a; enum b; }} }}\n\n  \
+             grouping counters {{\n    leaf in-octets {{ type uint64; }}\n    leaf out-octets {{ type uint64; }}\n  }}\n}}\n"
+        );
+        registry.add(format!("common/common-{i}.yang"), common);
+    }
+    let paths: Vec<PathBuf> = (0..MODULES)
+        .map(|i| PathBuf::from(format!("models/module-{i}.yang")))
+        .collect();
+    for (i, path) in paths.iter().enumerate() {
+        registry.add(path.clone(), module(&format!("module-{i}"), COMMON));
+    }
+    (registry, paths)
+}
+
+fn load(c: &mut Criterion) {
+    let (registry, paths) = registry();
+    let options = ParseOptions {
+        search_paths: vec![PathBuf::from("common")],
+        registry: Some(Arc::new(registry)),
+        ..Default::default()
+    };
+
+    let mut group = c.benchmark_group("load");
+    group.sample_size(10);
+    group.bench_function(BenchmarkId::new("scan", MODULES), |b| {
+        b.iter(|| scan(&paths, &options, |_, result| drop(result.unwrap())).unwrap())
+    });
+
+    // The first module with the others applied to it.
+    let with_others = ParseOptions {
+        modules: paths[1..].to_vec(),
+        ..options.clone()
+    };
+    group.bench_function(BenchmarkId::new("parse_with_options", MODULES), |b| {
+        b.iter(|| parse_with_options(&paths[0], &with_others).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, load);
+criterion_main!(benches);
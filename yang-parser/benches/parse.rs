@@ -0,0 +1,107 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use yang_parser::Document;
+
+// A module with the statements large vendor and IETF modules are made of: typedefs, groupings, and containers and
+// lists of leaves with long descriptions, patterns and comments, repeated until the module has the number of lines.
+fn module(lines: usize) -> String {
+    let mut source = String::from(
+        "module bench {\n  yang-version 1.1;\n  namespace \"urn:example:bench\";\n  prefix b;\n\n  \
+         organization \"Example\";\n  description\n    \"A module generated for benchmarking the parser.\";\n",
+    );
+
+    let mut i = 0;
+    let mut count = source.lines().count();
+    while count < lines {
+        let start = source.len();
+        source.push_str(&format!(
+            r#"
+  // Types and groupings of section {i}.
+  typedef name-{i} {{
+    type string {{
+      length "1..64";
+      pattern '[a-zA-Z_][a-zA-Z0-9_\-.]*';
+    }}
+    description
+      "The name of an entry in section {i}, which is " +
+      "unique within its list.";
+  }}
+
+  grouping counters-{i} {{
+    leaf in-octets {{
+      type uint64;
+      units "octets";
+      description "The number of octets received.";
+    }}
+    leaf out-octets {{
+      type uint64;
+      units "octets";
+      description "The number of octets sent.";
+    }}
+  }}
+
+  /*
+   * Configuration and state of section {i}.
+   */
+  container section-{i} {{
+    description
+      "Configuration and state of the entries of section {i}. The entries are keyed by their name, and are
+       enabled unless they are disabled explicitly.";
+    list entry {{
+      key "name";
+      leaf name {{
+        type name-{i};
+        description "The name of the entry.";
+      }}
+      leaf enabled {{
+        type boolean;
+        default "true";
+        description "Whether the entry is enabled.";
+      }}
+      leaf mtu {{
+        type uint16 {{
+          range "68..9216";
+        }}
+        description "The maximum transmission unit of the entry.";
+      }}
+      leaf mode {{
+        type enumeration {{
+          enum access;
+          enum trunk {{
+            description "Carries the traffic of several VLANs.";
+          }}
+        }}
+      }}
+      container state {{
+        config false;
+        uses counters-{i};
+        leaf-list addresses {{
+          type string;
+          description "The addresses of the entry."; // Without a prefix length.
+        }}
+      }}
+    }}
+  }}
+"#
+        ));
+        count += source[start..].lines().count();
+        i += 1;
+    }
+    source.push_str("}\n");
+
+    source
+}
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for lines in [1_000, 10_000, 50_000] {
+        let source = module(lines);
+        group.throughput(Throughput::Bytes(source.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(lines), &source, |b, source| {
+            b.iter(|| Document::parse(source.as_str()).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
@@ -0,0 +1,210 @@
+//! Parsing a module into statements which borrow their arguments from the source, for tools which read many modules
+//! and only look at some of their statements, like indexers and linters.
+//!
+//! [BorrowedModule::parse] checks the module against the grammar and keeps the source, and its statements are the
+//! keywords and arguments of the statements in the source, with their byte ranges. An argument is only allocated if
+//! processing it changed it, like a double-quoted string with escapes or a string concatenated with `+`. The model of
+//! [crate::model] is made from the kept source on demand by [BorrowedModule::to_owned_module], and the statements
+//! outlive the source after [Statement::into_owned].
+
+use std::{borrow::Cow, ops::Range};
+
+use pest::{iterators::Pair, Parser};
+
+use crate::{
+    error::ParserError,
+    model::YangModule,
+    parser::{self, YangParser},
+    parser_internal::{Rule, YangFile},
+    syntax,
+};
+
+/// A module or submodule parsed into the statements of its source, which borrow from the source.
+#[derive(Debug, Clone)]
+pub struct BorrowedModule<'src> {
+    source: &'src str,
+    statement: Statement<'src>,
+}
+
+/// A statement with its argument, which is borrowed from the source unless processing it changed it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Statement<'src> {
+    pub keyword: &'static str,
+    /// The value of the argument, without its quotes and with its escapes processed. The argument of a deviate
+    /// statement is what it does, like "add".
+    pub argument: Option<Cow<'src, str>>,
+    /// The byte range of the statement in the source, from its keyword to its semicolon or closing brace.
+    pub span: Range<usize>,
+    /// The byte range of the argument in the source, with its quotes.
+    pub argument_span: Option<Range<usize>>,
+    pub substatements: Vec<Statement<'src>>,
+}
+
+impl<'src> BorrowedModule<'src> {
+    /// Parse the source of a module or submodule. It's checked against the grammar, but nothing is resolved.
+    pub fn parse(source: &'src str) -> Result<Self, ParserError> {
+        let pair = YangFile::parse(Rule::file, source)
+            .map_err(|error| ParserError::from(syntax::explain(error, source)))?
+            .next()
+            .expect("a yang file to always include a module");
+        let statement = statements(pair).pop().expect("a module or submodule to be a statement");
+        Ok(Self { source, statement })
+    }
+
+    /// The source the module was parsed from.
+    pub fn source(&self) -> &'src str {
+        self.source
+    }
+
+    /// The module or submodule statement.
+    pub fn statement(&self) -> &Statement<'src> {
+        &self.statement
+    }
+
+    /// The name of the module or submodule.
+    pub fn name(&self) -> &str {
+        self.statement.argument.as_deref().unwrap_or_default()
+    }
+
+    /// Parse the kept source into the model, like [crate::parse] does without loading imports and includes.
+    pub fn to_owned_module(&self) -> Result<YangModule, ParserError> {
+        YangParser::new().parse(self.source)
+    }
+}
+
+impl<'src> Statement<'src> {
+    /// The substatements with the keyword.
+    pub fn substatements_with<'a>(&'a self, keyword: &'a str) -> impl Iterator<Item = &'a Statement<'src>> {
+        self.substatements
+            .iter()
+            .filter(move |statement| statement.keyword == keyword)
+    }
+
+    /// The argument of the first substatement with the keyword, like the type of a leaf.
+    pub fn substatement_argument(&self, keyword: &str) -> Option<&str> {
+        self.substatements
+            .iter()
+            .find(|statement| statement.keyword == keyword)
+            .and_then(|statement| statement.argument.as_deref())
+    }
+
+    /// The statement with its arguments copied, so it no longer borrows from the source.
+    pub fn into_owned(self) -> Statement<'static> {
+        Statement {
+            keyword: self.keyword,
+            argument: self.argument.map(|argument| Cow::Owned(argument.into_owned())),
+            span: self.span,
+            argument_span: self.argument_span,
+            substatements: self.substatements.into_iter().map(Statement::into_owned).collect(),
+        }
+    }
+}
+
+// The statements of the pair, which is a statement, a group of statements like a data definition, or an argument,
+// which has none.
+pub(crate) fn statements(pair: Pair<'_, Rule>) -> Vec<Statement<'_>> {
+    let Some(keyword) = syntax::keyword(pair.as_rule()) else {
+        return match pair.as_rule() {
+            Rule::string | Rule::boolean | Rule::integer => Vec::new(),
+            _ => pair.into_inner().flat_map(statements).collect(),
+        };
+    };
+
+    let span = pair.as_span();
+    let mut statement = Statement {
+        keyword,
+        argument: None,
+        span: span.start()..span.end(),
+        argument_span: None,
+        substatements: Vec::new(),
+    };
+    statement.argument = match pair.as_rule() {
+        Rule::deviation_not_supported => Some(Cow::Borrowed("not-supported")),
+        Rule::deviate_add => Some(Cow::Borrowed("add")),
+        Rule::deviate_delete => Some(Cow::Borrowed("delete")),
+        Rule::deviate_replace => Some(Cow::Borrowed("replace")),
+        _ => None,
+    };
+    for child in pair.into_inner() {
+        match child.as_rule() {
+            Rule::string
+            | Rule::boolean
+            | Rule::integer
+            | Rule::status_value
+            | Rule::ordered_by_value
+            | Rule::modifier_value
+                if statement.argument_span.is_none() =>
+            {
+                let span = child.as_span();
+                statement.argument_span = Some(span.start()..span.end());
+                statement.argument = Some(parser::string_value(child));
+            }
+            _ => statement.substatements.extend(statements(child)),
+        }
+    }
+
+    vec![statement]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODULE: &str = r#"module example {
+  yang-version 1.1;
+  namespace "urn:example";
+  prefix ex;
+
+  container system {
+    description
+      "The system, with a description
+       over two lines.";
+    leaf name {
+      type string {
+        pattern '[a-z]+' + "\t[0-9]+";
+      }
+    }
+  }
+}"#;
+
+    #[test]
+    fn arguments_are_borrowed_unless_they_are_processed() {
+        let module = BorrowedModule::parse(MODULE).expect("the module to parse");
+        assert_eq!(module.name(), "example");
+
+        let system = module
+            .statement()
+            .substatements_with("container")
+            .next()
+            .expect("the container");
+        assert!(matches!(system.argument, Some(Cow::Borrowed("system"))));
+        assert_eq!(
+            &MODULE[system.argument_span.clone().expect("the argument span")],
+            "system"
+        );
+
+        let description = system.substatement_argument("description");
+        assert_eq!(description, Some("The system, with a description\nover two lines."));
+
+        let leaf = system.substatements_with("leaf").next().expect("the leaf");
+        let pattern = &leaf.substatements[0].substatements[0];
+        assert_eq!(pattern.keyword, "pattern");
+        assert!(matches!(&pattern.argument, Some(Cow::Owned(pattern)) if pattern == "[a-z]+\t[0-9]+"));
+    }
+
+    #[test]
+    fn owned_statements_and_modules_are_made_on_demand() {
+        let source = MODULE.to_string();
+        let module = BorrowedModule::parse(&source).expect("the module to parse");
+        let owned = module.to_owned_module().expect("the module to parse");
+        let statement = module.statement().clone().into_owned();
+        drop(module);
+        drop(source);
+
+        let YangModule::Module(owned) = owned else {
+            panic!("expected a module");
+        };
+        assert_eq!(owned.name, "example");
+        assert_eq!(statement.substatement_argument("prefix"), Some("ex"));
+    }
+}
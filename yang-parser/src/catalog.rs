@@ -0,0 +1,283 @@
+//! Fetching the modules which aren't in the search paths from the public [YANG Catalog](https://yangcatalog.org), which
+//! has the modules of the IETF, IEEE, OpenConfig and most vendors, so their standard imports don't have to be vendored.
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::registry::{self, ModuleSource};
+
+/// A [ModuleSource] fetching modules from the YANG Catalog's API into a local cache, where they're named after the
+/// module and its revision, like `ietf-inet-types@2013-07-15.yang`, so the cache is a search path as well. Modules are
+/// fetched once: revisions which are cached aren't fetched again, and imports without a revision use the latest cached
+/// revision of the module if there's one, rather than asking the catalog for its latest revision.
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use yang_parser::{catalog::YangCatalog, ParseOptions};
+///
+/// let options = ParseOptions {
+///     source: Some(Arc::new(YangCatalog::new())),
+///     ..Default::default()
+/// };
+/// let module = yang_parser::parse_with_options("example-system.yang", &options)?;
+/// # Ok::<(), yang_parser::ParserError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct YangCatalog {
+    url: String,
+    cache: PathBuf,
+    agent: ureq::Agent,
+}
+
+impl YangCatalog {
+    /// The address of the public catalog.
+    pub const URL: &'static str = "https://yangcatalog.org";
+
+    /// The public catalog, cached in [YangCatalog::default_cache].
+    pub fn new() -> Self {
+        let agent = ureq::Agent::config_builder()
+            .timeout_global(Some(Duration::from_secs(60)))
+            .build()
+            .into();
+        Self {
+            url: Self::URL.to_string(),
+            cache: Self::default_cache(),
+            agent,
+        }
+    }
+
+    /// The catalog at another address, like a mirror run by an organization, which has the same API.
+    pub fn with_url(self, url: impl Into<String>) -> Self {
+        Self {
+            url: url.into().trim_end_matches('/').to_string(),
+            ..self
+        }
+    }
+
+    /// The catalog cached in the directory, like one checked into a repository, so its builds don't depend on the
+    /// catalog once the modules were fetched.
+    pub fn with_cache(self, cache: impl Into<PathBuf>) -> Self {
+        Self {
+            cache: cache.into(),
+            ..self
+        }
+    }
+
+    /// The directory modules are cached in: `yang-rs/yangcatalog` in `$XDG_CACHE_HOME`, or in `~/.cache`, or in the
+    /// temporary directory if there's no home directory.
+    pub fn default_cache() -> PathBuf {
+        std::env::var_os("XDG_CACHE_HOME")
+            .filter(|directory| !directory.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))
+            .unwrap_or_else(std::env::temp_dir)
+            .join("yang-rs")
+            .join("yangcatalog")
+    }
+
+    /// The directory modules are cached in.
+    pub fn cache(&self) -> &Path {
+        &self.cache
+    }
+
+    // The body of the response of the catalog to the path, or None if the catalog doesn't have it.
+    fn get(&self, path: &str) -> io::Result<Option<String>> {
+        let url = format!("{}/{}", self.url, path);
+        let fetch_error = |error: ureq::Error| io::Error::other(format!("failed to fetch {}: {}", url, error));
+        match self.agent.get(&url).call() {
+            Ok(mut response) => response.body_mut().read_to_string().map(Some).map_err(fetch_error),
+            Err(ureq::Error::StatusCode(404)) => Ok(None),
+            Err(error) => Err(fetch_error(error)),
+        }
+    }
+
+    // The latest revision of the module in the catalog.
+    fn latest_revision(&self, module: &str) -> io::Result<Option<String>> {
+        let Some(body) = self.get(&format!("api/search/name/{}", module))? else {
+            return Ok(None);
+        };
+        let response: serde_json::Value = serde_json::from_str(&body)?;
+        let revisions = response["yang-catalog:modules"]["module"]
+            .as_array()
+            .into_iter()
+            .flatten();
+        Ok(revisions
+            .filter(|entry| entry["name"] == module)
+            .filter_map(|entry| entry["revision"].as_str())
+            .max()
+            .map(String::from))
+    }
+}
+
+impl Default for YangCatalog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ModuleSource for YangCatalog {
+    fn find_module(&self, module: &str, revision: Option<&str>) -> io::Result<Option<PathBuf>> {
+        let revision = match revision {
+            Some(revision) => revision.to_string(),
+            None => {
+                let file_name = format!("{}.yang", module);
+                if let Some(path) = registry::find_in_dir(&self.cache, &file_name, &format!("{}@", module)) {
+                    return Ok(Some(path));
+                }
+                match self.latest_revision(module)? {
+                    Some(revision) => revision,
+                    None => return Ok(None),
+                }
+            }
+        };
+
+        let file_name = format!("{}@{}.yang", module, revision);
+        let path = self.cache.join(&file_name);
+        if path.is_file() {
+            return Ok(Some(path));
+        }
+        let Some(source) = self.get(&format!("all_modules/{}", file_name))? else {
+            return Ok(None);
+        };
+
+        // Modules are written to a temporary file first, so parsers fetching the same module at the same time never
+        // read a partly written file.
+        std::fs::create_dir_all(&self.cache)?;
+        let temporary = self.cache.join(format!(".{}.{}", file_name, std::process::id()));
+        std::fs::write(&temporary, source)?;
+        std::fs::rename(&temporary, &path)?;
+        Ok(Some(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        sync::{Arc, Mutex},
+    };
+
+    use super::*;
+
+    const MODULE: &str = "module example-types {\n  namespace \"urn:example:types\";\n  prefix et;\n}\n";
+
+    // A cache directory of its own for the test, removed when it's dropped.
+    struct Cache(PathBuf);
+
+    impl Cache {
+        fn new(test: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("yang-rs-catalog-{}-{}", test, std::process::id()));
+            let _ = std::fs::remove_dir_all(&path);
+            std::fs::create_dir_all(&path).expect("the cache to be created");
+            Self(path)
+        }
+    }
+
+    impl Drop for Cache {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    // A catalog on a local port serving the bodies at their paths, returning its address and the requested paths.
+    fn serve(bodies: &[(&str, &str)]) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("a local port to be free");
+        let url = format!(
+            "http://{}",
+            listener.local_addr().expect("the listener to have an address")
+        );
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let bodies: Vec<(String, String)> = bodies
+            .iter()
+            .map(|(path, body)| (path.to_string(), body.to_string()))
+            .collect();
+
+        let requested = requests.clone();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut reader = BufReader::new(&stream);
+                let mut request = String::new();
+                reader.read_line(&mut request).expect("the request to be read");
+                let mut header = String::new();
+                while reader.read_line(&mut header).is_ok_and(|len| len > 2) {
+                    header.clear();
+                }
+
+                let path = request.split_whitespace().nth(1).unwrap_or_default().to_string();
+                let response = match bodies.iter().find(|(served, _)| *served == path) {
+                    Some((_, body)) => format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    ),
+                    None => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+                };
+                requested.lock().expect("the requests to not be poisoned").push(path);
+                stream
+                    .write_all(response.as_bytes())
+                    .expect("the response to be written");
+            }
+        });
+
+        (url, requests)
+    }
+
+    #[test]
+    fn cached_modules_are_not_fetched() {
+        let cache = Cache::new("cached");
+        for revision in ["2020-01-01", "2021-06-01"] {
+            let path = cache.0.join(format!("example-types@{}.yang", revision));
+            std::fs::write(path, MODULE).expect("the module to be cached");
+        }
+        let (url, requests) = serve(&[]);
+        let catalog = YangCatalog::new().with_url(url).with_cache(&cache.0);
+
+        let module = catalog.find_module("example-types", Some("2020-01-01"));
+        assert_eq!(
+            module.expect("the module to be found"),
+            Some(cache.0.join("example-types@2020-01-01.yang"))
+        );
+        let latest = catalog.find_module("example-types", None);
+        assert_eq!(
+            latest.expect("the module to be found"),
+            Some(cache.0.join("example-types@2021-06-01.yang"))
+        );
+        assert!(requests.lock().expect("the requests to not be poisoned").is_empty());
+    }
+
+    #[test]
+    fn modules_are_fetched_into_the_cache_once() {
+        let cache = Cache::new("fetched");
+        let search = r#"{"yang-catalog:modules": {"module": [
+            {"name": "example-types", "revision": "2020-01-01"},
+            {"name": "example-types", "revision": "2021-06-01"},
+            {"name": "example-types-ext", "revision": "2023-01-01"}
+        ]}}"#;
+        let (url, requests) = serve(&[
+            ("/api/search/name/example-types", search),
+            ("/all_modules/example-types@2021-06-01.yang", MODULE),
+        ]);
+        let catalog = YangCatalog::new().with_url(format!("{}/", url)).with_cache(&cache.0);
+
+        let path = cache.0.join("example-types@2021-06-01.yang");
+        for _ in 0..2 {
+            let module = catalog.find_module("example-types", None);
+            assert_eq!(module.expect("the module to be fetched"), Some(path.clone()));
+        }
+        assert_eq!(std::fs::read_to_string(&path).expect("the module to be cached"), MODULE);
+        assert_eq!(
+            *requests.lock().expect("the requests to not be poisoned"),
+            [
+                "/api/search/name/example-types",
+                "/all_modules/example-types@2021-06-01.yang"
+            ]
+        );
+
+        let missing = catalog.find_module("example-missing", None);
+        assert_eq!(missing.expect("the catalog to answer"), None);
+    }
+}
@@ -0,0 +1,220 @@
+//! Saving resolved modules in a compact binary format and loading them back, so tools don't parse and resolve an
+//! unchanged module set on every start.
+//!
+//! The resolved [YangModule] is what's saved, rather than a [SchemaTree](crate::schema_tree::SchemaTree), as the tree
+//! borrows its nodes from the module. After loading a module, [SchemaTree::new](crate::schema_tree::SchemaTree::new)
+//! builds its tree by walking the loaded nodes, which only allocates the arena of the tree.
+
+use crate::{
+    error::{ErrorKind, ParserError},
+    model::YangModule,
+};
+
+// Compiled modules start with a header naming the format and the version of the crate which wrote them, as the model
+// changes between versions, followed by the module encoded with postcard.
+const MAGIC: &[u8] = b"yang-rs compiled module\0";
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Encode a parsed and resolved module in a compact binary format, which [from_bytes] loads without parsing and
+/// resolving the module and its imports again. Nodes expanded from the same grouping, and shared type bodies, are
+/// encoded for every use, and loaded as separate instances.
+pub fn to_bytes(module: &YangModule) -> Result<Vec<u8>, ParserError> {
+    let mut bytes = header();
+    postcard::to_io(module, &mut bytes).map_err(|e| ErrorKind::InvalidCompiledModule(e.to_string()))?;
+    Ok(bytes)
+}
+
+/// Decode a module encoded with [to_bytes]. Modules encoded by another version of the crate are rejected.
+pub fn from_bytes(bytes: &[u8]) -> Result<YangModule, ParserError> {
+    let header = header();
+    let Some(body) = bytes.strip_prefix(header.as_slice()) else {
+        return Err(
+            ErrorKind::InvalidCompiledModule(format!("not a module compiled by yang-parser {}", VERSION)).into(),
+        );
+    };
+    postcard::from_bytes(body).map_err(|e| ErrorKind::InvalidCompiledModule(e.to_string()).into())
+}
+
+/// Write a module encoded with [to_bytes] to a file.
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub fn save<P: AsRef<std::path::Path>>(module: &YangModule, path: P) -> Result<(), ParserError> {
+    let path = path.as_ref();
+    std::fs::write(path, to_bytes(module)?).map_err(|e| ParserError::from(e).in_file(path))
+}
+
+/// Load a module from a file written by [save].
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub fn load<P: AsRef<std::path::Path>>(path: P) -> Result<YangModule, ParserError> {
+    let path = path.as_ref();
+    from_bytes(&std::fs::read(path).map_err(|e| ParserError::from(e).in_file(path))?).map_err(|e| e.in_file(path))
+}
+
+fn header() -> Vec<u8> {
+    let mut header = MAGIC.to_vec();
+    header.extend_from_slice(VERSION.as_bytes());
+    header.push(0);
+    header
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{model::ReferenceNodes, registry::ModuleRegistry, schema_tree::SchemaTree, ParseOptions};
+
+    fn module() -> YangModule {
+        let common = r#"module common {
+  namespace "urn:common";
+  prefix c;
+
+  typedef name {
+    type string {
+      length "1..64";
+    }
+  }
+
+  grouping endpoint {
+    leaf address {
+      type string;
+    }
+    leaf port {
+      type uint16;
+      default 830;
+    }
+  }
+}
+"#;
+        let system = r#"module system {
+  yang-version 1.1;
+  namespace "urn:system";
+  prefix sys;
+
+  import common {
+    prefix c;
+  }
+
+  revision 2024-01-01;
+
+  identity protocol;
+
+  container system {
+    leaf hostname {
+      type c:name;
+    }
+    container server {
+      uses c:endpoint;
+    }
+    list user {
+      key name;
+      leaf name {
+        type c:name;
+      }
+      choice authentication {
+        leaf password {
+          type string;
+        }
+        leaf key {
+          type binary;
+        }
+      }
+    }
+  }
+
+  rpc restart {
+    input {
+      leaf delay {
+        type uint32;
+        units seconds;
+      }
+    }
+  }
+}
+"#;
+        let options = ParseOptions {
+            registry: Some(Arc::new(
+                ModuleRegistry::new()
+                    .with("models/common.yang", common)
+                    .with("models/system.yang", system),
+            )),
+            ..Default::default()
+        };
+        crate::parse_with_options("models/system.yang", &options).expect("the module to parse")
+    }
+
+    // The reference nodes in the order of their paths, as the order of a map depends on how it was filled.
+    fn reference_nodes(reference_nodes: &ReferenceNodes) -> Vec<String> {
+        let mut nodes: Vec<String> = (reference_nodes.features.iter().map(|node| format!("{node:?}")))
+            .chain(reference_nodes.groupings.iter().map(|node| format!("{node:?}")))
+            .chain(reference_nodes.identities.iter().map(|node| format!("{node:?}")))
+            .chain(reference_nodes.type_defs.iter().map(|node| format!("{node:?}")))
+            .collect();
+        nodes.sort();
+        nodes
+    }
+
+    fn schema_paths(module: &crate::model::Module) -> Vec<String> {
+        let tree = SchemaTree::new(module);
+        tree.iter().map(|id| tree.path(id)).collect()
+    }
+
+    #[test]
+    fn loaded_modules_are_equal_to_the_saved_modules() {
+        let YangModule::Module(module) = module() else {
+            panic!("expected a module");
+        };
+        let bytes = to_bytes(&YangModule::Module(module.clone())).expect("the module to encode");
+        let YangModule::Module(loaded) = from_bytes(&bytes).expect("the module to decode") else {
+            panic!("expected a module");
+        };
+
+        assert_eq!(loaded.name, module.name);
+        assert_eq!(loaded.yang_version, module.yang_version);
+        assert_eq!(loaded.namespace, module.namespace);
+        assert_eq!(loaded.prefix, module.prefix);
+        assert_eq!(format!("{:?}", loaded.meta), format!("{:?}", module.meta));
+        assert_eq!(format!("{:?}", loaded.revisions), format!("{:?}", module.revisions));
+        assert_eq!(format!("{:?}", loaded.body), format!("{:?}", module.body));
+        assert_eq!(format!("{:?}", loaded.augments), format!("{:?}", module.augments));
+        assert_eq!(format!("{:?}", loaded.deviations), format!("{:?}", module.deviations));
+        assert_eq!(
+            reference_nodes(&loaded.reference_nodes),
+            reference_nodes(&module.reference_nodes)
+        );
+        assert_eq!(
+            reference_nodes(&loaded.imported_modules["common"]),
+            reference_nodes(&module.imported_modules["common"])
+        );
+        assert_eq!(loaded.imported_namespaces, module.imported_namespaces);
+        assert_eq!(loaded.prefix_to_module, module.prefix_to_module);
+
+        let paths = schema_paths(&loaded);
+        assert_eq!(paths, schema_paths(&module));
+        assert!(paths.contains(&"/system/server/port".to_string()));
+        assert!(paths.contains(&"/system/user/password".to_string()));
+    }
+
+    #[test]
+    fn modules_with_another_header_are_rejected() {
+        let bytes = to_bytes(&module()).expect("the module to encode");
+        let body = &bytes[header().len()..];
+
+        let mut other_version = MAGIC.to_vec();
+        other_version.extend_from_slice(b"0.0.0-other\0");
+        other_version.extend_from_slice(body);
+        let mut other_format = b"another format\0".to_vec();
+        other_format.extend_from_slice(body);
+
+        for bytes in [other_version, other_format, body.to_vec(), Vec::new()] {
+            let error = from_bytes(&bytes).expect_err("the module to be rejected");
+            assert!(
+                matches!(error.kind(), ErrorKind::InvalidCompiledModule(message) if message.contains(VERSION)),
+                "{error:?}"
+            );
+        }
+
+        let truncated = &bytes[..bytes.len() / 2];
+        let error = from_bytes(truncated).expect_err("the truncated module to be rejected");
+        assert!(matches!(error.kind(), ErrorKind::InvalidCompiledModule(_)));
+    }
+}
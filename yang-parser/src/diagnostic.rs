@@ -0,0 +1,124 @@
+use std::fmt::Display;
+
+use miette::{Diagnostic, LabeledSpan, MietteError, MietteSpanContents, SourceCode, SourceSpan, SpanContents};
+use pest::error::LineColLocation;
+
+use crate::error::{ErrorKind, ParserError, ValueError};
+
+// Errors are rendered by miette, with the offending line of a YANG file and a caret under where parsing failed, instead
+// of the text of the pest error.
+
+impl Diagnostic for ParserError {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(self.code()))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        let help = match self.kind() {
+            ErrorKind::InvalidParserEntrypoint => "parse the module the submodule belongs to instead",
+            ErrorKind::InvalidInclude(_) => "modules are imported, only submodules are included",
+            ErrorKind::InvalidImport(_) => "submodules are included, only modules are imported",
+            ErrorKind::InvalidCompiledModule(_) => "compile the module again with this version of yang-parser",
+            _ => return None,
+        };
+        Some(Box::new(help))
+    }
+
+    fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn Diagnostic> + 'a>> {
+        match self.kind() {
+            ErrorKind::Multiple(errors) => Some(Box::new(errors.iter().map(|error| error as &dyn Diagnostic))),
+            _ => None,
+        }
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        match self.kind() {
+            ErrorKind::ParseError(_) => Some(self),
+            _ => None,
+        }
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        let ErrorKind::ParseError(error) = self.kind() else {
+            return None;
+        };
+
+        // The span is within the line of the error, and ends there if it spans several lines.
+        let line = error.line();
+        let (start, end) = match error.line_col {
+            LineColLocation::Pos((_, column)) => (column, column + 1),
+            LineColLocation::Span((start_line, start), (end_line, end)) if start_line == end_line => (start, end),
+            LineColLocation::Span((_, start), _) => (start, line.chars().count() + 1),
+        };
+        let offset = byte_offset(line, start);
+        let length = byte_offset(line, end).saturating_sub(offset).max(1);
+        // The message of the error says what was expected, so the label only points at where.
+        let label = LabeledSpan::new(None, offset, length);
+
+        Some(Box::new(std::iter::once(label)))
+    }
+}
+
+impl Diagnostic for ValueError {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        let code = match self {
+            ValueError::InvalidValue { .. } => "yang::value::invalid",
+            ValueError::OutOfRange { .. } => "yang::value::out_of_range",
+            ValueError::MissingFractionDigits => "yang::value::fraction_digits",
+            ValueError::UnknownEnum(_) => "yang::value::enum",
+            ValueError::UnknownBit(_) => "yang::value::bit",
+            ValueError::UnknownIdentity(_) => "yang::value::identity",
+            ValueError::WrongIdentityBase { .. } => "yang::value::identity_base",
+            ValueError::NoMatchingUnionMember(_) => "yang::value::union",
+            ValueError::RangeViolation { .. } => "yang::value::range",
+            ValueError::LengthViolation { .. } => "yang::value::length",
+            ValueError::PatternMismatch { .. } => "yang::value::pattern",
+            ValueError::InvalidRestriction(_) => "yang::value::restriction",
+            ValueError::UnsupportedPattern { .. } => "yang::value::unsupported_pattern",
+            ValueError::UnresolvedType(_) => "yang::value::unresolved_type",
+        };
+        Some(Box::new(code))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        let help = match self {
+            ValueError::UnresolvedType(_) => "resolve the type with types::TypeResolver::effective_type first",
+            ValueError::MissingFractionDigits => "decimal64 types need a fraction-digits statement",
+            _ => return None,
+        };
+        Some(Box::new(help))
+    }
+}
+
+// The byte offset of the 1-based character column in the line.
+fn byte_offset(line: &str, column: usize) -> usize {
+    line.char_indices()
+        .nth(column.saturating_sub(1))
+        .map_or(line.len(), |(offset, _)| offset)
+}
+
+// A pest error only keeps the line it failed at, so the source of a parse error is that line, numbered like in the file.
+// Offsets of labels are within the line.
+impl SourceCode for ParserError {
+    fn read_span<'a>(
+        &'a self,
+        _span: &SourceSpan,
+        _context_lines_before: usize,
+        _context_lines_after: usize,
+    ) -> Result<Box<dyn SpanContents<'a> + 'a>, MietteError> {
+        let ErrorKind::ParseError(error) = self.kind() else {
+            return Err(MietteError::OutOfBounds);
+        };
+
+        let line = error.line();
+        let line_number = match error.line_col {
+            LineColLocation::Pos((line, _)) | LineColLocation::Span((line, _), _) => line,
+        };
+        let span = SourceSpan::new(0.into(), line.len());
+        let contents = match error.path() {
+            Some(path) => MietteSpanContents::new_named(path.to_string(), line.as_bytes(), span, line_number - 1, 0, 1),
+            None => MietteSpanContents::new(line.as_bytes(), span, line_number - 1, 0, 1),
+        };
+        Ok(Box::new(contents))
+    }
+}
@@ -0,0 +1,801 @@
+use std::ops::Range;
+
+use pest::{iterators::Pair, Parser};
+
+use crate::{
+    borrowed,
+    error::{ErrorKind, ParserError},
+    model::*,
+    parser::YangParser,
+    parser_internal::{Rule, YangFile},
+    symbol::Interner,
+    syntax, Symbol,
+};
+
+/// A YANG module or submodule which is edited, like a file open in an editor. The top-level statements of the document
+/// are parsed one by one, so an edit only re-parses the statements it touches, and only their parts of the module are
+/// replaced. This keeps language servers and other editor tools responsive on multi-thousand-line
+/// modules.
+///
+/// The module is parsed like a file without its imports and includes, which are listed by [Document::imports] and
+/// [Document::includes]. Groupings, typedefs, augments and deviations are kept in the module, but references aren't
+/// resolved, as that needs the other files of the module set.
+#[derive(Debug)]
+pub struct Document {
+    source: String,
+    interner: Interner,
+
+    // The module or submodule with only its name, and the byte range between the end of its name and its closing
+    // brace, where its statements are.
+    header: YangModule,
+    body: Range<usize>,
+    statements: Vec<Statement>,
+    module: YangModule,
+
+    // Set when the source failed to parse, so the statements no longer match the source until it is parsed again.
+    stale: bool,
+}
+
+/// A statement of a [Document] with where it is in the source, for editor features like outlines, go-to-definition and
+/// hover, which need to know which statement is where rather than what it means.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceStatement {
+    pub keyword: &'static str,
+    /// The argument of the statement without its quotes, like the name of a leaf. The argument of a deviate statement
+    /// is what it does, like "add".
+    pub argument: Option<String>,
+    /// The byte range of the statement in the source, from its keyword to its semicolon or closing brace.
+    pub span: Range<usize>,
+    /// The byte range of the argument in the source, with its quotes.
+    pub argument_span: Option<Range<usize>>,
+    pub children: Vec<SourceStatement>,
+}
+
+impl SourceStatement {
+    /// The substatements with the keyword.
+    pub fn children_with<'a>(&'a self, keyword: &'a str) -> impl Iterator<Item = &'a SourceStatement> {
+        self.children.iter().filter(move |child| child.keyword == keyword)
+    }
+
+    /// The argument of the first substatement with the keyword, like the type of a leaf.
+    pub fn child_argument(&self, keyword: &str) -> Option<&str> {
+        self.children
+            .iter()
+            .find(|child| child.keyword == keyword)
+            .and_then(|child| child.argument.as_deref())
+    }
+
+    fn shift(&mut self, shift: &impl Fn(usize) -> usize) {
+        self.span = shift(self.span.start)..shift(self.span.end);
+        if let Some(span) = &mut self.argument_span {
+            *span = shift(span.start)..shift(span.end);
+        }
+        for child in &mut self.children {
+            child.shift(shift);
+        }
+    }
+}
+
+// A top-level statement with its byte range in the source, and what parsing it added to the module and the parser.
+// Its revisions, body nodes, reference nodes, augments and deviations are moved into the module of the document, and
+// its parts say how many it added, so an edit replaces them in place rather than putting the whole module together
+// again.
+#[derive(Debug)]
+struct Statement {
+    span: Range<usize>,
+    module: YangModule,
+    parser: YangParser,
+    parts: Parts,
+    source: Vec<SourceStatement>,
+}
+
+#[derive(Debug, Default)]
+struct Parts {
+    revisions: usize,
+    body: usize,
+    augments: usize,
+    deviations: usize,
+    features: Vec<String>,
+    groupings: Vec<String>,
+    identities: Vec<String>,
+    type_defs: Vec<String>,
+}
+
+impl Statement {
+    fn is_belongs_to(&self) -> bool {
+        matches!(&self.module, YangModule::Submodule(submodule) if !submodule.belongs_to.module.is_empty())
+    }
+
+    // Whether the statement defines a reference node the other statement also defines, so which one the module keeps
+    // depends on the order they were added in.
+    fn shares_reference_nodes(&self, other: &Statement) -> bool {
+        let (parts, other) = (&self.parts, &other.parts);
+        [
+            (&parts.features, &other.features),
+            (&parts.groupings, &other.groupings),
+            (&parts.identities, &other.identities),
+            (&parts.type_defs, &other.type_defs),
+        ]
+        .into_iter()
+        .any(|(paths, other)| paths.iter().any(|path| other.contains(path)))
+    }
+}
+
+impl Document {
+    /// Parse the source of a module or submodule into a document.
+    pub fn parse(source: impl Into<String>) -> Result<Self, ParserError> {
+        let mut document = Self {
+            source: source.into(),
+            interner: Interner::default(),
+            header: YangModule::Module(Module::default()),
+            body: 0..0,
+            statements: Vec::new(),
+            module: YangModule::Module(Module::default()),
+            stale: true,
+        };
+        document.reparse()?;
+
+        Ok(document)
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// The module or submodule as it was last parsed successfully.
+    pub fn module(&self) -> &YangModule {
+        &self.module
+    }
+
+    pub fn imports(&self) -> impl Iterator<Item = &Import> {
+        self.statements.iter().flat_map(|statement| &statement.parser.imports)
+    }
+
+    pub fn includes(&self) -> impl Iterator<Item = &Include> {
+        self.statements.iter().flat_map(|statement| statement.parser.includes())
+    }
+
+    /// The statements of the module or submodule, with where they are in the source, as they were last parsed
+    /// successfully.
+    pub fn statements(&self) -> impl Iterator<Item = &SourceStatement> {
+        self.statements.iter().flat_map(|statement| &statement.source)
+    }
+
+    /// The statements at the byte offset of the source, from the top-level statement down to the innermost statement,
+    /// like the grouping, the container in it and the leaf in the container. Empty if the offset isn't in a statement.
+    pub fn statements_at(&self, offset: usize) -> Vec<&SourceStatement> {
+        let mut path = Vec::new();
+        let mut statements: &[SourceStatement] = &[];
+        let index = self.statements.partition_point(|statement| statement.span.end < offset);
+        if let Some(statement) = self.statements.get(index) {
+            statements = &statement.source;
+        }
+        while let Some(statement) = statements
+            .iter()
+            .find(|statement| statement.span.start <= offset && offset <= statement.span.end)
+        {
+            path.push(statement);
+            statements = &statement.children;
+        }
+
+        path
+    }
+
+    /// Replace the byte range of the source with the text, and re-parse the top-level statements the edit touches.
+    /// Edits of the name of the module, and of a belongs-to statement, re-parse the whole document.
+    ///
+    /// If the edited source doesn't parse, the error is returned with its position in the whole source, and the module
+    /// stays as it was last parsed. The source keeps the edit, and later edits re-parse the whole document until it
+    /// parses again.
+    pub fn edit(&mut self, range: Range<usize>, text: &str) -> Result<(), ParserError> {
+        if range.start > range.end
+            || range.end > self.source.len()
+            || !self.source.is_char_boundary(range.start)
+            || !self.source.is_char_boundary(range.end)
+        {
+            return Err(ErrorKind::InvalidEdit(range).into());
+        }
+
+        self.source.replace_range(range.clone(), text);
+        if self.stale || range.start < self.body.start || range.end > self.body.end {
+            return self.reparse();
+        }
+
+        // The statements touched by the edit are re-parsed with the text between the statements before and after
+        // them, which are kept.
+        let first = self
+            .statements
+            .partition_point(|statement| statement.span.end < range.start);
+        let last = self
+            .statements
+            .partition_point(|statement| statement.span.start <= range.end);
+        if self.statements[first..last].iter().any(Statement::is_belongs_to) {
+            return self.reparse();
+        }
+
+        let shift = |offset: usize| offset + text.len() - range.len();
+        let start = match first {
+            0 => self.body.start,
+            first => self.statements[first - 1].span.end,
+        };
+        let end = shift(
+            self.statements
+                .get(last)
+                .map_or(self.body.end, |statement| statement.span.start),
+        );
+
+        // A line comment at the end of the text would comment out the start of the next statement in the whole source.
+        let region = &self.source[start..end];
+        if region.rsplit('\n').next().is_some_and(|line| line.contains("//")) {
+            return self.reparse();
+        }
+
+        let statements = match self.parse_statements(start..end) {
+            Ok(statements) if !statements.iter().any(Statement::is_belongs_to) => statements,
+            _ => return self.reparse(),
+        };
+
+        for statement in &mut self.statements[last..] {
+            statement.span = shift(statement.span.start)..shift(statement.span.end);
+            for source in &mut statement.source {
+                source.shift(&shift);
+            }
+        }
+        self.body.end = shift(self.body.end);
+
+        // A reference node defined by a statement the edit doesn't touch as well can't be replaced in place, as the
+        // module keeps the one of the later statement.
+        let (kept, replaced) = (
+            self.statements[..first].iter().chain(&self.statements[last..]),
+            self.statements[first..last].iter().chain(&statements),
+        );
+        if replaced
+            .flat_map(|statement| kept.clone().map(move |kept| (statement, kept)))
+            .any(|(statement, kept)| statement.shares_reference_nodes(kept))
+        {
+            return self.reparse();
+        }
+        self.splice(first..last, statements);
+
+        Ok(())
+    }
+
+    // Parse the whole source again, with a new interner, so identifiers which were edited away are dropped.
+    fn reparse(&mut self) -> Result<(), ParserError> {
+        self.stale = true;
+        self.interner = Interner::default();
+
+        let file = YangFile::parse(Rule::file, &self.source)
+            .map_err(|error| ParserError::from(syntax::explain(error, &self.source)))?
+            .next()
+            .expect("a yang file to always include a module");
+        self.header = match file.as_rule() {
+            Rule::module => YangModule::Module(Module::default()),
+            Rule::submodule => YangModule::Submodule(Submodule::default()),
+            _ => unreachable!("parsing a file can only result in a module or submodule"),
+        };
+
+        // The module ends with its closing brace.
+        let body_end = file.as_span().end() - 1;
+        let mut children = file.into_inner();
+        let name = children.next().expect("a module to start with its name");
+        let body_start = name.as_span().end();
+        let mut parser = YangParser::with_interner(self.interner.clone());
+        match &mut self.header {
+            YangModule::Module(module) => parser.parse_module_statement(module, name),
+            YangModule::Submodule(submodule) => parser.parse_submodule_statement(submodule, name),
+        }
+
+        let mut belongs_to_prefix = None;
+        let mut statements = Vec::new();
+        for child in children {
+            let statement = parse_statement(&self.interner, &self.header, belongs_to_prefix.clone(), child, 0);
+            if let YangModule::Submodule(submodule) = &statement.module {
+                if statement.is_belongs_to() {
+                    belongs_to_prefix = Some(submodule.belongs_to.prefix.clone());
+                }
+            }
+            statements.push(statement);
+        }
+
+        self.body = body_start..body_end;
+        self.module = self.header.clone();
+        self.statements.clear();
+        self.splice(0..0, statements);
+        self.stale = false;
+
+        Ok(())
+    }
+
+    // Parse the top-level statements in the byte range of the source.
+    fn parse_statements(&self, range: Range<usize>) -> Result<Vec<Statement>, ParserError> {
+        let belongs_to_prefix = match &self.module {
+            YangModule::Submodule(submodule) if !submodule.belongs_to.prefix.is_empty() => {
+                Some(submodule.belongs_to.prefix.clone())
+            }
+            _ => None,
+        };
+        let rule = match self.header {
+            YangModule::Module(_) => Rule::module_statements,
+            YangModule::Submodule(_) => Rule::submodule_statements,
+        };
+
+        let source = &self.source[range.clone()];
+        let statements = YangFile::parse(rule, source)
+            .map_err(|error| ParserError::from(syntax::explain(error, source)))?
+            .filter(|child| child.as_rule() != Rule::EOI)
+            .map(|child| {
+                parse_statement(
+                    &self.interner,
+                    &self.header,
+                    belongs_to_prefix.clone(),
+                    child,
+                    range.start,
+                )
+            })
+            .collect();
+
+        Ok(statements)
+    }
+
+    // Replace the statements in the range with the new statements, and their parts in the module with the parts of the
+    // new statements, which are moved into the module.
+    fn splice(&mut self, range: Range<usize>, mut statements: Vec<Statement>) {
+        let (before, replaced) = (&self.statements[..range.start], &self.statements[range.clone()]);
+        let parts = |count: fn(&Parts) -> usize| {
+            let start = before.iter().map(|statement| count(&statement.parts)).sum::<usize>();
+            start..start + replaced.iter().map(|statement| count(&statement.parts)).sum::<usize>()
+        };
+        let (revisions, body) = (parts(|parts| parts.revisions), parts(|parts| parts.body));
+        let (augments, deviations) = (parts(|parts| parts.augments), parts(|parts| parts.deviations));
+
+        match &mut self.module {
+            YangModule::Module(module) => {
+                let reference_nodes = &mut module.reference_nodes;
+                for parts in replaced.iter().map(|statement| &statement.parts) {
+                    for path in &parts.features {
+                        reference_nodes.features.remove(path);
+                    }
+                    for path in &parts.groupings {
+                        reference_nodes.groupings.remove(path);
+                    }
+                    for path in &parts.identities {
+                        reference_nodes.identities.remove(path);
+                    }
+                    for path in &parts.type_defs {
+                        reference_nodes.type_defs.remove(path);
+                    }
+                }
+                for statement in &mut statements {
+                    let part = std::mem::take(&mut statement.parser.reference_nodes);
+                    statement.parts.features = part.features.keys().cloned().collect();
+                    statement.parts.groupings = part.groupings.keys().cloned().collect();
+                    statement.parts.identities = part.identities.keys().cloned().collect();
+                    statement.parts.type_defs = part.type_defs.keys().cloned().collect();
+                    reference_nodes.features.extend(part.features);
+                    reference_nodes.groupings.extend(part.groupings);
+                    reference_nodes.identities.extend(part.identities);
+                    reference_nodes.type_defs.extend(part.type_defs);
+                }
+
+                let (new_revisions, new_body) = take_parts(&mut statements);
+                module.revisions.splice(revisions, new_revisions);
+                module.body.splice(body, new_body);
+                module.augments.splice(
+                    augments,
+                    take(&mut statements, |statement| {
+                        let augments = std::mem::take(&mut statement.parser.augments);
+                        statement.parts.augments = augments.len();
+                        augments
+                    }),
+                );
+                module.deviations.splice(
+                    deviations,
+                    take(&mut statements, |statement| {
+                        let deviations = std::mem::take(&mut statement.parser.deviations);
+                        statement.parts.deviations = deviations.len();
+                        deviations
+                    }),
+                );
+            }
+            YangModule::Submodule(submodule) => {
+                let (new_revisions, new_body) = take_parts(&mut statements);
+                submodule.revisions.splice(revisions, new_revisions);
+                submodule.body.splice(body, new_body);
+            }
+        }
+
+        self.statements.splice(range, statements);
+        self.assemble_header();
+    }
+
+    // Set the statements of the module which are kept in the statements rather than moved into the module, like its
+    // namespace and description, again.
+    fn assemble_header(&mut self) {
+        match (&mut self.module, &self.header) {
+            (YangModule::Module(module), YangModule::Module(header)) => {
+                module.yang_version.clone_from(&header.yang_version);
+                module.namespace.clone_from(&header.namespace);
+                module.prefix = header.prefix.clone();
+                module.meta.clone_from(&header.meta);
+            }
+            (YangModule::Submodule(submodule), YangModule::Submodule(header)) => {
+                submodule.yang_version.clone_from(&header.yang_version);
+                submodule.belongs_to = header.belongs_to.clone();
+                submodule.meta.clone_from(&header.meta);
+            }
+            _ => unreachable!("the module of a document to be made from its header"),
+        }
+
+        for statement in &self.statements {
+            match (&mut self.module, &statement.module) {
+                (YangModule::Module(module), YangModule::Module(part)) => {
+                    if part.yang_version.is_some() {
+                        module.yang_version.clone_from(&part.yang_version);
+                    }
+                    if !part.namespace.is_empty() {
+                        module.namespace.clone_from(&part.namespace);
+                    }
+                    if !part.prefix.is_empty() {
+                        module.prefix = part.prefix.clone();
+                    }
+                    merge_meta(&mut module.meta, &part.meta);
+                }
+                (YangModule::Submodule(submodule), YangModule::Submodule(part)) => {
+                    if part.yang_version.is_some() {
+                        submodule.yang_version.clone_from(&part.yang_version);
+                    }
+                    if statement.is_belongs_to() {
+                        submodule.belongs_to = part.belongs_to.clone();
+                    }
+                    merge_meta(&mut submodule.meta, &part.meta);
+                }
+                _ => unreachable!("the statements of a document to be parsed like its header"),
+            }
+        }
+    }
+}
+
+// Move the revisions and body nodes of the new statements out of them, for the module.
+fn take_parts(statements: &mut [Statement]) -> (Vec<Revision>, Vec<SchemaNode>) {
+    let revisions = take(statements, |statement| {
+        let revisions = match &mut statement.module {
+            YangModule::Module(module) => std::mem::take(&mut module.revisions),
+            YangModule::Submodule(submodule) => std::mem::take(&mut submodule.revisions),
+        };
+        statement.parts.revisions = revisions.len();
+        revisions
+    });
+    let body = take(statements, |statement| {
+        let body = match &mut statement.module {
+            YangModule::Module(module) => std::mem::take(&mut module.body),
+            YangModule::Submodule(submodule) => std::mem::take(&mut submodule.body),
+        };
+        statement.parts.body = body.len();
+        body
+    });
+
+    (revisions, body)
+}
+
+fn take<T>(statements: &mut [Statement], part: impl FnMut(&mut Statement) -> Vec<T>) -> Vec<T> {
+    statements.iter_mut().flat_map(part).collect()
+}
+
+// Parse a top-level statement with a new parser, as the statements only depend on each other through the prefix of
+// the belongs-to statement of a submodule. The offset is where the text the statement was parsed from starts in the
+// source.
+fn parse_statement(
+    interner: &Interner,
+    header: &YangModule,
+    belongs_to_prefix: Option<Symbol>,
+    child: Pair<Rule>,
+    offset: usize,
+) -> Statement {
+    let span = child.as_span();
+    let span = offset + span.start()..offset + span.end();
+    let source = source_statements(child.clone(), offset);
+    let mut parser = YangParser::with_interner(interner.clone());
+    let module = match header {
+        YangModule::Module(_) => {
+            let mut module = Module::default();
+            parser.parse_module_statement(&mut module, child);
+            YangModule::Module(module)
+        }
+        YangModule::Submodule(_) => {
+            let mut submodule = Submodule::default();
+            parser.set_belongs_to_prefix(belongs_to_prefix);
+            parser.parse_submodule_statement(&mut submodule, child);
+            YangModule::Submodule(submodule)
+        }
+    };
+
+    Statement {
+        span,
+        module,
+        parser,
+        parts: Parts::default(),
+        source,
+    }
+}
+
+// The statements of the pair, which is a statement, a group of statements like a data definition, or an argument,
+// which has none, at the offset of the pair's source in the document.
+fn source_statements(pair: Pair<Rule>, offset: usize) -> Vec<SourceStatement> {
+    borrowed::statements(pair)
+        .into_iter()
+        .map(|statement| source_statement(statement, offset))
+        .collect()
+}
+
+fn source_statement(statement: borrowed::Statement, offset: usize) -> SourceStatement {
+    SourceStatement {
+        keyword: statement.keyword,
+        argument: statement.argument.map(|argument| argument.into_owned()),
+        span: offset + statement.span.start..offset + statement.span.end,
+        argument_span: statement
+            .argument_span
+            .map(|span| offset + span.start..offset + span.end),
+        children: statement
+            .substatements
+            .into_iter()
+            .map(|statement| source_statement(statement, offset))
+            .collect(),
+    }
+}
+
+fn merge_meta(meta: &mut MetaInfo, part: &MetaInfo) {
+    for (field, value) in [
+        (&mut meta.organization, &part.organization),
+        (&mut meta.contact, &part.contact),
+        (&mut meta.description, &part.description),
+        (&mut meta.reference, &part.reference),
+    ] {
+        if value.is_some() {
+            field.clone_from(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODULE: &str = r#"module example {
+  yang-version 1.1;
+  namespace "urn:example";
+  prefix ex;
+
+  import ietf-inet-types {
+    prefix inet;
+  }
+
+  description "An example module.";
+
+  revision 2024-01-01;
+
+  typedef port {
+    type uint16;
+  }
+
+  grouping endpoint {
+    leaf address {
+      type inet:ip-address;
+    }
+    leaf port {
+      type port;
+    }
+  }
+
+  container system {
+    leaf hostname {
+      type string;
+    }
+    container server {
+      uses endpoint;
+    }
+  }
+
+  augment "/ex:system" {
+    leaf location {
+      type string;
+    }
+  }
+}
+"#;
+
+    const SUBMODULE: &str = r#"submodule example-types {
+  belongs-to example {
+    prefix ex;
+  }
+
+  typedef name {
+    type string;
+  }
+
+  leaf owner {
+    type ex:name;
+  }
+}
+"#;
+
+    // Edit the document and check that it's the same as the document parsed from the edited source.
+    fn edit(document: &mut Document, find: &str, text: &str) {
+        let start = document.source().find(find).expect("the text to edit");
+        document
+            .edit(start..start + find.len(), text)
+            .expect("the edited source to parse");
+        assert_parsed(document);
+    }
+
+    fn assert_parsed(document: &Document) {
+        let parsed = Document::parse(document.source()).expect("the edited source to parse");
+        assert_eq!(
+            document.statements().collect::<Vec<_>>(),
+            parsed.statements().collect::<Vec<_>>()
+        );
+        assert_eq!(document.imports().count(), parsed.imports().count());
+        assert_eq!(document.includes().count(), parsed.includes().count());
+
+        match (document.module(), parsed.module()) {
+            (YangModule::Module(module), YangModule::Module(parsed)) => {
+                assert_eq!(module.name, parsed.name);
+                assert_eq!(module.yang_version, parsed.yang_version);
+                assert_eq!(module.namespace, parsed.namespace);
+                assert_eq!(module.prefix, parsed.prefix);
+                assert_eq!(format!("{:?}", module.meta), format!("{:?}", parsed.meta));
+                assert_eq!(format!("{:?}", module.revisions), format!("{:?}", parsed.revisions));
+                assert_eq!(format!("{:?}", module.body), format!("{:?}", parsed.body));
+                assert_eq!(format!("{:?}", module.augments), format!("{:?}", parsed.augments));
+                assert_eq!(format!("{:?}", module.deviations), format!("{:?}", parsed.deviations));
+                assert_eq!(
+                    reference_nodes(&module.reference_nodes),
+                    reference_nodes(&parsed.reference_nodes)
+                );
+            }
+            (YangModule::Submodule(submodule), YangModule::Submodule(parsed)) => {
+                assert_eq!(format!("{submodule:?}"), format!("{parsed:?}"));
+            }
+            _ => panic!("expected the edited document to be parsed like the source"),
+        }
+    }
+
+    // The reference nodes in the order of their paths, as the order of a map depends on how it was filled.
+    fn reference_nodes(reference_nodes: &ReferenceNodes) -> Vec<String> {
+        let mut nodes: Vec<String> = (reference_nodes.features.iter().map(|node| format!("{node:?}")))
+            .chain(reference_nodes.groupings.iter().map(|node| format!("{node:?}")))
+            .chain(reference_nodes.identities.iter().map(|node| format!("{node:?}")))
+            .chain(reference_nodes.type_defs.iter().map(|node| format!("{node:?}")))
+            .collect();
+        nodes.sort();
+        nodes
+    }
+
+    #[test]
+    fn statements_are_inserted_and_deleted_at_statement_boundaries() {
+        let mut document = Document::parse(MODULE).expect("the module to parse");
+
+        edit(
+            &mut document,
+            "  container system {",
+            "  leaf enabled {\n    type boolean;\n  }\n\n  container system {",
+        );
+        edit(
+            &mut document,
+            "  revision 2024-01-01;\n",
+            "  revision 2024-02-01;\n  revision 2024-01-01;\n",
+        );
+        edit(&mut document, "\n}\n", "\n  feature ssh;\n}\n");
+        edit(&mut document, "  feature ssh;\n", "");
+        edit(&mut document, "  leaf enabled {\n    type boolean;\n  }\n\n", "");
+        edit(&mut document, "  typedef port {\n    type uint16;\n  }\n", "");
+        edit(&mut document, "  description \"An example module.\";\n", "");
+        edit(&mut document, "  import ietf-inet-types {\n    prefix inet;\n  }\n", "");
+    }
+
+    #[test]
+    fn statements_are_edited_inside_nested_statements() {
+        let mut document = Document::parse(MODULE).expect("the module to parse");
+
+        edit(
+            &mut document,
+            "type string;\n    }\n    container",
+            "type string;\n      mandatory true;\n    }\n    container",
+        );
+        edit(
+            &mut document,
+            "uses endpoint;",
+            "uses endpoint;\n      leaf timeout {\n        type uint32;\n      }",
+        );
+        edit(
+            &mut document,
+            "type uint16;",
+            "type uint16 {\n      range \"1..max\";\n    }",
+        );
+        edit(&mut document, "leaf port {", "leaf service-port {");
+        edit(&mut document, "leaf location", "leaf site");
+        edit(&mut document, "grouping endpoint", "grouping peer");
+        edit(&mut document, "uses endpoint;", "uses peer;");
+    }
+
+    #[test]
+    fn edits_which_break_the_source_keep_the_module_until_it_is_repaired() {
+        let mut document = Document::parse(MODULE).expect("the module to parse");
+        let body = format!("{:?}", document.module());
+
+        let start = document.source().find("leaf hostname {").expect("the leaf");
+        let error = document.edit(start..start + "leaf hostname {".len(), "leaf hostname");
+        assert!(error.is_err());
+        assert_eq!(format!("{:?}", document.module()), body);
+
+        let start = document.source().find("type string;").expect("the type");
+        assert!(document.edit(start..start, "mandatory true;").is_err());
+
+        let start = document.source().find("leaf hostname").expect("the leaf");
+        document
+            .edit(start..start + "leaf hostname".len(), "leaf hostname {")
+            .expect("the repaired source to parse");
+        assert_parsed(&document);
+
+        // A line comment left open at the end of an edit comments out the start of the next statement.
+        edit(
+            &mut document,
+            "  container system {",
+            "  // container\n  container system {",
+        );
+        let start = document.source().find("// container\n").expect("the comment") + "// container".len();
+        assert!(document.edit(start..start + 1, " ").is_err());
+        edit(&mut document, "// container   container", "// container\n  container");
+    }
+
+    #[test]
+    fn edits_of_the_module_header_reparse_the_module() {
+        let mut document = Document::parse(MODULE).expect("the module to parse");
+
+        edit(&mut document, "module example", "module sample");
+        edit(&mut document, "namespace \"urn:example\"", "namespace \"urn:sample\"");
+        edit(&mut document, "prefix ex;\n\n  import", "prefix sa;\n\n  import");
+        edit(&mut document, "\"/ex:system\"", "\"/sa:system\"");
+        edit(&mut document, "yang-version 1.1;\n", "");
+
+        let YangModule::Module(module) = document.module() else {
+            panic!("expected a module");
+        };
+        assert_eq!(module.name, "sample");
+        assert_eq!(module.namespace, "urn:sample");
+        assert_eq!(module.yang_version, None);
+    }
+
+    #[test]
+    fn edits_of_belongs_to_reparse_the_submodule() {
+        let mut document = Document::parse(SUBMODULE).expect("the submodule to parse");
+
+        edit(&mut document, "prefix ex;", "prefix types;");
+        edit(&mut document, "type ex:name;", "type types:name;");
+        edit(&mut document, "belongs-to example", "belongs-to sample");
+        edit(
+            &mut document,
+            "  leaf owner",
+            "  leaf manager {\n    type string;\n  }\n\n  leaf owner",
+        );
+
+        let YangModule::Submodule(submodule) = document.module() else {
+            panic!("expected a submodule");
+        };
+        assert_eq!(submodule.belongs_to.module, "sample");
+        assert_eq!(submodule.belongs_to.prefix, "types");
+    }
+
+    #[test]
+    fn reference_nodes_defined_twice_are_kept_like_a_parse_keeps_them() {
+        let mut document = Document::parse(MODULE).expect("the module to parse");
+
+        edit(
+            &mut document,
+            "  container system {",
+            "  typedef port {\n    type uint32;\n  }\n\n  container system {",
+        );
+        edit(&mut document, "type uint32;", "type int32;");
+        edit(&mut document, "  typedef port {\n    type uint16;\n  }\n", "");
+    }
+}
@@ -1,16 +1,37 @@
-use std::io;
+use std::{
+    fmt, io,
+    path::{Path, PathBuf},
+};
 
+use pest::error::LineColLocation;
 use thiserror::Error;
 
-use crate::parser_internal::Rule;
+use crate::{parser_internal::Rule, Symbol};
 
+/// An error of parsing or loading a YANG module, with the kind of error, and the module, statement path and location in
+/// the source the error is in, as far as they are known where it happened.
+#[derive(Debug)]
+pub struct ParserError {
+    kind: ErrorKind,
+    context: Box<ErrorContext>,
+}
+
+#[derive(Debug, Default)]
+struct ErrorContext {
+    module: Option<Symbol>,
+    path: Option<String>,
+    file: Option<PathBuf>,
+    location: Option<SourceLocation>,
+}
+
+/// The kinds of [ParserError].
 #[derive(Error, Debug)]
-pub enum ParserError {
+pub enum ErrorKind {
     #[error("entrypoint must be a YANG module, not submodule")]
     InvalidParserEntrypoint,
 
-    #[error("invalid YANG file")]
-    ParseError(#[from] pest::error::Error<Rule>),
+    #[error("invalid YANG file: {}", .0.variant.message())]
+    ParseError(#[from] Box<pest::error::Error<Rule>>),
 
     #[error("invalid input file")]
     InvalidFile(#[from] io::Error),
@@ -20,4 +41,487 @@ pub enum ParserError {
 
     #[error("imported file has to be a module, not submodule: {0}")]
     InvalidImport(String),
+
+    #[error("edit range {0:?} is outside of the document or not at character boundaries")]
+    InvalidEdit(std::ops::Range<usize>),
+
+    #[error("invalid compiled module: {0}")]
+    InvalidCompiledModule(String),
+
+    /// The errors found when parsing with [crate::ErrorPolicy::CollectAll], in the order they were found.
+    #[error("{} errors", .0.len())]
+    Multiple(Vec<ParserError>),
+
+    /// A warning which the [crate::SeverityPolicy] promotes to an error.
+    #[error("{}", .0.kind)]
+    Promoted(Warning),
+}
+
+/// A stable code for each kind of error and warning, for handling them programmatically, like in CI checks and
+/// controllers, without matching on their messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    Entrypoint,
+    Syntax,
+    Io,
+    Include,
+    Import,
+    Edit,
+    Compiled,
+    Multiple,
+    UnusedImport,
+    UnresolvedGrouping,
+    UnresolvedLeafref,
+    Deprecated,
+    Obsolete,
+    UnresolvedAugment,
+    UnresolvedDeviation,
+    UnresolvedType,
+    InvalidRestriction,
+    InvalidDefault,
+}
+
+/// Whether a diagnostic is an error, which fails parsing, or a warning, which doesn't.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A position in a YANG file, where lines and columns start at 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SourceLocation {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl ParserError {
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    pub fn into_kind(self) -> ErrorKind {
+        self.kind
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        self.kind.code()
+    }
+
+    /// The errors collected in the error, which is the error itself unless it's [ErrorKind::Multiple].
+    pub fn errors(&self) -> &[ParserError] {
+        match &self.kind {
+            ErrorKind::Multiple(errors) => errors,
+            _ => std::slice::from_ref(self),
+        }
+    }
+
+    pub(crate) fn into_errors(self) -> Vec<ParserError> {
+        match self.kind {
+            ErrorKind::Multiple(errors) => errors,
+            _ => vec![self],
+        }
+    }
+
+    // An error of all the errors, which is the error itself if there's only one.
+    pub(crate) fn collected(errors: impl IntoIterator<Item = ParserError>) -> Self {
+        let mut errors: Vec<ParserError> = errors.into_iter().flat_map(ParserError::into_errors).collect();
+        match errors.len() {
+            1 => errors.remove(0),
+            _ => ErrorKind::Multiple(errors).into(),
+        }
+    }
+
+    /// The name of the module the error is in.
+    pub fn module(&self) -> Option<&str> {
+        self.context.module.as_deref()
+    }
+
+    /// The schema path of the statement the error is in, like "/interfaces/interface".
+    pub fn path(&self) -> Option<&str> {
+        self.context.path.as_deref()
+    }
+
+    /// The file the error is in.
+    pub fn file(&self) -> Option<&Path> {
+        self.context.file.as_deref()
+    }
+
+    /// The position in the file the error is at.
+    pub fn location(&self) -> Option<SourceLocation> {
+        self.context.location
+    }
+
+    /// Set the module the error is in, unless it's already known, as errors are given the context closest to them
+    /// first.
+    pub fn with_module(mut self, module: impl Into<Symbol>) -> Self {
+        self.context.module.get_or_insert_with(|| module.into());
+        self
+    }
+
+    /// Set the schema path of the statement the error is in, unless it's already known.
+    pub fn with_path(mut self, path: impl Into<String>) -> Self {
+        self.context.path.get_or_insert_with(|| path.into());
+        self
+    }
+
+    // Set the file the error is in, unless it's already known. Parse errors name the file as well, which is shown with
+    // the line the error is at.
+    pub(crate) fn in_file(mut self, file: &Path) -> Self {
+        if self.context.file.is_some() {
+            return self;
+        }
+        if let ErrorKind::ParseError(error) = self.kind {
+            self.kind = ErrorKind::ParseError(Box::new(error.with_path(&file.to_string_lossy())));
+        }
+        self.context.file = Some(file.to_path_buf());
+        self
+    }
+}
+
+impl ErrorKind {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ErrorKind::InvalidParserEntrypoint => ErrorCode::Entrypoint,
+            ErrorKind::ParseError(_) => ErrorCode::Syntax,
+            ErrorKind::InvalidFile(_) => ErrorCode::Io,
+            ErrorKind::InvalidInclude(_) => ErrorCode::Include,
+            ErrorKind::InvalidImport(_) => ErrorCode::Import,
+            ErrorKind::InvalidEdit(_) => ErrorCode::Edit,
+            ErrorKind::InvalidCompiledModule(_) => ErrorCode::Compiled,
+            ErrorKind::Multiple(_) => ErrorCode::Multiple,
+            ErrorKind::Promoted(warning) => warning.code(),
+        }
+    }
+}
+
+impl ErrorCode {
+    pub const ALL: &'static [ErrorCode] = &[
+        ErrorCode::Entrypoint,
+        ErrorCode::Syntax,
+        ErrorCode::Io,
+        ErrorCode::Include,
+        ErrorCode::Import,
+        ErrorCode::Edit,
+        ErrorCode::Compiled,
+        ErrorCode::Multiple,
+        ErrorCode::UnusedImport,
+        ErrorCode::UnresolvedGrouping,
+        ErrorCode::UnresolvedLeafref,
+        ErrorCode::Deprecated,
+        ErrorCode::Obsolete,
+        ErrorCode::UnresolvedAugment,
+        ErrorCode::UnresolvedDeviation,
+        ErrorCode::UnresolvedType,
+        ErrorCode::InvalidRestriction,
+        ErrorCode::InvalidDefault,
+    ];
+
+    /// The code with the name, like "yang::unused_import", for codes named in configuration.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|code| code.as_str() == name)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::Entrypoint => "yang::entrypoint",
+            ErrorCode::Syntax => "yang::syntax",
+            ErrorCode::Io => "yang::io",
+            ErrorCode::Include => "yang::include",
+            ErrorCode::Import => "yang::import",
+            ErrorCode::Edit => "yang::edit",
+            ErrorCode::Compiled => "yang::compiled",
+            ErrorCode::Multiple => "yang::multiple",
+            ErrorCode::UnusedImport => "yang::unused_import",
+            ErrorCode::UnresolvedGrouping => "yang::unresolved_grouping",
+            ErrorCode::UnresolvedLeafref => "yang::unresolved_leafref",
+            ErrorCode::Deprecated => "yang::deprecated",
+            ErrorCode::Obsolete => "yang::obsolete",
+            ErrorCode::UnresolvedAugment => "yang::unresolved_augment",
+            ErrorCode::UnresolvedDeviation => "yang::unresolved_deviation",
+            ErrorCode::UnresolvedType => "yang::unresolved_type",
+            ErrorCode::InvalidRestriction => "yang::invalid_restriction",
+            ErrorCode::InvalidDefault => "yang::invalid_default",
+        }
+    }
+
+    /// The code libyang reports the error with, one of its `LY_VECODE` validation codes like "LYVE_SYNTAX_YANG", or
+    /// its `LY_ERR` code like "LY_ESYS" for errors which aren't validation errors. libyang only warns about unused
+    /// imports and definitions whose status the node using them doesn't allow, so these are semantic errors when
+    /// they're promoted.
+    pub fn libyang_code(self) -> &'static str {
+        match self {
+            ErrorCode::Entrypoint => "LY_EINVAL",
+            ErrorCode::Syntax => "LYVE_SYNTAX_YANG",
+            ErrorCode::Io => "LY_ESYS",
+            ErrorCode::Include
+            | ErrorCode::Import
+            | ErrorCode::UnresolvedGrouping
+            | ErrorCode::UnresolvedLeafref
+            | ErrorCode::UnresolvedAugment
+            | ErrorCode::UnresolvedDeviation
+            | ErrorCode::UnresolvedType => "LYVE_REFERENCE",
+            ErrorCode::Edit
+            | ErrorCode::UnusedImport
+            | ErrorCode::Deprecated
+            | ErrorCode::Obsolete
+            | ErrorCode::InvalidRestriction
+            | ErrorCode::InvalidDefault => "LYVE_SEMANTICS",
+            ErrorCode::Compiled => "LY_EINVAL",
+            ErrorCode::Multiple => "LY_EVALID",
+        }
+    }
+}
+
+impl Severity {
+    /// The severity with the name, "error" or "warning".
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "error" => Some(Severity::Error),
+            "warning" => Some(Severity::Warning),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+// The message of the error is followed by where it is, like "invalid YANG file (module a, at /b/c, a.yang:3:5)".
+impl fmt::Display for ParserError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind)?;
+
+        let context = &self.context;
+        let mut parts = Vec::new();
+        if let Some(module) = &context.module {
+            parts.push(format!("module {}", module));
+        }
+        if let Some(path) = &context.path {
+            parts.push(format!("at {}", path));
+        }
+        match (&context.file, context.location) {
+            (Some(file), Some(location)) => {
+                parts.push(format!("{}:{}:{}", file.display(), location.line, location.column))
+            }
+            (Some(file), None) => parts.push(file.display().to_string()),
+            (None, Some(location)) => parts.push(format!("line {}, column {}", location.line, location.column)),
+            (None, None) => {}
+        }
+        if !parts.is_empty() {
+            write!(f, " ({})", parts.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParserError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        std::error::Error::source(&self.kind)
+    }
+}
+
+/// A condition found while parsing which doesn't stop the module from loading, but is likely a mistake.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    pub kind: WarningKind,
+    /// The name of the module the warning is in.
+    pub module: Symbol,
+    /// The statement the warning is about, like `uses endpoint` or `augment "/if:interfaces/if:interface"`.
+    pub statement: Option<String>,
+    /// The schema path of the statement the warning is about, like "/interfaces/interface", which for the nodes of an
+    /// augment is below its target.
+    pub path: Option<String>,
+}
+
+/// The kinds of [Warning].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum WarningKind {
+    #[error("module {module} is imported with prefix {prefix}, which is never used")]
+    UnusedImport { module: Symbol, prefix: Symbol },
+
+    #[error("grouping {0} is not found, so the uses statement is kept")]
+    UnresolvedGrouping(String),
+
+    #[error("leafref path {0} doesn't refer to a leaf of the module")]
+    UnresolvedLeafref(String),
+
+    #[error("deprecated grouping {0} is used")]
+    DeprecatedGrouping(String),
+
+    #[error("obsolete grouping {0} is used")]
+    ObsoleteGrouping(String),
+
+    #[error("augment target {0} is not found, so its nodes aren't added")]
+    UnresolvedAugment(String),
+
+    #[error("deviation target {0} is not found, so the deviation isn't applied")]
+    UnresolvedDeviation(String),
+
+    /// An error which the [crate::SeverityPolicy] demotes to a warning, with the code and message of the error.
+    #[error("{message}")]
+    Demoted { code: ErrorCode, message: String },
+}
+
+impl Warning {
+    pub fn code(&self) -> ErrorCode {
+        match self.kind {
+            WarningKind::UnusedImport { .. } => ErrorCode::UnusedImport,
+            WarningKind::UnresolvedGrouping(_) => ErrorCode::UnresolvedGrouping,
+            WarningKind::UnresolvedLeafref(_) => ErrorCode::UnresolvedLeafref,
+            WarningKind::DeprecatedGrouping(_) => ErrorCode::Deprecated,
+            WarningKind::ObsoleteGrouping(_) => ErrorCode::Obsolete,
+            WarningKind::UnresolvedAugment(_) => ErrorCode::UnresolvedAugment,
+            WarningKind::UnresolvedDeviation(_) => ErrorCode::UnresolvedDeviation,
+            WarningKind::Demoted { code, .. } => code,
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (module {}", self.kind, self.module)?;
+        if let Some(statement) = &self.statement {
+            write!(f, ", in {}", statement)?;
+        }
+        if let Some(path) = &self.path {
+            write!(f, ", at {}", path)?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// Receives the warnings found while parsing, set with [crate::ParseOptions::warnings], so applications can log,
+/// collect or escalate them. Closures taking a [Warning] are sinks, and so is a `Mutex<Vec<Warning>>`, which collects
+/// them.
+pub trait DiagnosticSink: Send + Sync {
+    fn warning(&self, warning: Warning);
+}
+
+impl<F: Fn(Warning) + Send + Sync> DiagnosticSink for F {
+    fn warning(&self, warning: Warning) {
+        self(warning)
+    }
+}
+
+impl DiagnosticSink for std::sync::Mutex<Vec<Warning>> {
+    fn warning(&self, warning: Warning) {
+        self.lock().expect("the warnings to not be poisoned").push(warning)
+    }
+}
+
+impl fmt::Debug for dyn DiagnosticSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("DiagnosticSink")
+    }
+}
+
+impl From<ErrorKind> for ParserError {
+    fn from(kind: ErrorKind) -> Self {
+        let location = match &kind {
+            ErrorKind::ParseError(error) => match error.line_col {
+                LineColLocation::Pos((line, column)) | LineColLocation::Span((line, column), _) => {
+                    Some(SourceLocation { line, column })
+                }
+            },
+            _ => None,
+        };
+
+        Self {
+            kind,
+            context: Box::new(ErrorContext {
+                location,
+                ..Default::default()
+            }),
+        }
+    }
+}
+
+impl From<Box<pest::error::Error<Rule>>> for ParserError {
+    fn from(error: Box<pest::error::Error<Rule>>) -> Self {
+        ErrorKind::ParseError(error).into()
+    }
+}
+
+impl From<pest::error::Error<Rule>> for ParserError {
+    fn from(error: pest::error::Error<Rule>) -> Self {
+        Box::new(error).into()
+    }
+}
+
+impl From<io::Error> for ParserError {
+    fn from(error: io::Error) -> Self {
+        ErrorKind::InvalidFile(error).into()
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ValueError {
+    #[error("invalid {type_name} value: {value}")]
+    InvalidValue { type_name: String, value: String },
+
+    #[error("{type_name} value out of range: {value}")]
+    OutOfRange { type_name: String, value: String },
+
+    #[error("decimal64 type is missing fraction-digits")]
+    MissingFractionDigits,
+
+    #[error("unknown enum value: {0}")]
+    UnknownEnum(String),
+
+    #[error("unknown bit: {0}")]
+    UnknownBit(String),
+
+    #[error("unknown identity: {0}")]
+    UnknownIdentity(String),
+
+    #[error("identity {value} is not derived from {base}")]
+    WrongIdentityBase { value: String, base: String },
+
+    #[error("value does not match any member type of the union: {0}")]
+    NoMatchingUnionMember(String),
+
+    #[error("value {value} is outside of the allowed range {range}")]
+    RangeViolation { value: String, range: String },
+
+    #[error("value {value} does not satisfy the length restriction {length}")]
+    LengthViolation { value: String, length: String },
+
+    #[error("value {value} does not match the pattern {pattern}")]
+    PatternMismatch { value: String, pattern: String },
+
+    #[error("invalid restriction: {0}")]
+    InvalidRestriction(String),
+
+    /// A valid XML Schema pattern using a feature which can't be checked, like a Unicode block without a known range.
+    #[error("unsupported pattern {pattern}: {reason}")]
+    UnsupportedPattern { pattern: String, reason: String },
+
+    #[error("type has to be resolved to a built-in type before parsing values: {0}")]
+    UnresolvedType(String),
+}
+
+impl ValueError {
+    /// The code libyang reports the error with: "LYVE_DATA" for values the type doesn't allow, or "LY_EINVAL" for
+    /// types whose values can't be checked.
+    pub fn libyang_code(&self) -> &'static str {
+        match self {
+            ValueError::MissingFractionDigits
+            | ValueError::InvalidRestriction(_)
+            | ValueError::UnsupportedPattern { .. }
+            | ValueError::UnresolvedType(_) => "LY_EINVAL",
+            _ => "LYVE_DATA",
+        }
+    }
 }
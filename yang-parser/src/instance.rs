@@ -0,0 +1,710 @@
+//! Checking instance data encoded as JSON like RFC 7951 against the schema of a module: members which aren't nodes of
+//! the module, values which their leaf's type doesn't allow or which aren't encoded like the type requires, data of the
+//! wrong shape, missing mandatory nodes and choices, list entries without their keys or with the keys of another
+//! entry, lists and leaf-lists with fewer or more entries than their min-elements and max-elements, and list entries
+//! violating a `unique` statement. Nodes with a `when` or `if-feature` statement may not exist, so they aren't
+//! required, and `must` and `when` expressions aren't evaluated.
+
+use std::collections::HashMap;
+
+use serde_json::{Map, Value as Json};
+
+use crate::{
+    error::ValueError,
+    model::{MaxElements, Module, TypeBody, TypeInfo},
+    schema_tree::{NodeId, NodeKind, SchemaTree},
+    types::TypeResolver,
+    value::{self, Value},
+};
+
+/// An error of instance data, at the path of the data it's about, like "/system/interface[1]/mtu", where members are
+/// named like in the data and list entries and leaf-list values are indexed by their position, starting at 1 like the
+/// positions of XPath and the errors of the generated `validate` methods.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("{path}: {kind}")]
+pub struct DataError {
+    pub path: String,
+    pub kind: DataErrorKind,
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum DataErrorKind {
+    #[error("{0} is not a node of the module")]
+    UnknownNode(String),
+
+    #[error("{keyword} {name} is not a data node")]
+    NotDataNode { keyword: &'static str, name: String },
+
+    #[error("expected {0}")]
+    UnexpectedData(&'static str),
+
+    #[error("missing key {0}")]
+    MissingKey(String),
+
+    /// A mandatory node or choice, or a list or leaf-list with min-elements, which has no data.
+    #[error("missing {keyword} {name}")]
+    MissingNode { keyword: &'static str, name: String },
+
+    /// A list entry with the same keys as an earlier entry, with the position of that entry.
+    #[error("same keys as entry {0}")]
+    DuplicateKey(usize),
+
+    /// A list entry with the same values for the descendants of a `unique` statement as an earlier entry, with the
+    /// argument of the statement and the position of that entry.
+    #[error("same values for unique \"{unique}\" as entry {entry}")]
+    NotUnique { unique: String, entry: usize },
+
+    #[error("fewer than min-elements {0} entries")]
+    TooFewElements(i64),
+
+    #[error("more than max-elements {0} entries")]
+    TooManyElements(i64),
+
+    #[error(transparent)]
+    InvalidValue(#[from] ValueError),
+}
+
+/// The errors of the data of the module with the schema tree, whose leaf types are resolved with the resolver. The
+/// members of the top-level object are the top-level nodes, and names may be qualified with their module or not. The
+/// values of leaves whose type can't be resolved, like typedefs of modules which failed to load, aren't checked.
+pub fn validate(tree: &SchemaTree, types: &TypeResolver, data: &Json) -> Vec<DataError> {
+    let mut validator = Validator {
+        tree,
+        types,
+        errors: Vec::new(),
+    };
+    validator.members(None, "", "", data);
+    validator.errors
+}
+
+/// The value of a leaf or leaf-list of the module in JSON data, parsed and checked against its type, where identityrefs
+/// are resolved against the identities of the module and its imports.
+pub fn leaf_value(module: &Module, type_info: &TypeInfo, value: &Json) -> Result<Value, DataErrorKind> {
+    let lexical = lexical(type_info, value).ok_or(DataErrorKind::UnexpectedData(expected(type_info)))?;
+    let value = value::parse_value_in(module, type_info, &lexical)?;
+    value::validate_value(type_info, &value)?;
+    Ok(value)
+}
+
+/// The lexical representation of the JSON value of a leaf of the type, encoded like RFC 7951 requires: integers of up to
+/// 32 bits are numbers, 64-bit integers and decimals are strings, so they keep their precision, booleans are `true`
+/// and `false`, the value of an empty leaf is `[null]`, and values of the other types are strings. Values of unions are
+/// encoded like one of their member types. Values encoded like another type are `None`, except for the values of types
+/// which aren't resolved to a built-in type, which may be encoded like any type.
+pub fn lexical(type_info: &TypeInfo, value: &Json) -> Option<String> {
+    let name = type_info.name.as_str();
+    match value {
+        Json::Number(number) if NUMBERS.contains(&name) => Some(number.to_string()),
+        Json::Bool(boolean) if name == "boolean" => Some(boolean.to_string()),
+        Json::Array(values) if name == "empty" && is_empty_value(values) => Some(String::new()),
+        Json::String(string) if STRINGS.contains(&name) => Some(string.clone()),
+        _ if name == "union" => match type_info.type_body.as_deref() {
+            Some(TypeBody::Union { types }) => types.iter().find_map(|member| lexical(member, value)),
+            _ => None,
+        },
+        _ if NUMBERS.contains(&name) || STRINGS.contains(&name) || ["boolean", "empty"].contains(&name) => None,
+        Json::String(string) => Some(string.clone()),
+        Json::Number(number) => Some(number.to_string()),
+        Json::Bool(boolean) => Some(boolean.to_string()),
+        Json::Array(values) if is_empty_value(values) => Some(String::new()),
+        _ => None,
+    }
+}
+
+// The built-in types whose values are JSON numbers.
+const NUMBERS: &[&str] = &["int8", "int16", "int32", "uint8", "uint16", "uint32"];
+
+// The built-in types whose values are JSON strings.
+const STRINGS: &[&str] = &[
+    "int64",
+    "uint64",
+    "decimal64",
+    "string",
+    "enumeration",
+    "bits",
+    "binary",
+    "identityref",
+    "instance-identifier",
+];
+
+// The value of an empty leaf, which is [null].
+fn is_empty_value(values: &[Json]) -> bool {
+    matches!(values, [Json::Null])
+}
+
+// The JSON values of the type, for the errors of values encoded like another type.
+fn expected(type_info: &TypeInfo) -> &'static str {
+    match type_info.name.as_str() {
+        name if NUMBERS.contains(&name) => "a number",
+        name if STRINGS.contains(&name) => "a string",
+        "boolean" => "true or false",
+        "empty" => "[null]",
+        "union" => "a value of a member type of the union",
+        _ => "a string, number, boolean or [null]",
+    }
+}
+
+struct Validator<'a, 'm> {
+    tree: &'a SchemaTree<'m>,
+    types: &'a TypeResolver<'m>,
+    errors: Vec<DataError>,
+}
+
+impl Validator<'_, '_> {
+    fn error(&mut self, data_path: &str, kind: DataErrorKind) {
+        let path = if data_path.is_empty() { "/" } else { data_path };
+        self.errors.push(DataError {
+            path: path.to_string(),
+            kind,
+        });
+    }
+
+    // The members of an object, which are children of the node at the schema path, or top-level nodes at the root.
+    fn members(&mut self, parent: Option<NodeId>, schema_path: &str, data_path: &str, data: &Json) {
+        let Json::Object(members) = data else {
+            self.error(data_path, DataErrorKind::UnexpectedData("an object"));
+            return;
+        };
+
+        for (name, value) in members {
+            let member_path = format!("{}/{}", data_path, name);
+            let path = format!("{}/{}", schema_path, local_name(name));
+            match self.tree.find(&path) {
+                Some(id) => self.node(id, &path, &member_path, value),
+                None => self.error(&member_path, DataErrorKind::UnknownNode(name.clone())),
+            }
+        }
+
+        let children: Vec<NodeId> = match parent {
+            Some(parent) => self.tree.children(parent).collect(),
+            None => self.tree.roots().collect(),
+        };
+        self.mandatory(&children, members, data_path);
+    }
+
+    fn node(&mut self, id: NodeId, schema_path: &str, data_path: &str, value: &Json) {
+        match self.tree.kind(id) {
+            NodeKind::Container(_) | NodeKind::Input(_) | NodeKind::Output(_) => {
+                self.members(Some(id), schema_path, data_path, value)
+            }
+            NodeKind::List(list) => {
+                let Json::Array(entries) = value else {
+                    self.error(data_path, DataErrorKind::UnexpectedData("an array of list entries"));
+                    return;
+                };
+                self.count(data_path, entries.len(), list.min_elements, list.max_elements.as_ref());
+
+                let keys: Vec<&str> = list.key.iter().flat_map(|key| key.split_whitespace()).collect();
+                let mut entries_by_key = HashMap::new();
+                for (index, entry) in entries.iter().enumerate() {
+                    let entry_path = format!("{}[{}]", data_path, index + 1);
+                    if let Json::Object(members) = entry {
+                        let mut key_values = Vec::new();
+                        for key in &keys {
+                            match descendant(members, key) {
+                                Some(value) => key_values.push(value),
+                                None => self.error(&entry_path, DataErrorKind::MissingKey(key.to_string())),
+                            }
+                        }
+                        if key_values.len() == keys.len() && !keys.is_empty() {
+                            if let Some(first) = entries_by_key.insert(key_values, index + 1) {
+                                self.error(&entry_path, DataErrorKind::DuplicateKey(first));
+                            }
+                        }
+                    }
+                    self.members(Some(id), schema_path, &entry_path, entry);
+                }
+
+                for unique in &list.unique {
+                    self.unique(unique, entries, data_path);
+                }
+            }
+            NodeKind::Leaf(_) => self.leaf(schema_path, data_path, value),
+            NodeKind::LeafList(leaf_list) => {
+                let Json::Array(values) = value else {
+                    self.error(data_path, DataErrorKind::UnexpectedData("an array of values"));
+                    return;
+                };
+                self.count(
+                    data_path,
+                    values.len(),
+                    leaf_list.min_elements,
+                    leaf_list.max_elements.as_ref(),
+                );
+                for (index, value) in values.iter().enumerate() {
+                    self.leaf(schema_path, &format!("{}[{}]", data_path, index + 1), value);
+                }
+            }
+            NodeKind::Anydata(_) | NodeKind::Anyxml(_) => {}
+            kind => self.error(
+                data_path,
+                DataErrorKind::NotDataNode {
+                    keyword: kind.keyword(),
+                    name: kind.name().to_string(),
+                },
+            ),
+        }
+    }
+
+    fn leaf(&mut self, schema_path: &str, data_path: &str, value: &Json) {
+        let Some(type_info) = self.types.leaf_type(schema_path) else {
+            return;
+        };
+        match leaf_value(self.types.module(), &type_info, value) {
+            Ok(_) | Err(DataErrorKind::InvalidValue(ValueError::UnresolvedType(_))) => {}
+            Err(error) => self.error(data_path, error),
+        }
+    }
+
+    // Check the number of entries of a list or leaf-list against its min-elements and max-elements.
+    fn count(&mut self, data_path: &str, count: usize, min: Option<i64>, max: Option<&MaxElements>) {
+        if let Some(min) = min.filter(|min| (count as i64) < *min) {
+            self.error(data_path, DataErrorKind::TooFewElements(min));
+        }
+        if let Some(&MaxElements::Value(max)) = max {
+            if count as i64 > max {
+                self.error(data_path, DataErrorKind::TooManyElements(max));
+            }
+        }
+    }
+
+    // Entries with a value for every descendant of the unique statement have to differ in at least one of them.
+    fn unique(&mut self, unique: &str, entries: &[Json], data_path: &str) {
+        let mut entries_by_values = HashMap::new();
+        for (index, entry) in entries.iter().enumerate() {
+            let Json::Object(members) = entry else {
+                continue;
+            };
+            let values: Option<Vec<&Json>> = unique
+                .split_whitespace()
+                .map(|descendant_path| descendant(members, descendant_path))
+                .collect();
+            let Some(values) = values else {
+                continue;
+            };
+            if let Some(first) = entries_by_values.insert(values, index + 1) {
+                let entry_path = format!("{}[{}]", data_path, index + 1);
+                let unique = unique.to_string();
+                self.error(&entry_path, DataErrorKind::NotUnique { unique, entry: first });
+            }
+        }
+    }
+
+    // The mandatory nodes among the nodes, which are the children of an object with the members, which don't have
+    // data. The mandatory nodes of non-presence containers without data are missing as well, and choices are checked
+    // for being mandatory if none of their cases has data, and for the mandatory nodes of the case with data otherwise.
+    fn mandatory(&mut self, ids: &[NodeId], members: &Map<String, Json>, data_path: &str) {
+        for &id in ids {
+            let kind = self.tree.kind(id);
+            if is_conditional(kind) {
+                continue;
+            }
+            let has_data = self.has_data(id, members);
+            let missing = |keyword: &'static str| DataErrorKind::MissingNode {
+                keyword,
+                name: kind.name().to_string(),
+            };
+            match kind {
+                NodeKind::Leaf(leaf) if leaf.mandatory == Some(true) && !has_data => {
+                    self.error(data_path, missing("leaf"))
+                }
+                NodeKind::Anydata(anydata) if anydata.mandatory == Some(true) && !has_data => {
+                    self.error(data_path, missing("anydata"))
+                }
+                NodeKind::Anyxml(anyxml) if anyxml.mandatory == Some(true) && !has_data => {
+                    self.error(data_path, missing("anyxml"))
+                }
+                NodeKind::List(list) if list.min_elements.is_some_and(|min| min > 0) && !has_data => {
+                    self.error(data_path, missing("list"))
+                }
+                NodeKind::LeafList(leaf_list) if leaf_list.min_elements.is_some_and(|min| min > 0) && !has_data => {
+                    self.error(data_path, missing("leaf-list"))
+                }
+                NodeKind::Container(container) if container.meta.presence.is_none() && !has_data => {
+                    let children: Vec<NodeId> = self.tree.children(id).collect();
+                    let container_path = format!("{}/{}", data_path, kind.name());
+                    self.mandatory(&children, &Map::new(), &container_path);
+                }
+                NodeKind::Choice(choice) => {
+                    let case = self.tree.children(id).find(|case| self.has_data(*case, members));
+                    match case {
+                        Some(case) => {
+                            let nodes: Vec<NodeId> = match self.tree.kind(case) {
+                                NodeKind::Case(_) => self.tree.children(case).collect(),
+                                _ => vec![case],
+                            };
+                            self.mandatory(&nodes, members, data_path);
+                        }
+                        None if choice.mandatory == Some(true) => self.error(data_path, missing("choice")),
+                        None => {}
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // Whether the object with the members has data of the node, or of a node of the choice or case.
+    fn has_data(&self, id: NodeId, members: &Map<String, Json>) -> bool {
+        match self.tree.kind(id) {
+            NodeKind::Choice(_) | NodeKind::Case(_) => {
+                self.tree.children(id).any(|child| self.has_data(child, members))
+            }
+            kind => members.keys().any(|name| local_name(name) == kind.name()),
+        }
+    }
+}
+
+// Whether the node may not exist, because of its `when` or `if-feature` statements, which aren't evaluated.
+fn is_conditional(kind: NodeKind) -> bool {
+    let (when, if_features) = match kind {
+        NodeKind::Container(node) => (&node.when, &node.if_features),
+        NodeKind::Leaf(node) => (&node.when, &node.if_features),
+        NodeKind::LeafList(node) => (&node.when, &node.if_features),
+        NodeKind::List(node) => (&node.when, &node.if_features),
+        NodeKind::Choice(node) => (&node.when, &node.if_features),
+        NodeKind::Case(node) => (&node.when, &node.if_features),
+        NodeKind::Anydata(node) => (&node.when, &node.if_features),
+        NodeKind::Anyxml(node) => (&node.when, &node.if_features),
+        _ => return false,
+    };
+    when.is_some() || !if_features.is_empty()
+}
+
+// The value of the descendant of the entry at the path, which is a descendant schema node identifier like "ip" or
+// "address/ip", where prefixes are ignored.
+fn descendant<'d>(members: &'d Map<String, Json>, path: &str) -> Option<&'d Json> {
+    let mut members = members;
+    let mut steps = path.split('/').filter(|step| !step.is_empty()).peekable();
+    while let Some(step) = steps.next() {
+        let name = local_name(step);
+        let (_, value) = members.iter().find(|(member, _)| local_name(member) == name)?;
+        if steps.peek().is_none() {
+            return Some(value);
+        }
+        let Json::Object(children) = value else {
+            return None;
+        };
+        members = children;
+    }
+    None
+}
+
+fn local_name(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{model::YangModule, Document};
+
+    const MODULE: &str = r#"
+        module example-system {
+            namespace "urn:example:system";
+            prefix sys;
+
+            container system {
+                leaf hostname {
+                    type string {
+                        length "2..63";
+                    }
+                }
+                list interface {
+                    key "name";
+                    leaf name {
+                        type string;
+                    }
+                    leaf mtu {
+                        type uint16 {
+                            range "68..9000";
+                        }
+                    }
+                    leaf-list address {
+                        type string {
+                            pattern "[0-9.]+";
+                        }
+                    }
+                    leaf enabled {
+                        type empty;
+                    }
+                }
+            }
+            rpc reboot;
+        }
+    "#;
+
+    const NETWORK_MODULE: &str = r#"
+        module example-network {
+            namespace "urn:example:network";
+            prefix net;
+
+            identity protocol;
+            identity tcp {
+                base protocol;
+            }
+            identity transport;
+
+            container network {
+                leaf name {
+                    type string;
+                    mandatory true;
+                }
+                list server {
+                    key "name";
+                    unique "ip port";
+                    min-elements 1;
+                    max-elements 2;
+                    leaf name {
+                        type string;
+                    }
+                    leaf ip {
+                        type string;
+                    }
+                    leaf port {
+                        type uint16;
+                    }
+                    leaf protocol {
+                        type identityref {
+                            base protocol;
+                        }
+                    }
+                }
+                choice transport {
+                    mandatory true;
+                    case tcp {
+                        leaf tcp-port {
+                            type uint16;
+                        }
+                        leaf keepalive {
+                            type boolean;
+                            mandatory true;
+                        }
+                    }
+                    leaf udp-port {
+                        type uint16;
+                    }
+                }
+                container limits {
+                    leaf max-sessions {
+                        type uint32;
+                        mandatory true;
+                    }
+                }
+                container tls {
+                    presence "TLS is enabled";
+                    leaf certificate {
+                        type string;
+                        mandatory true;
+                    }
+                }
+                leaf debug {
+                    if-feature debugging;
+                    type boolean;
+                    mandatory true;
+                }
+                leaf session-id {
+                    type int64;
+                }
+                leaf load {
+                    type decimal64 {
+                        fraction-digits 2;
+                    }
+                }
+            }
+        }
+    "#;
+
+    fn errors(data: Json) -> Vec<String> {
+        errors_in(MODULE, data)
+    }
+
+    // The messages of the errors, sorted, as the members of objects are in order only with the preserve_order feature
+    // of serde_json.
+    fn errors_in(source: &str, data: Json) -> Vec<String> {
+        let document = Document::parse(source).expect("the module to parse");
+        let YangModule::Module(module) = document.module() else {
+            panic!("expected a module");
+        };
+        let tree = SchemaTree::new(module);
+        let types = TypeResolver::new(module);
+        let mut errors: Vec<String> = validate(&tree, &types, &data)
+            .into_iter()
+            .map(|error| error.to_string())
+            .collect();
+        errors.sort();
+        errors
+    }
+
+    #[test]
+    fn valid_data_has_no_errors() {
+        let data = json!({
+            "example-system:system": {
+                "hostname": "router",
+                "interface": [
+                    {"name": "eth0", "mtu": 1500, "address": ["192.0.2.1"], "enabled": [null]},
+                    {"name": "eth1", "mtu": 9000}
+                ]
+            }
+        });
+        assert_eq!(errors(data), Vec::<String>::new());
+    }
+
+    #[test]
+    fn errors_are_at_the_path_of_their_data() {
+        let data = json!({
+            "system": {
+                "hostname": "r",
+                "interface": [
+                    {"name": "eth0", "mtu": 67, "address": ["192.0.2.1", "fe80::1"]},
+                    {"mtu": 1500, "speed": 10}
+                ]
+            },
+            "reboot": {},
+            "location": "lab"
+        });
+        assert_eq!(
+            errors(data),
+            [
+                "/location: location is not a node of the module",
+                "/reboot: rpc reboot is not a data node",
+                "/system/hostname: value r does not satisfy the length restriction 2..63",
+                "/system/interface[1]/address[2]: value fe80::1 does not match the pattern [0-9.]+",
+                "/system/interface[1]/mtu: value 67 is outside of the allowed range 68..9000",
+                "/system/interface[2]/speed: speed is not a node of the module",
+                "/system/interface[2]: missing key name",
+            ]
+        );
+    }
+
+    #[test]
+    fn data_of_the_wrong_shape_is_an_error() {
+        let data = json!({
+            "system": {
+                "hostname": {"name": "router"},
+                "interface": {"name": "eth0"}
+            }
+        });
+        assert_eq!(
+            errors(data),
+            [
+                "/system/hostname: expected a string",
+                "/system/interface: expected an array of list entries",
+            ]
+        );
+        assert_eq!(errors(json!([])), ["/: expected an object"]);
+    }
+
+    #[test]
+    fn values_are_encoded_like_their_type() {
+        let data = json!({
+            "network": {
+                "name": true,
+                "server": [{"name": "a", "port": "22"}],
+                "udp-port": 53.5,
+                "session-id": 5,
+                "load": 1.5,
+                "limits": {"max-sessions": "10"}
+            }
+        });
+        assert_eq!(
+            errors_in(NETWORK_MODULE, data),
+            [
+                "/network/limits/max-sessions: expected a number",
+                "/network/load: expected a string",
+                "/network/name: expected a string",
+                "/network/server[1]/port: expected a number",
+                "/network/session-id: expected a string",
+                "/network/udp-port: invalid uint16 value: 53.5",
+            ]
+        );
+    }
+
+    #[test]
+    fn mandatory_nodes_and_choices_need_data() {
+        let expected = [
+            "/network/limits: missing leaf max-sessions",
+            "/network: missing choice transport",
+            "/network: missing leaf name",
+            "/network: missing list server",
+        ];
+        assert_eq!(errors_in(NETWORK_MODULE, json!({"network": {}})), expected);
+        // The non-presence container exists without data as well.
+        assert_eq!(errors_in(NETWORK_MODULE, json!({})), expected);
+
+        // The mandatory nodes of the case with data, and of the presence container with data, are missing.
+        let data = json!({
+            "network": {
+                "name": "lab",
+                "server": [{"name": "a"}],
+                "tcp-port": 22,
+                "limits": {"max-sessions": 10},
+                "tls": {}
+            }
+        });
+        assert_eq!(
+            errors_in(NETWORK_MODULE, data),
+            ["/network/tls: missing leaf certificate", "/network: missing leaf keepalive"]
+        );
+    }
+
+    #[test]
+    fn list_entries_have_distinct_keys_and_unique_values() {
+        let data = json!({
+            "network": {
+                "name": "lab",
+                "server": [
+                    {"name": "a", "ip": "192.0.2.1", "port": 22},
+                    {"name": "a", "ip": "192.0.2.2", "port": 22},
+                    {"name": "c", "ip": "192.0.2.1", "port": 22},
+                    {"name": "d", "ip": "192.0.2.1"}
+                ],
+                "udp-port": 53,
+                "limits": {"max-sessions": 10}
+            }
+        });
+        assert_eq!(
+            errors_in(NETWORK_MODULE, data),
+            [
+                "/network/server: more than max-elements 2 entries",
+                "/network/server[2]: same keys as entry 1",
+                "/network/server[3]: same values for unique \"ip port\" as entry 1",
+            ]
+        );
+
+        let data = json!({
+            "network": {"name": "lab", "server": [], "udp-port": 53, "limits": {"max-sessions": 10}}
+        });
+        assert_eq!(
+            errors_in(NETWORK_MODULE, data),
+            ["/network/server: fewer than min-elements 1 entries"]
+        );
+    }
+
+    #[test]
+    fn identityrefs_are_derived_from_their_bases() {
+        let data = json!({
+            "network": {
+                "name": "lab",
+                "server": [
+                    {"name": "a", "protocol": "tcp"},
+                    {"name": "b", "protocol": "example-network:tcp"},
+                    {"name": "c", "protocol": "transport"},
+                    {"name": "d", "protocol": "udp"}
+                ],
+                "udp-port": 53,
+                "limits": {"max-sessions": 10}
+            }
+        });
+        assert_eq!(
+            errors_in(NETWORK_MODULE, data),
+            [
+                "/network/server: more than max-elements 2 entries",
+                "/network/server[3]/protocol: identity transport is not derived from protocol",
+                "/network/server[4]/protocol: unknown identity: udp",
+            ]
+        );
+    }
+}
@@ -0,0 +1,483 @@
+use std::sync::Arc;
+
+use crate::{
+    error::{Warning, WarningKind},
+    model::*,
+    schema_tree::{NodeKind, SchemaTree},
+};
+
+// The value space of a leafref is the value space of the leaf or leaf-list its path refers to. Paths are absolute like
+// "/if:interfaces/if:interface/if:name", or relative to the leafref's leaf like "../name". Both are followed in the
+// schema tree of the module, after the groupings are expanded and the augments and deviations applied, with the
+// predicates of the path ignored, as they only select instances. Choices and cases aren't part of paths.
+
+// Leafrefs referring to leafrefs are followed to the end of the chain, up to a limit in case the chain is a cycle.
+const MAX_DEPTH: usize = 16;
+
+/// Resolve the leafrefs of the leaves and leaf-lists of the module to the types of the nodes their paths refer to,
+/// which are set as the target of the leafref types. Paths to nodes outside the schema tree of the module, and paths
+/// using functions like deref(), are left unresolved.
+///
+/// Containers and lists used from a grouping are the same wherever the grouping is used, unless a relative path in
+/// them refers to a node outside them, so their grouping is cleared then.
+pub fn resolve_leafrefs(module: &mut Module) {
+    let body = module.body.clone();
+    let mut location = Vec::new();
+    for node in &mut module.body {
+        match node {
+            SchemaNode::DataDef(data_def) => {
+                resolve_data_def(&body, &mut location, data_def);
+            }
+            SchemaNode::Rpc(rpc) => {
+                location.push(rpc.name.clone());
+                resolve_operation(&body, &mut location, &mut rpc.input, &mut rpc.output);
+                location.pop();
+            }
+            SchemaNode::Notification(notification) => {
+                location.push(notification.name.clone());
+                resolve_data_defs(&body, &mut location, &mut notification.data_defs);
+                location.pop();
+            }
+        }
+    }
+}
+
+/// The type of the leaf or leaf-list at the path, like "/interfaces/interface/name", with its leafrefs resolved, for
+/// resolving the types of modules parsed with lazy leafrefs on demand.
+pub fn leaf_type(body: &[SchemaNode], path: &str) -> Option<TypeInfo> {
+    let steps = path_steps(&[], path)?;
+    let node = steps
+        .iter()
+        .try_fold(PathNode::Body(body), |node, step| node.child(step))?;
+    let PathNode::Leaf(type_info) = node else {
+        return None;
+    };
+
+    let mut type_info = type_info.clone();
+    resolve_type(body, &steps, &mut type_info, 0);
+    Some(type_info)
+}
+
+/// Where the type statement of the leaf or leaf-list at the path is, for finding the typedefs defined around it: the
+/// grouping the deepest container or list on the path was used from, qualified with the name of its module like
+/// "module:/grouping", and the names of the nodes from that container, or from the top level if there's none, to the
+/// parent of the leaf.
+pub(crate) fn type_scope<'a>(body: &'a [SchemaNode], path: &str) -> Option<(Option<&'a Symbol>, Vec<String>)> {
+    let mut steps = path_steps(&[], path)?;
+    let mut node = PathNode::Body(body);
+    let mut grouping = None;
+    let mut start = 0;
+    for (index, step) in steps.iter().enumerate() {
+        node = node.child(step)?;
+        if let PathNode::Node(_, _, _, Some(used)) = node {
+            grouping = Some(used);
+            start = index;
+        }
+    }
+    steps.pop();
+
+    Some((grouping, steps.split_off(start)))
+}
+
+/// A warning for each leafref of the leaves and leaf-lists of the module which was left unresolved.
+pub fn unresolved_leafrefs(module: &Module) -> Vec<Warning> {
+    fn unresolved_paths<'a>(type_info: &'a TypeInfo, paths: &mut Vec<&'a str>) {
+        match type_info.type_body.as_deref() {
+            Some(TypeBody::Leafref { path, target: None, .. }) => paths.push(path),
+            Some(TypeBody::Union { types }) => types.iter().for_each(|member| unresolved_paths(member, paths)),
+            _ => {}
+        }
+    }
+
+    let tree = SchemaTree::new(module);
+    let mut unresolved = Vec::new();
+    for id in tree.iter() {
+        let kind = tree.kind(id);
+        let type_info = match kind {
+            NodeKind::Leaf(leaf) => &leaf.type_info,
+            NodeKind::LeafList(leaf_list) => &leaf_list.type_info,
+            _ => continue,
+        };
+        let mut paths = Vec::new();
+        unresolved_paths(type_info, &mut paths);
+        for path in paths {
+            unresolved.push(Warning {
+                kind: WarningKind::UnresolvedLeafref(path.to_string()),
+                module: module.name.clone(),
+                statement: Some(format!("{} {}", kind.keyword(), kind.name())),
+                path: Some(tree.path(id)),
+            });
+        }
+    }
+
+    unresolved
+}
+
+// The resolve functions take the location of the parent node, as the names of the nodes from the top level, and
+// return the number of levels the relative paths in the node climb above the node.
+fn resolve_data_defs(body: &[SchemaNode], location: &mut Vec<String>, data_defs: &mut [DataDef]) -> usize {
+    data_defs
+        .iter_mut()
+        .map(|data_def| resolve_data_def(body, location, data_def))
+        .max()
+        .unwrap_or(0)
+}
+
+// Nodes without leafrefs are left alone, so nodes shared with a grouping are not copied.
+fn resolve_data_def(body: &[SchemaNode], location: &mut Vec<String>, data_def: &mut DataDef) -> usize {
+    if !has_leafrefs(data_def) {
+        return 0;
+    }
+
+    match data_def {
+        DataDef::Container(container) => resolve_container(body, location, Arc::make_mut(container)),
+        DataDef::List(list) => resolve_list(body, location, Arc::make_mut(list)),
+        DataDef::Leaf(leaf) => {
+            let leaf = Arc::make_mut(leaf);
+            resolve_leaf(body, location, &leaf.name, &mut leaf.type_info)
+        }
+        DataDef::LeafList(leaf_list) => {
+            let leaf_list = Arc::make_mut(leaf_list);
+            resolve_leaf(body, location, &leaf_list.name, &mut leaf_list.type_info)
+        }
+        DataDef::Choice(choice) => resolve_choice(body, location, Arc::make_mut(choice)),
+        _ => 0,
+    }
+}
+
+fn resolve_container(body: &[SchemaNode], location: &mut Vec<String>, container: &mut Container) -> usize {
+    location.push(container.name.clone());
+    let climb = resolve_data_defs(body, location, &mut container.data_defs).saturating_sub(1);
+    resolve_actions(body, location, &mut container.actions, &mut container.notifications);
+    location.pop();
+    if climb > 0 {
+        container.grouping = None;
+    }
+
+    climb
+}
+
+fn resolve_list(body: &[SchemaNode], location: &mut Vec<String>, list: &mut List) -> usize {
+    location.push(list.name.clone());
+    let climb = resolve_data_defs(body, location, &mut list.data_defs).saturating_sub(1);
+    resolve_actions(body, location, &mut list.actions, &mut list.notifications);
+    location.pop();
+    if climb > 0 {
+        list.grouping = None;
+    }
+
+    climb
+}
+
+fn resolve_choice(body: &[SchemaNode], location: &mut Vec<String>, choice: &mut Choice) -> usize {
+    choice
+        .cases
+        .iter_mut()
+        .map(|case| match case {
+            Case::LongCase(long_case) if long_case.data_defs.iter().any(has_leafrefs) => {
+                resolve_data_defs(body, location, &mut Arc::make_mut(long_case).data_defs)
+            }
+            Case::LongCase(_) => 0,
+            Case::ShortCase(short_case) if !short_case_has_leafrefs(short_case) => 0,
+            Case::ShortCase(ShortCase::Container(container)) => {
+                resolve_container(body, location, Arc::make_mut(container))
+            }
+            Case::ShortCase(ShortCase::List(list)) => resolve_list(body, location, Arc::make_mut(list)),
+            Case::ShortCase(ShortCase::Leaf(leaf)) => {
+                let leaf = Arc::make_mut(leaf);
+                resolve_leaf(body, location, &leaf.name, &mut leaf.type_info)
+            }
+            Case::ShortCase(ShortCase::LeafList(leaf_list)) => {
+                let leaf_list = Arc::make_mut(leaf_list);
+                resolve_leaf(body, location, &leaf_list.name, &mut leaf_list.type_info)
+            }
+            Case::ShortCase(ShortCase::Choice(choice)) => resolve_choice(body, location, Arc::make_mut(choice)),
+            Case::ShortCase(ShortCase::Anydata(_)) | Case::ShortCase(ShortCase::Anyxml(_)) => 0,
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+fn resolve_leaf(body: &[SchemaNode], location: &mut Vec<String>, name: &str, type_info: &mut TypeInfo) -> usize {
+    location.push(name.to_string());
+    resolve_type(body, location, type_info, 0);
+    location.pop();
+
+    climb(type_info)
+}
+
+fn resolve_actions(
+    body: &[SchemaNode],
+    location: &mut Vec<String>,
+    actions: &mut [Action],
+    notifications: &mut [Notification],
+) {
+    for action in actions {
+        location.push(action.name.clone());
+        resolve_operation(body, location, &mut action.input, &mut action.output);
+        location.pop();
+    }
+    for notification in notifications {
+        location.push(notification.name.clone());
+        resolve_data_defs(body, location, &mut notification.data_defs);
+        location.pop();
+    }
+}
+
+fn resolve_operation(
+    body: &[SchemaNode],
+    location: &mut Vec<String>,
+    input: &mut Option<Input>,
+    output: &mut Option<Output>,
+) {
+    if let Some(input) = input {
+        location.push("input".to_string());
+        resolve_data_defs(body, location, &mut input.data_defs);
+        location.pop();
+    }
+    if let Some(output) = output {
+        location.push("output".to_string());
+        resolve_data_defs(body, location, &mut output.data_defs);
+        location.pop();
+    }
+}
+
+// Whether a leaf or leaf-list in the subtree of the node has a leafref type.
+fn has_leafrefs(data_def: &DataDef) -> bool {
+    match data_def {
+        DataDef::Container(container) => {
+            container.data_defs.iter().any(has_leafrefs)
+                || operations_have_leafrefs(&container.actions, &container.notifications)
+        }
+        DataDef::List(list) => {
+            list.data_defs.iter().any(has_leafrefs) || operations_have_leafrefs(&list.actions, &list.notifications)
+        }
+        DataDef::Leaf(leaf) => is_leafref(&leaf.type_info),
+        DataDef::LeafList(leaf_list) => is_leafref(&leaf_list.type_info),
+        DataDef::Choice(choice) => choice_has_leafrefs(choice),
+        _ => false,
+    }
+}
+
+fn choice_has_leafrefs(choice: &Choice) -> bool {
+    choice.cases.iter().any(|case| match case {
+        Case::LongCase(long_case) => long_case.data_defs.iter().any(has_leafrefs),
+        Case::ShortCase(short_case) => short_case_has_leafrefs(short_case),
+    })
+}
+
+fn short_case_has_leafrefs(short_case: &ShortCase) -> bool {
+    match short_case {
+        ShortCase::Container(container) => {
+            container.data_defs.iter().any(has_leafrefs)
+                || operations_have_leafrefs(&container.actions, &container.notifications)
+        }
+        ShortCase::List(list) => {
+            list.data_defs.iter().any(has_leafrefs) || operations_have_leafrefs(&list.actions, &list.notifications)
+        }
+        ShortCase::Leaf(leaf) => is_leafref(&leaf.type_info),
+        ShortCase::LeafList(leaf_list) => is_leafref(&leaf_list.type_info),
+        ShortCase::Choice(choice) => choice_has_leafrefs(choice),
+        ShortCase::Anydata(_) | ShortCase::Anyxml(_) => false,
+    }
+}
+
+fn operations_have_leafrefs(actions: &[Action], notifications: &[Notification]) -> bool {
+    actions.iter().any(|action| {
+        action
+            .input
+            .as_ref()
+            .is_some_and(|input| input.data_defs.iter().any(has_leafrefs))
+            || action
+                .output
+                .as_ref()
+                .is_some_and(|output| output.data_defs.iter().any(has_leafrefs))
+    }) || notifications
+        .iter()
+        .any(|notification| notification.data_defs.iter().any(has_leafrefs))
+}
+
+fn is_leafref(type_info: &TypeInfo) -> bool {
+    match type_info.type_body.as_deref() {
+        Some(TypeBody::Leafref { .. }) => true,
+        Some(TypeBody::Union { types }) => types.iter().any(is_leafref),
+        _ => false,
+    }
+}
+
+// Resolve the leafref type, or the leafref members of the union type, of the leaf at the location.
+fn resolve_type(body: &[SchemaNode], location: &[String], type_info: &mut TypeInfo, depth: usize) {
+    if !is_leafref(type_info) {
+        return;
+    }
+
+    match type_info.type_body.as_mut().map(Arc::make_mut) {
+        Some(TypeBody::Leafref { path, target, .. }) => {
+            *target = leafref_type(body, location, path, depth).map(Box::new);
+        }
+        Some(TypeBody::Union { types }) => {
+            for type_info in types {
+                resolve_type(body, location, type_info, depth);
+            }
+        }
+        _ => {}
+    }
+}
+
+// The type of the node the path of a leafref at the location refers to. The type of a leafref is the type the leafref
+// refers to, so the type is never a resolved leafref.
+fn leafref_type(body: &[SchemaNode], location: &[String], path: &str, depth: usize) -> Option<TypeInfo> {
+    if depth >= MAX_DEPTH {
+        return None;
+    }
+
+    let steps = path_steps(location, path)?;
+    let node = steps
+        .iter()
+        .try_fold(PathNode::Body(body), |node, step| node.child(step))?;
+    let PathNode::Leaf(type_info) = node else {
+        return None;
+    };
+
+    let mut type_info = type_info.clone();
+    resolve_type(body, &steps, &mut type_info, depth + 1);
+    match type_info.type_body.as_deref() {
+        Some(TypeBody::Leafref { target, .. }) => target.as_deref().cloned(),
+        _ => Some(type_info),
+    }
+}
+
+// The names of the nodes from the top level to the node the path refers to, without prefixes and predicates.
+fn path_steps(location: &[String], path: &str) -> Option<Vec<String>> {
+    let mut without_predicates = String::new();
+    let mut predicate_depth = 0;
+    for c in path.chars() {
+        match c {
+            '[' => predicate_depth += 1,
+            ']' => predicate_depth -= 1,
+            c if predicate_depth == 0 && !c.is_whitespace() => without_predicates.push(c),
+            _ => {}
+        }
+    }
+    if without_predicates.contains('(') {
+        return None;
+    }
+
+    let mut steps = match without_predicates.starts_with('/') {
+        true => Vec::new(),
+        false => location.to_vec(),
+    };
+    for step in without_predicates.split('/').filter(|step| !step.is_empty()) {
+        match step {
+            ".." => {
+                steps.pop()?;
+            }
+            "." => {}
+            _ => steps.push(step.rsplit(':').next().unwrap_or(step).to_string()),
+        }
+    }
+
+    Some(steps)
+}
+
+// The number of levels the relative leafref paths of the type climb above the leaf, where ".." is the parent of the
+// leaf.
+fn climb(type_info: &TypeInfo) -> usize {
+    match type_info.type_body.as_deref() {
+        Some(TypeBody::Leafref { path, .. }) if !path.trim_start().starts_with('/') => {
+            path.split('/').take_while(|step| step.trim() == "..").count()
+        }
+        Some(TypeBody::Union { types }) => types.iter().map(climb).max().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+// A node found by following the steps of a path.
+#[derive(Clone, Copy)]
+enum PathNode<'a> {
+    Body(&'a [SchemaNode]),
+    // The nodes of a container, list, notification, input or output, with the grouping the container or list was used
+    // from.
+    Node(&'a [DataDef], &'a [Action], &'a [Notification], Option<&'a Symbol>),
+    Operation(&'a Option<Input>, &'a Option<Output>),
+    Leaf(&'a TypeInfo),
+}
+
+impl<'a> PathNode<'a> {
+    fn child(self, name: &str) -> Option<PathNode<'a>> {
+        match self {
+            PathNode::Body(body) => body.iter().find_map(|node| match node {
+                SchemaNode::DataDef(data_def) => PathNode::from_data_defs(std::slice::from_ref(data_def), name),
+                SchemaNode::Rpc(rpc) if rpc.name == name => Some(PathNode::Operation(&rpc.input, &rpc.output)),
+                SchemaNode::Notification(notification) if notification.name == name => {
+                    Some(PathNode::Node(&notification.data_defs, &[], &[], None))
+                }
+                _ => None,
+            }),
+            PathNode::Node(data_defs, actions, notifications, _) => {
+                if let Some(action) = actions.iter().find(|action| action.name == name) {
+                    return Some(PathNode::Operation(&action.input, &action.output));
+                }
+                if let Some(notification) = notifications.iter().find(|notification| notification.name == name) {
+                    return Some(PathNode::Node(&notification.data_defs, &[], &[], None));
+                }
+                PathNode::from_data_defs(data_defs, name)
+            }
+            PathNode::Operation(input, output) => match name {
+                "input" => input
+                    .as_ref()
+                    .map(|input| PathNode::Node(&input.data_defs, &[], &[], None)),
+                "output" => output
+                    .as_ref()
+                    .map(|output| PathNode::Node(&output.data_defs, &[], &[], None)),
+                _ => None,
+            },
+            PathNode::Leaf(_) => None,
+        }
+    }
+
+    fn from_data_defs(data_defs: &'a [DataDef], name: &str) -> Option<PathNode<'a>> {
+        data_defs.iter().find_map(|data_def| match data_def {
+            DataDef::Container(container) if container.name == name => Some(PathNode::from_container(container)),
+            DataDef::List(list) if list.name == name => Some(PathNode::from_list(list)),
+            DataDef::Leaf(leaf) if leaf.name == name => Some(PathNode::Leaf(&leaf.type_info)),
+            DataDef::LeafList(leaf_list) if leaf_list.name == name => Some(PathNode::Leaf(&leaf_list.type_info)),
+            DataDef::Choice(choice) => PathNode::from_choice(choice, name),
+            _ => None,
+        })
+    }
+
+    fn from_choice(choice: &'a Choice, name: &str) -> Option<PathNode<'a>> {
+        choice.cases.iter().find_map(|case| match case {
+            Case::LongCase(long_case) => PathNode::from_data_defs(&long_case.data_defs, name),
+            Case::ShortCase(ShortCase::Container(container)) if container.name == name => {
+                Some(PathNode::from_container(container))
+            }
+            Case::ShortCase(ShortCase::List(list)) if list.name == name => Some(PathNode::from_list(list)),
+            Case::ShortCase(ShortCase::Leaf(leaf)) if leaf.name == name => Some(PathNode::Leaf(&leaf.type_info)),
+            Case::ShortCase(ShortCase::LeafList(leaf_list)) if leaf_list.name == name => {
+                Some(PathNode::Leaf(&leaf_list.type_info))
+            }
+            Case::ShortCase(ShortCase::Choice(choice)) => PathNode::from_choice(choice, name),
+            _ => None,
+        })
+    }
+
+    fn from_container(container: &'a Container) -> PathNode<'a> {
+        PathNode::Node(
+            &container.data_defs,
+            &container.actions,
+            &container.notifications,
+            container.grouping.as_ref(),
+        )
+    }
+
+    fn from_list(list: &'a List) -> PathNode<'a> {
+        PathNode::Node(
+            &list.data_defs,
+            &list.actions,
+            &list.notifications,
+            list.grouping.as_ref(),
+        )
+    }
+}
@@ -1,13 +1,45 @@
 #![allow(dead_code)]
 
+pub mod borrowed;
+#[cfg(feature = "catalog")]
+pub mod catalog;
+#[cfg(feature = "compiled")]
+pub mod compiled;
+#[cfg(feature = "miette")]
+mod diagnostic;
+mod document;
 mod error;
+#[cfg(feature = "json")]
+pub mod instance;
+mod leafref;
 pub mod model;
 mod module_loader;
 mod parser;
 mod parser_internal;
+mod registry;
+#[cfg(feature = "report")]
+pub mod report;
 mod resolver;
+mod schema;
+pub mod schema_tree;
+mod symbol;
+mod syntax;
+pub mod types;
+pub mod value;
 
-pub use error::ParserError;
+pub use document::{Document, SourceStatement};
+pub use error::{
+    DiagnosticSink, ErrorCode, ErrorKind, ParserError, Severity, SourceLocation, ValueError, Warning, WarningKind,
+};
+pub use registry::{ModuleRegistry, ModuleSource};
+pub use symbol::Symbol;
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use rayon::prelude::*;
 
 /// Parse a YANG module from a file.
 /// This is the main entry point for the YANG parser. It reads the YANG file
@@ -17,3 +49,512 @@ pub fn parse<P: AsRef<std::path::Path>>(path: P) -> Result<model::YangModule, Pa
     // Hide implementation details from users
     module_loader::ModuleLoader::new().load_file(path)
 }
+
+/// Parse a YANG module from a file like [parse], and apply the augments and deviations of the other modules which
+/// target it, like a server implementing all the modules does. The nodes added by the augments of other modules have
+/// the name of the module defining them in their `module` field.
+pub fn parse_with_modules<P: AsRef<std::path::Path>>(
+    path: P,
+    modules: impl IntoIterator<Item = impl AsRef<std::path::Path>>,
+) -> Result<model::YangModule, ParserError> {
+    let options = ParseOptions {
+        modules: modules.into_iter().map(|path| path.as_ref().to_path_buf()).collect(),
+        ..Default::default()
+    };
+    parse_with_options(path, &options)
+}
+
+/// Find the file of the module or submodule imported or included from the file at the base path, the way parsing finds
+/// it. The file is named after the module, like "ietf-inet-types.yang", or after the module and a revision, like
+/// "ietf-inet-types@2013-07-15.yang", where the latest revision is used. The directory of the base path is searched
+/// first, then the search paths.
+pub fn find_module_file(
+    base_path: &std::path::Path,
+    search_paths: &[std::path::PathBuf],
+    module: &str,
+) -> Option<std::path::PathBuf> {
+    registry::find_module_file(None, base_path, search_paths, module)
+}
+
+/// Options for parsing a YANG module, for module sets which need more than [parse] and [parse_with_modules].
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// Directories searched for imported modules and included submodules, after the directory of the module importing
+    /// or including them. Files are named after the module, like `ietf-inet-types.yang`, or after the module and its
+    /// revision, like `ietf-inet-types@2013-07-15.yang`, where the latest revision is used.
+    pub search_paths: Vec<std::path::PathBuf>,
+
+    /// Other modules whose augments and deviations targeting the module are applied, like [parse_with_modules] does.
+    pub modules: Vec<std::path::PathBuf>,
+
+    /// Leave the leafrefs of the module unresolved, without a target, so parsing doesn't pay for resolving every leafref
+    /// of a huge schema of which only a part is used. The types of the leaves used are resolved on demand with a
+    /// [types::TypeResolver] instead.
+    pub lazy_leafrefs: bool,
+
+    /// Whether parsing stops at the first error, or continues and returns all the errors it found.
+    pub error_policy: ErrorPolicy,
+
+    /// Receives the warnings found while loading and resolving the module, like imports which are never used and uses
+    /// statements of groupings which aren't found. Warnings are dropped if it's not set.
+    pub warnings: Option<Arc<dyn DiagnosticSink>>,
+
+    /// Which warnings are promoted to errors, and which errors are demoted to warnings.
+    pub severity_policy: SeverityPolicy,
+
+    /// Sources the module, the modules it imports and the other modules are loaded from before the file system, which
+    /// is the only place they are loaded from on `wasm32-unknown-unknown`.
+    pub registry: Option<Arc<ModuleRegistry>>,
+
+    /// Where the modules and submodules which aren't found in the search paths are fetched from, like the
+    /// [catalog::YangCatalog], so standard modules don't have to be vendored.
+    pub source: Option<Arc<dyn ModuleSource>>,
+}
+
+/// What parsing does when it finds an error.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ErrorPolicy {
+    /// Stop at the first error and return it.
+    #[default]
+    FailFast,
+    /// Leave out the imports, includes and other modules which fail to load, and keep going, so the errors of all of
+    /// them are found in one run, like when linting a repository of modules. If any fail, the errors are returned
+    /// together as an [ErrorKind::Multiple] error, or as the error itself if there's only one. The module itself is
+    /// only returned if nothing failed.
+    CollectAll,
+}
+
+/// Which errors are reported as warnings and which warnings as errors, for organizations enforcing stricter or looser
+/// compliance than the defaults, like failing on unused imports, or loading modules whose imports are missing.
+///
+/// Warnings promoted to errors fail parsing like other errors. The errors of imports, includes and other modules which
+/// are demoted to warnings leave them out, like [ErrorPolicy::CollectAll] does, and go to [ParseOptions::warnings] as
+/// [WarningKind::Demoted] warnings. Errors of the module itself, like syntax errors, can't be demoted, as there's no
+/// module without them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SeverityPolicy {
+    severities: HashMap<ErrorCode, Severity>,
+}
+
+impl SeverityPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Report the errors or warnings with the code with the severity.
+    pub fn with(mut self, code: ErrorCode, severity: Severity) -> Self {
+        self.set(code, severity);
+        self
+    }
+
+    pub fn set(&mut self, code: ErrorCode, severity: Severity) {
+        self.severities.insert(code, severity);
+    }
+
+    /// The severity of the errors or warnings with the code, which is their default severity unless the policy changes
+    /// it.
+    pub fn severity(&self, code: ErrorCode, default: Severity) -> Severity {
+        self.severities.get(&code).copied().unwrap_or(default)
+    }
+
+    // Whether warnings are looked for to be promoted, even if nothing receives them.
+    fn promotes_warnings(&self) -> bool {
+        self.severities.values().any(|severity| *severity == Severity::Error)
+    }
+
+    // Send the errors the policy demotes to the sink as warnings of the module, and return the rest.
+    fn demote(
+        &self,
+        error: ParserError,
+        warnings: Option<&Arc<dyn DiagnosticSink>>,
+        module: &str,
+        statement: Option<String>,
+    ) -> Option<ParserError> {
+        if self.severities.is_empty() {
+            return Some(error);
+        }
+
+        let mut errors = Vec::new();
+        for error in error.into_errors() {
+            if self.severity(error.code(), Severity::Error) == Severity::Error {
+                errors.push(error);
+            } else if let Some(warnings) = warnings {
+                warnings.warning(Warning {
+                    kind: WarningKind::Demoted {
+                        code: error.code(),
+                        message: error.to_string(),
+                    },
+                    module: error.module().unwrap_or(module).into(),
+                    statement: statement.clone(),
+                    path: error.path().map(str::to_string),
+                });
+            }
+        }
+        (!errors.is_empty()).then(|| ParserError::collected(errors))
+    }
+}
+
+/// Parse a YANG module from a file like [parse], with the options. The module and the other modules of the options are
+/// loaded in parallel.
+pub fn parse_with_options<P: AsRef<std::path::Path>>(
+    path: P,
+    options: &ParseOptions,
+) -> Result<model::YangModule, ParserError> {
+    let path = path.as_ref();
+    let sink = PolicySink::new(options);
+    if options.modules.is_empty() {
+        let result = loader(options, &sink).load_file(path);
+        if let Ok(model::YangModule::Module(module)) = &result {
+            warn_unresolved_leafrefs(module, options, &sink);
+        }
+        return with_promoted(result, &sink);
+    }
+
+    let (result, others) = rayon::join(
+        || with_promoted(loader(options, &sink).load_file(path), &sink),
+        || load_others(options, None),
+    );
+    let mut errors = Vec::new();
+    let result = options.error_policy.recover(result, &mut errors)?;
+    let others = options.recover_others(others, &mut errors)?;
+    let mut result = match result {
+        Some(result) if errors.is_empty() => result,
+        _ => return Err(ParserError::collected(errors)),
+    };
+
+    if let model::YangModule::Module(module) = &mut result {
+        apply_from(module, &others, &sink);
+        if !options.lazy_leafrefs {
+            leafref::resolve_leafrefs(module);
+        }
+        warn_unresolved_leafrefs(module, options, &sink);
+    }
+
+    with_promoted(Ok(result), &sink)
+}
+
+/// Parse many modules one at a time, like a validation or lint pipeline over a whole repository of models, passing each
+/// module to the callback, which owns it, so it's dropped after use. Modules imported by several of the modules are only
+/// parsed once, and only their groupings, typedefs, identities, features and imports are kept for the rest of the scan,
+/// so memory stays flat however many modules are scanned.
+///
+/// The modules are parsed like [parse_with_options] parses them, where the other modules of the options are loaded once
+/// and applied to every module. With [ErrorPolicy::CollectAll], the scan goes on with the other modules which loaded,
+/// and the errors of those which didn't are returned after the scan.
+///
+/// The modules are loaded in parallel, as many at a time as rayon has threads, and passed to the callback in the order
+/// of the paths.
+pub fn scan<P: AsRef<std::path::Path> + Sync>(
+    paths: impl IntoIterator<Item = P>,
+    options: &ParseOptions,
+    mut f: impl FnMut(&std::path::Path, Result<model::YangModule, ParserError>),
+) -> Result<(), ParserError> {
+    let import_cache = Arc::new(module_loader::ImportCache::default());
+
+    let mut errors = Vec::new();
+    let others = load_others(options, Some(&import_cache));
+    let others = options.recover_others(others, &mut errors)?;
+
+    // Each module gets a sink of its own, so the warnings promoted while loading it fail it, rather than another module
+    // loaded at the same time.
+    let load = |path: &std::path::Path| {
+        let sink = PolicySink::new(options);
+        let mut result = loader(options, &sink)
+            .with_import_cache(Some(import_cache.clone()))
+            .load_file(path);
+        if let Ok(model::YangModule::Module(module)) = &mut result {
+            if !others.is_empty() {
+                apply_from(module, &others, &sink);
+                if !options.lazy_leafrefs {
+                    leafref::resolve_leafrefs(module);
+                }
+            }
+            warn_unresolved_leafrefs(module, options, &sink);
+        }
+        with_promoted(result, &sink)
+    };
+
+    let mut paths = paths.into_iter().peekable();
+    while paths.peek().is_some() {
+        let batch: Vec<P> = paths.by_ref().take(rayon::current_num_threads()).collect();
+        let results: Vec<_> = batch.par_iter().map(|path| load(path.as_ref())).collect();
+        for (path, result) in batch.iter().zip(results) {
+            f(path.as_ref(), result);
+        }
+    }
+
+    match errors.is_empty() {
+        true => Ok(()),
+        false => Err(ParserError::collected(errors)),
+    }
+}
+
+// A loader of a module with the options, which sends its warnings to the sink.
+fn loader(options: &ParseOptions, sink: &Option<Arc<PolicySink>>) -> module_loader::ModuleLoader {
+    module_loader::ModuleLoader::with_search_paths(options.search_paths.clone())
+        .with_registry(options.registry.clone())
+        .with_source(options.source.clone())
+        .with_lazy_leafrefs(options.lazy_leafrefs)
+        .with_error_policy(options.error_policy)
+        .with_severity_policy(options.severity_policy.clone())
+        .with_warnings(PolicySink::as_dyn(sink))
+}
+
+// Load the other modules of the options in parallel, each with a sink of its own, in the order of the options.
+fn load_others(
+    options: &ParseOptions,
+    import_cache: Option<&Arc<module_loader::ImportCache>>,
+) -> Vec<Result<model::YangModule, ParserError>> {
+    options
+        .modules
+        .par_iter()
+        .map(|path| {
+            let sink = PolicySink::new(options);
+            let loader = loader(options, &sink).with_import_cache(import_cache.cloned());
+            with_promoted(loader.load_file(path), &sink)
+        })
+        .collect()
+}
+
+// The augments and deviations of the other modules whose targets aren't in the module are reported as warnings.
+fn apply_from(module: &mut model::Module, others: &[model::Module], sink: &Option<Arc<PolicySink>>) {
+    let warnings = schema::apply_from(module, others);
+    if let Some(sink) = sink {
+        warnings.into_iter().for_each(|warning| sink.warning(warning));
+    }
+}
+
+// Leafrefs are only reported once the module is complete, as the augments of other modules may add their targets.
+fn warn_unresolved_leafrefs(module: &model::Module, options: &ParseOptions, sink: &Option<Arc<PolicySink>>) {
+    let Some(sink) = sink else {
+        return;
+    };
+    if options.lazy_leafrefs {
+        return;
+    }
+
+    for warning in leafref::unresolved_leafrefs(module) {
+        sink.warning(warning);
+    }
+}
+
+// The result of loading a module, failed by the warnings the severity policy promoted to errors while loading it.
+fn with_promoted(
+    result: Result<model::YangModule, ParserError>,
+    sink: &Option<Arc<PolicySink>>,
+) -> Result<model::YangModule, ParserError> {
+    let promoted = match sink {
+        Some(sink) => sink.take_promoted(),
+        None => return result,
+    };
+    match result {
+        Ok(module) if promoted.is_empty() => Ok(module),
+        Ok(_) => Err(ParserError::collected(promoted)),
+        Err(error) => Err(ParserError::collected(std::iter::once(error).chain(promoted))),
+    }
+}
+
+// Sends the warnings found while loading to the sink of the options, except for those the severity policy promotes to
+// errors, which are kept to fail the module with.
+struct PolicySink {
+    warnings: Option<Arc<dyn DiagnosticSink>>,
+    severity_policy: SeverityPolicy,
+    promoted: Mutex<Vec<Warning>>,
+}
+
+impl PolicySink {
+    // There's no sink if warnings are neither received nor promoted, so they aren't looked for.
+    fn new(options: &ParseOptions) -> Option<Arc<Self>> {
+        if options.warnings.is_none() && !options.severity_policy.promotes_warnings() {
+            return None;
+        }
+
+        Some(Arc::new(Self {
+            warnings: options.warnings.clone(),
+            severity_policy: options.severity_policy.clone(),
+            promoted: Mutex::default(),
+        }))
+    }
+
+    fn as_dyn(sink: &Option<Arc<Self>>) -> Option<Arc<dyn DiagnosticSink>> {
+        sink.clone().map(|sink| sink as Arc<dyn DiagnosticSink>)
+    }
+
+    // The warnings promoted since the last call, as errors.
+    fn take_promoted(&self) -> Vec<ParserError> {
+        let promoted = std::mem::take(&mut *self.promoted.lock().expect("the promoted warnings to not be poisoned"));
+        promoted
+            .into_iter()
+            .map(|warning| {
+                let module = warning.module.clone();
+                let path = warning.path.clone();
+                let error = ParserError::from(ErrorKind::Promoted(warning)).with_module(module);
+                match path {
+                    Some(path) => error.with_path(path),
+                    None => error,
+                }
+            })
+            .collect()
+    }
+}
+
+impl DiagnosticSink for PolicySink {
+    fn warning(&self, warning: Warning) {
+        match self.severity_policy.severity(warning.code(), Severity::Warning) {
+            Severity::Error => self
+                .promoted
+                .lock()
+                .expect("the promoted warnings to not be poisoned")
+                .push(warning),
+            Severity::Warning => {
+                if let Some(warnings) = &self.warnings {
+                    warnings.warning(warning);
+                }
+            }
+        }
+    }
+}
+
+impl ParseOptions {
+    // The other modules which loaded, in the order of the options. The errors of the others are demoted, collected or
+    // returned.
+    fn recover_others(
+        &self,
+        others: Vec<Result<model::YangModule, ParserError>>,
+        errors: &mut Vec<ParserError>,
+    ) -> Result<Vec<model::Module>, ParserError> {
+        let mut modules = Vec::new();
+        for (path, other) in self.modules.iter().zip(others) {
+            if let Some(model::YangModule::Module(other)) = self.recover_other(path, other, errors)? {
+                modules.push(other);
+            }
+        }
+        Ok(modules)
+    }
+
+    // The other module, or None if it failed to load and its errors are demoted or collected.
+    fn recover_other<T>(
+        &self,
+        path: &std::path::Path,
+        result: Result<T, ParserError>,
+        errors: &mut Vec<ParserError>,
+    ) -> Result<Option<T>, ParserError> {
+        let error = match result {
+            Ok(value) => return Ok(Some(value)),
+            Err(error) => error,
+        };
+        match self
+            .severity_policy
+            .demote(error, self.warnings.as_ref(), &path.to_string_lossy(), None)
+        {
+            Some(error) => self.error_policy.recover(Err(error), errors),
+            None => Ok(None),
+        }
+    }
+}
+
+impl ErrorPolicy {
+    // The value of the result, or None with its error kept in the errors if errors are collected.
+    fn recover<T>(
+        self,
+        result: Result<T, ParserError>,
+        errors: &mut Vec<ParserError>,
+    ) -> Result<Option<T>, ParserError> {
+        match (self, result) {
+            (_, Ok(value)) => Ok(Some(value)),
+            (ErrorPolicy::FailFast, Err(error)) => Err(error),
+            (ErrorPolicy::CollectAll, Err(error)) => {
+                errors.push(error);
+                Ok(None)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        path::{Path, PathBuf},
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    use super::*;
+
+    // Finds the common module, which is in the registry rather than the directory of the modules, counting the times it
+    // was looked for.
+    #[derive(Default)]
+    struct CountingSource(AtomicUsize);
+
+    impl ModuleSource for CountingSource {
+        fn find_module(&self, module: &str, _: Option<&str>) -> std::io::Result<Option<PathBuf>> {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            Ok((module == "common").then(|| PathBuf::from("fetched/common.yang")))
+        }
+    }
+
+    fn importing(name: &str, unused: bool) -> String {
+        let used = match unused {
+            true => "",
+            false => "\n  leaf name { type c:name; }",
+        };
+        format!(
+            "module {name} {{\n  namespace \"urn:{name}\";\n  prefix {name};\n  \
+             import common {{ prefix c; }}{used}\n}}\n"
+        )
+    }
+
+    fn registry() -> ModuleRegistry {
+        let common = "module common {\n  namespace \"urn:common\";\n  prefix c;\n  typedef name { type string; }\n}\n";
+        ["a", "b", "c", "d", "e"]
+            .iter()
+            .fold(ModuleRegistry::new(), |registry, name| {
+                registry.with(format!("models/{}.yang", name), importing(name, *name == "c"))
+            })
+            .with("fetched/common.yang", common)
+    }
+
+    fn scanned(options: &ParseOptions) -> Vec<(PathBuf, Result<model::YangModule, ParserError>)> {
+        let paths = ["e", "d", "c", "b", "a"].map(|name| PathBuf::from(format!("models/{}.yang", name)));
+        let mut scanned = Vec::new();
+        scan(&paths, options, |path, result| scanned.push((path.to_path_buf(), result))).expect("the scan to succeed");
+        scanned
+    }
+
+    #[test]
+    fn scans_look_for_each_import_once_and_keep_the_order_of_the_paths() {
+        let source = Arc::new(CountingSource::default());
+        let options = ParseOptions {
+            registry: Some(Arc::new(registry())),
+            source: Some(source.clone()),
+            ..Default::default()
+        };
+
+        let scanned = scanned(&options);
+        let paths: Vec<&Path> = scanned.iter().map(|(path, _)| path.as_path()).collect();
+        assert_eq!(
+            paths,
+            ["e", "d", "c", "b", "a"].map(|name| format!("models/{}.yang", name)).map(PathBuf::from)
+        );
+        for (_, result) in &scanned {
+            let Ok(model::YangModule::Module(module)) = result else {
+                panic!("expected a module, found {:?}", result);
+            };
+            assert!(module.imported_modules.contains_key("common"));
+        }
+        assert_eq!(source.0.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn promoted_warnings_fail_the_module_they_were_found_in() {
+        let options = ParseOptions {
+            registry: Some(Arc::new(registry())),
+            source: Some(Arc::new(CountingSource::default())),
+            severity_policy: SeverityPolicy::new().with(ErrorCode::UnusedImport, Severity::Error),
+            ..Default::default()
+        };
+
+        for (path, result) in scanned(&options) {
+            assert_eq!(result.is_err(), path == Path::new("models/c.yang"), "{}", path.display());
+        }
+    }
+}
@@ -1,12 +1,91 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
+
+use smallvec::SmallVec;
+
+pub use crate::symbol::Symbol;
+
+/// The if-feature statements of a node. Nodes rarely have more than one, so one is stored without an allocation.
+pub type IfFeatures = SmallVec<[String; 1]>;
+
+/// The rarely set statements of a schema node, which are kept out of the node so a node without any of them holds a
+/// single null pointer in their place.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeMeta {
+    pub status: Option<Status>,
+    pub description: Option<String>,
+    pub reference: Option<String>,
+    /// Presence statement, only set for containers.
+    pub presence: Option<String>,
+    /// Units statement, only set for leaves and leaf-lists.
+    pub units: Option<String>,
+    /// Name of the module defining the node, set for nodes added by an augment of another module.
+    pub module: Option<Symbol>,
+}
+
+static NO_META: NodeMeta = NodeMeta {
+    status: None,
+    description: None,
+    reference: None,
+    presence: None,
+    units: None,
+    module: None,
+};
+
+/// The [NodeMeta] of a node, which is only allocated once one of its statements is set. It dereferences to the
+/// statements, like `leaf.meta.description`, and setting one through it allocates them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Meta(Option<Box<NodeMeta>>);
+
+impl Meta {
+    /// Whether none of the statements are set.
+    pub fn is_empty(&self) -> bool {
+        **self == NO_META
+    }
+}
+
+impl From<NodeMeta> for Meta {
+    fn from(meta: NodeMeta) -> Self {
+        match meta == NO_META {
+            true => Self(None),
+            false => Self(Some(Box::new(meta))),
+        }
+    }
+}
+
+impl std::ops::Deref for Meta {
+    type Target = NodeMeta;
+
+    fn deref(&self) -> &NodeMeta {
+        self.0.as_deref().unwrap_or(&NO_META)
+    }
+}
+
+impl std::ops::DerefMut for Meta {
+    fn deref_mut(&mut self) -> &mut NodeMeta {
+        self.0.get_or_insert_with(Default::default)
+    }
+}
+
+// Must statements are stored in boxed slices of their exact length, which are added to while parsing and by deviations.
+pub(crate) fn extend_boxed<T>(slice: &mut Box<[T]>, items: impl IntoIterator<Item = T>) {
+    let mut vec = std::mem::take(slice).into_vec();
+    vec.extend(items);
+    *slice = vec.into_boxed_slice();
+}
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[allow(clippy::large_enum_variant)]
 pub enum YangModule {
     Module(Module),
     Submodule(Submodule),
 }
 
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ReferenceNodes {
     pub features: HashMap<String, Feature>,
     pub groupings: HashMap<String, Grouping>,
@@ -16,20 +95,36 @@ pub struct ReferenceNodes {
 
 /// Represents a YANG module
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Module {
-    pub name: String,
+    pub name: Symbol,
     pub yang_version: Option<String>,
     pub namespace: String,
-    pub prefix: String,
+    pub prefix: Symbol,
     pub meta: MetaInfo,
     pub revisions: Vec<Revision>,
     pub body: Vec<SchemaNode>,
+    /// Features, groupings, identities and typedefs of the module and its submodules, keyed by their path.
+    pub reference_nodes: ReferenceNodes,
+    /// Reference nodes of all imported modules, keyed by module name.
+    pub imported_modules: HashMap<Symbol, ReferenceNodes>,
+    /// Namespaces of all imported modules, keyed by module name.
+    pub imported_namespaces: HashMap<Symbol, String>,
+    /// Prefixes used for imported modules, mapped to the module names.
+    pub prefix_to_module: HashMap<Symbol, Symbol>,
+    /// Augments and deviations of the module, including those targeting other modules. Those targeting the module
+    /// itself are already applied to its body.
+    pub augments: Vec<Augment>,
+    pub deviations: Vec<Deviation>,
+    /// Namespaces of the other modules whose augments were applied to the module, keyed by module name.
+    pub augmenting_modules: HashMap<Symbol, String>,
 }
 
 /// Represents a YANG submodule
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Submodule {
-    pub name: String,
+    pub name: Symbol,
     pub yang_version: Option<String>,
     pub belongs_to: BelongsTo,
     pub meta: MetaInfo,
@@ -38,16 +133,18 @@ pub struct Submodule {
 }
 
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BelongsTo {
-    pub module: String,
-    pub prefix: String,
+    pub module: Symbol,
+    pub prefix: Symbol,
 }
 
 /// Import statement
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Import {
-    pub module: String,
-    pub prefix: String,
+    pub module: Symbol,
+    pub prefix: Symbol,
     pub revision_date: Option<String>,
     pub description: Option<String>,
     pub reference: Option<String>,
@@ -55,8 +152,9 @@ pub struct Import {
 
 /// Include statement
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Include {
-    pub module: String,
+    pub module: Symbol,
     pub revision_date: Option<String>,
     pub description: Option<String>,
     pub reference: Option<String>,
@@ -64,6 +162,7 @@ pub struct Include {
 
 /// Meta information for modules
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MetaInfo {
     pub organization: Option<String>,
     pub contact: Option<String>,
@@ -73,6 +172,7 @@ pub struct MetaInfo {
 
 /// Revision history
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Revision {
     pub date: String,
     pub description: Option<String>,
@@ -81,176 +181,180 @@ pub struct Revision {
 
 /// All possible schema nodes that can appear in a YANG module body
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SchemaNode {
-    Rpc(Rpc),
-    Notification(Notification),
+    Rpc(Box<Rpc>),
+    Notification(Box<Notification>),
     DataDef(DataDef),
 }
 
+/// Data definition statement. The nodes are reference counted, so the nodes expanded from a grouping share its nodes,
+/// and are only copied when they are changed by refines, augments or deviations. Change them with `Arc::make_mut`.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DataDef {
-    Container(Container),
-    Leaf(Leaf),
-    LeafList(LeafList),
-    List(List),
-    Choice(Choice),
-    AnyData(Anydata),
-    Anyxml(Anyxml),
-    Uses(Uses),
+    Container(Arc<Container>),
+    Leaf(Arc<Leaf>),
+    LeafList(Arc<LeafList>),
+    List(Arc<List>),
+    Choice(Arc<Choice>),
+    AnyData(Arc<Anydata>),
+    Anyxml(Arc<Anyxml>),
+    Uses(Arc<Uses>),
 }
 
 /// Container statement
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Container {
     pub name: String,
-    pub when: Option<When>,
-    pub if_features: Vec<String>,
-    pub must: Vec<Must>,
-    pub presence: Option<String>,
+    pub when: Option<Box<When>>,
+    pub if_features: IfFeatures,
+    pub must: Box<[Must]>,
+    pub meta: Meta,
     pub config: Option<bool>,
-    pub status: Option<Status>,
-    pub description: Option<String>,
-    pub reference: Option<String>,
     pub data_defs: Vec<DataDef>,
     pub actions: Vec<Action>,
     pub notifications: Vec<Notification>,
+    /// Path of the grouping the node was used from, qualified with the name of the grouping's module, like
+    /// "module:/grouping". It's only set if the node is the same as in the grouping, so every node with the same
+    /// grouping has the same schema.
+    pub grouping: Option<Symbol>,
 }
 
 /// Leaf statement
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Leaf {
     pub name: String,
-    pub when: Option<When>,
-    pub if_features: Vec<String>,
+    pub when: Option<Box<When>>,
+    pub if_features: IfFeatures,
     pub type_info: TypeInfo,
-    pub units: Option<String>,
-    pub must: Vec<Must>,
+    pub meta: Meta,
+    pub must: Box<[Must]>,
     pub default: Option<String>,
     pub config: Option<bool>,
     pub mandatory: Option<bool>,
-    pub status: Option<Status>,
-    pub description: Option<String>,
-    pub reference: Option<String>,
 }
 
 /// Leaf-list statement
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LeafList {
     pub name: String,
-    pub when: Option<When>,
-    pub if_features: Vec<String>,
+    pub when: Option<Box<When>>,
+    pub if_features: IfFeatures,
     pub type_info: TypeInfo,
-    pub units: Option<String>,
-    pub must: Vec<Must>,
+    pub meta: Meta,
+    pub must: Box<[Must]>,
     pub default: Vec<String>,
     pub config: Option<bool>,
     pub min_elements: Option<i64>,
     pub max_elements: Option<MaxElements>,
     pub ordered_by: Option<OrderedBy>,
-    pub status: Option<Status>,
-    pub description: Option<String>,
-    pub reference: Option<String>,
 }
 
 /// List statement
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct List {
     pub name: String,
-    pub when: Option<When>,
-    pub if_features: Vec<String>,
-    pub must: Vec<Must>,
+    pub when: Option<Box<When>>,
+    pub if_features: IfFeatures,
+    pub must: Box<[Must]>,
     pub key: Option<String>,
     pub unique: Vec<String>,
     pub config: Option<bool>,
     pub min_elements: Option<i64>,
     pub max_elements: Option<MaxElements>,
     pub ordered_by: Option<OrderedBy>,
-    pub status: Option<Status>,
-    pub description: Option<String>,
-    pub reference: Option<String>,
+    pub meta: Meta,
     pub data_defs: Vec<DataDef>,
     pub actions: Vec<Action>,
     pub notifications: Vec<Notification>,
+    /// Path of the grouping the node was used from, qualified with the name of the grouping's module, like
+    /// "module:/grouping". It's only set if the node is the same as in the grouping, so every node with the same
+    /// grouping has the same schema.
+    pub grouping: Option<Symbol>,
 }
 
 /// Choice statement
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Choice {
     pub name: String,
-    pub when: Option<When>,
-    pub if_features: Vec<String>,
+    pub when: Option<Box<When>>,
+    pub if_features: IfFeatures,
     pub default: Option<String>,
     pub config: Option<bool>,
     pub mandatory: Option<bool>,
-    pub status: Option<Status>,
-    pub description: Option<String>,
-    pub reference: Option<String>,
+    pub meta: Meta,
     pub cases: Vec<Case>,
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Case {
-    LongCase(LongCase),
+    LongCase(Arc<LongCase>),
     ShortCase(ShortCase),
 }
 
 /// Case statement
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LongCase {
     pub name: String,
-    pub when: Option<When>,
-    pub if_features: Vec<String>,
-    pub status: Option<Status>,
-    pub description: Option<String>,
-    pub reference: Option<String>,
+    pub when: Option<Box<When>>,
+    pub if_features: IfFeatures,
+    pub meta: Meta,
     pub data_defs: Vec<DataDef>,
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ShortCase {
-    Choice(Choice),
-    Container(Container),
-    Leaf(Leaf),
-    LeafList(LeafList),
-    List(List),
-    Anydata(Anydata),
-    Anyxml(Anyxml),
+    Choice(Arc<Choice>),
+    Container(Arc<Container>),
+    Leaf(Arc<Leaf>),
+    LeafList(Arc<LeafList>),
+    List(Arc<List>),
+    Anydata(Arc<Anydata>),
+    Anyxml(Arc<Anyxml>),
 }
 
 /// Anydata statement
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Anydata {
     pub name: String,
-    pub when: Option<When>,
-    pub if_features: Vec<String>,
-    pub must: Vec<Must>,
+    pub when: Option<Box<When>>,
+    pub if_features: IfFeatures,
+    pub must: Box<[Must]>,
     pub config: Option<bool>,
     pub mandatory: Option<bool>,
-    pub status: Option<Status>,
-    pub description: Option<String>,
-    pub reference: Option<String>,
+    pub meta: Meta,
 }
 
 /// Anyxml statement
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Anyxml {
     pub name: String,
-    pub when: Option<When>,
-    pub if_features: Vec<String>,
-    pub must: Vec<Must>,
+    pub when: Option<Box<When>>,
+    pub if_features: IfFeatures,
+    pub must: Box<[Must]>,
     pub config: Option<bool>,
     pub mandatory: Option<bool>,
-    pub status: Option<Status>,
-    pub description: Option<String>,
-    pub reference: Option<String>,
+    pub meta: Meta,
 }
 
 /// Uses statement
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Uses {
     pub grouping: String,
-    pub when: Option<When>,
-    pub if_features: Vec<String>,
+    pub when: Option<Box<When>>,
+    pub if_features: IfFeatures,
     pub status: Option<Status>,
     pub description: Option<String>,
     pub reference: Option<String>,
@@ -260,6 +364,7 @@ pub struct Uses {
 
 /// Typedef statement
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TypeDef {
     pub name: String,
     pub type_info: TypeInfo,
@@ -271,14 +376,18 @@ pub struct TypeDef {
 }
 
 /// Type information
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TypeInfo {
-    pub name: String,
-    pub type_body: Option<TypeBody>,
+    pub name: Symbol,
+    /// Identical type bodies parsed in a module set are shared, so comparing them is usually a pointer comparison.
+    /// Change them with `Arc::make_mut`.
+    pub type_body: Option<Arc<TypeBody>>,
 }
 
 /// Type body for specific type constraints
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TypeBody {
     Numerical {
         range: Range,
@@ -297,6 +406,8 @@ pub enum TypeBody {
     Leafref {
         path: String,
         require_instance: Option<bool>,
+        /// Type of the leaf or leaf-list the path refers to, set when the path is resolved.
+        target: Option<Box<TypeInfo>>,
     },
     Identityref {
         bases: Vec<String>,
@@ -316,7 +427,8 @@ pub enum TypeBody {
 }
 
 /// Range restriction
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Range {
     pub value: String,
     pub error_message: Option<String>,
@@ -326,7 +438,8 @@ pub struct Range {
 }
 
 /// Length restriction
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Length {
     pub value: String,
     pub error_message: Option<String>,
@@ -336,7 +449,8 @@ pub struct Length {
 }
 
 /// Pattern restriction
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Pattern {
     pub value: String,
     pub modifier: Option<String>,
@@ -347,10 +461,11 @@ pub struct Pattern {
 }
 
 /// Enum value
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EnumValue {
     pub name: String,
-    pub if_features: Vec<String>,
+    pub if_features: IfFeatures,
     pub value: Option<i64>,
     pub status: Option<Status>,
     pub description: Option<String>,
@@ -358,10 +473,11 @@ pub struct EnumValue {
 }
 
 /// Bit value
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Bit {
     pub name: String,
-    pub if_features: Vec<String>,
+    pub if_features: IfFeatures,
     pub position: Option<i64>,
     pub status: Option<Status>,
     pub description: Option<String>,
@@ -370,6 +486,7 @@ pub struct Bit {
 
 /// Grouping statement
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Grouping {
     pub name: String,
     pub status: Option<Status>,
@@ -382,6 +499,7 @@ pub struct Grouping {
 
 /// Extension statement
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Extension {
     pub name: String,
     pub argument: Option<Argument>,
@@ -392,6 +510,7 @@ pub struct Extension {
 
 /// Argument for extension
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Argument {
     pub name: String,
     pub yin_element: Option<bool>,
@@ -399,9 +518,10 @@ pub struct Argument {
 
 /// Feature statement
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Feature {
     pub name: String,
-    pub if_features: Vec<String>,
+    pub if_features: IfFeatures,
     pub status: Option<Status>,
     pub description: Option<String>,
     pub reference: Option<String>,
@@ -409,9 +529,10 @@ pub struct Feature {
 
 /// Identity statement
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Identity {
     pub name: String,
-    pub if_features: Vec<String>,
+    pub if_features: IfFeatures,
     pub bases: Vec<String>,
     pub status: Option<Status>,
     pub description: Option<String>,
@@ -420,10 +541,11 @@ pub struct Identity {
 
 /// Augment statement
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Augment {
     pub target: String,
-    pub when: Option<When>,
-    pub if_features: Vec<String>,
+    pub when: Option<Box<When>>,
+    pub if_features: IfFeatures,
     pub status: Option<Status>,
     pub description: Option<String>,
     pub reference: Option<String>,
@@ -435,10 +557,11 @@ pub struct Augment {
 
 /// RPC statement
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rpc {
     pub name: String,
-    pub if_features: Vec<String>,
-    pub must: Vec<Must>,
+    pub if_features: IfFeatures,
+    pub must: Box<[Must]>,
     pub status: Option<Status>,
     pub description: Option<String>,
     pub reference: Option<String>,
@@ -448,24 +571,27 @@ pub struct Rpc {
 
 /// Input statement
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Input {
-    pub must: Vec<Must>,
+    pub must: Box<[Must]>,
     pub data_defs: Vec<DataDef>,
 }
 
 /// Output statement
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Output {
-    pub must: Vec<Must>,
+    pub must: Box<[Must]>,
     pub data_defs: Vec<DataDef>,
 }
 
 /// Action statement
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Action {
     pub name: String,
-    pub if_features: Vec<String>,
-    pub must: Vec<Must>,
+    pub if_features: IfFeatures,
+    pub must: Box<[Must]>,
     pub status: Option<Status>,
     pub description: Option<String>,
     pub reference: Option<String>,
@@ -475,10 +601,11 @@ pub struct Action {
 
 /// Notification statement
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Notification {
     pub name: String,
-    pub if_features: Vec<String>,
-    pub must: Vec<Must>,
+    pub if_features: IfFeatures,
+    pub must: Box<[Must]>,
     pub status: Option<Status>,
     pub description: Option<String>,
     pub reference: Option<String>,
@@ -487,6 +614,7 @@ pub struct Notification {
 
 /// Deviation statement
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Deviation {
     pub target: String,
     pub description: Option<String>,
@@ -499,9 +627,10 @@ pub struct Deviation {
 
 /// Deviate add
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DeviateAdd {
     pub units: Option<String>,
-    pub must: Vec<Must>,
+    pub must: Box<[Must]>,
     pub unique: Vec<String>,
     pub default: Vec<String>,
     pub config: Option<bool>,
@@ -512,15 +641,17 @@ pub struct DeviateAdd {
 
 /// Deviate delete
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DeviateDelete {
     pub units: Option<String>,
-    pub must: Vec<Must>,
+    pub must: Box<[Must]>,
     pub unique: Vec<String>,
     pub default: Vec<String>,
 }
 
 /// Deviate replace
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DeviateReplace {
     pub type_info: Option<TypeInfo>,
     pub units: Option<String>,
@@ -533,10 +664,11 @@ pub struct DeviateReplace {
 
 /// Refine statement
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Refine {
     pub target: String,
-    pub if_features: Vec<String>,
-    pub must: Vec<Must>,
+    pub if_features: IfFeatures,
+    pub must: Box<[Must]>,
     pub presence: Option<String>,
     pub default: Vec<String>,
     pub config: Option<bool>,
@@ -549,6 +681,7 @@ pub struct Refine {
 
 /// Must statement
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Must {
     pub condition: String,
     pub error_message: Option<String>,
@@ -559,6 +692,7 @@ pub struct Must {
 
 /// When statement
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct When {
     pub condition: String,
     pub description: Option<String>,
@@ -567,6 +701,7 @@ pub struct When {
 
 /// Max elements value
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MaxElements {
     #[default]
     Unbounded,
@@ -575,6 +710,7 @@ pub enum MaxElements {
 
 /// Ordered by value
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum OrderedBy {
     User,
     #[default]
@@ -582,10 +718,34 @@ pub enum OrderedBy {
 }
 
 /// Status value
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Status {
     #[default]
     Current,
     Obsolete,
     Deprecated,
 }
+
+// Full vendor model sets have millions of schema nodes, so the node enums hold their nodes behind a pointer, and rarely
+// set parts of the nodes, like when statements, type bodies and the statements of NodeMeta, are boxed. The bounds are
+// the sizes of the nodes with those parts boxed, so a field added inline to a node fails here rather than growing
+// every node of every model.
+const _: () = {
+    use std::mem::size_of;
+
+    assert!(size_of::<SchemaNode>() <= 16);
+    assert!(size_of::<DataDef>() <= 16);
+    assert!(size_of::<Case>() <= 16);
+    assert!(size_of::<ShortCase>() <= 16);
+    assert!(size_of::<TypeInfo>() <= 24);
+    assert!(size_of::<Meta>() == 8);
+    assert!(size_of::<Container>() <= 184);
+    assert!(size_of::<Leaf>() <= 144);
+    assert!(size_of::<LeafList>() <= 176);
+    assert!(size_of::<List>() <= 264);
+    assert!(size_of::<Choice>() <= 128);
+    assert!(size_of::<LongCase>() <= 96);
+    assert!(size_of::<Anydata>() <= 96);
+    assert!(size_of::<Anyxml>() <= 96);
+};
@@ -1,61 +1,284 @@
 use std::{
     collections::{HashMap, HashSet},
-    fs,
-    path::Path,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
 };
 
+use rayon::prelude::*;
+
 use crate::{
-    error::ParserError,
+    error::{DiagnosticSink, ErrorKind, ParserError, Warning, WarningKind},
+    leafref,
     model::{Import, Module, ReferenceNodes, Submodule, YangModule},
     parser::YangParser,
+    registry::{self, ModuleSource},
     resolver::ReferenceResolver,
+    schema,
+    symbol::Interner,
+    ErrorPolicy, ModuleRegistry, SeverityPolicy, Symbol,
 };
 
+/// The reference nodes, namespace and imports of an imported module, which is all the modules importing it need from it.
+#[derive(Debug, Clone)]
+pub struct ImportSummary {
+    reference_nodes: ReferenceNodes,
+    namespace: String,
+    imports: Vec<Import>,
+}
+
+/// What the loaders of the modules of a scan share about the modules they import: the summaries of the imported modules,
+/// keyed by their file, so each of them is only parsed once and their full trees are dropped, and the files the modules
+/// imported from each directory were found in, so each of them is only looked for once per directory.
+#[derive(Debug, Default)]
+pub struct ImportCache {
+    summaries: Mutex<HashMap<PathBuf, ImportSummary>>,
+    paths: Mutex<HashMap<ImportKey, PathBuf>>,
+}
+
+// The directory a module is imported from, with the name and revision of the module.
+type ImportKey = (PathBuf, Symbol, Option<String>);
+
+// An imported module, which was summarized by the loader of another module of a scan, or parsed from its file.
+#[allow(clippy::large_enum_variant)]
+enum Loaded {
+    Cached(ImportSummary),
+    Parsed(PathBuf, YangParser, YangModule),
+}
+
 /// Internal struct that handles loading, importing and including YANG modules and their dependencies.
 pub struct ModuleLoader {
     // Track imported modules by their names.
-    imported_modules: HashMap<String, ReferenceNodes>,
+    imported_modules: HashMap<Symbol, ReferenceNodes>,
+    imported_namespaces: HashMap<Symbol, String>,
     // Map from prefix to module name.
-    prefix_to_module: HashMap<String, String>,
+    prefix_to_module: HashMap<Symbol, Symbol>,
+    // Identifiers of all the loaded modules, shared by their parsers.
+    interner: Interner,
+    // Directories searched for imported and included modules after the directory of the module importing them.
+    search_paths: Vec<PathBuf>,
+    // Sources read before the file system.
+    registry: Option<Arc<ModuleRegistry>>,
+    // Where the modules which aren't in the search paths are fetched from.
+    source: Option<Arc<dyn ModuleSource>>,
+    // Summaries and files of imported modules shared with the loaders of the other modules of a scan.
+    import_cache: Option<Arc<ImportCache>>,
+    // Leafrefs are left unresolved, for resolving the types of the nodes on demand.
+    lazy_leafrefs: bool,
+    // Whether loading stops at the first error, or skips the imports and includes which fail and collects the errors.
+    error_policy: ErrorPolicy,
+    errors: Vec<ParserError>,
+    // The errors of imports and includes which are demoted to warnings.
+    severity_policy: SeverityPolicy,
+    // Receives the warnings found while loading and resolving the module.
+    warnings: Option<Arc<dyn DiagnosticSink>>,
 }
 
 impl ModuleLoader {
     pub fn new() -> Self {
         Self {
             imported_modules: HashMap::new(),
+            imported_namespaces: HashMap::new(),
             prefix_to_module: HashMap::new(),
+            interner: Interner::default(),
+            search_paths: Vec::new(),
+            registry: None,
+            source: None,
+            import_cache: None,
+            lazy_leafrefs: false,
+            error_policy: ErrorPolicy::default(),
+            errors: Vec::new(),
+            severity_policy: SeverityPolicy::default(),
+            warnings: None,
+        }
+    }
+
+    pub fn with_search_paths(search_paths: Vec<PathBuf>) -> Self {
+        Self {
+            search_paths,
+            ..Self::new()
+        }
+    }
+
+    pub fn with_import_cache(self, import_cache: Option<Arc<ImportCache>>) -> Self {
+        Self { import_cache, ..self }
+    }
+
+    pub fn with_registry(self, registry: Option<Arc<ModuleRegistry>>) -> Self {
+        Self { registry, ..self }
+    }
+
+    pub fn with_source(self, source: Option<Arc<dyn ModuleSource>>) -> Self {
+        Self { source, ..self }
+    }
+
+    pub fn with_lazy_leafrefs(self, lazy_leafrefs: bool) -> Self {
+        Self { lazy_leafrefs, ..self }
+    }
+
+    pub fn with_error_policy(self, error_policy: ErrorPolicy) -> Self {
+        Self { error_policy, ..self }
+    }
+
+    pub fn with_severity_policy(self, severity_policy: SeverityPolicy) -> Self {
+        Self {
+            severity_policy,
+            ..self
         }
     }
 
+    pub fn with_warnings(self, warnings: Option<Arc<dyn DiagnosticSink>>) -> Self {
+        Self { warnings, ..self }
+    }
+
+    /// Keep the error of the statement of the module to return it after loading, if errors are collected, or return it
+    /// to stop loading. Errors demoted to warnings are sent to the sink instead.
+    fn recover(&mut self, module: &str, statement: String, error: ParserError) -> Result<(), ParserError> {
+        let Some(error) = self
+            .severity_policy
+            .demote(error, self.warnings.as_ref(), module, Some(statement))
+        else {
+            return Ok(());
+        };
+        match self.error_policy {
+            ErrorPolicy::FailFast => Err(error),
+            ErrorPolicy::CollectAll => {
+                self.errors.push(error);
+                Ok(())
+            }
+        }
+    }
+
+    // The summary of the module in the file, if another module of the scan imported it already.
+    fn cached_import(&self, module_path: &Path) -> Option<ImportSummary> {
+        let import_cache = self.import_cache.as_ref()?;
+        let summaries = import_cache
+            .summaries
+            .lock()
+            .expect("the import cache to not be poisoned");
+        summaries.get(module_path).cloned()
+    }
+
+    /// The file of the module imported from the file at the base path, like [Self::module_path], which is only looked
+    /// for once per directory in a scan, as looking for it reads the directories of the search paths, and may fetch it.
+    fn import_path(&self, base_path: &Path, import: &Import) -> Result<PathBuf, ParserError> {
+        let revision = import.revision_date.as_deref();
+        let Some(import_cache) = &self.import_cache else {
+            return self.module_path(base_path, &import.module, revision);
+        };
+
+        let directory = base_path.parent().unwrap_or(Path::new("")).to_path_buf();
+        let key = (directory, import.module.clone(), import.revision_date.clone());
+        let paths = || import_cache.paths.lock().expect("the import cache to not be poisoned");
+        if let Some(path) = paths().get(&key) {
+            return Ok(path.clone());
+        }
+        let path = self.module_path(base_path, &import.module, revision)?;
+        paths().insert(key, path.clone());
+        Ok(path)
+    }
+
+    /// Find the file of the module or submodule imported or included from the file at the base path, like
+    /// [crate::find_module_file] does, looking in the registry as well, and fetch it from the source if it's not
+    /// there. If the file isn't found, the path in the directory of the base path is returned, so reading it fails with
+    /// the error of the missing file.
+    fn module_path(&self, base_path: &Path, module_name: &str, revision: Option<&str>) -> Result<PathBuf, ParserError> {
+        let registry = self.registry.as_deref();
+        let missing_path = || {
+            base_path
+                .parent()
+                .unwrap_or_else(|| Path::new("."))
+                .join(format!("{}.yang", module_name))
+        };
+        if let Some(path) = registry::find_module_file(registry, base_path, &self.search_paths, module_name) {
+            return Ok(path);
+        }
+        let fetched = match &self.source {
+            Some(source) => source
+                .find_module(module_name, revision)
+                .map_err(|e| ParserError::from(e).in_file(&missing_path()))?,
+            None => None,
+        };
+        Ok(fetched.unwrap_or_else(missing_path))
+    }
+
+    fn read_file(&self, path: &Path) -> Result<String, ParserError> {
+        registry::read(self.registry.as_deref(), path)
+    }
+
+    /// Read and parse a YANG file with a new parser sharing the loader's interner, returning the parser with the
+    /// imports and reference nodes it collected.
+    fn parse_file(&self, path: &Path) -> Result<(YangParser, YangModule), ParserError> {
+        let content = self.read_file(path)?;
+        self.parse_source(path, &content)
+    }
+
+    fn parse_source(&self, path: &Path, content: &str) -> Result<(YangParser, YangModule), ParserError> {
+        let mut parser = YangParser::with_interner(self.interner.clone());
+        let yangfile = parser.parse(content).map_err(|e| e.in_file(path))?;
+        Ok((parser, yangfile))
+    }
+
     /// Load a YANG file from the given path, processing all imports and includes.
     pub fn load_file<P: AsRef<Path>>(mut self, path: P) -> Result<YangModule, ParserError> {
         let path = path.as_ref();
-        let content = fs::read_to_string(path).map_err(ParserError::InvalidFile)?;
 
         // Create a new YangParser and parse the initial module.
-        let mut parser = YangParser::new();
-        let mut result = parser.parse(&content)?;
+        let content = self.read_file(path)?;
+        let (mut parser, mut result) = self.parse_source(path, &content)?;
 
         // The entrypoint for parsing should always be a module, not a submodule.
-        let mut module = match &mut result {
+        let module = match &mut result {
             YangModule::Module(module) => module,
-            YangModule::Submodule(_) => return Err(ParserError::InvalidParserEntrypoint),
+            YangModule::Submodule(_) => return Err(ParserError::from(ErrorKind::InvalidParserEntrypoint).in_file(path)),
         };
 
+        // Imports of the module itself are checked before the imports of its submodules are added.
+        self.warn_unused_imports(&module.name, &parser.imports, &content);
+
         // Process all included submodules and add their nodes to the main module.
-        self.process_includes(path, &mut module, &mut parser)?;
+        self.process_includes(path, module, &mut parser)?;
 
         // Collect imports from the parser, parse them and merge their reference nodes.
         let imports = parser.imports;
         self.process_imports(path, &module.name, imports)?;
 
+        // Keep the reference information on the module, so users can look up typedefs, identities and such.
+        module.reference_nodes = parser.reference_nodes.clone();
+        module.imported_modules = self.imported_modules.clone();
+        module.imported_namespaces = std::mem::take(&mut self.imported_namespaces);
+        module.prefix_to_module = self.prefix_to_module.clone();
+        module.augments = std::mem::take(&mut parser.augments);
+        module.deviations = std::mem::take(&mut parser.deviations);
+
+        // The module is still resolved with the imports and includes which loaded, but the errors are returned.
+        let errors = std::mem::take(&mut self.errors);
+
         // Create resolver with all reference information (local and imported)
-        let resolver = ReferenceResolver::new(parser.reference_nodes, self.imported_modules, self.prefix_to_module);
+        let resolver = ReferenceResolver::new(
+            module.name.clone(),
+            parser.reference_nodes,
+            self.imported_modules,
+            self.prefix_to_module,
+            self.interner,
+        )
+        .with_warnings(self.warnings.clone());
 
         // Walk the entire tree and resolve any references.
         resolver.resolve_references(module);
 
-        Ok(result)
+        // Apply the augments and deviations of the module targeting the module itself to the resolved tree.
+        let warnings = schema::apply_own(module);
+        if let Some(sink) = &self.warnings {
+            warnings.into_iter().for_each(|warning| sink.warning(warning));
+        }
+        if !self.lazy_leafrefs {
+            leafref::resolve_leafrefs(module);
+        }
+
+        match errors.is_empty() {
+            true => Ok(result),
+            false => Err(ParserError::collected(errors)),
+        }
     }
 
     /// Recursively process includes found in the main module and any nested includes.
@@ -69,28 +292,70 @@ impl ModuleLoader {
         let includes = parser.take_includes();
 
         for include in includes {
-            let parent_dir = base_path.as_ref().parent().unwrap_or_else(|| Path::new("."));
-            let submodule_path = parent_dir.join(format!("{}.yang", include.module));
-            let submodule_content = fs::read_to_string(&submodule_path).map_err(ParserError::InvalidFile)?;
-            let yangfile = parser.parse(&submodule_content)?;
-
-            if let YangModule::Submodule(submodule) = yangfile {
-                // Recursively process any includes in this submodule.
-                self.process_includes(&submodule_path, module, parser)?;
-
-                // After processing nested includes, merge the submodule's nodes into the main module.
-                self.merge_submodule_into_module(&submodule, module);
-            } else {
-                // This should never happen as included files should always be submodules.
-                return Err(ParserError::InvalidInclude(
-                    submodule_path.to_string_lossy().into_owned(),
-                ));
+            let revision = include.revision_date.as_deref();
+            let included = self
+                .module_path(base_path.as_ref(), &include.module, revision)
+                .and_then(|submodule_path| self.include_submodule(&submodule_path, module, parser));
+            if let Err(error) = included {
+                self.recover(&module.name, format!("include {}", include.module), error)?;
             }
         }
 
         Ok(())
     }
 
+    /// Parse the included submodule in the file, with its own includes, and merge it into the module.
+    fn include_submodule(
+        &mut self,
+        submodule_path: &Path,
+        module: &mut Module,
+        parser: &mut YangParser,
+    ) -> Result<(), ParserError> {
+        let submodule_content = self.read_file(submodule_path)?;
+        let yangfile = parser
+            .parse(&submodule_content)
+            .map_err(|e| e.in_file(submodule_path))?;
+
+        let YangModule::Submodule(submodule) = yangfile else {
+            // This should never happen as included files should always be submodules.
+            return Err(
+                ParserError::from(ErrorKind::InvalidInclude(submodule_path.to_string_lossy().into_owned()))
+                    .with_module(module.name.clone())
+                    .in_file(submodule_path),
+            );
+        };
+
+        // Recursively process any includes in this submodule.
+        self.process_includes(submodule_path, module, parser)?;
+
+        // After processing nested includes, merge the submodule's nodes into the main module.
+        self.merge_submodule_into_module(&submodule, module);
+
+        Ok(())
+    }
+
+    // Imports whose prefix isn't followed by a colon anywhere in the source of the module are never used. The source
+    // is searched rather than the parsed statements, as prefixes are used in all kinds of arguments, like paths.
+    fn warn_unused_imports(&self, module_name: &Symbol, imports: &[Import], content: &str) {
+        let Some(warnings) = &self.warnings else {
+            return;
+        };
+
+        for import in imports {
+            if !content.contains(&format!("{}:", import.prefix)) {
+                warnings.warning(Warning {
+                    kind: WarningKind::UnusedImport {
+                        module: import.module.clone(),
+                        prefix: import.prefix.clone(),
+                    },
+                    module: module_name.clone(),
+                    statement: Some(format!("import {}", import.module)),
+                    path: None,
+                });
+            }
+        }
+    }
+
     /// Merge a submodule's content into the main module
     fn merge_submodule_into_module(&self, submodule: &Submodule, module: &mut Module) {
         // Merge body nodes from submodule into the main module
@@ -117,56 +382,119 @@ impl ModuleLoader {
 
         // Track processed modules to avoid parsing the same module twice.
         let mut processed_modules = HashSet::new();
-        processed_modules.insert(current_module.to_string());
+        processed_modules.insert(self.interner.intern(current_module));
 
+        let base_path = base_path.as_ref();
         while !imports_to_process.is_empty() {
-            // Probably not optimal, but I don't think it matters that much here.
-            let import = imports_to_process.remove(0);
+            let imports = std::mem::take(&mut imports_to_process);
 
-            // Skip if we've already processed this module.
-            if self.imported_modules.contains_key(&import.module) || processed_modules.contains(&import.module) {
-                // Just update the prefix mapping to map the new prefix to existing module.
-                self.prefix_to_module
-                    .insert(import.prefix.clone(), import.module.clone());
-                continue;
+            // The modules imported by the previous round of modules don't depend on each other to be parsed, so the new
+            // ones are read and parsed in parallel. They are merged below in the order they were imported, so the
+            // result is the same as parsing them one by one. The file of each of them is looked for once.
+            let mut new_modules: Vec<&Import> = Vec::new();
+            for import in &imports {
+                let module = &import.module;
+                if !self.imported_modules.contains_key(module)
+                    && !processed_modules.contains(module)
+                    && !new_modules.iter().any(|new| &new.module == module)
+                {
+                    new_modules.push(import);
+                }
             }
+            let mut loaded_modules: HashMap<Symbol, _> = new_modules
+                .into_par_iter()
+                .map(|import| {
+                    let loaded = self.import_path(base_path, import).and_then(|module_path| {
+                        // Modules summarized by an earlier module of a scan aren't parsed again.
+                        if let Some(summary) = self.cached_import(&module_path) {
+                            return Ok(Loaded::Cached(summary));
+                        }
+                        let (parser, yangfile) = self.parse_file(&module_path)?;
+                        Ok(Loaded::Parsed(module_path, parser, yangfile))
+                    });
+                    (import.module.clone(), loaded)
+                })
+                .collect();
 
-            // Mark this module as processed
-            processed_modules.insert(import.module.clone());
-
-            let parent_dir = base_path.as_ref().parent().unwrap_or_else(|| Path::new("."));
-            let module_path = parent_dir.join(format!("{}.yang", import.module));
-
-            // Setup new YangParser for the imported module and parse it fully.
-            let module_content = fs::read_to_string(&module_path).map_err(ParserError::InvalidFile)?;
-            let mut module_parser = YangParser::new();
-            let yangfile = module_parser.parse(&module_content)?;
-
-            match yangfile {
-                YangModule::Module(mut module) => {
-                    // First, process includes in this module to make sure all submodule content is merged.
-                    self.process_includes(&module_path, &mut module, &mut module_parser)?;
-
-                    // Store the prefix mapping.
+            for import in imports {
+                // Skip if we've already processed this module.
+                if self.imported_modules.contains_key(&import.module) || processed_modules.contains(&import.module) {
+                    // Just update the prefix mapping to map the new prefix to existing module.
                     self.prefix_to_module
                         .insert(import.prefix.clone(), import.module.clone());
+                    continue;
+                }
 
-                    // Store the imported module's reference nodes.
-                    self.imported_modules
-                        .insert(import.module.clone(), module_parser.reference_nodes);
+                // Mark this module as processed
+                processed_modules.insert(import.module.clone());
 
-                    // Add any nested imports to our processing queue.
-                    for nested_import in module_parser.imports {
-                        imports_to_process.push(nested_import);
+                let loaded = loaded_modules
+                    .remove(&import.module)
+                    .expect("every new module to be loaded");
+                // Imports which fail to load are left out of the module if errors are collected.
+                let summary = loaded.and_then(|loaded| match loaded {
+                    Loaded::Cached(summary) => Ok(summary),
+                    Loaded::Parsed(module_path, parser, yangfile) => {
+                        self.summarize_import(module_path, parser, yangfile)
                     }
-                }
-                YangModule::Submodule(_) => {
-                    // This should never happen as imported files should always be modules
-                    return Err(ParserError::InvalidImport(module_path.to_string_lossy().into_owned()));
-                }
+                });
+                let summary = match summary {
+                    Ok(summary) => summary,
+                    Err(error) => {
+                        self.recover(current_module, format!("import {}", import.module), error)?;
+                        continue;
+                    }
+                };
+
+                // Store the prefix mapping.
+                self.prefix_to_module
+                    .insert(import.prefix.clone(), import.module.clone());
+
+                // Store the imported module's reference nodes.
+                self.imported_modules
+                    .insert(import.module.clone(), summary.reference_nodes);
+                self.imported_namespaces
+                    .insert(import.module.clone(), summary.namespace);
+
+                // Add any nested imports to our processing queue.
+                imports_to_process.extend(summary.imports);
             }
         }
 
         Ok(())
     }
+
+    /// Process the includes of a parsed imported module, and keep what the modules importing it need from it.
+    fn summarize_import(
+        &mut self,
+        module_path: PathBuf,
+        mut module_parser: YangParser,
+        yangfile: YangModule,
+    ) -> Result<ImportSummary, ParserError> {
+        let YangModule::Module(mut module) = yangfile else {
+            // This should never happen as imported files should always be modules
+            return Err(
+                ParserError::from(ErrorKind::InvalidImport(module_path.to_string_lossy().into_owned()))
+                    .in_file(&module_path),
+            );
+        };
+
+        // First, process includes in this module to make sure all submodule content is merged.
+        self.process_includes(&module_path, &mut module, &mut module_parser)?;
+
+        let summary = ImportSummary {
+            reference_nodes: module_parser.reference_nodes,
+            namespace: module.namespace,
+            imports: module_parser.imports,
+        };
+        if let Some(import_cache) = &self.import_cache {
+            let mut summaries = import_cache
+                .summaries
+                .lock()
+                .expect("the import cache to not be poisoned");
+            summaries.insert(module_path, summary.clone());
+        }
+
+        Ok(summary)
+    }
 }
@@ -1,9 +1,13 @@
+use std::{borrow::Cow, sync::Arc};
+
 use pest::{iterators::Pair, Parser};
 
 use crate::{
     error::ParserError,
     model::*,
     parser_internal::{Rule, YangFile},
+    symbol::Interner,
+    syntax, Symbol,
 };
 
 #[derive(Debug, Default)]
@@ -17,8 +21,8 @@ pub struct YangParser {
     pub reference_nodes: ReferenceNodes,
 
     // These node types are also not part of the data tree and will be processed at the end.
-    augments: Vec<Augment>,
-    deviations: Vec<Deviation>,
+    pub augments: Vec<Augment>,
+    pub deviations: Vec<Deviation>,
     extensions: Vec<Extension>,
 
     // Properties used during parsing.
@@ -29,7 +33,10 @@ pub struct YangParser {
     // current_belongs_to_prefix is used when parsing submodules to track which prefix they use for references
     // to nodes in the module they belong to. This prefix is stripped away as the nodes will be merged with the
     // original modules nodes anyway.
-    current_belongs_to_prefix: Option<String>,
+    current_belongs_to_prefix: Option<Symbol>,
+
+    // interner is shared with the parsers of the other modules in the module set, so their identifiers are shared.
+    interner: Interner,
 }
 
 impl YangParser {
@@ -40,6 +47,13 @@ impl YangParser {
         }
     }
 
+    pub fn with_interner(interner: Interner) -> Self {
+        Self {
+            interner,
+            ..Self::new()
+        }
+    }
+
     /// Take ownership of the current includes list and clear it
     pub fn take_includes(&mut self) -> Vec<Include> {
         std::mem::take(&mut self.includes)
@@ -50,7 +64,7 @@ impl YangParser {
     // works itself through the entire tree.
     pub fn parse(&mut self, input: &str) -> Result<YangModule, ParserError> {
         let module = YangFile::parse(Rule::file, input)
-            .map_err(ParserError::ParseError)?
+            .map_err(|error| ParserError::from(syntax::explain(error, input)))?
             .next()
             .expect("a yang file to always include a module");
 
@@ -65,58 +79,44 @@ impl YangParser {
         let mut module = Module::default();
 
         for child in input.into_inner() {
-            match child.as_rule() {
-                Rule::string => module.name = self.parse_string(child),
-                Rule::prefix => module.prefix = self.parse_string(child),
-                Rule::namespace => module.namespace = self.parse_string(child),
-                Rule::yang_version => module.yang_version = Some(self.parse_string(child)),
-                Rule::organization => module.meta.organization = Some(self.parse_string(child)),
-                Rule::contact => module.meta.contact = Some(self.parse_string(child)),
-                Rule::description => module.meta.description = Some(self.parse_string(child)),
-                Rule::reference => module.meta.reference = Some(self.parse_string(child)),
-                Rule::revision => module.revisions.push(self.parse_revision(child)),
-                Rule::import => self.parse_import(child),
-                Rule::include => self.parse_include(child),
-
-                // parse_body returns an option based on if the node it parsed was a data node or not.
-                // Data nodes return Some(node) while other nodes return None.
-                Rule::body => match self.parse_body(child) {
-                    Some(node) => module.body.push(node),
-                    None => {}
-                },
-                _ => unreachable!("Unexpected rule: {:?}", child.as_rule()),
-            }
+            self.parse_module_statement(&mut module, child);
         }
 
         module
     }
 
+    // parse_module_statement parses one of the top-level statements of a module, including its name, into the module.
+    // Documents parse the statements one by one, so they can re-parse the statements touched by an edit.
+    pub fn parse_module_statement(&mut self, module: &mut Module, child: Pair<Rule>) {
+        match child.as_rule() {
+            Rule::string => module.name = self.parse_symbol(child),
+            Rule::prefix => module.prefix = self.parse_symbol(child),
+            Rule::namespace => module.namespace = self.parse_string(child),
+            Rule::yang_version => module.yang_version = Some(self.parse_string(child)),
+            Rule::organization => module.meta.organization = Some(self.parse_string(child)),
+            Rule::contact => module.meta.contact = Some(self.parse_string(child)),
+            Rule::description => module.meta.description = Some(self.parse_string(child)),
+            Rule::reference => module.meta.reference = Some(self.parse_string(child)),
+            Rule::revision => module.revisions.push(self.parse_revision(child)),
+            Rule::import => self.parse_import(child),
+            Rule::include => self.parse_include(child),
+
+            // parse_body returns an option based on if the node it parsed was a data node or not.
+            // Data nodes return Some(node) while other nodes return None.
+            Rule::body => {
+                if let Some(node) = self.parse_body(child) {
+                    module.body.push(node)
+                }
+            }
+            _ => unreachable!("Unexpected rule: {:?}", child.as_rule()),
+        }
+    }
+
     fn parse_submodule(&mut self, input: Pair<Rule>) -> Submodule {
         let mut submodule = Submodule::default();
 
         for child in input.into_inner() {
-            match child.as_rule() {
-                Rule::string => submodule.name = self.parse_string(child),
-                Rule::belongs_to => {
-                    submodule.belongs_to = self.parse_belongs_to(child);
-                    // Store the prefix from the belongs-to statement for when parsing uses statements later.
-                    // See parse_uses function for more details.
-                    self.current_belongs_to_prefix = Some(submodule.belongs_to.prefix.clone());
-                }
-                Rule::yang_version => submodule.yang_version = Some(self.parse_string(child)),
-                Rule::organization => submodule.meta.organization = Some(self.parse_string(child)),
-                Rule::contact => submodule.meta.contact = Some(self.parse_string(child)),
-                Rule::description => submodule.meta.description = Some(self.parse_string(child)),
-                Rule::reference => submodule.meta.reference = Some(self.parse_string(child)),
-                Rule::revision => submodule.revisions.push(self.parse_revision(child)),
-                Rule::import => self.parse_import(child),
-                Rule::include => self.parse_include(child),
-                Rule::body => match self.parse_body(child) {
-                    Some(node) => submodule.body.push(node),
-                    None => {}
-                },
-                _ => unreachable!("Unexpected rule: {:?}", child.as_rule()),
-            }
+            self.parse_submodule_statement(&mut submodule, child);
         }
 
         // Clear the belongs-to prefix after parsing the submodule
@@ -125,6 +125,44 @@ impl YangParser {
         submodule
     }
 
+    // parse_submodule_statement parses one of the top-level statements of a submodule, like parse_module_statement.
+    pub fn parse_submodule_statement(&mut self, submodule: &mut Submodule, child: Pair<Rule>) {
+        match child.as_rule() {
+            Rule::string => submodule.name = self.parse_symbol(child),
+            Rule::belongs_to => {
+                submodule.belongs_to = self.parse_belongs_to(child);
+                // Store the prefix from the belongs-to statement for when parsing uses statements later.
+                // See parse_uses function for more details.
+                self.current_belongs_to_prefix = Some(submodule.belongs_to.prefix.clone());
+            }
+            Rule::yang_version => submodule.yang_version = Some(self.parse_string(child)),
+            Rule::organization => submodule.meta.organization = Some(self.parse_string(child)),
+            Rule::contact => submodule.meta.contact = Some(self.parse_string(child)),
+            Rule::description => submodule.meta.description = Some(self.parse_string(child)),
+            Rule::reference => submodule.meta.reference = Some(self.parse_string(child)),
+            Rule::revision => submodule.revisions.push(self.parse_revision(child)),
+            Rule::import => self.parse_import(child),
+            Rule::include => self.parse_include(child),
+            Rule::body => {
+                if let Some(node) = self.parse_body(child) {
+                    submodule.body.push(node)
+                }
+            }
+            _ => unreachable!("Unexpected rule: {:?}", child.as_rule()),
+        }
+    }
+
+    /// Set the prefix of the module a submodule belongs to, for parsing statements which follow its belongs-to
+    /// statement without parsing the belongs-to statement itself.
+    pub fn set_belongs_to_prefix(&mut self, prefix: Option<Symbol>) {
+        self.current_belongs_to_prefix = prefix;
+    }
+
+    /// The includes collected so far, without taking them.
+    pub fn includes(&self) -> &[Include] {
+        &self.includes
+    }
+
     // parse_body is a bit different than most parse functions as it might not always return the node is just parsed.
     // This is because a SchemaNode rerpresents both data nodes like containers and leaves, but also nodes like
     // groupings and typedefs, which are only referenced by other data nodes.
@@ -135,8 +173,8 @@ impl YangParser {
 
         match node.as_rule() {
             Rule::data_def => Some(SchemaNode::DataDef(self.parse_data_def(node))),
-            Rule::rpc => Some(SchemaNode::Rpc(self.parse_rpc(node))),
-            Rule::notification => Some(SchemaNode::Notification(self.parse_notification(node))),
+            Rule::rpc => Some(SchemaNode::Rpc(Box::new(self.parse_rpc(node)))),
+            Rule::notification => Some(SchemaNode::Notification(Box::new(self.parse_notification(node)))),
             Rule::extension => {
                 self.parse_extension(node);
                 None
@@ -154,7 +192,8 @@ impl YangParser {
                 None
             }
             Rule::augment => {
-                self.parse_augment(node);
+                let augment = self.parse_augment(node);
+                self.augments.push(augment);
                 None
             }
             Rule::deviation => {
@@ -177,12 +216,12 @@ impl YangParser {
                 Rule::string => anydata.name = self.parse_string(child),
                 Rule::when => anydata.when = Some(self.parse_when(child)),
                 Rule::if_feature => anydata.if_features.push(self.parse_string(child)),
-                Rule::must => anydata.must.push(self.parse_must(child)),
+                Rule::must => extend_boxed(&mut anydata.must, [self.parse_must(child)]),
                 Rule::config => anydata.config = Some(self.parse_boolean(child)),
                 Rule::mandatory => anydata.mandatory = Some(self.parse_boolean(child)),
-                Rule::status => anydata.status = Some(self.parse_status(child)),
-                Rule::description => anydata.description = Some(self.parse_string(child)),
-                Rule::reference => anydata.reference = Some(self.parse_string(child)),
+                Rule::status => anydata.meta.status = Some(self.parse_status(child)),
+                Rule::description => anydata.meta.description = Some(self.parse_string(child)),
+                Rule::reference => anydata.meta.reference = Some(self.parse_string(child)),
                 _ => unreachable!("Unexpected rule: {:?}", child.as_rule()),
             }
         }
@@ -198,12 +237,12 @@ impl YangParser {
                 Rule::string => anyxml.name = self.parse_string(child),
                 Rule::when => anyxml.when = Some(self.parse_when(child)),
                 Rule::if_feature => anyxml.if_features.push(self.parse_string(child)),
-                Rule::must => anyxml.must.push(self.parse_must(child)),
+                Rule::must => extend_boxed(&mut anyxml.must, [self.parse_must(child)]),
                 Rule::config => anyxml.config = Some(self.parse_boolean(child)),
                 Rule::mandatory => anyxml.mandatory = Some(self.parse_boolean(child)),
-                Rule::status => anyxml.status = Some(self.parse_status(child)),
-                Rule::description => anyxml.description = Some(self.parse_string(child)),
-                Rule::reference => anyxml.reference = Some(self.parse_string(child)),
+                Rule::status => anyxml.meta.status = Some(self.parse_status(child)),
+                Rule::description => anyxml.meta.description = Some(self.parse_string(child)),
+                Rule::reference => anyxml.meta.reference = Some(self.parse_string(child)),
                 _ => unreachable!("Unexpected rule: {:?}", child.as_rule()),
             }
         }
@@ -236,7 +275,7 @@ impl YangParser {
         for child in input.into_inner() {
             match child.as_rule() {
                 Rule::units => deviate.units = Some(self.parse_string(child)),
-                Rule::must => deviate.must.push(self.parse_must(child)),
+                Rule::must => extend_boxed(&mut deviate.must, [self.parse_must(child)]),
                 Rule::unique => deviate.unique.push(self.parse_string(child)),
                 Rule::default => deviate.default.push(self.parse_string(child)),
                 Rule::config => deviate.config = Some(self.parse_boolean(child)),
@@ -257,7 +296,7 @@ impl YangParser {
             match child.as_rule() {
                 Rule::units => deviate.units = Some(self.parse_string(child)),
                 Rule::default => deviate.default.push(self.parse_string(child)),
-                Rule::must => deviate.must.push(self.parse_must(child)),
+                Rule::must => extend_boxed(&mut deviate.must, [self.parse_must(child)]),
                 Rule::unique => deviate.unique.push(self.parse_string(child)),
                 _ => unreachable!("Unexpected rule: {:?}", child.as_rule()),
             }
@@ -289,14 +328,14 @@ impl YangParser {
         let node = input.into_inner().next().expect("to always have inner nodes");
 
         match node.as_rule() {
-            Rule::container => DataDef::Container(self.parse_container(node)),
-            Rule::leaf => DataDef::Leaf(self.parse_leaf(node)),
-            Rule::leaf_list => DataDef::LeafList(self.parse_leaf_list(node)),
-            Rule::list => DataDef::List(self.parse_list(node)),
-            Rule::choice => DataDef::Choice(self.parse_choice(node)),
-            Rule::anydata => DataDef::AnyData(self.parse_anydata(node)),
-            Rule::anyxml => DataDef::Anyxml(self.parse_anyxml(node)),
-            Rule::uses => DataDef::Uses(self.parse_uses(node)),
+            Rule::container => DataDef::Container(Arc::new(self.parse_container(node))),
+            Rule::leaf => DataDef::Leaf(Arc::new(self.parse_leaf(node))),
+            Rule::leaf_list => DataDef::LeafList(Arc::new(self.parse_leaf_list(node))),
+            Rule::list => DataDef::List(Arc::new(self.parse_list(node))),
+            Rule::choice => DataDef::Choice(Arc::new(self.parse_choice(node))),
+            Rule::anydata => DataDef::AnyData(Arc::new(self.parse_anydata(node))),
+            Rule::anyxml => DataDef::Anyxml(Arc::new(self.parse_anyxml(node))),
+            Rule::uses => DataDef::Uses(Arc::new(self.parse_uses(node))),
             _ => unreachable!("Unexpected rule: {:?}", node.as_rule()),
         }
     }
@@ -304,20 +343,20 @@ impl YangParser {
     fn parse_container(&mut self, input: Pair<Rule>) -> Container {
         let mut container = Container::default();
         let mut input = input.into_inner();
-        let name = self.parse_string(input.next().expect("first child to always be the name"));
-        container.name = name.clone();
+        let name = self.parse_str(input.next().expect("first child to always be the name"));
+        container.name = name.to_string();
 
-        self.with_path_scope(name, |this| {
+        self.with_path_scope(&name, |this| {
             for child in input {
                 match child.as_rule() {
                     Rule::when => container.when = Some(this.parse_when(child)),
                     Rule::if_feature => container.if_features.push(this.parse_string(child)),
-                    Rule::must => container.must.push(this.parse_must(child)),
-                    Rule::presence => container.presence = Some(this.parse_string(child)),
+                    Rule::must => extend_boxed(&mut container.must, [this.parse_must(child)]),
+                    Rule::presence => container.meta.presence = Some(this.parse_string(child)),
                     Rule::config => container.config = Some(this.parse_boolean(child)),
-                    Rule::status => container.status = Some(this.parse_status(child)),
-                    Rule::description => container.description = Some(this.parse_string(child)),
-                    Rule::reference => container.reference = Some(this.parse_string(child)),
+                    Rule::status => container.meta.status = Some(this.parse_status(child)),
+                    Rule::description => container.meta.description = Some(this.parse_string(child)),
+                    Rule::reference => container.meta.reference = Some(this.parse_string(child)),
                     Rule::type_def => this.parse_type_def(child),
                     Rule::grouping => this.parse_grouping(child),
                     Rule::data_def => container.data_defs.push(this.parse_data_def(child)),
@@ -342,10 +381,10 @@ impl YangParser {
                 Rule::default => choice.default = Some(self.parse_string(child)),
                 Rule::config => choice.config = Some(self.parse_boolean(child)),
                 Rule::mandatory => choice.mandatory = Some(self.parse_boolean(child)),
-                Rule::status => choice.status = Some(self.parse_status(child)),
-                Rule::description => choice.description = Some(self.parse_string(child)),
-                Rule::reference => choice.reference = Some(self.parse_string(child)),
-                Rule::long_case => choice.cases.push(Case::LongCase(self.parse_long_case(child))),
+                Rule::status => choice.meta.status = Some(self.parse_status(child)),
+                Rule::description => choice.meta.description = Some(self.parse_string(child)),
+                Rule::reference => choice.meta.reference = Some(self.parse_string(child)),
+                Rule::long_case => choice.cases.push(Case::LongCase(Arc::new(self.parse_long_case(child)))),
                 Rule::short_case => choice.cases.push(Case::ShortCase(self.parse_short_case(child))),
                 _ => unreachable!("Unexpected rule: {:?}", child.as_rule()),
             }
@@ -358,13 +397,13 @@ impl YangParser {
         let node = input.into_inner().next().expect("to always have inner node");
 
         match node.as_rule() {
-            Rule::choice => ShortCase::Choice(self.parse_choice(node)),
-            Rule::container => ShortCase::Container(self.parse_container(node)),
-            Rule::leaf => ShortCase::Leaf(self.parse_leaf(node)),
-            Rule::leaf_list => ShortCase::LeafList(self.parse_leaf_list(node)),
-            Rule::list => ShortCase::List(self.parse_list(node)),
-            Rule::anydata => ShortCase::Anydata(self.parse_anydata(node)),
-            Rule::anyxml => ShortCase::Anyxml(self.parse_anyxml(node)),
+            Rule::choice => ShortCase::Choice(Arc::new(self.parse_choice(node))),
+            Rule::container => ShortCase::Container(Arc::new(self.parse_container(node))),
+            Rule::leaf => ShortCase::Leaf(Arc::new(self.parse_leaf(node))),
+            Rule::leaf_list => ShortCase::LeafList(Arc::new(self.parse_leaf_list(node))),
+            Rule::list => ShortCase::List(Arc::new(self.parse_list(node))),
+            Rule::anydata => ShortCase::Anydata(Arc::new(self.parse_anydata(node))),
+            Rule::anyxml => ShortCase::Anyxml(Arc::new(self.parse_anyxml(node))),
             _ => unreachable!("Unexpected rule: {:?}", node.as_rule()),
         }
     }
@@ -377,9 +416,9 @@ impl YangParser {
                 Rule::string => case.name = self.parse_string(child),
                 Rule::when => case.when = Some(self.parse_when(child)),
                 Rule::if_feature => case.if_features.push(self.parse_string(child)),
-                Rule::status => case.status = Some(self.parse_status(child)),
-                Rule::description => case.description = Some(self.parse_string(child)),
-                Rule::reference => case.reference = Some(self.parse_string(child)),
+                Rule::status => case.meta.status = Some(self.parse_status(child)),
+                Rule::description => case.meta.description = Some(self.parse_string(child)),
+                Rule::reference => case.meta.reference = Some(self.parse_string(child)),
                 Rule::data_def => case.data_defs.push(self.parse_data_def(child)),
                 _ => unreachable!("Unexpected rule: {:?}", child.as_rule()),
             }
@@ -395,7 +434,7 @@ impl YangParser {
             match child.as_rule() {
                 // Uses statements primary function is to reference groupings to add nodes to the data tree.
                 Rule::string => {
-                    let grouping_name = self.parse_string(child);
+                    let grouping_name = self.parse_str(child);
 
                     // Handle different cases of prefixed names:
 
@@ -414,12 +453,12 @@ impl YangParser {
                     // Case 2: When a uses statement references a grouping from an imported module,
                     // we keep the prefix to find it in the correct imported module.
                     if grouping_name.contains(':') {
-                        uses.grouping = grouping_name;
+                        uses.grouping = grouping_name.into_owned();
                         continue;
                     }
 
                     // Case 3: If no prefix is present, it's a local reference
-                    uses.grouping = grouping_name;
+                    uses.grouping = grouping_name.into_owned();
                 }
                 Rule::when => uses.when = Some(self.parse_when(child)),
                 Rule::if_feature => uses.if_features.push(self.parse_string(child)),
@@ -427,7 +466,7 @@ impl YangParser {
                 Rule::description => uses.description = Some(self.parse_string(child)),
                 Rule::reference => uses.reference = Some(self.parse_string(child)),
                 Rule::refine => uses.refines.push(self.parse_refine(child)),
-                Rule::augment => self.parse_augment(child),
+                Rule::augment => uses.augments.push(self.parse_augment(child)),
                 _ => unreachable!("Unexpected rule: {:?}", child.as_rule()),
             }
         }
@@ -435,7 +474,7 @@ impl YangParser {
         uses
     }
 
-    fn parse_augment(&mut self, input: Pair<Rule>) {
+    fn parse_augment(&mut self, input: Pair<Rule>) -> Augment {
         let mut augment = Augment::default();
 
         for child in input.into_inner() {
@@ -447,14 +486,16 @@ impl YangParser {
                 Rule::description => augment.description = Some(self.parse_string(child)),
                 Rule::reference => augment.reference = Some(self.parse_string(child)),
                 Rule::data_def => augment.data_defs.push(self.parse_data_def(child)),
-                Rule::long_case => augment.cases.push(Case::LongCase(self.parse_long_case(child))),
+                Rule::long_case => augment
+                    .cases
+                    .push(Case::LongCase(Arc::new(self.parse_long_case(child)))),
                 Rule::action => augment.actions.push(self.parse_action(child)),
                 Rule::notification => augment.notifications.push(self.parse_notification(child)),
                 _ => unreachable!("Unexpected rule: {:?}", child.as_rule()),
             }
         }
 
-        self.augments.push(augment);
+        augment
     }
 
     fn parse_refine(&mut self, input: Pair<Rule>) -> Refine {
@@ -464,7 +505,7 @@ impl YangParser {
             match child.as_rule() {
                 Rule::string => refine.target = self.parse_string(child),
                 Rule::if_feature => refine.if_features.push(self.parse_string(child)),
-                Rule::must => refine.must.push(self.parse_must(child)),
+                Rule::must => extend_boxed(&mut refine.must, [self.parse_must(child)]),
                 Rule::presence => refine.presence = Some(self.parse_string(child)),
                 Rule::default => refine.default.push(self.parse_string(child)),
                 Rule::config => refine.config = Some(self.parse_boolean(child)),
@@ -483,10 +524,10 @@ impl YangParser {
     fn parse_output(&mut self, input: Pair<Rule>) -> Output {
         let mut output = Output::default();
 
-        self.with_path_scope("output".into(), |this| {
+        self.with_path_scope("output", |this| {
             for child in input.into_inner() {
                 match child.as_rule() {
-                    Rule::must => output.must.push(this.parse_must(child)),
+                    Rule::must => extend_boxed(&mut output.must, [this.parse_must(child)]),
                     Rule::type_def => this.parse_type_def(child),
                     Rule::grouping => this.parse_grouping(child),
                     Rule::data_def => output.data_defs.push(this.parse_data_def(child)),
@@ -501,10 +542,10 @@ impl YangParser {
     fn parse_input(&mut self, input: Pair<Rule>) -> Input {
         let mut new_input = Input::default();
 
-        self.with_path_scope("input".into(), |this| {
+        self.with_path_scope("input", |this| {
             for child in input.into_inner() {
                 match child.as_rule() {
-                    Rule::must => new_input.must.push(this.parse_must(child)),
+                    Rule::must => extend_boxed(&mut new_input.must, [this.parse_must(child)]),
                     Rule::type_def => this.parse_type_def(child),
                     Rule::grouping => this.parse_grouping(child),
                     Rule::data_def => new_input.data_defs.push(this.parse_data_def(child)),
@@ -519,16 +560,16 @@ impl YangParser {
     fn parse_rpc(&mut self, input: Pair<Rule>) -> Rpc {
         let mut rpc = Rpc::default();
         let mut input = input.into_inner();
-        let name = self.parse_string(input.next().expect("first child to always be the name"));
-        rpc.name = name.clone();
+        let name = self.parse_str(input.next().expect("first child to always be the name"));
+        rpc.name = name.to_string();
 
-        self.with_path_scope(name, |this| {
+        self.with_path_scope(&name, |this| {
             for child in input {
                 match child.as_rule() {
                     Rule::input => rpc.input = Some(this.parse_input(child)),
                     Rule::output => rpc.output = Some(this.parse_output(child)),
                     Rule::if_feature => rpc.if_features.push(this.parse_string(child)),
-                    Rule::must => rpc.must.push(this.parse_must(child)),
+                    Rule::must => extend_boxed(&mut rpc.must, [this.parse_must(child)]),
                     Rule::status => rpc.status = Some(this.parse_status(child)),
                     Rule::description => rpc.description = Some(this.parse_string(child)),
                     Rule::reference => rpc.reference = Some(this.parse_string(child)),
@@ -545,16 +586,16 @@ impl YangParser {
     fn parse_action(&mut self, input: Pair<Rule>) -> Action {
         let mut action = Action::default();
         let mut input = input.into_inner();
-        let name = self.parse_string(input.next().expect("first child to always be the name"));
-        action.name = name.clone();
+        let name = self.parse_str(input.next().expect("first child to always be the name"));
+        action.name = name.to_string();
 
-        self.with_path_scope(name, |this| {
+        self.with_path_scope(&name, |this| {
             for child in input {
                 match child.as_rule() {
                     Rule::input => action.input = Some(this.parse_input(child)),
                     Rule::output => action.output = Some(this.parse_output(child)),
                     Rule::if_feature => action.if_features.push(this.parse_string(child)),
-                    Rule::must => action.must.push(this.parse_must(child)),
+                    Rule::must => extend_boxed(&mut action.must, [this.parse_must(child)]),
                     Rule::status => action.status = Some(this.parse_status(child)),
                     Rule::description => action.description = Some(this.parse_string(child)),
                     Rule::reference => action.reference = Some(this.parse_string(child)),
@@ -571,15 +612,15 @@ impl YangParser {
     fn parse_notification(&mut self, input: Pair<Rule>) -> Notification {
         let mut notification = Notification::default();
         let mut input = input.into_inner();
-        let name = self.parse_string(input.next().expect("first child to always be the name"));
-        notification.name = name.clone();
+        let name = self.parse_str(input.next().expect("first child to always be the name"));
+        notification.name = name.to_string();
 
-        self.with_path_scope(name, |this| {
+        self.with_path_scope(&name, |this| {
             for child in input {
                 match child.as_rule() {
                     Rule::data_def => notification.data_defs.push(this.parse_data_def(child)),
                     Rule::if_feature => notification.if_features.push(this.parse_string(child)),
-                    Rule::must => notification.must.push(this.parse_must(child)),
+                    Rule::must => extend_boxed(&mut notification.must, [this.parse_must(child)]),
                     Rule::status => notification.status = Some(this.parse_status(child)),
                     Rule::description => notification.description = Some(this.parse_string(child)),
                     Rule::reference => notification.reference = Some(this.parse_string(child)),
@@ -630,8 +671,8 @@ impl YangParser {
         self.extensions.push(extension);
     }
 
-    fn parse_when(&mut self, input: Pair<Rule>) -> When {
-        let mut when = When::default();
+    fn parse_when(&mut self, input: Pair<Rule>) -> Box<When> {
+        let mut when = Box::<When>::default();
 
         for child in input.into_inner() {
             match child.as_rule() {
@@ -650,8 +691,8 @@ impl YangParser {
 
         for child in input.into_inner() {
             match child.as_rule() {
-                Rule::string => belongs_to.module = self.parse_string(child),
-                Rule::prefix => belongs_to.prefix = self.parse_string(child),
+                Rule::string => belongs_to.module = self.parse_symbol(child),
+                Rule::prefix => belongs_to.prefix = self.parse_symbol(child),
                 _ => unreachable!("Unexpected rule: {:?}", child.as_rule()),
             }
         }
@@ -674,10 +715,10 @@ impl YangParser {
     fn parse_grouping(&mut self, input: Pair<Rule>) {
         let mut grouping = Grouping::default();
         let mut input = input.into_inner();
-        let name = self.parse_string(input.next().expect("first child to always be the name"));
-        grouping.name = name.clone();
+        let name = self.parse_str(input.next().expect("first child to always be the name"));
+        grouping.name = name.to_string();
 
-        self.with_path_scope(name, |this| {
+        self.with_path_scope(&name, |this| {
             for child in input {
                 match child.as_rule() {
                     Rule::status => grouping.status = Some(this.parse_status(child)),
@@ -727,16 +768,16 @@ impl YangParser {
                 Rule::when => leaf_list.when = Some(self.parse_when(child)),
                 Rule::if_feature => leaf_list.if_features.push(self.parse_string(child)),
                 Rule::type_info => leaf_list.type_info = self.parse_type_info(child),
-                Rule::units => leaf_list.units = Some(self.parse_string(child)),
-                Rule::must => leaf_list.must.push(self.parse_must(child)),
+                Rule::units => leaf_list.meta.units = Some(self.parse_string(child)),
+                Rule::must => extend_boxed(&mut leaf_list.must, [self.parse_must(child)]),
                 Rule::default => leaf_list.default.push(self.parse_string(child)),
                 Rule::config => leaf_list.config = Some(self.parse_boolean(child)),
                 Rule::ordered_by => leaf_list.ordered_by = Some(self.parse_ordered_by(child)),
                 Rule::min_elements => leaf_list.min_elements = Some(self.parse_integer(child)),
                 Rule::max_elements => leaf_list.max_elements = Some(self.parse_max_elements(child)),
-                Rule::status => leaf_list.status = Some(self.parse_status(child)),
-                Rule::description => leaf_list.description = Some(self.parse_string(child)),
-                Rule::reference => leaf_list.reference = Some(self.parse_string(child)),
+                Rule::status => leaf_list.meta.status = Some(self.parse_status(child)),
+                Rule::description => leaf_list.meta.description = Some(self.parse_string(child)),
+                Rule::reference => leaf_list.meta.reference = Some(self.parse_string(child)),
                 _ => unreachable!("Unexpected rule: {:?}", child.as_rule()),
             }
         }
@@ -753,14 +794,14 @@ impl YangParser {
                 Rule::when => leaf.when = Some(self.parse_when(child)),
                 Rule::if_feature => leaf.if_features.push(self.parse_string(child)),
                 Rule::type_info => leaf.type_info = self.parse_type_info(child),
-                Rule::units => leaf.units = Some(self.parse_string(child)),
-                Rule::must => leaf.must.push(self.parse_must(child)),
+                Rule::units => leaf.meta.units = Some(self.parse_string(child)),
+                Rule::must => extend_boxed(&mut leaf.must, [self.parse_must(child)]),
                 Rule::default => leaf.default = Some(self.parse_string(child)),
                 Rule::config => leaf.config = Some(self.parse_boolean(child)),
                 Rule::mandatory => leaf.mandatory = Some(self.parse_boolean(child)),
-                Rule::status => leaf.status = Some(self.parse_status(child)),
-                Rule::description => leaf.description = Some(self.parse_string(child)),
-                Rule::reference => leaf.reference = Some(self.parse_string(child)),
+                Rule::status => leaf.meta.status = Some(self.parse_status(child)),
+                Rule::description => leaf.meta.description = Some(self.parse_string(child)),
+                Rule::reference => leaf.meta.reference = Some(self.parse_string(child)),
                 _ => unreachable!("Unexpected rule: {:?}", child.as_rule()),
             }
         }
@@ -771,24 +812,24 @@ impl YangParser {
     fn parse_list(&mut self, input: Pair<Rule>) -> List {
         let mut list = List::default();
         let mut input = input.into_inner();
-        let name = self.parse_string(input.next().expect("first child to always be the name"));
-        list.name = name.clone();
+        let name = self.parse_str(input.next().expect("first child to always be the name"));
+        list.name = name.to_string();
 
-        self.with_path_scope(name, |this| {
+        self.with_path_scope(&name, |this| {
             for child in input {
                 match child.as_rule() {
                     Rule::when => list.when = Some(this.parse_when(child)),
                     Rule::if_feature => list.if_features.push(this.parse_string(child)),
-                    Rule::must => list.must.push(this.parse_must(child)),
+                    Rule::must => extend_boxed(&mut list.must, [this.parse_must(child)]),
                     Rule::key => list.key = Some(this.parse_string(child)),
                     Rule::unique => list.unique.push(this.parse_string(child)),
                     Rule::config => list.config = Some(this.parse_boolean(child)),
                     Rule::min_elements => list.min_elements = Some(this.parse_integer(child)),
                     Rule::max_elements => list.max_elements = Some(this.parse_max_elements(child)),
                     Rule::ordered_by => list.ordered_by = Some(this.parse_ordered_by(child)),
-                    Rule::status => list.status = Some(this.parse_status(child)),
-                    Rule::description => list.description = Some(this.parse_string(child)),
-                    Rule::reference => list.reference = Some(this.parse_string(child)),
+                    Rule::status => list.meta.status = Some(this.parse_status(child)),
+                    Rule::description => list.meta.description = Some(this.parse_string(child)),
+                    Rule::reference => list.meta.reference = Some(this.parse_string(child)),
                     Rule::type_def => this.parse_type_def(child),
                     Rule::grouping => this.parse_grouping(child),
                     Rule::data_def => list.data_defs.push(this.parse_data_def(child)),
@@ -804,27 +845,29 @@ impl YangParser {
 
     fn parse_type_info(&mut self, input: Pair<Rule>) -> TypeInfo {
         let mut type_info = TypeInfo::default();
+        let mut type_body = None;
 
         for child in input.into_inner() {
             match child.as_rule() {
-                Rule::string => type_info.name = self.parse_string(child),
-                Rule::numberical_restriction => type_info.type_body = Some(self.parse_numerical(child)),
-                Rule::decimal64_specification => type_info.type_body = Some(self.parse_decimal(child)),
-                Rule::string_restriction => type_info.type_body = Some(self.parse_string_restriction(child)),
-                Rule::enum_specification => type_info.type_body = Some(self.parse_enum(child)),
-                Rule::leafref_specification => type_info.type_body = Some(self.parse_leafref(child)),
-                Rule::identityref_specification => type_info.type_body = Some(self.parse_identityref(child)),
-                Rule::bits_specification => type_info.type_body = Some(self.parse_bit_specification(child)),
-                Rule::binary_specification => type_info.type_body = Some(self.parse_binary_specification(child)),
-                Rule::union_specification => type_info.type_body = Some(self.parse_union_specification(child)),
-                Rule::instance_identifier_specification => {
-                    type_info.type_body = Some(self.parse_instance_identifier(child))
-                }
+                Rule::string => type_info.name = self.parse_symbol(child),
+                Rule::numberical_restriction => type_body = Some(self.parse_numerical(child)),
+                Rule::decimal64_specification => type_body = Some(self.parse_decimal(child)),
+                Rule::string_restriction => type_body = Some(self.parse_string_restriction(child)),
+                Rule::enum_specification => type_body = Some(self.parse_enum(child)),
+                Rule::leafref_specification => type_body = Some(self.parse_leafref(child)),
+                Rule::identityref_specification => type_body = Some(self.parse_identityref(child)),
+                Rule::bits_specification => type_body = Some(self.parse_bit_specification(child)),
+                Rule::binary_specification => type_body = Some(self.parse_binary_specification(child)),
+                Rule::union_specification => type_body = Some(self.parse_union_specification(child)),
+                Rule::instance_identifier_specification => type_body = Some(self.parse_instance_identifier(child)),
                 _ => unreachable!("Unexpected rule: {:?}", child.as_rule()),
             }
         }
 
-        return type_info;
+        // Identical type bodies, like the same string pattern repeated for many leaves, share one instance.
+        type_info.type_body = type_body.map(|type_body| self.interner.intern_type_body(type_body));
+
+        type_info
     }
 
     fn parse_union_specification(&mut self, input: Pair<Rule>) -> TypeBody {
@@ -865,7 +908,8 @@ impl YangParser {
     fn parse_identity(&mut self, input: Pair<Rule>) {
         let mut identity = Identity::default();
         let mut input = input.into_inner();
-        let name = input.next().expect("first child to always be the name");
+        let name = self.parse_string(input.next().expect("first child to always be the name"));
+        identity.name = name.clone();
 
         for child in input {
             match child.as_rule() {
@@ -914,10 +958,12 @@ impl YangParser {
             Some(require_instance) => TypeBody::Leafref {
                 path,
                 require_instance: Some(self.parse_boolean(require_instance)),
+                target: None,
             },
             None => TypeBody::Leafref {
                 path,
                 require_instance: None,
+                target: None,
             },
         }
     }
@@ -1119,7 +1165,12 @@ impl YangParser {
             .expect("max-elements to always have a max_elements_value as the only child");
 
         match max_elements.as_rule() {
-            Rule::integer => MaxElements::Value(self.parse_integer(max_elements)),
+            Rule::integer => MaxElements::Value(
+                max_elements
+                    .as_str()
+                    .parse()
+                    .expect("integer value to always be a valid integer"),
+            ),
             Rule::string => MaxElements::Unbounded,
             _ => unreachable!("Unexpected rule: {:?}", max_elements),
         }
@@ -1145,8 +1196,8 @@ impl YangParser {
 
         for child in input.into_inner() {
             match child.as_rule() {
-                Rule::string => import.module = self.parse_string(child),
-                Rule::prefix => import.prefix = self.parse_string(child),
+                Rule::string => import.module = self.parse_symbol(child),
+                Rule::prefix => import.prefix = self.parse_symbol(child),
                 Rule::revision_date => import.revision_date = Some(self.parse_string(child)),
                 Rule::description => import.description = Some(self.parse_string(child)),
                 Rule::reference => import.reference = Some(self.parse_string(child)),
@@ -1162,7 +1213,7 @@ impl YangParser {
 
         for child in input.into_inner() {
             match child.as_rule() {
-                Rule::string => include.module = self.parse_string(child),
+                Rule::string => include.module = self.parse_symbol(child),
                 Rule::revision_date => include.revision_date = Some(self.parse_string(child)),
                 Rule::description => include.description = Some(self.parse_string(child)),
                 Rule::reference => include.reference = Some(self.parse_string(child)),
@@ -1196,33 +1247,35 @@ impl YangParser {
             .expect("integer value to always be a valid integer")
     }
 
+    // parse_symbol parses a string which is an identifier repeated throughout the module set, like a module name,
+    // prefix or type name, into an interned symbol.
+    fn parse_symbol(&mut self, input: Pair<Rule>) -> Symbol {
+        let value = self.parse_str(input);
+        self.interner.intern(&value)
+    }
+
     fn parse_string(&mut self, input: Pair<Rule>) -> String {
+        self.parse_str(input).into_owned()
+    }
+
+    // parse_str parses a string borrowed from the input, so strings which are only looked at, interned or trimmed
+    // before they're stored aren't allocated. Only double-quoted strings with escapes or several lines, and strings
+    // concatenated with `+`, are owned.
+    fn parse_str<'i>(&self, input: Pair<'i, Rule>) -> Cow<'i, str> {
         let value = input
             .into_inner()
             .next()
             .expect("string to always have the string value as the only child");
-
-        match value.as_rule() {
-            Rule::string => self.parse_string(value),
-            Rule::unquoted_string => value.as_str().to_string(),
-            Rule::double_quoted_string => {
-                let s = value.as_str();
-                s[1..s.len() - 1].to_string()
-            }
-            Rule::single_quoted_string => {
-                let s = value.as_str();
-                s[1..s.len() - 1].to_string()
-            }
-            _ => unreachable!("Unexpected rule: {:?}", value.as_rule()),
-        }
+        string_value(value)
     }
 
-    fn with_path_scope<F, T>(&mut self, name: String, f: F) -> T
+    fn with_path_scope<F, T>(&mut self, name: &str, f: F) -> T
     where
         F: FnOnce(&mut Self) -> T,
     {
         let original_path_len = self.current_path.len();
-        self.current_path.push_str(format!("{}/", &name).as_ref());
+        self.current_path.push_str(name);
+        self.current_path.push('/');
 
         let result = f(self);
 
@@ -1256,3 +1309,83 @@ impl YangParser {
         self.reference_nodes.groupings.get(&path)
     }
 }
+
+/// The value of a string, which is the string without its quotes, with the escapes and indentation of double-quoted
+/// strings processed, and with the quoted strings joined by `+` concatenated, like RFC 7950 section 6.1.3 describes.
+/// It's borrowed from the input unless processing it changed it.
+pub(crate) fn string_value(pair: Pair<'_, Rule>) -> Cow<'_, str> {
+    match pair.as_rule() {
+        Rule::string => {
+            let mut parts = pair.into_inner().map(string_value);
+            let first = parts.next().unwrap_or_default();
+            parts.fold(first, |mut value, part| {
+                value.to_mut().push_str(&part);
+                value
+            })
+        }
+        Rule::single_quoted_string => {
+            let s = pair.as_str();
+            Cow::Borrowed(&s[1..s.len() - 1])
+        }
+        Rule::double_quoted_string => {
+            let start = pair.as_span().start();
+            let column = pair.get_input()[..start]
+                .rsplit('\n')
+                .next()
+                .map_or(0, |line| line.chars().map(|c| if c == '\t' { 8 } else { 1 }).sum());
+            let s = pair.as_str();
+            double_quoted(&s[1..s.len() - 1], column)
+        }
+        _ => Cow::Borrowed(pair.as_str()),
+    }
+}
+
+// The value of a double-quoted string, whose opening quote is at the column. The whitespace before each line break is
+// removed, and the indentation of each line after the first is removed up to the column after the quote. Escapes
+// other than \n, \t, \" and \\ are kept as they are.
+fn double_quoted(s: &str, column: usize) -> Cow<'_, str> {
+    if !s.contains(['\\', '\n']) {
+        return Cow::Borrowed(s);
+    }
+
+    let last = s.matches('\n').count();
+    let mut value = String::with_capacity(s.len());
+    for (idx, line) in s.split('\n').enumerate() {
+        let mut line = line.strip_suffix('\r').unwrap_or(line);
+        if idx > 0 {
+            value.push('\n');
+            let mut width = 0;
+            line = line.trim_start_matches(|c| {
+                width += match c {
+                    ' ' => 1,
+                    '\t' => 8,
+                    _ => return false,
+                };
+                width <= column + 1
+            });
+        }
+        let line = match idx == last {
+            true => line,
+            false => line.trim_end_matches([' ', '\t']),
+        };
+
+        let mut chars = line.chars();
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                value.push(c);
+                continue;
+            }
+            match chars.clone().next() {
+                Some('n') => value.push('\n'),
+                Some('t') => value.push('\t'),
+                Some(c @ ('"' | '\\')) => value.push(c),
+                _ => {
+                    value.push('\\');
+                    continue;
+                }
+            }
+            chars.next();
+        }
+    }
+    Cow::Owned(value)
+}
@@ -0,0 +1,125 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use crate::error::ParserError;
+
+/// A place modules which aren't found in the search paths are fetched from, like the [crate::catalog::YangCatalog] of
+/// published modules, set with [crate::ParseOptions::source]. Modules are fetched into files, which are parsed like
+/// the files of the search paths, so sources can cache them.
+pub trait ModuleSource: Send + Sync {
+    /// The file of the module or submodule at the revision, or at its latest revision if there's none, or `None` if the
+    /// source doesn't have it.
+    fn find_module(&self, module: &str, revision: Option<&str>) -> std::io::Result<Option<PathBuf>>;
+}
+
+impl std::fmt::Debug for dyn ModuleSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ModuleSource")
+    }
+}
+
+/// YANG sources held in memory, which modules and the modules they import are loaded from before the file system, like
+/// in a browser, which has no file system, or for modules open in an editor which aren't saved. Sources are keyed by the
+/// path their file would have, and imports are looked up the way they are in files: in the directory of the module
+/// importing them, then in the search paths, by name or by name and revision.
+///
+/// On `wasm32-unknown-unknown`, where there's no file system, modules are only loaded from the registry.
+#[derive(Debug, Clone, Default)]
+pub struct ModuleRegistry {
+    sources: BTreeMap<PathBuf, String>,
+}
+
+impl ModuleRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the source of the file at the path, like "models/ietf-interfaces.yang", replacing the source it had.
+    pub fn add(&mut self, path: impl Into<PathBuf>, source: impl Into<String>) {
+        self.sources.insert(path.into(), source.into());
+    }
+
+    /// Add the source like [ModuleRegistry::add], for building a registry in one expression.
+    pub fn with(mut self, path: impl Into<PathBuf>, source: impl Into<String>) -> Self {
+        self.add(path, source);
+        self
+    }
+
+    /// The source of the file at the path.
+    pub fn get(&self, path: &Path) -> Option<&str> {
+        self.sources.get(path).map(String::as_str)
+    }
+
+    // The file of the module in the directory, which is named after the module, or after the module and its latest
+    // revision.
+    fn find_in(&self, dir: &Path, file_name: &str, revision_prefix: &str) -> Option<PathBuf> {
+        let path = dir.join(file_name);
+        if self.sources.contains_key(&path) {
+            return Some(path);
+        }
+        self.sources
+            .keys()
+            .filter(|path| path.parent() == Some(dir))
+            .filter_map(|path| path.file_name()?.to_str())
+            .filter(|name| name.starts_with(revision_prefix) && name.ends_with(".yang"))
+            .max()
+            .map(|name| dir.join(name))
+    }
+}
+
+// Read the file from the registry, or from the file system if it's not there and there is one.
+pub(crate) fn read(registry: Option<&ModuleRegistry>, path: &Path) -> Result<String, ParserError> {
+    if let Some(source) = registry.and_then(|registry| registry.get(path)) {
+        return Ok(source.to_string());
+    }
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    let result = std::fs::read_to_string(path);
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    let result = Err(std::io::Error::new(
+        std::io::ErrorKind::NotFound,
+        "the module is not in the registry",
+    ));
+    result.map_err(|e| ParserError::from(e).in_file(path))
+}
+
+// Find the file of the module imported or included from the base path like [crate::find_module_file], where each
+// directory is searched in the registry before the file system.
+pub(crate) fn find_module_file(
+    registry: Option<&ModuleRegistry>,
+    base_path: &Path,
+    search_paths: &[PathBuf],
+    module: &str,
+) -> Option<PathBuf> {
+    let parent_dir = base_path.parent().unwrap_or_else(|| Path::new("."));
+    let file_name = format!("{}.yang", module);
+    let revision_prefix = format!("{}@", module);
+
+    std::iter::once(parent_dir)
+        .chain(search_paths.iter().map(PathBuf::as_path))
+        .find_map(|dir| {
+            registry
+                .and_then(|registry| registry.find_in(dir, &file_name, &revision_prefix))
+                .or_else(|| find_in_dir(dir, &file_name, &revision_prefix))
+        })
+}
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub(crate) fn find_in_dir(dir: &Path, file_name: &str, revision_prefix: &str) -> Option<PathBuf> {
+    let path = dir.join(file_name);
+    if path.is_file() {
+        return Some(path);
+    }
+    std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter(|name| name.starts_with(revision_prefix) && name.ends_with(".yang"))
+        .max()
+        .map(|name| dir.join(name))
+}
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+pub(crate) fn find_in_dir(_dir: &Path, _file_name: &str, _revision_prefix: &str) -> Option<PathBuf> {
+    None
+}
@@ -0,0 +1,255 @@
+use std::path::PathBuf;
+
+use serde_json::{json, Map, Value as JsonValue};
+
+use crate::error::{ErrorCode, ParserError, Severity, SourceLocation, Warning};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const TOOL_NAME: &str = "yang-rs";
+const TOOL_URI: &str = "https://github.com/TerjeLafton/yang-rs";
+
+/// The errors and warnings of a run, like a lint of a repository of modules, which are written as JSON or SARIF for CI
+/// annotations, code scanning and dashboards.
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// An error or warning of a [Report], with where it is as far as it's known.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: ErrorCode,
+    pub message: String,
+    pub module: Option<String>,
+    pub statement: Option<String>,
+    pub path: Option<String>,
+    pub file: Option<PathBuf>,
+    pub location: Option<SourceLocation>,
+}
+
+impl Report {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the error, or each of the errors collected in it.
+    pub fn add_error(&mut self, error: &ParserError) {
+        for error in error.errors() {
+            self.diagnostics.push(Diagnostic {
+                severity: Severity::Error,
+                code: error.code(),
+                message: error.kind().to_string(),
+                module: error.module().map(str::to_string),
+                statement: None,
+                path: error.path().map(str::to_string),
+                file: error.file().map(PathBuf::from),
+                location: error.location(),
+            });
+        }
+    }
+
+    pub fn add_warning(&mut self, warning: &Warning) {
+        self.diagnostics.push(Diagnostic {
+            severity: Severity::Warning,
+            code: warning.code(),
+            message: warning.kind.to_string(),
+            module: Some(warning.module.to_string()),
+            statement: warning.statement.clone(),
+            path: warning.path.clone(),
+            file: None,
+            location: None,
+        });
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.severity == Severity::Error)
+    }
+
+    /// The diagnostics as a JSON object with a `diagnostics` array, where each diagnostic has its `severity`, `code`
+    /// and `message`, and the `module`, `statement`, `path`, `file`, `line` and `column` which are known.
+    pub fn to_json(&self) -> String {
+        let diagnostics: Vec<JsonValue> = self.diagnostics.iter().map(Diagnostic::to_json).collect();
+        serde_json::to_string_pretty(&json!({ "diagnostics": diagnostics })).expect("a report to serialize")
+    }
+
+    /// The diagnostics as a SARIF 2.1.0 log with a single run, where each code is a rule, for GitHub code scanning and
+    /// other tools reading static analysis results.
+    pub fn to_sarif(&self) -> String {
+        let mut codes: Vec<ErrorCode> = Vec::new();
+        for diagnostic in &self.diagnostics {
+            if !codes.contains(&diagnostic.code) {
+                codes.push(diagnostic.code);
+            }
+        }
+        let rules: Vec<JsonValue> = codes.iter().map(|code| json!({ "id": code.as_str() })).collect();
+        let results: Vec<JsonValue> = self.diagnostics.iter().map(Diagnostic::to_sarif).collect();
+
+        let log = json!({
+            "$schema": SARIF_SCHEMA,
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": TOOL_NAME,
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "informationUri": TOOL_URI,
+                        "rules": rules,
+                    }
+                },
+                "results": results,
+            }],
+        });
+        serde_json::to_string_pretty(&log).expect("a report to serialize")
+    }
+}
+
+impl Diagnostic {
+    fn to_json(&self) -> JsonValue {
+        let mut object = Map::new();
+        object.insert("severity".into(), self.severity.as_str().into());
+        object.insert("code".into(), self.code.as_str().into());
+        object.insert("message".into(), self.message.clone().into());
+        if let Some(module) = &self.module {
+            object.insert("module".into(), module.clone().into());
+        }
+        if let Some(statement) = &self.statement {
+            object.insert("statement".into(), statement.clone().into());
+        }
+        if let Some(path) = &self.path {
+            object.insert("path".into(), path.clone().into());
+        }
+        if let Some(file) = &self.file {
+            object.insert("file".into(), file_uri(file).into());
+        }
+        if let Some(location) = self.location {
+            object.insert("line".into(), location.line.into());
+            object.insert("column".into(), location.column.into());
+        }
+
+        JsonValue::Object(object)
+    }
+
+    // A result with a physical location if the file is known, and a logical location of the module and path if they
+    // are.
+    fn to_sarif(&self) -> JsonValue {
+        let mut location = Map::new();
+        if let Some(file) = &self.file {
+            let mut physical = json!({ "artifactLocation": { "uri": file_uri(file) } });
+            if let Some(source_location) = self.location {
+                physical["region"] = json!({
+                    "startLine": source_location.line,
+                    "startColumn": source_location.column,
+                });
+            }
+            location.insert("physicalLocation".into(), physical);
+        }
+        let logical_name = match (&self.module, &self.path) {
+            (Some(module), Some(path)) => Some(format!("{}:{}", module, path)),
+            (Some(module), None) => Some(module.clone()),
+            (None, Some(path)) => Some(path.clone()),
+            (None, None) => None,
+        };
+        if let Some(name) = logical_name {
+            location.insert("logicalLocations".into(), json!([{ "fullyQualifiedName": name }]));
+        }
+
+        let mut result = json!({
+            "ruleId": self.code.as_str(),
+            "level": self.severity.as_str(),
+            "message": { "text": self.message },
+        });
+        if !location.is_empty() {
+            result["locations"] = json!([location]);
+        }
+
+        result
+    }
+}
+
+// SARIF and most CI tools expect forward slashes in file paths.
+fn file_uri(file: &std::path::Path) -> String {
+    file.to_string_lossy().replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error::WarningKind, Document, Symbol};
+
+    fn report() -> Report {
+        let error = Document::parse("module example {\n  namespace \"urn:example\";\n  prefix ex;\n  leaf\n}\n")
+            .expect_err("the module to have a syntax error");
+        let warning = Warning {
+            kind: WarningKind::UnusedImport {
+                module: Symbol::from("ietf-inet-types"),
+                prefix: Symbol::from("inet"),
+            },
+            module: Symbol::from("example"),
+            statement: Some("import ietf-inet-types".to_string()),
+            path: None,
+        };
+
+        let mut report = Report::new();
+        report.add_error(&error);
+        report.add_warning(&warning);
+        report
+    }
+
+    #[test]
+    fn json_has_each_diagnostic_with_what_is_known_of_it() {
+        let report = report();
+        assert!(report.has_errors());
+
+        let json: JsonValue = serde_json::from_str(&report.to_json()).expect("the report to be JSON");
+        let diagnostics = json["diagnostics"].as_array().expect("the diagnostics to be an array");
+        assert_eq!(diagnostics.len(), 2);
+
+        assert_eq!(diagnostics[0]["severity"], "error");
+        assert_eq!(diagnostics[0]["code"], "yang::syntax");
+        assert_eq!(diagnostics[0]["line"], 5);
+        assert!(diagnostics[0].get("module").is_none());
+
+        assert_eq!(
+            diagnostics[1],
+            json!({
+                "severity": "warning",
+                "code": "yang::unused_import",
+                "message": "module ietf-inet-types is imported with prefix inet, which is never used",
+                "module": "example",
+                "statement": "import ietf-inet-types",
+            })
+        );
+    }
+
+    #[test]
+    fn sarif_has_a_rule_per_code_and_a_result_per_diagnostic() {
+        let mut report = report();
+        report.diagnostics[0].file = Some(PathBuf::from("models\\example.yang"));
+
+        let sarif: JsonValue = serde_json::from_str(&report.to_sarif()).expect("the log to be JSON");
+        assert_eq!(sarif["version"], "2.1.0");
+        let run = &sarif["runs"][0];
+        assert_eq!(run["tool"]["driver"]["name"], "yang-rs");
+        assert_eq!(
+            run["tool"]["driver"]["rules"],
+            json!([{ "id": "yang::syntax" }, { "id": "yang::unused_import" }])
+        );
+
+        let results = run["results"].as_array().expect("the results to be an array");
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0]["ruleId"], "yang::syntax");
+        assert_eq!(results[0]["level"], "error");
+        let physical = &results[0]["locations"][0]["physicalLocation"];
+        assert_eq!(physical["artifactLocation"]["uri"], "models/example.yang");
+        assert_eq!(physical["region"]["startLine"], 5);
+
+        assert_eq!(results[1]["level"], "warning");
+        assert_eq!(
+            results[1]["locations"],
+            json!([{ "logicalLocations": [{ "fullyQualifiedName": "example" }] }])
+        );
+    }
+}
@@ -1,178 +1,203 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 
-use crate::model::*;
+use crate::{
+    error::{DiagnosticSink, Warning, WarningKind},
+    model::*,
+    schema,
+    symbol::Interner,
+    Symbol,
+};
 
 /// Resolves references between YANG nodes.
 pub struct ReferenceResolver {
+    module_name: Symbol,
     reference_nodes: ReferenceNodes,
-    imported_modules: HashMap<String, ReferenceNodes>,
-    prefix_to_module: HashMap<String, String>,
+    imported_modules: HashMap<Symbol, ReferenceNodes>,
+    prefix_to_module: HashMap<Symbol, Symbol>,
+    interner: Interner,
+    warnings: Option<Arc<dyn DiagnosticSink>>,
 }
 
 impl ReferenceResolver {
     /// Create a new reference resolver with the given reference information
     pub fn new(
+        module_name: Symbol,
         reference_nodes: ReferenceNodes,
-        imported_modules: HashMap<String, ReferenceNodes>,
-        prefix_to_module: HashMap<String, String>,
+        imported_modules: HashMap<Symbol, ReferenceNodes>,
+        prefix_to_module: HashMap<Symbol, Symbol>,
+        interner: Interner,
     ) -> Self {
         Self {
+            module_name,
             reference_nodes,
             imported_modules,
             prefix_to_module,
+            interner,
+            warnings: None,
+        }
+    }
+
+    /// Report uses statements of groupings which aren't found, or are deprecated or obsolete, to the sink.
+    pub fn with_warnings(self, warnings: Option<Arc<dyn DiagnosticSink>>) -> Self {
+        Self { warnings, ..self }
+    }
+
+    fn warn(&self, kind: WarningKind, statement: String, scope: &Scope) {
+        if let Some(warnings) = &self.warnings {
+            warnings.warning(Warning {
+                kind,
+                module: self.module_name.clone(),
+                statement: Some(statement),
+                path: Some(scope.path().to_string()),
+            });
         }
     }
 
     /// Start resolving references by walking the tree. Walks only through nodes that can actually have references.
     pub fn resolve_references(&self, module: &mut Module) {
-        for node in &mut module.body {
-            self.resolve_schema_node_references(node, "/");
+        let root = Scope::root();
+        // Uses statements at the top level are replaced by the nodes of their groupings, like in containers.
+        let mut body = Vec::with_capacity(module.body.len());
+        for node in std::mem::take(&mut module.body) {
+            match node {
+                SchemaNode::DataDef(data_def @ DataDef::Uses(_)) => {
+                    let mut data_defs = vec![data_def];
+                    self.resolve_data_defs(&mut data_defs, &root);
+                    body.extend(data_defs.into_iter().map(SchemaNode::DataDef));
+                }
+                mut node => {
+                    self.resolve_schema_node_references(&mut node, &root);
+                    body.push(node);
+                }
+            }
+        }
+        module.body = body;
+
+        for augment in &mut module.augments {
+            let scope = root.augment(&augment.target);
+            self.resolve_augment_references(augment, &scope);
         }
     }
 
-    fn resolve_schema_node_references(&self, node: &mut SchemaNode, path: &str) {
+    fn resolve_schema_node_references(&self, node: &mut SchemaNode, scope: &Scope) {
         match node {
-            SchemaNode::DataDef(data_def) => self.resolve_data_def_references(data_def, path),
-            SchemaNode::Rpc(rpc) => self.resolve_rpc_references(rpc, path),
-            SchemaNode::Notification(notification) => self.resolve_notification_references(notification, path),
+            SchemaNode::DataDef(data_def) => self.resolve_data_def_references(data_def, scope),
+            SchemaNode::Rpc(rpc) => self.resolve_rpc_references(rpc, &scope.child(&rpc.name)),
+            SchemaNode::Notification(notification) => {
+                self.resolve_notification_references(notification, &scope.child(&notification.name))
+            }
         }
     }
 
-    fn resolve_data_def_references(&self, data_def: &mut DataDef, path: &str) {
+    fn resolve_data_def_references(&self, data_def: &mut DataDef, scope: &Scope) {
+        // Shared nodes without uses statements are left alone, so nodes shared with a grouping are not copied.
+        if is_shared(data_def) && !has_uses(data_def) {
+            return;
+        }
+
         match data_def {
             DataDef::Container(container) => {
-                let container_path = format!("{}{}/", path, container.name);
-                self.resolve_container_references(container, &container_path);
+                let scope = scope.child(&container.name);
+                self.resolve_container_references(Arc::make_mut(container), &scope);
             }
             DataDef::List(list) => {
-                let list_path = format!("{}{}/", path, list.name);
-                self.resolve_list_references(list, &list_path);
-            }
-            DataDef::Choice(choice) => {
-                let choice_path = format!("{}{}/", path, choice.name);
-                self.resolve_choice_references(choice, &choice_path);
+                let scope = scope.child(&list.name);
+                self.resolve_list_references(Arc::make_mut(list), &scope);
             }
+            DataDef::Choice(choice) => self.resolve_choice_references(Arc::make_mut(choice), scope),
             _ => {}
         }
     }
 
-    fn resolve_container_references(&self, container: &mut Container, path: &str) {
-        self.resolve_data_defs(&mut container.data_defs, path);
-
-        for action in &mut container.actions {
-            let action_path = format!("{}{}/", path, action.name);
-            self.resolve_action_references(action, &action_path);
-        }
-
-        for notification in &mut container.notifications {
-            let notification_path = format!("{}{}/", path, notification.name);
-            self.resolve_notification_references(notification, &notification_path);
-        }
+    fn resolve_container_references(&self, container: &mut Container, scope: &Scope) {
+        self.resolve_data_defs(&mut container.data_defs, scope);
+        self.resolve_operation_references(&mut container.actions, &mut container.notifications, scope);
     }
 
-    fn resolve_list_references(&self, list: &mut List, path: &str) {
-        self.resolve_data_defs(&mut list.data_defs, path);
+    fn resolve_list_references(&self, list: &mut List, scope: &Scope) {
+        self.resolve_data_defs(&mut list.data_defs, scope);
+        self.resolve_operation_references(&mut list.actions, &mut list.notifications, scope);
+    }
 
-        for action in &mut list.actions {
-            let action_path = format!("{}{}/", path, action.name);
-            self.resolve_action_references(action, &action_path);
+    fn resolve_operation_references(&self, actions: &mut [Action], notifications: &mut [Notification], scope: &Scope) {
+        for action in actions {
+            let scope = scope.child(&action.name);
+            self.resolve_action_references(action, &scope);
         }
 
-        for notification in &mut list.notifications {
-            let notification_path = format!("{}{}/", path, notification.name);
-            self.resolve_notification_references(notification, &notification_path);
+        for notification in notifications {
+            let scope = scope.child(&notification.name);
+            self.resolve_notification_references(notification, &scope);
         }
     }
 
-    fn resolve_choice_references(&self, choice: &mut Choice, path: &str) {
+    // Choices and cases aren't part of schema paths, so their nodes are in the scope of the parent of the choice.
+    fn resolve_choice_references(&self, choice: &mut Choice, scope: &Scope) {
         for case in &mut choice.cases {
             match case {
-                Case::LongCase(long_case) => {
-                    let case_path = format!("{}{}/", path, long_case.name);
-                    self.resolve_long_case_references(long_case, &case_path);
-                }
-                Case::ShortCase(short_case) => self.resolve_short_case_references(short_case, path),
+                Case::LongCase(long_case) => self.resolve_long_case_references(Arc::make_mut(long_case), scope),
+                Case::ShortCase(short_case) => self.resolve_short_case_references(short_case, scope),
             }
         }
     }
 
-    fn resolve_long_case_references(&self, long_case: &mut LongCase, path: &str) {
-        self.resolve_data_defs(&mut long_case.data_defs, path);
+    fn resolve_long_case_references(&self, long_case: &mut LongCase, scope: &Scope) {
+        self.resolve_data_defs(&mut long_case.data_defs, scope);
     }
 
-    fn resolve_short_case_references(&self, short_case: &mut ShortCase, path: &str) {
+    fn resolve_short_case_references(&self, short_case: &mut ShortCase, scope: &Scope) {
         match short_case {
             ShortCase::Container(container) => {
-                let container_path = format!("{}{}/", path, container.name);
-                self.resolve_container_references(container, &container_path);
+                let scope = scope.child(&container.name);
+                self.resolve_container_references(Arc::make_mut(container), &scope);
             }
             ShortCase::List(list) => {
-                let list_path = format!("{}{}/", path, list.name);
-                self.resolve_list_references(list, &list_path);
-            }
-            ShortCase::Choice(choice) => {
-                let choice_path = format!("{}{}/", path, choice.name);
-                self.resolve_choice_references(choice, &choice_path);
+                let scope = scope.child(&list.name);
+                self.resolve_list_references(Arc::make_mut(list), &scope);
             }
+            ShortCase::Choice(choice) => self.resolve_choice_references(Arc::make_mut(choice), scope),
             _ => {}
         }
     }
 
-    fn resolve_augment_references(&self, augment: &mut Augment, path: &str) {
-        self.resolve_data_defs(&mut augment.data_defs, path);
+    fn resolve_augment_references(&self, augment: &mut Augment, scope: &Scope) {
+        self.resolve_data_defs(&mut augment.data_defs, scope);
 
         for case in &mut augment.cases {
             match case {
-                Case::LongCase(long_case) => {
-                    let case_path = format!("{}{}/", path, long_case.name);
-                    self.resolve_long_case_references(long_case, &case_path);
-                }
-                Case::ShortCase(short_case) => self.resolve_short_case_references(short_case, path),
+                Case::LongCase(long_case) => self.resolve_long_case_references(Arc::make_mut(long_case), scope),
+                Case::ShortCase(short_case) => self.resolve_short_case_references(short_case, scope),
             }
         }
 
-        for action in &mut augment.actions {
-            let action_path = format!("{}{}/", path, action.name);
-            self.resolve_action_references(action, &action_path);
-        }
-
-        for notification in &mut augment.notifications {
-            let notification_path = format!("{}{}/", path, notification.name);
-            self.resolve_notification_references(notification, &notification_path);
-        }
+        self.resolve_operation_references(&mut augment.actions, &mut augment.notifications, scope);
     }
 
-    fn resolve_action_references(&self, action: &mut Action, path: &str) {
-        if let Some(input) = &mut action.input {
-            let input_path = format!("{}input/", path);
-            self.resolve_data_defs(&mut input.data_defs, &input_path);
-        }
+    fn resolve_action_references(&self, action: &mut Action, scope: &Scope) {
+        self.resolve_input_output_references(&mut action.input, &mut action.output, scope);
+    }
 
-        if let Some(output) = &mut action.output {
-            let output_path = format!("{}output/", path);
-            self.resolve_data_defs(&mut output.data_defs, &output_path);
-        }
+    fn resolve_rpc_references(&self, rpc: &mut Rpc, scope: &Scope) {
+        self.resolve_input_output_references(&mut rpc.input, &mut rpc.output, scope);
     }
 
-    fn resolve_rpc_references(&self, rpc: &mut Rpc, path: &str) {
-        if let Some(input) = &mut rpc.input {
-            let input_path = format!("{}input/", path);
-            self.resolve_data_defs(&mut input.data_defs, &input_path);
+    fn resolve_input_output_references(&self, input: &mut Option<Input>, output: &mut Option<Output>, scope: &Scope) {
+        if let Some(input) = input {
+            self.resolve_data_defs(&mut input.data_defs, &scope.child("input"));
         }
 
-        if let Some(output) = &mut rpc.output {
-            let output_path = format!("{}output/", path);
-            self.resolve_data_defs(&mut output.data_defs, &output_path);
+        if let Some(output) = output {
+            self.resolve_data_defs(&mut output.data_defs, &scope.child("output"));
         }
     }
 
-    fn resolve_notification_references(&self, notification: &mut Notification, path: &str) {
-        self.resolve_data_defs(&mut notification.data_defs, path);
+    fn resolve_notification_references(&self, notification: &mut Notification, scope: &Scope) {
+        self.resolve_data_defs(&mut notification.data_defs, scope);
     }
 
     /// Find a grouping by traversing from current path up to the root or from imported modules
-    /// when a prefix is present.
-    fn find_grouping(&self, grouping_name: &str, current_path: &str) -> Option<&Grouping> {
+    /// when a prefix is present. The grouping is returned with its path, qualified with the name of its module.
+    fn find_grouping(&self, grouping_name: &str, current_path: &str) -> Option<(String, &Grouping)> {
         // Check if the grouping name has a prefix (indicating an imported module).
         if let Some(idx) = grouping_name.find(':') {
             let prefix = &grouping_name[..idx];
@@ -186,21 +211,10 @@ impl ReferenceResolver {
                     // Imported groupings are expected to be at the top level.
                     let path = format!("/{}", name);
 
-                    #[cfg(debug_assertions)]
-                    println!(
-                        "Looking for imported grouping {} in module {} (path: {})",
-                        name, module_name, path
-                    );
-
                     if let Some(grouping) = ref_nodes.groupings.get(&path) {
-                        #[cfg(debug_assertions)]
-                        println!("Found imported grouping {} in module {}", name, module_name);
-                        return Some(grouping);
+                        return Some((format!("{}:{}", module_name, path), grouping));
                     }
                 }
-
-                #[cfg(debug_assertions)]
-                println!("Failed to find imported grouping {} in module {}", name, module_name);
             }
 
             // If prefix resolution failed, return None.
@@ -215,19 +229,12 @@ impl ReferenceResolver {
             // Try to find the grouping in the current search path.
             let full_path = format!("{}{}", search_path, grouping_name);
 
-            #[cfg(debug_assertions)]
-            println!("Looking for local grouping {} at path {}", grouping_name, full_path);
-
             if let Some(grouping) = self.reference_nodes.groupings.get(&full_path) {
-                #[cfg(debug_assertions)]
-                println!("Found local grouping {} at path {}", grouping_name, full_path);
-                return Some(grouping);
+                return Some((format!("{}:{}", self.module_name, full_path), grouping));
             }
 
             // If we're at the root, we've exhausted all options.
             if search_path == "/" {
-                #[cfg(debug_assertions)]
-                println!("Exhausted all options for local grouping {}", grouping_name);
                 break;
             }
 
@@ -248,46 +255,186 @@ impl ReferenceResolver {
         None
     }
 
-    /// The core method that resolves all references in a vector of DataDef nodes.
-    fn resolve_data_defs(&self, data_defs: &mut Vec<DataDef>, path: &str) {
-        // Find indices of all Uses nodes.
-        let mut uses_indices: Vec<(usize, String)> = Vec::new();
-
-        // Collect all Uses nodes and their grouping names.
-        for (idx, data_def) in data_defs.iter().enumerate() {
-            if let DataDef::Uses(uses) = data_def {
-                uses_indices.push((idx, uses.grouping.clone()));
+    /// The core method that resolves all references in a vector of DataDef nodes. The nodes are rebuilt in a single
+    /// pass, with each uses statement replaced by the nodes of its grouping, and every node is resolved once.
+    fn resolve_data_defs(&self, data_defs: &mut Vec<DataDef>, scope: &Scope) {
+        if !data_defs.iter().any(|data_def| matches!(data_def, DataDef::Uses(_))) {
+            for data_def in data_defs.iter_mut() {
+                self.resolve_data_def_references(data_def, scope);
             }
+            return;
         }
 
-        // Process Uses nodes in reverse order to avoid index invalidation.
-        for (idx, grouping_name) in uses_indices.iter().rev() {
-            // Look up the grouping by hierarchical path resolution.
-            if let Some(grouping) = self.find_grouping(&grouping_name, path) {
-                // Clone the data_defs from the grouping.
-                let grouping_data_defs = grouping.data_defs.clone();
-                let data_defs_len = grouping_data_defs.len();
-
-                // Remove the Uses node as it is not needed in the final data tree.
-                data_defs.remove(*idx);
-
-                // Insert all data_defs from the grouping at the same position.
-                for (inner_idx, data_def) in grouping_data_defs.into_iter().enumerate() {
-                    data_defs.insert(*idx + inner_idx, data_def);
+        let mut resolved = Vec::with_capacity(data_defs.len());
+        let mut expanded_uses = Vec::new();
+        for mut data_def in std::mem::take(data_defs) {
+            let DataDef::Uses(uses) = &data_def else {
+                self.resolve_data_def_references(&mut data_def, scope);
+                resolved.push(data_def);
+                continue;
+            };
+
+            // Look up the grouping by hierarchical path resolution. Uses statements of unknown groupings are kept.
+            let statement = format!("uses {}", uses.grouping);
+            let Some((grouping_path, grouping)) = self.find_grouping(&uses.grouping, &scope.grouping_path) else {
+                self.warn(WarningKind::UnresolvedGrouping(uses.grouping.clone()), statement, scope);
+                resolved.push(data_def);
+                continue;
+            };
+            match grouping.status {
+                Some(Status::Deprecated) => {
+                    self.warn(WarningKind::DeprecatedGrouping(uses.grouping.clone()), statement, scope)
+                }
+                Some(Status::Obsolete) => {
+                    self.warn(WarningKind::ObsoleteGrouping(uses.grouping.clone()), statement, scope)
                 }
+                _ => {}
+            }
 
-                // Process the newly inserted nodes to resolve any nested references.
-                for inner_idx in 0..data_defs_len {
-                    if let Some(data_def) = data_defs.get_mut(*idx + inner_idx) {
-                        self.resolve_data_def_references(data_def, path);
+            // The data_defs of the grouping are shared with it until they are changed.
+            let start = resolved.len();
+            resolved.extend(grouping.data_defs.iter().cloned());
+
+            // Containers and lists used without changes are marked with the grouping, as they are the same
+            // wherever the grouping is used.
+            if uses.refines.is_empty() && uses.augments.is_empty() {
+                let grouping_path = self.interner.intern(&grouping_path);
+                for data_def in &mut resolved[start..] {
+                    match data_def {
+                        DataDef::Container(container) => {
+                            Arc::make_mut(container).grouping = Some(grouping_path.clone())
+                        }
+                        DataDef::List(list) => Arc::make_mut(list).grouping = Some(grouping_path.clone()),
+                        _ => {}
                     }
                 }
             }
+
+            // Resolve any nested references in the nodes of the grouping.
+            for data_def in &mut resolved[start..] {
+                self.resolve_data_def_references(data_def, scope);
+            }
+
+            if !uses.augments.is_empty() {
+                expanded_uses.push(uses.clone());
+            }
+        }
+
+        // Augments of the uses statements add nodes to the resolved nodes of the groupings.
+        for mut uses in expanded_uses {
+            for augment in &mut Arc::make_mut(&mut uses).augments {
+                self.resolve_augment_references(augment, &scope.augment(&augment.target));
+                if !schema::augment_data_defs(&mut resolved, augment) {
+                    let statement = format!("augment \"{}\"", augment.target);
+                    self.warn(WarningKind::UnresolvedAugment(augment.target.clone()), statement, scope);
+                }
+            }
+        }
+
+        *data_defs = resolved;
+    }
+}
+
+// Where the resolver is in the module, kept as it walks the tree, so warnings tell where they are. Groupings are looked
+// up from where the statement using them is, which for the nodes of an augment is where the augment is rather than its
+// target.
+#[derive(Debug, Clone)]
+struct Scope {
+    // The schema path of the nodes, like "/interfaces/interface", which is empty at the top level.
+    path: String,
+    // The path groupings are looked up from, like "/interfaces/interface/", as the parser keys them.
+    grouping_path: String,
+}
+
+impl Scope {
+    fn root() -> Self {
+        Self {
+            path: String::new(),
+            grouping_path: "/".to_string(),
+        }
+    }
+
+    fn child(&self, name: &str) -> Self {
+        Self {
+            path: format!("{}/{}", self.path, name),
+            grouping_path: format!("{}{}/", self.grouping_path, name),
+        }
+    }
+
+    // The target of an augment is absolute for the augments of a module, and relative to the nodes of the grouping for
+    // the augments of a uses statement. Prefixes are left out of the path, like in other schema paths.
+    fn augment(&self, target: &str) -> Self {
+        let mut path = match target.starts_with('/') {
+            true => String::new(),
+            false => self.path.clone(),
+        };
+        for step in target.split('/').filter(|step| !step.is_empty()) {
+            path.push('/');
+            path.push_str(step.split_once(':').map_or(step, |(_, name)| name));
         }
 
-        // Recursively resolve any references in remaining nodes
-        for data_def in data_defs.iter_mut() {
-            self.resolve_data_def_references(data_def, path);
+        Self {
+            path,
+            grouping_path: self.grouping_path.clone(),
+        }
+    }
+
+    fn path(&self) -> &str {
+        match self.path.as_str() {
+            "" => "/",
+            path => path,
+        }
+    }
+}
+
+/// Whether the node is shared with a grouping or another use of it.
+fn is_shared(data_def: &DataDef) -> bool {
+    match data_def {
+        DataDef::Container(container) => Arc::strong_count(container) > 1,
+        DataDef::List(list) => Arc::strong_count(list) > 1,
+        DataDef::Choice(choice) => Arc::strong_count(choice) > 1,
+        _ => false,
+    }
+}
+
+/// Whether a uses statement is left in the subtree of the node.
+fn has_uses(data_def: &DataDef) -> bool {
+    fn any_uses(data_defs: &[DataDef]) -> bool {
+        data_defs.iter().any(has_uses)
+    }
+
+    fn operations_have_uses(actions: &[Action], notifications: &[Notification]) -> bool {
+        actions.iter().any(|action| {
+            action.input.as_ref().is_some_and(|input| any_uses(&input.data_defs))
+                || action.output.as_ref().is_some_and(|output| any_uses(&output.data_defs))
+        }) || notifications
+            .iter()
+            .any(|notification| any_uses(&notification.data_defs))
+    }
+
+    fn choice_has_uses(choice: &Choice) -> bool {
+        choice.cases.iter().any(|case| match case {
+            Case::LongCase(long_case) => any_uses(&long_case.data_defs),
+            Case::ShortCase(short_case) => match short_case {
+                ShortCase::Container(container) => {
+                    any_uses(&container.data_defs) || operations_have_uses(&container.actions, &container.notifications)
+                }
+                ShortCase::List(list) => {
+                    any_uses(&list.data_defs) || operations_have_uses(&list.actions, &list.notifications)
+                }
+                ShortCase::Choice(choice) => choice_has_uses(choice),
+                _ => false,
+            },
+        })
+    }
+
+    match data_def {
+        DataDef::Uses(_) => true,
+        DataDef::Container(container) => {
+            any_uses(&container.data_defs) || operations_have_uses(&container.actions, &container.notifications)
         }
+        DataDef::List(list) => any_uses(&list.data_defs) || operations_have_uses(&list.actions, &list.notifications),
+        DataDef::Choice(choice) => choice_has_uses(choice),
+        _ => false,
     }
 }
@@ -0,0 +1,763 @@
+use std::sync::Arc;
+
+use crate::{
+    error::{Warning, WarningKind},
+    model::*,
+    Symbol,
+};
+
+// Augments and deviations change the schema tree of the module they target, which makes the tree match what a server
+// implementing the modules exposes. Their targets are schema node identifiers, which are absolute like
+// "/if:interfaces/if:interface" for the statements of a module, or relative like "address/ip" for the augments of a
+// uses statement, which target the nodes of the grouping.
+
+pub(crate) const BUILT_IN_TYPES: &[&str] = &[
+    "binary",
+    "bits",
+    "boolean",
+    "decimal64",
+    "empty",
+    "enumeration",
+    "identityref",
+    "instance-identifier",
+    "int8",
+    "int16",
+    "int32",
+    "int64",
+    "leafref",
+    "string",
+    "uint8",
+    "uint16",
+    "uint32",
+    "uint64",
+    "union",
+];
+
+/// Apply the augments and deviations of the module which target the module itself. Those whose targets aren't found
+/// are returned as warnings.
+pub fn apply_own(module: &mut Module) -> Vec<Warning> {
+    let in_module = |target: &str| target_module(target, module) == Some(&module.name);
+    let augments = module
+        .augments
+        .iter()
+        .filter(|augment| in_module(&augment.target))
+        .map(|augment| (module.name.clone(), augment.clone()))
+        .collect();
+    let deviations = module
+        .deviations
+        .iter()
+        .filter(|deviation| in_module(&deviation.target))
+        .map(|deviation| (module.name.clone(), deviation.clone()))
+        .collect();
+    apply(module, augments, deviations)
+}
+
+/// Apply the augments and deviations of other modules which target the module. The nodes added by the augments are
+/// marked with the module defining them, whose reference nodes are added to the imported modules of the module, so
+/// the types of the added nodes can be resolved.
+///
+/// The names of the typedefs, identities and features the added nodes refer to are qualified with the name of the
+/// module defining them rather than a prefix, as the prefixes of another module might mean something else in the
+/// module. The module names are added to the prefixes of the module, mapped to themselves.
+///
+/// The augments and deviations whose targets aren't found are returned as warnings of the modules defining them.
+pub fn apply_from(module: &mut Module, others: &[Module]) -> Vec<Warning> {
+    let mut augments = Vec::new();
+    let mut deviations = Vec::new();
+
+    for other in others {
+        let mut prefixes = other.prefix_to_module.clone();
+        prefixes.insert(other.prefix.clone(), other.name.clone());
+        let qualify = |name: &str| match name.split_once(':') {
+            Some((prefix, name)) => match prefixes.get(prefix) {
+                Some(module_name) => format!("{}:{}", module_name, name),
+                None => format!("{}:{}", prefix, name),
+            },
+            None => format!("{}:{}", other.name, name),
+        };
+        let in_module = |target: &str| target_module(target, other) == Some(&module.name);
+
+        let other_augments = other.augments.iter().filter(|augment| in_module(&augment.target));
+        let other_deviations = other.deviations.iter().filter(|deviation| in_module(&deviation.target));
+        let (augments_len, deviations_len) = (augments.len(), deviations.len());
+        augments.extend(other_augments.cloned().map(|mut augment| {
+            augment.if_features = augment.if_features.iter().map(|feature| qualify(feature)).collect();
+            for data_def in &mut augment.data_defs {
+                adopt_data_def(data_def, &other.name, &qualify);
+            }
+            for case in &mut augment.cases {
+                adopt_case(case, &other.name, &qualify);
+            }
+            (other.name.clone(), augment)
+        }));
+        deviations.extend(other_deviations.cloned().map(|mut deviation| {
+            for replace in &mut deviation.replace {
+                if let Some(type_info) = &mut replace.type_info {
+                    qualify_type(type_info, &qualify);
+                }
+            }
+            (other.name.clone(), deviation)
+        }));
+
+        if augments.len() == augments_len && deviations.len() == deviations_len {
+            continue;
+        }
+        if augments.len() > augments_len {
+            module
+                .augmenting_modules
+                .insert(other.name.clone(), other.namespace.clone());
+        }
+        let other_modules = std::iter::once((&other.name, &other.reference_nodes)).chain(&other.imported_modules);
+        for (module_name, reference_nodes) in other_modules {
+            if *module_name != module.name {
+                module
+                    .imported_modules
+                    .entry(module_name.clone())
+                    .or_insert_with(|| reference_nodes.clone());
+                module
+                    .prefix_to_module
+                    .entry(module_name.clone())
+                    .or_insert_with(|| module_name.clone());
+            }
+        }
+    }
+
+    apply(module, augments, deviations)
+}
+
+/// Apply an augment of a uses statement to the nodes of the grouping, which are among the data nodes. Returns whether
+/// the target was found.
+pub fn augment_data_defs(data_defs: &mut Vec<DataDef>, augment: &Augment) -> bool {
+    match Target::DataDefs(data_defs).find(&augment.target) {
+        Some(mut target) => {
+            target.augment(augment);
+            true
+        }
+        None => false,
+    }
+}
+
+// Augments are applied before deviations, which might target the nodes added by augments. The if-features of an
+// augment are added to the nodes it adds. Targets which don't exist in the module are skipped, with a warning of the
+// module the statement is in.
+fn apply(module: &mut Module, augments: Vec<(Symbol, Augment)>, deviations: Vec<(Symbol, Deviation)>) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+    let mut unresolved = |module: &Symbol, kind: WarningKind, statement: String| {
+        warnings.push(Warning {
+            kind,
+            module: module.clone(),
+            statement: Some(statement),
+            path: None,
+        })
+    };
+
+    for (module_name, mut augment) in augments {
+        let if_features = std::mem::take(&mut augment.if_features);
+        for data_def in &mut augment.data_defs {
+            if let Some(features) = if_features_mut(data_def) {
+                features.extend(if_features.iter().cloned());
+            }
+        }
+        for case in &mut augment.cases {
+            if let Case::LongCase(case) = case {
+                Arc::make_mut(case).if_features.extend(if_features.iter().cloned());
+            }
+        }
+
+        match Target::Body(&mut module.body).find(&augment.target) {
+            Some(mut target) => target.augment(&augment),
+            None => unresolved(
+                &module_name,
+                WarningKind::UnresolvedAugment(augment.target.clone()),
+                format!("augment \"{}\"", augment.target),
+            ),
+        }
+    }
+
+    for (module_name, deviation) in &deviations {
+        let statement = || format!("deviation \"{}\"", deviation.target);
+        if deviation.not_supported {
+            let target = deviation.target.trim_end_matches('/');
+            let (parent, name) = target.rsplit_once('/').unwrap_or(("", target));
+            let removed = Target::Body(&mut module.body)
+                .find(parent)
+                .is_some_and(|mut parent| parent.remove_child(node_name(name)));
+            if !removed {
+                unresolved(
+                    module_name,
+                    WarningKind::UnresolvedDeviation(deviation.target.clone()),
+                    statement(),
+                );
+            }
+            continue;
+        }
+
+        let Some(mut target) = Target::Body(&mut module.body).find(&deviation.target) else {
+            unresolved(
+                module_name,
+                WarningKind::UnresolvedDeviation(deviation.target.clone()),
+                statement(),
+            );
+            continue;
+        };
+        for add in &deviation.add {
+            target.deviate_add(add);
+        }
+        for delete in &deviation.delete {
+            target.deviate_delete(delete);
+        }
+        for replace in &deviation.replace {
+            target.deviate_replace(replace);
+        }
+    }
+
+    warnings
+}
+
+// The module an absolute target is in is given by the prefix of its first node, where no prefix means the module
+// with the statement itself.
+fn target_module<'m>(target: &str, module: &'m Module) -> Option<&'m Symbol> {
+    let first_node = target.trim_start_matches('/').split('/').next()?;
+    match first_node.split_once(':') {
+        Some((prefix, _)) if prefix != module.prefix => module.prefix_to_module.get(prefix),
+        _ => Some(&module.name),
+    }
+}
+
+fn node_name(step: &str) -> &str {
+    step.split_once(':').map_or(step, |(_, name)| name)
+}
+
+fn data_def_name(data_def: &DataDef) -> Option<&str> {
+    match data_def {
+        DataDef::Container(container) => Some(&container.name),
+        DataDef::Leaf(leaf) => Some(&leaf.name),
+        DataDef::LeafList(leaf_list) => Some(&leaf_list.name),
+        DataDef::List(list) => Some(&list.name),
+        DataDef::Choice(choice) => Some(&choice.name),
+        DataDef::AnyData(anydata) => Some(&anydata.name),
+        DataDef::Anyxml(anyxml) => Some(&anyxml.name),
+        DataDef::Uses(_) => None,
+    }
+}
+
+fn case_name(case: &Case) -> &str {
+    match case {
+        Case::LongCase(long_case) => &long_case.name,
+        Case::ShortCase(ShortCase::Choice(choice)) => &choice.name,
+        Case::ShortCase(ShortCase::Container(container)) => &container.name,
+        Case::ShortCase(ShortCase::Leaf(leaf)) => &leaf.name,
+        Case::ShortCase(ShortCase::LeafList(leaf_list)) => &leaf_list.name,
+        Case::ShortCase(ShortCase::List(list)) => &list.name,
+        Case::ShortCase(ShortCase::Anydata(anydata)) => &anydata.name,
+        Case::ShortCase(ShortCase::Anyxml(anyxml)) => &anyxml.name,
+    }
+}
+
+fn if_features_mut(data_def: &mut DataDef) -> Option<&mut IfFeatures> {
+    match data_def {
+        DataDef::Container(container) => Some(&mut Arc::make_mut(container).if_features),
+        DataDef::Leaf(leaf) => Some(&mut Arc::make_mut(leaf).if_features),
+        DataDef::LeafList(leaf_list) => Some(&mut Arc::make_mut(leaf_list).if_features),
+        DataDef::List(list) => Some(&mut Arc::make_mut(list).if_features),
+        DataDef::Choice(choice) => Some(&mut Arc::make_mut(choice).if_features),
+        DataDef::AnyData(anydata) => Some(&mut Arc::make_mut(anydata).if_features),
+        DataDef::Anyxml(anyxml) => Some(&mut Arc::make_mut(anyxml).if_features),
+        DataDef::Uses(uses) => Some(&mut Arc::make_mut(uses).if_features),
+    }
+}
+
+// Data nodes added to a choice by an augment are shorthand cases.
+fn short_case(data_def: DataDef) -> Option<Case> {
+    let short_case = match data_def {
+        DataDef::Container(container) => ShortCase::Container(container),
+        DataDef::Leaf(leaf) => ShortCase::Leaf(leaf),
+        DataDef::LeafList(leaf_list) => ShortCase::LeafList(leaf_list),
+        DataDef::List(list) => ShortCase::List(list),
+        DataDef::Choice(choice) => ShortCase::Choice(choice),
+        DataDef::AnyData(anydata) => ShortCase::Anydata(anydata),
+        DataDef::Anyxml(anyxml) => ShortCase::Anyxml(anyxml),
+        DataDef::Uses(_) => return None,
+    };
+    Some(Case::ShortCase(short_case))
+}
+
+// Mark a node added by an augment of another module, and the nodes within it, as defined by that module.
+fn adopt_data_def(data_def: &mut DataDef, module_name: &Symbol, qualify: &impl Fn(&str) -> String) {
+    let module = Some(module_name.clone());
+    if let Some(if_features) = if_features_mut(data_def) {
+        *if_features = if_features.iter().map(|feature| qualify(feature)).collect();
+    }
+
+    match data_def {
+        DataDef::Container(container) => {
+            let container = Arc::make_mut(container);
+            container.meta.module = module;
+            adopt_children(
+                &mut container.data_defs,
+                &mut container.actions,
+                &mut container.notifications,
+                module_name,
+                qualify,
+            );
+        }
+        DataDef::List(list) => {
+            let list = Arc::make_mut(list);
+            list.meta.module = module;
+            adopt_children(
+                &mut list.data_defs,
+                &mut list.actions,
+                &mut list.notifications,
+                module_name,
+                qualify,
+            );
+        }
+        DataDef::Leaf(leaf) => {
+            let leaf = Arc::make_mut(leaf);
+            leaf.meta.module = module;
+            qualify_type(&mut leaf.type_info, qualify);
+        }
+        DataDef::LeafList(leaf_list) => {
+            let leaf_list = Arc::make_mut(leaf_list);
+            leaf_list.meta.module = module;
+            qualify_type(&mut leaf_list.type_info, qualify);
+        }
+        DataDef::Choice(choice) => {
+            let choice = Arc::make_mut(choice);
+            choice.meta.module = module;
+            for case in &mut choice.cases {
+                adopt_case(case, module_name, qualify);
+            }
+        }
+        DataDef::AnyData(anydata) => Arc::make_mut(anydata).meta.module = module,
+        DataDef::Anyxml(anyxml) => Arc::make_mut(anyxml).meta.module = module,
+        DataDef::Uses(_) => {}
+    }
+}
+
+fn adopt_children(
+    data_defs: &mut [DataDef],
+    actions: &mut [Action],
+    notifications: &mut [Notification],
+    module_name: &Symbol,
+    qualify: &impl Fn(&str) -> String,
+) {
+    let operation_data_defs = actions.iter_mut().flat_map(|action| {
+        let input = action.input.iter_mut().flat_map(|input| &mut input.data_defs);
+        let output = action.output.iter_mut().flat_map(|output| &mut output.data_defs);
+        input.chain(output)
+    });
+    let notification_data_defs = notifications
+        .iter_mut()
+        .flat_map(|notification| &mut notification.data_defs);
+
+    for data_def in data_defs
+        .iter_mut()
+        .chain(operation_data_defs)
+        .chain(notification_data_defs)
+    {
+        adopt_data_def(data_def, module_name, qualify);
+    }
+}
+
+fn adopt_case(case: &mut Case, module_name: &Symbol, qualify: &impl Fn(&str) -> String) {
+    let data_def = match case {
+        Case::LongCase(long_case) => {
+            let long_case = Arc::make_mut(long_case);
+            long_case.if_features = long_case.if_features.iter().map(|feature| qualify(feature)).collect();
+            for data_def in &mut long_case.data_defs {
+                adopt_data_def(data_def, module_name, qualify);
+            }
+            return;
+        }
+        Case::ShortCase(short_case) => match short_case.clone() {
+            ShortCase::Choice(choice) => DataDef::Choice(choice),
+            ShortCase::Container(container) => DataDef::Container(container),
+            ShortCase::Leaf(leaf) => DataDef::Leaf(leaf),
+            ShortCase::LeafList(leaf_list) => DataDef::LeafList(leaf_list),
+            ShortCase::List(list) => DataDef::List(list),
+            ShortCase::Anydata(anydata) => DataDef::AnyData(anydata),
+            ShortCase::Anyxml(anyxml) => DataDef::Anyxml(anyxml),
+        },
+    };
+
+    let mut data_def = data_def;
+    adopt_data_def(&mut data_def, module_name, qualify);
+    if let Some(adopted) = short_case(data_def) {
+        *case = adopted;
+    }
+}
+
+// Typedefs and identities referred to without a prefix are in the module defining the node, so they get qualified.
+fn qualify_type(type_info: &mut TypeInfo, qualify: &impl Fn(&str) -> String) {
+    if !BUILT_IN_TYPES.contains(&type_info.name.as_str()) {
+        type_info.name = qualify(&type_info.name).into();
+    }
+
+    // Other type bodies are left alone, so they stay shared.
+    if !matches!(
+        type_info.type_body.as_deref(),
+        Some(TypeBody::Identityref { .. } | TypeBody::Union { .. })
+    ) {
+        return;
+    }
+
+    match type_info.type_body.as_mut().map(Arc::make_mut) {
+        Some(TypeBody::Identityref { bases }) => {
+            *bases = bases.iter().map(|base| qualify(base)).collect();
+        }
+        Some(TypeBody::Union { types }) => {
+            for member in types {
+                qualify_type(member, qualify);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// A node of the schema tree, which can be the target of an augment or deviation, or lead to one. The body of the
+/// module, and the data nodes of a grouping, are the roots of absolute and relative targets.
+enum Target<'a> {
+    Body(&'a mut Vec<SchemaNode>),
+    DataDefs(&'a mut Vec<DataDef>),
+    Container(&'a mut Container),
+    List(&'a mut List),
+    Choice(&'a mut Choice),
+    Case(&'a mut LongCase),
+    ShortCase(&'a mut ShortCase),
+    Leaf(&'a mut Leaf),
+    LeafList(&'a mut LeafList),
+    Anydata(&'a mut Anydata),
+    Anyxml(&'a mut Anyxml),
+    Operation(&'a mut Option<Input>, &'a mut Option<Output>),
+    Input(&'a mut Input),
+    Output(&'a mut Output),
+    Notification(&'a mut Notification),
+}
+
+impl<'a> Target<'a> {
+    fn find(self, target: &str) -> Option<Target<'a>> {
+        target
+            .split('/')
+            .filter(|step| !step.is_empty())
+            .try_fold(self, |node, step| node.child(node_name(step)))
+    }
+
+    // Nodes are only found to be changed, so a found node, or a node with a changed descendant, is no longer the same as
+    // in the grouping it was used from.
+    fn from_data_def(data_def: &'a mut DataDef) -> Option<Target<'a>> {
+        match data_def {
+            DataDef::Container(container) => {
+                let container = Arc::make_mut(container);
+                container.grouping = None;
+                Some(Target::Container(container))
+            }
+            DataDef::Leaf(leaf) => Some(Target::Leaf(Arc::make_mut(leaf))),
+            DataDef::LeafList(leaf_list) => Some(Target::LeafList(Arc::make_mut(leaf_list))),
+            DataDef::List(list) => {
+                let list = Arc::make_mut(list);
+                list.grouping = None;
+                Some(Target::List(list))
+            }
+            DataDef::Choice(choice) => Some(Target::Choice(Arc::make_mut(choice))),
+            DataDef::AnyData(anydata) => Some(Target::Anydata(Arc::make_mut(anydata))),
+            DataDef::Anyxml(anyxml) => Some(Target::Anyxml(Arc::make_mut(anyxml))),
+            DataDef::Uses(_) => None,
+        }
+    }
+
+    fn from_data_defs(data_defs: &'a mut [DataDef], name: &str) -> Option<Target<'a>> {
+        data_defs
+            .iter_mut()
+            .find(|data_def| data_def_name(data_def) == Some(name))
+            .and_then(Target::from_data_def)
+    }
+
+    // The node of a shorthand case has the name of the case, so it's both the case and its child.
+    fn child(self, name: &str) -> Option<Target<'a>> {
+        match self {
+            Target::Body(body) => body.iter_mut().find_map(|node| match node {
+                SchemaNode::DataDef(data_def) if data_def_name(data_def) == Some(name) => {
+                    Target::from_data_def(data_def)
+                }
+                SchemaNode::Rpc(rpc) if rpc.name == name => Some(Target::Operation(&mut rpc.input, &mut rpc.output)),
+                SchemaNode::Notification(notification) if notification.name == name => {
+                    Some(Target::Notification(notification))
+                }
+                _ => None,
+            }),
+            Target::Container(Container {
+                data_defs,
+                actions,
+                notifications,
+                ..
+            })
+            | Target::List(List {
+                data_defs,
+                actions,
+                notifications,
+                ..
+            }) => {
+                if let Some(action) = actions.iter_mut().find(|action| action.name == name) {
+                    return Some(Target::Operation(&mut action.input, &mut action.output));
+                }
+                if let Some(notification) = notifications.iter_mut().find(|notification| notification.name == name) {
+                    return Some(Target::Notification(notification));
+                }
+                Target::from_data_defs(data_defs, name)
+            }
+            Target::DataDefs(data_defs)
+            | Target::Case(LongCase { data_defs, .. })
+            | Target::Input(Input { data_defs, .. })
+            | Target::Output(Output { data_defs, .. })
+            | Target::Notification(Notification { data_defs, .. }) => Target::from_data_defs(data_defs, name),
+            Target::Choice(choice) => {
+                choice
+                    .cases
+                    .iter_mut()
+                    .find(|case| case_name(case) == name)
+                    .map(|case| match case {
+                        Case::LongCase(long_case) => Target::Case(Arc::make_mut(long_case)),
+                        Case::ShortCase(short_case) => Target::ShortCase(short_case),
+                    })
+            }
+            Target::ShortCase(short_case) => match short_case {
+                ShortCase::Choice(choice) => Some(Target::Choice(Arc::make_mut(choice))),
+                ShortCase::Container(container) => Some(Target::Container(Arc::make_mut(container))),
+                ShortCase::Leaf(leaf) => Some(Target::Leaf(Arc::make_mut(leaf))),
+                ShortCase::LeafList(leaf_list) => Some(Target::LeafList(Arc::make_mut(leaf_list))),
+                ShortCase::List(list) => Some(Target::List(Arc::make_mut(list))),
+                ShortCase::Anydata(anydata) => Some(Target::Anydata(Arc::make_mut(anydata))),
+                ShortCase::Anyxml(anyxml) => Some(Target::Anyxml(Arc::make_mut(anyxml))),
+            },
+            Target::Operation(input, output) => match name {
+                "input" => input.as_mut().map(Target::Input),
+                "output" => output.as_mut().map(Target::Output),
+                _ => None,
+            },
+            Target::Leaf(_) | Target::LeafList(_) | Target::Anydata(_) | Target::Anyxml(_) => None,
+        }
+    }
+
+    fn augment(&mut self, augment: &Augment) {
+        let data_defs = augment.data_defs.iter().cloned();
+        match self {
+            Target::Container(Container {
+                data_defs: children,
+                actions,
+                notifications,
+                ..
+            })
+            | Target::List(List {
+                data_defs: children,
+                actions,
+                notifications,
+                ..
+            }) => {
+                children.extend(data_defs);
+                actions.extend(augment.actions.iter().cloned());
+                notifications.extend(augment.notifications.iter().cloned());
+            }
+            Target::Case(LongCase {
+                data_defs: children, ..
+            })
+            | Target::Input(Input {
+                data_defs: children, ..
+            })
+            | Target::Output(Output {
+                data_defs: children, ..
+            })
+            | Target::Notification(Notification {
+                data_defs: children, ..
+            }) => children.extend(data_defs),
+            Target::Choice(choice) => {
+                choice.cases.extend(augment.cases.iter().cloned());
+                choice.cases.extend(data_defs.filter_map(short_case));
+            }
+            _ => {}
+        }
+    }
+
+    // Whether the child was found, and so removed.
+    fn remove_child(&mut self, name: &str) -> bool {
+        fn remove<T>(items: &mut Vec<T>, keep: impl FnMut(&T) -> bool) -> bool {
+            let len = items.len();
+            items.retain(keep);
+            items.len() < len
+        }
+
+        let keep_data_def = |data_def: &DataDef| data_def_name(data_def) != Some(name);
+        match self {
+            Target::Body(body) => remove(body, |node| match node {
+                SchemaNode::DataDef(data_def) => keep_data_def(data_def),
+                SchemaNode::Rpc(rpc) => rpc.name != name,
+                SchemaNode::Notification(notification) => notification.name != name,
+            }),
+            Target::Container(Container {
+                data_defs,
+                actions,
+                notifications,
+                ..
+            })
+            | Target::List(List {
+                data_defs,
+                actions,
+                notifications,
+                ..
+            }) => {
+                remove(data_defs, keep_data_def)
+                    | remove(actions, |action| action.name != name)
+                    | remove(notifications, |notification| notification.name != name)
+            }
+            Target::DataDefs(data_defs) => remove(data_defs, keep_data_def),
+            Target::Case(LongCase { data_defs, .. })
+            | Target::Input(Input { data_defs, .. })
+            | Target::Output(Output { data_defs, .. })
+            | Target::Notification(Notification { data_defs, .. }) => remove(data_defs, keep_data_def),
+            Target::Choice(choice) => remove(&mut choice.cases, |case| case_name(case) != name),
+            Target::Operation(input, output) => match name {
+                "input" => input.take().is_some(),
+                "output" => output.take().is_some(),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    fn deviate_add(&mut self, add: &DeviateAdd) {
+        match self {
+            Target::Leaf(leaf) => {
+                set(&mut leaf.meta.units, &add.units);
+                extend_boxed(&mut leaf.must, add.must.iter().cloned());
+                set(&mut leaf.default, &add.default.first().cloned());
+                set(&mut leaf.config, &add.config);
+                set(&mut leaf.mandatory, &add.mandatory);
+            }
+            Target::LeafList(leaf_list) => {
+                set(&mut leaf_list.meta.units, &add.units);
+                extend_boxed(&mut leaf_list.must, add.must.iter().cloned());
+                leaf_list.default.extend(add.default.iter().cloned());
+                set(&mut leaf_list.config, &add.config);
+                set(&mut leaf_list.min_elements, &add.min_elements);
+                set(&mut leaf_list.max_elements, &add.max_elements);
+            }
+            Target::List(list) => {
+                extend_boxed(&mut list.must, add.must.iter().cloned());
+                list.unique.extend(add.unique.iter().cloned());
+                set(&mut list.config, &add.config);
+                set(&mut list.min_elements, &add.min_elements);
+                set(&mut list.max_elements, &add.max_elements);
+            }
+            Target::Container(container) => {
+                extend_boxed(&mut container.must, add.must.iter().cloned());
+                set(&mut container.config, &add.config);
+            }
+            Target::Choice(choice) => {
+                set(&mut choice.default, &add.default.first().cloned());
+                set(&mut choice.config, &add.config);
+                set(&mut choice.mandatory, &add.mandatory);
+            }
+            Target::Anydata(Anydata {
+                must,
+                config,
+                mandatory,
+                ..
+            })
+            | Target::Anyxml(Anyxml {
+                must,
+                config,
+                mandatory,
+                ..
+            }) => {
+                extend_boxed(must, add.must.iter().cloned());
+                set(config, &add.config);
+                set(mandatory, &add.mandatory);
+            }
+            _ => {}
+        }
+    }
+
+    fn deviate_delete(&mut self, delete: &DeviateDelete) {
+        let keep_must = |must: &Must| !delete.must.iter().any(|deleted| deleted.condition == must.condition);
+        match self {
+            Target::Leaf(leaf) => {
+                unset(&mut leaf.meta.units, &delete.units);
+                leaf.must = leaf.must.iter().filter(|must| keep_must(must)).cloned().collect();
+                leaf.default.take_if(|default| delete.default.contains(default));
+            }
+            Target::LeafList(leaf_list) => {
+                unset(&mut leaf_list.meta.units, &delete.units);
+                leaf_list.must = leaf_list.must.iter().filter(|must| keep_must(must)).cloned().collect();
+                leaf_list.default.retain(|default| !delete.default.contains(default));
+            }
+            Target::List(list) => {
+                list.must = list.must.iter().filter(|must| keep_must(must)).cloned().collect();
+                list.unique.retain(|unique| !delete.unique.contains(unique));
+            }
+            Target::Choice(choice) => {
+                choice.default.take_if(|default| delete.default.contains(default));
+            }
+            Target::Container(Container { must, .. })
+            | Target::Anydata(Anydata { must, .. })
+            | Target::Anyxml(Anyxml { must, .. }) => {
+                *must = must.iter().filter(|must| keep_must(must)).cloned().collect()
+            }
+            _ => {}
+        }
+    }
+
+    fn deviate_replace(&mut self, replace: &DeviateReplace) {
+        match self {
+            Target::Leaf(leaf) => {
+                if let Some(type_info) = &replace.type_info {
+                    leaf.type_info = type_info.clone();
+                }
+                set(&mut leaf.meta.units, &replace.units);
+                set(&mut leaf.default, &replace.default.first().cloned());
+                set(&mut leaf.config, &replace.config);
+                set(&mut leaf.mandatory, &replace.mandatory);
+            }
+            Target::LeafList(leaf_list) => {
+                if let Some(type_info) = &replace.type_info {
+                    leaf_list.type_info = type_info.clone();
+                }
+                set(&mut leaf_list.meta.units, &replace.units);
+                if !replace.default.is_empty() {
+                    leaf_list.default = replace.default.clone();
+                }
+                set(&mut leaf_list.config, &replace.config);
+                set(&mut leaf_list.min_elements, &replace.min_elements);
+                set(&mut leaf_list.max_elements, &replace.max_elements);
+            }
+            Target::List(list) => {
+                set(&mut list.config, &replace.config);
+                set(&mut list.min_elements, &replace.min_elements);
+                set(&mut list.max_elements, &replace.max_elements);
+            }
+            Target::Container(Container { config, .. }) => set(config, &replace.config),
+            Target::Choice(choice) => {
+                set(&mut choice.default, &replace.default.first().cloned());
+                set(&mut choice.config, &replace.config);
+                set(&mut choice.mandatory, &replace.mandatory);
+            }
+            Target::Anydata(Anydata { config, mandatory, .. }) | Target::Anyxml(Anyxml { config, mandatory, .. }) => {
+                set(config, &replace.config);
+                set(mandatory, &replace.mandatory);
+            }
+            _ => {}
+        }
+    }
+}
+
+// Properties are only changed by the deviates which have a value for them.
+fn set<T: Clone>(property: &mut Option<T>, value: &Option<T>) {
+    if value.is_some() {
+        property.clone_from(value);
+    }
+}
+
+fn unset<T: PartialEq>(property: &mut Option<T>, value: &Option<T>) {
+    if value.is_some() && property == value {
+        *property = None;
+    }
+}
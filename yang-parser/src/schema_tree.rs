@@ -0,0 +1,284 @@
+use std::ops::Range;
+
+use crate::model::*;
+
+/// The id of a node of a [SchemaTree], which is its index in the tree's arena of nodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(u32);
+
+impl NodeId {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// The statement of a node of a [SchemaTree], borrowed from the module.
+#[derive(Debug, Clone, Copy)]
+pub enum NodeKind<'m> {
+    Container(&'m Container),
+    Leaf(&'m Leaf),
+    LeafList(&'m LeafList),
+    List(&'m List),
+    Choice(&'m Choice),
+    Case(&'m LongCase),
+    Anydata(&'m Anydata),
+    Anyxml(&'m Anyxml),
+    Rpc(&'m Rpc),
+    Action(&'m Action),
+    Input(&'m Input),
+    Output(&'m Output),
+    Notification(&'m Notification),
+}
+
+impl<'m> NodeKind<'m> {
+    pub fn name(&self) -> &'m str {
+        match self {
+            NodeKind::Container(container) => &container.name,
+            NodeKind::Leaf(leaf) => &leaf.name,
+            NodeKind::LeafList(leaf_list) => &leaf_list.name,
+            NodeKind::List(list) => &list.name,
+            NodeKind::Choice(choice) => &choice.name,
+            NodeKind::Case(case) => &case.name,
+            NodeKind::Anydata(anydata) => &anydata.name,
+            NodeKind::Anyxml(anyxml) => &anyxml.name,
+            NodeKind::Rpc(rpc) => &rpc.name,
+            NodeKind::Action(action) => &action.name,
+            NodeKind::Input(_) => "input",
+            NodeKind::Output(_) => "output",
+            NodeKind::Notification(notification) => &notification.name,
+        }
+    }
+
+    /// The keyword of the statement, like "container" or "leaf-list".
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            NodeKind::Container(_) => "container",
+            NodeKind::Leaf(_) => "leaf",
+            NodeKind::LeafList(_) => "leaf-list",
+            NodeKind::List(_) => "list",
+            NodeKind::Choice(_) => "choice",
+            NodeKind::Case(_) => "case",
+            NodeKind::Anydata(_) => "anydata",
+            NodeKind::Anyxml(_) => "anyxml",
+            NodeKind::Rpc(_) => "rpc",
+            NodeKind::Action(_) => "action",
+            NodeKind::Input(_) => "input",
+            NodeKind::Output(_) => "output",
+            NodeKind::Notification(_) => "notification",
+        }
+    }
+
+    /// Whether the node is part of schema paths. Choices and cases aren't.
+    pub fn is_in_path(&self) -> bool {
+        !matches!(self, NodeKind::Choice(_) | NodeKind::Case(_))
+    }
+
+    // Call the function with the child nodes of the node, in the order of the statements.
+    fn for_each_child(&self, mut f: impl FnMut(NodeKind<'m>)) {
+        match *self {
+            NodeKind::Container(container) => {
+                data_def_kinds(&container.data_defs, &mut f);
+                operation_kinds(&container.actions, &container.notifications, &mut f);
+            }
+            NodeKind::List(list) => {
+                data_def_kinds(&list.data_defs, &mut f);
+                operation_kinds(&list.actions, &list.notifications, &mut f);
+            }
+            NodeKind::Choice(choice) => {
+                for case in &choice.cases {
+                    match case {
+                        Case::LongCase(long_case) => f(NodeKind::Case(long_case)),
+                        Case::ShortCase(short_case) => f(short_case_kind(short_case)),
+                    }
+                }
+            }
+            NodeKind::Case(case) => data_def_kinds(&case.data_defs, &mut f),
+            NodeKind::Rpc(rpc) => input_output_kinds(&rpc.input, &rpc.output, &mut f),
+            NodeKind::Action(action) => input_output_kinds(&action.input, &action.output, &mut f),
+            NodeKind::Input(input) => data_def_kinds(&input.data_defs, &mut f),
+            NodeKind::Output(output) => data_def_kinds(&output.data_defs, &mut f),
+            NodeKind::Notification(notification) => data_def_kinds(&notification.data_defs, &mut f),
+            NodeKind::Leaf(_) | NodeKind::LeafList(_) | NodeKind::Anydata(_) | NodeKind::Anyxml(_) => {}
+        }
+    }
+}
+
+/// A node of a [SchemaTree], with the ids of its parent and children.
+#[derive(Debug, Clone)]
+pub struct Node<'m> {
+    pub kind: NodeKind<'m>,
+    pub parent: Option<NodeId>,
+    children: Range<u32>,
+}
+
+/// The schema tree of a resolved module, with every node in one arena and addressed by a [NodeId], so a node knows its
+/// parent as well as its children, and ids are cheap to copy and keep around, unlike references into the nested nodes
+/// of the module. The tree borrows the nodes from the module, so nodes expanded from the same grouping aren't copied,
+/// and building it only allocates the arena.
+///
+/// Choices and cases are nodes of the tree, where the node of a short case is the child of its choice, without a case
+/// node in between. Uses statements of groupings which couldn't be resolved aren't part of the tree.
+#[derive(Debug, Clone)]
+pub struct SchemaTree<'m> {
+    nodes: Vec<Node<'m>>,
+    roots: Range<u32>,
+}
+
+impl<'m> SchemaTree<'m> {
+    pub fn new(module: &'m Module) -> Self {
+        let mut nodes = Vec::new();
+        for node in &module.body {
+            let kind = match node {
+                SchemaNode::DataDef(data_def) => data_def_kind(data_def),
+                SchemaNode::Rpc(rpc) => Some(NodeKind::Rpc(rpc)),
+                SchemaNode::Notification(notification) => Some(NodeKind::Notification(notification)),
+            };
+            if let Some(kind) = kind {
+                nodes.push(Node {
+                    kind,
+                    parent: None,
+                    children: 0..0,
+                });
+            }
+        }
+        let roots = 0..nodes.len() as u32;
+
+        // The nodes are added breadth first, so the children of every node are next to each other in the arena.
+        let mut next = 0;
+        while next < nodes.len() {
+            let start = nodes.len() as u32;
+            let parent = Some(NodeId(next as u32));
+            let kind = nodes[next].kind;
+            kind.for_each_child(|kind| {
+                nodes.push(Node {
+                    kind,
+                    parent,
+                    children: 0..0,
+                })
+            });
+            nodes[next].children = start..nodes.len() as u32;
+            next += 1;
+        }
+
+        Self { nodes, roots }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn node(&self, id: NodeId) -> &Node<'m> {
+        &self.nodes[id.index()]
+    }
+
+    pub fn kind(&self, id: NodeId) -> NodeKind<'m> {
+        self.nodes[id.index()].kind
+    }
+
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.nodes[id.index()].parent
+    }
+
+    /// The top-level nodes of the module.
+    pub fn roots(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.roots.clone().map(NodeId)
+    }
+
+    pub fn children(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.nodes[id.index()].children.clone().map(NodeId)
+    }
+
+    /// The parent of the node, its parent, and so on up to the top level.
+    pub fn ancestors(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        std::iter::successors(self.parent(id), |id| self.parent(*id))
+    }
+
+    /// Every node of the tree, where parents come before their children.
+    pub fn iter(&self) -> impl Iterator<Item = NodeId> + '_ {
+        (0..self.nodes.len() as u32).map(NodeId)
+    }
+
+    /// The schema path of the node, like "/interfaces/interface/name", without choices and cases.
+    pub fn path(&self, id: NodeId) -> String {
+        let mut names: Vec<&str> = std::iter::once(id)
+            .chain(self.ancestors(id))
+            .map(|id| self.kind(id))
+            .filter(NodeKind::is_in_path)
+            .map(|kind| kind.name())
+            .collect();
+        names.reverse();
+
+        let mut path = String::new();
+        for name in names {
+            path.push('/');
+            path.push_str(name);
+        }
+        path
+    }
+
+    /// The node at the schema path, like "/interfaces/interface/name", where prefixes are ignored. Choices and cases
+    /// aren't part of paths.
+    pub fn find(&self, path: &str) -> Option<NodeId> {
+        let mut candidates: Vec<NodeId> = self.roots().collect();
+        let mut found = None;
+        for step in path.split('/').filter(|step| !step.is_empty()) {
+            let name = step.rsplit(':').next().unwrap_or(step);
+            let id = self.find_child(&candidates, name)?;
+            candidates = self.children(id).collect();
+            found = Some(id);
+        }
+
+        found
+    }
+
+    // The node with the name among the nodes, looking into choices and cases.
+    fn find_child(&self, ids: &[NodeId], name: &str) -> Option<NodeId> {
+        ids.iter().find_map(|&id| match self.kind(id) {
+            kind if kind.is_in_path() => (kind.name() == name).then_some(id),
+            _ => self.find_child(&self.children(id).collect::<Vec<_>>(), name),
+        })
+    }
+}
+
+fn data_def_kind(data_def: &DataDef) -> Option<NodeKind<'_>> {
+    match data_def {
+        DataDef::Container(container) => Some(NodeKind::Container(container)),
+        DataDef::Leaf(leaf) => Some(NodeKind::Leaf(leaf)),
+        DataDef::LeafList(leaf_list) => Some(NodeKind::LeafList(leaf_list)),
+        DataDef::List(list) => Some(NodeKind::List(list)),
+        DataDef::Choice(choice) => Some(NodeKind::Choice(choice)),
+        DataDef::AnyData(anydata) => Some(NodeKind::Anydata(anydata)),
+        DataDef::Anyxml(anyxml) => Some(NodeKind::Anyxml(anyxml)),
+        DataDef::Uses(_) => None,
+    }
+}
+
+fn short_case_kind(short_case: &ShortCase) -> NodeKind<'_> {
+    match short_case {
+        ShortCase::Choice(choice) => NodeKind::Choice(choice),
+        ShortCase::Container(container) => NodeKind::Container(container),
+        ShortCase::Leaf(leaf) => NodeKind::Leaf(leaf),
+        ShortCase::LeafList(leaf_list) => NodeKind::LeafList(leaf_list),
+        ShortCase::List(list) => NodeKind::List(list),
+        ShortCase::Anydata(anydata) => NodeKind::Anydata(anydata),
+        ShortCase::Anyxml(anyxml) => NodeKind::Anyxml(anyxml),
+    }
+}
+
+fn data_def_kinds<'m>(data_defs: &'m [DataDef], f: &mut impl FnMut(NodeKind<'m>)) {
+    data_defs.iter().filter_map(data_def_kind).for_each(f);
+}
+
+fn operation_kinds<'m>(actions: &'m [Action], notifications: &'m [Notification], f: &mut impl FnMut(NodeKind<'m>)) {
+    actions.iter().map(NodeKind::Action).for_each(&mut *f);
+    notifications.iter().map(NodeKind::Notification).for_each(f);
+}
+
+fn input_output_kinds<'m>(input: &'m Option<Input>, output: &'m Option<Output>, f: &mut impl FnMut(NodeKind<'m>)) {
+    input.iter().map(NodeKind::Input).for_each(&mut *f);
+    output.iter().map(NodeKind::Output).for_each(f);
+}
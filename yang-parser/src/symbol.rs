@@ -0,0 +1,198 @@
+use std::{
+    borrow::Borrow,
+    collections::HashSet,
+    fmt,
+    hash::{DefaultHasher, Hash, Hasher},
+    ops::Deref,
+    sync::{Arc, Mutex},
+};
+
+use crate::model::TypeBody;
+
+/// An immutable, cheaply cloned string used for identifiers which repeat throughout a module set, like module names,
+/// prefixes and type names. Symbols created by the parser are interned, so every occurrence of the same identifier
+/// shares one allocation, and comparing them is usually a pointer comparison.
+///
+/// A Symbol dereferences to `str` and compares equal to strings, so it can mostly be used like a `String`.
+#[derive(Clone, Default, PartialOrd, Ord)]
+pub struct Symbol(Arc<str>);
+
+impl Symbol {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Deref for Symbol {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Symbol {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+// Symbols hash like their string, so maps keyed by symbols can be looked up with a `&str`.
+impl Borrow<str> for Symbol {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Hash for Symbol {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+impl PartialEq for Symbol {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0) || self.0 == other.0
+    }
+}
+
+impl Eq for Symbol {}
+
+impl PartialEq<str> for Symbol {
+    fn eq(&self, other: &str) -> bool {
+        &*self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Symbol {
+    fn eq(&self, other: &&str) -> bool {
+        &*self.0 == *other
+    }
+}
+
+impl PartialEq<String> for Symbol {
+    fn eq(&self, other: &String) -> bool {
+        *self.0 == **other
+    }
+}
+
+impl PartialEq<Symbol> for str {
+    fn eq(&self, other: &Symbol) -> bool {
+        self == &*other.0
+    }
+}
+
+impl PartialEq<Symbol> for &str {
+    fn eq(&self, other: &Symbol) -> bool {
+        *self == &*other.0
+    }
+}
+
+impl PartialEq<Symbol> for String {
+    fn eq(&self, other: &Symbol) -> bool {
+        **self == *other.0
+    }
+}
+
+impl From<&str> for Symbol {
+    fn from(value: &str) -> Self {
+        Self(value.into())
+    }
+}
+
+impl From<String> for Symbol {
+    fn from(value: String) -> Self {
+        Self(value.into())
+    }
+}
+
+impl From<Symbol> for String {
+    fn from(value: Symbol) -> Self {
+        value.0.to_string()
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&*self.0, f)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Symbol {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Symbol {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Symbol::from)
+    }
+}
+
+// The number of shards of the interner's tables. The parsers of the modules imported at the same level, and of the
+// modules of a scan, run on the threads of rayon and intern every identifier they parse, so each table is split into
+// shards with a lock of their own, and parsers interning different strings rarely wait for each other.
+const SHARDS: usize = 16;
+
+/// Internal table of the symbols and type bodies created while loading a module and the modules it imports. Clones
+/// share the table.
+#[derive(Debug, Clone, Default)]
+pub struct Interner {
+    symbols: Arc<Shards<Symbol>>,
+    type_bodies: Arc<Shards<Arc<TypeBody>>>,
+}
+
+#[derive(Debug)]
+struct Shards<T>([Mutex<HashSet<T>>; SHARDS]);
+
+impl<T> Default for Shards<T> {
+    fn default() -> Self {
+        Self(std::array::from_fn(|_| Mutex::default()))
+    }
+}
+
+impl<T: Hash + Eq + Clone> Shards<T> {
+    // The shared value equal to the value, which is made from the value and added to the shard of its hash if there's
+    // none.
+    fn get_or_insert<K, V>(&self, value: V, make: impl FnOnce(V) -> T) -> T
+    where
+        T: Borrow<K>,
+        K: Hash + Eq + ?Sized,
+        V: Borrow<K>,
+    {
+        let mut hasher = DefaultHasher::new();
+        value.borrow().hash(&mut hasher);
+        let shard = &self.0[hasher.finish() as usize % SHARDS];
+
+        let mut values = shard.lock().expect("the interner to not be poisoned");
+        match values.get(value.borrow()) {
+            Some(shared) => shared.clone(),
+            None => {
+                let shared = make(value);
+                values.insert(shared.clone());
+                shared
+            }
+        }
+    }
+}
+
+impl Interner {
+    /// The symbol of the value, which is the same allocation for every call with an equal value.
+    pub fn intern(&self, value: &str) -> Symbol {
+        self.symbols.get_or_insert::<str, _>(value, Symbol::from)
+    }
+
+    /// The shared instance of the type body, which is the same allocation for every call with an equal type body.
+    pub fn intern_type_body(&self, type_body: TypeBody) -> Arc<TypeBody> {
+        self.type_bodies.get_or_insert::<TypeBody, _>(type_body, Arc::new)
+    }
+}
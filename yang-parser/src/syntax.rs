@@ -0,0 +1,513 @@
+use pest::{
+    error::{Error, ErrorVariant, InputLocation},
+    Position,
+};
+
+use crate::parser_internal::Rule;
+
+// Pest reports the rules it expected where parsing failed, like "expected type_info, must, or when". The rules are
+// translated to the statements of YANG they stand for, with the statement they would be in and what was found instead,
+// like "expected a substatement of 'leaf' such as 'type', 'must' or 'when', found 'tpye'; did you mean 'type'?".
+
+const MAX_EXAMPLES: usize = 5;
+
+// Arguments which aren't keywords are described, rather than quoted.
+const STRING: &str = "a string";
+const INTEGER: &str = "an integer";
+const END: &str = "the end of the file";
+
+/// The error with its message in terms of YANG statements, where the input is the source which was parsed.
+pub fn explain(mut error: Error<Rule>, input: &str) -> Error<Rule> {
+    let ErrorVariant::ParsingError { positives, .. } = &error.variant else {
+        return error;
+    };
+    let position = match error.location {
+        InputLocation::Pos(position) | InputLocation::Span((position, _)) => position,
+    };
+
+    let mut expected = Vec::new();
+    for rule in positives {
+        for keyword in keywords(*rule) {
+            if !expected.contains(&keyword) {
+                expected.push(keyword);
+            }
+        }
+    }
+    if expected.is_empty() {
+        return error;
+    }
+
+    let found = found(input, position);
+    let context = context(input, position);
+
+    // A statement which was expected but didn't parse, or whose argument parsing failed in, is usually missing the ';'
+    // or block after its argument, which is reported where it's missing, rather than where pest gave up.
+    let current = match found {
+        Found::Token(token) if is_keyword(token) && expected.contains(&token) => Some((position, token)),
+        _ => context.current,
+    };
+    if let Some((start, keyword)) = current.filter(|(_, keyword)| is_keyword(keyword)) {
+        if let Some(end) = argument_end(input, start + keyword.len()).filter(|end| *end >= position) {
+            let message = format!(
+                "expected ';' or '{{' after {}, found {}",
+                statement(&input[start..end], keyword),
+                self::found(input, skip_separators(input, end))
+            );
+            let position = Position::new(input, end).expect("the end of the argument to be in the input");
+            return Error::new_from_pos(ErrorVariant::CustomError { message }, position);
+        }
+    }
+
+    let message = match (context.parent, &found) {
+        // The statement itself was expected, so it's the statement which is wrong, like a missing semicolon.
+        (_, Found::Token(token)) if is_keyword(token) && expected.contains(token) => {
+            format!(
+                "invalid '{}' statement, which ends with ';' or a block in braces",
+                token
+            )
+        }
+        (Some(parent), _) if positives.iter().all(|rule| is_statement(*rule)) => format!(
+            "expected a substatement of '{}' such as {}, found {}",
+            parent,
+            examples(&expected),
+            found
+        ),
+        _ => format!("expected {}, found {}", examples(&expected), found),
+    };
+    let suggestion = match &found {
+        Found::Token(token) if !is_keyword(token) => suggest(token, &expected),
+        _ => None,
+    };
+
+    error.variant = ErrorVariant::CustomError {
+        message: match suggestion {
+            Some(keyword) => format!("{}; did you mean '{}'?", message, keyword),
+            None => message,
+        },
+    };
+    error
+}
+
+// What is at the position where parsing failed.
+enum Found<'a> {
+    Token(&'a str),
+    End,
+}
+
+impl std::fmt::Display for Found<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Found::Token(token) => write!(f, "'{}'", token),
+            Found::End => f.write_str(END),
+        }
+    }
+}
+
+fn found(input: &str, position: usize) -> Found<'_> {
+    let rest = &input[position..];
+    let Some(first) = rest.chars().next() else {
+        return Found::End;
+    };
+    let len = rest.find(is_delimiter).unwrap_or(rest.len());
+    Found::Token(&rest[..len.max(first.len_utf8())])
+}
+
+fn is_delimiter(c: char) -> bool {
+    c.is_whitespace() || matches!(c, ';' | '{' | '}' | '"' | '\'')
+}
+
+// Where the argument of the statement, which starts at the position, ends, if it's not followed by ';' or '{'. Quoted
+// arguments may be concatenated with '+'.
+fn argument_end(input: &str, position: usize) -> Option<usize> {
+    let mut index = skip_separators(input, position);
+    if index == position || index == input.len() {
+        return None;
+    }
+    loop {
+        let rest = &input[index..];
+        index += match rest.chars().next()? {
+            ';' | '{' => return None,
+            quote @ ('"' | '\'') => quoted_len(rest, quote),
+            _ => rest
+                .find(|c: char| c.is_whitespace() || matches!(c, ';' | '{' | '}'))
+                .unwrap_or(rest.len()),
+        };
+        let end = index;
+        index = skip_separators(input, index);
+        match input[index..].chars().next() {
+            Some(';' | '{') => return None,
+            Some('+') if input[..end].ends_with(['"', '\'']) => {
+                index = skip_separators(input, index + 1);
+            }
+            _ => return Some(end),
+        }
+    }
+}
+
+// The position after the whitespace and comments at the position.
+fn skip_separators(input: &str, mut position: usize) -> usize {
+    loop {
+        let rest = &input[position..];
+        position += match rest.chars().next() {
+            Some(c) if c.is_whitespace() => c.len_utf8(),
+            _ if rest.starts_with("//") => rest.find('\n').unwrap_or(rest.len()),
+            _ if rest.starts_with("/*") => rest.find("*/").map_or(rest.len(), |end| end + 2),
+            _ => return position,
+        };
+    }
+}
+
+// The statement quoted if it fits on a line, like "'prefix ex'", or else described by its keyword.
+fn statement(statement: &str, keyword: &str) -> String {
+    match statement.contains('\n') || statement.chars().count() > 40 {
+        true => format!("the argument of '{}'", keyword),
+        false => format!("'{}'", statement),
+    }
+}
+
+// The statements around a position.
+struct Context<'a> {
+    // The keyword of the statement whose block the position is in.
+    parent: Option<&'a str>,
+    // The start and keyword of the statement the position is in, if it's past the keyword.
+    current: Option<(usize, &'a str)>,
+}
+
+// The statements around the position, found by following the braces and semicolons of the source before it, skipping
+// strings and comments.
+fn context(input: &str, position: usize) -> Context<'_> {
+    let source = &input[..position];
+    let bytes = source.as_bytes();
+    let mut blocks: Vec<Option<&str>> = Vec::new();
+    let mut keyword: Option<(usize, &str)> = None;
+    let mut index = 0;
+    while index < bytes.len() {
+        let rest = &source[index..];
+        index += match bytes[index] {
+            b'{' => {
+                blocks.push(keyword.take().map(|(_, keyword)| keyword));
+                1
+            }
+            b'}' => {
+                blocks.pop();
+                keyword = None;
+                1
+            }
+            b';' => {
+                keyword = None;
+                1
+            }
+            b'"' => quoted_len(rest, '"'),
+            b'\'' => quoted_len(rest, '\''),
+            _ if rest.starts_with("//") => rest.find('\n').unwrap_or(rest.len()),
+            _ if rest.starts_with("/*") => rest.find("*/").map_or(rest.len(), |end| end + 2),
+            byte if byte.is_ascii_whitespace() || byte == b'+' => 1,
+            _ => {
+                let len = rest.find(is_delimiter).unwrap_or(rest.len());
+                keyword.get_or_insert((index, &rest[..len]));
+                len
+            }
+        };
+    }
+
+    Context {
+        parent: blocks.pop().flatten(),
+        current: keyword,
+    }
+}
+
+// The length of the quoted string at the start of the source, with its quotes, where double quoted strings have escapes.
+fn quoted_len(source: &str, quote: char) -> usize {
+    let mut escaped = false;
+    for (index, c) in source.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quote == '"' => escaped = true,
+            _ if c == quote => return index + 1,
+            _ => {}
+        }
+    }
+    source.len()
+}
+
+// The first few keywords, like "'type', 'must' or 'when'".
+fn examples(keywords: &[&str]) -> String {
+    let quoted: Vec<String> = keywords
+        .iter()
+        .take(MAX_EXAMPLES)
+        .map(|keyword| match *keyword {
+            STRING | INTEGER | END => keyword.to_string(),
+            keyword => format!("'{}'", keyword),
+        })
+        .collect();
+    match quoted.split_last() {
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) if keywords.len() > MAX_EXAMPLES => format!("{}, {}, …", rest.join(", "), last),
+        Some((last, rest)) => format!("{} or {}", rest.join(", "), last),
+        None => String::new(),
+    }
+}
+
+// The expected keyword closest to the token, if it's close enough to be a misspelling of it.
+fn suggest<'a>(token: &str, expected: &[&'a str]) -> Option<&'a str> {
+    expected
+        .iter()
+        .map(|keyword| (edit_distance(token, keyword), *keyword))
+        .filter(|(distance, keyword)| *distance <= (keyword.len() / 4).max(1))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, keyword)| keyword)
+}
+
+// The number of insertions, deletions, substitutions and transpositions of adjacent characters between the strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![(0..=b.len()).collect::<Vec<_>>()];
+    for i in 1..=a.len() {
+        let mut row = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            row[j] = (rows[i - 1][j] + 1).min(row[j - 1] + 1).min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(rows[i - 2][j - 2] + 1);
+            }
+        }
+        rows.push(row);
+    }
+
+    rows[a.len()][b.len()]
+}
+
+fn is_keyword(token: &str) -> bool {
+    KEYWORDS.contains(&token)
+}
+
+// Whether the rule is one or more statements, rather than an argument, like a string or a boolean.
+fn is_statement(rule: Rule) -> bool {
+    !matches!(
+        rule,
+        Rule::string
+            | Rule::boolean
+            | Rule::integer
+            | Rule::status_value
+            | Rule::ordered_by_value
+            | Rule::modifier_value
+            | Rule::EOI
+    )
+}
+
+// The keywords of the statements the rule stands for, or the values or description of the argument it is.
+fn keywords(rule: Rule) -> Vec<&'static str> {
+    let keyword = match rule {
+        Rule::body => {
+            let mut keywords = keywords(Rule::data_def);
+            keywords.extend([
+                "grouping",
+                "typedef",
+                "identity",
+                "augment",
+                "feature",
+                "extension",
+                "rpc",
+                "notification",
+                "deviation",
+            ]);
+            return keywords;
+        }
+        Rule::data_def => {
+            return vec![
+                "container",
+                "leaf",
+                "leaf-list",
+                "list",
+                "uses",
+                "choice",
+                "anydata",
+                "anyxml",
+            ]
+        }
+        Rule::short_case => return vec!["container", "leaf", "leaf-list", "list", "choice", "anydata", "anyxml"],
+        Rule::string_restriction | Rule::binary_specification => return vec!["length", "pattern"],
+        Rule::type_info | Rule::union_specification => "type",
+        Rule::type_def => "typedef",
+        Rule::long_case => "case",
+        Rule::decimal64_specification => "fraction-digits",
+        Rule::numberical_restriction => "range",
+        Rule::enum_specification => "enum",
+        Rule::leafref_specification => "path",
+        Rule::identityref_specification => "base",
+        Rule::instance_identifier_specification => "require-instance",
+        Rule::bits_specification => "bit",
+        Rule::deviation_not_supported => "deviate not-supported",
+        Rule::deviate_add => "deviate add",
+        Rule::deviate_delete => "deviate delete",
+        Rule::deviate_replace => "deviate replace",
+        Rule::string | Rule::unquoted_string | Rule::single_quoted_string | Rule::double_quoted_string => STRING,
+        Rule::integer => INTEGER,
+        Rule::EOI => END,
+        Rule::boolean => return vec!["true", "false"],
+        Rule::status_value => return vec!["current", "deprecated", "obsolete"],
+        Rule::ordered_by_value => return vec!["user", "system"],
+        Rule::modifier_value => "invert-match",
+        _ => match KEYWORDS.iter().find(|keyword| is_rule_of(rule, keyword)) {
+            Some(keyword) => keyword,
+            None => return Vec::new(),
+        },
+    };
+    vec![keyword]
+}
+
+// Most rules are named after their keyword, with underscores for dashes, like if_feature for "if-feature".
+fn is_rule_of(rule: Rule, keyword: &str) -> bool {
+    is_named_after(&format!("{:?}", rule), keyword)
+}
+
+fn is_named_after(name: &str, keyword: &str) -> bool {
+    name.len() == keyword.len()
+        && name
+            .chars()
+            .zip(keyword.chars())
+            .all(|(a, b)| a == b || (a == '_' && b == '-'))
+}
+
+/// The keyword of the statement the rule matches, or None if the rule is a group of statements, like a data definition,
+/// or an argument.
+pub fn keyword(rule: Rule) -> Option<&'static str> {
+    match rule {
+        Rule::type_info => Some("type"),
+        Rule::type_def => Some("typedef"),
+        Rule::long_case => Some("case"),
+        Rule::deviation_not_supported | Rule::deviate_add | Rule::deviate_delete | Rule::deviate_replace => {
+            Some("deviate")
+        }
+        _ => {
+            let name = format!("{:?}", rule);
+            KEYWORDS.iter().copied().find(|keyword| is_named_after(&name, keyword))
+        }
+    }
+}
+
+const KEYWORDS: &[&str] = &[
+    "action",
+    "anydata",
+    "anyxml",
+    "argument",
+    "augment",
+    "base",
+    "belongs-to",
+    "bit",
+    "case",
+    "choice",
+    "config",
+    "contact",
+    "container",
+    "default",
+    "description",
+    "deviate",
+    "deviation",
+    "enum",
+    "error-app-tag",
+    "error-message",
+    "extension",
+    "feature",
+    "fraction-digits",
+    "grouping",
+    "identity",
+    "if-feature",
+    "import",
+    "include",
+    "input",
+    "key",
+    "leaf",
+    "leaf-list",
+    "length",
+    "list",
+    "mandatory",
+    "max-elements",
+    "min-elements",
+    "modifier",
+    "module",
+    "must",
+    "namespace",
+    "notification",
+    "ordered-by",
+    "organization",
+    "output",
+    "path",
+    "pattern",
+    "position",
+    "prefix",
+    "presence",
+    "range",
+    "reference",
+    "refine",
+    "require-instance",
+    "revision",
+    "revision-date",
+    "rpc",
+    "status",
+    "submodule",
+    "type",
+    "typedef",
+    "unique",
+    "units",
+    "uses",
+    "value",
+    "when",
+    "yang-version",
+    "yin-element",
+];
+
+#[cfg(test)]
+mod tests {
+    use crate::Document;
+
+    fn message(source: &str) -> String {
+        Document::parse(source)
+            .expect_err("the module to have a syntax error")
+            .to_string()
+    }
+
+    #[test]
+    fn missing_semicolons_are_reported_after_the_argument() {
+        let source = "module example {\n  namespace \"urn:example\";\n  prefix ex\n  leaf name { type string; }\n}\n";
+        assert_eq!(
+            message(source),
+            "invalid YANG file: expected ';' or '{' after 'prefix ex', found 'leaf' (line 3, column 12)"
+        );
+
+        let source = "module example {\n  namespace \"urn:example\";\n  prefix ex;\n  leaf name { type string }\n}\n";
+        assert_eq!(
+            message(source),
+            "invalid YANG file: expected ';' or '{' after 'type string', found '}' (line 4, column 26)"
+        );
+
+        let source = "module example {\n  namespace \"urn:example\";\n  prefix ex;\n  \
+                      container system { description \"System\" }\n}\n";
+        assert_eq!(
+            message(source),
+            "invalid YANG file: expected ';' or '{' after 'description \"System\"', found '}' (line 4, column 42)"
+        );
+    }
+
+    #[test]
+    fn long_and_concatenated_arguments_are_described_by_their_keyword() {
+        let source = "module example {\n  namespace \"urn:example\";\n  prefix ex;\n  \
+                      organization \"Example\" +\n    \" Networks\"\n  leaf name { type string; }\n}\n";
+        assert_eq!(
+            message(source),
+            "invalid YANG file: expected ';' or '{' after the argument of 'organization', found 'leaf' \
+             (line 5, column 16)"
+        );
+    }
+
+    #[test]
+    fn misspelled_keywords_are_suggested() {
+        let source = "module example {\n  namespace \"urn:example\";\n  prefix ex;\n  leaf name { tpye string; }\n}\n";
+        assert_eq!(
+            message(source),
+            "invalid YANG file: expected a substatement of 'leaf' such as 'type', 'must', 'when', 'if-feature', \
+             'description', …, found 'tpye'; did you mean 'type'? (line 4, column 15)"
+        );
+    }
+}
@@ -0,0 +1,348 @@
+use std::{cell::RefCell, collections::HashMap, sync::Arc};
+
+use crate::{leafref, model::*, schema};
+
+// Typedefs deriving from typedefs are followed to the built-in type, up to a limit in case the chain is a cycle.
+const MAX_DEPTH: usize = 32;
+
+/// Resolves the types of the leaves of a module on demand, rather than all of them up front, for workflows which only
+/// look at a part of a huge schema, like looking up a few paths or generating code for a single subtree. A type is
+/// resolved the first time it's asked for and remembered, so asking again is a lookup.
+///
+/// Modules parsed with [crate::ParseOptions::lazy_leafrefs] have their leafrefs resolved here. Modules with resolved
+/// leafrefs can be used as well, where the leafrefs keep their targets.
+#[derive(Debug)]
+pub struct TypeResolver<'m> {
+    module: &'m Module,
+    leaf_types: RefCell<HashMap<String, Option<TypeInfo>>>,
+}
+
+impl<'m> TypeResolver<'m> {
+    pub fn new(module: &'m Module) -> Self {
+        Self {
+            module,
+            leaf_types: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// The module the types are resolved in.
+    pub fn module(&self) -> &'m Module {
+        self.module
+    }
+
+    /// The effective type of the leaf or leaf-list at the schema path, like "/interfaces/interface/name", where
+    /// prefixes are ignored, as described by [TypeResolver::effective_type]. Choices and cases aren't part of paths.
+    pub fn leaf_type(&self, path: &str) -> Option<TypeInfo> {
+        if let Some(type_info) = self.leaf_types.borrow().get(path) {
+            return type_info.clone();
+        }
+
+        let type_info = leafref::leaf_type(&self.module.body, path).map(|type_info| {
+            // The type statement of a leaf used from a grouping is in the grouping, where its typedefs are.
+            let (grouping, steps) = leafref::type_scope(&self.module.body, path).unwrap_or_default();
+            let (module_name, mut scope) = match grouping.and_then(|grouping| grouping.split_once(':')) {
+                Some((module_name, grouping_path)) => (module_name, format!("{}/", grouping_path)),
+                None => (self.module.name.as_str(), String::from("/")),
+            };
+            for step in steps {
+                scope.push_str(&step);
+                scope.push('/');
+            }
+            self.effective_type_in(&type_info, module_name, &scope)
+        });
+        self.leaf_types.borrow_mut().insert(path.to_string(), type_info.clone());
+        type_info
+    }
+
+    /// The effective type of a type statement in the module with the name, which is the built-in type its typedefs
+    /// derive from, with the restrictions of the most derived type restricting them, and the patterns of all of them.
+    /// The members of unions are resolved the same way, and leafrefs with a target are the type of their target.
+    /// Types which can't be resolved are returned as they are.
+    ///
+    /// Only typedefs at the top level are found, as nested typedefs are only visible within the statements they are
+    /// defined in. Use [TypeResolver::effective_type_in] for type statements within other statements.
+    pub fn effective_type(&self, type_info: &TypeInfo, module_name: &str) -> TypeInfo {
+        self.effective_type_in(type_info, module_name, "/")
+    }
+
+    /// The effective type of a type statement like [TypeResolver::effective_type], where the statement is within the
+    /// statements at the scope, so the typedefs defined in them are found as well. The scope is the path of the
+    /// statements, like "/system/server/" for a leaf of the server container in the system container, where groupings
+    /// are part of the path, but choices and cases aren't.
+    pub fn effective_type_in(&self, type_info: &TypeInfo, module_name: &str, scope: &str) -> TypeInfo {
+        let mut effective = type_info.clone();
+        let mut module_name = module_name.to_string();
+        let mut scope = scope.to_string();
+        for _ in 0..MAX_DEPTH {
+            let Some((type_def_module, type_def_scope, type_def)) =
+                self.find_type_def(&effective.name, &module_name, &scope)
+            else {
+                break;
+            };
+            effective.name = type_def.type_info.name.clone();
+            merge_restrictions(&mut effective.type_body, type_def.type_info.type_body.as_ref());
+            module_name = type_def_module;
+            scope = type_def_scope;
+        }
+
+        match effective.type_body.as_mut().map(Arc::make_mut) {
+            Some(TypeBody::Leafref {
+                target: Some(target), ..
+            }) => return self.effective_type(target, &module_name),
+            Some(TypeBody::Union { types }) => {
+                for member in types {
+                    *member = self.effective_type_in(member, &module_name, &scope);
+                }
+            }
+            _ => {}
+        }
+
+        effective
+    }
+
+    // The typedef a type name refers to from a statement within the statements at the scope in the module with the
+    // name, with the name of the module defining it and the scope of the typedef. The typedefs defined in the
+    // statements at the scope and in the statements they are in are found, from the innermost statement up to the top
+    // level, like groupings are. Typedefs of other modules are only found at their top level.
+    fn find_type_def(&self, name: &str, module_name: &str, scope: &str) -> Option<(String, String, &'m TypeDef)> {
+        let module = self.module;
+        let (module_name, name, scope) = match name.split_once(':') {
+            Some((prefix, name)) if prefix == module.prefix => (module.name.to_string(), name, scope),
+            Some((prefix, name)) => match module.prefix_to_module.get(prefix) {
+                Some(module_name) => (module_name.to_string(), name, "/"),
+                None => (module_name.to_string(), name, scope),
+            },
+            None => (module_name.to_string(), name, scope),
+        };
+        let reference_nodes = match module_name == module.name {
+            true => &module.reference_nodes,
+            false => module.imported_modules.get(module_name.as_str())?,
+        };
+
+        let mut scope = scope;
+        loop {
+            if let Some(type_def) = reference_nodes.type_defs.get(&format!("{}{}", scope, name)) {
+                return Some((module_name, scope.to_string(), type_def));
+            }
+
+            // The scope of the statement the statements at the scope are in, like "/system/" for "/system/server/".
+            let parent = scope.strip_suffix('/').and_then(|scope| scope.rfind('/'))?;
+            scope = &scope[..=parent];
+        }
+    }
+}
+
+/// Whether the type name is a built-in type, like "uint8" or "union", rather than a typedef.
+pub fn is_built_in(name: &str) -> bool {
+    schema::BUILT_IN_TYPES.contains(&name)
+}
+
+// Restrictions of a derived type replace those of its base type, except patterns, which all have to match.
+fn merge_restrictions(derived: &mut Option<Arc<TypeBody>>, base: Option<&Arc<TypeBody>>) {
+    let Some(base) = base else {
+        return;
+    };
+    let Some(derived) = derived.as_mut().map(Arc::make_mut) else {
+        *derived = Some(base.clone());
+        return;
+    };
+    let base = &**base;
+
+    // String types restricted only by a length are parsed like binary types.
+    if let (TypeBody::Binary { length }, TypeBody::String { .. }) = (&mut *derived, base) {
+        *derived = TypeBody::String {
+            length: length.take(),
+            patterns: Vec::new(),
+        };
+    }
+
+    match (derived, base) {
+        (
+            TypeBody::String { length, patterns },
+            TypeBody::String {
+                length: base_length,
+                patterns: base_patterns,
+            },
+        ) => {
+            if length.is_none() {
+                length.clone_from(base_length);
+            }
+            patterns.extend(base_patterns.iter().cloned());
+        }
+        (
+            TypeBody::Decimal64 { fraction_digits, range },
+            TypeBody::Decimal64 {
+                fraction_digits: base_fraction_digits,
+                range: base_range,
+            },
+        ) => {
+            if fraction_digits.is_empty() {
+                fraction_digits.clone_from(base_fraction_digits);
+            }
+            if range.is_none() {
+                range.clone_from(base_range);
+            }
+        }
+        (TypeBody::String { length, .. } | TypeBody::Binary { length }, TypeBody::Binary { length: base_length })
+            if length.is_none() =>
+        {
+            length.clone_from(base_length)
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{registry::ModuleRegistry, ParseOptions};
+
+    const MODULE: &str = r#"module example {
+  namespace "urn:example";
+  prefix ex;
+
+  import common {
+    prefix c;
+  }
+
+  typedef name {
+    type string;
+  }
+
+  grouping endpoint {
+    typedef port {
+      type uint16 {
+        range "1..max";
+      }
+    }
+    container address {
+      leaf port {
+        type port;
+      }
+    }
+  }
+
+  container system {
+    typedef name {
+      type string {
+        length "1..63";
+      }
+    }
+    typedef level {
+      type uint8 {
+        range "0..7";
+      }
+    }
+    typedef severity {
+      type level;
+    }
+    leaf hostname {
+      type name;
+    }
+    container logging {
+      leaf severity {
+        type severity;
+      }
+    }
+    container server {
+      uses endpoint;
+    }
+    container client {
+      uses c:endpoint;
+    }
+  }
+
+  container user {
+    leaf name {
+      type name;
+    }
+    leaf level {
+      type level;
+    }
+  }
+}
+"#;
+
+    const COMMON: &str = r#"module common {
+  namespace "urn:common";
+  prefix c;
+
+  grouping endpoint {
+    typedef port {
+      type uint32;
+    }
+    container address {
+      leaf port {
+        type port;
+      }
+    }
+  }
+}
+"#;
+
+    fn module() -> Module {
+        let options = ParseOptions {
+            registry: Some(Arc::new(
+                ModuleRegistry::new()
+                    .with("models/example.yang", MODULE)
+                    .with("models/common.yang", COMMON),
+            )),
+            ..Default::default()
+        };
+        match crate::parse_with_options("models/example.yang", &options).expect("the module to parse") {
+            YangModule::Module(module) => module,
+            YangModule::Submodule(_) => panic!("expected a module"),
+        }
+    }
+
+    #[test]
+    fn nested_typedefs_are_found_from_the_statements_they_are_defined_in() {
+        let module = module();
+        let types = TypeResolver::new(&module);
+
+        let hostname = types.leaf_type("/system/hostname").expect("the leaf");
+        assert_eq!(hostname.name, "string");
+        // String types restricted only by a length are parsed like binary types.
+        assert!(matches!(
+            hostname.type_body.as_deref(),
+            Some(TypeBody::Binary { length: Some(length) }) if length.value == "1..63"
+        ));
+
+        let severity = types.leaf_type("/system/logging/severity").expect("the leaf");
+        assert_eq!(severity.name, "uint8");
+        assert!(matches!(
+            severity.type_body.as_deref(),
+            Some(TypeBody::Numerical { range }) if range.value == "0..7"
+        ));
+    }
+
+    #[test]
+    fn nested_typedefs_of_other_statements_are_not_found() {
+        let module = module();
+        let types = TypeResolver::new(&module);
+
+        let name = types.leaf_type("/user/name").expect("the leaf");
+        assert_eq!(name.name, "string");
+        assert!(name.type_body.is_none());
+
+        // The level typedef is only visible within the system container.
+        let level = types.leaf_type("/user/level").expect("the leaf");
+        assert_eq!(level.name, "level");
+    }
+
+    #[test]
+    fn typedefs_of_groupings_are_found_where_the_grouping_is_used() {
+        let module = module();
+        let types = TypeResolver::new(&module);
+
+        let port = types.leaf_type("/system/server/address/port").expect("the leaf");
+        assert_eq!(port.name, "uint16");
+        assert!(matches!(
+            port.type_body.as_deref(),
+            Some(TypeBody::Numerical { range }) if range.value == "1..max"
+        ));
+
+        let port = types.leaf_type("/system/client/address/port").expect("the leaf");
+        assert_eq!(port.name, "uint32");
+    }
+}
@@ -0,0 +1,1227 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Write},
+    sync::{OnceLock, RwLock},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use regex::Regex;
+
+use crate::{error::ValueError, model::*};
+
+/// A leaf value parsed according to its YANG type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Int8(i8),
+    Int16(i16),
+    Int32(i32),
+    Int64(i64),
+    Uint8(u8),
+    Uint16(u16),
+    Uint32(u32),
+    Uint64(u64),
+    Decimal64(Decimal64),
+    Boolean(bool),
+    String(String),
+    Enumeration { name: String, value: i64 },
+    /// Names of the set bits, ordered by position.
+    Bits(Vec<String>),
+    Binary(Vec<u8>),
+    Empty,
+    Identityref { prefix: Option<String>, name: String },
+    InstanceIdentifier(String),
+}
+
+impl Value {
+    pub fn as_i8(&self) -> Option<i8> {
+        self.as_integer()
+    }
+
+    pub fn as_i16(&self) -> Option<i16> {
+        self.as_integer()
+    }
+
+    pub fn as_i32(&self) -> Option<i32> {
+        self.as_integer()
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_integer()
+    }
+
+    pub fn as_u8(&self) -> Option<u8> {
+        self.as_integer()
+    }
+
+    pub fn as_u16(&self) -> Option<u16> {
+        self.as_integer()
+    }
+
+    pub fn as_u32(&self) -> Option<u32> {
+        self.as_integer()
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_integer()
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Boolean(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_decimal64(&self) -> Option<Decimal64> {
+        match self {
+            Value::Decimal64(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(value) | Value::InstanceIdentifier(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the enum.
+    pub fn as_enum(&self) -> Option<&str> {
+        match self {
+            Value::Enumeration { name, .. } => Some(name),
+            _ => None,
+        }
+    }
+
+    pub fn as_bits(&self) -> Option<&[String]> {
+        match self {
+            Value::Bits(bits) => Some(bits),
+            _ => None,
+        }
+    }
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Value::Binary(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    /// Returns the prefix and name of the identity.
+    pub fn as_identity(&self) -> Option<(Option<&str>, &str)> {
+        match self {
+            Value::Identityref { prefix, name } => Some((prefix.as_deref(), name)),
+            _ => None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        matches!(self, Value::Empty)
+    }
+
+    /// The value in the canonical form libyang uses, where identityrefs and instance-identifiers are qualified with
+    /// module names rather than prefixes, like "ietf-interfaces:ethernetCsmacd", the way they are in JSON encoded data
+    /// (RFC 7951). Prefixes are those of the module the value is in, and unprefixed names are in that module. Other
+    /// values are in the same canonical form as their [fmt::Display] output.
+    pub fn to_libyang_string(&self, module: &Module) -> String {
+        match self {
+            Value::Identityref { prefix, name } => format!("{}:{}", module_of(module, prefix.as_deref()), name),
+            Value::InstanceIdentifier(path) => qualify_path(module, path),
+            _ => self.to_string(),
+        }
+    }
+
+    // Integer accessors accept any integer variant, as long as the value fits in the requested type.
+    fn as_integer<T: TryFrom<i128>>(&self) -> Option<T> {
+        integer_value(self).and_then(|value| T::try_from(value).ok())
+    }
+}
+
+/// Fixed-point decimal64 value, stored as the value multiplied by 10^fraction_digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Decimal64 {
+    pub value: i64,
+    pub fraction_digits: u8,
+}
+
+impl Decimal64 {
+    pub fn to_f64(&self) -> f64 {
+        self.value as f64 / 10f64.powi(self.fraction_digits as i32)
+    }
+}
+
+impl fmt::Display for Decimal64 {
+    // The canonical form has no leading zeros except the one before the decimal point, and no trailing zeros
+    // except the one after the decimal point.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let scale = 10u64.pow(self.fraction_digits as u32);
+        let abs = self.value.unsigned_abs();
+        let sign = if self.value < 0 { "-" } else { "" };
+        let fraction = format!("{:0width$}", abs % scale, width = self.fraction_digits as usize);
+        let fraction = fraction.trim_end_matches('0');
+        let fraction = if fraction.is_empty() { "0" } else { fraction };
+
+        write!(f, "{}{}.{}", sign, abs / scale, fraction)
+    }
+}
+
+impl fmt::Display for Value {
+    /// Formats the value in its canonical form.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Int8(v) => write!(f, "{}", v),
+            Value::Int16(v) => write!(f, "{}", v),
+            Value::Int32(v) => write!(f, "{}", v),
+            Value::Int64(v) => write!(f, "{}", v),
+            Value::Uint8(v) => write!(f, "{}", v),
+            Value::Uint16(v) => write!(f, "{}", v),
+            Value::Uint32(v) => write!(f, "{}", v),
+            Value::Uint64(v) => write!(f, "{}", v),
+            Value::Decimal64(v) => write!(f, "{}", v),
+            Value::Boolean(v) => write!(f, "{}", v),
+            Value::String(v) => write!(f, "{}", v),
+            Value::Enumeration { name, .. } => write!(f, "{}", name),
+            Value::Bits(bits) => write!(f, "{}", bits.join(" ")),
+            Value::Binary(bytes) => write!(f, "{}", STANDARD.encode(bytes)),
+            Value::Empty => Ok(()),
+            Value::Identityref { prefix: Some(prefix), name } => write!(f, "{}:{}", prefix, name),
+            Value::Identityref { prefix: None, name } => write!(f, "{}", name),
+            Value::InstanceIdentifier(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+/// Parse the lexical representation of a value of instance data according to the given type, where integers are only
+/// written in decimal.
+/// Only built-in types can be parsed. Typedefs and leafrefs have to be resolved to the type they derive from first.
+pub fn parse_value(type_info: &TypeInfo, input: &str) -> Result<Value, ValueError> {
+    parse_lexical(type_info, input, Notation::Instance, None)
+}
+
+/// Parse a value written in a module, like a `default` statement, like [parse_value], where integers may also be
+/// written in hexadecimal, like "0x1F", or octal, like "017", as RFC 7950 allows for defaults.
+pub fn parse_default(type_info: &TypeInfo, input: &str) -> Result<Value, ValueError> {
+    parse_lexical(type_info, input, Notation::Schema, None)
+}
+
+/// Parse a value of instance data like [parse_value], where identityrefs are also resolved against the identities of
+/// the module and its imports. Identities which aren't defined, or aren't derived from the bases of the type, are
+/// errors. The prefix of an identityref is the prefix of a module imported by the module, or the name of a module, as
+/// values are qualified with module names in JSON.
+pub fn parse_value_in(module: &Module, type_info: &TypeInfo, input: &str) -> Result<Value, ValueError> {
+    parse_lexical(type_info, input, Notation::Instance, Some(module))
+}
+
+// Where a value is written, which decides the notations its integers may be written in.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Notation {
+    // Instance data, where integers are decimal (RFC 7950 section 9.2.1 and RFC 7951).
+    Instance,
+    // The module itself, where integers may also be hexadecimal or octal.
+    Schema,
+}
+
+// Identityrefs are resolved if the module the value is in is given.
+fn parse_lexical(
+    type_info: &TypeInfo,
+    input: &str,
+    notation: Notation,
+    module: Option<&Module>,
+) -> Result<Value, ValueError> {
+    match type_info.name.as_str() {
+        "int8" => parse_integer(type_info, input, notation).map(Value::Int8),
+        "int16" => parse_integer(type_info, input, notation).map(Value::Int16),
+        "int32" => parse_integer(type_info, input, notation).map(Value::Int32),
+        "int64" => parse_integer(type_info, input, notation).map(Value::Int64),
+        "uint8" => parse_integer(type_info, input, notation).map(Value::Uint8),
+        "uint16" => parse_integer(type_info, input, notation).map(Value::Uint16),
+        "uint32" => parse_integer(type_info, input, notation).map(Value::Uint32),
+        "uint64" => parse_integer(type_info, input, notation).map(Value::Uint64),
+        "decimal64" => parse_decimal64(type_info, input).map(Value::Decimal64),
+        "boolean" => match input {
+            "true" => Ok(Value::Boolean(true)),
+            "false" => Ok(Value::Boolean(false)),
+            _ => Err(invalid(type_info, input)),
+        },
+        "string" => Ok(Value::String(input.to_string())),
+        "enumeration" => parse_enumeration(type_info, input),
+        "bits" => parse_bits(type_info, input),
+        "binary" => STANDARD
+            .decode(input)
+            .map(Value::Binary)
+            .map_err(|_| invalid(type_info, input)),
+        "empty" if input.is_empty() => Ok(Value::Empty),
+        "empty" => Err(invalid(type_info, input)),
+        "identityref" => parse_identityref(type_info, input, module),
+        "instance-identifier" if input.starts_with('/') => Ok(Value::InstanceIdentifier(input.to_string())),
+        "instance-identifier" => Err(invalid(type_info, input)),
+        "union" => parse_union(type_info, input, notation, module),
+        _ => Err(ValueError::UnresolvedType(type_info.name.to_string())),
+    }
+}
+
+/// Check a parsed value against the range, length and pattern restrictions of its type.
+pub fn validate_value(type_info: &TypeInfo, value: &Value) -> Result<(), ValueError> {
+    match (type_info.type_body.as_deref(), value) {
+        (Some(TypeBody::Numerical { range }), _) => {
+            let Some(number) = integer_value(value) else {
+                return Ok(());
+            };
+            let (min, max) = integer_bounds(&type_info.name);
+            let bound = |bound: &str| match bound {
+                "min" => Ok(min),
+                "max" => Ok(max),
+                _ => parse_integer::<i128>(type_info, bound, Notation::Schema),
+            };
+
+            if !in_intervals(&range.value, number, bound)? {
+                return Err(ValueError::RangeViolation {
+                    value: value.to_string(),
+                    range: range.value.clone(),
+                });
+            }
+        }
+        (Some(TypeBody::Decimal64 { range: Some(range), .. }), Value::Decimal64(decimal)) => {
+            let bound = |bound: &str| match bound {
+                "min" => Ok(i64::MIN),
+                "max" => Ok(i64::MAX),
+                _ => parse_decimal64(type_info, bound).map(|bound| bound.value),
+            };
+
+            if !in_intervals(&range.value, decimal.value, bound)? {
+                return Err(ValueError::RangeViolation {
+                    value: value.to_string(),
+                    range: range.value.clone(),
+                });
+            }
+        }
+        (Some(TypeBody::String { length, patterns }), Value::String(string)) => {
+            if let Some(length) = length {
+                check_length(length, string.chars().count() as u64, value)?;
+            }
+
+            for pattern in patterns {
+                let regex = compiled_pattern(&pattern.value)?;
+                let inverted = pattern.modifier.as_deref() == Some("invert-match");
+
+                if regex.is_match(string) == inverted {
+                    return Err(ValueError::PatternMismatch {
+                        value: string.clone(),
+                        pattern: pattern.value.clone(),
+                    });
+                }
+            }
+        }
+        (Some(TypeBody::Binary { length: Some(length) }), Value::Binary(bytes)) => {
+            check_length(length, bytes.len() as u64, value)?;
+        }
+        // A string type restricted only by its length has the body of a binary type, which the grammar can't tell apart.
+        (Some(TypeBody::Binary { length: Some(length) }), Value::String(string)) => {
+            check_length(length, string.chars().count() as u64, value)?;
+        }
+        (Some(TypeBody::Union { .. }), _) => {
+            // The value doesn't record which member type it was parsed as, so parse it again to find a member
+            // type that accepts it.
+            parse_union(type_info, &value.to_string(), Notation::Instance, None)?;
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Parse a value of instance data with [parse_value], and check it against the restrictions of its type with
+/// [validate_value].
+pub fn parse_and_validate(type_info: &TypeInfo, input: &str) -> Result<Value, ValueError> {
+    let value = parse_value(type_info, input)?;
+    validate_value(type_info, &value)?;
+    Ok(value)
+}
+
+/// Parse a value and return it in its canonical form.
+pub fn canonicalize(type_info: &TypeInfo, input: &str) -> Result<String, ValueError> {
+    parse_value(type_info, input).map(|value| value.to_string())
+}
+
+/// Parse a value of the module and return it in the canonical form libyang uses, see [Value::to_libyang_string].
+pub fn canonicalize_libyang(type_info: &TypeInfo, input: &str, module: &Module) -> Result<String, ValueError> {
+    parse_value(type_info, input).map(|value| value.to_libyang_string(module))
+}
+
+fn invalid(type_info: &TypeInfo, input: &str) -> ValueError {
+    ValueError::InvalidValue {
+        type_name: type_info.name.to_string(),
+        value: input.to_string(),
+    }
+}
+
+// Integers are decimal with an optional sign. In the module, they can also be written in hexadecimal ("0x" prefix) or
+// octal (leading "0") notation, so "010" is 10 in instance data and 8 in a default.
+fn parse_integer<T: TryFrom<i128>>(type_info: &TypeInfo, input: &str, notation: Notation) -> Result<T, ValueError> {
+    let (negative, digits) = match input.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, input.strip_prefix('+').unwrap_or(input)),
+    };
+
+    let (radix, digits) = match digits.strip_prefix("0x") {
+        Some(hex) if notation == Notation::Schema => (16, hex),
+        _ if notation == Notation::Schema && digits.len() > 1 && digits.starts_with('0') => (8, &digits[1..]),
+        _ => (10, digits),
+    };
+
+    // Only a single leading sign is allowed, so anything but digits after stripping it means the input was malformed.
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return Err(invalid(type_info, input));
+    }
+
+    let out_of_range = || ValueError::OutOfRange {
+        type_name: type_info.name.to_string(),
+        value: input.to_string(),
+    };
+    // Only the digits of numbers too large for any integer type fail to parse.
+    let magnitude = i128::from_str_radix(digits, radix).map_err(|_| out_of_range())?;
+    let value = if negative { -magnitude } else { magnitude };
+    T::try_from(value).map_err(|_| out_of_range())
+}
+
+fn parse_decimal64(type_info: &TypeInfo, input: &str) -> Result<Decimal64, ValueError> {
+    let fraction_digits = match type_info.type_body.as_deref() {
+        Some(TypeBody::Decimal64 { fraction_digits, .. }) => fraction_digits
+            .parse::<u8>()
+            .ok()
+            .filter(|digits| (1..=18).contains(digits))
+            .ok_or_else(|| invalid(type_info, input))?,
+        _ => return Err(ValueError::MissingFractionDigits),
+    };
+
+    let (negative, unsigned) = match input.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, input.strip_prefix('+').unwrap_or(input)),
+    };
+    // A decimal point has to be followed by digits, so "1." isn't a value, and neither is ".5".
+    let (integer, fraction) = match unsigned.split_once('.') {
+        Some((_, "")) => return Err(invalid(type_info, input)),
+        Some((integer, fraction)) => (integer, fraction),
+        None => (unsigned, ""),
+    };
+
+    let all_digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+    if integer.is_empty() || !all_digits(integer) || !all_digits(fraction) || fraction.len() > fraction_digits as usize
+    {
+        return Err(invalid(type_info, input));
+    }
+
+    // Pad the fraction to the number of fraction digits, so the concatenation becomes the scaled integer value.
+    let scaled = format!("{}{:0<width$}", integer, fraction, width = fraction_digits as usize);
+    let magnitude = scaled.parse::<i128>().map_err(|_| invalid(type_info, input))?;
+    let value = if negative { -magnitude } else { magnitude };
+
+    let value = i64::try_from(value).map_err(|_| ValueError::OutOfRange {
+        type_name: type_info.name.to_string(),
+        value: input.to_string(),
+    })?;
+
+    Ok(Decimal64 { value, fraction_digits })
+}
+
+fn parse_enumeration(type_info: &TypeInfo, input: &str) -> Result<Value, ValueError> {
+    let Some(TypeBody::Enum { enums }) = type_info.type_body.as_deref() else {
+        return Err(ValueError::UnknownEnum(input.to_string()));
+    };
+
+    // Enums without an explicit value are assigned one greater than the current highest value, starting at zero.
+    let mut next_value = 0;
+    for enum_value in enums {
+        let value = enum_value.value.unwrap_or(next_value);
+        next_value = next_value.max(value + 1);
+
+        if enum_value.name == input {
+            return Ok(Value::Enumeration {
+                name: enum_value.name.clone(),
+                value,
+            });
+        }
+    }
+
+    Err(ValueError::UnknownEnum(input.to_string()))
+}
+
+fn parse_bits(type_info: &TypeInfo, input: &str) -> Result<Value, ValueError> {
+    let Some(TypeBody::Bits { bits }) = type_info.type_body.as_deref() else {
+        return Err(ValueError::UnknownBit(input.to_string()));
+    };
+
+    // Bits without an explicit position are assigned one greater than the current highest position.
+    let mut next_position = 0;
+    let positions: Vec<(i64, &str)> = bits
+        .iter()
+        .map(|bit| {
+            let position = bit.position.unwrap_or(next_position);
+            next_position = next_position.max(position + 1);
+            (position, bit.name.as_str())
+        })
+        .collect();
+
+    let mut set_bits = Vec::new();
+    for name in input.split_whitespace() {
+        match positions.iter().find(|(_, bit)| *bit == name) {
+            Some(bit) if !set_bits.contains(bit) => set_bits.push(*bit),
+            Some(_) => {}
+            None => return Err(ValueError::UnknownBit(name.to_string())),
+        }
+    }
+
+    // The canonical form lists the bits ordered by their position.
+    set_bits.sort();
+    Ok(Value::Bits(set_bits.into_iter().map(|(_, name)| name.to_string()).collect()))
+}
+
+fn parse_identityref(type_info: &TypeInfo, input: &str, module: Option<&Module>) -> Result<Value, ValueError> {
+    let (prefix, name) = match input.split_once(':') {
+        Some((prefix, name)) => (Some(prefix), name),
+        None => (None, input),
+    };
+
+    if name.is_empty() || prefix == Some("") {
+        return Err(invalid(type_info, input));
+    }
+
+    if let Some(module) = module {
+        let identity = find_identity(module, &module.name, prefix, name)
+            .ok_or_else(|| ValueError::UnknownIdentity(input.to_string()))?;
+        let bases = match type_info.type_body.as_deref() {
+            Some(TypeBody::Identityref { bases }) => bases.as_slice(),
+            _ => &[],
+        };
+        for base in bases {
+            let (base_prefix, base_name) = split_prefix(base);
+            let derived = find_identity(module, &module.name, base_prefix, base_name)
+                .is_some_and(|base| derived_from(module, &identity, &base, 0));
+            if !derived {
+                return Err(ValueError::WrongIdentityBase {
+                    value: input.to_string(),
+                    base: base.clone(),
+                });
+            }
+        }
+    }
+
+    Ok(Value::Identityref {
+        prefix: prefix.map(str::to_string),
+        name: name.to_string(),
+    })
+}
+
+// Identities deriving from identities are followed up to a limit, in case the bases are a cycle.
+const MAX_IDENTITY_DEPTH: usize = 32;
+
+// An identity with the name of the module defining it.
+struct FoundIdentity<'m> {
+    module: &'m str,
+    identity: &'m Identity,
+}
+
+impl PartialEq for FoundIdentity<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.module == other.module && self.identity.name == other.identity.name
+    }
+}
+
+fn split_prefix(name: &str) -> (Option<&str>, &str) {
+    match name.split_once(':') {
+        Some((prefix, name)) => (Some(prefix), name),
+        None => (None, name),
+    }
+}
+
+// The identity with the prefix and name, referred to from the module with the name, where no prefix refers to that
+// module, and a prefix is a prefix of the module or the name of a module.
+fn find_identity<'m>(
+    module: &'m Module,
+    context: &'m str,
+    prefix: Option<&str>,
+    name: &str,
+) -> Option<FoundIdentity<'m>> {
+    let module_name = match prefix {
+        None => context,
+        Some(prefix) if module.prefix == prefix || module.name == prefix => module.name.as_str(),
+        Some(prefix) => match module.prefix_to_module.get(prefix) {
+            Some(module_name) => module_name.as_str(),
+            None => module.imported_modules.get_key_value(prefix)?.0.as_str(),
+        },
+    };
+    identity_in(module, module_name, name)
+}
+
+// The top-level identity with the name in the module with the name, which is the module itself or one of its imports.
+fn identity_in<'m>(module: &'m Module, module_name: &str, name: &str) -> Option<FoundIdentity<'m>> {
+    let (module_name, reference_nodes) = match module.name == module_name {
+        true => (module.name.as_str(), &module.reference_nodes),
+        false => module
+            .imported_modules
+            .get_key_value(module_name)
+            .map(|(module_name, reference_nodes)| (module_name.as_str(), reference_nodes))?,
+    };
+    let identity = reference_nodes.identities.get(&format!("/{}", name))?;
+    Some(FoundIdentity {
+        module: module_name,
+        identity,
+    })
+}
+
+// Whether the identity is derived from the base, directly or through the identities it derives from.
+fn derived_from(module: &Module, identity: &FoundIdentity, base: &FoundIdentity, depth: usize) -> bool {
+    if depth == MAX_IDENTITY_DEPTH {
+        return false;
+    }
+
+    identity.identity.bases.iter().any(|own_base| {
+        let (prefix, name) = split_prefix(own_base);
+        // The prefixes of the imports of imported modules aren't kept, so the bases of their identities are found by
+        // the prefixes of the module, or by their name in any of the modules.
+        let found = find_identity(module, identity.module, prefix, name).or_else(|| {
+            let mut modules: Vec<&str> = module.imported_modules.keys().map(|name| name.as_str()).collect();
+            modules.sort();
+            std::iter::once(module.name.as_str())
+                .chain(modules)
+                .find_map(|module_name| identity_in(module, module_name, name))
+        });
+        found.is_some_and(|found| found == *base || derived_from(module, &found, base, depth + 1))
+    })
+}
+
+// The name of the module the prefix refers to in the module, where no prefix and the module's own prefix refer to the
+// module itself. Unknown prefixes are kept as they are.
+fn module_of<'a>(module: &'a Module, prefix: Option<&'a str>) -> &'a str {
+    match prefix {
+        None => &module.name,
+        Some(prefix) if module.prefix == prefix => &module.name,
+        Some(prefix) => module.prefix_to_module.get(prefix).map_or(prefix, |name| name.as_str()),
+    }
+}
+
+// The instance-identifier with the prefixes of its nodes replaced with module names, where only nodes in another module
+// than their parent are qualified, and keys in another module than their list, like
+// "/ietf-interfaces:interfaces/interface[name='eth0']".
+fn qualify_path(module: &Module, path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    let mut parent_module = None;
+    let mut quote = None;
+    let mut rest = path;
+    while let Some(c) = rest.chars().next() {
+        rest = &rest[c.len_utf8()..];
+        out.push(c);
+        match (quote, c) {
+            (Some(open), _) if c == open => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '/' | '[') => {
+                // Predicates on positions and leaf-list values, like "[1]" and "[.='eth0']", have no node name.
+                let Some((prefix, name, after)) = node_name(rest) else {
+                    continue;
+                };
+                let node_module = module_of(module, prefix);
+                if parent_module != Some(node_module) {
+                    out.push_str(node_module);
+                    out.push(':');
+                }
+                out.push_str(name);
+                if c == '/' {
+                    parent_module = Some(node_module);
+                }
+                rest = after;
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+// The prefix and name of the node name at the start of the text, like "if:interface", and the text after it.
+fn node_name(text: &str) -> Option<(Option<&str>, &str, &str)> {
+    let (first, rest) = identifier(text)?;
+    match rest.strip_prefix(':') {
+        Some(after) => {
+            let (name, rest) = identifier(after)?;
+            Some((Some(first), name, rest))
+        }
+        None => Some((None, first, rest)),
+    }
+}
+
+// The identifier at the start of the text, and the text after it.
+fn identifier(text: &str) -> Option<(&str, &str)> {
+    if !text.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        return None;
+    }
+    let end = text
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.')))
+        .unwrap_or(text.len());
+    Some(text.split_at(end))
+}
+
+/// The expression of the `regex` crate matching the strings a YANG pattern matches. Patterns are XML Schema regular
+/// expressions, which differ from the crate's: `^` and `$` are characters rather than anchors, `.` doesn't match
+/// carriage returns, `\s` is only ASCII whitespace and `\w` is anything but punctuation, separators and other
+/// characters, and there are `\i` and `\c` for the characters of XML names, `\p{IsBlock}` for Unicode blocks and
+/// `[a-z-[aeiou]]` for subtracting classes. The expression isn't anchored, while patterns match whole values, so it's
+/// wrapped like `^(?:...)$` to check values. Unicode blocks whose range isn't known are unsupported.
+pub fn pattern_regex(pattern: &str) -> Result<String, ValueError> {
+    let invalid = || ValueError::InvalidRestriction(pattern.to_string());
+    let mut regex = String::with_capacity(pattern.len());
+    // Character classes nest when they're subtracted, like "[a-z-[aeiou]]".
+    let mut class_depth = 0;
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                let escape = chars.next().ok_or_else(invalid)?;
+                match escape {
+                    'n' | 'r' | 't' | '\\' | '|' | '.' | '-' | '^' | '?' | '*' | '+' | '{' | '}' | '(' | ')' | '['
+                    | ']' | 'd' | 'D' => {
+                        regex.push('\\');
+                        regex.push(escape);
+                    }
+                    's' => regex.push_str(r"[ \t\n\r]"),
+                    'S' => regex.push_str(r"[^ \t\n\r]"),
+                    'w' => regex.push_str(r"[^\p{P}\p{Z}\p{C}]"),
+                    'W' => regex.push_str(r"[\p{P}\p{Z}\p{C}]"),
+                    'i' => write!(regex, "[{}]", NAME_START_CHARACTERS).expect("writing to a string to succeed"),
+                    'I' => write!(regex, "[^{}]", NAME_START_CHARACTERS).expect("writing to a string to succeed"),
+                    'c' => write!(regex, "[{}{}]", NAME_START_CHARACTERS, NAME_CHARACTERS)
+                        .expect("writing to a string to succeed"),
+                    'C' => write!(regex, "[^{}{}]", NAME_START_CHARACTERS, NAME_CHARACTERS)
+                        .expect("writing to a string to succeed"),
+                    'p' | 'P' => {
+                        if chars.next() != Some('{') {
+                            return Err(invalid());
+                        }
+                        let mut name = String::new();
+                        loop {
+                            match chars.next().ok_or_else(invalid)? {
+                                '}' => break,
+                                c => name.push(c),
+                            }
+                        }
+                        let negation = if escape == 'P' { "^" } else { "" };
+                        match name.strip_prefix("Is") {
+                            Some(block) => {
+                                let (first, last) =
+                                    unicode_block(block).ok_or_else(|| ValueError::UnsupportedPattern {
+                                        pattern: pattern.to_string(),
+                                        reason: format!("the range of the Unicode block {} is not known", block),
+                                    })?;
+                                write!(regex, "[{}\\x{{{:X}}}-\\x{{{:X}}}]", negation, first, last)
+                                    .expect("writing to a string to succeed");
+                            }
+                            // General categories, like "L" and "Nd", are the same in both.
+                            None => write!(regex, "\\{}{{{}}}", escape, name).expect("writing to a string to succeed"),
+                        }
+                    }
+                    // Other escapes, like "\b" and "\A", aren't XML Schema escapes.
+                    _ => return Err(invalid()),
+                }
+            }
+            '[' if class_depth == 0 => {
+                regex.push('[');
+                class_depth += 1;
+                if chars.next_if_eq(&'^').is_some() {
+                    regex.push('^');
+                }
+            }
+            '-' if class_depth > 0 && chars.next_if_eq(&'[').is_some() => {
+                regex.push_str("--[");
+                class_depth += 1;
+                if chars.next_if_eq(&'^').is_some() {
+                    regex.push('^');
+                }
+            }
+            ']' if class_depth > 0 => {
+                regex.push(']');
+                class_depth -= 1;
+            }
+            // Characters which nest classes or are operators in classes of the crate are characters in patterns.
+            '[' | '&' | '~' if class_depth > 0 => {
+                regex.push('\\');
+                regex.push(c);
+            }
+            '.' if class_depth == 0 => regex.push_str(r"[^\n\r]"),
+            '^' | '$' if class_depth == 0 => {
+                regex.push('\\');
+                regex.push(c);
+            }
+            c => regex.push(c),
+        }
+    }
+    Ok(regex)
+}
+
+// The characters names start with, matched by "\i", and the other characters of names, which "\c" matches as well. Like
+// libyang, these are the characters of names in XML 1.0 (fifth edition).
+const NAME_START_CHARACTERS: &str = r":A-Z_a-z\x{C0}-\x{D6}\x{D8}-\x{F6}\x{F8}-\x{2FF}\x{370}-\x{37D}\x{37F}-\x{1FFF}\x{200C}-\x{200D}\x{2070}-\x{218F}\x{2C00}-\x{2FEF}\x{3001}-\x{D7FF}\x{F900}-\x{FDCF}\x{FDF0}-\x{FFFD}\x{10000}-\x{EFFFF}";
+const NAME_CHARACTERS: &str = r"\-.0-9\x{B7}\x{300}-\x{36F}\x{203F}-\x{2040}";
+
+// The first and last character of the Unicode block with the name, as it's written in "\p{IsBasicLatin}".
+fn unicode_block(name: &str) -> Option<(u32, u32)> {
+    let range = match name {
+        "BasicLatin" => (0x0000, 0x007F),
+        "Latin-1Supplement" => (0x0080, 0x00FF),
+        "LatinExtended-A" => (0x0100, 0x017F),
+        "LatinExtended-B" => (0x0180, 0x024F),
+        "IPAExtensions" => (0x0250, 0x02AF),
+        "SpacingModifierLetters" => (0x02B0, 0x02FF),
+        "CombiningDiacriticalMarks" => (0x0300, 0x036F),
+        "Greek" => (0x0370, 0x03FF),
+        "Cyrillic" => (0x0400, 0x04FF),
+        "Armenian" => (0x0530, 0x058F),
+        "Hebrew" => (0x0590, 0x05FF),
+        "Arabic" => (0x0600, 0x06FF),
+        "Devanagari" => (0x0900, 0x097F),
+        "Thai" => (0x0E00, 0x0E7F),
+        "Georgian" => (0x10A0, 0x10FF),
+        "HangulJamo" => (0x1100, 0x11FF),
+        "LatinExtendedAdditional" => (0x1E00, 0x1EFF),
+        "GreekExtended" => (0x1F00, 0x1FFF),
+        "GeneralPunctuation" => (0x2000, 0x206F),
+        "SuperscriptsandSubscripts" => (0x2070, 0x209F),
+        "CurrencySymbols" => (0x20A0, 0x20CF),
+        "LetterlikeSymbols" => (0x2100, 0x214F),
+        "NumberForms" => (0x2150, 0x218F),
+        "Arrows" => (0x2190, 0x21FF),
+        "MathematicalOperators" => (0x2200, 0x22FF),
+        "MiscellaneousTechnical" => (0x2300, 0x23FF),
+        "BoxDrawing" => (0x2500, 0x257F),
+        "BlockElements" => (0x2580, 0x259F),
+        "GeometricShapes" => (0x25A0, 0x25FF),
+        "MiscellaneousSymbols" => (0x2600, 0x26FF),
+        "Dingbats" => (0x2700, 0x27BF),
+        "CJKSymbolsandPunctuation" => (0x3000, 0x303F),
+        "Hiragana" => (0x3040, 0x309F),
+        "Katakana" => (0x30A0, 0x30FF),
+        "CJKUnifiedIdeographs" => (0x4E00, 0x9FFF),
+        "HangulSyllables" => (0xAC00, 0xD7AF),
+        "PrivateUse" => (0xE000, 0xF8FF),
+        "CJKCompatibilityIdeographs" => (0xF900, 0xFAFF),
+        "AlphabeticPresentationForms" => (0xFB00, 0xFB4F),
+        "HalfwidthandFullwidthForms" => (0xFF00, 0xFFEF),
+        "Specials" => (0xFFF0, 0xFFFF),
+        _ => return None,
+    };
+    Some(range)
+}
+
+// The patterns compiled so far, as the patterns of a schema are checked for every value of their types. Patterns which
+// fail to compile are kept with their error. The cache is emptied when it's full, so a process checking the values of
+// many schemas doesn't keep the patterns of all of them.
+static PATTERNS: OnceLock<RwLock<HashMap<String, Result<Regex, ValueError>>>> = OnceLock::new();
+
+// The number of patterns kept, which is more than the patterns of the largest schemas, like the IETF and OpenConfig
+// models.
+const MAX_PATTERNS: usize = 4096;
+
+// The pattern compiled to a regex matching whole values.
+fn compiled_pattern(pattern: &str) -> Result<Regex, ValueError> {
+    let patterns = PATTERNS.get_or_init(Default::default);
+    if let Some(compiled) = patterns.read().expect("the patterns to not be poisoned").get(pattern) {
+        return compiled.clone();
+    }
+
+    let compiled = pattern_regex(pattern).and_then(|regex| {
+        Regex::new(&format!("^(?:{})$", regex)).map_err(|_| ValueError::InvalidRestriction(pattern.to_string()))
+    });
+    remember(
+        &mut patterns.write().expect("the patterns to not be poisoned"),
+        pattern,
+        compiled.clone(),
+    );
+    compiled
+}
+
+// Keep the compiled pattern, emptying the cache first if it's full.
+fn remember(
+    patterns: &mut HashMap<String, Result<Regex, ValueError>>,
+    pattern: &str,
+    compiled: Result<Regex, ValueError>,
+) {
+    if patterns.len() >= MAX_PATTERNS {
+        patterns.clear();
+    }
+    patterns.insert(pattern.to_string(), compiled);
+}
+
+fn check_length(length: &Length, actual: u64, value: &Value) -> Result<(), ValueError> {
+    let bound = |bound: &str| match bound {
+        "min" => Ok(0),
+        "max" => Ok(u64::MAX),
+        _ => bound
+            .parse::<u64>()
+            .map_err(|_| ValueError::InvalidRestriction(length.value.clone())),
+    };
+
+    if in_intervals(&length.value, actual, bound)? {
+        Ok(())
+    } else {
+        Err(ValueError::LengthViolation {
+            value: value.to_string(),
+            length: length.value.clone(),
+        })
+    }
+}
+
+// Ranges and lengths are a list of intervals separated by "|", where each interval is either a single value or a
+// lower and upper bound separated by "..".
+fn in_intervals<T, F>(expression: &str, value: T, parse_bound: F) -> Result<bool, ValueError>
+where
+    T: PartialOrd,
+    F: Fn(&str) -> Result<T, ValueError>,
+{
+    for interval in expression.split('|') {
+        let (lower, upper) = interval.split_once("..").unwrap_or((interval, interval));
+        let lower = parse_bound(lower.trim()).map_err(|_| ValueError::InvalidRestriction(expression.to_string()))?;
+        let upper = parse_bound(upper.trim()).map_err(|_| ValueError::InvalidRestriction(expression.to_string()))?;
+
+        if lower <= value && value <= upper {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+fn integer_value(value: &Value) -> Option<i128> {
+    match *value {
+        Value::Int8(v) => Some(v.into()),
+        Value::Int16(v) => Some(v.into()),
+        Value::Int32(v) => Some(v.into()),
+        Value::Int64(v) => Some(v.into()),
+        Value::Uint8(v) => Some(v.into()),
+        Value::Uint16(v) => Some(v.into()),
+        Value::Uint32(v) => Some(v.into()),
+        Value::Uint64(v) => Some(v.into()),
+        _ => None,
+    }
+}
+
+fn integer_bounds(type_name: &str) -> (i128, i128) {
+    match type_name {
+        "int8" => (i8::MIN.into(), i8::MAX.into()),
+        "int16" => (i16::MIN.into(), i16::MAX.into()),
+        "int32" => (i32::MIN.into(), i32::MAX.into()),
+        "int64" => (i64::MIN.into(), i64::MAX.into()),
+        "uint8" => (0, u8::MAX.into()),
+        "uint16" => (0, u16::MAX.into()),
+        "uint32" => (0, u32::MAX.into()),
+        _ => (0, u64::MAX.into()),
+    }
+}
+
+// Union members are tried in the order they are defined, and the first type that accepts the value, including
+// its restrictions, wins.
+fn parse_union(
+    type_info: &TypeInfo,
+    input: &str,
+    notation: Notation,
+    module: Option<&Module>,
+) -> Result<Value, ValueError> {
+    let Some(TypeBody::Union { types }) = type_info.type_body.as_deref() else {
+        return Err(ValueError::NoMatchingUnionMember(input.to_string()));
+    };
+
+    types
+        .iter()
+        .find_map(|member| {
+            let value = parse_lexical(member, input, notation, module).ok()?;
+            validate_value(member, &value).ok().map(|_| value)
+        })
+        .ok_or_else(|| ValueError::NoMatchingUnionMember(input.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    fn built_in(name: &str, type_body: Option<TypeBody>) -> TypeInfo {
+        TypeInfo {
+            name: name.into(),
+            type_body: type_body.map(Arc::new),
+        }
+    }
+
+    fn ranged(name: &str, range: &str) -> TypeInfo {
+        let range = Range {
+            value: range.to_string(),
+            ..Default::default()
+        };
+        built_in(name, Some(TypeBody::Numerical { range }))
+    }
+
+    fn decimal64(fraction_digits: &str, range: Option<&str>) -> TypeInfo {
+        let type_body = TypeBody::Decimal64 {
+            fraction_digits: fraction_digits.to_string(),
+            range: range.map(|range| Range {
+                value: range.to_string(),
+                ..Default::default()
+            }),
+        };
+        built_in("decimal64", Some(type_body))
+    }
+
+    fn patterned(pattern: &str) -> TypeInfo {
+        let patterns = vec![Pattern {
+            value: pattern.to_string(),
+            ..Default::default()
+        }];
+        built_in("string", Some(TypeBody::String { length: None, patterns }))
+    }
+
+    fn matches(pattern: &str, input: &str) -> bool {
+        parse_and_validate(&patterned(pattern), input).is_ok()
+    }
+
+    #[test]
+    fn integers_are_decimal_in_instance_data() {
+        let int8 = built_in("int8", None);
+        assert_eq!(parse_value(&int8, "42"), Ok(Value::Int8(42)));
+        assert_eq!(parse_value(&int8, "+7"), Ok(Value::Int8(7)));
+        assert_eq!(parse_value(&int8, "-128"), Ok(Value::Int8(-128)));
+        assert_eq!(canonicalize(&int8, "010").as_deref(), Ok("10"));
+
+        for input in ["0x1F", "0X1F", "1F", "", "-", "+-1", "--1", " 1", "1 ", "1_000", "1.0", "1e3"] {
+            assert!(
+                matches!(parse_value(&int8, input), Err(ValueError::InvalidValue { .. })),
+                "{:?} should be rejected",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn integers_may_be_hexadecimal_or_octal_in_defaults() {
+        let uint16 = built_in("uint16", None);
+        assert_eq!(parse_default(&uint16, "0x1F"), Ok(Value::Uint16(31)));
+        assert_eq!(parse_default(&uint16, "010"), Ok(Value::Uint16(8)));
+        assert_eq!(parse_default(&uint16, "0"), Ok(Value::Uint16(0)));
+        assert_eq!(parse_default(&built_in("int32", None), "-0x10"), Ok(Value::Int32(-16)));
+        assert!(parse_default(&uint16, "09").is_err());
+        assert!(parse_default(&uint16, "0xG").is_err());
+    }
+
+    #[test]
+    fn integers_overflowing_their_type_are_out_of_range() {
+        let out_of_range = |type_name: &str, input: &str| {
+            matches!(
+                parse_value(&built_in(type_name, None), input),
+                Err(ValueError::OutOfRange { .. })
+            )
+        };
+        assert!(out_of_range("int8", "128"));
+        assert!(out_of_range("int8", "-129"));
+        assert!(out_of_range("uint8", "256"));
+        assert!(out_of_range("uint8", "-1"));
+        assert!(out_of_range("int64", "9223372036854775808"));
+        assert!(out_of_range("uint64", "18446744073709551616"));
+        assert!(out_of_range("uint64", "1000000000000000000000000000000000000000000"));
+        assert_eq!(
+            parse_value(&built_in("uint64", None), "18446744073709551615"),
+            Ok(Value::Uint64(u64::MAX))
+        );
+    }
+
+    #[test]
+    fn ranges_include_their_edges() {
+        let percent = ranged("uint8", "0 | 10..100");
+        assert!(parse_and_validate(&percent, "0").is_ok());
+        assert!(parse_and_validate(&percent, "10").is_ok());
+        assert!(parse_and_validate(&percent, "100").is_ok());
+        for input in ["1", "9", "101"] {
+            assert!(matches!(
+                parse_and_validate(&percent, input),
+                Err(ValueError::RangeViolation { .. })
+            ));
+        }
+
+        let open = ranged("int16", "min..-1 | 1..max");
+        assert!(parse_and_validate(&open, "-32768").is_ok());
+        assert!(parse_and_validate(&open, "32767").is_ok());
+        assert!(parse_and_validate(&open, "0").is_err());
+    }
+
+    #[test]
+    fn decimal64_values_have_at_most_their_fraction_digits() {
+        let two = decimal64("2", None);
+        assert_eq!(canonicalize(&two, "1.5").as_deref(), Ok("1.5"));
+        assert_eq!(canonicalize(&two, "+01.50").as_deref(), Ok("1.5"));
+        assert_eq!(canonicalize(&two, "-0.05").as_deref(), Ok("-0.05"));
+        assert_eq!(canonicalize(&two, "3").as_deref(), Ok("3.0"));
+        assert_eq!(
+            parse_value(&two, "12.34"),
+            Ok(Value::Decimal64(Decimal64 {
+                value: 1234,
+                fraction_digits: 2
+            }))
+        );
+
+        for input in ["1.234", "1.", ".5", "1.2.3", "1,5", "0x1", "", "-"] {
+            assert!(parse_value(&two, input).is_err(), "{:?} should be rejected", input);
+        }
+
+        let eighteen = decimal64("18", None);
+        assert!(parse_value(&eighteen, "9.223372036854775807").is_ok());
+        assert!(matches!(
+            parse_value(&eighteen, "9.223372036854775808"),
+            Err(ValueError::OutOfRange { .. })
+        ));
+        assert!(parse_value(&decimal64("19", None), "1.0").is_err());
+        assert_eq!(
+            parse_value(&built_in("decimal64", None), "1.0"),
+            Err(ValueError::MissingFractionDigits)
+        );
+    }
+
+    #[test]
+    fn decimal64_ranges_include_their_edges() {
+        let temperature = decimal64("1", Some("-40.0..125.5"));
+        assert!(parse_and_validate(&temperature, "-40").is_ok());
+        assert!(parse_and_validate(&temperature, "125.5").is_ok());
+        assert!(matches!(
+            parse_and_validate(&temperature, "125.6"),
+            Err(ValueError::RangeViolation { .. })
+        ));
+        assert!(parse_and_validate(&temperature, "-40.1").is_err());
+    }
+
+    #[test]
+    fn patterns_match_whole_values() {
+        assert!(matches("[a-z]+", "abc"));
+        assert!(!matches("[a-z]+", "abc1"));
+        assert!(!matches("[a-z]+", "1abc"));
+        assert!(matches("a|b", "b"));
+        assert!(!matches("a|b", "ab"));
+    }
+
+    #[test]
+    fn patterns_are_xml_schema_regular_expressions() {
+        // "^" and "$" are characters, not anchors.
+        assert!(matches("^[0-9]+$", "^42$"));
+        assert!(!matches("^[0-9]+$", "42"));
+        // "." doesn't match line breaks, and "\s" is only ASCII whitespace.
+        assert!(matches(".", "a"));
+        assert!(!matches(".", "\r"));
+        assert!(!matches(".", "\n"));
+        assert!(matches("\\s", "\t"));
+        assert!(!matches("\\s", "\u{A0}"));
+        // "\i" and "\c" are the characters of XML names.
+        assert!(matches("\\i\\c*", "_a-1.b"));
+        assert!(!matches("\\i\\c*", "-a"));
+        assert!(!matches("\\i\\c*", "a b"));
+        assert!(matches("\\I", "1"));
+        // Classes are subtracted, and "&" and "~" are characters in classes.
+        assert!(matches("[a-z-[aeiou]]+", "xyz"));
+        assert!(!matches("[a-z-[aeiou]]+", "xaz"));
+        assert!(matches("[&~]+", "&~"));
+        // Unicode blocks and general categories.
+        assert!(matches("\\p{IsBasicLatin}+", "abc"));
+        assert!(!matches("\\p{IsBasicLatin}+", "\u{E9}"));
+        assert!(matches("\\P{IsBasicLatin}", "\u{E9}"));
+        assert!(matches("\\p{Lu}\\p{Nd}", "A1"));
+    }
+
+    #[test]
+    fn patterns_which_cannot_be_checked_are_errors() {
+        assert!(matches!(
+            parse_and_validate(&patterned("\\p{IsTibetan}"), "a"),
+            Err(ValueError::UnsupportedPattern { .. })
+        ));
+        // "\b" and "\A" aren't escapes of XML Schema.
+        for pattern in ["\\b", "\\Aa", "a\\", "[a-z"] {
+            assert!(
+                matches!(
+                    parse_and_validate(&patterned(pattern), "a"),
+                    Err(ValueError::InvalidRestriction(_))
+                ),
+                "{:?} should be invalid",
+                pattern
+            );
+        }
+    }
+
+    #[test]
+    fn identityrefs_are_derived_from_their_bases() {
+        let document = crate::Document::parse(
+            r#"
+            module example-crypto {
+                namespace "urn:example:crypto";
+                prefix crypto;
+
+                identity crypto-alg;
+                identity symmetric-alg {
+                    base crypto-alg;
+                }
+                identity aes {
+                    base crypto:symmetric-alg;
+                }
+                identity hash-alg;
+            }
+            "#,
+        )
+        .expect("the module to parse");
+        let YangModule::Module(module) = document.module() else {
+            panic!("expected a module");
+        };
+        let bases = vec!["crypto-alg".to_string()];
+        let type_info = built_in("identityref", Some(TypeBody::Identityref { bases }));
+
+        for input in ["aes", "crypto:aes", "example-crypto:symmetric-alg"] {
+            assert!(
+                parse_value_in(module, &type_info, input).is_ok(),
+                "{:?} should be valid",
+                input
+            );
+        }
+        assert_eq!(
+            parse_value_in(module, &type_info, "des"),
+            Err(ValueError::UnknownIdentity("des".to_string()))
+        );
+        assert_eq!(
+            parse_value_in(module, &type_info, "other:aes"),
+            Err(ValueError::UnknownIdentity("other:aes".to_string()))
+        );
+        // An identity isn't derived from itself.
+        for input in ["hash-alg", "crypto-alg"] {
+            assert_eq!(
+                parse_value_in(module, &type_info, input),
+                Err(ValueError::WrongIdentityBase {
+                    value: input.to_string(),
+                    base: "crypto-alg".to_string(),
+                })
+            );
+        }
+        // Without the module, only the syntax of the value is checked.
+        assert!(parse_value(&type_info, "des").is_ok());
+    }
+
+    #[test]
+    fn patterns_are_compiled_once() {
+        let pattern = "[0-9]{2}-cached";
+        assert!(matches(pattern, "12-cached"));
+        let compiled = PATTERNS.get().expect("the patterns to be compiled");
+        assert!(compiled.read().expect("the patterns to not be poisoned").contains_key(pattern));
+        assert!(!matches(pattern, "1-cached"));
+    }
+
+    #[test]
+    fn pattern_cache_is_bounded() {
+        let regex = Regex::new("^$").expect("the regex to compile");
+        let mut patterns = HashMap::new();
+        for index in 0..MAX_PATTERNS {
+            remember(&mut patterns, &index.to_string(), Ok(regex.clone()));
+        }
+        assert_eq!(patterns.len(), MAX_PATTERNS);
+        remember(&mut patterns, "[0-9]+", Ok(regex));
+        assert_eq!(patterns.len(), 1);
+        assert!(patterns.contains_key("[0-9]+"));
+    }
+}
@@ -0,0 +1,565 @@
+use std::sync::Arc;
+
+use pyo3::{
+    exceptions::PyValueError,
+    prelude::*,
+    types::{PyBool, PyDict, PyFloat, PyInt, PyList, PyString},
+};
+use serde_json::{Map, Number, Value as Json};
+use yang_parser::{
+    instance,
+    model::{Module, TypeInfo},
+    schema_tree::{NodeId, NodeKind},
+    types::TypeResolver,
+    value::{self, Value},
+};
+
+use crate::schema::Schema;
+
+/// The errors of instance data decoded from RFC 7951 JSON, where objects are dicts, arrays are lists, and members may
+/// be qualified with their module name, like "ietf-interfaces:interfaces".
+pub fn validate(schema: &Schema, data: &Bound<'_, PyAny>) -> PyResult<Vec<(String, String)>> {
+    Ok(errors(schema, &json(data)?))
+}
+
+fn errors(schema: &Schema, data: &Json) -> Vec<(String, String)> {
+    let types = TypeResolver::new(schema.module());
+    let errors = instance::validate(schema.tree(), &types, data);
+    errors
+        .into_iter()
+        .map(|error| (error.path, error.kind.to_string()))
+        .collect()
+}
+
+/// Instance data of a module, kept as RFC 7951 JSON, whose nodes are read and written at data paths like
+/// "/interfaces/interface[name='eth0']/mtu", where list entries are selected by the values of their keys, and prefixes
+/// are ignored. Top-level members are qualified with the name of the module when they're added, like
+/// "ietf-interfaces:interfaces".
+#[pyclass]
+pub struct Data {
+    schema: Arc<Schema>,
+    data: Map<String, Json>,
+}
+
+// Where the node at a data path is: a member of an object, or an entry of a list.
+enum Target<'d> {
+    Member(&'d mut Map<String, Json>, String),
+    Entry(&'d mut Vec<Json>, usize),
+}
+
+impl Data {
+    /// The data of the module, decoded from RFC 7951 JSON like [validate] decodes it, which has to be an object.
+    pub fn new(schema: Arc<Schema>, data: Option<&Bound<'_, PyAny>>) -> PyResult<Self> {
+        let data = match data.map(json).transpose()? {
+            None => Map::new(),
+            Some(Json::Object(data)) => data,
+            Some(_) => return Err(PyValueError::new_err("expected a dict of the top-level nodes")),
+        };
+        Ok(Self { schema, data })
+    }
+}
+
+#[pymethods]
+impl Data {
+    /// The value at the data path, like the dict of a container or list entry, the list of a leaf-list, or the value of
+    /// a leaf as RFC 7951 encodes it. None if there's no data at the path.
+    fn get<'py>(&mut self, py: Python<'py>, path: &str) -> PyResult<Option<Bound<'py, PyAny>>> {
+        let steps = steps(&self.schema, path)?;
+        let Some(target) = find(&self.schema, &mut self.data, &steps, false)? else {
+            return Ok(None);
+        };
+        let value = match target {
+            Target::Member(object, name) => object.get(&name),
+            Target::Entry(entries, position) => entries.get(position),
+        };
+        value.map(|value| python(py, value)).transpose()
+    }
+
+    /// Set the value at the data path, adding the containers and list entries on the way to it. Values of leaves and
+    /// leaf-lists are checked against their type and encoded like RFC 7951 requires, where they may be given in their
+    /// lexical form, like "1500" for an integer. Raises a ValueError if the path isn't a node of the module, or the
+    /// type doesn't allow the value.
+    fn set(&mut self, path: &str, value: &Bound<'_, PyAny>) -> PyResult<()> {
+        let schema = self.schema.clone();
+        let steps = steps(&schema, path)?;
+        let step = steps.last().expect("data paths to have steps");
+        let module = schema.module();
+        let leaf_type = || leaf_type(&schema, step.id);
+        let value = match (schema.tree().kind(step.id), json(value)?) {
+            (NodeKind::Leaf(_), value) => encode(module, &leaf_type()?, &value)?,
+            (NodeKind::LeafList(_), Json::Array(values)) => {
+                let type_info = leaf_type()?;
+                let values = values.iter().map(|value| encode(module, &type_info, value));
+                Json::Array(values.collect::<PyResult<_>>()?)
+            }
+            (NodeKind::LeafList(_), _) => return Err(PyValueError::new_err("expected a list of values")),
+            (NodeKind::List(_), value) if !step.keys.is_empty() && !value.is_object() => {
+                return Err(PyValueError::new_err("expected a dict of the list entry"));
+            }
+            (_, value) => value,
+        };
+
+        match find(&schema, &mut self.data, &steps, true)?.expect("the path to be created") {
+            Target::Member(object, name) => {
+                object.insert(name, value);
+            }
+            // The entry keeps the values of its keys, which the path selected it by.
+            Target::Entry(entries, position) => {
+                let Json::Object(value) = value else {
+                    unreachable!("list entries to be dicts")
+                };
+                let keys = step.keys.iter().map(|(key, value)| (key.to_string(), value.clone()));
+                let members = value
+                    .into_iter()
+                    .filter(|(member, _)| step.keys.iter().all(|(key, _)| local_name(member) != *key));
+                entries[position] = Json::Object(keys.chain(members).collect());
+            }
+        }
+        Ok(())
+    }
+
+    /// Merge the data into the data, like a NETCONF merge: containers and list entries with the same keys are merged,
+    /// values are added to leaf-lists, and leaves are replaced.
+    fn merge(&mut self, data: &Bound<'_, PyAny>) -> PyResult<()> {
+        let Json::Object(data) = json(data)? else {
+            return Err(PyValueError::new_err("expected a dict of the top-level nodes"));
+        };
+        merge(&self.schema, "", &mut self.data, data)
+    }
+
+    /// Remove the data at the data path, returning whether there was any.
+    fn delete(&mut self, path: &str) -> PyResult<bool> {
+        let steps = steps(&self.schema, path)?;
+        Ok(match find(&self.schema, &mut self.data, &steps, false)? {
+            Some(Target::Member(object, name)) => object.remove(&name).is_some(),
+            Some(Target::Entry(entries, position)) => {
+                entries.remove(position);
+                true
+            }
+            None => false,
+        })
+    }
+
+    /// The errors of the data, like [Module.validate_data].
+    fn validate(&self) -> Vec<(String, String)> {
+        errors(&self.schema, &Json::Object(self.data.clone()))
+    }
+
+    /// The data as RFC 7951 JSON decoded with `json.load`.
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        python(py, &Json::Object(self.data.clone()))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("<Data of {}>", self.schema.module().name)
+    }
+}
+
+// A step of a data path, like "interface[name='eth0']", with its schema node and the encoded values of the keys of the
+// list entry it selects.
+struct Step<'p> {
+    name: &'p str,
+    id: NodeId,
+    keys: Vec<(&'p str, Json)>,
+}
+
+// The steps of the data path, which are checked against the schema before any data is looked at, so that data isn't
+// added for a path which turns out to be invalid.
+fn steps<'p>(schema: &Schema, path: &'p str) -> PyResult<Vec<Step<'p>>> {
+    let invalid = || PyValueError::new_err(format!("invalid data path {}", path));
+    let mut names = Vec::new();
+    let mut rest = path.strip_prefix('/').ok_or_else(invalid)?;
+    while !rest.is_empty() {
+        let (name, mut tail) = rest.split_at(rest.find(['/', '[']).unwrap_or(rest.len()));
+        let mut keys = Vec::new();
+        while let Some(predicate) = tail.strip_prefix('[') {
+            let (key, value) = predicate.split_once('=').ok_or_else(invalid)?;
+            let value = value.trim_start();
+            let quote = value
+                .chars()
+                .next()
+                .filter(|c| *c == '\'' || *c == '"')
+                .ok_or_else(invalid)?;
+            let (value, after) = value[1..].split_once(quote).ok_or_else(invalid)?;
+            tail = after.trim_start().strip_prefix(']').ok_or_else(invalid)?;
+            keys.push((local_name(key.trim()), value));
+        }
+        if name.is_empty() {
+            return Err(invalid());
+        }
+        names.push((local_name(name), keys));
+        rest = match tail.strip_prefix('/') {
+            Some(rest) => rest,
+            None if tail.is_empty() => tail,
+            None => return Err(invalid()),
+        };
+    }
+    if names.is_empty() {
+        return Err(invalid());
+    }
+
+    let tree = schema.tree();
+    let mut schema_path = String::new();
+    let mut steps = Vec::new();
+    let count = names.len();
+    for (index, (name, keys)) in names.into_iter().enumerate() {
+        schema_path.push('/');
+        schema_path.push_str(name);
+        let id = tree
+            .find(&schema_path)
+            .ok_or_else(|| PyValueError::new_err(format!("{} is not a node of the module", schema_path)))?;
+        let last = index + 1 == count;
+        match tree.kind(id) {
+            NodeKind::List(_) if !keys.is_empty() => {}
+            _ if !keys.is_empty() => {
+                return Err(PyValueError::new_err(format!("{} is not a list", schema_path)));
+            }
+            _ if last => {}
+            NodeKind::List(list) => {
+                let key = list.key.as_deref().unwrap_or_default();
+                return Err(PyValueError::new_err(format!(
+                    "select an entry of list {} by its keys, like {}[{}='value']",
+                    name,
+                    name,
+                    key.split_whitespace().next().unwrap_or("key")
+                )));
+            }
+            NodeKind::Leaf(_) | NodeKind::LeafList(_) | NodeKind::Anydata(_) | NodeKind::Anyxml(_) => {
+                return Err(PyValueError::new_err(format!("{} has no child nodes", schema_path)));
+            }
+            _ => {}
+        }
+
+        let keys = keys
+            .into_iter()
+            .map(|(key, value)| {
+                let id = tree
+                    .find(&format!("{}/{}", schema_path, key))
+                    .ok_or_else(|| PyValueError::new_err(format!("{} is not a key of list {}", key, name)))?;
+                let value = encode(
+                    schema.module(),
+                    &leaf_type(schema, id)?,
+                    &Json::String(value.to_string()),
+                )?;
+                Ok((key, value))
+            })
+            .collect::<PyResult<_>>()?;
+        steps.push(Step { name, id, keys });
+    }
+    Ok(steps)
+}
+
+// The name without the prefix or module name it may be qualified with.
+fn local_name(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+// The name of the member of the object for the node with the name, which is the member already in the object, or a new
+// member, which is qualified with the name of the module at the top level.
+fn member_name(schema: &Schema, object: &Map<String, Json>, name: &str, top_level: bool) -> String {
+    match object.keys().find(|member| local_name(member) == name) {
+        Some(member) => member.clone(),
+        None if top_level => format!("{}:{}", schema.module().name, name),
+        None => name.to_string(),
+    }
+}
+
+// Find the node at the steps of a data path in the data, adding the containers and list entries on the way to it if
+// it's created, or None if there's no data at the path.
+fn find<'d>(
+    schema: &Schema,
+    data: &'d mut Map<String, Json>,
+    steps: &[Step<'_>],
+    create: bool,
+) -> PyResult<Option<Target<'d>>> {
+    let mut object = data;
+    for (index, step) in steps.iter().enumerate() {
+        let member = member_name(schema, object, step.name, index == 0);
+        if !object.contains_key(&member) && !create {
+            return Ok(None);
+        }
+        let last = index + 1 == steps.len();
+
+        let next = if step.keys.is_empty() {
+            if last {
+                return Ok(Some(Target::Member(object, member)));
+            }
+            object.entry(member).or_insert_with(|| Json::Object(Map::new()))
+        } else {
+            let Json::Array(entries) = object.entry(member.clone()).or_insert_with(|| Json::Array(Vec::new())) else {
+                return Err(PyValueError::new_err(format!("the data of {} is not a list", member)));
+            };
+            let position = entries.iter().position(|entry| {
+                step.keys.iter().all(|(key, value)| {
+                    entry
+                        .as_object()
+                        .and_then(|entry| entry.iter().find(|(member, _)| local_name(member) == *key))
+                        .is_some_and(|(_, entry_value)| entry_value == value)
+                })
+            });
+            let position = match position {
+                Some(position) => position,
+                None if create => {
+                    let entry = step.keys.iter().map(|(key, value)| (key.to_string(), value.clone()));
+                    entries.push(Json::Object(entry.collect()));
+                    entries.len() - 1
+                }
+                None => return Ok(None),
+            };
+            if last {
+                return Ok(Some(Target::Entry(entries, position)));
+            }
+            &mut entries[position]
+        };
+        object = next
+            .as_object_mut()
+            .ok_or_else(|| PyValueError::new_err(format!("the data of {} is not a dict", step.name)))?;
+    }
+
+    unreachable!("data paths to have steps")
+}
+
+// Merge the members of the source into the members of the object of the node at the schema path.
+fn merge(
+    schema: &Schema,
+    schema_path: &str,
+    object: &mut Map<String, Json>,
+    source: Map<String, Json>,
+) -> PyResult<()> {
+    let tree = schema.tree();
+    for (name, value) in source {
+        let path = format!("{}/{}", schema_path, local_name(&name));
+        let id = tree
+            .find(&path)
+            .ok_or_else(|| PyValueError::new_err(format!("{} is not a node of the module", path)))?;
+        let member = member_name(schema, object, local_name(&name), schema_path.is_empty());
+        let Some(existing) = object.get_mut(&member) else {
+            object.insert(member, value);
+            continue;
+        };
+
+        match (tree.kind(id), existing, value) {
+            (NodeKind::List(list), Json::Array(entries), Json::Array(values)) => {
+                let keys: Vec<&str> = list.key.iter().flat_map(|key| key.split_whitespace()).collect();
+                for value in values {
+                    let key_values = |entry: &Json| -> Option<Vec<Json>> {
+                        let entry = entry.as_object()?;
+                        keys.iter()
+                            .map(|key| {
+                                entry
+                                    .iter()
+                                    .find(|(member, _)| local_name(member) == *key)
+                                    .map(|(_, value)| value.clone())
+                            })
+                            .collect()
+                    };
+                    let same = key_values(&value)
+                        .and_then(|keys| entries.iter().position(|entry| key_values(entry) == Some(keys.clone())));
+                    match (same.map(|position| &mut entries[position]), value) {
+                        (Some(Json::Object(entry)), Json::Object(value)) if !keys.is_empty() => {
+                            merge(schema, &path, entry, value)?
+                        }
+                        (_, value) => entries.push(value),
+                    }
+                }
+            }
+            (NodeKind::LeafList(_), Json::Array(existing), Json::Array(values)) => {
+                for value in values {
+                    if !existing.contains(&value) {
+                        existing.push(value);
+                    }
+                }
+            }
+            (
+                NodeKind::Leaf(_) | NodeKind::LeafList(_) | NodeKind::Anydata(_) | NodeKind::Anyxml(_),
+                existing,
+                value,
+            ) => *existing = value,
+            (_, Json::Object(existing), Json::Object(value)) => merge(schema, &path, existing, value)?,
+            (_, existing, value) => *existing = value,
+        }
+    }
+    Ok(())
+}
+
+fn leaf_type(schema: &Schema, id: NodeId) -> PyResult<TypeInfo> {
+    let path = schema.tree().path(id);
+    TypeResolver::new(schema.module())
+        .leaf_type(&path)
+        .ok_or_else(|| PyValueError::new_err(format!("{} is not a leaf or leaf-list", path)))
+}
+
+// The value of a leaf of the type encoded like RFC 7951, where values encoded like another type are parsed from their
+// lexical form, like "1500" for an integer, or 1500 for a string.
+fn encode(module: &Module, type_info: &TypeInfo, value: &Json) -> PyResult<Json> {
+    let value = match instance::leaf_value(module, type_info, value) {
+        Ok(value) => value,
+        Err(error) => {
+            let lexical = match value {
+                Json::String(string) => string.clone(),
+                Json::Number(number) => number.to_string(),
+                Json::Bool(boolean) => boolean.to_string(),
+                _ => return Err(PyValueError::new_err(error.to_string())),
+            };
+            let parsed = value::parse_value_in(module, type_info, &lexical)
+                .map_err(|error| PyValueError::new_err(error.to_string()))?;
+            value::validate_value(type_info, &parsed).map_err(|error| PyValueError::new_err(error.to_string()))?;
+            parsed
+        }
+    };
+
+    Ok(match value {
+        Value::Int8(value) => value.into(),
+        Value::Int16(value) => value.into(),
+        Value::Int32(value) => value.into(),
+        Value::Uint8(value) => value.into(),
+        Value::Uint16(value) => value.into(),
+        Value::Uint32(value) => value.into(),
+        Value::Boolean(value) => value.into(),
+        Value::Empty => Json::Array(vec![Json::Null]),
+        value => Json::String(value.to_libyang_string(module)),
+    })
+}
+
+// The JSON value as Python objects, like `json.load` decodes it.
+fn python<'py>(py: Python<'py>, value: &Json) -> PyResult<Bound<'py, PyAny>> {
+    Ok(match value {
+        Json::Null => py.None().into_bound(py),
+        Json::Bool(boolean) => PyBool::new(py, *boolean).to_owned().into_any(),
+        Json::Number(number) => match (number.as_i64(), number.as_u64()) {
+            (Some(integer), _) => integer.into_pyobject(py)?.into_any(),
+            (_, Some(integer)) => integer.into_pyobject(py)?.into_any(),
+            _ => number.as_f64().unwrap_or_default().into_pyobject(py)?.into_any(),
+        },
+        Json::String(string) => PyString::new(py, string).into_any(),
+        Json::Array(values) => {
+            let values = values
+                .iter()
+                .map(|value| python(py, value))
+                .collect::<PyResult<Vec<_>>>()?;
+            PyList::new(py, values)?.into_any()
+        }
+        Json::Object(members) => {
+            let dict = PyDict::new(py);
+            for (name, value) in members {
+                dict.set_item(name, python(py, value)?)?;
+            }
+            dict.into_any()
+        }
+    })
+}
+
+// The data as JSON, where integers which don't fit in 64 bits are strings, which is how RFC 7951 writes them anyway,
+// and values which JSON doesn't have, like tuples or None outside of [None], are null, which no node allows.
+fn json(data: &Bound<'_, PyAny>) -> PyResult<Json> {
+    if let Ok(boolean) = data.downcast::<PyBool>() {
+        return Ok(Json::Bool(boolean.is_true()));
+    }
+    if data.is_instance_of::<PyInt>() {
+        if let Ok(integer) = data.extract::<i64>() {
+            return Ok(integer.into());
+        }
+        if let Ok(integer) = data.extract::<u64>() {
+            return Ok(integer.into());
+        }
+        return Ok(Json::String(data.str()?.to_string()));
+    }
+    if data.is_instance_of::<PyFloat>() {
+        let float: f64 = data.extract()?;
+        return Ok(Number::from_f64(float).map_or(Json::Null, Json::Number));
+    }
+    if let Ok(string) = data.downcast::<PyString>() {
+        return Ok(Json::String(string.to_str()?.to_string()));
+    }
+    if let Ok(list) = data.downcast::<PyList>() {
+        return Ok(Json::Array(list.iter().map(|item| json(&item)).collect::<PyResult<_>>()?));
+    }
+    if let Ok(object) = data.downcast::<PyDict>() {
+        let mut members = Map::new();
+        for (name, value) in object.iter() {
+            members.insert(name.extract()?, json(&value)?);
+        }
+        return Ok(Json::Object(members));
+    }
+    Ok(Json::Null)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::run;
+
+    #[test]
+    fn values_are_set_and_got_by_path() {
+        run(
+            "set",
+            r#"
+data = yang_rs.parse(path).data()
+data.set("/system/hostname", "router")
+data.set("/system/interface[name='eth0']/mtu", "1500")
+data.set("/sys:system/interface[name=\"eth0\"]/enabled", True)
+data.set("/system/interface[name='eth0']/address", ["10.0.0.1"])
+
+assert data.get("/system/hostname") == "router"
+assert data.get("/system/interface[name='eth0']/mtu") == 1500
+assert data.get("/system/interface[name='eth0']") == {
+    "name": "eth0", "mtu": 1500, "enabled": True, "address": ["10.0.0.1"]
+}
+assert data.get("/system/interface[name='eth1']/mtu") is None
+assert data.to_dict() == {"example-system:system": {
+    "hostname": "router",
+    "interface": [{"name": "eth0", "mtu": 1500, "enabled": True, "address": ["10.0.0.1"]}],
+}}
+assert data.validate() == []
+
+for path, value in [
+    ("/system/interface[name='eth1']/mtu", 9001),
+    ("/system/interface[name='eth1']/missing", 1),
+    ("/system/interface/mtu", 1),
+    ("/system/hostname[name='eth1']", "router"),
+    ("/system/interface[name='eth1']", "eth1"),
+]:
+    try:
+        data.set(path, value)
+        raise AssertionError(path + " was set")
+    except ValueError:
+        pass
+assert data.get("/system/interface[name='eth1']") is None
+
+data.set("/system/interface[name='eth0']", {"name": "eth1", "mtu": 9000})
+assert data.get("/system/interface[name='eth0']") == {"name": "eth0", "mtu": 9000}
+
+assert data.delete("/system/interface[name='eth0']")
+assert not data.delete("/system/interface[name='eth0']")
+assert data.get("/system/interface") == []
+"#,
+        );
+    }
+
+    #[test]
+    fn data_is_merged() {
+        run(
+            "merge",
+            r#"
+data = yang_rs.parse(path).data({"example-system:system": {
+    "hostname": "router",
+    "interface": [{"name": "eth0", "mtu": 1500, "address": ["10.0.0.1"]}],
+}})
+data.merge({"example-system:system": {
+    "interface": [
+        {"name": "eth0", "enabled": True, "address": ["10.0.0.1", "10.0.0.2"]},
+        {"name": "eth1", "mtu": 9000},
+    ],
+}})
+data.merge({"system": {"hostname": "switch"}})
+
+assert data.to_dict() == {"example-system:system": {
+    "hostname": "switch",
+    "interface": [
+        {"name": "eth0", "mtu": 1500, "address": ["10.0.0.1", "10.0.0.2"], "enabled": True},
+        {"name": "eth1", "mtu": 9000},
+    ],
+}}
+"#,
+        );
+    }
+}
@@ -0,0 +1,307 @@
+mod data;
+mod schema;
+
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use pyo3::{create_exception, exceptions::PyException, prelude::*};
+use yang_parser::{model::YangModule, report::Report, ErrorPolicy, ModuleRegistry, ParseOptions, ParserError};
+
+use schema::{Module, Node};
+
+create_exception!(
+    yang_rs,
+    YangError,
+    PyException,
+    "An error parsing or loading a YANG module."
+);
+
+fn yang_error(error: ParserError) -> PyErr {
+    YangError::new_err(error.to_string())
+}
+
+/// Parse the YANG module in the file, loading the modules it imports and includes from its directory and the search
+/// paths, and resolve it.
+#[pyfunction]
+#[pyo3(signature = (path, search_paths = Vec::new()))]
+fn parse(py: Python<'_>, path: PathBuf, search_paths: Vec<PathBuf>) -> PyResult<Module> {
+    Context::new(search_paths, Vec::new()).load(py, path)
+}
+
+/// The settings modules are loaded with: where imports are searched for, which other modules' augments and deviations
+/// apply, and sources held in memory rather than in files.
+#[pyclass]
+pub struct Context {
+    search_paths: Vec<PathBuf>,
+    deviations: Vec<PathBuf>,
+    registry: ModuleRegistry,
+}
+
+#[pymethods]
+impl Context {
+    #[new]
+    #[pyo3(signature = (search_paths = Vec::new(), deviations = Vec::new()))]
+    fn new(search_paths: Vec<PathBuf>, deviations: Vec<PathBuf>) -> Self {
+        Self {
+            search_paths,
+            deviations,
+            registry: ModuleRegistry::new(),
+        }
+    }
+
+    /// Add the source of the file at the path, which is loaded from memory rather than from the file system.
+    fn add_source(&mut self, path: PathBuf, source: String) {
+        self.registry.add(path, source);
+    }
+
+    /// Parse and resolve the module in the file, raising a YangError if it fails.
+    fn load(&self, py: Python<'_>, path: PathBuf) -> PyResult<Module> {
+        let options = self.options();
+        let module = py
+            .allow_threads(|| yang_parser::parse_with_options(&path, &options))
+            .map_err(yang_error)?;
+        match module {
+            YangModule::Module(module) => Ok(Module::new(module)),
+            YangModule::Submodule(_) => unreachable!("parsing only returns modules"),
+        }
+    }
+
+    /// The errors and warnings of the modules in the files, which are all checked however many of them fail.
+    fn validate(&self, py: Python<'_>, paths: Vec<PathBuf>) -> Vec<Diagnostic> {
+        let mut options = self.options();
+        options.error_policy = ErrorPolicy::CollectAll;
+        let warnings = Arc::new(Mutex::new(Vec::new()));
+        options.warnings = Some(warnings.clone());
+
+        let report = py.allow_threads(|| {
+            let mut report = Report::new();
+            let mut add = |path: Option<&std::path::Path>, result: Result<(), ParserError>| {
+                let start = report.diagnostics.len();
+                if let Err(error) = result {
+                    report.add_error(&error);
+                }
+                for warning in std::mem::take(&mut *warnings.lock().expect("the warnings to not be poisoned")) {
+                    report.add_warning(&warning);
+                }
+                // Errors which aren't in a file, like the errors of most references, are in the module being checked.
+                for diagnostic in &mut report.diagnostics[start..] {
+                    if diagnostic.file.is_none() {
+                        diagnostic.file = path.map(std::path::Path::to_path_buf);
+                    }
+                }
+            };
+            let result = yang_parser::scan(&paths, &options, |path, result| add(Some(path), result.map(|_| ())));
+            add(None, result);
+            report
+        });
+
+        report.diagnostics.into_iter().map(Diagnostic::from).collect()
+    }
+}
+
+impl Context {
+    fn options(&self) -> ParseOptions {
+        ParseOptions {
+            search_paths: self.search_paths.clone(),
+            modules: self.deviations.clone(),
+            registry: Some(Arc::new(self.registry.clone())),
+            ..Default::default()
+        }
+    }
+}
+
+/// An error or warning found when validating modules.
+#[pyclass(frozen, get_all)]
+pub struct Diagnostic {
+    /// "error" or "warning".
+    severity: &'static str,
+    /// The stable code of the kind of error, like "yang::unused_import".
+    code: &'static str,
+    /// The code libyang reports the same kind of error with, like "LYVE_REFERENCE".
+    libyang_code: &'static str,
+    message: String,
+    module: Option<String>,
+    /// The statement the diagnostic is about, like "uses endpoint".
+    statement: Option<String>,
+    /// The schema path of the node the diagnostic is about, like "/interfaces/interface".
+    path: Option<String>,
+    file: Option<PathBuf>,
+    line: Option<usize>,
+    column: Option<usize>,
+}
+
+#[pymethods]
+impl Diagnostic {
+    fn __repr__(&self) -> String {
+        format!("<Diagnostic {} {}: {}>", self.severity, self.code, self.message)
+    }
+}
+
+impl From<yang_parser::report::Diagnostic> for Diagnostic {
+    fn from(diagnostic: yang_parser::report::Diagnostic) -> Self {
+        Self {
+            severity: diagnostic.severity.as_str(),
+            code: diagnostic.code.as_str(),
+            libyang_code: diagnostic.code.libyang_code(),
+            message: diagnostic.message,
+            module: diagnostic.module,
+            statement: diagnostic.statement,
+            path: diagnostic.path,
+            file: diagnostic.file,
+            line: diagnostic.location.map(|location| location.line),
+            column: diagnostic.location.map(|location| location.column),
+        }
+    }
+}
+
+#[pymodule]
+fn yang_rs(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("YangError", m.py().get_type::<YangError>())?;
+    m.add_class::<Context>()?;
+    m.add_class::<Module>()?;
+    m.add_class::<Node>()?;
+    m.add_class::<data::Data>()?;
+    m.add_class::<Diagnostic>()?;
+    m.add_function(wrap_pyfunction!(parse, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CString;
+
+    use pyo3::types::PyDict;
+
+    use super::*;
+
+    const MODULE: &str = r#"module example-system {
+  namespace "urn:example:system";
+  prefix sys;
+
+  typedef mtu {
+    type uint16 {
+      range "68..9000";
+    }
+  }
+
+  container system {
+    description "The system.";
+    leaf hostname {
+      type string;
+      default "localhost";
+    }
+    list interface {
+      key "name";
+      leaf name {
+        type string;
+      }
+      leaf mtu {
+        type mtu;
+      }
+      leaf enabled {
+        type boolean;
+      }
+      leaf-list address {
+        type string;
+      }
+    }
+  }
+}
+"#;
+
+    // Run the Python code with the module imported as yang_rs, and the path of a file with MODULE as `path`.
+    pub(crate) fn run(test: &str, code: &str) {
+        let directory = std::env::temp_dir().join(format!("yang-python-{}-{}", test, std::process::id()));
+        std::fs::create_dir_all(&directory).expect("the directory to be created");
+        let path = directory.join("example-system.yang");
+        std::fs::write(&path, MODULE).expect("the module to be written");
+
+        pyo3::prepare_freethreaded_python();
+        let result = Python::with_gil(|py| {
+            let globals = PyDict::new(py);
+            globals.set_item("yang_rs", pyo3::wrap_pymodule!(yang_rs)(py))?;
+            globals.set_item("path", &path)?;
+            globals.set_item("source", MODULE)?;
+            let code = CString::new(code).expect("the code to have no nul bytes");
+            py.run(&code, Some(&globals), None)
+        });
+        let _ = std::fs::remove_dir_all(&directory);
+        if let Err(error) = result {
+            panic!("{}", error);
+        }
+    }
+
+    #[test]
+    fn modules_are_parsed_from_files_and_sources() {
+        run(
+            "parse",
+            r#"
+module = yang_rs.parse(path)
+assert module.name == "example-system"
+assert module.prefix == "sys"
+assert repr(module) == "<Module example-system>"
+
+context = yang_rs.Context()
+context.add_source("/memory/example-system.yang", source)
+assert context.load("/memory/example-system.yang").namespace == "urn:example:system"
+
+try:
+    yang_rs.parse(str(path) + ".missing")
+    raise AssertionError("a missing file was parsed")
+except yang_rs.YangError:
+    pass
+"#,
+        );
+    }
+
+    #[test]
+    fn schema_trees_are_navigated() {
+        run(
+            "navigate",
+            r#"
+module = yang_rs.parse(path)
+assert [node.name for node in module.roots()] == ["system"]
+
+system = module.find("/system")
+assert system.keyword == "container"
+assert system.description == "The system."
+assert [node.name for node in system.children] == ["hostname", "interface"]
+
+mtu = module.find("/system/interface/mtu")
+assert mtu.parent.keys == ["name"]
+assert mtu.type == "mtu"
+assert mtu.base_type == "uint16"
+assert mtu.is_valid("1500") and not mtu.is_valid("9001")
+assert module.find("/system/hostname").default == ["localhost"]
+assert module.find("/system/missing") is None
+"#,
+        );
+    }
+
+    #[test]
+    fn modules_and_data_are_validated() {
+        run(
+            "validate",
+            r#"
+context = yang_rs.Context()
+context.add_source(
+    "/memory/example-broken.yang",
+    'module example-broken { namespace "urn:b"; prefix b; import missing { prefix m; } }',
+)
+diagnostics = context.validate(["/memory/example-broken.yang"])
+assert sorted((diagnostic.severity, diagnostic.code) for diagnostic in diagnostics) == [
+    ("error", "yang::io"),
+    ("warning", "yang::unused_import"),
+], diagnostics
+assert context.validate([path]) == []
+
+module = yang_rs.parse(path)
+assert module.validate_data({"example-system:system": {"interface": [{"name": "eth0", "mtu": 1500}]}}) == []
+errors = module.validate_data({"example-system:system": {"interface": [{"name": "eth0", "mtu": 20}]}})
+assert [path for path, _ in errors] == ["/example-system:system/interface[1]/mtu"], errors
+"#,
+        );
+    }
+}
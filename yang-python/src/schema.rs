@@ -0,0 +1,294 @@
+use std::sync::Arc;
+
+use pyo3::{exceptions::PyValueError, prelude::*};
+use yang_parser::{
+    model,
+    schema_tree::{NodeId, NodeKind, SchemaTree},
+    types::TypeResolver,
+    value,
+};
+
+use crate::data;
+
+type Tree<'m> = SchemaTree<'m>;
+
+self_cell::self_cell!(
+    /// A resolved module with its schema tree, which borrows the nodes of the module, so nodes can be handed to Python
+    /// as ids into the tree.
+    pub struct Schema {
+        owner: model::Module,
+
+        #[covariant]
+        dependent: Tree,
+    }
+);
+
+impl Schema {
+    pub fn module(&self) -> &model::Module {
+        self.borrow_owner()
+    }
+
+    pub fn tree(&self) -> &SchemaTree<'_> {
+        self.borrow_dependent()
+    }
+}
+
+/// A resolved YANG module, with its groupings expanded and its augments and deviations applied.
+#[pyclass(frozen)]
+pub struct Module {
+    schema: Arc<Schema>,
+}
+
+impl Module {
+    pub fn new(module: model::Module) -> Self {
+        Self {
+            schema: Arc::new(Schema::new(module, |module| SchemaTree::new(module))),
+        }
+    }
+
+    fn node(&self, id: NodeId) -> Node {
+        Node {
+            schema: self.schema.clone(),
+            id,
+        }
+    }
+}
+
+#[pymethods]
+impl Module {
+    #[getter]
+    fn name(&self) -> &str {
+        &self.schema.module().name
+    }
+
+    #[getter]
+    fn namespace(&self) -> &str {
+        &self.schema.module().namespace
+    }
+
+    #[getter]
+    fn prefix(&self) -> &str {
+        &self.schema.module().prefix
+    }
+
+    /// The latest revision of the module, like "2018-02-20".
+    #[getter]
+    fn revision(&self) -> Option<&str> {
+        let revisions = &self.schema.module().revisions;
+        revisions.iter().map(|revision| revision.date.as_str()).max()
+    }
+
+    #[getter]
+    fn description(&self) -> Option<&str> {
+        self.schema.module().meta.description.as_deref()
+    }
+
+    /// The top-level nodes of the module: its data nodes, rpcs and notifications.
+    fn roots(&self) -> Vec<Node> {
+        self.schema.tree().roots().map(|id| self.node(id)).collect()
+    }
+
+    /// Every node of the module, where parents come before their children.
+    fn nodes(&self) -> Vec<Node> {
+        self.schema.tree().iter().map(|id| self.node(id)).collect()
+    }
+
+    /// The node at the schema path, like "/interfaces/interface/name", where prefixes are ignored, and choices and
+    /// cases aren't part of the path.
+    fn find(&self, path: &str) -> Option<Node> {
+        self.schema.tree().find(path).map(|id| self.node(id))
+    }
+
+    /// The errors of instance data, as decoded from RFC 7951 JSON with `json.load`, as pairs of the data path and the
+    /// message: members which aren't nodes of the module, values which their leaf's type doesn't allow, and list
+    /// entries without their keys.
+    fn validate_data(&self, data: &Bound<'_, PyAny>) -> PyResult<Vec<(String, String)>> {
+        data::validate(&self.schema, data)
+    }
+
+    /// The instance data of the module, to read and write by data path, starting from the data decoded from RFC 7951
+    /// JSON with `json.load`, or no data.
+    #[pyo3(signature = (data = None))]
+    fn data(&self, data: Option<&Bound<'_, PyAny>>) -> PyResult<data::Data> {
+        data::Data::new(self.schema.clone(), data)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("<Module {}>", self.schema.module().name)
+    }
+}
+
+/// A node of the schema tree of a module, like a container, a leaf, a choice or an rpc.
+#[pyclass(frozen)]
+pub struct Node {
+    schema: Arc<Schema>,
+    id: NodeId,
+}
+
+impl Node {
+    fn kind(&self) -> NodeKind<'_> {
+        self.schema.tree().kind(self.id)
+    }
+
+    fn node(&self, id: NodeId) -> Node {
+        Node {
+            schema: self.schema.clone(),
+            id,
+        }
+    }
+
+    // The effective type of the leaf or leaf-list, which its typedefs derive from.
+    fn effective_type(&self) -> PyResult<model::TypeInfo> {
+        let path = self.schema.tree().path(self.id);
+        TypeResolver::new(self.schema.module())
+            .leaf_type(&path)
+            .ok_or_else(|| PyValueError::new_err(format!("{} is not a leaf or leaf-list", path)))
+    }
+}
+
+#[pymethods]
+impl Node {
+    #[getter]
+    fn name(&self) -> &str {
+        self.kind().name()
+    }
+
+    /// The keyword of the node's statement, like "container" or "leaf-list".
+    #[getter]
+    fn keyword(&self) -> &'static str {
+        self.kind().keyword()
+    }
+
+    /// The schema path of the node, like "/interfaces/interface/name", without choices and cases.
+    #[getter]
+    fn path(&self) -> String {
+        self.schema.tree().path(self.id)
+    }
+
+    #[getter]
+    fn parent(&self) -> Option<Node> {
+        self.schema.tree().parent(self.id).map(|id| self.node(id))
+    }
+
+    #[getter]
+    fn children(&self) -> Vec<Node> {
+        self.schema.tree().children(self.id).map(|id| self.node(id)).collect()
+    }
+
+    #[getter]
+    fn description(&self) -> Option<&str> {
+        match self.kind() {
+            NodeKind::Container(container) => container.meta.description.as_deref(),
+            NodeKind::Leaf(leaf) => leaf.meta.description.as_deref(),
+            NodeKind::LeafList(leaf_list) => leaf_list.meta.description.as_deref(),
+            NodeKind::List(list) => list.meta.description.as_deref(),
+            NodeKind::Choice(choice) => choice.meta.description.as_deref(),
+            NodeKind::Case(case) => case.meta.description.as_deref(),
+            NodeKind::Anydata(anydata) => anydata.meta.description.as_deref(),
+            NodeKind::Anyxml(anyxml) => anyxml.meta.description.as_deref(),
+            NodeKind::Rpc(rpc) => rpc.description.as_deref(),
+            NodeKind::Action(action) => action.description.as_deref(),
+            NodeKind::Notification(notification) => notification.description.as_deref(),
+            NodeKind::Input(_) | NodeKind::Output(_) => None,
+        }
+    }
+
+    /// The type of the leaf or leaf-list as it's written, like "inet:ipv4-address", or None for other nodes.
+    #[getter]
+    fn r#type(&self) -> Option<&str> {
+        match self.kind() {
+            NodeKind::Leaf(leaf) => Some(&leaf.type_info.name),
+            NodeKind::LeafList(leaf_list) => Some(&leaf_list.type_info.name),
+            _ => None,
+        }
+    }
+
+    /// The built-in type the type of the leaf or leaf-list derives from, like "string".
+    #[getter]
+    fn base_type(&self) -> PyResult<String> {
+        Ok(self.effective_type()?.name.to_string())
+    }
+
+    /// Whether the node is configuration, as set on the node itself, or None if it inherits it from its parent.
+    #[getter]
+    fn config(&self) -> Option<bool> {
+        match self.kind() {
+            NodeKind::Container(container) => container.config,
+            NodeKind::Leaf(leaf) => leaf.config,
+            NodeKind::LeafList(leaf_list) => leaf_list.config,
+            NodeKind::List(list) => list.config,
+            NodeKind::Choice(choice) => choice.config,
+            NodeKind::Anydata(anydata) => anydata.config,
+            NodeKind::Anyxml(anyxml) => anyxml.config,
+            _ => None,
+        }
+    }
+
+    #[getter]
+    fn mandatory(&self) -> bool {
+        let mandatory = match self.kind() {
+            NodeKind::Leaf(leaf) => leaf.mandatory,
+            NodeKind::Choice(choice) => choice.mandatory,
+            NodeKind::Anydata(anydata) => anydata.mandatory,
+            NodeKind::Anyxml(anyxml) => anyxml.mandatory,
+            _ => None,
+        };
+        mandatory.unwrap_or(false)
+    }
+
+    #[getter]
+    fn default(&self) -> Vec<&str> {
+        match self.kind() {
+            NodeKind::Leaf(leaf) => leaf.default.iter().map(String::as_str).collect(),
+            NodeKind::LeafList(leaf_list) => leaf_list.default.iter().map(String::as_str).collect(),
+            NodeKind::Choice(choice) => choice.default.iter().map(String::as_str).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    #[getter]
+    fn units(&self) -> Option<&str> {
+        match self.kind() {
+            NodeKind::Leaf(leaf) => leaf.meta.units.as_deref(),
+            NodeKind::LeafList(leaf_list) => leaf_list.meta.units.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// The names of the keys of the list, in order.
+    #[getter]
+    fn keys(&self) -> Vec<&str> {
+        match self.kind() {
+            NodeKind::List(list) => list.key.iter().flat_map(|key| key.split_whitespace()).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// The value in the canonical form of the leaf or leaf-list's type, like "10" for "0xA" of an integer type, or with
+    /// `libyang=True` in the form libyang uses, where identityrefs and instance-identifiers are qualified with module
+    /// names rather than prefixes. Raises a ValueError if the type doesn't allow the value.
+    #[pyo3(signature = (value, libyang = false))]
+    fn canonicalize(&self, value: &str, libyang: bool) -> PyResult<String> {
+        let type_info = self.effective_type()?;
+        let parsed = value::parse_value(&type_info, value).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        value::validate_value(&type_info, &parsed).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(if libyang {
+            parsed.to_libyang_string(self.schema.module())
+        } else {
+            parsed.to_string()
+        })
+    }
+
+    /// Whether the type of the leaf or leaf-list allows the value.
+    fn is_valid(&self, value: &str) -> PyResult<bool> {
+        let type_info = self.effective_type()?;
+        Ok(
+            value::parse_value(&type_info, value)
+                .is_ok_and(|parsed| value::validate_value(&type_info, &parsed).is_ok()),
+        )
+    }
+
+    fn __repr__(&self) -> String {
+        format!("<Node {} {}>", self.kind().keyword(), self.schema.tree().path(self.id))
+    }
+}